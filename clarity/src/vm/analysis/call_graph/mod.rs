@@ -0,0 +1,457 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::vm::analysis::analysis_db::AnalysisDatabase;
+use crate::vm::analysis::types::ContractAnalysis;
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::NativeFunctions;
+use crate::vm::representations::{ClarityName, SymbolicExpression};
+use crate::vm::types::signatures::CallableSubtype;
+use crate::vm::types::{FunctionType, PrincipalData, TypeSignature, Value};
+
+#[cfg(test)]
+mod tests;
+
+/// A `contract-call?` to a contract named in the source
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ContractCall {
+    pub contract: String,
+    pub function: String,
+    /// `public` or `read-only`, or `None` if the callee isn't in the analysis database
+    pub function_type: Option<String>,
+    pub as_contract: bool,
+}
+
+/// A `contract-call?` to a contract supplied at runtime
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct TraitCall {
+    /// the name the contract is bound to
+    pub argument: String,
+    /// the trait the contract was passed in as, if it is an argument of the function
+    pub trait_identifier: Option<String>,
+    pub function: String,
+    pub as_contract: bool,
+}
+
+/// A transfer, mint or burn of STX or of one of the contract's tokens
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct AssetOperation {
+    pub operation: String,
+    /// the fungible or non-fungible token, or `None` for STX
+    pub asset: Option<String>,
+    pub as_contract: bool,
+}
+
+/// Everything a function's body refers to directly.  Follow `calls` for what it does
+/// transitively.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionDependencies {
+    /// `public`, `read-only` or `private`
+    pub visibility: String,
+    /// functions of this contract that are called, or passed to `map`, `filter` or `fold`
+    pub calls: BTreeSet<String>,
+    pub contract_calls: BTreeSet<ContractCall>,
+    pub trait_calls: BTreeSet<TraitCall>,
+    pub var_reads: BTreeSet<String>,
+    pub var_writes: BTreeSet<String>,
+    pub map_reads: BTreeSet<String>,
+    pub map_writes: BTreeSet<String>,
+    pub asset_operations: BTreeSet<AssetOperation>,
+}
+
+///
+/// The static call graph of a contract that has passed the type checker, along with the data
+///  and assets each function touches.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallGraph {
+    pub contract: String,
+    pub functions: BTreeMap<String, FunctionDependencies>,
+}
+
+impl CallGraph {
+    /// Build the call graph of `contract_analysis`.  `analysis_db` is consulted (and left
+    /// unchanged) to check whether the targets of `contract-call?`s exist.
+    pub fn run(
+        contract_analysis: &ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+    ) -> CallGraph {
+        let defined_functions: BTreeSet<&ClarityName> = contract_analysis
+            .public_function_types
+            .keys()
+            .chain(contract_analysis.read_only_function_types.keys())
+            .chain(contract_analysis.private_function_types.keys())
+            .collect();
+
+        let mut functions = BTreeMap::new();
+        analysis_db.begin();
+        for expr in contract_analysis.expressions.iter() {
+            let (visibility, signature, body, function_types) =
+                match DefineFunctionsParsed::try_parse(expr) {
+                    Ok(Some(DefineFunctionsParsed::PublicFunction { signature, body })) => (
+                        "public",
+                        signature,
+                        body,
+                        &contract_analysis.public_function_types,
+                    ),
+                    Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, body })) => (
+                        "read-only",
+                        signature,
+                        body,
+                        &contract_analysis.read_only_function_types,
+                    ),
+                    Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, body })) => (
+                        "private",
+                        signature,
+                        body,
+                        &contract_analysis.private_function_types,
+                    ),
+                    _ => continue,
+                };
+            let name = match signature.first().and_then(|name| name.match_atom()) {
+                Some(name) => name,
+                None => continue,
+            };
+            let arguments = match function_types.get(name) {
+                Some(FunctionType::Fixed(function)) => function
+                    .args
+                    .iter()
+                    .map(|arg| (&arg.name, &arg.signature))
+                    .collect(),
+                _ => BTreeMap::new(),
+            };
+
+            let mut walker = Walker {
+                contract_analysis,
+                analysis_db: &mut *analysis_db,
+                defined_functions: &defined_functions,
+                arguments,
+                as_contract: 0,
+                dependencies: FunctionDependencies {
+                    visibility: visibility.to_string(),
+                    calls: BTreeSet::new(),
+                    contract_calls: BTreeSet::new(),
+                    trait_calls: BTreeSet::new(),
+                    var_reads: BTreeSet::new(),
+                    var_writes: BTreeSet::new(),
+                    map_reads: BTreeSet::new(),
+                    map_writes: BTreeSet::new(),
+                    asset_operations: BTreeSet::new(),
+                },
+            };
+            walker.walk(body);
+            functions.insert(name.to_string(), walker.dependencies);
+        }
+        analysis_db.roll_back();
+
+        CallGraph {
+            contract: contract_analysis.contract_identifier.to_string(),
+            functions,
+        }
+    }
+
+    /// Render the graph in Graphviz's DOT language.  Public functions are drawn bold and
+    /// private ones dashed; calls into other contracts are dashed when the callee is only known
+    /// at runtime, and calls, data writes and asset operations made inside `as-contract` are
+    /// drawn red.
+    pub fn to_dot(&self) -> String {
+        let mut nodes = BTreeMap::new();
+        let mut edges = vec![];
+        let as_contract_color = |as_contract: bool| if as_contract { ", color=red" } else { "" };
+
+        for (name, function) in self.functions.iter() {
+            let function_id = format!("fn:{}", name);
+            let style = match function.visibility.as_str() {
+                "public" => "bold",
+                "private" => "dashed",
+                _ => "solid",
+            };
+            nodes.insert(
+                function_id.clone(),
+                format!("label=\"{}\", shape=box, style={}", name, style),
+            );
+            for callee in function.calls.iter() {
+                edges.push(format!("\"{}\" -> \"fn:{}\"", function_id, callee));
+            }
+            for call in function.contract_calls.iter() {
+                let callee_id = format!("call:{}.{}", call.contract, call.function);
+                nodes.insert(
+                    callee_id.clone(),
+                    format!(
+                        "label=\"{}\\n{}\", shape=component",
+                        call.contract, call.function
+                    ),
+                );
+                edges.push(format!(
+                    "\"{}\" -> \"{}\" [style=solid{}]",
+                    function_id,
+                    callee_id,
+                    as_contract_color(call.as_contract)
+                ));
+            }
+            for call in function.trait_calls.iter() {
+                let target = call
+                    .trait_identifier
+                    .clone()
+                    .unwrap_or_else(|| call.argument.clone());
+                let callee_id = format!("trait:{}.{}", target, call.function);
+                nodes.insert(
+                    callee_id.clone(),
+                    format!(
+                        "label=\"<{}>\\n{}\", shape=component, style=dashed",
+                        target, call.function
+                    ),
+                );
+                edges.push(format!(
+                    "\"{}\" -> \"{}\" [style=dashed{}]",
+                    function_id,
+                    callee_id,
+                    as_contract_color(call.as_contract)
+                ));
+            }
+            for (kind, reads, writes) in [
+                ("var", &function.var_reads, &function.var_writes),
+                ("map", &function.map_reads, &function.map_writes),
+            ]
+            .iter()
+            {
+                for (label, names) in [("reads", reads), ("writes", writes)].iter() {
+                    for data in names.iter() {
+                        let data_id = format!("{}:{}", kind, data);
+                        nodes.insert(
+                            data_id.clone(),
+                            format!("label=\"{} {}\", shape=cylinder", kind, data),
+                        );
+                        edges.push(format!(
+                            "\"{}\" -> \"{}\" [label=\"{}\"]",
+                            function_id, data_id, label
+                        ));
+                    }
+                }
+            }
+            for operation in function.asset_operations.iter() {
+                let asset = operation.asset.as_deref().unwrap_or("STX");
+                let asset_id = format!("asset:{}", asset);
+                nodes.insert(
+                    asset_id.clone(),
+                    format!("label=\"{}\", shape=diamond", asset),
+                );
+                edges.push(format!(
+                    "\"{}\" -> \"{}\" [label=\"{}\"{}]",
+                    function_id,
+                    asset_id,
+                    operation.operation,
+                    as_contract_color(operation.as_contract)
+                ));
+            }
+        }
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", self.contract).unwrap();
+        writeln!(dot, "  rankdir=LR;").unwrap();
+        for (id, attributes) in nodes.iter() {
+            writeln!(dot, "  \"{}\" [{}];", id, attributes).unwrap();
+        }
+        for edge in edges.iter() {
+            writeln!(dot, "  {};", edge).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+struct Walker<'a, 'b, 'c> {
+    contract_analysis: &'a ContractAnalysis,
+    analysis_db: &'b mut AnalysisDatabase<'c>,
+    defined_functions: &'a BTreeSet<&'a ClarityName>,
+    /// the arguments of the function being walked, and their types
+    arguments: BTreeMap<&'a ClarityName, &'a TypeSignature>,
+    /// how many `as-contract`s we're inside
+    as_contract: u32,
+    dependencies: FunctionDependencies,
+}
+
+impl<'a, 'b, 'c> Walker<'a, 'b, 'c> {
+    fn walk(&mut self, expr: &'a SymbolicExpression) {
+        let list = match expr.match_list() {
+            Some(list) => list,
+            None => return,
+        };
+        let (function, args) = match list.split_first() {
+            Some((function, args)) => (function, args),
+            None => return,
+        };
+        let function_name = match function.match_atom() {
+            Some(name) => name,
+            None => {
+                for e in list.iter() {
+                    self.walk(e);
+                }
+                return;
+            }
+        };
+        let native = NativeFunctions::lookup_by_name_at_version(
+            function_name,
+            &self.contract_analysis.clarity_version,
+        );
+        let first_name = || args.first().and_then(|arg| arg.match_atom());
+
+        match native {
+            Some(NativeFunctions::Let) if !args.is_empty() => {
+                // (let ((name value) ...) body ...) -- names are not calls
+                for binding in args[0].match_list().unwrap_or(&[]).iter() {
+                    if let Some([_, value]) = binding.match_list() {
+                        self.walk(value);
+                    }
+                }
+                for e in args[1..].iter() {
+                    self.walk(e);
+                }
+                return;
+            }
+            Some(NativeFunctions::TupleCons) => {
+                // (tuple (key value) ...) -- keys are not calls
+                for pair in args.iter() {
+                    if let Some([_, value]) = pair.match_list() {
+                        self.walk(value);
+                    }
+                }
+                return;
+            }
+            Some(NativeFunctions::AsContract) => {
+                self.as_contract += 1;
+                for e in args.iter() {
+                    self.walk(e);
+                }
+                self.as_contract -= 1;
+                return;
+            }
+            Some(NativeFunctions::FetchVar) => {
+                if let Some(name) = first_name() {
+                    self.dependencies.var_reads.insert(name.to_string());
+                }
+            }
+            Some(NativeFunctions::SetVar) => {
+                if let Some(name) = first_name() {
+                    self.dependencies.var_writes.insert(name.to_string());
+                }
+            }
+            Some(NativeFunctions::FetchEntry) => {
+                if let Some(name) = first_name() {
+                    self.dependencies.map_reads.insert(name.to_string());
+                }
+            }
+            Some(NativeFunctions::SetEntry)
+            | Some(NativeFunctions::InsertEntry)
+            | Some(NativeFunctions::DeleteEntry) => {
+                if let Some(name) = first_name() {
+                    self.dependencies.map_writes.insert(name.to_string());
+                }
+            }
+            Some(NativeFunctions::TransferToken)
+            | Some(NativeFunctions::TransferAsset)
+            | Some(NativeFunctions::MintToken)
+            | Some(NativeFunctions::MintAsset)
+            | Some(NativeFunctions::BurnToken)
+            | Some(NativeFunctions::BurnAsset) => {
+                let asset = first_name().map(|name| name.to_string());
+                self.asset_operation(function_name, asset);
+            }
+            Some(NativeFunctions::StxTransfer)
+            | Some(NativeFunctions::StxTransferMemo)
+            | Some(NativeFunctions::StxBurn) => {
+                self.asset_operation(function_name, None);
+            }
+            Some(NativeFunctions::Map)
+            | Some(NativeFunctions::Filter)
+            | Some(NativeFunctions::Fold) => {
+                if let Some(name) = first_name() {
+                    if self.defined_functions.contains(name) {
+                        self.dependencies.calls.insert(name.to_string());
+                    }
+                }
+            }
+            Some(NativeFunctions::ContractCall) if args.len() >= 2 => {
+                if let Some(callee) = args[1].match_atom() {
+                    self.contract_call(&args[0], callee);
+                }
+            }
+            Some(_) => {}
+            None => {
+                if self.defined_functions.contains(function_name) {
+                    self.dependencies.calls.insert(function_name.to_string());
+                }
+            }
+        }
+
+        for e in args.iter() {
+            self.walk(e);
+        }
+    }
+
+    fn asset_operation(&mut self, operation: &ClarityName, asset: Option<String>) {
+        self.dependencies.asset_operations.insert(AssetOperation {
+            operation: operation.to_string(),
+            asset,
+            as_contract: self.as_contract > 0,
+        });
+    }
+
+    fn contract_call(&mut self, target: &SymbolicExpression, function: &ClarityName) {
+        let literal = target
+            .match_literal_value()
+            .or_else(|| target.match_atom_value());
+        if let Some(Value::Principal(PrincipalData::Contract(contract))) = literal {
+            let epoch = &self.contract_analysis.epoch;
+            let function_type = if let Ok(Some(_)) = self
+                .analysis_db
+                .get_public_function_type(contract, function, epoch)
+            {
+                Some("public".to_string())
+            } else if let Ok(Some(_)) = self
+                .analysis_db
+                .get_read_only_function_type(contract, function, epoch)
+            {
+                Some("read-only".to_string())
+            } else {
+                None
+            };
+            self.dependencies.contract_calls.insert(ContractCall {
+                contract: contract.to_string(),
+                function: function.to_string(),
+                function_type,
+                as_contract: self.as_contract > 0,
+            });
+        } else if let Some(argument) = target.match_atom() {
+            let trait_identifier = match self.arguments.get(argument) {
+                Some(TypeSignature::CallableType(CallableSubtype::Trait(trait_identifier)))
+                | Some(TypeSignature::TraitReferenceType(trait_identifier)) => {
+                    Some(trait_identifier.to_string())
+                }
+                _ => None,
+            };
+            self.dependencies.trait_calls.insert(TraitCall {
+                argument: argument.to_string(),
+                trait_identifier,
+                function: function.to_string(),
+                as_contract: self.as_contract > 0,
+            });
+        }
+    }
+}
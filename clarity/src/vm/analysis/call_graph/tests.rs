@@ -0,0 +1,223 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::vm::analysis::call_graph::{AssetOperation, CallGraph, ContractCall, TraitCall};
+use crate::vm::analysis::type_check;
+use crate::vm::ast::parse;
+use crate::vm::database::MemoryBackingStore;
+use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::ClarityVersion;
+use stacks_common::types::StacksEpochId;
+
+const TOKEN: &str = "(define-fungible-token token)
+    (define-public (transfer (amount uint) (recipient principal))
+        (ft-transfer? token amount tx-sender recipient))
+    (define-read-only (get-balance (who principal))
+        (ok (ft-get-balance token who)))";
+
+const VAULT: &str = "(define-trait sip-010 ((transfer (uint principal) (response bool uint))))
+    (define-constant owner tx-sender)
+    (define-data-var total uint u0)
+    (define-map deposits principal uint)
+    (define-non-fungible-token receipt uint)
+
+    (define-private (add-deposit (who principal) (amount uint))
+        (map-set deposits who (+ amount (default-to u0 (map-get? deposits who)))))
+
+    (define-private (add-one (x uint)) (+ x u1))
+
+    (define-public (deposit (amount uint))
+        (begin
+            (try! (contract-call? .token transfer amount (as-contract tx-sender)))
+            (add-deposit tx-sender amount)
+            (var-set total (+ (var-get total) amount))
+            (nft-mint? receipt (var-get total) tx-sender)))
+
+    (define-public (sweep (asset <sip-010>) (amount uint))
+        (let ((balance (unwrap-panic (contract-call? .token get-balance (as-contract tx-sender)))))
+            (asserts! (is-eq contract-caller owner) (err u1))
+            (as-contract (begin
+                (try! (stx-transfer? amount tx-sender owner))
+                (contract-call? asset transfer balance owner)))))
+
+    (define-read-only (counts (xs (list 10 uint)))
+        { total: (var-get total), bumped: (map add-one xs), missing: (contract-call? .missing foo) })";
+
+fn call_graph() -> CallGraph {
+    let version = ClarityVersion::Clarity2;
+    let epoch = StacksEpochId::Epoch21;
+    let token_id = QualifiedContractIdentifier::local("token").unwrap();
+    let vault_id = QualifiedContractIdentifier::local("vault").unwrap();
+    let mut token = parse(&token_id, TOKEN, version, epoch).unwrap();
+    let mut vault = parse(&vault_id, VAULT, version, epoch).unwrap();
+
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+    db.execute(|db| {
+        db.test_insert_contract_hash(&token_id);
+        type_check(&token_id, &mut token, db, true, &epoch, &version)
+    })
+    .unwrap();
+    // `.missing` doesn't exist, so the vault can't pass the type checker against this database
+    let analysis = {
+        let mut scratch = MemoryBackingStore::new();
+        let mut scratch_db = scratch.as_analysis_db();
+        scratch_db
+            .execute(|db| {
+                let mut missing = parse(
+                    &QualifiedContractIdentifier::local("missing").unwrap(),
+                    "(define-read-only (foo) (ok u1))",
+                    version,
+                    epoch,
+                )
+                .unwrap();
+                type_check(
+                    &QualifiedContractIdentifier::local("missing").unwrap(),
+                    &mut missing,
+                    db,
+                    true,
+                    &epoch,
+                    &version,
+                )?;
+                let mut token = token.clone();
+                type_check(&token_id, &mut token, db, true, &epoch, &version)?;
+                type_check(&vault_id, &mut vault, db, false, &epoch, &version)
+            })
+            .unwrap()
+    };
+    CallGraph::run(&analysis, &mut db)
+}
+
+#[test]
+fn test_internal_calls_and_data() {
+    let graph = call_graph();
+    assert_eq!(
+        graph.contract,
+        QualifiedContractIdentifier::local("vault")
+            .unwrap()
+            .to_string()
+    );
+    let names: Vec<_> = graph.functions.keys().cloned().collect();
+    assert_eq!(
+        names,
+        vec!["add-deposit", "add-one", "counts", "deposit", "sweep"]
+    );
+
+    let deposit = &graph.functions["deposit"];
+    assert_eq!(deposit.visibility, "public");
+    assert_eq!(
+        deposit.calls.iter().collect::<Vec<_>>(),
+        vec!["add-deposit"]
+    );
+    assert_eq!(deposit.var_reads.iter().collect::<Vec<_>>(), vec!["total"]);
+    assert_eq!(deposit.var_writes.iter().collect::<Vec<_>>(), vec!["total"]);
+    assert!(deposit.map_writes.is_empty());
+
+    let add_deposit = &graph.functions["add-deposit"];
+    assert_eq!(add_deposit.visibility, "private");
+    assert_eq!(
+        add_deposit.map_reads.iter().collect::<Vec<_>>(),
+        vec!["deposits"]
+    );
+    assert_eq!(
+        add_deposit.map_writes.iter().collect::<Vec<_>>(),
+        vec!["deposits"]
+    );
+
+    // functions passed to `map` are calls; tuple keys and `let` names are not
+    let counts = &graph.functions["counts"];
+    assert_eq!(counts.visibility, "read-only");
+    assert_eq!(counts.calls.iter().collect::<Vec<_>>(), vec!["add-one"]);
+    assert!(graph.functions["sweep"].calls.is_empty());
+}
+
+#[test]
+fn test_contract_calls_and_assets() {
+    let graph = call_graph();
+    let token = QualifiedContractIdentifier::local("token")
+        .unwrap()
+        .to_string();
+    let vault = QualifiedContractIdentifier::local("vault")
+        .unwrap()
+        .to_string();
+
+    let deposit = &graph.functions["deposit"];
+    assert_eq!(
+        deposit.contract_calls.iter().collect::<Vec<_>>(),
+        vec![&ContractCall {
+            contract: token.clone(),
+            function: "transfer".into(),
+            function_type: Some("public".into()),
+            as_contract: false,
+        }]
+    );
+    assert_eq!(
+        deposit.asset_operations.iter().collect::<Vec<_>>(),
+        vec![&AssetOperation {
+            operation: "nft-mint?".into(),
+            asset: Some("receipt".into()),
+            as_contract: false,
+        }]
+    );
+
+    let sweep = &graph.functions["sweep"];
+    assert_eq!(
+        sweep.contract_calls.iter().collect::<Vec<_>>(),
+        vec![&ContractCall {
+            contract: token.clone(),
+            function: "get-balance".into(),
+            function_type: Some("read-only".into()),
+            as_contract: false,
+        }]
+    );
+    assert_eq!(
+        sweep.trait_calls.iter().collect::<Vec<_>>(),
+        vec![&TraitCall {
+            argument: "asset".into(),
+            trait_identifier: Some(format!("{}.sip-010", vault)),
+            function: "transfer".into(),
+            as_contract: true,
+        }]
+    );
+    assert_eq!(
+        sweep.asset_operations.iter().collect::<Vec<_>>(),
+        vec![&AssetOperation {
+            operation: "stx-transfer?".into(),
+            asset: None,
+            as_contract: true,
+        }]
+    );
+
+    // not in the database the graph was built against
+    let counts = &graph.functions["counts"];
+    let missing = counts.contract_calls.iter().next().unwrap();
+    assert_eq!(missing.function, "foo");
+    assert_eq!(missing.function_type, None);
+}
+
+#[test]
+fn test_dot() {
+    let graph = call_graph();
+    let dot = graph.to_dot();
+    assert!(dot.starts_with(&format!("digraph \"{}\" {{\n", graph.contract)));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("  \"fn:deposit\" [label=\"deposit\", shape=box, style=bold];\n"));
+    assert!(dot.contains("  \"fn:add-one\" [label=\"add-one\", shape=box, style=dashed];\n"));
+    assert!(dot.contains("  \"fn:deposit\" -> \"fn:add-deposit\";\n"));
+    assert!(dot.contains("  \"fn:deposit\" -> \"var:total\" [label=\"writes\"];\n"));
+    assert!(dot.contains("  \"fn:sweep\" -> \"asset:STX\" [label=\"stx-transfer?\", color=red];\n"));
+    assert!(dot.contains("[style=dashed, color=red];\n"));
+}
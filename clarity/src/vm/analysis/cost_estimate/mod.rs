@@ -0,0 +1,646 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::vm::analysis::type_checker::contexts::TypeMap;
+use crate::vm::analysis::types::ContractAnalysis;
+use crate::vm::callables::CallableType;
+use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::{CostErrors, CostTracker, ExecutionCost};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::{lookup_reserved_functions, NativeFunctions};
+use crate::vm::representations::{SymbolicExpression, SymbolicExpressionType};
+use crate::vm::types::signatures::SequenceSubtype;
+use crate::vm::types::{FunctionType, PrincipalData, StringSubtype, TypeSignature, Value};
+use crate::vm::variables::NativeVariables;
+
+#[cfg(test)]
+mod tests;
+
+/// A `contract-call?` whose cost can't be known from this contract alone
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ExternalCall {
+    /// the contract named in the source, or the name the contract is bound to if it is supplied
+    /// at runtime
+    pub contract: String,
+    pub function: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionCostEstimate {
+    /// `public` or `read-only`
+    pub visibility: String,
+    /// the most the function can cost, over every input its argument types admit
+    pub cost: ExecutionCost,
+    pub exceeds_block_limit: bool,
+    /// calls into other contracts made by the function or the private functions it calls.  Their
+    /// cost is not included in `cost`, beyond that of making the call.
+    pub external_calls: BTreeSet<ExternalCall>,
+}
+
+///
+/// Upper bounds on the runtime cost of calling each public and read-only function of a contract
+///  that has passed the type checker.
+///
+/// Every expression is charged as the interpreter would charge it, taking the input sizes of
+///  cost functions from the largest value of each expression's type (so a `(list 200 uint)`
+///  argument is charged as if it held 200 items, and a `(buff 1024)` as if it were full).
+///  Both branches of an `if`, `match` or `asserts!` are charged and the more expensive one kept.
+///  Because the cost functions only grow with their inputs, the result bounds every execution.
+///
+/// Each estimate includes loading the contract, as every transaction calling it does, but not the
+///  cost of the functions run by `contract-call?`s, which are reported as `external_calls`.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostEstimate {
+    pub contract: String,
+    pub block_limit: ExecutionCost,
+    pub functions: BTreeMap<String, FunctionCostEstimate>,
+}
+
+impl CostEstimate {
+    /// Estimate the costs of `contract_analysis`'s functions, whose source is `source_len` bytes
+    /// long, evaluating the cost functions with `cost_tracker` and comparing the results against
+    /// `block_limit`.  The cost tracker's running total is not changed.
+    pub fn run<T: CostTracker>(
+        contract_analysis: &ContractAnalysis,
+        source_len: u64,
+        cost_tracker: &mut T,
+        block_limit: &ExecutionCost,
+    ) -> Result<CostEstimate, CostErrors> {
+        let type_map = contract_analysis.type_map.as_ref().ok_or_else(|| {
+            CostErrors::CostComputationFailed("Contract analysis has no type map".into())
+        })?;
+
+        let mut definitions = HashMap::new();
+        let mut entry_points = vec![];
+        for expr in contract_analysis.expressions.iter() {
+            let (visibility, signature, body, function_types) =
+                match DefineFunctionsParsed::try_parse(expr) {
+                    Ok(Some(DefineFunctionsParsed::PublicFunction { signature, body })) => (
+                        Some("public"),
+                        signature,
+                        body,
+                        &contract_analysis.public_function_types,
+                    ),
+                    Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, body })) => (
+                        Some("read-only"),
+                        signature,
+                        body,
+                        &contract_analysis.read_only_function_types,
+                    ),
+                    Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, body })) => (
+                        None,
+                        signature,
+                        body,
+                        &contract_analysis.private_function_types,
+                    ),
+                    _ => continue,
+                };
+            let name = match signature.first().and_then(|name| name.match_atom()) {
+                Some(name) => name,
+                None => continue,
+            };
+            let argument_types = match function_types.get(name) {
+                Some(FunctionType::Fixed(function)) => {
+                    function.args.iter().map(|arg| &arg.signature).collect()
+                }
+                _ => vec![],
+            };
+            definitions.insert(name.as_str(), (argument_types, body));
+            if let Some(visibility) = visibility {
+                entry_points.push((name.as_str(), visibility));
+            }
+        }
+
+        let mut estimator = Estimator {
+            contract_analysis,
+            type_map,
+            cost_tracker,
+            definitions,
+            applications: HashMap::new(),
+            external_calls: BTreeSet::new(),
+        };
+        // the stored size of a contract is its source plus the values of its constants
+        let contract_size = contract_analysis
+            .variable_types
+            .values()
+            .fold(source_len, |size, constant_type| {
+                size.saturating_add(constant_type.size().into())
+            });
+        let load_contract = estimator.charge(ClarityCostFunction::LoadContract, contract_size)?;
+
+        let mut functions = BTreeMap::new();
+        for (name, visibility) in entry_points.into_iter() {
+            let (mut cost, external_calls) = estimator.application(name)?;
+            cost.add(&load_contract)?;
+            functions.insert(
+                name.to_string(),
+                FunctionCostEstimate {
+                    visibility: visibility.to_string(),
+                    exceeds_block_limit: cost.exceeds(block_limit),
+                    cost,
+                    external_calls,
+                },
+            );
+        }
+
+        Ok(CostEstimate {
+            contract: contract_analysis.contract_identifier.to_string(),
+            block_limit: block_limit.clone(),
+            functions,
+        })
+    }
+}
+
+struct Estimator<'a, T: CostTracker> {
+    contract_analysis: &'a ContractAnalysis,
+    type_map: &'a TypeMap,
+    cost_tracker: &'a mut T,
+    /// the argument types and body of each function defined in the contract
+    definitions: HashMap<&'a str, (Vec<&'a TypeSignature>, &'a SymbolicExpression)>,
+    /// the cost of applying each function estimated so far, and the external calls it makes
+    applications: HashMap<&'a str, (ExecutionCost, BTreeSet<ExternalCall>)>,
+    /// the external calls made by the function being estimated
+    external_calls: BTreeSet<ExternalCall>,
+}
+
+impl<'a, T: CostTracker> Estimator<'a, T> {
+    fn charge(
+        &mut self,
+        cost_function: ClarityCostFunction,
+        input: u64,
+    ) -> Result<ExecutionCost, CostErrors> {
+        self.cost_tracker.compute_cost(cost_function, &[input])
+    }
+
+    /// The cost of applying a function defined in the contract to already-evaluated arguments.
+    /// Clarity functions can't recurse, so every application is estimated once and reused.
+    fn application(
+        &mut self,
+        name: &'a str,
+    ) -> Result<(ExecutionCost, BTreeSet<ExternalCall>), CostErrors> {
+        if let Some(application) = self.applications.get(name) {
+            return Ok(application.clone());
+        }
+        let (argument_types, body) = match self.definitions.get(name) {
+            Some((argument_types, body)) => (argument_types.clone(), *body),
+            None => {
+                return Err(CostErrors::CostComputationFailed(format!(
+                    "Undefined function: {}",
+                    name
+                )))
+            }
+        };
+
+        let outer_calls = std::mem::take(&mut self.external_calls);
+        let mut cost = self.charge(
+            ClarityCostFunction::UserFunctionApplication,
+            argument_types.len() as u64,
+        )?;
+        for argument_type in argument_types.into_iter() {
+            cost.add(&self.charge(
+                ClarityCostFunction::InnerTypeCheckCost,
+                argument_type.size().into(),
+            )?)?;
+        }
+        cost.add(&self.expr(body, 0)?)?;
+        let external_calls = std::mem::replace(&mut self.external_calls, outer_calls);
+
+        self.applications
+            .insert(name, (cost.clone(), external_calls.clone()));
+        Ok((cost, external_calls))
+    }
+
+    /// The size of the largest value of `expr`'s type
+    fn size(&self, expr: &SymbolicExpression) -> u64 {
+        self.type_map
+            .get_type(expr)
+            .map(|t| u64::from(t.size()))
+            .unwrap_or(0)
+    }
+
+    /// The length of the largest consensus serialization of a value of `expr`'s type
+    fn serialized_size(&self, expr: &SymbolicExpression) -> u64 {
+        self.type_map
+            .get_type(expr)
+            .map(|t| u64::from(t.max_serialized_size().unwrap_or_else(|_| t.size())))
+            .unwrap_or(0)
+    }
+
+    /// The most items a sequence expression can hold, and the type of each item
+    fn sequence(&self, expr: &SymbolicExpression) -> (u64, TypeSignature) {
+        match self.type_map.get_type(expr) {
+            Some(TypeSignature::SequenceType(subtype)) => {
+                let max_len = match subtype {
+                    SequenceSubtype::ListType(list) => list.get_max_len(),
+                    SequenceSubtype::BufferType(len)
+                    | SequenceSubtype::StringType(StringSubtype::ASCII(len)) => u32::from(len),
+                    SequenceSubtype::StringType(StringSubtype::UTF8(len)) => u32::from(len),
+                };
+                (max_len.into(), subtype.unit_type())
+            }
+            _ => (0, TypeSignature::NoType),
+        }
+    }
+
+    fn sum(
+        &mut self,
+        exprs: &'a [SymbolicExpression],
+        depth: u64,
+    ) -> Result<ExecutionCost, CostErrors> {
+        let mut cost = ExecutionCost::zero();
+        for expr in exprs.iter() {
+            cost.add(&self.expr(expr, depth)?)?;
+        }
+        Ok(cost)
+    }
+
+    /// The cost of evaluating `expr` in a local context nested `depth` deep
+    fn expr(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        depth: u64,
+    ) -> Result<ExecutionCost, CostErrors> {
+        let version = &self.contract_analysis.clarity_version;
+        match &expr.expr {
+            SymbolicExpressionType::Atom(name) => {
+                match NativeVariables::lookup_by_name_at_version(name, version) {
+                    Some(NativeVariables::BlockHeight)
+                    | Some(NativeVariables::BurnBlockHeight)
+                    | Some(NativeVariables::TotalLiquidMicroSTX) => {
+                        self.charge(ClarityCostFunction::FetchVar, 1)
+                    }
+                    Some(_) => Ok(ExecutionCost::zero()),
+                    None => {
+                        let mut cost =
+                            self.charge(ClarityCostFunction::LookupVariableDepth, depth)?;
+                        cost.add(
+                            &self
+                                .charge(ClarityCostFunction::LookupVariableSize, self.size(expr))?,
+                        )?;
+                        Ok(cost)
+                    }
+                }
+            }
+            SymbolicExpressionType::List(list) => {
+                let (function, args) = match list.split_first() {
+                    Some((function, args)) => (function, args),
+                    None => return Ok(ExecutionCost::zero()),
+                };
+                let name = match function.match_atom() {
+                    Some(name) => name,
+                    None => return Ok(ExecutionCost::zero()),
+                };
+                let mut cost = self.charge(ClarityCostFunction::LookupFunction, 0)?;
+                if let Some(native) = NativeFunctions::lookup_by_name_at_version(name, version) {
+                    cost.add(&self.native(native, args, depth)?)?;
+                } else {
+                    cost.add(&self.sum(args, depth)?)?;
+                    let (application, external_calls) = self.application(name.as_str())?;
+                    cost.add(&application)?;
+                    self.external_calls.extend(external_calls);
+                }
+                Ok(cost)
+            }
+            _ => Ok(ExecutionCost::zero()),
+        }
+    }
+
+    /// The cost of applying the function named by `function` once, as `map`, `filter` and
+    /// `fold` do, to arguments of `argument_types`
+    fn iteration(
+        &mut self,
+        function: &'a SymbolicExpression,
+        argument_types: &[TypeSignature],
+    ) -> Result<ExecutionCost, CostErrors> {
+        let name = match function.match_atom() {
+            Some(name) => name,
+            None => return Ok(ExecutionCost::zero()),
+        };
+        match lookup_reserved_functions(name, &self.contract_analysis.clarity_version) {
+            Some(CallableType::NativeFunction(_, _, cost_function)) => {
+                self.charge(cost_function, argument_types.len() as u64)
+            }
+            Some(CallableType::NativeFunction205(_, _, cost_function, _)) => {
+                let input = argument_types
+                    .iter()
+                    .map(|t| u64::from(t.max_serialized_size().unwrap_or_else(|_| t.size())))
+                    .sum();
+                self.charge(cost_function, input)
+            }
+            Some(_) => Ok(ExecutionCost::zero()),
+            None => {
+                let (cost, external_calls) = self.application(name.as_str())?;
+                self.external_calls.extend(external_calls);
+                Ok(cost)
+            }
+        }
+    }
+
+    fn native(
+        &mut self,
+        native: NativeFunctions,
+        args: &'a [SymbolicExpression],
+        depth: u64,
+    ) -> Result<ExecutionCost, CostErrors> {
+        use crate::vm::functions::NativeFunctions::*;
+
+        let version = &self.contract_analysis.clarity_version;
+        let callable = match lookup_reserved_functions(native.get_name_str(), version) {
+            Some(callable) => callable,
+            None => return self.sum(args, depth),
+        };
+        let mut cost = match callable {
+            CallableType::NativeFunction(_, _, cost_function) => {
+                self.charge(cost_function, args.len() as u64)?
+            }
+            CallableType::NativeFunction205(_, _, cost_function, _) => {
+                let input = args.iter().map(|arg| self.serialized_size(arg)).sum();
+                self.charge(cost_function, input)?
+            }
+            _ => ExecutionCost::zero(),
+        };
+        if !matches!(callable, CallableType::SpecialFunction(..)) {
+            cost.add(&self.sum(args, depth)?)?;
+            return Ok(cost);
+        }
+
+        let rest = if args.is_empty() { args } else { &args[1..] };
+
+        match native {
+            If if args.len() == 3 => {
+                cost.add(&self.charge(ClarityCostFunction::If, 0)?)?;
+                cost.add(&self.expr(&args[0], depth)?)?;
+                let then_branch = self.expr(&args[1], depth)?;
+                let else_branch = self.expr(&args[2], depth)?;
+                cost.add(&ExecutionCost::max_cost(then_branch, else_branch))?;
+            }
+            Asserts => {
+                cost.add(&self.charge(ClarityCostFunction::Asserts, 0)?)?;
+                cost.add(&self.sum(args, depth)?)?;
+            }
+            Let if !args.is_empty() => {
+                let bindings = args[0].match_list().unwrap_or(&[]);
+                cost.add(&self.charge(ClarityCostFunction::Let, bindings.len() as u64)?)?;
+                for binding in bindings.iter() {
+                    if let Some([_, value]) = binding.match_list() {
+                        cost.add(&self.expr(value, depth + 1)?)?;
+                    }
+                }
+                cost.add(&self.sum(rest, depth + 1)?)?;
+            }
+            Match if args.len() == 4 || args.len() == 5 => {
+                // (match opt some-name some-branch none-branch)
+                // (match resp ok-name ok-branch err-name err-branch)
+                cost.add(&self.charge(ClarityCostFunction::Match, 0)?)?;
+                cost.add(&self.expr(&args[0], depth)?)?;
+                let first_branch = self.expr(&args[2], depth + 1)?;
+                let second_branch = self.expr(&args[args.len() - 1], depth + 1)?;
+                cost.add(&ExecutionCost::max_cost(first_branch, second_branch))?;
+            }
+            And | Or => {
+                let cost_function = if native == And {
+                    ClarityCostFunction::And
+                } else {
+                    ClarityCostFunction::Or
+                };
+                cost.add(&self.charge(cost_function, args.len() as u64)?)?;
+                cost.add(&self.sum(args, depth)?)?;
+            }
+            CmpGeq | CmpLeq | CmpLess | CmpGreater => {
+                let cost_function = match native {
+                    CmpGeq => ClarityCostFunction::Geq,
+                    CmpLeq => ClarityCostFunction::Leq,
+                    CmpLess => ClarityCostFunction::Le,
+                    _ => ClarityCostFunction::Ge,
+                };
+                // charged by argument count before Clarity 2, and by the smaller argument after
+                let smallest = args.iter().map(|arg| self.size(arg)).min().unwrap_or(0);
+                let input = cmp::max(args.len() as u64, smallest);
+                cost.add(&self.charge(cost_function, input)?)?;
+                cost.add(&self.sum(args, depth)?)?;
+            }
+            FetchVar | SetVar => {
+                let var_size = args
+                    .first()
+                    .and_then(|var| var.match_atom())
+                    .and_then(|var| self.contract_analysis.persisted_variable_types.get(var))
+                    .map(|var_type| u64::from(var_type.size()))
+                    .unwrap_or(0);
+                let cost_function = if native == FetchVar {
+                    ClarityCostFunction::FetchVar
+                } else {
+                    ClarityCostFunction::SetVar
+                };
+                cost.add(&self.charge(cost_function, var_size)?)?;
+                cost.add(&self.sum(rest, depth)?)?;
+            }
+            FetchEntry | SetEntry | InsertEntry | DeleteEntry => {
+                let (key_size, value_size) = args
+                    .first()
+                    .and_then(|map| map.match_atom())
+                    .and_then(|map| self.contract_analysis.map_types.get(map))
+                    .map(|(key, value)| (u64::from(key.size()), u64::from(value.size())))
+                    .unwrap_or((0, 0));
+                let (cost_function, input) = match native {
+                    FetchEntry => (ClarityCostFunction::FetchEntry, key_size + value_size),
+                    DeleteEntry => (ClarityCostFunction::SetEntry, key_size),
+                    _ => (ClarityCostFunction::SetEntry, key_size + value_size),
+                };
+                cost.add(&self.charge(cost_function, input)?)?;
+                cost.add(&self.sum(rest, depth)?)?;
+            }
+            Map | Filter | Fold if args.len() >= 2 => {
+                let (cost_function, input) = match native {
+                    Map => (ClarityCostFunction::Map, args.len() as u64),
+                    Filter => (ClarityCostFunction::Filter, 0),
+                    _ => (ClarityCostFunction::Fold, 0),
+                };
+                cost.add(&self.charge(cost_function, input)?)?;
+                cost.add(&self.charge(ClarityCostFunction::LookupFunction, 0)?)?;
+                cost.add(&self.sum(rest, depth)?)?;
+
+                let sequences = if native == Map { rest } else { &rest[..1] };
+                let mut iterations = u64::MAX;
+                let mut argument_types = vec![];
+                for sequence in sequences.iter() {
+                    let (max_len, item_type) = self.sequence(sequence);
+                    iterations = cmp::min(iterations, max_len);
+                    argument_types.push(item_type);
+                }
+                if native == Fold {
+                    if let Some(initial_type) = rest.get(1).and_then(|e| self.type_map.get_type(e))
+                    {
+                        argument_types.push(initial_type.clone());
+                    }
+                }
+                let mut iteration = self.iteration(&args[0], &argument_types)?;
+                iteration.multiply(iterations)?;
+                cost.add(&iteration)?;
+            }
+            Append | Concat | ListCons | Slice | ReplaceAt | Print => {
+                let (cost_function, input) = match native {
+                    Append => {
+                        let (_, item_type) = args
+                            .first()
+                            .map(|list| self.sequence(list))
+                            .unwrap_or((0, TypeSignature::NoType));
+                        let element_size = args.get(1).map(|e| self.size(e)).unwrap_or(0);
+                        (
+                            ClarityCostFunction::Append,
+                            cmp::max(u64::from(item_type.size()), element_size),
+                        )
+                    }
+                    Concat => (
+                        ClarityCostFunction::Concat,
+                        args.iter().map(|arg| self.size(arg)).sum(),
+                    ),
+                    ListCons => (
+                        ClarityCostFunction::ListCons,
+                        args.iter().map(|arg| self.size(arg)).sum(),
+                    ),
+                    Slice => (
+                        ClarityCostFunction::Slice,
+                        args.first().map(|seq| self.size(seq)).unwrap_or(0),
+                    ),
+                    ReplaceAt => (
+                        ClarityCostFunction::ReplaceAt,
+                        args.first().map(|seq| self.size(seq)).unwrap_or(0),
+                    ),
+                    _ => (
+                        ClarityCostFunction::Print,
+                        args.first().map(|value| self.size(value)).unwrap_or(0),
+                    ),
+                };
+                cost.add(&self.charge(cost_function, input)?)?;
+                cost.add(&self.sum(args, depth)?)?;
+            }
+            TupleCons => {
+                cost.add(&self.charge(ClarityCostFunction::TupleCons, args.len() as u64)?)?;
+                for pair in args.iter() {
+                    if let Some([_, value]) = pair.match_list() {
+                        cost.add(&self.expr(value, depth)?)?;
+                    }
+                }
+            }
+            TupleGet if args.len() == 2 => {
+                let fields = match self.type_map.get_type(&args[1]) {
+                    Some(TypeSignature::TupleType(tuple)) => tuple.len(),
+                    Some(TypeSignature::OptionalType(inner)) => match inner.as_ref() {
+                        TypeSignature::TupleType(tuple) => tuple.len(),
+                        _ => 0,
+                    },
+                    _ => 0,
+                };
+                cost.add(&self.charge(ClarityCostFunction::TupleGet, fields)?)?;
+                cost.add(&self.expr(&args[1], depth)?)?;
+            }
+            AsMaxLen if !args.is_empty() => {
+                cost.add(&self.charge(ClarityCostFunction::AsMaxLen, 0)?)?;
+                cost.add(&self.expr(&args[0], depth)?)?;
+            }
+            FromConsensusBuff if args.len() == 2 => {
+                let input = self.size(&args[1]);
+                cost.add(&self.charge(ClarityCostFunction::FromConsensusBuff, input)?)?;
+                cost.add(&self.expr(&args[1], depth)?)?;
+            }
+            ContractCall if args.len() >= 2 => {
+                cost.add(&self.charge(ClarityCostFunction::ContractCall, 0)?)?;
+                cost.add(&self.sum(&args[2..], depth)?)?;
+                let literal = args[0]
+                    .match_literal_value()
+                    .or_else(|| args[0].match_atom_value());
+                let contract = match literal {
+                    Some(Value::Principal(PrincipalData::Contract(contract))) => {
+                        Some(contract.to_string())
+                    }
+                    _ => args[0].match_atom().map(|name| name.to_string()),
+                };
+                if let (Some(contract), Some(function)) = (contract, args[1].match_atom()) {
+                    self.external_calls.insert(ExternalCall {
+                        contract,
+                        function: function.to_string(),
+                    });
+                }
+            }
+            MintAsset | TransferAsset | GetAssetOwner | BurnAsset => {
+                let cost_function = match native {
+                    MintAsset => ClarityCostFunction::NftMint,
+                    TransferAsset => ClarityCostFunction::NftTransfer,
+                    GetAssetOwner => ClarityCostFunction::NftOwner,
+                    _ => ClarityCostFunction::NftBurn,
+                };
+                let asset_size = args
+                    .first()
+                    .and_then(|asset| asset.match_atom())
+                    .and_then(|asset| self.contract_analysis.non_fungible_tokens.get(asset))
+                    .map(|asset_type| u64::from(asset_type.size()))
+                    .unwrap_or(0);
+                cost.add(&self.charge(cost_function, asset_size)?)?;
+                cost.add(&self.sum(rest, depth)?)?;
+            }
+            MintToken | TransferToken | GetTokenBalance | GetTokenSupply | BurnToken => {
+                let cost_function = match native {
+                    MintToken => ClarityCostFunction::FtMint,
+                    TransferToken => ClarityCostFunction::FtTransfer,
+                    GetTokenBalance => ClarityCostFunction::FtBalance,
+                    GetTokenSupply => ClarityCostFunction::FtSupply,
+                    _ => ClarityCostFunction::FtBurn,
+                };
+                cost.add(&self.charge(cost_function, 0)?)?;
+                cost.add(&self.sum(rest, depth)?)?;
+            }
+            GetBlockInfo | GetBurnBlockInfo => {
+                let cost_function = if native == GetBlockInfo {
+                    ClarityCostFunction::BlockInfo
+                } else {
+                    ClarityCostFunction::GetBurnBlockInfo
+                };
+                cost.add(&self.charge(cost_function, 0)?)?;
+                cost.add(&self.sum(rest, depth)?)?;
+            }
+            _ => {
+                // the remaining special functions charge a constant cost and evaluate each argument
+                let cost_function = match native {
+                    AsContract => Some(ClarityCostFunction::AsContract),
+                    AtBlock => Some(ClarityCostFunction::AtBlock),
+                    ContractOf => Some(ClarityCostFunction::ContractOf),
+                    PrincipalOf => Some(ClarityCostFunction::PrincipalOf),
+                    Secp256k1Recover => Some(ClarityCostFunction::Secp256k1recover),
+                    Secp256k1Verify => Some(ClarityCostFunction::Secp256k1verify),
+                    IsStandard => Some(ClarityCostFunction::IsStandard),
+                    PrincipalDestruct => Some(ClarityCostFunction::PrincipalDestruct),
+                    PrincipalConstruct => Some(ClarityCostFunction::PrincipalConstruct),
+                    GetStxBalance => Some(ClarityCostFunction::StxBalance),
+                    StxTransfer | StxBurn => Some(ClarityCostFunction::StxTransfer),
+                    StxTransferMemo => Some(ClarityCostFunction::StxTransferMemo),
+                    StxGetAccount => Some(ClarityCostFunction::StxGetAccount),
+                    _ => None,
+                };
+                if let Some(cost_function) = cost_function {
+                    cost.add(&self.charge(cost_function, 0)?)?;
+                }
+                if native != ContractOf {
+                    cost.add(&self.sum(args, depth)?)?;
+                }
+            }
+        }
+        Ok(cost)
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::vm::analysis::cost_estimate::{CostEstimate, ExternalCall};
+use crate::vm::analysis::type_check;
+use crate::vm::ast::parse;
+use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::{CostErrors, CostTracker, ExecutionCost};
+use crate::vm::database::MemoryBackingStore;
+use crate::vm::representations::ClarityName;
+use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::ClarityVersion;
+use stacks_common::types::StacksEpochId;
+
+/// Charges 1000 plus the input size for every cost function, and one read or write for the
+///  data functions, so that estimates can be checked by hand
+struct UnitCosts;
+
+impl CostTracker for UnitCosts {
+    fn compute_cost(
+        &mut self,
+        cost_function: ClarityCostFunction,
+        input: &[u64],
+    ) -> Result<ExecutionCost, CostErrors> {
+        let mut cost = ExecutionCost::runtime(1000 + input[0]);
+        match cost_function {
+            ClarityCostFunction::FetchVar | ClarityCostFunction::FetchEntry => {
+                cost.read_count = 1;
+            }
+            ClarityCostFunction::SetVar | ClarityCostFunction::SetEntry => {
+                cost.write_count = 1;
+            }
+            _ => {}
+        }
+        Ok(cost)
+    }
+    fn add_cost(&mut self, _cost: ExecutionCost) -> Result<(), CostErrors> {
+        panic!("Cost estimates should not add to the running total");
+    }
+    fn add_memory(&mut self, _memory: u64) -> Result<(), CostErrors> {
+        Ok(())
+    }
+    fn drop_memory(&mut self, _memory: u64) {}
+    fn reset_memory(&mut self) {}
+    fn short_circuit_contract_call(
+        &mut self,
+        _contract: &QualifiedContractIdentifier,
+        _function: &ClarityName,
+        _input: &[u64],
+    ) -> Result<bool, CostErrors> {
+        Ok(false)
+    }
+}
+
+fn estimate(contract: &str, block_limit: &ExecutionCost) -> CostEstimate {
+    let version = ClarityVersion::Clarity2;
+    let epoch = StacksEpochId::Epoch21;
+    let contract_id = QualifiedContractIdentifier::local("estimated").unwrap();
+    let mut expressions = parse(&contract_id, contract, version, epoch).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+    let analysis = db
+        .execute(|db| type_check(&contract_id, &mut expressions, db, false, &epoch, &version))
+        .unwrap();
+    CostEstimate::run(&analysis, 0, &mut UnitCosts, block_limit).unwrap()
+}
+
+#[test]
+fn test_charges() {
+    let estimate = estimate(
+        "(define-read-only (one) u1)
+         (define-read-only (inc (a uint)) (+ a u1))
+         (define-private (hidden) u2)",
+        &ExecutionCost::max_value(),
+    );
+    assert_eq!(
+        estimate.functions.keys().collect::<Vec<_>>(),
+        vec!["inc", "one"]
+    );
+
+    // loading an empty contract, and applying a function of no arguments to a literal
+    assert_eq!(
+        estimate.functions["one"].cost,
+        ExecutionCost::runtime(1000 + 1000)
+    );
+    // loading + application (1 argument) + argument type check (uint is 16 bytes) + function lookup +
+    //  `+` of 2 arguments + variable lookup at depth 0 + variable size
+    assert_eq!(
+        estimate.functions["inc"].cost,
+        ExecutionCost::runtime(1000 + 1001 + 1016 + 1000 + 1002 + 1000 + 1016)
+    );
+    assert!(!estimate.functions["inc"].exceeds_block_limit);
+}
+
+#[test]
+fn test_input_sizes() {
+    let estimate = estimate(
+        "(define-read-only (sum-small (xs (list 10 uint))) (fold + xs u0))
+         (define-read-only (sum-large (xs (list 100 uint))) (fold + xs u0))
+         (define-data-var total uint u0)
+         (define-public (pick (flag bool))
+             (if flag
+                 (begin (var-set total (+ (var-get total) u1)) (ok true))
+                 (ok false)))",
+        &ExecutionCost::max_value(),
+    );
+    let small = &estimate.functions["sum-small"].cost;
+    let large = &estimate.functions["sum-large"].cost;
+    // each of the 90 extra items is folded with one application of `+`, and the larger list
+    //  costs 16 bytes more per item to type check and to look up
+    assert_eq!(large.runtime - small.runtime, 90 * 1002 + 2 * 90 * 16);
+
+    // the more expensive branch of the `if` is charged
+    let pick = &estimate.functions["pick"].cost;
+    assert_eq!(pick.read_count, 1);
+    assert_eq!(pick.write_count, 1);
+}
+
+#[test]
+fn test_external_calls_and_limits() {
+    let limit = ExecutionCost {
+        runtime: 10_000,
+        ..ExecutionCost::max_value()
+    };
+    let estimate = estimate(
+        "(define-trait adder ((add (uint) (response uint uint))))
+         (define-private (forward (target <adder>) (n uint))
+             (contract-call? target add n))
+         (define-public (call-twice (target <adder>))
+             (begin
+                 (try! (forward target u1))
+                 (forward target u2)))
+         (define-read-only (cheap) (ok u1))",
+        &limit,
+    );
+
+    let call_twice = &estimate.functions["call-twice"];
+    assert_eq!(call_twice.visibility, "public");
+    assert_eq!(
+        call_twice.external_calls.iter().collect::<Vec<_>>(),
+        vec![&ExternalCall {
+            contract: "target".into(),
+            function: "add".into(),
+        }]
+    );
+    assert!(call_twice.exceeds_block_limit);
+
+    let cheap = &estimate.functions["cheap"];
+    assert_eq!(cheap.visibility, "read-only");
+    assert!(cheap.external_calls.is_empty());
+    assert!(!cheap.exceeds_block_limit);
+    assert_eq!(estimate.block_limit, limit);
+}
@@ -0,0 +1,430 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use crate::vm::analysis::types::ContractAnalysis;
+use crate::vm::diagnostic::{DiagnosableError, Diagnostic, Level};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::NativeFunctions;
+use crate::vm::representations::{ClarityName, SymbolicExpression};
+use crate::vm::types::TypeSignature;
+use crate::vm::variables::NativeVariables;
+use crate::vm::Value;
+
+#[cfg(test)]
+mod tests;
+
+/// Warnings produced by the lint pass.  None of these make a contract invalid.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LintWarning {
+    /// `unwrap-panic` or `unwrap-err-panic` applied to a response
+    UncheckedResponse(String),
+    /// a `let` binding or private function argument that is never read
+    UnusedBinding(String),
+    /// a private function that is never called
+    UnusedPrivateFunction(String),
+    /// `as-contract` around a `contract-call?` to a contract passed in by the caller
+    AsContractUntrustedCall,
+    /// an `asserts!` or `if` whose condition is `(is-eq tx-sender <principal>)`, where the
+    /// principal is fixed by the contract (a constant, a data var or a literal) rather than
+    /// supplied by the caller
+    TxSenderAuthorization,
+}
+
+impl DiagnosableError for LintWarning {
+    fn message(&self) -> String {
+        match self {
+            LintWarning::UncheckedResponse(func) => {
+                format!("'{}' aborts the transaction if the response is not the expected variant", func)
+            }
+            LintWarning::UnusedBinding(name) => format!("'{}' is never used", name),
+            LintWarning::UnusedPrivateFunction(name) => {
+                format!("private function '{}' is never called", name)
+            }
+            LintWarning::AsContractUntrustedCall => {
+                "'as-contract' wraps a 'contract-call?' to a contract supplied by the caller".into()
+            }
+            LintWarning::TxSenderAuthorization => {
+                "authorization check against 'tx-sender' can be satisfied by any contract the sender calls".into()
+            }
+        }
+    }
+
+    fn suggestion(&self) -> Option<String> {
+        match self {
+            LintWarning::UncheckedResponse(_) => {
+                Some("use 'unwrap!' or 'try!' to return a meaningful error instead".into())
+            }
+            LintWarning::UnusedBinding(_) => Some("remove the binding".into()),
+            LintWarning::UnusedPrivateFunction(_) => Some("remove the function".into()),
+            LintWarning::AsContractUntrustedCall => Some(
+                "the callee acts with this contract's assets; only call contracts you trust here"
+                    .into(),
+            ),
+            LintWarning::TxSenderAuthorization => {
+                Some("compare against 'contract-caller' instead".into())
+            }
+        }
+    }
+
+    fn level(&self) -> Level {
+        Level::Warning
+    }
+}
+
+/// A binding in scope while walking an expression
+struct Binding<'a> {
+    name: &'a ClarityName,
+    expr: &'a SymbolicExpression,
+    used: bool,
+    /// should this binding be reported if it goes unused?
+    check_unused: bool,
+}
+
+///
+/// An optional static-analysis pass that looks for questionable (but valid) code in a
+///  contract that has passed the type checker.
+///
+pub struct Linter<'a> {
+    contract_analysis: &'a ContractAnalysis,
+    scopes: Vec<Vec<Binding<'a>>>,
+    /// names of everything referenced as an atom anywhere in a function body
+    referenced: HashSet<&'a ClarityName>,
+    /// names of the contract's constants
+    constants: HashSet<&'a ClarityName>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    /// Lint a contract that has passed analysis.  Returns the warnings, in source order per
+    /// definition.
+    pub fn run(contract_analysis: &'a ContractAnalysis) -> Vec<Diagnostic> {
+        let mut linter = Linter {
+            contract_analysis,
+            scopes: vec![],
+            referenced: HashSet::new(),
+            constants: HashSet::new(),
+            diagnostics: vec![],
+        };
+
+        for expr in contract_analysis.expressions.iter() {
+            if let Ok(Some(DefineFunctionsParsed::Constant { name, .. })) =
+                DefineFunctionsParsed::try_parse(expr)
+            {
+                linter.constants.insert(name);
+            }
+        }
+
+        let mut private_functions = vec![];
+        for expr in contract_analysis.expressions.iter() {
+            match DefineFunctionsParsed::try_parse(expr) {
+                Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, body })) => {
+                    private_functions.push(&signature[0]);
+                    linter.lint_function(signature, body, true);
+                }
+                Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, body }))
+                | Ok(Some(DefineFunctionsParsed::PublicFunction { signature, body })) => {
+                    linter.lint_function(signature, body, false);
+                }
+                Ok(Some(DefineFunctionsParsed::Constant { value, .. })) => {
+                    linter.lint_expression(value);
+                }
+                Ok(Some(DefineFunctionsParsed::PersistedVariable { initial, .. })) => {
+                    linter.lint_expression(initial);
+                }
+                Ok(Some(_)) | Err(_) => {}
+                Ok(None) => linter.lint_expression(expr),
+            }
+        }
+
+        for name_expr in private_functions {
+            if let Some(name) = name_expr.match_atom() {
+                if !linter.referenced.contains(name) {
+                    linter.warn(
+                        LintWarning::UnusedPrivateFunction(name.to_string()),
+                        name_expr,
+                    );
+                }
+            }
+        }
+
+        linter.diagnostics
+    }
+
+    fn warn(&mut self, warning: LintWarning, expr: &SymbolicExpression) {
+        let mut diagnostic = Diagnostic::new(&warning);
+        diagnostic.spans = vec![expr.span.clone()];
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Lint a function definition.  Arguments are only reported as unused for private functions,
+    /// since public and read-only signatures are part of the contract's interface.
+    fn lint_function(
+        &mut self,
+        signature: &'a [SymbolicExpression],
+        body: &'a SymbolicExpression,
+        is_private: bool,
+    ) {
+        self.scopes.push(vec![]);
+        for arg in signature[1..].iter() {
+            if let Some(name_expr) = arg.match_list().and_then(|pair| pair.first()) {
+                self.bind(name_expr, is_private);
+            }
+        }
+        self.lint_expression(body);
+        self.pop_scope();
+    }
+
+    fn bind(&mut self, name_expr: &'a SymbolicExpression, check_unused: bool) {
+        let name = match name_expr.match_atom() {
+            Some(name) => name,
+            None => return,
+        };
+        self.scopes
+            .last_mut()
+            .expect("BUG: binding outside of a scope")
+            .push(Binding {
+                name,
+                expr: name_expr,
+                used: false,
+                check_unused,
+            });
+    }
+
+    fn pop_scope(&mut self) {
+        let scope = self.scopes.pop().expect("BUG: no scope to pop");
+        for binding in scope {
+            if binding.check_unused && !binding.used {
+                self.warn(
+                    LintWarning::UnusedBinding(binding.name.to_string()),
+                    binding.expr,
+                );
+            }
+        }
+    }
+
+    fn mark_used(&mut self, name: &'a ClarityName) {
+        self.referenced.insert(name);
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(binding) = scope.iter_mut().rev().find(|b| b.name == name) {
+                binding.used = true;
+                return;
+            }
+        }
+    }
+
+    fn lint_expression(&mut self, expr: &'a SymbolicExpression) {
+        if let Some(name) = expr.match_atom() {
+            self.mark_used(name);
+            return;
+        }
+        let list = match expr.match_list() {
+            Some(list) => list,
+            None => return,
+        };
+        let (function, args) = match list.split_first() {
+            Some((function, args)) => (function, args),
+            None => return,
+        };
+        let function_name = match function.match_atom() {
+            Some(name) => name,
+            None => {
+                for e in list.iter() {
+                    self.lint_expression(e);
+                }
+                return;
+            }
+        };
+        let native = NativeFunctions::lookup_by_name_at_version(
+            function_name,
+            &self.contract_analysis.clarity_version,
+        );
+
+        match native {
+            Some(NativeFunctions::Let) if !args.is_empty() => {
+                self.scopes.push(vec![]);
+                for binding in args[0].match_list().unwrap_or(&[]).iter() {
+                    if let Some([name_expr, value]) = binding.match_list() {
+                        self.lint_expression(value);
+                        self.bind(name_expr, true);
+                    }
+                }
+                for e in args[1..].iter() {
+                    self.lint_expression(e);
+                }
+                self.pop_scope();
+            }
+            Some(NativeFunctions::Match) => self.lint_match(args),
+            Some(NativeFunctions::TupleCons) => {
+                // (tuple (key value) ...) -- keys are not variables
+                for pair in args.iter() {
+                    if let Some([_, value]) = pair.match_list() {
+                        self.lint_expression(value);
+                    }
+                }
+            }
+            Some(NativeFunctions::TupleGet) if args.len() == 2 => {
+                // (get key tuple) -- key is not a variable
+                self.lint_expression(&args[1]);
+            }
+            _ => {
+                self.mark_used(function_name);
+                match native {
+                    Some(NativeFunctions::UnwrapRet) | Some(NativeFunctions::UnwrapErrRet) => {}
+                    Some(NativeFunctions::Unwrap) | Some(NativeFunctions::UnwrapErr) => {
+                        if let Some(arg) = args.first() {
+                            if self.is_response(arg) {
+                                self.warn(
+                                    LintWarning::UncheckedResponse(function_name.to_string()),
+                                    expr,
+                                );
+                            }
+                        }
+                    }
+                    Some(NativeFunctions::AsContract)
+                        if args.iter().any(Self::has_dynamic_contract_call) =>
+                    {
+                        self.warn(LintWarning::AsContractUntrustedCall, expr);
+                    }
+                    Some(NativeFunctions::Asserts) | Some(NativeFunctions::If) => {
+                        if let Some(condition) = args.first() {
+                            if self.is_tx_sender_authorization(condition) {
+                                self.warn(LintWarning::TxSenderAuthorization, condition);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                for e in args.iter() {
+                    self.lint_expression(e);
+                }
+            }
+        }
+    }
+
+    /// (match opt some-name some-branch none-branch) or
+    /// (match resp ok-name ok-branch err-name err-branch)
+    fn lint_match(&mut self, args: &'a [SymbolicExpression]) {
+        if args.is_empty() {
+            return;
+        }
+        self.lint_expression(&args[0]);
+        for branch in args[1..].chunks(2) {
+            match branch {
+                [name_expr, body] if name_expr.match_atom().is_some() => {
+                    self.scopes.push(vec![]);
+                    self.bind(name_expr, false);
+                    self.lint_expression(body);
+                    self.pop_scope();
+                }
+                _ => {
+                    for e in branch.iter() {
+                        self.lint_expression(e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Is this `(is-eq tx-sender <principal>)` (in either order), where the principal is fixed
+    /// by the contract?  Comparisons against a function argument, a map entry or
+    /// `contract-caller` are not authorization checks of this kind.
+    fn is_tx_sender_authorization(&self, expr: &SymbolicExpression) -> bool {
+        let list = match expr.match_list() {
+            Some(list) if list.len() == 3 => list,
+            _ => return false,
+        };
+        if list[0]
+            .match_atom()
+            .and_then(|name| NativeFunctions::lookup_by_name(name))
+            != Some(NativeFunctions::Equals)
+        {
+            return false;
+        }
+        let is_tx_sender = |e: &SymbolicExpression| {
+            e.match_atom()
+                .and_then(|name| NativeVariables::lookup_by_name(name))
+                == Some(NativeVariables::TxSender)
+        };
+        match (&list[1], &list[2]) {
+            (a, b) if is_tx_sender(a) => self.is_contract_principal(b),
+            (a, b) if is_tx_sender(b) => self.is_contract_principal(a),
+            _ => false,
+        }
+    }
+
+    /// Is this a principal fixed by the contract: a constant, a `var-get` or a literal?
+    fn is_contract_principal(&self, expr: &SymbolicExpression) -> bool {
+        if let Some(name) = expr.match_atom() {
+            return self.constants.contains(name);
+        }
+        if let Some(Value::Principal(_)) = expr.match_literal_value() {
+            return true;
+        }
+        match expr.match_list() {
+            Some([function, _]) => {
+                function
+                    .match_atom()
+                    .and_then(|name| NativeFunctions::lookup_by_name(name))
+                    == Some(NativeFunctions::FetchVar)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_response(&self, expr: &SymbolicExpression) -> bool {
+        match self
+            .contract_analysis
+            .type_map
+            .as_ref()
+            .and_then(|type_map| type_map.get_type(expr))
+        {
+            Some(TypeSignature::ResponseType(_)) => true,
+            Some(_) => false,
+            // no type information, so only flag what's obviously a response
+            None => Self::is_contract_call(expr).is_some(),
+        }
+    }
+
+    /// If this is a `contract-call?`, return its target contract expression
+    fn is_contract_call(expr: &SymbolicExpression) -> Option<&SymbolicExpression> {
+        let list = expr.match_list()?;
+        if list.len() >= 2
+            && list[0]
+                .match_atom()
+                .and_then(|name| NativeFunctions::lookup_by_name(name))
+                == Some(NativeFunctions::ContractCall)
+        {
+            Some(&list[1])
+        } else {
+            None
+        }
+    }
+
+    /// Does this expression contain a `contract-call?` to a contract that isn't known until
+    /// runtime (i.e. a trait reference)?
+    fn has_dynamic_contract_call(expr: &SymbolicExpression) -> bool {
+        if let Some(target) = Self::is_contract_call(expr) {
+            if target.match_atom().is_some() {
+                return true;
+            }
+        }
+        expr.match_list()
+            .map(|list| list.iter().any(Self::has_dynamic_contract_call))
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::types::StacksEpochId;
+use crate::vm::analysis::lint::{LintWarning, Linter};
+use crate::vm::analysis::mem_type_check;
+use crate::vm::diagnostic::{DiagnosableError, Diagnostic, Level};
+use crate::vm::ClarityVersion;
+
+fn lint(snippet: &str) -> Vec<Diagnostic> {
+    let (_, analysis) =
+        mem_type_check(snippet, ClarityVersion::Clarity2, StacksEpochId::Epoch21).unwrap();
+    Linter::run(&analysis)
+}
+
+fn assert_warnings(snippet: &str, expected: &[LintWarning]) {
+    let diagnostics = lint(snippet);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.clone()).collect();
+    let expected_messages: Vec<_> = expected.iter().map(|w| w.message()).collect();
+    assert_eq!(messages, expected_messages);
+    for diagnostic in diagnostics.iter() {
+        assert_eq!(diagnostic.level, Level::Warning);
+        assert!(diagnostic.suggestion.is_some());
+        assert_eq!(diagnostic.spans.len(), 1);
+    }
+}
+
+#[test]
+fn test_clean_contract() {
+    let contract = "(define-data-var owner principal tx-sender)
+        (define-map balances principal uint)
+        (define-private (add (a uint) (b uint)) (+ a b))
+        (define-public (deposit (amount uint))
+            (let ((balance (default-to u0 (map-get? balances contract-caller))))
+                (asserts! (is-eq contract-caller (var-get owner)) (err u1))
+                (map-set balances contract-caller (add balance amount))
+                (ok (get total { total: balance }))))
+        (define-read-only (get-balance (who principal))
+            (match (map-get? balances who) balance (ok balance) (err u2)))";
+    assert_warnings(contract, &[]);
+}
+
+#[test]
+fn test_unused_bindings() {
+    let contract = "(define-private (helper (a uint) (b uint)) (+ a u1))
+        (define-public (go (unused-arg uint))
+            (let ((x u1) (y (helper x u2)))
+                (ok u3)))";
+    let diagnostics = lint(contract);
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|d| d.message.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            LintWarning::UnusedBinding("b".into()).message(),
+            LintWarning::UnusedBinding("y".into()).message(),
+        ]
+    );
+    // the unused binding is pointed at
+    assert_eq!(diagnostics[0].spans[0].start_line, 1);
+    assert_eq!(diagnostics[1].spans[0].start_line, 3);
+}
+
+#[test]
+fn test_unused_private_function() {
+    let contract = "(define-private (used (a uint)) a)
+        (define-private (unused (a uint)) a)
+        (define-private (mapped (a uint)) a)
+        (define-read-only (go) (+ (used u1) (fold + (map mapped (list u1 u2)) u0)))";
+    assert_warnings(
+        contract,
+        &[LintWarning::UnusedPrivateFunction("unused".into())],
+    );
+}
+
+#[test]
+fn test_unchecked_response() {
+    let contract = "(define-private (get-it) (ok u1))
+        (define-read-only (go)
+            (+ (unwrap-panic (get-it)) (unwrap-panic (some u1)) (unwrap! (get-it) u0)))";
+    assert_warnings(
+        contract,
+        &[LintWarning::UncheckedResponse("unwrap-panic".into())],
+    );
+}
+
+#[test]
+fn test_as_contract_untrusted_call() {
+    let contract = "(define-trait token-trait ((transfer (uint principal) (response bool uint))))
+        (define-public (pay (token <token-trait>) (amount uint))
+            (as-contract (contract-call? token transfer amount tx-sender)))";
+    assert_warnings(contract, &[LintWarning::AsContractUntrustedCall]);
+}
+
+#[test]
+fn test_tx_sender_authorization() {
+    let contract = "(define-constant owner tx-sender)
+        (define-data-var admin principal tx-sender)
+        (define-public (by-constant)
+            (begin
+                (asserts! (is-eq tx-sender owner) (err u1))
+                (ok true)))
+        (define-public (by-var)
+            (if (is-eq (var-get admin) tx-sender) (ok true) (err u1)))
+        (define-public (by-literal)
+            (begin
+                (asserts! (is-eq tx-sender 'SP000000000000000000002Q6VF78) (err u1))
+                (ok true)))";
+    assert_warnings(
+        contract,
+        &[
+            LintWarning::TxSenderAuthorization,
+            LintWarning::TxSenderAuthorization,
+            LintWarning::TxSenderAuthorization,
+        ],
+    );
+}
+
+#[test]
+fn test_tx_sender_comparisons_not_authorization() {
+    // comparisons against caller-supplied principals, map entries and `contract-caller`, and
+    // comparisons outside of a condition, are not flagged
+    let contract = "(define-constant owner tx-sender)
+        (define-map buyers uint principal)
+        (define-public (transfer (recipient principal))
+            (begin
+                (asserts! (not (is-eq tx-sender recipient)) (err u1))
+                (asserts! (is-eq tx-sender recipient) (err u2))
+                (asserts! (is-eq (some tx-sender) (map-get? buyers u1)) (err u3))
+                (asserts! (is-eq tx-sender contract-caller) (err u4))
+                (ok (is-eq tx-sender owner))))";
+    assert_warnings(contract, &[]);
+}
@@ -16,8 +16,11 @@
 
 pub mod analysis_db;
 pub mod arithmetic_checker;
+pub mod call_graph;
+pub mod cost_estimate;
 pub mod contract_interface_builder;
 pub mod errors;
+pub mod lint;
 pub mod read_only_checker;
 pub mod trait_checker;
 pub mod type_checker;
@@ -0,0 +1,467 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A canonical pretty-printer for Clarity source code.
+//!
+//! The formatter works on the v2 parser's `PreSymbolicExpression`s, which keep comments and
+//! source spans.  Literals and names are copied verbatim from the source, so only whitespace
+//! and the placement of comments ever change.  An expression is printed on one line if it fits
+//! in `MAX_WIDTH` columns and holds no comments; otherwise its elements are broken onto their
+//! own lines, indented two spaces from the opening paren.  Forms like `define-public` and
+//! `let` keep their first argument (the signature, the bindings) on the opening line.  Single
+//! blank lines between expressions are kept, runs of them are collapsed, and a comment that
+//! trails an expression stays on that expression's line.
+
+use crate::vm::ast::parser::v2::parse_collect_diagnostics;
+use crate::vm::diagnostic::Level;
+use crate::vm::representations::{PreSymbolicExpression, PreSymbolicExpressionType, Span};
+
+/// Lines are kept within this many columns, unless a single name or literal is longer
+pub const MAX_WIDTH: usize = 80;
+
+const INDENT: usize = 2;
+
+/// Forms that keep this many arguments on the line with their name when they are broken
+const HEADER_ARGUMENTS: &[(&str, usize)] = &[
+    ("define-constant", 1),
+    ("define-data-var", 1),
+    ("define-fungible-token", 1),
+    ("define-map", 1),
+    ("define-non-fungible-token", 1),
+    ("define-private", 1),
+    ("define-public", 1),
+    ("define-read-only", 1),
+    ("define-trait", 1),
+    ("if", 1),
+    ("let", 1),
+    ("match", 1),
+];
+
+/// Format a contract.  Fails with the parser's error messages if `source` doesn't parse.
+pub fn format_source(source: &str) -> Result<String, String> {
+    let (expressions, diagnostics, success) = parse_collect_diagnostics(source);
+    if !success {
+        return Err(diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.level == Level::Error)
+            .map(|diagnostic| diagnostic.to_string())
+            .collect());
+    }
+
+    let formatter = Formatter {
+        lines: source.split('\n').collect(),
+    };
+    let mut out = String::new();
+    formatter.write_lines(&mut out, &expressions, 0, None);
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    // never hand back something that means anything different
+    let (reformatted, _, success) = parse_collect_diagnostics(&out);
+    if !success || !same_expressions(&expressions, &reformatted) {
+        return Err("BUG: formatting changed the structure of the contract".to_string());
+    }
+    Ok(out)
+}
+
+struct Formatter<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Formatter<'a> {
+    /// The source text of a name or literal
+    fn source_text(&self, span: &Span) -> &'a str {
+        let line = self.lines[span.start_line as usize - 1];
+        &line[span.start_column as usize - 1..span.end_column as usize]
+    }
+
+    /// The source text of a comment, which runs to the end of its line.  (The parser's copy of
+    /// the text has been trimmed, and would lose the indentation of comment blocks.)
+    fn comment_text(&self, span: &Span) -> &'a str {
+        let line = self.lines[span.start_line as usize - 1];
+        line[span.start_column as usize - 1..].trim_end()
+    }
+
+    /// `expr` on a single line, unless it contains a comment
+    fn flat(&self, expr: &PreSymbolicExpression) -> Option<String> {
+        match expr.pre_expr {
+            PreSymbolicExpressionType::List(ref children) => {
+                let children = children
+                    .iter()
+                    .map(|child| self.flat(child))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("({})", children.join(" ")))
+            }
+            PreSymbolicExpressionType::Tuple(ref children) => {
+                let children = children
+                    .iter()
+                    .map(|child| self.flat(child))
+                    .collect::<Option<Vec<_>>>()?;
+                let entries: Vec<String> =
+                    children.chunks(2).map(|entry| entry.join(": ")).collect();
+                Some(format!("{{ {} }}", entries.join(", ")))
+            }
+            PreSymbolicExpressionType::Comment(_) => None,
+            _ => Some(self.source_text(&expr.span).to_string()),
+        }
+    }
+
+    /// Write `expr`, which starts at `column`
+    fn write_expr(&self, out: &mut String, expr: &PreSymbolicExpression, column: usize) {
+        if let Some(flat) = self.flat(expr) {
+            if column + flat.len() <= MAX_WIDTH {
+                out.push_str(&flat);
+                return;
+            }
+        }
+        match expr.pre_expr {
+            PreSymbolicExpressionType::List(ref children) => self.write_list(out, children, column),
+            PreSymbolicExpressionType::Tuple(ref children) => {
+                self.write_tuple(out, children, column)
+            }
+            PreSymbolicExpressionType::Comment(_) => out.push_str(self.comment_text(&expr.span)),
+            _ => out.push_str(self.source_text(&expr.span)),
+        }
+    }
+
+    /// Write `exprs` one per line at `column`.  The first is written where `out` leaves off,
+    /// unless there is a `previous` expression on the current line for it to follow.  Returns
+    /// whether the output now ends in a comment.
+    fn write_lines<'b>(
+        &self,
+        out: &mut String,
+        exprs: &'b [PreSymbolicExpression],
+        column: usize,
+        mut previous: Option<&'b PreSymbolicExpression>,
+    ) -> bool {
+        let mut in_comment = false;
+        for expr in exprs.iter() {
+            in_comment = expr.match_comment().is_some();
+            if let Some(previous) = previous {
+                if expr.match_comment().is_some() && expr.span.start_line == previous.span.end_line
+                {
+                    // a trailing comment
+                    out.push(' ');
+                    self.write_expr(out, expr, current_column(out));
+                    continue;
+                }
+                out.push('\n');
+                if expr.span.start_line > previous.span.end_line + 1 {
+                    out.push('\n');
+                }
+                indent(out, column);
+            }
+            self.write_expr(out, expr, column);
+            previous = Some(expr);
+        }
+        in_comment
+    }
+
+    fn write_list(&self, out: &mut String, children: &[PreSymbolicExpression], column: usize) {
+        out.push('(');
+        let (header, body_column) = match children.first().and_then(|head| head.match_atom()) {
+            Some(name) => {
+                let arguments = HEADER_ARGUMENTS
+                    .iter()
+                    .find(|(form, _)| *form == name.as_str())
+                    .map(|(_, arguments)| *arguments)
+                    .unwrap_or(0);
+                (1 + arguments, column + INDENT)
+            }
+            // a list of bindings or arguments lines its elements up
+            None => (1, column + 1),
+        };
+        // a comment ends the line, so nothing may follow one on the opening line
+        let header = children
+            .iter()
+            .take(header)
+            .take_while(|child| child.match_comment().is_none())
+            .count();
+
+        let mut previous = None;
+        for (i, child) in children[..header].iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            self.write_expr(out, child, current_column(out));
+            previous = Some(child);
+        }
+        let body = &children[header..];
+        if !body.is_empty() && body.iter().all(is_simple) {
+            // fill lines with names and literals rather than giving each its own
+            for child in body.iter() {
+                let text = self.source_text(&child.span);
+                if current_column(out) + 1 + text.len() <= MAX_WIDTH {
+                    out.push(' ');
+                } else {
+                    out.push('\n');
+                    indent(out, body_column);
+                }
+                out.push_str(text);
+            }
+        } else if self.write_lines(out, body, body_column, previous) {
+            out.push('\n');
+            indent(out, column);
+        }
+        out.push(')');
+    }
+
+    fn write_tuple(&self, out: &mut String, children: &[PreSymbolicExpression], column: usize) {
+        // pair up keys and values, moving any comment between the two ahead of the entry
+        let mut items = vec![];
+        let mut key = None;
+        for child in children.iter() {
+            if child.match_comment().is_some() {
+                items.push(TupleItem::Comment(child));
+            } else if let Some(key) = key.take() {
+                items.push(TupleItem::Entry(key, child));
+            } else {
+                key = Some(child);
+            }
+        }
+
+        out.push('{');
+        let entry_column = column + INDENT;
+        let mut previous_line = None;
+        for (i, item) in items.iter().enumerate() {
+            match item {
+                TupleItem::Comment(comment) if previous_line == Some(comment.span.start_line) => {
+                    out.push(' ');
+                    self.write_expr(out, comment, current_column(out));
+                }
+                _ => {
+                    out.push('\n');
+                    if matches!(previous_line, Some(line) if item.start_line() > line + 1) {
+                        out.push('\n');
+                    }
+                    indent(out, entry_column);
+                    match item {
+                        TupleItem::Comment(comment) => self.write_expr(out, comment, entry_column),
+                        TupleItem::Entry(key, value) => {
+                            out.push_str(self.source_text(&key.span));
+                            out.push_str(": ");
+                            self.write_expr(out, value, current_column(out));
+                            let more = items[i + 1..]
+                                .iter()
+                                .any(|item| matches!(item, TupleItem::Entry(..)));
+                            if more {
+                                out.push(',');
+                            }
+                        }
+                    }
+                }
+            }
+            previous_line = Some(item.end_line());
+        }
+        out.push('\n');
+        indent(out, column);
+        out.push('}');
+    }
+}
+
+enum TupleItem<'a> {
+    Entry(&'a PreSymbolicExpression, &'a PreSymbolicExpression),
+    Comment(&'a PreSymbolicExpression),
+}
+
+impl<'a> TupleItem<'a> {
+    fn start_line(&self) -> u32 {
+        match self {
+            TupleItem::Entry(key, _) => key.span.start_line,
+            TupleItem::Comment(comment) => comment.span.start_line,
+        }
+    }
+
+    fn end_line(&self) -> u32 {
+        match self {
+            TupleItem::Entry(_, value) => value.span.end_line,
+            TupleItem::Comment(comment) => comment.span.end_line,
+        }
+    }
+}
+
+/// Is `expr` a name or literal?
+fn is_simple(expr: &PreSymbolicExpression) -> bool {
+    !matches!(
+        expr.pre_expr,
+        PreSymbolicExpressionType::List(_)
+            | PreSymbolicExpressionType::Tuple(_)
+            | PreSymbolicExpressionType::Comment(_)
+    )
+}
+
+fn indent(out: &mut String, column: usize) {
+    out.push_str(&" ".repeat(column));
+}
+
+fn current_column(out: &str) -> usize {
+    out.len() - out.rfind('\n').map_or(0, |newline| newline + 1)
+}
+
+/// Do `a` and `b` parse to the same thing, spans and whitespace aside?  Comments may move
+/// around code, but not past each other.
+fn same_expressions(a: &[PreSymbolicExpression], b: &[PreSymbolicExpression]) -> bool {
+    let mut a_comments = vec![];
+    let mut b_comments = vec![];
+    same_code(a, b, &mut a_comments, &mut b_comments) && a_comments == b_comments
+}
+
+fn same_code<'a>(
+    a: &'a [PreSymbolicExpression],
+    b: &'a [PreSymbolicExpression],
+    a_comments: &mut Vec<&'a str>,
+    b_comments: &mut Vec<&'a str>,
+) -> bool {
+    let a: Vec<_> = a
+        .iter()
+        .filter(|expr| match expr.match_comment() {
+            Some(comment) => {
+                a_comments.push(comment.trim_end());
+                false
+            }
+            None => true,
+        })
+        .collect();
+    let b: Vec<_> = b
+        .iter()
+        .filter(|expr| match expr.match_comment() {
+            Some(comment) => {
+                b_comments.push(comment.trim_end());
+                false
+            }
+            None => true,
+        })
+        .collect();
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(a, b)| match (&a.pre_expr, &b.pre_expr) {
+                (PreSymbolicExpressionType::List(a), PreSymbolicExpressionType::List(b))
+                | (PreSymbolicExpressionType::Tuple(a), PreSymbolicExpressionType::Tuple(b)) => {
+                    same_code(a, b, a_comments, b_comments)
+                }
+                (a, b) => a == b,
+            })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_formats(source: &str, expected: &str) {
+        let formatted = format_source(source).unwrap();
+        assert_eq!(formatted, expected);
+        // formatting is idempotent
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_format_layout() {
+        assert_formats(
+            "(define-data-var   counter uint u0)
+(define-public (increment (by uint)) (begin (var-set counter (+ (var-get counter) by)) (ok (var-get counter))))
+(define-read-only (get-owner (id uint)) (let ((owner (unwrap! (map-get? owners {id: id}) (err u404))) (balance (stx-get-balance owner)) (name \"some \\\"quoted\\\" name\")) (ok {owner: owner, balance: balance, name: name, flags: 0x0102ff, memo: u\"h\\u{e9}llo\"})))
+(define-public (call (t <token-trait>))
+    (contract-call? t transfer u1 tx-sender 'SP000000000000000000002Q6VF78 .other-contract none))
+(define-constant LIST (list 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28))
+",
+            "(define-data-var counter uint u0)
+(define-public (increment (by uint))
+  (begin (var-set counter (+ (var-get counter) by)) (ok (var-get counter))))
+(define-read-only (get-owner (id uint))
+  (let ((owner (unwrap! (map-get? owners { id: id }) (err u404)))
+        (balance (stx-get-balance owner))
+        (name \"some \\\"quoted\\\" name\"))
+    (ok
+      {
+        owner: owner,
+        balance: balance,
+        name: name,
+        flags: 0x0102ff,
+        memo: u\"h\\u{e9}llo\"
+      })))
+(define-public (call (t <token-trait>))
+  (contract-call? t transfer u1 tx-sender 'SP000000000000000000002Q6VF78
+    .other-contract none))
+(define-constant LIST
+  (list 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27
+    28))
+",
+        );
+    }
+
+    #[test]
+    fn test_format_comments() {
+        assert_formats(
+            ";;;; Counter
+
+
+;; the count
+(define-data-var counter uint u0)   ;; starts at zero
+;;
+
+
+(define-public (reset)
+  (begin
+    ;; standalone
+    (var-set counter u0) ;; trailing
+    (ok true) ;; last
+  )
+)
+(define-constant TUPLE {
+  a: 1, ;; first
+  ;; about b
+  b: (list u1 u2)
+})
+",
+            ";;;; Counter
+
+;; the count
+(define-data-var counter uint u0) ;; starts at zero
+;;
+
+(define-public (reset)
+  (begin
+    ;; standalone
+    (var-set counter u0) ;; trailing
+    (ok true) ;; last
+  ))
+(define-constant TUPLE
+  {
+    a: 1, ;; first
+    ;; about b
+    b: (list u1 u2)
+  })
+",
+        );
+
+        // comments between a key and its value move ahead of the entry
+        assert_formats(
+            "(define-constant T { a: ;; one\n 1, b: 2 })",
+            "(define-constant T\n  {\n    ;; one\n    a: 1,\n    b: 2\n  })\n",
+        );
+    }
+
+    #[test]
+    fn test_format_errors() {
+        assert_eq!(format_source("").unwrap(), "");
+        assert_eq!(format_source("  \n\n").unwrap(), "");
+
+        let error = format_source("(define-constant x (+ 1 2)").unwrap_err();
+        assert!(error.contains("expected closing ')'"), "{}", error);
+    }
+}
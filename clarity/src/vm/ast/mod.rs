@@ -16,6 +16,7 @@
 
 pub mod definition_sorter;
 pub mod expression_identifier;
+pub mod format;
 pub mod parser;
 pub mod traits_resolver;
 
@@ -1656,7 +1656,7 @@ impl<'a, 'hooks> GlobalContext<'a, 'hooks> {
         &mut self,
         sender: PrincipalData,
         sponsor: Option<PrincipalData>,
-        contract_context: ContractContext,
+        contract_context: &ContractContext,
         f: F,
     ) -> std::result::Result<A, E>
     where
@@ -1671,7 +1671,7 @@ impl<'a, 'hooks> GlobalContext<'a, 'hooks> {
             let mut callstack = CallStack::new();
             let mut exec_env = Environment::new(
                 self,
-                &contract_context,
+                contract_context,
                 &mut callstack,
                 Some(sender.clone()),
                 Some(sender),
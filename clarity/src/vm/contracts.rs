@@ -26,7 +26,7 @@ use crate::vm::version::ClarityVersion;
 use crate::vm::{apply, eval_all, Value};
 use std::convert::TryInto;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Contract {
     pub contract_context: ContractContext,
 }
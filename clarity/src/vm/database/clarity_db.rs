@@ -16,6 +16,7 @@
 
 use std::collections::{HashMap, VecDeque};
 use std::convert::{TryFrom, TryInto};
+use std::sync::Arc;
 
 use crate::vm::analysis::{AnalysisDatabase, ContractAnalysis};
 use crate::vm::ast::ASTRules;
@@ -27,8 +28,8 @@ use crate::vm::database::structures::{
     DataVariableMetadata, FungibleTokenMetadata, NonFungibleTokenMetadata, STXBalance,
     STXBalanceSnapshot, SimmedBlock,
 };
-use crate::vm::database::ClarityBackingStore;
 use crate::vm::database::RollbackWrapper;
+use crate::vm::database::{ClarityBackingStore, ContractCacheKey};
 use crate::vm::errors::{
     CheckErrors, Error, IncomparableError, InterpreterError, InterpreterResult as Result,
     RuntimeErrorType,
@@ -652,15 +653,54 @@ impl<'a> ClarityDatabase<'a> {
     pub fn get_contract(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
-    ) -> Result<Contract> {
+    ) -> Result<Arc<Contract>> {
+        let epoch = self.get_clarity_epoch_version();
+        let cache_key = self.get_contract_cache_key(contract_identifier, epoch);
+        if let Some(ref cache_key) = cache_key {
+            if let Some(contract) = self
+                .store
+                .get_contract_cache()
+                .and_then(|cache| cache.get(cache_key))
+            {
+                return Ok(contract);
+            }
+        }
+
         let key = ClarityDatabase::make_metadata_key(StoreType::Contract, "contract");
-        let mut data: Contract = self.fetch_metadata(contract_identifier, &key)?.expect(
+        let serialized = self.store.get_metadata(contract_identifier, &key)?.expect(
             "Failed to read non-consensus contract metadata, even though contract exists in MARF.",
         );
-        data.canonicalize_types(&self.get_clarity_epoch_version());
+        let mut data = Contract::deserialize(&serialized);
+        data.canonicalize_types(&epoch);
+        let data = Arc::new(data);
+
+        if let Some(cache_key) = cache_key {
+            if let Some(cache) = self.store.get_contract_cache() {
+                cache.insert(cache_key, data.clone(), serialized.len() as u64);
+            }
+        }
         Ok(data)
     }
 
+    /// Contracts are only cached once the block that deployed them is committed, so this is None
+    ///  if the store has no cache or the contract was deployed in the open chain tip.
+    fn get_contract_cache_key(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        epoch: StacksEpochId,
+    ) -> Option<ContractCacheKey> {
+        self.store.get_contract_cache()?;
+        let (deployed_at, contract_hash) = self
+            .store
+            .get_committed_contract_hash(contract_identifier)?;
+        Some(ContractCacheKey {
+            contract_identifier: contract_identifier.clone(),
+            deployed_at,
+            contract_hash,
+            epoch,
+        })
+    }
+
     pub fn ustx_liquid_supply_key() -> &'static str {
         "_stx-data::ustx_liquid_supply"
     }
@@ -21,8 +21,8 @@ use rusqlite::Connection;
 
 use crate::vm::analysis::AnalysisDatabase;
 use crate::vm::database::{
-    BurnStateDB, ClarityDatabase, ClarityDeserializable, ClaritySerializable, HeadersDB,
-    SqliteConnection, NULL_BURN_STATE_DB, NULL_HEADER_DB,
+    BurnStateDB, ClarityDatabase, ClarityDeserializable, ClaritySerializable, ContractCache,
+    HeadersDB, SqliteConnection, NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
 use crate::vm::errors::{
     CheckErrors, IncomparableError, InterpreterError, InterpreterResult as Result,
@@ -92,6 +92,18 @@ pub trait ClarityBackingStore {
         None
     }
 
+    /// The cache of parsed contracts shared by every `ClarityDatabase` opened on this store, if
+    ///  it has one.
+    fn get_contract_cache(&self) -> Option<&ContractCache> {
+        None
+    }
+
+    /// Is the block committed, i.e. can the metadata stored in it never change again?  Only
+    ///  contracts in committed blocks may be cached.
+    fn is_committed_block(&mut self, _bhh: &StacksBlockId) -> bool {
+        false
+    }
+
     /// The contract commitment is the hash of the contract, plus the block height in
     ///   which the contract was initialized.
     fn make_contract_commitment(&mut self, contract_hash: Sha512Trunc256Sum) -> String {
@@ -124,6 +136,18 @@ pub trait ClarityBackingStore {
         Ok((bhh, contract_hash))
     }
 
+    /// Like `get_contract_hash()`, but None unless the contract's metadata is in a committed
+    ///  block (see `is_committed_block()`).  Stores with a contract cache should memoize this, so
+    ///  that cache hits don't need a MARF lookup.
+    fn get_committed_contract_hash(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> Option<(StacksBlockId, Sha512Trunc256Sum)> {
+        self.get_contract_hash(contract)
+            .ok()
+            .filter(|(bhh, _)| self.is_committed_block(bhh))
+    }
+
     fn insert_metadata(&mut self, contract: &QualifiedContractIdentifier, key: &str, value: &str) {
         let bhh = self.get_open_chain_tip();
         SqliteConnection::insert_metadata(
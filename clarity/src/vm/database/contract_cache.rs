@@ -0,0 +1,257 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::types::chainstate::StacksBlockId;
+use crate::types::StacksEpochId;
+use crate::vm::contracts::Contract;
+use crate::vm::types::QualifiedContractIdentifier;
+
+/// Default bound on the total serialized size of the contracts held in a `ContractCache`
+pub const DEFAULT_CONTRACT_CACHE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Identifies a parsed contract.  The block in which the contract's metadata was stored is an
+///  index block hash, so the same contract deployed in two different forks gets two different
+///  keys.  The epoch is included because `Contract::canonicalize_types` depends on it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContractCacheKey {
+    pub contract_identifier: QualifiedContractIdentifier,
+    pub deployed_at: StacksBlockId,
+    pub contract_hash: Sha512Trunc256Sum,
+    pub epoch: StacksEpochId,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// number of contracts currently cached
+    pub entries: u64,
+    /// total serialized size of the contracts currently cached
+    pub size: u64,
+}
+
+struct CacheEntry {
+    contract: Arc<Contract>,
+    size: u64,
+    last_used: u64,
+}
+
+struct ContractCacheInner {
+    entries: HashMap<ContractCacheKey, CacheEntry>,
+    /// `last_used` tick -> key, for finding the least-recently-used entry
+    lru: BTreeMap<u64, ContractCacheKey>,
+    next_tick: u64,
+    stats: ContractCacheStats,
+}
+
+///
+/// A bounded, least-recently-used cache of deserialized and canonicalized contracts, so that
+///  `ClarityDatabase::get_contract` doesn't need to re-parse a contract's JSON metadata on every
+///  call into it.  The cache is owned by the backing store's owner (e.g. `MarfedKV`), and shared
+///  by every `ClarityDatabase` opened on it.
+///
+/// Only contracts whose metadata is in a block that is no longer open for writing are cached,
+///  since that metadata can never change.
+///
+pub struct ContractCache {
+    max_size: u64,
+    inner: Mutex<ContractCacheInner>,
+}
+
+impl ContractCacheInner {
+    fn touch(&mut self, key: &ContractCacheKey) -> Option<&CacheEntry> {
+        let tick = self.next_tick;
+        let entry = self.entries.get_mut(key)?;
+        self.lru.remove(&entry.last_used);
+        self.lru.insert(tick, key.clone());
+        entry.last_used = tick;
+        self.next_tick += 1;
+        Some(entry)
+    }
+
+    fn evict_one(&mut self) -> bool {
+        let oldest = match self.lru.keys().next() {
+            Some(tick) => *tick,
+            None => return false,
+        };
+        let key = self.lru.remove(&oldest).expect("BUG: LRU tick disappeared");
+        let entry = self
+            .entries
+            .remove(&key)
+            .expect("BUG: LRU entry without cache entry");
+        self.stats.size -= entry.size;
+        self.stats.entries -= 1;
+        self.stats.evictions += 1;
+        true
+    }
+}
+
+impl ContractCache {
+    pub fn new(max_size: u64) -> ContractCache {
+        ContractCache {
+            max_size,
+            inner: Mutex::new(ContractCacheInner {
+                entries: HashMap::new(),
+                lru: BTreeMap::new(),
+                next_tick: 0,
+                stats: ContractCacheStats::default(),
+            }),
+        }
+    }
+
+    /// Look up a contract, returning a shared reference to it if present.
+    pub fn get(&self, key: &ContractCacheKey) -> Option<Arc<Contract>> {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("FATAL: contract cache lock poisoned");
+        let contract = inner.touch(key).map(|entry| entry.contract.clone());
+        if contract.is_some() {
+            inner.stats.hits += 1;
+        } else {
+            inner.stats.misses += 1;
+        }
+        contract
+    }
+
+    /// Cache a contract.  `size` is the length of its serialized metadata.  Contracts larger than
+    ///  the whole cache are not stored.
+    pub fn insert(&self, key: ContractCacheKey, contract: Arc<Contract>, size: u64) {
+        if size > self.max_size {
+            return;
+        }
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("FATAL: contract cache lock poisoned");
+        if inner.touch(&key).is_some() {
+            return;
+        }
+        while inner.stats.size + size > self.max_size {
+            if !inner.evict_one() {
+                break;
+            }
+        }
+        let tick = inner.next_tick;
+        inner.next_tick += 1;
+        inner.lru.insert(tick, key.clone());
+        inner.entries.insert(
+            key,
+            CacheEntry {
+                contract,
+                size,
+                last_used: tick,
+            },
+        );
+        inner.stats.size += size;
+        inner.stats.entries += 1;
+    }
+
+    pub fn stats(&self) -> ContractCacheStats {
+        self.inner
+            .lock()
+            .expect("FATAL: contract cache lock poisoned")
+            .stats
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::contexts::ContractContext;
+    use crate::vm::ClarityVersion;
+
+    fn make_key(name: &str, deployed_at: u8) -> ContractCacheKey {
+        let contract_identifier = QualifiedContractIdentifier::local(name).unwrap();
+        ContractCacheKey {
+            contract_identifier,
+            deployed_at: StacksBlockId([deployed_at; 32]),
+            contract_hash: Sha512Trunc256Sum::from_data(name.as_bytes()),
+            epoch: StacksEpochId::Epoch21,
+        }
+    }
+
+    fn make_contract(key: &ContractCacheKey) -> Arc<Contract> {
+        Arc::new(Contract {
+            contract_context: ContractContext::new(
+                key.contract_identifier.clone(),
+                ClarityVersion::Clarity2,
+            ),
+        })
+    }
+
+    #[test]
+    fn test_contract_cache_lru() {
+        let cache = ContractCache::new(100);
+        let a = make_key("a", 1);
+        let b = make_key("b", 1);
+        let c = make_key("c", 1);
+
+        assert!(cache.get(&a).is_none());
+        cache.insert(a.clone(), make_contract(&a), 40);
+        cache.insert(b.clone(), make_contract(&b), 40);
+        assert_eq!(
+            cache.get(&a).unwrap().contract_context.contract_identifier,
+            a.contract_identifier
+        );
+
+        // b is now the least-recently used, so it makes room for c
+        cache.insert(c.clone(), make_contract(&c), 40);
+        assert!(cache.get(&b).is_none());
+        assert!(cache.get(&a).is_some());
+        assert!(cache.get(&c).is_some());
+
+        // too big to ever fit
+        let d = make_key("d", 1);
+        cache.insert(d.clone(), make_contract(&d), 101);
+        assert!(cache.get(&d).is_none());
+
+        assert_eq!(
+            cache.stats(),
+            ContractCacheStats {
+                hits: 3,
+                misses: 3,
+                evictions: 1,
+                entries: 2,
+                size: 80,
+            }
+        );
+    }
+
+    #[test]
+    fn test_contract_cache_forks() {
+        let cache = ContractCache::new(100);
+        let fork_1 = make_key("a", 1);
+        let fork_2 = make_key("a", 2);
+        let contract = make_contract(&fork_1);
+        cache.insert(fork_1.clone(), contract.clone(), 10);
+        assert!(cache.get(&fork_2).is_none());
+        // hits share the cached contract rather than copying it
+        assert!(Arc::ptr_eq(&cache.get(&fork_1).unwrap(), &contract));
+
+        let mut other_epoch = fork_1.clone();
+        other_epoch.epoch = StacksEpochId::Epoch2_05;
+        assert!(cache.get(&other_epoch).is_none());
+        assert!(cache.get(&fork_1).is_some());
+    }
+}
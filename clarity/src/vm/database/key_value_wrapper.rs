@@ -26,7 +26,7 @@ use stacks_common::util::hash::Sha512Trunc256Sum;
 use crate::types::chainstate::StacksBlockId;
 
 use super::clarity_store::SpecialCaseHandler;
-use super::{ClarityBackingStore, ClarityDeserializable};
+use super::{ClarityBackingStore, ClarityDeserializable, ContractCache};
 
 #[cfg(rollback_value_check)]
 type RollbackValueCheck = String;
@@ -119,6 +119,8 @@ pub struct RollbackWrapper<'a> {
     //   this allows ~ O(1) lookups, and ~ O(1) commits, roll-backs (amortized by # of PUTs).
     lookup_map: HashMap<String, Vec<String>>,
     metadata_lookup_map: HashMap<(QualifiedContractIdentifier, String), Vec<String>>,
+    // number of keys in metadata_lookup_map for each contract
+    metadata_contracts: HashMap<QualifiedContractIdentifier, usize>,
     // stack keeps track of the most recent rollback context, which tells us which
     //   edits were performed by which context. at the moment, each context's edit history
     //   is a separate Vec which must be drained into the parent on commits, meaning that
@@ -137,6 +139,7 @@ pub struct RollbackWrapper<'a> {
 pub struct RollbackWrapperPersistedLog {
     lookup_map: HashMap<String, Vec<String>>,
     metadata_lookup_map: HashMap<(QualifiedContractIdentifier, String), Vec<String>>,
+    metadata_contracts: HashMap<QualifiedContractIdentifier, usize>,
     stack: Vec<RollbackContext>,
 }
 
@@ -145,6 +148,7 @@ impl From<RollbackWrapper<'_>> for RollbackWrapperPersistedLog {
         RollbackWrapperPersistedLog {
             lookup_map: o.lookup_map,
             metadata_lookup_map: o.metadata_lookup_map,
+            metadata_contracts: o.metadata_contracts,
             stack: o.stack,
         }
     }
@@ -155,6 +159,7 @@ impl RollbackWrapperPersistedLog {
         RollbackWrapperPersistedLog {
             lookup_map: HashMap::new(),
             metadata_lookup_map: HashMap::new(),
+            metadata_contracts: HashMap::new(),
             stack: Vec::new(),
         }
     }
@@ -196,6 +201,7 @@ impl<'a> RollbackWrapper<'a> {
             store,
             lookup_map: HashMap::new(),
             metadata_lookup_map: HashMap::new(),
+            metadata_contracts: HashMap::new(),
             stack: Vec::new(),
             query_pending_data: true,
         }
@@ -209,6 +215,7 @@ impl<'a> RollbackWrapper<'a> {
             store,
             lookup_map: log.lookup_map,
             metadata_lookup_map: log.metadata_lookup_map,
+            metadata_contracts: log.metadata_contracts,
             stack: log.stack,
             query_pending_data: true,
         }
@@ -218,6 +225,10 @@ impl<'a> RollbackWrapper<'a> {
         self.store.get_cc_special_cases_handler()
     }
 
+    pub fn get_contract_cache(&self) -> Option<&ContractCache> {
+        self.store.get_contract_cache()
+    }
+
     pub fn nest(&mut self) {
         self.stack.push(RollbackContext {
             edits: Vec::new(),
@@ -243,6 +254,17 @@ impl<'a> RollbackWrapper<'a> {
 
         for (key, value) in last_item.metadata_edits.drain(..) {
             rollback_lookup_map(&key, &value, &mut self.metadata_lookup_map);
+            if !self.metadata_lookup_map.contains_key(&key) {
+                let (contract, _) = key;
+                let remaining = self
+                    .metadata_contracts
+                    .get_mut(&contract)
+                    .expect("ERROR: Clarity VM had metadata entry, but no contract count");
+                *remaining -= 1;
+                if *remaining == 0 {
+                    self.metadata_contracts.remove(&contract);
+                }
+            }
         }
     }
 
@@ -267,6 +289,7 @@ impl<'a> RollbackWrapper<'a> {
                 last_item.metadata_edits,
                 &mut self.metadata_lookup_map,
             );
+            self.metadata_contracts.clear();
             if metadata_edits.len() > 0 {
                 self.store.put_all_metadata(metadata_edits);
             }
@@ -424,6 +447,9 @@ impl<'a> RollbackWrapper<'a> {
             .expect("ERROR: Clarity VM attempted PUT on non-nested context.");
 
         let metadata_key = (contract.clone(), key.to_string());
+        if !self.metadata_lookup_map.contains_key(&metadata_key) {
+            *self.metadata_contracts.entry(contract.clone()).or_insert(0) += 1;
+        }
 
         inner_put(
             &mut self.metadata_lookup_map,
@@ -501,6 +527,19 @@ impl<'a> RollbackWrapper<'a> {
         }
     }
 
+    /// Get the block in which a contract's metadata was committed, and the contract's hash.
+    ///  Returns None if the contract's metadata is still pending in this wrapper or in the open
+    ///  chain tip (i.e. it is being deployed right now), or if the contract does not exist.
+    pub fn get_committed_contract_hash(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> Option<(StacksBlockId, Sha512Trunc256Sum)> {
+        if self.metadata_contracts.contains_key(contract) {
+            return None;
+        }
+        self.store.get_committed_contract_hash(contract)
+    }
+
     pub fn has_metadata_entry(
         &mut self,
         contract: &QualifiedContractIdentifier,
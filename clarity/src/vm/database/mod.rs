@@ -22,6 +22,9 @@ pub use self::clarity_db::{
 };
 pub use self::clarity_store::MemoryBackingStore;
 pub use self::clarity_store::{ClarityBackingStore, SpecialCaseHandler};
+pub use self::contract_cache::{
+    ContractCache, ContractCacheKey, ContractCacheStats, DEFAULT_CONTRACT_CACHE_SIZE,
+};
 pub use self::key_value_wrapper::{RollbackWrapper, RollbackWrapperPersistedLog};
 pub use self::sqlite::SqliteConnection;
 pub use self::structures::{
@@ -31,6 +34,7 @@ pub use self::structures::{
 
 pub mod clarity_db;
 pub mod clarity_store;
+mod contract_cache;
 mod key_value_wrapper;
 mod sqlite;
 mod structures;
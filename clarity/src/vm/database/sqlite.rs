@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use rusqlite::types::{FromSql, ToSql};
+use rusqlite::types::{FromSql, ToSql, Value as SqliteValue};
 use rusqlite::{
     Connection, Error as SqliteError, ErrorCode as SqliteErrorCode, OptionalExtension, Row,
     Savepoint, NO_PARAMS,
@@ -24,7 +24,13 @@ use crate::types::chainstate::StacksBlockId;
 
 use stacks_common::util::db_common::tx_busy_handler;
 
+use crate::vm::analysis::{AnalysisDatabase, ContractAnalysis};
 use crate::vm::contracts::Contract;
+use crate::vm::database::structures::{
+    deserialize_metadata, metadata_from_blob, metadata_to_blob, serialize_metadata,
+    DataMapMetadata, DataVariableMetadata, FungibleTokenMetadata, NonFungibleTokenMetadata,
+};
+use crate::vm::database::{ClarityDatabase, StoreType};
 use crate::vm::errors::{
     Error, IncomparableError, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
 };
@@ -84,6 +90,7 @@ impl SqliteConnection {
         sqlite_get(conn, key)
     }
 
+    /// Binary metadata entries are stored as BLOBs; everything else as TEXT.
     pub fn insert_metadata(
         conn: &Connection,
         bhh: &StacksBlockId,
@@ -92,7 +99,11 @@ impl SqliteConnection {
         value: &str,
     ) {
         let key = format!("clr-meta::{}::{}", contract_hash, key);
-        let params: [&dyn ToSql; 3] = [&bhh, &key, &value];
+        let stored_value = match metadata_to_blob(value) {
+            Some(blob) => SqliteValue::Blob(blob),
+            None => SqliteValue::Text(value.to_string()),
+        };
+        let params: [&dyn ToSql; 3] = [&bhh, &key, &stored_value];
 
         if let Err(e) = conn.execute(
             "INSERT INTO metadata_table (blockhash, key, value) VALUES (?, ?, ?)",
@@ -144,7 +155,7 @@ impl SqliteConnection {
             )
             .optional()
         {
-            Ok(x) => x,
+            Ok(x) => x.map(metadata_from_sql),
             Err(e) => {
                 error!("Failed to query ({},{}): {:?}", &bhh, &key, &e);
                 panic!("{}", SQL_FAIL_MESSAGE);
@@ -155,6 +166,102 @@ impl SqliteConnection {
     pub fn has_entry(conn: &Connection, key: &str) -> bool {
         sqlite_has_entry(conn, key)
     }
+
+    /// Rewrite every metadata entry that is still in the legacy JSON encoding in the binary
+    ///  encoding.  Entries are rewritten in batches, each in its own transaction, so this can be
+    ///  interrupted and run again.  Returns the number of entries rewritten.
+    pub fn migrate_metadata_to_binary(conn: &mut Connection) -> Result<u64> {
+        let mut last_rowid: i64 = 0;
+        let mut migrated = 0;
+        loop {
+            let tx = conn.transaction().map_err(sqlite_error)?;
+            let rows = {
+                let mut stmt = tx
+                    .prepare(
+                        "SELECT rowid, key, value FROM metadata_table WHERE rowid > ? AND typeof(value) = 'text' ORDER BY rowid LIMIT ?",
+                    )
+                    .map_err(sqlite_error)?;
+                let params: [&dyn ToSql; 2] = [&last_rowid, &METADATA_MIGRATION_BATCH_SIZE];
+                let rows = stmt
+                    .query_map(&params, |row| {
+                        Ok((
+                            row.get::<_, i64>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, String>(2)?,
+                        ))
+                    })
+                    .map_err(sqlite_error)?;
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(sqlite_error)?
+            };
+            let last_row = match rows.last() {
+                Some((rowid, _, _)) => *rowid,
+                None => break,
+            };
+
+            for (rowid, key, value) in rows.iter() {
+                if let Some(reencoded) = reencode_legacy_metadata(key, value) {
+                    let blob = metadata_to_blob(&reencoded)
+                        .expect("BUG: re-encoded metadata is not binary");
+                    let params: [&dyn ToSql; 2] = [&blob, rowid];
+                    tx.execute(
+                        "UPDATE metadata_table SET value = ? WHERE rowid = ?",
+                        &params,
+                    )
+                    .map_err(sqlite_error)?;
+                    migrated += 1;
+                }
+            }
+            tx.commit().map_err(sqlite_error)?;
+            last_rowid = last_row;
+        }
+        Ok(migrated)
+    }
+}
+
+const METADATA_MIGRATION_BATCH_SIZE: i64 = 1000;
+
+/// Convert a stored metadata value back into its string form
+fn metadata_from_sql(value: SqliteValue) -> String {
+    match value {
+        SqliteValue::Text(s) => s,
+        SqliteValue::Blob(blob) => metadata_from_blob(&blob),
+        other => {
+            error!("Unexpected metadata value type: {:?}", &other);
+            panic!("{}", SQL_FAIL_MESSAGE);
+        }
+    }
+}
+
+fn sqlite_error(err: SqliteError) -> Error {
+    InterpreterError::SqliteError(IncomparableError { err }).into()
+}
+
+/// If this side-store entry is a legacy JSON entry of one of the metadata types that are now
+///  written in the binary encoding, re-encode it.
+fn reencode_legacy_metadata(key: &str, value: &str) -> Option<String> {
+    // side-store keys are `clr-meta::{contract}::{metadata key}`
+    let (_, metadata_key) = key.strip_prefix("clr-meta::")?.split_once("::")?;
+
+    let is_kind = |store_type: StoreType| {
+        metadata_key.starts_with(&ClarityDatabase::make_metadata_key(store_type, ""))
+    };
+    let reencoded = if metadata_key == AnalysisDatabase::storage_key() {
+        serialize_metadata(&deserialize_metadata::<ContractAnalysis>(value))
+    } else if metadata_key == ClarityDatabase::make_metadata_key(StoreType::Contract, "contract") {
+        serialize_metadata(&deserialize_metadata::<Contract>(value))
+    } else if is_kind(StoreType::DataMapMeta) {
+        serialize_metadata(&deserialize_metadata::<DataMapMetadata>(value))
+    } else if is_kind(StoreType::VariableMeta) {
+        serialize_metadata(&deserialize_metadata::<DataVariableMetadata>(value))
+    } else if is_kind(StoreType::FungibleTokenMeta) {
+        serialize_metadata(&deserialize_metadata::<FungibleTokenMetadata>(value))
+    } else if is_kind(StoreType::NonFungibleTokenMeta) {
+        serialize_metadata(&deserialize_metadata::<NonFungibleTokenMetadata>(value))
+    } else {
+        return None;
+    };
+    Some(reencoded)
 }
 
 impl SqliteConnection {
@@ -212,3 +319,78 @@ impl SqliteConnection {
         Ok(conn)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::contexts::ContractContext;
+    use crate::vm::database::structures::is_binary_metadata;
+    use crate::vm::types::{QualifiedContractIdentifier, TypeSignature};
+    use crate::vm::ClarityVersion;
+
+    #[test]
+    fn test_migrate_metadata_to_binary() {
+        let mut conn = SqliteConnection::memory().unwrap();
+        let bhh = StacksBlockId([1; 32]);
+        let contract_id = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = Contract {
+            contract_context: ContractContext::new(contract_id.clone(), ClarityVersion::Clarity2),
+        };
+        let var_meta = DataVariableMetadata {
+            value_type: TypeSignature::UIntType,
+        };
+        let contract_key = ClarityDatabase::make_metadata_key(StoreType::Contract, "contract");
+        let var_key = ClarityDatabase::make_metadata_key(StoreType::VariableMeta, "bar");
+        let size_key = ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-size");
+
+        // legacy rows
+        for (key, value) in [
+            (&contract_key, serde_json::to_string(&contract).unwrap()),
+            (&var_key, serde_json::to_string(&var_meta).unwrap()),
+            (&size_key, "123".to_string()),
+        ] {
+            SqliteConnection::insert_metadata(&conn, &bhh, &contract_id.to_string(), key, &value);
+        }
+
+        assert_eq!(
+            SqliteConnection::migrate_metadata_to_binary(&mut conn).unwrap(),
+            2
+        );
+        let get = |key: &str| {
+            SqliteConnection::get_metadata(&conn, &bhh, &contract_id.to_string(), key).unwrap()
+        };
+        let migrated_contract = get(&contract_key);
+        assert!(is_binary_metadata(&migrated_contract));
+        assert_eq!(
+            deserialize_metadata::<Contract>(&migrated_contract)
+                .contract_context
+                .contract_identifier,
+            contract_id
+        );
+        assert_eq!(
+            deserialize_metadata::<DataVariableMetadata>(&get(&var_key)),
+            var_meta
+        );
+        assert_eq!(get(&size_key), "123");
+
+        // binary entries are stored as BLOBs
+        let stored_type = |key: &str| -> String {
+            let key = format!("clr-meta::{}::{}", &contract_id, key);
+            conn.query_row(
+                "SELECT typeof(value) FROM metadata_table WHERE key = ?",
+                &[&key],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(stored_type(&contract_key), "blob");
+        assert_eq!(stored_type(&var_key), "blob");
+        assert_eq!(stored_type(&size_key), "text");
+
+        // nothing left to do
+        assert_eq!(
+            SqliteConnection::migrate_metadata_to_binary(&mut conn).unwrap(),
+            0
+        );
+    }
+}
@@ -23,7 +23,8 @@ use crate::vm::errors::{
 use crate::vm::types::{
     OptionalData, PrincipalData, TupleTypeSignature, TypeSignature, Value, NONE,
 };
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use stacks_common::util::hash::{hex_bytes, to_hex};
 use std::convert::TryInto;
 use std::io::Write;
@@ -57,14 +58,107 @@ macro_rules! clarity_serializable {
         }
         impl ClarityDeserializable<$Name> for $Name {
             fn deserialize(json: &str) -> Self {
-                let mut deserializer = serde_json::Deserializer::from_str(&json);
-                // serde's default 128 depth limit can be exhausted
-                //  by a 64-stack-depth AST, so disable the recursion limit
-                deserializer.disable_recursion_limit();
-                // use stacker to prevent the deserializer from overflowing.
-                //  this will instead spill to the heap
-                let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
-                Deserialize::deserialize(deserializer).expect("Failed to deserialize vm.Value")
+                deserialize_json(json)
+            }
+        }
+    };
+}
+
+/// Prefix of binary metadata entries as they pass through the (string-valued) metadata store
+///  interface.  The rest of the entry is the hex-encoded binary entry, which the SQLite side
+///  store writes as a BLOB (see `metadata_to_blob()`).
+pub const BINARY_METADATA_PREFIX: &str = "bin:";
+
+/// Version of the binary metadata encoding; the first byte of each binary entry.  To be bumped if
+///  the binary layout of any metadata type changes.
+pub const BINARY_METADATA_VERSION: u8 = 1;
+
+/// Layout of the AST types in binary metadata entries; the second byte of each binary entry.
+///  With `developer-mode`, every expression carries a source span, which bincode (unlike JSON)
+///  cannot skip over, so entries written with and without it are not interchangeable.
+#[cfg(feature = "developer-mode")]
+pub const BINARY_METADATA_LAYOUT: u8 = 1;
+#[cfg(not(feature = "developer-mode"))]
+pub const BINARY_METADATA_LAYOUT: u8 = 0;
+
+/// Is this metadata entry in the binary encoding (as opposed to legacy JSON)?
+pub fn is_binary_metadata(serialized: &str) -> bool {
+    serialized.starts_with(BINARY_METADATA_PREFIX)
+}
+
+/// Get the bytes to store for a binary metadata entry, or None if it is a JSON entry.
+pub fn metadata_to_blob(serialized: &str) -> Option<Vec<u8>> {
+    let hex = serialized.strip_prefix(BINARY_METADATA_PREFIX)?;
+    Some(hex_bytes(hex).expect("Failed to decode binary Clarity metadata"))
+}
+
+/// Inverse of `metadata_to_blob()`
+pub fn metadata_from_blob(blob: &[u8]) -> String {
+    format!("{}{}", BINARY_METADATA_PREFIX, to_hex(blob))
+}
+
+fn metadata_bincode_options() -> impl bincode::Options {
+    bincode::DefaultOptions::new()
+}
+
+/// Serialize a non-consensus metadata entry (stored in the side store, not the MARF) in the
+///  versioned binary encoding.
+pub fn serialize_metadata<T: Serialize>(value: &T) -> String {
+    let mut bytes = vec![BINARY_METADATA_VERSION, BINARY_METADATA_LAYOUT];
+    bincode::Options::serialize_into(metadata_bincode_options(), &mut bytes, value)
+        .expect("Failed to serialize Clarity metadata");
+    metadata_from_blob(&bytes)
+}
+
+/// Deserialize a metadata entry written by `serialize_metadata`, or a legacy JSON entry.
+pub fn deserialize_metadata<T: DeserializeOwned>(serialized: &str) -> T {
+    let bytes = match metadata_to_blob(serialized) {
+        Some(bytes) => bytes,
+        None => {
+            return deserialize_json(serialized);
+        }
+    };
+    match bytes.get(0..2) {
+        Some(&[BINARY_METADATA_VERSION, BINARY_METADATA_LAYOUT]) => {}
+        Some(&[BINARY_METADATA_VERSION, layout]) => {
+            panic!(
+                "Clarity metadata was written with AST layout {}, but this build uses layout {}: it must be built with the same `developer-mode` setting as the node that wrote it",
+                layout, BINARY_METADATA_LAYOUT
+            );
+        }
+        _ => {
+            panic!("Unsupported binary Clarity metadata version");
+        }
+    }
+    let mut deserializer =
+        bincode::Deserializer::from_slice(&bytes[2..], metadata_bincode_options());
+    // like JSON below, spill deep ASTs to the heap rather than overflowing the stack
+    let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
+    Deserialize::deserialize(deserializer).expect("Failed to deserialize Clarity metadata")
+}
+
+fn deserialize_json<T: DeserializeOwned>(json: &str) -> T {
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    // serde's default 128 depth limit can be exhausted
+    //  by a 64-stack-depth AST, so disable the recursion limit
+    deserializer.disable_recursion_limit();
+    // use stacker to prevent the deserializer from overflowing.
+    //  this will instead spill to the heap
+    let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
+    Deserialize::deserialize(deserializer).expect("Failed to deserialize vm.Value")
+}
+
+/// Metadata types are written in the binary encoding, but may be read from either encoding.
+macro_rules! clarity_metadata_serializable {
+    ($Name:ident) => {
+        impl ClaritySerializable for $Name {
+            fn serialize(&self) -> String {
+                serialize_metadata(self)
+            }
+        }
+        impl ClarityDeserializable<$Name> for $Name {
+            fn deserialize(serialized: &str) -> Self {
+                deserialize_metadata(serialized)
             }
         }
     };
@@ -75,14 +169,14 @@ pub struct FungibleTokenMetadata {
     pub total_supply: Option<u128>,
 }
 
-clarity_serializable!(FungibleTokenMetadata);
+clarity_metadata_serializable!(FungibleTokenMetadata);
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NonFungibleTokenMetadata {
     pub key_type: TypeSignature,
 }
 
-clarity_serializable!(NonFungibleTokenMetadata);
+clarity_metadata_serializable!(NonFungibleTokenMetadata);
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DataMapMetadata {
@@ -90,21 +184,21 @@ pub struct DataMapMetadata {
     pub value_type: TypeSignature,
 }
 
-clarity_serializable!(DataMapMetadata);
+clarity_metadata_serializable!(DataMapMetadata);
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DataVariableMetadata {
     pub value_type: TypeSignature,
 }
 
-clarity_serializable!(DataVariableMetadata);
+clarity_metadata_serializable!(DataVariableMetadata);
 
 #[derive(Serialize, Deserialize)]
 pub struct ContractMetadata {
     pub contract: Contract,
 }
 
-clarity_serializable!(ContractMetadata);
+clarity_metadata_serializable!(ContractMetadata);
 
 #[derive(Serialize, Deserialize)]
 pub struct SimmedBlock {
@@ -121,8 +215,8 @@ clarity_serializable!(PrincipalData);
 clarity_serializable!(i128);
 clarity_serializable!(u128);
 clarity_serializable!(u64);
-clarity_serializable!(Contract);
-clarity_serializable!(ContractAnalysis);
+clarity_metadata_serializable!(Contract);
+clarity_metadata_serializable!(ContractAnalysis);
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum STXBalance {
@@ -852,3 +946,94 @@ impl STXBalance {
         self.get_available_balance_at_burn_block(burn_block_height, v1_unlock_height) >= amount
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::StacksEpochId;
+    use crate::vm::ast::ASTRules;
+    use crate::vm::contexts::OwnedEnvironment;
+    use crate::vm::database::{MemoryBackingStore, StoreType};
+    use crate::vm::types::QualifiedContractIdentifier;
+
+    #[test]
+    fn test_binary_metadata() {
+        let contract_id = QualifiedContractIdentifier::local("big").unwrap();
+        let contract_src = "(define-constant big u340282366920938463463374607431768211455)
+            (define-data-var small int -170141183460469231731687303715884105728)
+            (define-map balances principal (buff 10))
+            (define-fungible-token ft u100)
+            (define-read-only (get-big) (let ((x big)) (if (> x u0) (ok x) (err small))))";
+
+        let mut store = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), StacksEpochId::Epoch21);
+        owned_env
+            .initialize_contract(
+                contract_id.clone(),
+                contract_src,
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+        drop(owned_env);
+
+        let mut db = store.as_clarity_db();
+        db.begin();
+        let key = ClarityDatabase::make_metadata_key(StoreType::Contract, "contract");
+        let serialized = db.store.get_metadata(&contract_id, &key).unwrap().unwrap();
+        assert!(is_binary_metadata(&serialized));
+
+        let contract: Contract = deserialize_metadata(&serialized);
+        assert_eq!(
+            contract.contract_context.variables.get("big").unwrap(),
+            &Value::UInt(u128::MAX)
+        );
+        assert!(contract.contract_context.functions.get("get-big").is_some());
+
+        // legacy JSON entries are still readable
+        let json = serde_json::to_string(&contract).unwrap();
+        assert!(serialized.len() < json.len());
+        let from_json: Contract = deserialize_metadata(&json);
+        assert_eq!(
+            from_json.contract_context.variables,
+            contract.contract_context.variables
+        );
+
+        let key = ClarityDatabase::make_metadata_key(StoreType::VariableMeta, "small");
+        let serialized = db.store.get_metadata(&contract_id, &key).unwrap().unwrap();
+        assert!(is_binary_metadata(&serialized));
+        let var_meta: DataVariableMetadata = deserialize_metadata(&serialized);
+        assert_eq!(
+            deserialize_metadata::<DataVariableMetadata>(
+                &serde_json::to_string(&var_meta).unwrap()
+            ),
+            var_meta
+        );
+
+        let ft_meta = FungibleTokenMetadata {
+            total_supply: Some(u128::MAX),
+        };
+        let serialized = serialize_metadata(&ft_meta);
+        assert_eq!(
+            deserialize_metadata::<FungibleTokenMetadata>(&serialized),
+            ft_meta
+        );
+
+        // entries are tagged with the encoding version and AST layout
+        let blob = metadata_to_blob(&serialized).unwrap();
+        assert_eq!(blob[0], BINARY_METADATA_VERSION);
+        assert_eq!(blob[1], BINARY_METADATA_LAYOUT);
+        assert_eq!(metadata_from_blob(&blob), serialized);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be built with the same `developer-mode` setting")]
+    fn test_binary_metadata_layout_mismatch() {
+        let mut blob = metadata_to_blob(&serialize_metadata(&DataVariableMetadata {
+            value_type: TypeSignature::IntType,
+        }))
+        .unwrap();
+        blob[1] = 1 - BINARY_METADATA_LAYOUT;
+        deserialize_metadata::<DataVariableMetadata>(&metadata_from_blob(&blob));
+    }
+}
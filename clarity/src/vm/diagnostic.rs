@@ -43,6 +43,16 @@ pub struct Diagnostic {
 }
 
 impl Diagnostic {
+    /// Make a diagnostic at whatever level `error` reports for itself
+    pub fn new(error: &dyn DiagnosableError) -> Diagnostic {
+        Diagnostic {
+            spans: vec![],
+            level: error.level(),
+            message: error.message(),
+            suggestion: error.suggestion(),
+        }
+    }
+
     pub fn err(error: &dyn DiagnosableError) -> Diagnostic {
         Diagnostic {
             spans: vec![],
@@ -111,7 +111,7 @@ pub fn special_contract_call(
                         .map_err(|_e| {
                             CheckErrors::NoSuchContract(trait_data.contract_identifier.to_string())
                         })?;
-                    let contract_context_to_check = contract_to_check.contract_context;
+                    let contract_context_to_check = &contract_to_check.contract_context;
 
                     // This error case indicates a bad implementation. Only traits should be
                     // added to callable_contracts.
@@ -140,7 +140,7 @@ pub fn special_contract_call(
                                 )
                             })?;
                         let contract_context_defining_trait =
-                            contract_defining_trait.contract_context;
+                            &contract_defining_trait.contract_context;
 
                         // Retrieve the function that will be invoked
                         let function_to_check = contract_context_to_check
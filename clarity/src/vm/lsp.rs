@@ -0,0 +1,906 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A Language Server Protocol server for Clarity contracts.
+//!
+//! Documents are kept in memory as the editor sends them.  Each time a document is opened or
+//! saved it is parsed and type-checked on its own, against an empty analysis database, and the
+//! resulting diagnostics (including lint warnings) are published back to the editor.  Hover and
+//! go-to-definition answer from the last version of the document that parsed or type-checked
+//! successfully, so they keep working while the buffer holds a half-typed edit.
+//!
+//! Contracts that call into other contracts will report those calls as unresolved, since the
+//! server only ever sees one contract at a time.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use serde_json::json;
+use serde_json::Value as JsonValue;
+
+use crate::vm::analysis::lint::Linter;
+use crate::vm::analysis::{self, ContractAnalysis};
+use crate::vm::ast::build_ast_with_diagnostics;
+use crate::vm::costs::LimitedCostTracker;
+use crate::vm::database::MemoryBackingStore;
+use crate::vm::diagnostic::{Diagnostic, Level};
+use crate::vm::docs::{make_api_reference, make_define_reference, make_keyword_reference};
+use crate::vm::functions::define::DefineFunctions;
+use crate::vm::functions::NativeFunctions;
+use crate::vm::representations::Span;
+use crate::vm::types::{FunctionType, QualifiedContractIdentifier};
+use crate::vm::variables::NativeVariables;
+use crate::vm::{ClarityName, ClarityVersion, SymbolicExpression};
+use stacks_common::types::StacksEpochId;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// LSP completion item kinds
+const COMPLETION_FUNCTION: u32 = 3;
+const COMPLETION_VARIABLE: u32 = 6;
+const COMPLETION_INTERFACE: u32 = 8;
+const COMPLETION_KEYWORD: u32 = 14;
+const COMPLETION_CONSTANT: u32 = 21;
+
+struct Document {
+    text: String,
+    /// top-level expressions of the last version of the text that parsed
+    expressions: Vec<SymbolicExpression>,
+    /// analysis of the last version of the text that type-checked
+    analysis: Option<ContractAnalysis>,
+}
+
+pub struct LanguageServer {
+    documents: HashMap<String, Document>,
+    clarity_version: ClarityVersion,
+    epoch: StacksEpochId,
+    shutdown_requested: bool,
+    exit_code: Option<i32>,
+}
+
+impl Default for LanguageServer {
+    fn default() -> LanguageServer {
+        LanguageServer::new()
+    }
+}
+
+impl LanguageServer {
+    pub fn new() -> LanguageServer {
+        LanguageServer {
+            documents: HashMap::new(),
+            clarity_version: ClarityVersion::latest(),
+            epoch: StacksEpochId::latest(),
+            shutdown_requested: false,
+            exit_code: None,
+        }
+    }
+
+    /// Once the client has sent `exit`, the code the process should exit with: 0 if a
+    /// `shutdown` request came first, 1 otherwise.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Handle one decoded JSON-RPC message, returning the messages to send back: the response to
+    /// a request, or any notifications (i.e. diagnostics) a notification produced.
+    pub fn handle_message(&mut self, message: &JsonValue) -> Vec<JsonValue> {
+        let method = match message.get("method").and_then(|m| m.as_str()) {
+            Some(method) => method,
+            None => {
+                // the server never sends requests, so there are no responses to expect
+                return match message.get("id") {
+                    Some(id)
+                        if message.get("result").is_none() && message.get("error").is_none() =>
+                    {
+                        vec![error_response(id, INVALID_REQUEST, "Missing method")]
+                    }
+                    _ => vec![],
+                };
+            }
+        };
+        let params = message.get("params").cloned().unwrap_or(JsonValue::Null);
+
+        match message.get("id") {
+            Some(id) => match self.handle_request(method, &params) {
+                Ok(result) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })],
+                Err((code, error)) => vec![error_response(id, code, &error)],
+            },
+            None => self.handle_notification(method, &params),
+        }
+    }
+
+    fn handle_request(
+        &mut self,
+        method: &str,
+        params: &JsonValue,
+    ) -> Result<JsonValue, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        // full document sync
+                        "change": 1,
+                        "save": { "includeText": true },
+                    },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": {},
+                },
+                "serverInfo": {
+                    "name": "clarity-lsp",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(JsonValue::Null)
+            }
+            "textDocument/hover" => Ok(self.hover(params)),
+            "textDocument/definition" => Ok(self.definition(params)),
+            "textDocument/completion" => Ok(self.completion(params)),
+            _ => Err((METHOD_NOT_FOUND, format!("Unhandled method: {}", method))),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &JsonValue) -> Vec<JsonValue> {
+        let uri = params["textDocument"]["uri"].as_str();
+        match method {
+            "textDocument/didOpen" => {
+                if let (Some(uri), Some(text)) = (uri, params["textDocument"]["text"].as_str()) {
+                    return vec![self.check_document(uri, text.to_string())];
+                }
+            }
+            "textDocument/didChange" => {
+                // with full document sync, the last change carries the whole text
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    if let Some(document) = self.documents.get_mut(uri) {
+                        document.text = text.to_string();
+                    }
+                }
+            }
+            "textDocument/didSave" => {
+                if let Some(uri) = uri {
+                    let text = match params["text"].as_str() {
+                        Some(text) => Some(text.to_string()),
+                        None => self.documents.get(uri).map(|d| d.text.clone()),
+                    };
+                    if let Some(text) = text {
+                        return vec![self.check_document(uri, text)];
+                    }
+                }
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = uri {
+                    self.documents.remove(uri);
+                    return vec![publish_diagnostics(uri, &[])];
+                }
+            }
+            "exit" => {
+                self.exit_code = Some(if self.shutdown_requested { 0 } else { 1 });
+            }
+            // `initialized`, `$/cancelRequest`, and anything else we don't act upon
+            _ => {}
+        }
+        vec![]
+    }
+
+    /// Parse and type-check `text` as the new contents of `uri`, returning the
+    /// `publishDiagnostics` notification for it.
+    fn check_document(&mut self, uri: &str, text: String) -> JsonValue {
+        let contract_identifier = contract_identifier_for_uri(uri);
+        let (ast, mut diagnostics, success) = build_ast_with_diagnostics(
+            &contract_identifier,
+            &text,
+            &mut LimitedCostTracker::new_free(),
+            self.clarity_version,
+            self.epoch,
+        );
+
+        let mut analysis = None;
+        if success {
+            let mut expressions = ast.expressions.clone();
+            let mut store = MemoryBackingStore::new();
+            match analysis::run_analysis(
+                &contract_identifier,
+                &mut expressions,
+                &mut store.as_analysis_db(),
+                false,
+                LimitedCostTracker::new_free(),
+                self.epoch,
+                self.clarity_version,
+            ) {
+                Ok(contract_analysis) => {
+                    diagnostics.extend(Linter::run(&contract_analysis));
+                    analysis = Some(contract_analysis);
+                }
+                Err((e, _)) => diagnostics.push(e.diagnostic),
+            }
+        }
+
+        let document = self
+            .documents
+            .entry(uri.to_string())
+            .or_insert_with(|| Document {
+                text: String::new(),
+                expressions: vec![],
+                analysis: None,
+            });
+        document.text = text;
+        if success {
+            document.expressions = ast.expressions;
+        }
+        if analysis.is_some() {
+            document.analysis = analysis;
+        }
+
+        publish_diagnostics(uri, &diagnostics)
+    }
+
+    /// Look up the document and 1-based line and column named by a `TextDocumentPositionParams`
+    fn document_position<'a>(
+        &'a self,
+        params: &'a JsonValue,
+    ) -> Option<(&'a str, &'a Document, u32, u32)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let document = self.documents.get(uri)?;
+        let (line, column) = from_lsp_position(&params["position"])?;
+        Some((uri, document, line, column))
+    }
+
+    fn hover(&self, params: &JsonValue) -> JsonValue {
+        let (_, document, line, column) = match self.document_position(params) {
+            Some(x) => x,
+            None => return JsonValue::Null,
+        };
+        // the type map is keyed by expression id, so search the analyzed expressions if we can
+        let expressions = match document.analysis {
+            Some(ref analysis) => &analysis.expressions,
+            None => &document.expressions,
+        };
+        let expr = match expression_at(expressions, line, column) {
+            Some(expr) => expr,
+            None => return JsonValue::Null,
+        };
+        match self.describe(document, expr) {
+            Some(contents) => json!({
+                "contents": { "kind": "markdown", "value": contents },
+                "range": span_to_range(&expr.span),
+            }),
+            None => JsonValue::Null,
+        }
+    }
+
+    /// Markdown describing `expr`: the docs of a native function, define or keyword, the
+    /// signature of something the contract defines, or else the type the checker inferred.
+    fn describe(&self, document: &Document, expr: &SymbolicExpression) -> Option<String> {
+        if let Some(name) = expr.match_atom() {
+            if let Some(native) =
+                NativeFunctions::lookup_by_name_at_version(name, &self.clarity_version)
+            {
+                let api = make_api_reference(&native);
+                return Some(format!(
+                    "```clarity\n{}\n```\n\n{}",
+                    api.signature, api.description
+                ));
+            }
+            if let Some(define) = DefineFunctions::lookup_by_name(name) {
+                let api = make_define_reference(&define);
+                return Some(format!(
+                    "```clarity\n{}\n```\n\n{}",
+                    api.signature, api.description
+                ));
+            }
+            if let Some(keyword) =
+                NativeVariables::lookup_by_name_at_version(name, &self.clarity_version)
+                    .and_then(|variable| make_keyword_reference(&variable))
+            {
+                return Some(format!(
+                    "```clarity\n{}\n```\n\n{}",
+                    keyword.output_type, keyword.description
+                ));
+            }
+            if let Some(defined) = document
+                .analysis
+                .as_ref()
+                .and_then(|analysis| describe_definition(analysis, name))
+            {
+                return Some(defined);
+            }
+        }
+
+        let type_signature = document
+            .analysis
+            .as_ref()?
+            .type_map
+            .as_ref()?
+            .get_type(expr)?;
+        Some(format!("```clarity\n{}\n```", type_signature))
+    }
+
+    fn definition(&self, params: &JsonValue) -> JsonValue {
+        let (uri, document, line, column) = match self.document_position(params) {
+            Some(x) => x,
+            None => return JsonValue::Null,
+        };
+        let name = match expression_at(&document.expressions, line, column)
+            .and_then(|expr| expr.match_atom())
+        {
+            Some(name) => name,
+            None => return JsonValue::Null,
+        };
+        definitions(&document.expressions)
+            .into_iter()
+            .find(|(_, defined, _)| *defined == name)
+            .map(|(_, _, name_expr)| json!({ "uri": uri, "range": span_to_range(&name_expr.span) }))
+            .unwrap_or(JsonValue::Null)
+    }
+
+    fn completion(&self, params: &JsonValue) -> JsonValue {
+        let mut items = vec![];
+        for native in NativeFunctions::ALL.iter() {
+            if native.get_version() > self.clarity_version {
+                continue;
+            }
+            let api = make_api_reference(native);
+            items.push(json!({
+                "label": api.name,
+                "kind": COMPLETION_FUNCTION,
+                "detail": api.signature,
+                "documentation": { "kind": "markdown", "value": api.description },
+            }));
+        }
+        for define in DefineFunctions::ALL.iter() {
+            let api = make_define_reference(define);
+            items.push(json!({
+                "label": api.name,
+                "kind": COMPLETION_KEYWORD,
+                "detail": api.signature,
+                "documentation": { "kind": "markdown", "value": api.description },
+            }));
+        }
+        for variable in NativeVariables::ALL.iter() {
+            if variable.get_version() > self.clarity_version {
+                continue;
+            }
+            if let Some(keyword) = make_keyword_reference(variable) {
+                items.push(json!({
+                    "label": keyword.name,
+                    "kind": COMPLETION_KEYWORD,
+                    "detail": keyword.output_type,
+                    "documentation": { "kind": "markdown", "value": keyword.description },
+                }));
+            }
+        }
+
+        let document = params["textDocument"]["uri"]
+            .as_str()
+            .and_then(|uri| self.documents.get(uri));
+        if let Some(document) = document {
+            for (define_type, name, _) in definitions(&document.expressions) {
+                let kind = match define_type {
+                    DefineFunctions::PublicFunction
+                    | DefineFunctions::ReadOnlyFunction
+                    | DefineFunctions::PrivateFunction => COMPLETION_FUNCTION,
+                    DefineFunctions::Constant => COMPLETION_CONSTANT,
+                    DefineFunctions::Trait | DefineFunctions::UseTrait => COMPLETION_INTERFACE,
+                    _ => COMPLETION_VARIABLE,
+                };
+                items.push(json!({
+                    "label": name.as_str(),
+                    "kind": kind,
+                    "detail": define_type.get_name(),
+                }));
+            }
+        }
+
+        JsonValue::Array(items)
+    }
+}
+
+/// Serve the client on the other end of `input` and `output` until it sends `exit` or closes
+/// the stream.  Returns the code the process should exit with.
+pub fn run<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<i32> {
+    let mut server = LanguageServer::new();
+    while let Some(body) = read_message(&mut input)? {
+        let replies = match serde_json::from_slice::<JsonValue>(&body) {
+            Ok(message) => server.handle_message(&message),
+            Err(e) => vec![error_response(
+                &JsonValue::Null,
+                PARSE_ERROR,
+                &format!("Failed to parse message: {}", e),
+            )],
+        };
+        for reply in replies.iter() {
+            write_message(&mut output, reply)?;
+        }
+        if let Some(exit_code) = server.exit_code() {
+            return Ok(exit_code);
+        }
+    }
+    // the client went away without asking us to exit
+    Ok(1)
+}
+
+/// Read the body of the next `Content-Length`-framed message, or `None` at the end of input
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            match content_length {
+                Some(length) => {
+                    let mut body = vec![0u8; length];
+                    input.read_exact(&mut body)?;
+                    return Ok(Some(body));
+                }
+                // tolerate stray blank lines between messages
+                None => continue,
+            }
+        }
+        if let Some((header, value)) = line.split_once(':') {
+            if header.trim().eq_ignore_ascii_case("Content-Length") {
+                let length = value.trim().parse::<usize>().map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Bad Content-Length: {}", e),
+                    )
+                })?;
+                content_length = Some(length);
+            }
+        }
+    }
+}
+
+pub fn write_message<W: Write>(output: &mut W, message: &JsonValue) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn error_response(id: &JsonValue, code: i64, message: &str) -> JsonValue {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: &[Diagnostic]) -> JsonValue {
+    let diagnostics: Vec<JsonValue> = diagnostics.iter().map(diagnostic_to_lsp).collect();
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn diagnostic_to_lsp(diagnostic: &Diagnostic) -> JsonValue {
+    let range = match diagnostic.spans.first() {
+        Some(span) => span_to_range(span),
+        None => span_to_range(&Span::zero()),
+    };
+    let severity = match diagnostic.level {
+        Level::Error => 1,
+        Level::Warning => 2,
+        Level::Note => 3,
+    };
+    let message = match diagnostic.suggestion {
+        Some(ref suggestion) => format!("{}\n{}", diagnostic.message, suggestion),
+        None => diagnostic.message.clone(),
+    };
+    json!({
+        "range": range,
+        "severity": severity,
+        "source": "clarity",
+        "message": message,
+    })
+}
+
+/// Convert a span (1-based lines and columns, inclusive end) to an LSP range (0-based,
+/// exclusive end)
+fn span_to_range(span: &Span) -> JsonValue {
+    json!({
+        "start": to_lsp_position(span.start_line, span.start_column),
+        "end": to_lsp_position(span.end_line, span.end_column.saturating_add(1)),
+    })
+}
+
+/// Convert a 1-based line and column to an LSP position (0-based line and character)
+fn to_lsp_position(line: u32, column: u32) -> JsonValue {
+    json!({
+        "line": line.saturating_sub(1),
+        "character": column.saturating_sub(1),
+    })
+}
+
+/// Convert an LSP position (0-based line and character) to a 1-based line and column
+fn from_lsp_position(position: &JsonValue) -> Option<(u32, u32)> {
+    let line = u32::try_from(position["line"].as_u64()?).ok()?;
+    let character = u32::try_from(position["character"].as_u64()?).ok()?;
+    Some((line.checked_add(1)?, character.checked_add(1)?))
+}
+
+/// Name the contract after the file, falling back to a placeholder if the file name isn't a
+/// valid contract name
+fn contract_identifier_for_uri(uri: &str) -> QualifiedContractIdentifier {
+    let file_name = uri.rsplit('/').next().unwrap_or(uri);
+    let name = file_name.strip_suffix(".clar").unwrap_or(file_name);
+    QualifiedContractIdentifier::local(name)
+        .ok()
+        .or_else(|| QualifiedContractIdentifier::local("contract").ok())
+        .expect("FATAL: failed to construct a contract identifier")
+}
+
+fn span_contains(span: &Span, line: u32, column: u32) -> bool {
+    span.start_line != 0
+        && (span.start_line, span.start_column) <= (line, column)
+        && (line, column) <= (span.end_line, span.end_column)
+}
+
+/// The innermost expression covering the 1-based `line` and `column`
+fn expression_at(
+    expressions: &[SymbolicExpression],
+    line: u32,
+    column: u32,
+) -> Option<&SymbolicExpression> {
+    let expr = expressions
+        .iter()
+        .find(|expr| span_contains(&expr.span, line, column))?;
+    match expr.match_list() {
+        Some(children) => expression_at(children, line, column).or(Some(expr)),
+        None => Some(expr),
+    }
+}
+
+/// Every name defined at the top level of a contract, with the kind of define and the atom
+/// that names it
+fn definitions(
+    expressions: &[SymbolicExpression],
+) -> Vec<(DefineFunctions, &ClarityName, &SymbolicExpression)> {
+    expressions
+        .iter()
+        .filter_map(|expr| {
+            let (define_type, args) = DefineFunctions::try_parse(expr)?;
+            let name_expr = args.first()?;
+            // functions are named by the head of their signature
+            let name_expr = match name_expr.match_list() {
+                Some(signature) => signature.first()?,
+                None => name_expr,
+            };
+            let name = name_expr.match_atom()?;
+            Some((define_type, name, name_expr))
+        })
+        .collect()
+}
+
+/// Markdown for something `analysis` says the contract defines as `name`
+fn describe_definition(analysis: &ContractAnalysis, name: &ClarityName) -> Option<String> {
+    let functions = [
+        ("define-public", &analysis.public_function_types),
+        ("define-read-only", &analysis.read_only_function_types),
+        ("define-private", &analysis.private_function_types),
+    ];
+    for (define, function_types) in functions.iter() {
+        if let Some(FunctionType::Fixed(function)) = function_types.get(name) {
+            let args: String = function
+                .args
+                .iter()
+                .map(|arg| format!(" ({} {})", arg.name, arg.signature))
+                .collect();
+            return Some(format!(
+                "```clarity\n({} ({}{}))\n```\n\nReturns `{}`",
+                define, name, args, function.returns
+            ));
+        }
+    }
+    if let Some((key_type, value_type)) = analysis.map_types.get(name) {
+        return Some(format!(
+            "```clarity\n(define-map {} {} {})\n```",
+            name, key_type, value_type
+        ));
+    }
+    if let Some(data_type) = analysis.persisted_variable_types.get(name) {
+        return Some(format!(
+            "```clarity\n(define-data-var {} {})\n```",
+            name, data_type
+        ));
+    }
+    if let Some(nft_type) = analysis.non_fungible_tokens.get(name) {
+        return Some(format!(
+            "```clarity\n(define-non-fungible-token {} {})\n```",
+            name, nft_type
+        ));
+    }
+    if analysis.fungible_tokens.contains(name) {
+        return Some(format!("```clarity\n(define-fungible-token {})\n```", name));
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    const URI: &str = "file:///tmp/counter.clar";
+
+    const COUNTER: &str = "(define-data-var counter uint u0)
+(define-map owners principal uint)
+(define-public (increment (by uint))
+  (begin
+    (var-set counter (+ (var-get counter) by))
+    (ok (var-get counter))))
+(define-private (helper) true)
+";
+
+    fn request(id: u64, method: &str, params: JsonValue) -> JsonValue {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: JsonValue) -> JsonValue {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn open(server: &mut LanguageServer, text: &str) -> JsonValue {
+        let mut replies = server.handle_message(&notification(
+            "textDocument/didOpen",
+            json!({ "textDocument": {
+                "uri": URI, "languageId": "clarity", "version": 1, "text": text,
+            }}),
+        ));
+        assert_eq!(replies.len(), 1);
+        replies.remove(0)
+    }
+
+    fn at(line: u32, character: u32) -> JsonValue {
+        json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        })
+    }
+
+    fn result(server: &mut LanguageServer, method: &str, params: JsonValue) -> JsonValue {
+        let mut replies = server.handle_message(&request(1, method, params));
+        assert_eq!(replies.len(), 1);
+        replies.remove(0)["result"].take()
+    }
+
+    #[test]
+    fn test_lsp_lifecycle() {
+        let mut server = LanguageServer::new();
+        let init = result(&mut server, "initialize", json!({ "capabilities": {} }));
+        assert_eq!(init["capabilities"]["hoverProvider"], json!(true));
+        assert_eq!(init["capabilities"]["definitionProvider"], json!(true));
+        assert_eq!(
+            init["capabilities"]["textDocumentSync"]["save"]["includeText"],
+            json!(true)
+        );
+        assert!(server
+            .handle_message(&notification("initialized", json!({})))
+            .is_empty());
+
+        let replies = server.handle_message(&request(7, "workspace/symbol", json!({})));
+        assert_eq!(replies[0]["id"], json!(7));
+        assert_eq!(replies[0]["error"]["code"], json!(METHOD_NOT_FOUND));
+
+        assert_eq!(
+            result(&mut server, "shutdown", JsonValue::Null),
+            JsonValue::Null
+        );
+        assert_eq!(server.exit_code(), None);
+        server.handle_message(&notification("exit", JsonValue::Null));
+        assert_eq!(server.exit_code(), Some(0));
+
+        let mut server = LanguageServer::new();
+        server.handle_message(&notification("exit", JsonValue::Null));
+        assert_eq!(server.exit_code(), Some(1));
+    }
+
+    #[test]
+    fn test_lsp_diagnostics() {
+        let mut server = LanguageServer::new();
+        let published = open(&mut server, COUNTER);
+        assert_eq!(
+            published["method"],
+            json!("textDocument/publishDiagnostics")
+        );
+        assert_eq!(published["params"]["uri"], json!(URI));
+        // `helper` is never called
+        let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["severity"], json!(2));
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], json!(6));
+
+        // a type error, only reported once the buffer is saved
+        let broken = COUNTER.replace("(+ (var-get counter) by)", "(+ (var-get counter) 1)");
+        let replies = server.handle_message(&notification(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": URI, "version": 2 },
+                "contentChanges": [{ "text": broken }],
+            }),
+        ));
+        assert!(replies.is_empty());
+        let replies = server.handle_message(&notification(
+            "textDocument/didSave",
+            json!({ "textDocument": { "uri": URI } }),
+        ));
+        let diagnostics = replies[0]["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["severity"], json!(1));
+        assert_eq!(
+            diagnostics[0]["range"],
+            json!({
+                "start": { "line": 4, "character": 21 },
+                "end": { "line": 4, "character": 44 },
+            })
+        );
+
+        // a parse error
+        let replies = server.handle_message(&notification(
+            "textDocument/didSave",
+            json!({ "textDocument": { "uri": URI }, "text": "(define-constant x (+ 1 2)" }),
+        ));
+        let diagnostics = replies[0]["params"]["diagnostics"].as_array().unwrap();
+        assert!(!diagnostics.is_empty());
+        assert_eq!(diagnostics[0]["severity"], json!(1));
+
+        // closing clears them
+        let replies = server.handle_message(&notification(
+            "textDocument/didClose",
+            json!({ "textDocument": { "uri": URI } }),
+        ));
+        assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn test_lsp_hover() {
+        let mut server = LanguageServer::new();
+        open(&mut server, COUNTER);
+
+        // the inferred type of `(var-get counter)`
+        let hover = result(&mut server, "textDocument/hover", at(5, 8));
+        assert_eq!(hover["contents"]["value"], json!("```clarity\nuint\n```"));
+        assert_eq!(
+            hover["range"],
+            json!({
+                "start": { "line": 5, "character": 8 },
+                "end": { "line": 5, "character": 25 },
+            })
+        );
+
+        // a native function's docs
+        let hover = result(&mut server, "textDocument/hover", at(4, 6));
+        let contents = hover["contents"]["value"].as_str().unwrap();
+        assert!(contents.starts_with("```clarity\n(var-set var-name expr1)\n```"));
+
+        // a function's signature
+        let hover = result(&mut server, "textDocument/hover", at(2, 17));
+        assert_eq!(
+            hover["contents"]["value"],
+            json!("```clarity\n(define-public (increment (by uint)))\n```\n\nReturns `(response uint UnknownType)`")
+        );
+
+        // a map
+        let hover = result(&mut server, "textDocument/hover", at(1, 13));
+        assert_eq!(
+            hover["contents"]["value"],
+            json!("```clarity\n(define-map owners principal uint)\n```")
+        );
+
+        // past the end of the contract
+        let hover = result(&mut server, "textDocument/hover", at(7, 0));
+        assert_eq!(hover, JsonValue::Null);
+        let hover = result(&mut server, "textDocument/hover", at(u32::MAX, u32::MAX));
+        assert_eq!(hover, JsonValue::Null);
+    }
+
+    #[test]
+    fn test_lsp_definition() {
+        let mut server = LanguageServer::new();
+        open(&mut server, COUNTER);
+
+        // `counter` in `(var-get counter)`
+        let location = result(&mut server, "textDocument/definition", at(4, 35));
+        assert_eq!(location["uri"], json!(URI));
+        assert_eq!(
+            location["range"],
+            json!({
+                "start": { "line": 0, "character": 17 },
+                "end": { "line": 0, "character": 24 },
+            })
+        );
+
+        // natives aren't defined anywhere
+        let location = result(&mut server, "textDocument/definition", at(4, 6));
+        assert_eq!(location, JsonValue::Null);
+    }
+
+    #[test]
+    fn test_lsp_completion() {
+        let mut server = LanguageServer::new();
+        open(&mut server, COUNTER);
+
+        let items = result(&mut server, "textDocument/completion", at(4, 6));
+        let items = items.as_array().unwrap();
+        let item = |label: &str| {
+            items
+                .iter()
+                .find(|item| item["label"] == json!(label))
+                .unwrap_or_else(|| panic!("no completion for {}", label))
+                .clone()
+        };
+        assert_eq!(item("map-get?")["kind"], json!(COMPLETION_FUNCTION));
+        assert_eq!(
+            item("map-get?")["detail"],
+            json!("(map-get? map-name key-tuple)")
+        );
+        assert_eq!(item("define-map")["kind"], json!(COMPLETION_KEYWORD));
+        assert_eq!(item("tx-sender")["kind"], json!(COMPLETION_KEYWORD));
+        assert_eq!(item("increment")["kind"], json!(COMPLETION_FUNCTION));
+        assert_eq!(item("owners")["detail"], json!("define-map"));
+    }
+
+    #[test]
+    fn test_lsp_run() {
+        let mut input = vec![];
+        for message in [
+            request(1, "initialize", json!({ "capabilities": {} })),
+            notification(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": URI, "text": COUNTER } }),
+            ),
+            request(2, "shutdown", JsonValue::Null),
+            notification("exit", JsonValue::Null),
+        ]
+        .iter()
+        {
+            write_message(&mut input, message).unwrap();
+        }
+        // a garbled message gets a parse error without ending the session
+        input.splice(0..0, b"Content-Length: 3\r\n\r\n{{{".iter().cloned());
+
+        let mut output = vec![];
+        let exit_code = run(Cursor::new(input), &mut output).unwrap();
+        assert_eq!(exit_code, 0);
+
+        let mut output = Cursor::new(output);
+        let mut replies = vec![];
+        while let Some(body) = read_message(&mut output).unwrap() {
+            replies.push(serde_json::from_slice::<JsonValue>(&body).unwrap());
+        }
+        assert_eq!(replies.len(), 4);
+        assert_eq!(replies[0]["error"]["code"], json!(PARSE_ERROR));
+        assert_eq!(replies[1]["id"], json!(1));
+        assert_eq!(
+            replies[2]["method"],
+            json!("textDocument/publishDiagnostics")
+        );
+        assert_eq!(replies[3]["id"], json!(2));
+    }
+}
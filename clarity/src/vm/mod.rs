@@ -40,6 +40,8 @@ pub mod docs;
 pub mod version;
 
 pub mod coverage;
+pub mod lsp;
+pub mod profiler;
 
 pub mod events;
 
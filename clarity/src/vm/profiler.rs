@@ -0,0 +1,375 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use serde_json::json;
+use serde_json::Value as JsonValue;
+
+use crate::vm::contexts::{Environment, LocalContext};
+use crate::vm::costs::ExecutionCost;
+use crate::vm::errors::Error;
+use crate::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use crate::vm::{EvalHook, ExecutionResult, SymbolicExpression, Value};
+
+/// One dimension of an `ExecutionCost`, used to weigh the stacks in a folded-stack profile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CostDimension {
+    Runtime,
+    ReadCount,
+    ReadLength,
+    WriteCount,
+    WriteLength,
+}
+
+impl CostDimension {
+    pub fn of(&self, cost: &ExecutionCost) -> u64 {
+        match self {
+            CostDimension::Runtime => cost.runtime,
+            CostDimension::ReadCount => cost.read_count,
+            CostDimension::ReadLength => cost.read_length,
+            CostDimension::WriteCount => cost.write_count,
+            CostDimension::WriteLength => cost.write_length,
+        }
+    }
+}
+
+impl FromStr for CostDimension {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CostDimension, String> {
+        match s {
+            "runtime" => Ok(CostDimension::Runtime),
+            "read_count" => Ok(CostDimension::ReadCount),
+            "read_length" => Ok(CostDimension::ReadLength),
+            "write_count" => Ok(CostDimension::WriteCount),
+            "write_length" => Ok(CostDimension::WriteLength),
+            _ => Err(format!("Unknown cost dimension: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionCost {
+    /// number of times the function was called
+    pub calls: u64,
+    /// total cost of those calls, including the functions they called
+    pub cost: ExecutionCost,
+}
+
+struct EvalFrame {
+    expr: *const SymbolicExpression,
+    contract: String,
+    line: u32,
+    start: ExecutionCost,
+    children: ExecutionCost,
+    /// whether evaluating this expression called into a function, i.e. pushed onto `call_stack`
+    is_call: bool,
+}
+
+///
+/// An `EvalHook` that attributes the cost of a transaction to the functions and source lines
+///  that incurred it, following calls across `contract-call?` boundaries.
+///
+/// Each expression's cost is measured from the environment's cost tracker before and after it
+///  is evaluated.  Its own cost (less that of its sub-expressions) is charged to its source line
+///  and to the current call stack, so the profile can be rendered as folded stacks for a
+///  flamegraph.  If the environment isn't tracking costs, everything costs zero, but call counts
+///  are still recorded.
+///
+pub struct CostProfiler {
+    eval_stack: Vec<EvalFrame>,
+    call_stack: Vec<String>,
+    stacks: BTreeMap<String, ExecutionCost>,
+    functions: BTreeMap<String, FunctionCost>,
+    lines: BTreeMap<(String, u32), ExecutionCost>,
+}
+
+fn add_cost(total: &mut ExecutionCost, cost: &ExecutionCost) {
+    total.add(cost).expect("BUG: cost overflow in profiler");
+}
+
+fn sub_cost(from: &ExecutionCost, cost: &ExecutionCost) -> ExecutionCost {
+    let mut result = from.clone();
+    // the cost tracker only ever accumulates, so this can't underflow unless it was reset
+    if result.sub(cost).is_err() {
+        return ExecutionCost::zero();
+    }
+    result
+}
+
+impl CostProfiler {
+    /// Create a profiler for a transaction.  `root` names the outermost stack frame, e.g. the
+    ///  public function being called.
+    pub fn new(root: &str) -> CostProfiler {
+        CostProfiler {
+            eval_stack: vec![],
+            call_stack: vec![root.to_string()],
+            stacks: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            lines: BTreeMap::new(),
+        }
+    }
+
+    /// If `expr` calls a function, return its name as `contract::function`
+    fn function_called(
+        env: &Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) -> Option<String> {
+        let children = expr.match_list()?;
+        let name = children.first()?.match_atom()?;
+        if name.as_str() == "contract-call?" {
+            let contract: &QualifiedContractIdentifier = match children.get(1) {
+                Some(SymbolicExpression {
+                    expr:
+                        crate::vm::SymbolicExpressionType::LiteralValue(Value::Principal(
+                            PrincipalData::Contract(ref contract_identifier),
+                        )),
+                    ..
+                }) => contract_identifier,
+                Some(target) => {
+                    &context
+                        .lookup_callable_contract(target.match_atom()?)?
+                        .contract_identifier
+                }
+                None => return None,
+            };
+            let function_name = children.get(2)?.match_atom()?;
+            Some(format!("{}::{}", contract, function_name))
+        } else if env.contract_context.functions.contains_key(name) {
+            Some(format!(
+                "{}::{}",
+                &env.contract_context.contract_identifier, name
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn finish(&mut self, frame: EvalFrame, now: &ExecutionCost) {
+        let total = sub_cost(now, &frame.start);
+        let own = sub_cost(&total, &frame.children);
+
+        add_cost(
+            self.lines
+                .entry((frame.contract, frame.line))
+                .or_insert_with(ExecutionCost::zero),
+            &own,
+        );
+        add_cost(
+            self.stacks
+                .entry(self.call_stack.join(";"))
+                .or_insert_with(ExecutionCost::zero),
+            &own,
+        );
+
+        if frame.is_call {
+            let function = self
+                .call_stack
+                .pop()
+                .expect("BUG: profiler call stack underflow");
+            let function_cost = self.functions.entry(function).or_insert(FunctionCost {
+                calls: 0,
+                cost: ExecutionCost::zero(),
+            });
+            function_cost.calls += 1;
+            add_cost(&mut function_cost.cost, &total);
+        }
+
+        if let Some(parent) = self.eval_stack.last_mut() {
+            add_cost(&mut parent.children, &total);
+        }
+    }
+
+    /// The cost of each function called, keyed by `contract::function`
+    pub fn functions(&self) -> &BTreeMap<String, FunctionCost> {
+        &self.functions
+    }
+
+    /// The cost incurred by the expressions on each line of each contract, not counting the
+    ///  functions those expressions call.
+    pub fn lines(&self) -> &BTreeMap<(String, u32), ExecutionCost> {
+        &self.lines
+    }
+
+    /// Render the profile in the folded-stack format read by `flamegraph.pl` and `inferno`:
+    ///  one `frame;frame;frame weight` line per call stack that incurred a nonzero cost in
+    ///  `dimension`.
+    pub fn folded_stacks(&self, dimension: CostDimension) -> String {
+        let mut out = String::new();
+        for (stack, cost) in self.stacks.iter() {
+            let weight = dimension.of(cost);
+            if weight > 0 {
+                writeln!(out, "{} {}", stack, weight).expect("BUG: failed to write to String");
+            }
+        }
+        out
+    }
+
+    /// Summarize the profile as JSON, with functions and lines sorted by decreasing runtime
+    pub fn to_json(&self) -> JsonValue {
+        let mut functions: Vec<_> = self.functions.iter().collect();
+        functions.sort_by_key(|(_, function_cost)| Reverse(function_cost.cost.runtime));
+        let mut lines: Vec<_> = self.lines.iter().collect();
+        lines.sort_by_key(|(_, cost)| Reverse(cost.runtime));
+
+        json!({
+            "functions": functions
+                .into_iter()
+                .map(|(name, function_cost)| json!({
+                    "function": name,
+                    "calls": function_cost.calls,
+                    "cost": function_cost.cost,
+                }))
+                .collect::<Vec<_>>(),
+            "lines": lines
+                .into_iter()
+                .map(|((contract, line), cost)| json!({
+                    "contract": contract,
+                    "line": line,
+                    "cost": cost,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl EvalHook for CostProfiler {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        let is_call = match CostProfiler::function_called(env, context, expr) {
+            Some(function) => {
+                self.call_stack.push(function);
+                true
+            }
+            None => false,
+        };
+        self.eval_stack.push(EvalFrame {
+            expr: expr as *const SymbolicExpression,
+            contract: env.contract_context.contract_identifier.to_string(),
+            line: expr.span.start_line,
+            start: env.global_context.cost_track.get_total(),
+            children: ExecutionCost::zero(),
+            is_call,
+        });
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        _context: &LocalContext,
+        expr: &SymbolicExpression,
+        _res: &core::result::Result<Value, Error>,
+    ) {
+        let now = env.global_context.cost_track.get_total();
+        // expressions that fail before they are applied never report that they finished, so
+        //  unwind to this one.
+        while let Some(frame) = self.eval_stack.pop() {
+            let done = std::ptr::eq(frame.expr, expr);
+            self.finish(frame, &now);
+            if done {
+                break;
+            }
+        }
+    }
+
+    fn did_complete(&mut self, _result: core::result::Result<&mut ExecutionResult, String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::ast::ASTRules;
+    use crate::vm::contexts::OwnedEnvironment;
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::types::StandardPrincipalData;
+    use crate::vm::ClarityVersion;
+    use stacks_common::types::StacksEpochId;
+
+    #[test]
+    fn test_profiler_call_stacks() {
+        let callee = "(define-read-only (get-one) u1)";
+        let caller = "
+(define-private (add-one (x uint)) (+ x (contract-call? .callee get-one)))
+(define-public (go)
+  (ok (add-one (add-one u0))))";
+
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db(), StacksEpochId::Epoch21);
+        let callee_id = QualifiedContractIdentifier::local("callee").unwrap();
+        let caller_id = QualifiedContractIdentifier::local("caller").unwrap();
+        for (contract_identifier, contract) in [(&callee_id, callee), (&caller_id, caller)] {
+            owned_env
+                .initialize_versioned_contract(
+                    contract_identifier.clone(),
+                    ClarityVersion::Clarity2,
+                    contract,
+                    None,
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap();
+        }
+
+        let mut profiler = CostProfiler::new("caller::go");
+        owned_env.add_eval_hook(&mut profiler);
+        let (result, ..) = owned_env
+            .execute_transaction(
+                PrincipalData::Standard(StandardPrincipalData::transient()),
+                None,
+                caller_id.clone(),
+                "go",
+                &[],
+            )
+            .unwrap();
+        assert_eq!(result, Value::okay(Value::UInt(2)).unwrap());
+
+        let calls: Vec<_> = profiler
+            .functions()
+            .iter()
+            .map(|(name, function_cost)| (name.as_str(), function_cost.calls))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("S1G2081040G2081040G2081040G208105NK8PE5.callee::get-one", 2),
+                ("S1G2081040G2081040G2081040G208105NK8PE5.caller::add-one", 2),
+            ]
+        );
+        assert!(profiler.stacks.contains_key(
+            "caller::go;S1G2081040G2081040G2081040G208105NK8PE5.caller::add-one;S1G2081040G2081040G2081040G208105NK8PE5.callee::get-one"
+        ));
+        assert!(profiler.eval_stack.is_empty());
+        assert_eq!(profiler.call_stack, vec!["caller::go".to_string()]);
+
+        // the lines of both contracts are accounted for
+        assert!(profiler
+            .lines()
+            .keys()
+            .any(|(contract, line)| contract == &callee_id.to_string() && *line == 1));
+        assert!(profiler
+            .lines()
+            .keys()
+            .any(|(contract, line)| contract == &caller_id.to_string() && *line == 4));
+    }
+}
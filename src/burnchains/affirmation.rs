@@ -342,6 +342,7 @@ impl<'de> Deserialize<'de> for AffirmationMap {
 }
 
 /// The pointer to the PoX anchor block in the burnchain
+#[derive(Debug, Clone, PartialEq)]
 pub struct PoxAnchorPtr {
     /// height of the block
     pub block_height: u64,
@@ -541,7 +542,7 @@ pub fn get_parent_child_reward_cycles(
 /// block-commits in block order for the ith prepare-phase block (item 0 is the first prepare-phase
 /// block's block-commits).
 pub fn read_prepare_phase_commits<B: BurnchainHeaderReader>(
-    burnchain_tx: &BurnchainDBTransaction,
+    burnchain_conn: &DBConn,
     indexer: &B,
     pox_consts: &PoxConstants,
     first_block_height: u64,
@@ -557,8 +558,8 @@ pub fn read_prepare_phase_commits<B: BurnchainHeaderReader>(
 
     let mut ret = vec![];
     for header in headers.into_iter() {
-        let blk = BurnchainDB::get_burnchain_block(&burnchain_tx.conn(), &header.block_hash)
-            .expect(&format!(
+        let blk =
+            BurnchainDB::get_burnchain_block(burnchain_conn, &header.block_hash).expect(&format!(
                 "BUG: failed to load prepare-phase block {} ({})",
                 &header.block_hash, header.block_height
             ));
@@ -619,7 +620,7 @@ pub fn read_prepare_phase_commits<B: BurnchainHeaderReader>(
 /// If this method errors out, it's because it couldn't read the burnchain headers DB (or it's
 /// corrupted). Either way, the caller may treat this as a fatal condition.
 pub fn read_parent_block_commits<B: BurnchainHeaderReader>(
-    burnchain_tx: &BurnchainDBTransaction,
+    burnchain_conn: &DBConn,
     indexer: &B,
     prepare_phase_ops: &[Vec<LeaderBlockCommitOp>],
 ) -> Result<Vec<LeaderBlockCommitOp>, Error> {
@@ -642,8 +643,8 @@ pub fn read_parent_block_commits<B: BurnchainHeaderReader>(
             assert_eq!(hdr.block_height, opdata.parent_block_ptr as u64);
 
             let mut found = false;
-            let blk = BurnchainDB::get_burnchain_block(burnchain_tx.conn(), &hdr.block_hash)
-                .expect(&format!(
+            let blk =
+                BurnchainDB::get_burnchain_block(burnchain_conn, &hdr.block_hash).expect(&format!(
                     "BUG: failed to load existing block {} ({})",
                     &hdr.block_hash, &hdr.block_height
                 ));
@@ -765,17 +766,18 @@ pub fn filter_missed_block_commits(
         .collect()
 }
 
-/// Given a list of block-commits in the prepare-phase, find the block-commit pointer outside the
-/// prepare-phase which must be the anchor block, if it exists at all.  This is always
-/// the block-commit that has the most cumulative BTC committed behind it (and the highest
-/// such in the event of a tie), as well as at least `anchor_threshold` confirmations.
-/// Returns the pointer into the burnchain where the anchor block-commit can be found, if it
-/// exists at all.
-/// Returns None otherwise
-fn inner_find_heaviest_block_commit_ptr(
+/// Given a list of block-commits in the prepare-phase, find the highest block-commit outside the
+/// prepare-phase that each one descends from, and how many distinct prepare-phase blocks (and how
+/// much BTC) confirm each such ancestor.
+/// Returns the map of (block_height, vtxindex) of each prepare-phase block-commit to its
+/// ancestor's (block_height, vtxindex), and the map of each ancestor's (block_height, vtxindex) to
+/// the set of confirming block heights and the total BTC burnt by the confirming block-commits.
+fn inner_find_ancestor_confirmations(
     prepare_phase_ops: &[Vec<LeaderBlockCommitOp>],
-    anchor_threshold: u32,
-) -> Option<(PoxAnchorPtr, BTreeMap<(u64, u32), (u64, u32)>)> {
+) -> (
+    BTreeMap<(u64, u32), (u64, u32)>,
+    BTreeMap<(u64, u32), (HashSet<u64>, u64)>,
+) {
     // sanity check -- must be in order by block height and vtxindex
     for prepare_block_ops in prepare_phase_ops.iter() {
         let mut expected_block_height = None;
@@ -869,6 +871,22 @@ fn inner_find_heaviest_block_commit_ptr(
     test_debug!("ancestors = {:?}", &ancestors);
     test_debug!("ancestor_confirmations = {:?}", &ancestor_confirmations);
 
+    (ancestors, ancestor_confirmations)
+}
+
+/// Given a list of block-commits in the prepare-phase, find the block-commit pointer outside the
+/// prepare-phase which must be the anchor block, if it exists at all.  This is always
+/// the block-commit that has the most cumulative BTC committed behind it (and the highest
+/// such in the event of a tie), as well as at least `anchor_threshold` confirmations.
+/// Returns the pointer into the burnchain where the anchor block-commit can be found, if it
+/// exists at all.
+/// Returns None otherwise
+fn inner_find_heaviest_block_commit_ptr(
+    prepare_phase_ops: &[Vec<LeaderBlockCommitOp>],
+    anchor_threshold: u32,
+) -> Option<(PoxAnchorPtr, BTreeMap<(u64, u32), (u64, u32)>)> {
+    let (ancestors, ancestor_confirmations) = inner_find_ancestor_confirmations(prepare_phase_ops);
+
     if ancestor_confirmations.len() == 0 {
         // empty prepare phase
         test_debug!("Prepare-phase has no block-commits");
@@ -1023,7 +1041,7 @@ pub fn find_heaviest_block_commit<B: BurnchainHeaderReader>(
 
 /// Find the valid prepare-phase ops for a given reward cycle
 fn inner_find_valid_prepare_phase_commits<B: BurnchainHeaderReader>(
-    burnchain_tx: &BurnchainDBTransaction,
+    burnchain_conn: &DBConn,
     reward_cycle: u64,
     indexer: &B,
     burnchain: &Burnchain,
@@ -1032,7 +1050,7 @@ fn inner_find_valid_prepare_phase_commits<B: BurnchainHeaderReader>(
     let first_block_height = burnchain.first_block_height;
 
     let prepare_ops = read_prepare_phase_commits(
-        burnchain_tx,
+        burnchain_conn,
         indexer,
         pox_consts,
         first_block_height,
@@ -1040,7 +1058,7 @@ fn inner_find_valid_prepare_phase_commits<B: BurnchainHeaderReader>(
     )?;
     test_debug!("{} prepare-phase commits", prepare_ops.len());
 
-    let parent_commits = read_parent_block_commits(burnchain_tx, indexer, &prepare_ops)?;
+    let parent_commits = read_parent_block_commits(burnchain_conn, indexer, &prepare_ops)?;
     test_debug!("{} parent block-commits", parent_commits.len());
 
     let prepare_ops_no_orphans = filter_orphan_block_commits(&parent_commits, prepare_ops);
@@ -1071,8 +1089,12 @@ pub fn find_pox_anchor_block_ptr<B: BurnchainHeaderReader>(
     indexer: &B,
     burnchain: &Burnchain,
 ) -> Result<Option<PoxAnchorPtr>, Error> {
-    let prepare_ops_valid =
-        inner_find_valid_prepare_phase_commits(burnchain_tx, reward_cycle, indexer, burnchain)?;
+    let prepare_ops_valid = inner_find_valid_prepare_phase_commits(
+        burnchain_tx.conn(),
+        reward_cycle,
+        indexer,
+        burnchain,
+    )?;
     Ok(inner_find_heaviest_block_commit_ptr(
         &prepare_ops_valid,
         burnchain.pox_constants.anchor_threshold,
@@ -1080,6 +1102,35 @@ pub fn find_pox_anchor_block_ptr<B: BurnchainHeaderReader>(
     .map(|(ptr, _)| ptr))
 }
 
+/// Find every block-commit outside of a reward cycle's prepare phase that the valid prepare-phase
+/// block-commits descend from.  Each of these could have been chosen as the anchor block for the
+/// *next* reward cycle; the anchor block is the one with the most BTC burnt behind it among those
+/// with at least `anchor_threshold` confirmations.
+/// Returns a pointer to each candidate, with its confirmations and the BTC burnt to confirm it,
+/// highest candidate first.
+pub fn find_pox_anchor_block_candidates<B: BurnchainHeaderReader>(
+    burnchain_conn: &DBConn,
+    reward_cycle: u64,
+    indexer: &B,
+    burnchain: &Burnchain,
+) -> Result<Vec<PoxAnchorPtr>, Error> {
+    let prepare_ops_valid =
+        inner_find_valid_prepare_phase_commits(burnchain_conn, reward_cycle, indexer, burnchain)?;
+    let (_, ancestor_confirmations) = inner_find_ancestor_confirmations(&prepare_ops_valid);
+    Ok(ancestor_confirmations
+        .into_iter()
+        .rev()
+        .map(
+            |((block_height, vtxindex), (block_set, burnt))| PoxAnchorPtr {
+                block_height,
+                vtxindex,
+                burnt,
+                confs: block_set.len() as u64,
+            },
+        )
+        .collect())
+}
+
 /// Find the PoX anchor block selected in a reward cycle, if it exists.  This is the heaviest F*w-confirmed
 /// block-commit before the prepare-phase of this reward cycle, provided that it is not already an
 /// anchor block for some other reward cycle.  Note that the anchor block found will be the anchor
@@ -1106,8 +1157,12 @@ pub fn find_pox_anchor_block<B: BurnchainHeaderReader>(
     ),
     Error,
 > {
-    let prepare_ops_valid =
-        inner_find_valid_prepare_phase_commits(burnchain_tx, reward_cycle, indexer, burnchain)?;
+    let prepare_ops_valid = inner_find_valid_prepare_phase_commits(
+        burnchain_tx.conn(),
+        reward_cycle,
+        indexer,
+        burnchain,
+    )?;
     let anchor_block_and_descendancy_opt = find_heaviest_block_commit(
         &burnchain_tx,
         indexer,
@@ -1233,3 +1288,76 @@ pub fn update_pox_affirmation_maps<B: BurnchainHeaderReader>(
 
     Ok(())
 }
+
+/// Reads the burnchain headers on a sortition's burnchain fork from the sortition DB and the
+/// burnchain DB.  This lets callers without a burnchain indexer (such as the RPC server, or an
+/// offline inspection tool) replay anchor block selection for the blocks this node has processed.
+pub struct SortitionForkHeaderReader<'a> {
+    burnchain_db: &'a BurnchainDB,
+    sortdb: &'a SortitionDB,
+    tip: BlockSnapshot,
+}
+
+impl<'a> SortitionForkHeaderReader<'a> {
+    pub fn new(
+        burnchain_db: &'a BurnchainDB,
+        sortdb: &'a SortitionDB,
+        tip: BlockSnapshot,
+    ) -> SortitionForkHeaderReader<'a> {
+        SortitionForkHeaderReader {
+            burnchain_db,
+            sortdb,
+            tip,
+        }
+    }
+
+    fn read_header(
+        &self,
+        burn_header_hash: &BurnchainHeaderHash,
+    ) -> Result<BurnchainBlockHeader, DBError> {
+        BurnchainDB::get_burnchain_block(self.burnchain_db.conn(), burn_header_hash)
+            .map(|block| block.header)
+            .map_err(|e| match e {
+                Error::DBError(dbe) => dbe,
+                _ => DBError::Other(format!("Burnchain error: {:?}", &e)),
+            })
+    }
+}
+
+impl<'a> BurnchainHeaderReader for SortitionForkHeaderReader<'a> {
+    fn read_burnchain_headers(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<BurnchainBlockHeader>, DBError> {
+        let ic = self.sortdb.index_handle(&self.tip.sortition_id);
+        let end_height = cmp::min(end_height, self.tip.block_height + 1);
+        let mut headers = vec![];
+        for height in start_height..end_height {
+            let burn_header_hash = if height == self.tip.block_height {
+                self.tip.burn_header_hash
+            } else {
+                match ic.get_block_snapshot_by_height(height)? {
+                    Some(sn) => sn.burn_header_hash,
+                    None => break,
+                }
+            };
+            headers.push(self.read_header(&burn_header_hash)?);
+        }
+        Ok(headers)
+    }
+
+    fn get_burnchain_headers_height(&self) -> Result<u64, DBError> {
+        Ok(self.tip.block_height + 1)
+    }
+
+    fn find_burnchain_header_height(
+        &self,
+        burn_header_hash: &BurnchainHeaderHash,
+    ) -> Result<Option<u64>, DBError> {
+        let ic = self.sortdb.index_handle(&self.tip.sortition_id);
+        Ok(ic
+            .get_block_snapshot(burn_header_hash)?
+            .map(|sn| sn.block_height))
+    }
+}
@@ -554,7 +554,7 @@ impl BitcoinTxInput {
     }
 }
 
-fn to_txid(txin: &BtcTxIn) -> (Txid, u32) {
+pub fn to_txid(txin: &BtcTxIn) -> (Txid, u32) {
     // bitcoin-rs library (which stacks_common::deps_common::bitcoin is based on)
     //   operates in a different endian-ness for txids than the rest of
     //   the codebase. so this method reverses the txid bits.
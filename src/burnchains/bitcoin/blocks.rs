@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
+use std::collections::HashSet;
 use std::ops::Deref;
 
 use crate::burnchains::bitcoin::address::BitcoinAddress;
@@ -26,7 +28,8 @@ use crate::burnchains::bitcoin::BitcoinNetworkType;
 use crate::burnchains::bitcoin::Error as btc_error;
 use crate::burnchains::bitcoin::PeerMessage;
 use crate::burnchains::bitcoin::{
-    BitcoinBlock, BitcoinTransaction, BitcoinTxInput, BitcoinTxOutput,
+    BitcoinBlock, BitcoinTransaction, BitcoinTxInput, BitcoinTxMerkleProof, BitcoinTxOutput,
+    BitcoinWatchedOutput,
 };
 use crate::burnchains::indexer::{
     BurnBlockIPC, BurnHeaderIPC, BurnchainBlockDownloader, BurnchainBlockParser,
@@ -35,14 +38,15 @@ use crate::burnchains::Error as burnchain_error;
 use crate::burnchains::{
     BurnchainBlock, BurnchainTransaction, MagicBytes, Txid, MAGIC_BYTES_LENGTH,
 };
+use crate::chainstate::burn::Opcodes;
 use crate::deps;
 use stacks_common::deps_common::bitcoin::blockdata::block::{Block, LoneBlockHeader};
 use stacks_common::deps_common::bitcoin::blockdata::opcodes::All as btc_opcodes;
 use stacks_common::deps_common::bitcoin::blockdata::script::{Instruction, Script};
-use stacks_common::deps_common::bitcoin::blockdata::transaction::Transaction;
+use stacks_common::deps_common::bitcoin::blockdata::transaction::{Transaction, TxIn};
 use stacks_common::deps_common::bitcoin::network::message as btc_message;
-use stacks_common::deps_common::bitcoin::network::serialize::BitcoinHash;
-use stacks_common::deps_common::bitcoin::util::hash::bitcoin_merkle_root;
+use stacks_common::deps_common::bitcoin::network::serialize::{serialize, BitcoinHash};
+use stacks_common::deps_common::bitcoin::util::hash::{bitcoin_merkle_root, Sha256dHash};
 use stacks_common::util::hash::to_hex;
 use stacks_common::util::log;
 
@@ -104,6 +108,7 @@ pub struct BitcoinBlockDownloader {
 pub struct BitcoinBlockParser {
     network_id: BitcoinNetworkType,
     magic_bytes: MagicBytes,
+    watched_scripts: HashSet<Vec<u8>>,
 }
 
 impl BitcoinBlockDownloader {
@@ -238,9 +243,37 @@ impl BitcoinBlockParser {
         BitcoinBlockParser {
             network_id: network_id,
             magic_bytes: magic_bytes.clone(),
+            watched_scripts: HashSet::new(),
         }
     }
 
+    /// Track outputs paying to the given scriptPubKeys, as well as all spent outpoints, in
+    /// each parsed block.  This is what lets the burnchain DB maintain a UTXO set for them.
+    pub fn with_watched_scripts(mut self, scripts: &[Vec<u8>]) -> BitcoinBlockParser {
+        self.watched_scripts = scripts.iter().cloned().collect();
+        self
+    }
+
+    /// Find the outputs in a transaction that pay to a watched scriptPubKey
+    fn find_watched_outputs(&self, tx: &Transaction) -> Vec<BitcoinWatchedOutput> {
+        let mut ret = vec![];
+        for (vout, output) in tx.output.iter().enumerate() {
+            if !self
+                .watched_scripts
+                .contains(output.script_pubkey.as_bytes())
+            {
+                continue;
+            }
+            ret.push(BitcoinWatchedOutput {
+                txid: Txid::from_vec_be(&tx.txid().as_bytes().to_vec()).unwrap(), // this *should* panic if it fails
+                vout: vout as u32,
+                script_pubkey: output.script_pubkey.to_bytes(),
+                amount: output.value,
+            });
+        }
+        ret
+    }
+
     /// Allow raw inputs?
     fn allow_raw_inputs(epoch_id: StacksEpochId) -> bool {
         epoch_id >= StacksEpochId::Epoch21
@@ -470,8 +503,18 @@ impl BitcoinBlockParser {
         epoch_id: StacksEpochId,
     ) -> BitcoinBlock {
         let mut accepted_txs = vec![];
+        let mut watched_outputs = vec![];
+        let mut spent_outpoints = vec![];
         for i in 0..block.txdata.len() {
             let tx = &block.txdata[i];
+            if !self.watched_scripts.is_empty() {
+                watched_outputs.append(&mut self.find_watched_outputs(tx));
+                if !tx.is_coin_base() {
+                    for input in tx.input.iter() {
+                        spent_outpoints.push(bits::to_txid(input));
+                    }
+                }
+            }
             match self.parse_tx(tx, i, epoch_id) {
                 Some(bitcoin_tx) => {
                     accepted_txs.push(bitcoin_tx);
@@ -482,15 +525,78 @@ impl BitcoinBlockParser {
             }
         }
 
+        let mut tx_proofs = vec![];
+        let mut txids = vec![];
+        for bitcoin_tx in accepted_txs.iter() {
+            if bitcoin_tx.opcode != Opcodes::LeaderBlockCommit as u8 {
+                continue;
+            }
+            if txids.is_empty() {
+                txids = block.txdata.iter().map(|tx| tx.txid()).collect();
+            }
+            let vtxindex = bitcoin_tx.vtxindex as usize;
+            tx_proofs.push(BitcoinTxMerkleProof {
+                txid: bitcoin_tx.txid.clone(),
+                vtxindex: bitcoin_tx.vtxindex,
+                raw_tx: BitcoinBlockParser::serialize_tx_without_witness(&block.txdata[vtxindex]),
+                merkle_branch: BitcoinBlockParser::merkle_branch(&txids, vtxindex),
+            });
+        }
+
         BitcoinBlock {
             block_height: block_height,
             block_hash: BurnchainHeaderHash::from_bitcoin_hash(&block.bitcoin_hash()),
             parent_block_hash: BurnchainHeaderHash::from_bitcoin_hash(&block.header.prev_blockhash),
             txs: accepted_txs,
             timestamp: block.header.time as u64,
+            watched_outputs,
+            spent_outpoints,
+            raw_header: serialize(&block.header).expect("BUG: failed to serialize block header"),
+            tx_proofs,
         }
     }
 
+    /// Serialize a transaction without its witness data, so that it hashes to its txid.
+    fn serialize_tx_without_witness(tx: &Transaction) -> Vec<u8> {
+        let stripped_tx = Transaction {
+            version: tx.version,
+            lock_time: tx.lock_time,
+            input: tx
+                .input
+                .iter()
+                .map(|txin| TxIn {
+                    witness: vec![],
+                    ..txin.clone()
+                })
+                .collect(),
+            output: tx.output.clone(),
+        };
+        serialize(&stripped_tx).expect("BUG: failed to serialize transaction")
+    }
+
+    /// Find the sibling hashes on the path from the `index`-th leaf of a block's merkle tree to
+    /// its root.  As in `bitcoin_merkle_root`, a level's last node is paired with itself if the
+    /// level has an odd number of nodes.
+    fn merkle_branch(txids: &[Sha256dHash], index: usize) -> Vec<[u8; 32]> {
+        let mut branch = vec![];
+        let mut level: Vec<[u8; 32]> = txids.iter().map(|txid| txid.0).collect();
+        let mut index = index;
+        while level.len() > 1 {
+            branch.push(level[cmp::min(index ^ 1, level.len() - 1)]);
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let mut buf = [0u8; 64];
+                    buf[0..32].copy_from_slice(&pair[0]);
+                    buf[32..64].copy_from_slice(&pair[pair.len() - 1]);
+                    Sha256dHash::from_data(&buf).0
+                })
+                .collect();
+            index /= 2;
+        }
+        branch
+    }
+
     /// Return true if we handled the block, and we can receive the next one.  Update internal
     /// state, extract the BitcoinTransactions we care about
     ///
@@ -553,14 +659,15 @@ mod tests {
     use crate::burnchains::bitcoin::BitcoinNetworkType;
     use crate::burnchains::bitcoin::{
         BitcoinBlock, BitcoinInputType, BitcoinTransaction, BitcoinTxInput, BitcoinTxInputRaw,
-        BitcoinTxInputStructured, BitcoinTxOutput,
+        BitcoinTxInputStructured, BitcoinTxOutput, BitcoinWatchedOutput,
     };
     use crate::burnchains::{BurnchainBlock, BurnchainTransaction, MagicBytes, Txid};
     use crate::core::StacksEpochId;
     use stacks_common::deps_common::bitcoin::blockdata::block::{Block, LoneBlockHeader};
     use stacks_common::deps_common::bitcoin::blockdata::transaction::Transaction;
     use stacks_common::deps_common::bitcoin::network::encodable::VarInt;
-    use stacks_common::deps_common::bitcoin::network::serialize::deserialize;
+    use stacks_common::deps_common::bitcoin::network::serialize::{deserialize, serialize};
+    use stacks_common::deps_common::bitcoin::util::hash::{bitcoin_merkle_root, Sha256dHash};
     use stacks_common::types::Address;
     use stacks_common::util::hash::hex_bytes;
     use stacks_common::util::log;
@@ -1071,6 +1178,10 @@ mod tests {
                         }
                     ],
                     timestamp: 1543267060,
+                    watched_outputs: vec![],
+                    spent_outpoints: vec![],
+                    raw_header: hex_bytes("000000209cef4ccd19f4294dd5c762aab6d9577fb4412cd4c0a662a953a8b7969697bc1ddab52e6f053758022fb92f04388eb5fdd87046776e9c406880e728b48e6930aff462fc5bffff7f2000000000").unwrap(),
+                    tx_proofs: vec![],
                 })
             },
             BlockFixture {
@@ -1083,6 +1194,10 @@ mod tests {
                     block_hash: to_block_hash(&hex_bytes("4f3757bc236e58b87d6208aa795115002b739bf39268cf69640f0b092e8cdafe").unwrap()),
                     parent_block_hash: to_block_hash(&hex_bytes("25af4b7151b77f6f8235bda83a8062fba621591beef57e18f4697c8b88a298ad").unwrap()),
                     timestamp: 1543272755,
+                    watched_outputs: vec![],
+                    spent_outpoints: vec![],
+                    raw_header: hex_bytes("00000020ad98a2888b7c69f4187ef5ee1b5921a6fb62803aa8bd35826f7fb751714baf250cb5ef03478d35ed7f6582ab40232ee39744471b2bcb40b91db0f29102d695123379fc5bffff7f2002000000").unwrap(),
+                    tx_proofs: vec![],
                     txs: vec![
                         BitcoinTransaction {
                             data_amt: 0,
@@ -1247,4 +1362,108 @@ mod tests {
             assert_eq!(parsed_block_opt, block_fixture.result);
         }
     }
+
+    #[test]
+    fn parse_block_watched_outputs() {
+        // coinbase
+        let coinbase = make_tx("02000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0502b7020101ffffffff024023b71200000000232103ecfa5bcaa0d2b7dd3a705342be2e144f66293be99488c8e5c9bc3d843036f1bfac0000000000000000266a24aa21a9ed620a2609f2f58ea62134d1c54bf73cb6e0cf194cfbdf25ae32b55dd167ee64bb00000000").unwrap();
+        // NAMESPACE_REVEAL, with a change output to 74178497e927ff3ff1428a241be454d393c3c91c
+        let tx = make_tx("0100000001fde2146ec3ecf037ad515c0c1e2ba8abee348bd2b3c6a576bf909d78b0b18cd2010000006a47304402203ec06f11bc5b7e79fad54b2d69a375ba78576a2a0293f531a082fcfe13a9e9e802201afcf0038d9ccb9c88113248faaf812321b65d7b09b4a6e2f04f463d2741101e012103d6fd1ba0effaf1e8d94ea7b7a3d0ef26fea00a14ce5ffcc1495fe588a2c6d0f3ffffffff0300000000000000001a6a186964260000cd73fa046543210000000000aa0001746573747c1500000000000016001482093b62a3699282d926981bed7665e8384caa552076fd29010000001976a91474178497e927ff3ff1428a241be454d393c3c91c88ac00000000").unwrap();
+        let header = make_block_header("000000209cef4ccd19f4294dd5c762aab6d9577fb4412cd4c0a662a953a8b7969697bc1ddab52e6f053758022fb92f04388eb5fdd87046776e9c406880e728b48e6930aff462fc5bffff7f2000000000").unwrap();
+        let block = Block {
+            header: header.header,
+            txdata: vec![coinbase, tx.clone()],
+        };
+
+        let watched_script =
+            hex_bytes("76a91474178497e927ff3ff1428a241be454d393c3c91c88ac").unwrap();
+
+        // nothing is tracked unless a script is watched
+        let parser = BitcoinBlockParser::new(BitcoinNetworkType::Testnet, MagicBytes([105, 100])); // "id"
+        let parsed_block = parser.parse_block(&block, 32, StacksEpochId::Epoch21);
+        assert!(parsed_block.watched_outputs.is_empty());
+        assert!(parsed_block.spent_outpoints.is_empty());
+
+        let parser = BitcoinBlockParser::new(BitcoinNetworkType::Testnet, MagicBytes([105, 100]))
+            .with_watched_scripts(&[watched_script.clone()]);
+        let parsed_block = parser.parse_block(&block, 32, StacksEpochId::Epoch21);
+        assert_eq!(
+            parsed_block.watched_outputs,
+            vec![BitcoinWatchedOutput {
+                txid: Txid::from_vec_be(&tx.txid().as_bytes().to_vec()).unwrap(),
+                vout: 2,
+                script_pubkey: watched_script,
+                amount: tx.output[2].value,
+            }]
+        );
+
+        // the coinbase's input is not an outpoint
+        assert_eq!(
+            parsed_block.spent_outpoints,
+            vec![(
+                Txid::from_hex("d28cb1b0789d90bf76a5c6b3d28b34eeaba82b1e0c5c51ad37f0ecc36e14e2fd")
+                    .unwrap(),
+                1
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_block_tx_proofs() {
+        // coinbase
+        let coinbase = make_tx("02000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0502b7020101ffffffff024023b71200000000232103ecfa5bcaa0d2b7dd3a705342be2e144f66293be99488c8e5c9bc3d843036f1bfac0000000000000000266a24aa21a9ed620a2609f2f58ea62134d1c54bf73cb6e0cf194cfbdf25ae32b55dd167ee64bb00000000").unwrap();
+        // NAME_REGISTRATION with a segwit input
+        let name_registration = make_tx("01000000000101a7ef2b09722ad786c569c0812005a731ce19290bb0a2afc16cb91056c2e4c19e0100000017160014393ffec4f09b38895b8502377693f23c6ae00f19ffffffff0300000000000000000d6a0b69643a666f6f2e746573747c1500000000000017a9144b85301ba8e42bf98472b8ed4939d5f76b98fcea87144d9c290100000017a91431f8968eb1730c83fb58409a9a560a0a0835027f8702483045022100fc82815edf1c0ef0c601cf1e26494626d7b01597be5ab83df025ff1ee67730130220016c4c29d77aadb5ff57c0c9272a43950ca29b84d8adfaed95ac69db90b35d5b012102d341f728783eb93e6fb5921a1ebe9d149e941de31e403cd69afa2f0f1e698e8100000000").unwrap();
+        // LEADER_BLOCK_COMMIT
+        let block_commit = make_tx("01000000011111111111111111111111111111111111111111111111111111111111111111000000006b483045022100eba8c0a57c1eb71cdfba0874de63cf37b3aace1e56dcbd61701548194a79af34022041dd191256f3f8a45562e5d60956bb871421ba69db605716250554b23b08277b012102d8015134d9db8178ac93acbc43170a2f20febba5087a5b0437058765ad5133d000000000040000000000000000536a4c5069645b22222222222222222222222222222222222222222222222222222222222222223333333333333333333333333333333333333333333333333333333333333333404142435051606162637071fa39300000000000001976a914000000000000000000000000000000000000000088ac39300000000000001976a914000000000000000000000000000000000000000088aca05b0000000000001976a9140be3e286a15ea85882761618e366586b5574100d88ac00000000").unwrap();
+        let header = make_block_header("000000209cef4ccd19f4294dd5c762aab6d9577fb4412cd4c0a662a953a8b7969697bc1ddab52e6f053758022fb92f04388eb5fdd87046776e9c406880e728b48e6930aff462fc5bffff7f2000000000").unwrap();
+
+        // witness data is not part of the proof, so the proven transaction hashes to its txid
+        let raw_tx = BitcoinBlockParser::serialize_tx_without_witness(&name_registration);
+        assert!(raw_tx.len() < serialize(&name_registration).unwrap().len());
+        assert_eq!(Sha256dHash::from_data(&raw_tx), name_registration.txid());
+
+        let parser = BitcoinBlockParser::new(BitcoinNetworkType::Testnet, MagicBytes([105, 100])); // "id"
+
+        // try trees of every shape up to 3 levels deep, with the block-commit at every position
+        for num_txs in 2..=8 {
+            for commit_index in 1..num_txs {
+                let mut txdata = vec![coinbase.clone()];
+                for i in 1..num_txs {
+                    if i == commit_index {
+                        txdata.push(block_commit.clone());
+                    } else {
+                        txdata.push(name_registration.clone());
+                    }
+                }
+                let block = Block {
+                    header: header.header.clone(),
+                    txdata,
+                };
+                let merkle_root =
+                    bitcoin_merkle_root(block.txdata.iter().map(|tx| tx.txid()).collect());
+
+                let parsed_block = parser.parse_block(&block, 32, StacksEpochId::Epoch2_05);
+                assert_eq!(parsed_block.raw_header, serialize(&block.header).unwrap());
+                assert_eq!(parsed_block.raw_header.len(), 80);
+
+                // only the block-commit gets a proof
+                assert_eq!(parsed_block.txs.len(), num_txs - 1);
+                assert_eq!(parsed_block.tx_proofs.len(), 1);
+
+                let proof = &parsed_block.tx_proofs[0];
+                assert_eq!(
+                    proof.txid,
+                    Txid::from_vec_be(&block_commit.txid().as_bytes().to_vec()).unwrap()
+                );
+                assert_eq!(proof.vtxindex, commit_index as u32);
+                assert_eq!(proof.merkle_root(), merkle_root.0);
+
+                // the proof does not verify for any other transaction
+                let mut bad_proof = proof.clone();
+                bad_proof.raw_tx = serialize(&name_registration).unwrap();
+                assert_ne!(bad_proof.merkle_root(), merkle_root.0);
+            }
+        }
+    }
 }
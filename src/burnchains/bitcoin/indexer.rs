@@ -122,6 +122,8 @@ pub struct BitcoinIndexerConfig {
     pub first_block: u64,
     pub magic_bytes: MagicBytes,
     pub epochs: Option<Vec<StacksEpoch>>,
+    /// scriptPubKeys whose UTXOs are tracked in the burnchain DB as blocks are downloaded
+    pub watched_scripts: Vec<Vec<u8>>,
 }
 
 #[derive(Debug)]
@@ -156,6 +158,7 @@ impl BitcoinIndexerConfig {
             first_block,
             magic_bytes: BLOCKSTACK_MAGIC_MAINNET.clone(),
             epochs: None,
+            watched_scripts: vec![],
         }
     }
 
@@ -172,6 +175,7 @@ impl BitcoinIndexerConfig {
             first_block: 0,
             magic_bytes: BLOCKSTACK_MAGIC_MAINNET.clone(),
             epochs: None,
+            watched_scripts: vec![],
         }
     }
 
@@ -189,6 +193,7 @@ impl BitcoinIndexerConfig {
             first_block: 0,
             magic_bytes: BLOCKSTACK_MAGIC_MAINNET.clone(),
             epochs: None,
+            watched_scripts: vec![],
         }
     }
 }
@@ -1131,6 +1136,7 @@ impl BurnchainIndexer for BitcoinIndexer {
 
     fn parser(&self) -> BitcoinBlockParser {
         BitcoinBlockParser::new(self.runtime.network_id, self.config.magic_bytes)
+            .with_watched_scripts(&self.config.watched_scripts)
     }
 
     fn reader(&self) -> BitcoinIndexer {
@@ -1604,6 +1610,7 @@ mod test {
             first_block: 0,
             magic_bytes: MagicBytes([105, 100]),
             epochs: None,
+            watched_scripts: vec![],
         };
 
         if fs::metadata(&indexer_conf.spv_headers_path).is_ok() {
@@ -29,6 +29,7 @@ use crate::chainstate::burn::operations::BlockstackOperationType;
 use crate::deps;
 use crate::util_lib::db::Error as db_error;
 use stacks_common::deps_common::bitcoin::network::serialize::Error as btc_serialize_error;
+use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
 use stacks_common::util::HexError as btc_hex_error;
 
 use crate::types::chainstate::BurnchainHeaderHash;
@@ -220,6 +221,62 @@ pub struct BitcoinTransaction {
     pub outputs: Vec<BitcoinTxOutput>,
 }
 
+/// A transaction output that pays to one of the scriptPubKeys the indexer was asked to watch.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BitcoinWatchedOutput {
+    pub txid: Txid,
+    pub vout: u32,
+    pub script_pubkey: Vec<u8>,
+    pub amount: u64,
+}
+
+/// A transaction the node broadcast that spends watched outputs, and which has not been seen
+/// in a burnchain block yet.  Until it is, its inputs are treated as spent and its watched
+/// outputs (i.e. its change) as spendable, so the node can send several transactions per block.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BitcoinPendingTx {
+    pub txid: Txid,
+    /// height of the canonical burnchain tip when the transaction was broadcast
+    pub broadcast_height: u64,
+    pub spent_outpoints: Vec<(Txid, u32)>,
+    /// the transaction's outputs that pay to a watched scriptPubKey
+    pub outputs: Vec<BitcoinWatchedOutput>,
+}
+
+/// Proof that a transaction was mined in a Bitcoin block.  `raw_tx` is the transaction without
+/// its witness data, so it hashes to the txid, and `merkle_branch` holds the hashes of the
+/// transaction's ancestors' siblings in the block's merkle tree, from the leaf up.  Hashes are
+/// in internal byte order, as they appear in the block header.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BitcoinTxMerkleProof {
+    pub txid: Txid,
+    pub vtxindex: u32,
+    pub raw_tx: Vec<u8>,
+    pub merkle_branch: Vec<[u8; 32]>,
+}
+
+impl BitcoinTxMerkleProof {
+    /// Compute the merkle root this proof commits to, in internal byte order.  The proof is
+    /// valid if this matches the merkle root in the block's header.
+    pub fn merkle_root(&self) -> [u8; 32] {
+        let mut hash = Sha256dHash::from_data(&self.raw_tx).0;
+        let mut index = self.vtxindex;
+        for sibling in self.merkle_branch.iter() {
+            let mut buf = [0u8; 64];
+            if index & 1 == 0 {
+                buf[0..32].copy_from_slice(&hash);
+                buf[32..64].copy_from_slice(sibling);
+            } else {
+                buf[0..32].copy_from_slice(sibling);
+                buf[32..64].copy_from_slice(&hash);
+            }
+            hash = Sha256dHash::from_data(&buf).0;
+            index >>= 1;
+        }
+        hash
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct BitcoinBlock {
     pub block_height: u64,
@@ -227,6 +284,20 @@ pub struct BitcoinBlock {
     pub parent_block_hash: BurnchainHeaderHash,
     pub txs: Vec<BitcoinTransaction>,
     pub timestamp: u64,
+    /// outputs in this block that pay to a watched scriptPubKey
+    #[serde(default)]
+    pub watched_outputs: Vec<BitcoinWatchedOutput>,
+    /// every outpoint consumed by this block's transactions.
+    /// Only populated if the parser is watching at least one scriptPubKey.
+    #[serde(default)]
+    pub spent_outpoints: Vec<(Txid, u32)>,
+    /// the block's 80-byte header
+    #[serde(default)]
+    pub raw_header: Vec<u8>,
+    /// inclusion proofs for this block's block-commits, so light clients can check that a
+    /// sortition's winning block-commit was really mined
+    #[serde(default)]
+    pub tx_proofs: Vec<BitcoinTxMerkleProof>,
 }
 
 impl BitcoinBlock {
@@ -243,6 +314,10 @@ impl BitcoinBlock {
             parent_block_hash: parent.clone(),
             txs: txs,
             timestamp: timestamp,
+            watched_outputs: vec![],
+            spent_outpoints: vec![],
+            raw_header: vec![],
+            tx_proofs: vec![],
         }
     }
 }
@@ -53,7 +53,7 @@ use crate::chainstate::burn::distribution::BurnSamplePoint;
 use crate::chainstate::burn::operations::{
     leader_block_commit::MissedBlockCommit, BlockstackOperationType, DelegateStxOp,
     LeaderBlockCommitOp, LeaderKeyRegisterOp, PreStxOp, StackStxOp, TransferStxOp,
-    UserBurnSupportOp,
+    UnparsedStacksOp, UserBurnSupportOp,
 };
 use crate::chainstate::burn::{BlockSnapshot, Opcodes};
 use crate::chainstate::coordinator::comm::CoordinatorChannels;
@@ -169,10 +169,6 @@ impl BurnchainStateTransition {
         let consumed_leader_keys =
             sort_tx.get_consumed_leader_keys(&parent_snapshot, &block_commits)?;
 
-        // assemble the commit windows
-        let mut windowed_block_commits = vec![block_commits];
-        let mut windowed_missed_commits = vec![];
-
         // what epoch are we in?
         let epoch_id = SortitionDB::get_stacks_epoch(sort_tx, parent_snapshot.block_height + 1)?
             .expect(&format!(
@@ -181,10 +177,81 @@ impl BurnchainStateTransition {
             ))
             .epoch_id;
 
-        if !burnchain.is_in_prepare_phase(parent_snapshot.block_height + 1)
+        // calculate the burn distribution from these operations.
+        // The resulting distribution will contain the user burns that match block commits
+        let burn_dist = BurnchainStateTransition::make_windowed_burn_distribution(
+            sort_tx,
+            burnchain,
+            parent_snapshot.block_height + 1,
+            epoch_id,
+            block_commits,
+            missed_commits,
+        )?;
+        BurnSamplePoint::prometheus_update_miner_commitments(&burn_dist);
+
+        // find out which user burns and block commits we're going to take
+        for i in 0..burn_dist.len() {
+            let burn_point = &burn_dist[i];
+
+            // taking this commit in this sample point
+            accepted_ops.push(BlockstackOperationType::LeaderBlockCommit(
+                burn_point.candidate.clone(),
+            ));
+            all_block_commits.remove(&burn_point.candidate.txid);
+
+            // taking each user burn in this sample point
+            for j in 0..burn_point.user_burns.len() {
+                accepted_ops.push(BlockstackOperationType::UserBurnSupport(
+                    burn_point.user_burns[j].clone(),
+                ));
+                all_user_burns.remove(&burn_point.user_burns[j].txid);
+            }
+        }
+
+        // accepted_ops contains all accepted commits and user burns now.
+        // only rejected ones remain in all_user_burns and all_block_commits
+        for op in all_block_commits.values() {
+            warn!(
+                "REJECTED({}) block commit {} at {},{}: Committed to an already-consumed VRF key",
+                op.block_height, &op.txid, op.block_height, op.vtxindex
+            );
+        }
+
+        for op in all_user_burns.values() {
+            warn!("REJECTED({}) user burn support {} at {},{}: No matching block commit in this block", op.block_height, &op.txid, op.block_height, op.vtxindex);
+        }
+
+        accepted_ops.sort_by(|ref a, ref b| a.vtxindex().partial_cmp(&b.vtxindex()).unwrap());
+
+        Ok(BurnchainStateTransition {
+            burn_dist,
+            accepted_ops,
+            consumed_leader_keys,
+        })
+    }
+
+    /// Assemble the mining commitment window for the sortition at `block_height` -- the
+    /// `block_commits` mined in that block, plus the block-commits and missed commits of the
+    /// sortitions before it on `sort_handle`'s fork -- and compute the burn distribution that the
+    /// sortition is drawn from.
+    /// `missed_commits` are the missed commits discovered in the block at `block_height`, which
+    /// are not yet stored.
+    pub fn make_windowed_burn_distribution<SH: SortitionHandle>(
+        sort_handle: &mut SH,
+        burnchain: &Burnchain,
+        block_height: u64,
+        epoch_id: StacksEpochId,
+        block_commits: Vec<LeaderBlockCommitOp>,
+        missed_commits: &[MissedBlockCommit],
+    ) -> Result<Vec<BurnSamplePoint>, db_error> {
+        // assemble the commit windows
+        let mut windowed_block_commits = vec![block_commits];
+        let mut windowed_missed_commits = vec![];
+
+        if !burnchain.is_in_prepare_phase(block_height)
             && !burnchain
                 .pox_constants
-                .is_after_pox_sunset_end(parent_snapshot.block_height + 1, epoch_id)
+                .is_after_pox_sunset_end(block_height, epoch_id)
         {
             // PoX reward-phase is active!
             // build a map of intended sortition -> missed commit for the missed commits
@@ -201,23 +268,25 @@ impl BurnchainStateTransition {
             }
 
             for blocks_back in 0..(MINING_COMMITMENT_WINDOW - 1) {
-                if parent_snapshot.block_height < (blocks_back as u64) {
+                if block_height < (blocks_back as u64) + 1 {
                     debug!("Mining commitment window shortened because block height is less than window size";
-                           "block_height" => %parent_snapshot.block_height,
+                           "block_height" => %block_height,
                            "window_size" => %MINING_COMMITMENT_WINDOW);
                     break;
                 }
-                let block_height = parent_snapshot.block_height - (blocks_back as u64);
-                let sortition_id = match sort_tx.get_block_snapshot_by_height(block_height)? {
+                let window_height = block_height - 1 - (blocks_back as u64);
+                let sortition_id = match sort_handle.get_block_snapshot_by_height(window_height)? {
                     Some(sn) => sn.sortition_id,
                     None => break,
                 };
                 windowed_block_commits.push(SortitionDB::get_block_commits_by_block(
-                    sort_tx.tx(),
+                    sort_handle.sqlite(),
                     &sortition_id,
                 )?);
-                let mut missed_commits_at_height =
-                    SortitionDB::get_missed_commits_by_intended(sort_tx.tx(), &sortition_id)?;
+                let mut missed_commits_at_height = SortitionDB::get_missed_commits_by_intended(
+                    sort_handle.sqlite(),
+                    &sortition_id,
+                )?;
                 if let Some(missed_commit_in_block) = missed_commits_map.remove(&sortition_id) {
                     missed_commits_at_height
                         .extend(missed_commit_in_block.into_iter().map(|x| x.clone()));
@@ -229,7 +298,7 @@ impl BurnchainStateTransition {
             // PoX reward-phase is not active
             debug!(
                 "Block {} is in a prepare phase or post-PoX sunset, so no windowing will take place",
-                parent_snapshot.block_height + 1
+                block_height
             );
 
             assert_eq!(windowed_block_commits.len(), 1);
@@ -242,7 +311,7 @@ impl BurnchainStateTransition {
 
         // figure out if the PoX sunset finished during the window,
         // and/or which sortitions must be PoB due to them falling in a prepare phase.
-        let window_end_height = parent_snapshot.block_height + 1;
+        let window_end_height = block_height;
         let window_start_height = window_end_height + 1 - (windowed_block_commits.len() as u64);
         let mut burn_blocks = vec![false; windowed_block_commits.len()];
 
@@ -264,54 +333,11 @@ impl BurnchainStateTransition {
             }
         }
 
-        // calculate the burn distribution from these operations.
-        // The resulting distribution will contain the user burns that match block commits
-        let burn_dist = BurnSamplePoint::make_min_median_distribution(
+        Ok(BurnSamplePoint::make_min_median_distribution(
             windowed_block_commits,
             windowed_missed_commits,
             burn_blocks,
-        );
-        BurnSamplePoint::prometheus_update_miner_commitments(&burn_dist);
-
-        // find out which user burns and block commits we're going to take
-        for i in 0..burn_dist.len() {
-            let burn_point = &burn_dist[i];
-
-            // taking this commit in this sample point
-            accepted_ops.push(BlockstackOperationType::LeaderBlockCommit(
-                burn_point.candidate.clone(),
-            ));
-            all_block_commits.remove(&burn_point.candidate.txid);
-
-            // taking each user burn in this sample point
-            for j in 0..burn_point.user_burns.len() {
-                accepted_ops.push(BlockstackOperationType::UserBurnSupport(
-                    burn_point.user_burns[j].clone(),
-                ));
-                all_user_burns.remove(&burn_point.user_burns[j].txid);
-            }
-        }
-
-        // accepted_ops contains all accepted commits and user burns now.
-        // only rejected ones remain in all_user_burns and all_block_commits
-        for op in all_block_commits.values() {
-            warn!(
-                "REJECTED({}) block commit {} at {},{}: Committed to an already-consumed VRF key",
-                op.block_height, &op.txid, op.block_height, op.vtxindex
-            );
-        }
-
-        for op in all_user_burns.values() {
-            warn!("REJECTED({}) user burn support {} at {},{}: No matching block commit in this block", op.block_height, &op.txid, op.block_height, op.vtxindex);
-        }
-
-        accepted_ops.sort_by(|ref a, ref b| a.vtxindex().partial_cmp(&b.vtxindex()).unwrap());
-
-        Ok(BurnchainStateTransition {
-            burn_dist,
-            accepted_ops,
-            consumed_leader_keys,
-        })
+        ))
     }
 }
 
@@ -728,9 +754,31 @@ impl Burnchain {
         Ok((sort_db, burn_db))
     }
 
+    /// Describe a burnchain transaction with a Stacks operation's opcode that could not be
+    /// parsed into one.
+    fn unparsed_stacks_op(
+        block_header: &BurnchainBlockHeader,
+        burn_tx: &BurnchainTransaction,
+        op_type: &str,
+        sender: Option<&StacksAddress>,
+        reason: String,
+    ) -> UnparsedStacksOp {
+        UnparsedStacksOp {
+            op_type: op_type.to_string(),
+            sender: sender.cloned(),
+            reason,
+            txid: burn_tx.txid(),
+            vtxindex: burn_tx.vtxindex(),
+            block_height: block_header.block_height,
+            burn_header_hash: block_header.block_hash.clone(),
+        }
+    }
+
     /// Try to parse a burnchain transaction into a Blockstack operation
     /// `pre_stx_op_map` should contain any valid PreStxOps that occurred before
     ///   the currently-being-evaluated tx in the same burn block.
+    /// Transactions with a Stacks operation's opcode that fail to parse are added to
+    /// `unparsed_stacks_ops`, so their senders can find out why.
     pub fn classify_transaction<B: BurnchainHeaderReader>(
         burnchain: &Burnchain,
         indexer: &B,
@@ -739,6 +787,7 @@ impl Burnchain {
         epoch_id: StacksEpochId,
         burn_tx: &BurnchainTransaction,
         pre_stx_op_map: &HashMap<Txid, PreStxOp>,
+        unparsed_stacks_ops: &mut Vec<UnparsedStacksOp>,
     ) -> Option<BlockstackOperationType> {
         match burn_tx.opcode() {
             x if x == Opcodes::LeaderKeyRegister as u8 => {
@@ -798,12 +847,32 @@ impl Burnchain {
                             "data" => %to_hex(&burn_tx.data()),
                             "error" => ?e,
                         );
+                        unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                            block_header,
+                            burn_tx,
+                            "pre-stx",
+                            None,
+                            e.to_string(),
+                        ));
                         None
                     }
                 }
             }
             x if x == Opcodes::TransferStx as u8 => {
-                let pre_stx_txid = TransferStxOp::get_sender_txid(burn_tx).ok()?;
+                let pre_stx_txid = match TransferStxOp::get_sender_txid(burn_tx) {
+                    Ok(pre_stx_txid) => pre_stx_txid,
+                    Err(_e) => {
+                        unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                            block_header,
+                            burn_tx,
+                            "transfer-stx",
+                            None,
+                            "Its first input does not spend the second output of a pre-stx operation"
+                                .to_string(),
+                        ));
+                        return None;
+                    }
+                };
                 let pre_stx_tx = match pre_stx_op_map.get(&pre_stx_txid) {
                     Some(tx_ref) => Some(BlockstackOperationType::PreStx(tx_ref.clone())),
                     None => burnchain_db.find_burnchain_op(indexer, pre_stx_txid),
@@ -819,6 +888,13 @@ impl Burnchain {
                                 "data" => %to_hex(&burn_tx.data()),
                                 "error" => ?e,
                             );
+                            unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                                block_header,
+                                burn_tx,
+                                "transfer-stx",
+                                Some(sender),
+                                e.to_string(),
+                            ));
                             None
                         }
                     }
@@ -828,11 +904,34 @@ impl Burnchain {
                         "txid" => %burn_tx.txid(),
                         "pre_stx_txid" => %pre_stx_txid
                     );
+                    unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                        block_header,
+                        burn_tx,
+                        "transfer-stx",
+                        None,
+                        format!(
+                            "No pre-stx operation found at its first input ({})",
+                            pre_stx_txid
+                        ),
+                    ));
                     None
                 }
             }
             x if x == Opcodes::StackStx as u8 => {
-                let pre_stx_txid = StackStxOp::get_sender_txid(burn_tx).ok()?;
+                let pre_stx_txid = match StackStxOp::get_sender_txid(burn_tx) {
+                    Ok(pre_stx_txid) => pre_stx_txid,
+                    Err(_e) => {
+                        unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                            block_header,
+                            burn_tx,
+                            "stack-stx",
+                            None,
+                            "Its first input does not spend the second output of a pre-stx operation"
+                                .to_string(),
+                        ));
+                        return None;
+                    }
+                };
                 let pre_stx_tx = match pre_stx_op_map.get(&pre_stx_txid) {
                     Some(tx_ref) => Some(BlockstackOperationType::PreStx(tx_ref.clone())),
                     None => burnchain_db.find_burnchain_op(indexer, pre_stx_txid),
@@ -854,6 +953,13 @@ impl Burnchain {
                                 "data" => %to_hex(&burn_tx.data()),
                                 "error" => ?e,
                             );
+                            unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                                block_header,
+                                burn_tx,
+                                "stack-stx",
+                                Some(sender),
+                                e.to_string(),
+                            ));
                             None
                         }
                     }
@@ -863,11 +969,34 @@ impl Burnchain {
                         "txid" => %burn_tx.txid().to_string(),
                         "pre_stx_txid" => %pre_stx_txid.to_string()
                     );
+                    unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                        block_header,
+                        burn_tx,
+                        "stack-stx",
+                        None,
+                        format!(
+                            "No pre-stx operation found at its first input ({})",
+                            pre_stx_txid
+                        ),
+                    ));
                     None
                 }
             }
             x if x == Opcodes::DelegateStx as u8 => {
-                let pre_stx_txid = DelegateStxOp::get_sender_txid(burn_tx).ok()?;
+                let pre_stx_txid = match DelegateStxOp::get_sender_txid(burn_tx) {
+                    Ok(pre_stx_txid) => pre_stx_txid,
+                    Err(_e) => {
+                        unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                            block_header,
+                            burn_tx,
+                            "delegate-stx",
+                            None,
+                            "Its first input does not spend the second output of a pre-stx operation"
+                                .to_string(),
+                        ));
+                        return None;
+                    }
+                };
                 let pre_stx_tx = match pre_stx_op_map.get(&pre_stx_txid) {
                     Some(tx_ref) => Some(BlockstackOperationType::PreStx(tx_ref.clone())),
                     None => burnchain_db.find_burnchain_op(indexer, pre_stx_txid),
@@ -883,6 +1012,13 @@ impl Burnchain {
                                 "data" => %to_hex(&burn_tx.data()),
                                 "error" => ?e,
                             );
+                            unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                                block_header,
+                                burn_tx,
+                                "delegate-stx",
+                                Some(sender),
+                                e.to_string(),
+                            ));
                             None
                         }
                     }
@@ -892,6 +1028,16 @@ impl Burnchain {
                         "txid" => %burn_tx.txid().to_string(),
                         "pre_stx_txid" => %pre_stx_txid.to_string()
                     );
+                    unparsed_stacks_ops.push(Burnchain::unparsed_stacks_op(
+                        block_header,
+                        burn_tx,
+                        "delegate-stx",
+                        None,
+                        format!(
+                            "No pre-stx operation found at its first input ({})",
+                            pre_stx_txid
+                        ),
+                    ));
                     None
                 }
             }
@@ -25,10 +25,14 @@ use rusqlite::{
 use serde_json;
 
 use crate::burnchains::affirmation::*;
+use crate::burnchains::bitcoin::{
+    BitcoinBlock, BitcoinPendingTx, BitcoinTxMerkleProof, BitcoinWatchedOutput,
+};
 use crate::burnchains::Txid;
 use crate::burnchains::{Burnchain, BurnchainBlock, BurnchainBlockHeader, Error as BurnchainError};
 use crate::chainstate::burn::operations::BlockstackOperationType;
 use crate::chainstate::burn::operations::LeaderBlockCommitOp;
+use crate::chainstate::burn::operations::UnparsedStacksOp;
 use crate::chainstate::burn::BlockSnapshot;
 use crate::chainstate::stacks::index::MarfTrieId;
 use crate::util_lib::db::{
@@ -38,6 +42,7 @@ use crate::util_lib::db::{
 
 use crate::chainstate::stacks::index::ClarityMarfTrieId;
 use stacks_common::types::chainstate::BurnchainHeaderHash;
+use stacks_common::util::hash::{hex_bytes, to_hex};
 
 use crate::core::StacksEpochId;
 
@@ -201,6 +206,52 @@ impl FromRow<BurnchainBlockHeader> for BurnchainBlockHeader {
     }
 }
 
+impl FromRow<BitcoinWatchedOutput> for BitcoinWatchedOutput {
+    fn from_row(row: &Row) -> Result<BitcoinWatchedOutput, DBError> {
+        let txid = Txid::from_column(row, "txid")?;
+        let vout: u32 = row.get_unwrap("vout");
+        let script_pubkey_hex: String = row.get_unwrap("script_pubkey");
+        let script_pubkey = hex_bytes(&script_pubkey_hex).map_err(|_| DBError::ParseError)?;
+        let amount = u64::from_column(row, "amount")?;
+
+        Ok(BitcoinWatchedOutput {
+            txid,
+            vout,
+            script_pubkey,
+            amount,
+        })
+    }
+}
+
+impl FromRow<BitcoinTxMerkleProof> for BitcoinTxMerkleProof {
+    fn from_row(row: &Row) -> Result<BitcoinTxMerkleProof, DBError> {
+        let txid = Txid::from_column(row, "txid")?;
+        let vtxindex: u32 = row.get_unwrap("vtxindex");
+        let raw_tx_hex: String = row.get_unwrap("raw_tx");
+        let raw_tx = hex_bytes(&raw_tx_hex).map_err(|_| DBError::ParseError)?;
+        let merkle_branch_hex: String = row.get_unwrap("merkle_branch");
+        let merkle_branch_bytes = hex_bytes(&merkle_branch_hex).map_err(|_| DBError::ParseError)?;
+        if merkle_branch_bytes.len() % 32 != 0 {
+            return Err(DBError::ParseError);
+        }
+        let merkle_branch = merkle_branch_bytes
+            .chunks(32)
+            .map(|chunk| {
+                let mut hash = [0u8; 32];
+                hash.copy_from_slice(chunk);
+                hash
+            })
+            .collect();
+
+        Ok(BitcoinTxMerkleProof {
+            txid,
+            vtxindex,
+            raw_tx,
+            merkle_branch,
+        })
+    }
+}
+
 impl FromRow<BlockstackOperationType> for BlockstackOperationType {
     fn from_row(row: &Row) -> Result<BlockstackOperationType, DBError> {
         let serialized: String = row.get_unwrap("op");
@@ -211,7 +262,11 @@ impl FromRow<BlockstackOperationType> for BlockstackOperationType {
     }
 }
 
-pub const BURNCHAIN_DB_VERSION: &'static str = "2";
+pub const BURNCHAIN_DB_VERSION: &'static str = "6";
+
+/// Number of burnchain blocks after which a pending transaction that has not been mined is
+/// no longer counted, so the outputs it spends become spendable again.
+pub const PENDING_TX_LIFETIME: u64 = 6;
 
 const BURNCHAIN_DB_SCHEMA: &'static str = r#"
 CREATE TABLE burnchain_db_block_headers (
@@ -298,6 +353,92 @@ CREATE TABLE db_config(version TEXT NOT NULL);
 INSERT INTO affirmation_maps(affirmation_id,weight,affirmation_map) VALUES (0,0,"");
 "#;
 
+const BURNCHAIN_DB_SCHEMA_3: &'static [&'static str] = &[
+    r#"
+    -- outputs that pay to one of the scriptPubKeys the indexer watches (e.g. the miner's address).
+    -- since the burnchain can fork, the same output can appear in more than one block.
+    CREATE TABLE bitcoin_watched_outputs (
+        -- 32-byte hash of the burnchain block that contains the transaction that created this output
+        burn_block_hash TEXT NOT NULL,
+        -- height of that burnchain block
+        block_height INTEGER NOT NULL,
+        -- 32-byte transaction ID and output index
+        txid TEXT NOT NULL,
+        vout INTEGER NOT NULL,
+        -- hex-encoded scriptPubKey
+        script_pubkey TEXT NOT NULL,
+        -- value of this output, in satoshis
+        amount INTEGER NOT NULL,
+
+        PRIMARY KEY(burn_block_hash,txid,vout)
+    );"#,
+    r#"
+    -- outpoints of watched outputs that were consumed by a transaction in a burnchain block
+    CREATE TABLE bitcoin_spent_outpoints (
+        -- 32-byte hash of the burnchain block that contains the spending transaction
+        burn_block_hash TEXT NOT NULL,
+        -- height of that burnchain block
+        block_height INTEGER NOT NULL,
+        -- the spent outpoint
+        txid TEXT NOT NULL,
+        vout INTEGER NOT NULL,
+
+        PRIMARY KEY(burn_block_hash,txid,vout)
+    );"#,
+    "CREATE INDEX index_bitcoin_watched_outputs_script_pubkey ON bitcoin_watched_outputs(script_pubkey);",
+    "CREATE INDEX index_bitcoin_watched_outputs_txid_vout ON bitcoin_watched_outputs(txid,vout);",
+    "CREATE INDEX index_bitcoin_spent_outpoints_txid_vout ON bitcoin_spent_outpoints(txid,vout);",
+];
+
+const BURNCHAIN_DB_SCHEMA_4: &'static [&'static str] = &[
+    r#"
+    -- raw 80-byte headers of burnchain blocks, so light clients can be shown the Bitcoin block
+    -- a sortition happened in
+    CREATE TABLE bitcoin_block_raw_headers (
+        -- 32-byte hash of the burnchain block
+        burn_block_hash TEXT PRIMARY KEY NOT NULL,
+        -- hex-encoded header
+        raw_header TEXT NOT NULL
+    );"#,
+    r#"
+    -- proofs that block-commits were mined in the burnchain blocks that contain them
+    CREATE TABLE bitcoin_tx_merkle_proofs (
+        -- 32-byte hash of the burnchain block that contains the transaction
+        burn_block_hash TEXT NOT NULL,
+        -- 32-byte transaction ID, and the transaction's index in the block
+        txid TEXT NOT NULL,
+        vtxindex INTEGER NOT NULL,
+        -- hex-encoded transaction, without witness data
+        raw_tx TEXT NOT NULL,
+        -- hex-encoded concatenation of the 32-byte sibling hashes from the leaf to the root
+        merkle_branch TEXT NOT NULL,
+
+        PRIMARY KEY(burn_block_hash,txid)
+    );"#,
+];
+
+const BURNCHAIN_DB_SCHEMA_5: &'static [&'static str] = &[r#"
+    -- transactions with a Stacks operation's opcode that could not be parsed, and why
+    CREATE TABLE unparsed_stacks_ops (
+        block_hash TEXT NOT NULL,
+        txid TEXT NOT NULL,
+        -- JSON-encoded UnparsedStacksOp
+        unparsed_op TEXT NOT NULL,
+
+        PRIMARY KEY(block_hash,txid)
+    );"#];
+
+const BURNCHAIN_DB_SCHEMA_6: &'static [&'static str] = &[r#"
+    -- transactions the node broadcast that spend watched outputs, but which have not been seen
+    -- in a burnchain block yet.
+    CREATE TABLE bitcoin_pending_txs (
+        txid TEXT PRIMARY KEY,
+        -- height of the canonical burnchain tip when the transaction was broadcast
+        broadcast_height INTEGER NOT NULL,
+        -- JSON-encoded BitcoinPendingTx
+        pending_tx TEXT NOT NULL
+    );"#];
+
 const LAST_BURNCHAIN_DB_INDEX: &'static str =
     "index_block_commit_metadata_burn_block_hash_anchor_block";
 const BURNCHAIN_DB_INDEXES: &'static [&'static str] = &[
@@ -918,6 +1059,109 @@ impl<'a> BurnchainDBTransaction<'a> {
         Ok(())
     }
 
+    /// Store the watched outputs created by a Bitcoin block, as well as the outpoints of any
+    /// watched outputs it spends.  Spent outpoints that do not refer to a watched output are
+    /// dropped, so the tables only ever grow with the watched scripts' activity.
+    fn store_bitcoin_utxo_changes(&self, block: &BitcoinBlock) -> Result<(), BurnchainError> {
+        let sql = "INSERT OR REPLACE INTO bitcoin_watched_outputs
+                   (burn_block_hash, block_height, txid, vout, script_pubkey, amount)
+                   VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
+        let mut stmt = self.sql_tx.prepare(sql)?;
+        for output in block.watched_outputs.iter() {
+            let args: &[&dyn ToSql] = &[
+                &block.block_hash,
+                &u64_to_sql(block.block_height)?,
+                &output.txid,
+                &output.vout,
+                &to_hex(&output.script_pubkey),
+                &u64_to_sql(output.amount)?,
+            ];
+            stmt.execute(args)?;
+        }
+
+        let mut num_spent = 0;
+        let sql = "INSERT OR REPLACE INTO bitcoin_spent_outpoints
+                   (burn_block_hash, block_height, txid, vout)
+                   VALUES (?1, ?2, ?3, ?4)";
+        let mut stmt = self.sql_tx.prepare(sql)?;
+        for (txid, vout) in block.spent_outpoints.iter() {
+            let qry = "SELECT 1 FROM bitcoin_watched_outputs WHERE txid = ?1 AND vout = ?2 LIMIT 1";
+            let args: &[&dyn ToSql] = &[txid, vout];
+            let watched: Option<i64> = query_row(&self.sql_tx, qry, args)?;
+            if watched.is_none() {
+                continue;
+            }
+
+            let args: &[&dyn ToSql] = &[
+                &block.block_hash,
+                &u64_to_sql(block.block_height)?,
+                txid,
+                vout,
+            ];
+            stmt.execute(args)?;
+            num_spent += 1;
+        }
+
+        if !block.watched_outputs.is_empty() || num_spent > 0 {
+            debug!(
+                "Burnchain block {} at height {} creates {} and spends {} watched outputs",
+                &block.block_hash,
+                block.block_height,
+                block.watched_outputs.len(),
+                num_spent
+            );
+        }
+        Ok(())
+    }
+
+    /// Store a Bitcoin block's raw header and the merkle proofs of its block-commits.
+    fn store_bitcoin_block_proofs(&self, block: &BitcoinBlock) -> Result<(), BurnchainError> {
+        if !block.raw_header.is_empty() {
+            let sql = "INSERT OR REPLACE INTO bitcoin_block_raw_headers
+                       (burn_block_hash, raw_header) VALUES (?1, ?2)";
+            let args: &[&dyn ToSql] = &[&block.block_hash, &to_hex(&block.raw_header)];
+            self.sql_tx.execute(sql, args)?;
+        }
+
+        let sql = "INSERT OR REPLACE INTO bitcoin_tx_merkle_proofs
+                   (burn_block_hash, txid, vtxindex, raw_tx, merkle_branch)
+                   VALUES (?1, ?2, ?3, ?4, ?5)";
+        let mut stmt = self.sql_tx.prepare(sql)?;
+        for proof in block.tx_proofs.iter() {
+            let args: &[&dyn ToSql] = &[
+                &block.block_hash,
+                &proof.txid,
+                &proof.vtxindex,
+                &to_hex(&proof.raw_tx),
+                &to_hex(&proof.merkle_branch.concat()),
+            ];
+            stmt.execute(args)?;
+        }
+        Ok(())
+    }
+
+    /// Store the transactions in a burnchain block that have a Stacks operation's opcode, but
+    /// could not be parsed.
+    fn store_unparsed_stacks_ops(
+        &self,
+        block_header: &BurnchainBlockHeader,
+        unparsed_ops: &[UnparsedStacksOp],
+    ) -> Result<(), BurnchainError> {
+        let sql = "INSERT OR REPLACE INTO unparsed_stacks_ops (block_hash, txid, unparsed_op) VALUES (?1, ?2, ?3)";
+        let mut stmt = self.sql_tx.prepare(sql)?;
+        for unparsed_op in unparsed_ops.iter() {
+            let unparsed_op_json =
+                serde_json::to_string(unparsed_op).map_err(|_| DBError::ParseError)?;
+            let args: &[&dyn ToSql] = &[
+                &block_header.block_hash,
+                &unparsed_op.txid,
+                &unparsed_op_json,
+            ];
+            stmt.execute(args)?;
+        }
+        Ok(())
+    }
+
     pub fn commit(self) -> Result<(), BurnchainError> {
         self.sql_tx.commit().map_err(BurnchainError::from)
     }
@@ -957,6 +1201,89 @@ impl<'a> BurnchainDBTransaction<'a> {
 }
 
 impl BurnchainDB {
+    /// Add the tables used to track watched Bitcoin outputs
+    fn apply_schema_3(tx: &Transaction) -> Result<(), BurnchainError> {
+        for sql_exec in BURNCHAIN_DB_SCHEMA_3 {
+            tx.execute_batch(sql_exec)?;
+        }
+        tx.execute("UPDATE db_config SET version = ?1", &["3"])?;
+        Ok(())
+    }
+
+    /// Add the tables used to prove sortitions to light clients
+    fn apply_schema_4(tx: &Transaction) -> Result<(), BurnchainError> {
+        for sql_exec in BURNCHAIN_DB_SCHEMA_4 {
+            tx.execute_batch(sql_exec)?;
+        }
+        tx.execute("UPDATE db_config SET version = ?1", &["4"])?;
+        Ok(())
+    }
+
+    /// Add the table of transactions whose Stacks operations could not be parsed
+    fn apply_schema_5(tx: &Transaction) -> Result<(), BurnchainError> {
+        for sql_exec in BURNCHAIN_DB_SCHEMA_5 {
+            tx.execute_batch(sql_exec)?;
+        }
+        tx.execute("UPDATE db_config SET version = ?1", &["5"])?;
+        Ok(())
+    }
+
+    /// Add the table of broadcast transactions that have not been mined yet
+    fn apply_schema_6(tx: &Transaction) -> Result<(), BurnchainError> {
+        for sql_exec in BURNCHAIN_DB_SCHEMA_6 {
+            tx.execute_batch(sql_exec)?;
+        }
+        tx.execute("UPDATE db_config SET version = ?1", &["6"])?;
+        Ok(())
+    }
+
+    fn get_schema_version(conn: &DBConn) -> Result<Option<String>, BurnchainError> {
+        let version = conn
+            .query_row("SELECT version FROM db_config LIMIT 1", NO_PARAMS, |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(version)
+    }
+
+    /// Bring an existing database up to the current schema version
+    fn instantiate_migrations(&mut self) -> Result<(), BurnchainError> {
+        loop {
+            let version = BurnchainDB::get_schema_version(self.conn())?;
+            match version.as_deref() {
+                Some("2") => {
+                    let db_tx = self.tx_begin()?;
+                    BurnchainDB::apply_schema_3(&db_tx.sql_tx)?;
+                    db_tx.commit()?;
+                }
+                Some("3") => {
+                    let db_tx = self.tx_begin()?;
+                    BurnchainDB::apply_schema_4(&db_tx.sql_tx)?;
+                    db_tx.commit()?;
+                }
+                Some("4") => {
+                    let db_tx = self.tx_begin()?;
+                    BurnchainDB::apply_schema_5(&db_tx.sql_tx)?;
+                    db_tx.commit()?;
+                }
+                Some("5") => {
+                    let db_tx = self.tx_begin()?;
+                    BurnchainDB::apply_schema_6(&db_tx.sql_tx)?;
+                    db_tx.commit()?;
+                }
+                Some(version) if version == BURNCHAIN_DB_VERSION => {
+                    return Ok(());
+                }
+                _ => {
+                    panic!(
+                        "The schema version of the burnchain DB is invalid: {:?}",
+                        &version
+                    );
+                }
+            }
+        }
+    }
+
     fn add_indexes(&mut self) -> Result<(), BurnchainError> {
         let exists: i64 = query_row(
             self.conn(),
@@ -1017,10 +1344,13 @@ impl BurnchainDB {
         if create_flag {
             let db_tx = db.tx_begin()?;
             db_tx.sql_tx.execute_batch(BURNCHAIN_DB_SCHEMA)?;
-            db_tx.sql_tx.execute(
-                "INSERT INTO db_config (version) VALUES (?1)",
-                &[&BURNCHAIN_DB_VERSION],
-            )?;
+            db_tx
+                .sql_tx
+                .execute("INSERT INTO db_config (version) VALUES (?1)", &["2"])?;
+            BurnchainDB::apply_schema_3(&db_tx.sql_tx)?;
+            BurnchainDB::apply_schema_4(&db_tx.sql_tx)?;
+            BurnchainDB::apply_schema_5(&db_tx.sql_tx)?;
+            BurnchainDB::apply_schema_6(&db_tx.sql_tx)?;
 
             let first_block_header = BurnchainBlockHeader {
                 block_height: burnchain.first_block_height,
@@ -1055,6 +1385,7 @@ impl BurnchainDB {
         }
 
         if readwrite {
+            db.instantiate_migrations()?;
             db.add_indexes()?;
         }
         Ok(db)
@@ -1070,6 +1401,7 @@ impl BurnchainDB {
         let mut db = BurnchainDB { conn };
 
         if readwrite {
+            db.instantiate_migrations()?;
             db.add_indexes()?;
         }
         Ok(db)
@@ -1182,8 +1514,239 @@ impl BurnchainDB {
         None
     }
 
+    /// Get the unspent watched outputs on the canonical burnchain fork that pay to one of the
+    /// given scriptPubKeys and are worth at least `min_amount` satoshis.
+    /// Returns each output along with the height of the burnchain block that created it.
+    pub fn get_unspent_watched_outputs<B: BurnchainHeaderReader>(
+        &self,
+        indexer: &B,
+        script_pubkeys: &[Vec<u8>],
+        min_amount: u64,
+    ) -> Result<Vec<(BitcoinWatchedOutput, u64)>, BurnchainError> {
+        // memoize canonical-fork checks, since most outputs share only a few blocks
+        let mut canonical_cache: HashMap<BurnchainHeaderHash, bool> = HashMap::new();
+        let mut is_canonical = |burn_block_hash: &BurnchainHeaderHash| -> Result<bool, DBError> {
+            if let Some(canonical) = canonical_cache.get(burn_block_hash) {
+                return Ok(*canonical);
+            }
+            let canonical = indexer
+                .find_burnchain_header_height(burn_block_hash)?
+                .is_some();
+            canonical_cache.insert(*burn_block_hash, canonical);
+            Ok(canonical)
+        };
+
+        let mut ret = vec![];
+        let mut seen = HashSet::new();
+        for script_pubkey in script_pubkeys.iter() {
+            let qry = "SELECT * FROM bitcoin_watched_outputs WHERE script_pubkey = ?1 AND amount >= ?2 ORDER BY block_height ASC, txid ASC, vout ASC";
+            let args: &[&dyn ToSql] = &[&to_hex(script_pubkey), &u64_to_sql(min_amount)?];
+            let mut stmt = self.conn.prepare(qry)?;
+            let mut rows = stmt.query(args)?;
+            while let Some(row) = rows.next()? {
+                let output = BitcoinWatchedOutput::from_row(row)?;
+                let burn_block_hash = BurnchainHeaderHash::from_column(row, "burn_block_hash")?;
+                let block_height = u64::from_column(row, "block_height")?;
+
+                if !is_canonical(&burn_block_hash)? {
+                    continue;
+                }
+                if !seen.insert((output.txid, output.vout)) {
+                    continue;
+                }
+
+                let qry = "SELECT burn_block_hash AS burn_header_hash FROM bitcoin_spent_outpoints WHERE txid = ?1 AND vout = ?2";
+                let args: &[&dyn ToSql] = &[&output.txid, &output.vout];
+                let spenders: Vec<BurnchainHeaderHash> = query_rows(&self.conn, qry, args)?;
+                let mut spent = false;
+                for spender in spenders.iter() {
+                    if is_canonical(spender)? {
+                        spent = true;
+                        break;
+                    }
+                }
+                if spent {
+                    continue;
+                }
+
+                ret.push((output, block_height));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Get the raw 80-byte header of a Bitcoin block.  Returns None if the block was stored before
+    /// raw headers were recorded.
+    pub fn get_bitcoin_raw_header(
+        conn: &DBConn,
+        burn_block_hash: &BurnchainHeaderHash,
+    ) -> Result<Option<Vec<u8>>, DBError> {
+        let qry = "SELECT raw_header FROM bitcoin_block_raw_headers WHERE burn_block_hash = ?1";
+        let args: &[&dyn ToSql] = &[burn_block_hash];
+        let raw_header_hex: Option<String> = query_row(conn, qry, args)?;
+        raw_header_hex
+            .map(|hex| hex_bytes(&hex).map_err(|_| DBError::ParseError))
+            .transpose()
+    }
+
+    /// Get the merkle proof that a block-commit was mined in the given Bitcoin block.  Returns
+    /// None if there is no such block-commit, or if its block was stored before proofs were
+    /// recorded.
+    pub fn get_bitcoin_tx_merkle_proof(
+        conn: &DBConn,
+        burn_block_hash: &BurnchainHeaderHash,
+        txid: &Txid,
+    ) -> Result<Option<BitcoinTxMerkleProof>, DBError> {
+        let qry = "SELECT * FROM bitcoin_tx_merkle_proofs WHERE burn_block_hash = ?1 AND txid = ?2";
+        let args: &[&dyn ToSql] = &[burn_block_hash, txid];
+        query_row(conn, qry, args)
+    }
+
+    /// Get the transactions in the given burnchain block that have a Stacks operation's opcode,
+    /// but could not be parsed, in block order.
+    pub fn get_unparsed_stacks_ops(
+        conn: &DBConn,
+        block_hash: &BurnchainHeaderHash,
+    ) -> Result<Vec<UnparsedStacksOp>, DBError> {
+        let qry = "SELECT unparsed_op FROM unparsed_stacks_ops WHERE block_hash = ?1";
+        let args: &[&dyn ToSql] = &[block_hash];
+        let unparsed_ops_json: Vec<String> = query_rows(conn, qry, args)?;
+        let mut unparsed_ops = unparsed_ops_json
+            .iter()
+            .map(|unparsed_op_json| {
+                serde_json::from_str::<UnparsedStacksOp>(unparsed_op_json)
+                    .map_err(|_| DBError::ParseError)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        unparsed_ops.sort_by_key(|unparsed_op| unparsed_op.vtxindex);
+        Ok(unparsed_ops)
+    }
+
+    /// Record a transaction the node broadcast that spends watched outputs.  Any pending
+    /// transaction that spends one of the same outpoints was replaced by it, and is dropped.  So
+    /// are pending transactions broadcast more than `PENDING_TX_LIFETIME` blocks before it.
+    pub fn store_pending_tx(
+        &mut self,
+        pending_tx: &BitcoinPendingTx,
+    ) -> Result<(), BurnchainError> {
+        let db_tx = self.tx_begin()?;
+        let existing: Vec<String> = query_rows(
+            &db_tx.sql_tx,
+            "SELECT pending_tx FROM bitcoin_pending_txs",
+            NO_PARAMS,
+        )?;
+        for existing_json in existing.into_iter() {
+            let existing: BitcoinPendingTx =
+                serde_json::from_str(&existing_json).map_err(|_| DBError::ParseError)?;
+            let replaced = existing
+                .spent_outpoints
+                .iter()
+                .any(|outpoint| pending_tx.spent_outpoints.contains(outpoint));
+            let expired =
+                existing.broadcast_height + PENDING_TX_LIFETIME < pending_tx.broadcast_height;
+            if replaced || expired {
+                db_tx.sql_tx.execute(
+                    "DELETE FROM bitcoin_pending_txs WHERE txid = ?1",
+                    &[&existing.txid],
+                )?;
+            }
+        }
+
+        let pending_tx_json = serde_json::to_string(pending_tx).map_err(|_| DBError::ParseError)?;
+        let args: &[&dyn ToSql] = &[
+            &pending_tx.txid,
+            &u64_to_sql(pending_tx.broadcast_height)?,
+            &pending_tx_json,
+        ];
+        db_tx.sql_tx.execute(
+            "INSERT OR REPLACE INTO bitcoin_pending_txs (txid, broadcast_height, pending_tx) VALUES (?1, ?2, ?3)",
+            args,
+        )?;
+        db_tx.commit()
+    }
+
+    /// Is the given watched outpoint spent by a burnchain block on the canonical fork?
+    fn is_watched_outpoint_spent<B: BurnchainHeaderReader>(
+        &self,
+        indexer: &B,
+        txid: &Txid,
+        vout: u32,
+    ) -> Result<bool, BurnchainError> {
+        let qry = "SELECT burn_block_hash AS burn_header_hash FROM bitcoin_spent_outpoints WHERE txid = ?1 AND vout = ?2";
+        let args: &[&dyn ToSql] = &[txid, &vout];
+        let spenders: Vec<BurnchainHeaderHash> = query_rows(&self.conn, qry, args)?;
+        for spender in spenders.iter() {
+            if indexer.find_burnchain_header_height(spender)?.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Get the watched outputs that pay to one of the given scriptPubKeys, are worth at least
+    /// `min_amount` satoshis, and can be spent now: the unspent outputs on the canonical
+    /// burnchain fork, less the ones spent by pending transactions, plus the outputs of pending
+    /// transactions.  A pending transaction counts until one of its inputs is spent on the
+    /// canonical fork (by it or by a conflicting transaction), or if it was broadcast at a height
+    /// below `min_broadcast_height`.
+    /// Returns each output along with the height of the burnchain block that created it, which
+    /// is None for the outputs of pending transactions.
+    pub fn get_spendable_watched_outputs<B: BurnchainHeaderReader>(
+        &self,
+        indexer: &B,
+        script_pubkeys: &[Vec<u8>],
+        min_amount: u64,
+        min_broadcast_height: u64,
+    ) -> Result<Vec<(BitcoinWatchedOutput, Option<u64>)>, BurnchainError> {
+        let qry = "SELECT pending_tx FROM bitcoin_pending_txs WHERE broadcast_height >= ?1 ORDER BY broadcast_height ASC, txid ASC";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(min_broadcast_height)?];
+        let pending_txs_json: Vec<String> = query_rows(&self.conn, qry, args)?;
+
+        let mut pending_txs = vec![];
+        for pending_tx_json in pending_txs_json.into_iter() {
+            let pending_tx: BitcoinPendingTx =
+                serde_json::from_str(&pending_tx_json).map_err(|_| DBError::ParseError)?;
+            let mut confirmed = false;
+            for (txid, vout) in pending_tx.spent_outpoints.iter() {
+                if self.is_watched_outpoint_spent(indexer, txid, *vout)? {
+                    confirmed = true;
+                    break;
+                }
+            }
+            if !confirmed {
+                pending_txs.push(pending_tx);
+            }
+        }
+
+        let pending_spent: HashSet<(Txid, u32)> = pending_txs
+            .iter()
+            .flat_map(|pending_tx| pending_tx.spent_outpoints.iter().cloned())
+            .collect();
+
+        let mut ret: Vec<_> = self
+            .get_unspent_watched_outputs(indexer, script_pubkeys, min_amount)?
+            .into_iter()
+            .filter(|(output, _)| !pending_spent.contains(&(output.txid, output.vout)))
+            .map(|(output, block_height)| (output, Some(block_height)))
+            .collect();
+
+        for pending_tx in pending_txs.into_iter() {
+            for output in pending_tx.outputs.into_iter() {
+                if !script_pubkeys.contains(&output.script_pubkey)
+                    || output.amount < min_amount
+                    || pending_spent.contains(&(output.txid, output.vout))
+                {
+                    continue;
+                }
+                ret.push((output, None));
+            }
+        }
+        Ok(ret)
+    }
+
     /// Filter out the burnchain block's transactions that could be blockstack transactions.
-    /// Return the ordered list of blockstack operations by vtxindex
+    /// Return the ordered list of blockstack operations by vtxindex, and the transactions with a
+    /// Stacks operation's opcode that could not be parsed.
     fn get_blockstack_transactions<B: BurnchainHeaderReader>(
         &self,
         burnchain: &Burnchain,
@@ -1191,7 +1754,7 @@ impl BurnchainDB {
         block: &BurnchainBlock,
         block_header: &BurnchainBlockHeader,
         epoch_id: StacksEpochId,
-    ) -> Vec<BlockstackOperationType> {
+    ) -> (Vec<BlockstackOperationType>, Vec<UnparsedStacksOp>) {
         debug!(
             "Extract Blockstack transactions from block {} {} ({} txs)",
             block.block_height(),
@@ -1201,6 +1764,7 @@ impl BurnchainDB {
 
         let mut ops = Vec::new();
         let mut pre_stx_ops = HashMap::new();
+        let mut unparsed_stacks_ops = vec![];
 
         for tx in block.txs().iter() {
             let result = Burnchain::classify_transaction(
@@ -1211,6 +1775,7 @@ impl BurnchainDB {
                 epoch_id,
                 &tx,
                 &pre_stx_ops,
+                &mut unparsed_stacks_ops,
             );
             if let Some(classified_tx) = result {
                 if let BlockstackOperationType::PreStx(pre_stx_op) = classified_tx {
@@ -1229,7 +1794,7 @@ impl BurnchainDB {
 
         ops.sort_by_key(|op| op.vtxindex());
 
-        ops
+        (ops, unparsed_stacks_ops)
     }
 
     pub fn get_affirmation_map(
@@ -1403,11 +1968,29 @@ impl BurnchainDB {
         let header = block.header();
         debug!("Storing new burnchain block";
               "burn_header_hash" => %header.block_hash.to_string());
-        let mut blockstack_ops =
+        let (mut blockstack_ops, unparsed_stacks_ops) =
             self.get_blockstack_transactions(burnchain, indexer, block, &header, epoch_id);
         apply_blockstack_txs_safety_checks(header.block_height, &mut blockstack_ops);
 
-        self.store_new_burnchain_block_ops_unchecked(burnchain, indexer, &header, &blockstack_ops)?;
+        let db_tx = self.tx_begin()?;
+
+        test_debug!(
+            "Store block {},{} with {} ops",
+            &header.block_hash,
+            header.block_height,
+            blockstack_ops.len()
+        );
+        db_tx.store_burnchain_db_entry(&header)?;
+        db_tx.store_blockstack_ops(burnchain, indexer, &header, &blockstack_ops)?;
+        db_tx.store_unparsed_stacks_ops(&header, &unparsed_stacks_ops)?;
+        match block {
+            BurnchainBlock::Bitcoin(ref bitcoin_block) => {
+                db_tx.store_bitcoin_utxo_changes(bitcoin_block)?;
+                db_tx.store_bitcoin_block_proofs(bitcoin_block)?;
+            }
+        }
+
+        db_tx.commit()?;
         Ok(blockstack_ops)
     }
 
@@ -526,7 +526,7 @@ fn test_read_prepare_phase_commits() {
     }
 
     let all_ops = read_prepare_phase_commits(
-        &burnchain_db.tx_begin().unwrap(),
+        burnchain_db.conn(),
         &headers,
         &burnchain.pox_constants,
         first_block_header.block_height,
@@ -578,7 +578,7 @@ fn test_parent_block_commits() {
     );
 
     let all_ops = read_prepare_phase_commits(
-        &burnchain_db.tx_begin().unwrap(),
+        burnchain_db.conn(),
         &headers,
         &burnchain.pox_constants,
         first_block_header.block_height,
@@ -586,7 +586,7 @@ fn test_parent_block_commits() {
     )
     .unwrap();
     let parent_commits =
-        read_parent_block_commits(&burnchain_db.tx_begin().unwrap(), &headers, &all_ops).unwrap();
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops).unwrap();
 
     // this is a simple reward cycle -- each block-commit has a unique parent
     assert_eq!(parent_commits.len(), all_ops.len());
@@ -609,12 +609,8 @@ fn test_parent_block_commits() {
     let mut all_ops_with_orphan = all_ops.clone();
     all_ops_with_orphan[1][0].parent_vtxindex += 1;
 
-    let parent_commits = read_parent_block_commits(
-        &burnchain_db.tx_begin().unwrap(),
-        &headers,
-        &all_ops_with_orphan,
-    )
-    .unwrap();
+    let parent_commits =
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops_with_orphan).unwrap();
 
     // this is a simple reward cycle -- each block-commit has a unique parent, except for the
     // orphan
@@ -628,12 +624,9 @@ fn test_parent_block_commits() {
         }
     }
 
-    let parent_commits = read_parent_block_commits(
-        &burnchain_db.tx_begin().unwrap(),
-        &headers,
-        &all_ops_with_same_parent,
-    )
-    .unwrap();
+    let parent_commits =
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops_with_same_parent)
+            .unwrap();
 
     assert_eq!(parent_commits.len(), 1);
     assert_eq!(parent_commits[0].block_height, 3);
@@ -670,7 +663,7 @@ fn test_filter_orphan_block_commits() {
     );
 
     let all_ops = read_prepare_phase_commits(
-        &burnchain_db.tx_begin().unwrap(),
+        burnchain_db.conn(),
         &headers,
         &burnchain.pox_constants,
         first_block_header.block_height,
@@ -678,7 +671,7 @@ fn test_filter_orphan_block_commits() {
     )
     .unwrap();
     let parent_commits =
-        read_parent_block_commits(&burnchain_db.tx_begin().unwrap(), &headers, &all_ops).unwrap();
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops).unwrap();
 
     let mut all_ops_with_orphan = all_ops.clone();
     all_ops_with_orphan[1][0].parent_vtxindex += 1;
@@ -687,12 +680,8 @@ fn test_filter_orphan_block_commits() {
     assert_eq!(all_ops_with_orphan[1].len(), 1);
     assert_eq!(all_ops_with_orphan[2].len(), 1);
 
-    let parent_commits = read_parent_block_commits(
-        &burnchain_db.tx_begin().unwrap(),
-        &headers,
-        &all_ops_with_orphan,
-    )
-    .unwrap();
+    let parent_commits =
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops_with_orphan).unwrap();
     let filtered_ops = filter_orphan_block_commits(&parent_commits, all_ops_with_orphan);
 
     assert_eq!(filtered_ops.len(), all_ops.len());
@@ -731,7 +720,7 @@ fn test_filter_missed_block_commits() {
     );
 
     let all_ops = read_prepare_phase_commits(
-        &burnchain_db.tx_begin().unwrap(),
+        burnchain_db.conn(),
         &headers,
         &burnchain.pox_constants,
         first_block_header.block_height,
@@ -739,7 +728,7 @@ fn test_filter_missed_block_commits() {
     )
     .unwrap();
     let parent_commits =
-        read_parent_block_commits(&burnchain_db.tx_begin().unwrap(), &headers, &all_ops).unwrap();
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops).unwrap();
 
     let mut all_ops_with_missed = all_ops.clone();
     all_ops_with_missed[1][0].burn_parent_modulus -= 1;
@@ -748,12 +737,8 @@ fn test_filter_missed_block_commits() {
     assert_eq!(all_ops_with_missed[1].len(), 1);
     assert_eq!(all_ops_with_missed[2].len(), 1);
 
-    let parent_commits = read_parent_block_commits(
-        &burnchain_db.tx_begin().unwrap(),
-        &headers,
-        &all_ops_with_missed,
-    )
-    .unwrap();
+    let parent_commits =
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops_with_missed).unwrap();
     let filtered_ops = filter_missed_block_commits(all_ops_with_missed);
 
     assert_eq!(filtered_ops.len(), all_ops.len());
@@ -792,7 +777,7 @@ fn test_find_heaviest_block_commit() {
     );
 
     let all_ops = read_prepare_phase_commits(
-        &burnchain_db.tx_begin().unwrap(),
+        burnchain_db.conn(),
         &headers,
         &burnchain.pox_constants,
         first_block_header.block_height,
@@ -800,7 +785,7 @@ fn test_find_heaviest_block_commit() {
     )
     .unwrap();
     let parent_commits =
-        read_parent_block_commits(&burnchain_db.tx_begin().unwrap(), &headers, &all_ops).unwrap();
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops).unwrap();
     let filtered_ops = filter_orphan_block_commits(&parent_commits, all_ops);
 
     let heaviest_parent_commit_opt = find_heaviest_block_commit(
@@ -973,6 +958,64 @@ fn test_find_heaviest_block_commit() {
     assert!(heaviest_parent_commit_opt.is_none());
 }
 
+#[test]
+fn test_find_pox_anchor_block_candidates() {
+    let first_bhh = BurnchainHeaderHash([0; 32]);
+    let first_timestamp = 0;
+    let first_height = 0;
+
+    let mut burnchain = Burnchain::regtest(":memory:");
+    burnchain.pox_constants =
+        PoxConstants::new(5, 3, 2, 3, 0, u64::MAX - 1, u64::MAX, u32::max_value());
+    burnchain.first_block_height = first_height;
+    burnchain.first_block_hash = first_bhh;
+    burnchain.first_block_timestamp = first_timestamp;
+
+    let mut burnchain_db = BurnchainDB::connect(":memory:", &burnchain, true).unwrap();
+
+    let first_block_header = burnchain_db.get_canonical_chain_tip().unwrap();
+
+    let mut headers = vec![first_block_header.clone()];
+    let key_register = make_simple_key_register(&first_block_header.block_hash, 0, 1);
+
+    // first reward cycle is all (linear) commits, so its only candidate is the anchor block
+    let (next_headers, commits) = make_simple_reward_cycle(
+        &mut burnchain_db,
+        &burnchain,
+        &key_register,
+        &mut headers,
+        None,
+    );
+
+    let candidates =
+        find_pox_anchor_block_candidates(burnchain_db.conn(), 0, &headers, &burnchain).unwrap();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].confs, 3);
+    assert_eq!(candidates[0].burnt, 3 * 10000);
+
+    let anchor_commit = commits[1].as_ref().unwrap();
+    assert_eq!(candidates[0].block_height, anchor_commit.block_height);
+    assert_eq!(candidates[0].vtxindex, anchor_commit.vtxindex);
+
+    let (_, anchor_block_opt) =
+        find_pox_anchor_block(&burnchain_db.tx_begin().unwrap(), 0, &headers, &burnchain).unwrap();
+    assert_eq!(&anchor_block_opt.unwrap().0, anchor_commit);
+
+    // no candidate in a reward cycle without an anchor block has enough confirmations
+    let (next_headers, commits) = make_reward_cycle_without_anchor(
+        &mut burnchain_db,
+        &burnchain,
+        &key_register,
+        &mut headers,
+        vec![None],
+    );
+    let candidates =
+        find_pox_anchor_block_candidates(burnchain_db.conn(), 1, &headers, &burnchain).unwrap();
+    assert!(candidates
+        .iter()
+        .all(|candidate| candidate.confs < u64::from(burnchain.pox_constants.anchor_threshold)));
+}
+
 #[test]
 fn test_find_heaviest_parent_commit_many_commits() {
     // Test finding parent block commits when there's multiple block-commit forks to choose from.
@@ -1004,7 +1047,7 @@ fn test_find_heaviest_parent_commit_many_commits() {
     );
 
     let all_ops = read_prepare_phase_commits(
-        &burnchain_db.tx_begin().unwrap(),
+        burnchain_db.conn(),
         &headers,
         &burnchain.pox_constants,
         first_block_header.block_height,
@@ -1012,7 +1055,7 @@ fn test_find_heaviest_parent_commit_many_commits() {
     )
     .unwrap();
     let parent_commits =
-        read_parent_block_commits(&burnchain_db.tx_begin().unwrap(), &headers, &all_ops).unwrap();
+        read_parent_block_commits(burnchain_db.conn(), &headers, &all_ops).unwrap();
     let filtered_ops = filter_orphan_block_commits(&parent_commits, all_ops);
 
     // make a history with two miners' commits.
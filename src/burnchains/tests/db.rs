@@ -17,6 +17,7 @@
 use crate::chainstate::stacks::address::StacksAddressExtensions;
 use std::cmp;
 use std::convert::TryInto;
+use std::fs;
 
 use crate::burnchains::affirmation::AffirmationMap;
 use crate::burnchains::bitcoin::address::*;
@@ -33,7 +34,8 @@ use crate::chainstate::stacks::*;
 use crate::core::StacksEpochId;
 use crate::core::BITCOIN_REGTEST_FIRST_BLOCK_HASH;
 use crate::types::chainstate::StacksAddress;
-use crate::util_lib::db::Error as DBError;
+use crate::util_lib::db::{query_row, Error as DBError};
+use rusqlite::NO_PARAMS;
 use stacks_common::address::AddressHashMode;
 use stacks_common::deps_common::bitcoin::blockdata::transaction::Transaction as BtcTx;
 use stacks_common::deps_common::bitcoin::network::serialize::deserialize;
@@ -193,6 +195,324 @@ fn test_store_and_fetch() {
     assert_eq!(&header, &looked_up_canon);
 }
 
+#[test]
+fn test_track_watched_outputs() {
+    let first_bhh = BurnchainHeaderHash::from_hex(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap();
+
+    let burnchain = Burnchain::regtest(":memory:");
+    let mut burnchain_db = BurnchainDB::connect(":memory:", &burnchain, true).unwrap();
+
+    let first_block_header = burnchain_db.get_canonical_chain_tip().unwrap();
+    let watched_script = vec![0x00, 0x14, 0x01, 0x02];
+    let other_script = vec![0x00, 0x14, 0x03, 0x04];
+
+    let make_output =
+        |txid: Txid, vout: u32, script_pubkey: &Vec<u8>, amount: u64| BitcoinWatchedOutput {
+            txid,
+            vout,
+            script_pubkey: script_pubkey.clone(),
+            amount,
+        };
+
+    let output_a = make_output(Txid([0x0a; 32]), 0, &watched_script, 1000);
+    let output_c = make_output(Txid([0x0c; 32]), 1, &watched_script, 5000);
+    let output_d = make_output(Txid([0x0d; 32]), 0, &watched_script, 100);
+    let output_e = make_output(Txid([0x0e; 32]), 0, &other_script, 7000);
+
+    // block 1 creates A, C and E, and spends something we don't watch
+    let hash_1 = BurnchainHeaderHash([1; 32]);
+    let mut block_1 = BitcoinBlock::new(1, &hash_1, &first_bhh, vec![], 1);
+    block_1.watched_outputs = vec![output_a.clone(), output_c.clone(), output_e.clone()];
+    block_1.spent_outpoints = vec![(Txid([0xff; 32]), 0)];
+
+    // block 2 spends A and creates D
+    let hash_2 = BurnchainHeaderHash([2; 32]);
+    let mut block_2 = BitcoinBlock::new(2, &hash_2, &hash_1, vec![], 2);
+    block_2.watched_outputs = vec![output_d.clone()];
+    block_2.spent_outpoints = vec![(Txid([0x0a; 32]), 0)];
+
+    // block 2' is a sibling of block 2 that spends C
+    let hash_2_fork = BurnchainHeaderHash([3; 32]);
+    let mut block_2_fork = BitcoinBlock::new(2, &hash_2_fork, &hash_1, vec![], 3);
+    block_2_fork.spent_outpoints = vec![(Txid([0x0c; 32]), 1)];
+
+    let mut headers = vec![first_block_header];
+    for block in [block_1, block_2, block_2_fork].iter() {
+        let block = BurnchainBlock::Bitcoin(block.clone());
+        burnchain_db
+            .store_new_burnchain_block(&burnchain, &headers, &block, StacksEpochId::Epoch21)
+            .unwrap();
+        if block.block_hash() != hash_2_fork {
+            headers.push(block.header());
+        }
+    }
+
+    // unwatched spends aren't stored
+    let num_spent: i64 = query_row(
+        burnchain_db.conn(),
+        "SELECT COUNT(*) FROM bitcoin_spent_outpoints",
+        NO_PARAMS,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(num_spent, 2);
+
+    // A was spent on the canonical fork, but C was only spent on a non-canonical fork
+    let utxos = burnchain_db
+        .get_unspent_watched_outputs(&headers, &[watched_script.clone()], 0)
+        .unwrap();
+    assert_eq!(utxos, vec![(output_c.clone(), 1), (output_d, 2)]);
+
+    let utxos = burnchain_db
+        .get_unspent_watched_outputs(&headers, &[watched_script.clone()], 1000)
+        .unwrap();
+    assert_eq!(utxos, vec![(output_c, 1)]);
+
+    let utxos = burnchain_db
+        .get_unspent_watched_outputs(&headers, &[other_script], 0)
+        .unwrap();
+    assert_eq!(utxos, vec![(output_e, 1)]);
+
+    // if block 2' becomes canonical, then C is spent, and A and D are no longer spent or created
+    let fork_headers = vec![
+        headers[0].clone(),
+        headers[1].clone(),
+        BurnchainBlockHeader {
+            block_height: 2,
+            block_hash: hash_2_fork,
+            parent_block_hash: hash_1,
+            num_txs: 0,
+            timestamp: 3,
+        },
+    ];
+    let utxos = burnchain_db
+        .get_unspent_watched_outputs(&fork_headers, &[watched_script], 0)
+        .unwrap();
+    assert_eq!(utxos, vec![(output_a, 1)]);
+}
+
+#[test]
+fn test_track_pending_txs() {
+    let first_bhh = BurnchainHeaderHash::from_hex(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap();
+
+    let burnchain = Burnchain::regtest(":memory:");
+    let mut burnchain_db = BurnchainDB::connect(":memory:", &burnchain, true).unwrap();
+
+    let first_block_header = burnchain_db.get_canonical_chain_tip().unwrap();
+    let watched_script = vec![0x00, 0x14, 0x01, 0x02];
+
+    let make_output = |txid: Txid, vout: u32, amount: u64| BitcoinWatchedOutput {
+        txid,
+        vout,
+        script_pubkey: watched_script.clone(),
+        amount,
+    };
+
+    let output_a = make_output(Txid([0x0a; 32]), 0, 1000);
+    let output_c = make_output(Txid([0x0c; 32]), 0, 5000);
+
+    // block 1 creates A and C
+    let hash_1 = BurnchainHeaderHash([1; 32]);
+    let mut block_1 = BitcoinBlock::new(1, &hash_1, &first_bhh, vec![], 1);
+    block_1.watched_outputs = vec![output_a.clone(), output_c.clone()];
+
+    let mut headers = vec![first_block_header];
+    let block = BurnchainBlock::Bitcoin(block_1);
+    burnchain_db
+        .store_new_burnchain_block(&burnchain, &headers, &block, StacksEpochId::Epoch21)
+        .unwrap();
+    headers.push(block.header());
+
+    let spendable = |burnchain_db: &BurnchainDB, headers: &Vec<BurnchainBlockHeader>| {
+        burnchain_db
+            .get_spendable_watched_outputs(headers, &[watched_script.clone()], 0, 0)
+            .unwrap()
+    };
+
+    // a pending transaction spends A and sends its change back
+    let change_1 = make_output(Txid([0x01; 32]), 1, 800);
+    let pending_1 = BitcoinPendingTx {
+        txid: Txid([0x01; 32]),
+        broadcast_height: 1,
+        spent_outpoints: vec![(output_a.txid, 0)],
+        outputs: vec![change_1.clone()],
+    };
+    burnchain_db.store_pending_tx(&pending_1).unwrap();
+    assert_eq!(
+        spendable(&burnchain_db, &headers),
+        vec![(output_c.clone(), Some(1)), (change_1.clone(), None)]
+    );
+
+    // replacing it (i.e. RBF) drops the original
+    let change_2 = make_output(Txid([0x02; 32]), 1, 700);
+    let pending_2 = BitcoinPendingTx {
+        txid: Txid([0x02; 32]),
+        broadcast_height: 1,
+        spent_outpoints: vec![(output_a.txid, 0)],
+        outputs: vec![change_2.clone()],
+    };
+    burnchain_db.store_pending_tx(&pending_2).unwrap();
+    assert_eq!(
+        spendable(&burnchain_db, &headers),
+        vec![(output_c.clone(), Some(1)), (change_2.clone(), None)]
+    );
+
+    // a second transaction in the same block can spend the first one's change
+    let change_3 = make_output(Txid([0x03; 32]), 1, 600);
+    let pending_3 = BitcoinPendingTx {
+        txid: Txid([0x03; 32]),
+        broadcast_height: 1,
+        spent_outpoints: vec![(change_2.txid, 1)],
+        outputs: vec![change_3.clone()],
+    };
+    burnchain_db.store_pending_tx(&pending_3).unwrap();
+    assert_eq!(
+        spendable(&burnchain_db, &headers),
+        vec![(output_c.clone(), Some(1)), (change_3.clone(), None)]
+    );
+
+    // block 2 confirms the replacement, but not the second transaction
+    let hash_2 = BurnchainHeaderHash([2; 32]);
+    let mut block_2 = BitcoinBlock::new(2, &hash_2, &hash_1, vec![], 2);
+    block_2.watched_outputs = vec![change_2.clone()];
+    block_2.spent_outpoints = vec![(output_a.txid, 0)];
+    let block = BurnchainBlock::Bitcoin(block_2);
+    burnchain_db
+        .store_new_burnchain_block(&burnchain, &headers, &block, StacksEpochId::Epoch21)
+        .unwrap();
+    headers.push(block.header());
+
+    assert_eq!(
+        spendable(&burnchain_db, &headers),
+        vec![(output_c.clone(), Some(1)), (change_3.clone(), None)]
+    );
+
+    // pending transactions broadcast too long ago are not counted
+    assert_eq!(
+        burnchain_db
+            .get_spendable_watched_outputs(&headers, &[watched_script.clone()], 0, 2)
+            .unwrap(),
+        vec![(output_c.clone(), Some(1)), (change_2.clone(), Some(2))]
+    );
+
+    // and they are dropped once a later transaction is stored
+    let pending_4 = BitcoinPendingTx {
+        txid: Txid([0x04; 32]),
+        broadcast_height: 2 + PENDING_TX_LIFETIME,
+        spent_outpoints: vec![(output_c.txid, 0)],
+        outputs: vec![],
+    };
+    burnchain_db.store_pending_tx(&pending_4).unwrap();
+    let num_pending: i64 = query_row(
+        burnchain_db.conn(),
+        "SELECT COUNT(*) FROM bitcoin_pending_txs",
+        NO_PARAMS,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(num_pending, 1);
+    assert_eq!(
+        spendable(&burnchain_db, &headers),
+        vec![(change_2, Some(2))]
+    );
+}
+
+#[test]
+fn test_migrate_pending_txs() {
+    let path = "/tmp/test-burnchain-db-migrate-pending-txs.sqlite";
+    if fs::metadata(path).is_ok() {
+        fs::remove_file(path).unwrap();
+    }
+
+    // make a version-3 database, which predates the pending transactions table
+    let burnchain = Burnchain::regtest(":memory:");
+    let burnchain_db = BurnchainDB::connect(path, &burnchain, true).unwrap();
+    burnchain_db
+        .conn()
+        .execute_batch(
+            "DROP TABLE bitcoin_pending_txs; \
+             DROP TABLE unparsed_stacks_ops; \
+             DROP TABLE bitcoin_tx_merkle_proofs; \
+             DROP TABLE bitcoin_block_raw_headers; \
+             UPDATE db_config SET version = '3';",
+        )
+        .unwrap();
+    drop(burnchain_db);
+
+    let mut burnchain_db = BurnchainDB::open(path, true).unwrap();
+    let version: String = query_row(
+        burnchain_db.conn(),
+        "SELECT version FROM db_config",
+        NO_PARAMS,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(version, BURNCHAIN_DB_VERSION);
+
+    let pending_tx = BitcoinPendingTx {
+        txid: Txid([0x01; 32]),
+        broadcast_height: 1,
+        spent_outpoints: vec![(Txid([0x0a; 32]), 0)],
+        outputs: vec![],
+    };
+    burnchain_db.store_pending_tx(&pending_tx).unwrap();
+}
+
+#[test]
+fn test_store_bitcoin_block_proofs() {
+    let first_bhh = BurnchainHeaderHash::from_hex(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap();
+
+    let burnchain = Burnchain::regtest(":memory:");
+    let mut burnchain_db = BurnchainDB::connect(":memory:", &burnchain, true).unwrap();
+
+    let first_block_header = burnchain_db.get_canonical_chain_tip().unwrap();
+
+    let proof = BitcoinTxMerkleProof {
+        txid: Txid([0x0a; 32]),
+        vtxindex: 3,
+        raw_tx: vec![0x01, 0x02, 0x03],
+        merkle_branch: vec![[0x11; 32], [0x22; 32], [0x33; 32]],
+    };
+
+    let hash_1 = BurnchainHeaderHash([1; 32]);
+    let mut block_1 = BitcoinBlock::new(1, &hash_1, &first_bhh, vec![], 1);
+    block_1.raw_header = vec![0x44; 80];
+    block_1.tx_proofs = vec![proof.clone()];
+
+    // a block stored without a raw header or proofs
+    let hash_2 = BurnchainHeaderHash([2; 32]);
+    let block_2 = BitcoinBlock::new(2, &hash_2, &hash_1, vec![], 2);
+
+    let mut headers = vec![first_block_header];
+    for block in [block_1, block_2].iter() {
+        let block = BurnchainBlock::Bitcoin(block.clone());
+        burnchain_db
+            .store_new_burnchain_block(&burnchain, &headers, &block, StacksEpochId::Epoch21)
+            .unwrap();
+        headers.push(block.header());
+    }
+
+    assert_eq!(
+        BurnchainDB::get_bitcoin_raw_header(burnchain_db.conn(), &hash_1).unwrap(),
+        Some(vec![0x44; 80])
+    );
+    assert_eq!(
+        BurnchainDB::get_bitcoin_tx_merkle_proof(burnchain_db.conn(), &hash_1, &proof.txid)
+            .unwrap(),
+        Some(proof.clone())
+    );
+
+    assert_eq!(
+        BurnchainDB::get_bitcoin_raw_header(burnchain_db.conn(), &hash_2).unwrap(),
+        None
+    );
+    assert_eq!(
+        BurnchainDB::get_bitcoin_tx_merkle_proof(burnchain_db.conn(), &hash_2, &proof.txid)
+            .unwrap(),
+        None
+    );
+}
+
 #[test]
 fn test_classify_stack_stx() {
     let first_bhh = BurnchainHeaderHash::from_hex(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap();
@@ -450,6 +770,177 @@ fn test_classify_stack_stx() {
     }
 }
 
+#[test]
+fn test_migrate_unparsed_stacks_ops() {
+    let path = "/tmp/test-burnchain-db-migrate-unparsed-stacks-ops.sqlite";
+    if fs::metadata(path).is_ok() {
+        fs::remove_file(path).unwrap();
+    }
+
+    // make a version-4 database, which predates the unparsed Stacks ops table
+    let burnchain = Burnchain::regtest(":memory:");
+    let burnchain_db = BurnchainDB::connect(path, &burnchain, true).unwrap();
+    burnchain_db
+        .conn()
+        .execute_batch(
+            "DROP TABLE bitcoin_pending_txs; \
+             DROP TABLE unparsed_stacks_ops; \
+             UPDATE db_config SET version = '4';",
+        )
+        .unwrap();
+    drop(burnchain_db);
+
+    let burnchain_db = BurnchainDB::open(path, true).unwrap();
+    let version: String = query_row(
+        burnchain_db.conn(),
+        "SELECT version FROM db_config",
+        NO_PARAMS,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(version, BURNCHAIN_DB_VERSION);
+
+    let unparsed =
+        BurnchainDB::get_unparsed_stacks_ops(burnchain_db.conn(), &BurnchainHeaderHash([0x01; 32]))
+            .unwrap();
+    assert!(unparsed.is_empty());
+}
+
+#[test]
+fn test_record_unparsed_stacks_ops() {
+    let first_bhh = BurnchainHeaderHash::from_hex(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap();
+
+    let mut burnchain = Burnchain::regtest(":memory:");
+    let mut burnchain_db = BurnchainDB::connect(":memory:", &burnchain, true).unwrap();
+
+    burnchain.pox_constants = PoxConstants::test_default();
+    burnchain.pox_constants.sunset_start = 999;
+    burnchain.pox_constants.sunset_end = 1000;
+
+    let first_block_header = burnchain_db.get_canonical_chain_tip().unwrap();
+    let mut headers = vec![first_block_header.clone()];
+
+    let sender_btc_addr = BitcoinAddress::Legacy(LegacyBitcoinAddress {
+        addrtype: LegacyBitcoinAddressType::PublicKeyHash,
+        network_id: BitcoinNetworkType::Mainnet,
+        bytes: Hash160([1; 20]),
+    });
+    let make_tx =
+        |txid: Txid, vtxindex: u32, opcode: Opcodes, data: Vec<u8>, input: (Txid, u32)| {
+            BitcoinTransaction {
+                txid,
+                vtxindex,
+                opcode: opcode as u8,
+                data,
+                data_amt: 0,
+                inputs: vec![BitcoinTxInputStructured {
+                    keys: vec![],
+                    num_required: 0,
+                    in_type: BitcoinInputType::Standard,
+                    tx_ref: input,
+                }
+                .into()],
+                outputs: vec![BitcoinTxOutput {
+                    units: 10,
+                    address: sender_btc_addr.clone(),
+                }],
+            }
+        };
+
+    let pre_stx_txid = Txid([5; 32]);
+    let txs = vec![
+        make_tx(
+            pre_stx_txid.clone(),
+            0,
+            Opcodes::PreStx,
+            vec![0; 80],
+            (Txid([0; 32]), 1),
+        ),
+        // the payload is too short
+        make_tx(
+            Txid([6; 32]),
+            1,
+            Opcodes::StackStx,
+            vec![1; 10],
+            (pre_stx_txid.clone(), 1),
+        ),
+        // there is no pre-stx operation at its input
+        make_tx(
+            Txid([7; 32]),
+            2,
+            Opcodes::StackStx,
+            vec![1; 80],
+            (Txid([9; 32]), 1),
+        ),
+        // leader key registrations are not Stacks operations
+        make_tx(
+            Txid([8; 32]),
+            3,
+            Opcodes::LeaderKeyRegister,
+            vec![1; 10],
+            (Txid([0; 32]), 1),
+        ),
+    ];
+
+    let block_hash = BurnchainHeaderHash([2; 32]);
+    headers.push(BurnchainBlockHeader {
+        block_height: first_block_header.block_height + 1,
+        block_hash: block_hash.clone(),
+        parent_block_hash: first_bhh.clone(),
+        num_txs: txs.len() as u64,
+        timestamp: first_block_header.timestamp + 1,
+    });
+    let block = BurnchainBlock::Bitcoin(BitcoinBlock::new(501, &block_hash, &first_bhh, txs, 350));
+
+    let ops = burnchain_db
+        .store_new_burnchain_block(&burnchain, &headers, &block, StacksEpochId::Epoch21)
+        .unwrap();
+    assert_eq!(
+        ops.len(),
+        1,
+        "Only the pre-stx op should have been accepted"
+    );
+
+    let sender = StacksAddress::from_legacy_bitcoin_address(&LegacyBitcoinAddress {
+        addrtype: LegacyBitcoinAddressType::PublicKeyHash,
+        network_id: BitcoinNetworkType::Mainnet,
+        bytes: Hash160([1; 20]),
+    });
+    let unparsed_ops =
+        BurnchainDB::get_unparsed_stacks_ops(burnchain_db.conn(), &block_hash).unwrap();
+    assert_eq!(
+        unparsed_ops,
+        vec![
+            UnparsedStacksOp {
+                op_type: "stack-stx".to_string(),
+                sender: Some(sender),
+                reason: "Failed to parse transaction into Blockstack operation".to_string(),
+                txid: Txid([6; 32]),
+                vtxindex: 1,
+                block_height: 501,
+                burn_header_hash: block_hash.clone(),
+            },
+            UnparsedStacksOp {
+                op_type: "stack-stx".to_string(),
+                sender: None,
+                reason: format!(
+                    "No pre-stx operation found at its first input ({})",
+                    &Txid([9; 32])
+                ),
+                txid: Txid([7; 32]),
+                vtxindex: 2,
+                block_height: 501,
+                burn_header_hash: block_hash.clone(),
+            },
+        ]
+    );
+
+    assert_eq!(
+        BurnchainDB::get_unparsed_stacks_ops(burnchain_db.conn(), &first_bhh).unwrap(),
+        vec![]
+    );
+}
+
 pub fn make_simple_block_commit(
     burnchain: &Burnchain,
     parent: Option<&LeaderBlockCommitOp>,
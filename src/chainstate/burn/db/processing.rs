@@ -287,10 +287,17 @@ impl<'a> SortitionHandleTx<'a> {
         );
 
         let mut missed_block_commits = vec![];
+        let mut stacks_op_records = vec![];
 
         // classify and check each transaction
         blockstack_txs.retain(|blockstack_op| {
-            match self.check_transaction(burnchain, blockstack_op, reward_set_info) {
+            let result = self.check_transaction(burnchain, blockstack_op, reward_set_info);
+            if blockstack_op.is_stacks_op() {
+                // remember why the sender's operation was (not) accepted
+                let rejection_reason = result.as_ref().err().map(|e| e.to_string());
+                stacks_op_records.push((blockstack_op.clone(), rejection_reason));
+            }
+            match result {
                 Ok(_) => true,
                 Err(BurnchainError::OpError(OpError::MissedBlockCommit(missed_op))) => {
                     missed_block_commits.push(missed_op);
@@ -300,6 +307,10 @@ impl<'a> SortitionHandleTx<'a> {
             }
         });
 
+        for (blockstack_op, rejection_reason) in stacks_op_records.iter() {
+            self.insert_stacks_op_record(blockstack_op, rejection_reason.as_deref())?;
+        }
+
         // block-wide check: no duplicate keys registered
         let block_ops = Burnchain::filter_block_VRF_dups(blockstack_txs);
         assert!(Burnchain::ops_are_sorted(&block_ops));
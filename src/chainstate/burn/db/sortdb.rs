@@ -42,11 +42,12 @@ use crate::burnchains::{
     BurnchainStateTransitionOps, BurnchainTransaction, BurnchainView, Error as BurnchainError,
     PoxConstants,
 };
+use crate::chainstate::burn::distribution::BurnSamplePoint;
 use crate::chainstate::burn::operations::DelegateStxOp;
 use crate::chainstate::burn::operations::{
     leader_block_commit::{MissedBlockCommit, RewardSetInfo, OUTPUTS_PER_COMMIT},
     BlockstackOperationType, LeaderBlockCommitOp, LeaderKeyRegisterOp, PreStxOp, StackStxOp,
-    TransferStxOp, UserBurnSupportOp,
+    TransferStxOp, UnparsedStacksOp, UserBurnSupportOp,
 };
 use crate::chainstate::burn::Opcodes;
 use crate::chainstate::burn::{BlockSnapshot, ConsensusHash, OpsHash, SortitionHash};
@@ -458,6 +459,97 @@ struct AcceptedStacksBlockHeader {
     pub height: u64,                       // stacks block height
 }
 
+/// A burnchain-hosted Stacks operation (stack-stx, transfer-stx, delegate-stx or pre-stx), and
+/// whether or not it parsed and passed its burnchain checks.  Operations that pass still have to
+/// be applied by a Stacks block, which may reject them in turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StacksOpRecord {
+    pub txid: Txid,
+    pub vtxindex: u32,
+    pub block_height: u64,
+    pub burn_header_hash: BurnchainHeaderHash,
+    /// "stack-stx", "transfer-stx", "delegate-stx" or "pre-stx"
+    pub op_type: String,
+    /// the sender, unless the operation's pre-stx input could not be found
+    pub sender: Option<StacksAddress>,
+    /// the operation, unless the transaction could not be parsed into one
+    pub op: Option<BlockstackOperationType>,
+    /// why the transaction could not be parsed or failed its burnchain checks, if it did
+    pub rejection_reason: Option<String>,
+}
+
+impl StacksOpRecord {
+    /// Make the record of a parsed operation.  Returns None if it is not a Stacks operation.
+    pub fn from_op(
+        op: &BlockstackOperationType,
+        rejection_reason: Option<&str>,
+    ) -> Option<StacksOpRecord> {
+        let sender = match op {
+            BlockstackOperationType::StackStx(ref op) => &op.sender,
+            BlockstackOperationType::TransferStx(ref op) => &op.sender,
+            BlockstackOperationType::DelegateStx(ref op) => &op.sender,
+            BlockstackOperationType::PreStx(ref op) => &op.output,
+            _ => {
+                return None;
+            }
+        };
+        Some(StacksOpRecord {
+            txid: op.txid(),
+            vtxindex: op.vtxindex(),
+            block_height: op.block_height(),
+            burn_header_hash: op.burn_header_hash(),
+            op_type: op.stacks_op_type()?.to_string(),
+            sender: Some(sender.clone()),
+            op: Some(op.clone()),
+            rejection_reason: rejection_reason.map(|reason| reason.to_string()),
+        })
+    }
+}
+
+impl From<UnparsedStacksOp> for StacksOpRecord {
+    fn from(unparsed_op: UnparsedStacksOp) -> StacksOpRecord {
+        StacksOpRecord {
+            txid: unparsed_op.txid,
+            vtxindex: unparsed_op.vtxindex,
+            block_height: unparsed_op.block_height,
+            burn_header_hash: unparsed_op.burn_header_hash,
+            op_type: unparsed_op.op_type,
+            sender: unparsed_op.sender,
+            op: None,
+            rejection_reason: Some(unparsed_op.reason),
+        }
+    }
+}
+
+impl FromRow<StacksOpRecord> for StacksOpRecord {
+    fn from_row(row: &Row) -> Result<StacksOpRecord, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let vtxindex: u32 = row.get_unwrap("vtxindex");
+        let block_height = u64::from_column(row, "block_height")?;
+        let burn_header_hash = BurnchainHeaderHash::from_column(row, "burn_header_hash")?;
+        let op_type: String = row.get_unwrap("op_type");
+        let sender = row
+            .get_unwrap::<_, Option<String>>("sender_addr")
+            .map(|sender| StacksAddress::from_string(&sender).ok_or(db_error::ParseError))
+            .transpose()?;
+        let op = row
+            .get_unwrap::<_, Option<String>>("op")
+            .map(|op_json| serde_json::from_str(&op_json).map_err(|_| db_error::ParseError))
+            .transpose()?;
+        let rejection_reason: Option<String> = row.get_unwrap("rejection_reason");
+        Ok(StacksOpRecord {
+            txid,
+            vtxindex,
+            block_height,
+            burn_header_hash,
+            op_type,
+            sender,
+            op,
+            rejection_reason,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct InitialMiningBonus {
     pub total_reward: u128,
@@ -501,7 +593,7 @@ impl FromRow<StacksEpoch> for StacksEpoch {
     }
 }
 
-pub const SORTITION_DB_VERSION: &'static str = "4";
+pub const SORTITION_DB_VERSION: &str = "5";
 
 const SORTITION_DB_INITIAL_SCHEMA: &'static [&'static str] = &[
     r#"
@@ -702,8 +794,29 @@ const SORTITION_DB_SCHEMA_4: &'static [&'static str] = &[
     );"#,
 ];
 
+const SORTITION_DB_SCHEMA_5: &[&str] = &[r#"
+    -- every burnchain transaction with a Stacks operation's opcode, whether or not it parsed and
+    -- passed its burnchain checks.  Like stack_stx, this does not depend on the sortition history.
+    CREATE TABLE stacks_ops (
+        txid TEXT NOT NULL,
+        vtxindex INTEGER NOT NULL,
+        block_height INTEGER NOT NULL,
+        burn_header_hash TEXT NOT NULL,
+
+        -- "stack-stx", "transfer-stx", "delegate-stx" or "pre-stx"
+        op_type TEXT NOT NULL,
+        -- NULL if the operation's pre-stx input could not be found
+        sender_addr TEXT,
+        -- JSON-encoded BlockstackOperationType, or NULL if the transaction could not be parsed
+        op TEXT,
+        -- NULL if the operation parsed and passed its burnchain checks
+        rejection_reason TEXT,
+
+        PRIMARY KEY(txid,burn_header_hash)
+    );"#];
+
 // update this to add new indexes
-const LAST_SORTITION_DB_INDEX: &'static str = "index_delegate_stx_burn_header_hash";
+const LAST_SORTITION_DB_INDEX: &str = "index_stacks_ops_sender_addr";
 
 const SORTITION_DB_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS snapshots_block_hashes ON snapshots(block_height,index_root,winning_stacks_block_hash);",
@@ -728,6 +841,8 @@ const SORTITION_DB_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS index_pox_payouts ON snapshots(pox_payouts);",
     "CREATE INDEX IF NOT EXISTS index_burn_header_hash_pox_valid ON snapshots(burn_header_hash,pox_valid);",
     "CREATE INDEX IF NOT EXISTS index_delegate_stx_burn_header_hash ON delegate_stx(burn_header_hash);",
+    "CREATE INDEX IF NOT EXISTS index_stacks_ops_txid ON stacks_ops(txid);",
+    "CREATE INDEX IF NOT EXISTS index_stacks_ops_sender_addr ON stacks_ops(sender_addr,block_height);",
 ];
 
 pub struct SortitionDB {
@@ -2657,6 +2772,7 @@ impl SortitionDB {
         SortitionDB::apply_schema_2(&db_tx, epochs_ref)?;
         SortitionDB::apply_schema_3(&db_tx)?;
         SortitionDB::apply_schema_4(&db_tx)?;
+        SortitionDB::apply_schema_5(&db_tx)?;
 
         db_tx.instantiate_index()?;
 
@@ -2843,10 +2959,16 @@ impl SortitionDB {
         match epoch {
             StacksEpochId::Epoch10 => true,
             StacksEpochId::Epoch20 => {
-                version == "1" || version == "2" || version == "3" || version == "4"
+                version == "1"
+                    || version == "2"
+                    || version == "3"
+                    || version == "4"
+                    || version == "5"
             }
-            StacksEpochId::Epoch2_05 => version == "2" || version == "3" || version == "4",
-            StacksEpochId::Epoch21 => version == "3" || version == "4",
+            StacksEpochId::Epoch2_05 => {
+                version == "2" || version == "3" || version == "4" || version == "5"
+            }
+            StacksEpochId::Epoch21 => version == "3" || version == "4" || version == "5",
         }
     }
 
@@ -2915,6 +3037,17 @@ impl SortitionDB {
         Ok(())
     }
 
+    fn apply_schema_5(tx: &DBTx) -> Result<(), db_error> {
+        for sql_exec in SORTITION_DB_SCHEMA_5 {
+            tx.execute_batch(sql_exec)?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
+            &["5"],
+        )?;
+        Ok(())
+    }
+
     fn check_schema_version_or_error(&mut self) -> Result<(), db_error> {
         match SortitionDB::get_schema_version(self.conn()) {
             Ok(Some(version)) => {
@@ -2953,6 +3086,11 @@ impl SortitionDB {
                         let tx = self.tx_begin()?;
                         SortitionDB::apply_schema_4(&tx.deref())?;
                         tx.commit()?;
+                    } else if version == "4" {
+                        // add the table of schema 5, but do not populate it.
+                        let tx = self.tx_begin()?;
+                        SortitionDB::apply_schema_5(tx.deref())?;
+                        tx.commit()?;
                     } else if version == expected_version {
                         return Ok(());
                     } else {
@@ -3867,6 +4005,34 @@ impl SortitionDB {
         )
     }
 
+    /// Get every record of the burnchain-hosted Stacks operation with the given txid.  There is
+    /// one per burnchain block the transaction was mined in (i.e. one per burnchain fork).
+    pub fn get_stacks_op_records_by_txid(
+        conn: &Connection,
+        txid: &Txid,
+    ) -> Result<Vec<StacksOpRecord>, db_error> {
+        query_rows(
+            conn,
+            "SELECT * FROM stacks_ops WHERE txid = ?1 ORDER BY block_height DESC",
+            &[txid],
+        )
+    }
+
+    /// Get the most recent burnchain-hosted Stacks operations sent by the given address, newest
+    /// first.
+    pub fn get_stacks_op_records_by_sender(
+        conn: &Connection,
+        sender: &StacksAddress,
+        limit: u64,
+    ) -> Result<Vec<StacksOpRecord>, db_error> {
+        let args: &[&dyn ToSql] = &[&sender.to_string(), &u64_to_sql(limit)?];
+        query_rows(
+            conn,
+            "SELECT * FROM stacks_ops WHERE sender_addr = ?1 ORDER BY block_height DESC, vtxindex DESC LIMIT ?2",
+            args,
+        )
+    }
+
     /// Get the list of Delegate-STX operations processed in a given burnchain block.
     /// This will be the same list in each PoX fork; it's up to the Stacks block-processing logic
     /// to reject them.
@@ -4146,6 +4312,32 @@ impl SortitionDB {
         Ok(burn_total)
     }
 
+    /// Reconstruct the burn distribution that the given sortition was drawn from: every accepted
+    /// block-commit in `block_snapshot`, weighted by its min-median burn over the mining
+    /// commitment window (or by its plain burn, if the sortition was not windowed).
+    pub fn get_block_burn_distribution(
+        ic: &SortitionDBConn,
+        burnchain: &Burnchain,
+        block_snapshot: &BlockSnapshot,
+    ) -> Result<Vec<BurnSamplePoint>, db_error> {
+        let epoch_id = SortitionDB::get_stacks_epoch(ic, block_snapshot.block_height)?
+            .ok_or(db_error::NotFoundError)?
+            .epoch_id;
+        let block_commits =
+            SortitionDB::get_block_commits_by_block(ic, &block_snapshot.sortition_id)?;
+
+        // this block's missed commits, if any, are already stored
+        let mut handle = SortitionHandleConn::open_reader(ic, &block_snapshot.sortition_id)?;
+        BurnchainStateTransition::make_windowed_burn_distribution(
+            &mut handle,
+            burnchain,
+            block_snapshot.block_height,
+            epoch_id,
+            block_commits,
+            &[],
+        )
+    }
+
     /// Get all user burns registered in a block on is fork.
     /// Returns list of user burns in order by vtxindex.
     pub fn get_user_burns_by_block(
@@ -4808,6 +5000,48 @@ impl<'a> SortitionHandleTx<'a> {
         Ok(())
     }
 
+    /// Record a burnchain-hosted Stacks operation and the outcome of its burnchain checks.
+    /// Operations of any other kind are ignored.
+    pub fn insert_stacks_op_record(
+        &mut self,
+        blockstack_op: &BlockstackOperationType,
+        rejection_reason: Option<&str>,
+    ) -> Result<(), db_error> {
+        match StacksOpRecord::from_op(blockstack_op, rejection_reason) {
+            Some(record) => self.insert_stacks_op_record_row(&record),
+            None => Ok(()),
+        }
+    }
+
+    /// Record a burnchain transaction with a Stacks operation's opcode that could not be
+    /// parsed, and why.
+    pub fn insert_unparsed_stacks_op_record(
+        &mut self,
+        unparsed_op: &UnparsedStacksOp,
+    ) -> Result<(), db_error> {
+        self.insert_stacks_op_record_row(&StacksOpRecord::from(unparsed_op.clone()))
+    }
+
+    fn insert_stacks_op_record_row(&mut self, record: &StacksOpRecord) -> Result<(), db_error> {
+        let args: &[&dyn ToSql] = &[
+            &record.txid,
+            &record.vtxindex,
+            &u64_to_sql(record.block_height)?,
+            &record.burn_header_hash,
+            &record.op_type,
+            &record.sender.as_ref().map(|sender| sender.to_string()),
+            &record
+                .op
+                .as_ref()
+                .map(|op| serde_json::to_string(op).unwrap()),
+            &record.rejection_reason,
+        ];
+
+        self.execute("REPLACE INTO stacks_ops (txid, vtxindex, block_height, burn_header_hash, op_type, sender_addr, op, rejection_reason) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)", args)?;
+
+        Ok(())
+    }
+
     /// Insert a transfer-stx op
     fn insert_transfer_stx(&mut self, op: &TransferStxOp) -> Result<(), db_error> {
         let args: &[&dyn ToSql] = &[
@@ -9737,4 +9971,255 @@ pub mod tests {
             good_ops_2[2]
         );
     }
+
+    #[test]
+    fn test_load_store_stacks_op_records() {
+        let block_height = 123;
+        let first_burn_hash = BurnchainHeaderHash([0x00; 32]);
+        let fork_burn_hash = BurnchainHeaderHash([0x01; 32]);
+        let mut db = SortitionDB::connect_test(block_height, &first_burn_hash).unwrap();
+
+        let sender = StacksAddress::new(1, Hash160([1u8; 20]));
+        let pre_stx_op = BlockstackOperationType::PreStx(PreStxOp {
+            output: sender,
+
+            txid: Txid([0x01; 32]),
+            vtxindex: 1,
+            block_height,
+            burn_header_hash: first_burn_hash,
+        });
+        let transfer_op = BlockstackOperationType::TransferStx(TransferStxOp {
+            sender,
+            recipient: sender,
+            transfered_ustx: 123,
+            memo: vec![],
+
+            txid: Txid([0x02; 32]),
+            vtxindex: 2,
+            block_height,
+            burn_header_hash: first_burn_hash,
+        });
+        let fork_transfer_op = BlockstackOperationType::TransferStx(TransferStxOp {
+            sender,
+            recipient: sender,
+            transfered_ustx: 123,
+            memo: vec![],
+
+            txid: Txid([0x02; 32]),
+            vtxindex: 2,
+            block_height: block_height + 1,
+            burn_header_hash: fork_burn_hash,
+        });
+        let user_burn_op = BlockstackOperationType::UserBurnSupport(UserBurnSupportOp {
+            address: sender,
+            consensus_hash: ConsensusHash([0x03; 20]),
+            public_key: VRFPublicKey::from_bytes(
+                &hex_bytes("a366b51292bef4edd64063d9145c617fec373bceb0758e98cd72becd84d54c7a")
+                    .unwrap(),
+            )
+            .unwrap(),
+            key_block_ptr: 1,
+            key_vtxindex: 1,
+            block_header_hash_160: Hash160([0x04; 20]),
+            burn_fee: 1,
+
+            txid: Txid([0x03; 32]),
+            vtxindex: 3,
+            block_height,
+            burn_header_hash: first_burn_hash,
+        });
+
+        let unparsed_stack_stx_op = UnparsedStacksOp {
+            op_type: "stack-stx".to_string(),
+            sender: Some(sender),
+            reason: "Failed to parse transaction into Blockstack operation".to_string(),
+
+            txid: Txid([0x04; 32]),
+            vtxindex: 4,
+            block_height,
+            burn_header_hash: first_burn_hash,
+        };
+        let unparsed_transfer_op = UnparsedStacksOp {
+            op_type: "transfer-stx".to_string(),
+            sender: None,
+            reason: "No pre-stx operation found at its first input".to_string(),
+
+            txid: Txid([0x05; 32]),
+            vtxindex: 5,
+            block_height,
+            burn_header_hash: first_burn_hash,
+        };
+
+        let mut tx = db.tx_begin_at_tip();
+        tx.insert_stacks_op_record(&pre_stx_op, None).unwrap();
+        tx.insert_stacks_op_record(&transfer_op, Some("Transfer STX must not send to self"))
+            .unwrap();
+        tx.insert_stacks_op_record(&fork_transfer_op, None).unwrap();
+        // not a Stacks operation, so not recorded
+        tx.insert_stacks_op_record(&user_burn_op, None).unwrap();
+        tx.insert_unparsed_stacks_op_record(&unparsed_stack_stx_op)
+            .unwrap();
+        tx.insert_unparsed_stacks_op_record(&unparsed_transfer_op)
+            .unwrap();
+        tx.commit().unwrap();
+
+        // one record per burnchain block the op was mined in, newest first
+        let records =
+            SortitionDB::get_stacks_op_records_by_txid(db.conn(), &Txid([0x02; 32])).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                StacksOpRecord::from_op(&fork_transfer_op, None).unwrap(),
+                StacksOpRecord::from_op(&transfer_op, Some("Transfer STX must not send to self"))
+                    .unwrap(),
+            ]
+        );
+        assert_eq!(records[0].op_type, "transfer-stx");
+        assert_eq!(records[0].sender, Some(sender));
+        assert_eq!(records[0].op, Some(fork_transfer_op.clone()));
+        assert_eq!(records[0].block_height, block_height + 1);
+        assert_eq!(records[0].burn_header_hash, fork_burn_hash);
+
+        let records =
+            SortitionDB::get_stacks_op_records_by_txid(db.conn(), &Txid([0x03; 32])).unwrap();
+        assert_eq!(records, vec![]);
+
+        // transactions that could not be parsed are recorded along with the reason
+        let records =
+            SortitionDB::get_stacks_op_records_by_txid(db.conn(), &Txid([0x04; 32])).unwrap();
+        assert_eq!(
+            records,
+            vec![StacksOpRecord {
+                txid: Txid([0x04; 32]),
+                vtxindex: 4,
+                block_height,
+                burn_header_hash: first_burn_hash,
+                op_type: "stack-stx".to_string(),
+                sender: Some(sender),
+                op: None,
+                rejection_reason: Some(
+                    "Failed to parse transaction into Blockstack operation".to_string()
+                ),
+            }]
+        );
+
+        let records =
+            SortitionDB::get_stacks_op_records_by_txid(db.conn(), &Txid([0x05; 32])).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sender, None);
+        assert_eq!(
+            records[0].rejection_reason,
+            Some("No pre-stx operation found at its first input".to_string())
+        );
+
+        let records = SortitionDB::get_stacks_op_records_by_sender(db.conn(), &sender, 10).unwrap();
+        let txids: Vec<_> = records.iter().map(|record| record.txid).collect();
+        assert_eq!(
+            txids,
+            vec![
+                Txid([0x02; 32]),
+                Txid([0x04; 32]),
+                Txid([0x02; 32]),
+                Txid([0x01; 32])
+            ]
+        );
+
+        let records = SortitionDB::get_stacks_op_records_by_sender(db.conn(), &sender, 1).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].op, Some(fork_transfer_op));
+
+        let records = SortitionDB::get_stacks_op_records_by_sender(
+            db.conn(),
+            &StacksAddress::new(2, Hash160([2u8; 20])),
+            10,
+        )
+        .unwrap();
+        assert_eq!(records, vec![]);
+    }
+
+    #[test]
+    fn test_get_block_burn_distribution() {
+        let block_height = 123;
+        let first_burn_hash = BurnchainHeaderHash([0x00; 32]);
+        let mut burnchain = Burnchain::default_unittest(block_height, &first_burn_hash);
+        burnchain.pox_constants = PoxConstants::test_default();
+
+        let make_commit =
+            |txid: u8, burn_fee: u64, height: u64, input: (Txid, u32)| LeaderBlockCommitOp {
+                sunset_burn: 0,
+                block_header_hash: BlockHeaderHash([txid; 32]),
+                new_seed: VRFSeed([txid; 32]),
+                parent_block_ptr: 0,
+                parent_vtxindex: 0,
+                key_block_ptr: 0,
+                key_vtxindex: 0,
+                memo: vec![0x80],
+                commit_outs: vec![],
+                burn_fee,
+                input,
+                apparent_sender: BurnchainSigner::mock_parts(
+                    AddressHashMode::SerializeP2PKH,
+                    1,
+                    vec![StacksPublicKey::from_hex(
+                        "02d8015134d9db8178ac93acbc43170a2f20febba5087a5b0437058765ad5133d0",
+                    )
+                    .unwrap()],
+                ),
+                txid: Txid([txid; 32]),
+                vtxindex: txid as u32,
+                block_height: height,
+                burn_parent_modulus: ((height - 1) % BURN_BLOCK_MINED_AT_MODULUS) as u8,
+                burn_header_hash: BurnchainHeaderHash([height as u8; 32]),
+            };
+
+        let mut db = SortitionDB::connect_test(block_height, &first_burn_hash).unwrap();
+
+        // one miner commits 100 and then chains a 300 commit off of it; another shows up with a
+        // single 50 commit.
+        let first_commit = make_commit(0x01, 100, block_height + 1, (Txid([0xff; 32]), 0));
+        let chained_commit = make_commit(
+            0x02,
+            300,
+            block_height + 2,
+            (
+                first_commit.txid,
+                LeaderBlockCommitOp::expected_chained_utxo(false),
+            ),
+        );
+        let lone_commit = make_commit(0x03, 50, block_height + 2, (Txid([0xfe; 32]), 0));
+
+        let first_snapshot = test_append_snapshot(
+            &mut db,
+            BurnchainHeaderHash([(block_height + 1) as u8; 32]),
+            &vec![BlockstackOperationType::LeaderBlockCommit(
+                first_commit.clone(),
+            )],
+        );
+        let second_snapshot = test_append_snapshot(
+            &mut db,
+            BurnchainHeaderHash([(block_height + 2) as u8; 32]),
+            &vec![
+                BlockstackOperationType::LeaderBlockCommit(chained_commit.clone()),
+                BlockstackOperationType::LeaderBlockCommit(lone_commit.clone()),
+            ],
+        );
+
+        let ic = db.index_conn();
+
+        // window of [-, 100]: the missing commit counts as 1, so the median is 50
+        let dist =
+            SortitionDB::get_block_burn_distribution(&ic, &burnchain, &first_snapshot).unwrap();
+        assert_eq!(dist.len(), 1);
+        assert_eq!(dist[0].candidate, first_commit);
+        assert_eq!(dist[0].burns, 50);
+
+        // window of [-, 100, 300] for the chained commit, and [-, -, 50] for the lone one
+        let dist =
+            SortitionDB::get_block_burn_distribution(&ic, &burnchain, &second_snapshot).unwrap();
+        assert_eq!(dist.len(), 2);
+        assert_eq!(dist[0].candidate, chained_commit);
+        assert_eq!(dist[0].burns, 100);
+        assert_eq!(dist[1].candidate, lone_commit);
+        assert_eq!(dist[1].burns, 1);
+    }
 }
@@ -308,6 +308,24 @@ pub struct DelegateStxOp {
     pub burn_header_hash: BurnchainHeaderHash, // hash of the burn chain block header
 }
 
+/// A burnchain transaction with the opcode of a Stacks operation (stack-stx, transfer-stx,
+/// delegate-stx or pre-stx) that could not be parsed into one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnparsedStacksOp {
+    /// "stack-stx", "transfer-stx", "delegate-stx" or "pre-stx"
+    pub op_type: String,
+    /// the sender named by the transaction's pre-stx input, if it could be found
+    pub sender: Option<StacksAddress>,
+    /// why the transaction could not be parsed
+    pub reason: String,
+
+    // common to all transactions
+    pub txid: Txid,                            // transaction ID
+    pub vtxindex: u32,                         // index in the block where this tx occurs
+    pub block_height: u64,                     // block height at which this tx occurs
+    pub burn_header_hash: BurnchainHeaderHash, // hash of burn chain block
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BlockstackOperationType {
     LeaderKeyRegister(LeaderKeyRegisterOp),
@@ -347,6 +365,29 @@ impl BlockstackOperationType {
         }
     }
 
+    /// Is this an operation that acts on a Stacks account (stack-stx, transfer-stx,
+    /// delegate-stx, or the pre-stx that names the account)?
+    pub fn is_stacks_op(&self) -> bool {
+        matches!(
+            self,
+            BlockstackOperationType::StackStx(_)
+                | BlockstackOperationType::TransferStx(_)
+                | BlockstackOperationType::DelegateStx(_)
+                | BlockstackOperationType::PreStx(_)
+        )
+    }
+
+    /// The name of this operation's kind, if it is a Stacks operation (see `is_stacks_op()`)
+    pub fn stacks_op_type(&self) -> Option<&'static str> {
+        match self {
+            BlockstackOperationType::StackStx(_) => Some("stack-stx"),
+            BlockstackOperationType::TransferStx(_) => Some("transfer-stx"),
+            BlockstackOperationType::DelegateStx(_) => Some("delegate-stx"),
+            BlockstackOperationType::PreStx(_) => Some("pre-stx"),
+            _ => None,
+        }
+    }
+
     pub fn txid(&self) -> Txid {
         self.txid_ref().clone()
     }
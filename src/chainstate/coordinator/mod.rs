@@ -57,6 +57,7 @@ use crate::chainstate::stacks::{
 use crate::core::{StacksEpoch, StacksEpochId};
 use crate::monitoring::{
     increment_contract_calls_processed, increment_stx_blocks_processed_counter,
+    update_contract_cache_stats,
 };
 use crate::net::atlas::{AtlasConfig, AttachmentInstance};
 use crate::util_lib::db::DBConn;
@@ -2341,6 +2342,20 @@ impl<
                             Error::FailedToProcessSortition(e)
                         })?;
 
+                    // the burnchain DB kept the Stacks operations that could not be parsed;
+                    // record them alongside the ones that could, so their senders can see why
+                    let unparsed_stacks_ops = BurnchainDB::get_unparsed_stacks_ops(
+                        self.burnchain_blocks_db.conn(),
+                        &header.block_hash,
+                    )?;
+                    if !unparsed_stacks_ops.is_empty() {
+                        let mut sort_tx = self.sortition_db.tx_begin_at_tip();
+                        for unparsed_op in unparsed_stacks_ops.iter() {
+                            sort_tx.insert_unparsed_stacks_op_record(unparsed_op)?;
+                        }
+                        sort_tx.commit()?;
+                    }
+
                     next_snapshot
                 }
             };
@@ -2864,6 +2879,10 @@ impl<
             self.dispatcher,
         )?;
 
+        if let Some(stats) = self.chain_state_db.clarity_state.get_contract_cache_stats() {
+            update_contract_cache_stats(&stats);
+        }
+
         while let Some(block_result) = processed_blocks.pop() {
             if block_result.0.is_none() && block_result.1.is_none() {
                 // this block was invalid
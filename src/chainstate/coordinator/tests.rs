@@ -43,7 +43,7 @@ use crate::chainstate::stacks::boot::PoxStartCycleInfo;
 use crate::chainstate::stacks::boot::POX_1_NAME;
 use crate::chainstate::stacks::boot::POX_2_NAME;
 use crate::chainstate::stacks::db::{
-    accounts::MinerReward, ClarityTx, StacksChainState, StacksHeaderInfo,
+    accounts::MinerReward, AccountHistoryCursor, ClarityTx, StacksChainState, StacksHeaderInfo,
 };
 use crate::chainstate::stacks::*;
 use crate::clarity_vm::clarity::ClarityConnection;
@@ -51,6 +51,7 @@ use crate::core;
 use crate::core::*;
 use crate::monitoring::increment_stx_blocks_processed_counter;
 use crate::util_lib::boot::boot_code_addr;
+use crate::util_lib::db::Error as db_error;
 use crate::vm::errors::Error as InterpreterError;
 use clarity::vm::{
     costs::{ExecutionCost, LimitedCostTracker},
@@ -2971,6 +2972,7 @@ fn test_stx_transfer_btc_ops() {
     );
 
     let mut coord = make_coordinator(path, Some(burnchain_conf.clone()));
+    coord.chain_state_db.index_account_history = true;
 
     coord.handle_new_burnchain_block().unwrap();
 
@@ -2995,6 +2997,7 @@ fn test_stx_transfer_btc_ops() {
 
     // track the reward set consumption
     let mut reward_recipients = HashSet::new();
+    let mut transfer_txids = vec![];
     for ix in 0..vrf_keys.len() {
         let vrf_key = &vrf_keys[ix];
         let miner = &committers[ix];
@@ -3087,24 +3090,42 @@ fn test_stx_transfer_btc_ops() {
                 burn_header_hash: BurnchainHeaderHash([0; 32]),
             }));
         } else if ix == 1 {
+            let txid = next_txid();
+            transfer_txids.push(txid);
             ops.push(BlockstackOperationType::TransferStx(TransferStxOp {
                 sender: stacker.clone(),
                 recipient: recipient.clone(),
                 transfered_ustx: transfer_amt,
                 memo: vec![],
-                txid: next_txid(),
+                txid,
                 vtxindex: 5,
                 block_height: 0,
                 burn_header_hash: BurnchainHeaderHash([0; 32]),
             }));
         } else if ix == 2 {
             // shouldn't be accepted -- transfer amount is too large
+            let txid = next_txid();
+            transfer_txids.push(txid);
             ops.push(BlockstackOperationType::TransferStx(TransferStxOp {
                 sender: recipient.clone(),
                 recipient: stacker.clone(),
                 transfered_ustx: transfer_amt + 1,
                 memo: vec![],
-                txid: next_txid(),
+                txid,
+                vtxindex: 5,
+                block_height: 0,
+                burn_header_hash: BurnchainHeaderHash([0; 32]),
+            }));
+        } else if ix == 3 {
+            // shouldn't be accepted -- fails its burnchain checks
+            let txid = next_txid();
+            transfer_txids.push(txid);
+            ops.push(BlockstackOperationType::TransferStx(TransferStxOp {
+                sender: stacker,
+                recipient: stacker,
+                transfered_ustx: transfer_amt,
+                memo: vec![],
+                txid,
                 vtxindex: 5,
                 block_height: 0,
                 burn_header_hash: BurnchainHeaderHash([0; 32]),
@@ -3247,6 +3268,96 @@ fn test_stx_transfer_btc_ops() {
                    "111111111111",
                    "PoX ID should reflect the 5 reward cycles _with_ a known anchor block, plus the 'initial' known reward cycle at genesis");
     }
+
+    // each transfer's outcome was recorded
+    let records =
+        SortitionDB::get_stacks_op_records_by_txid(sort_db.conn(), &transfer_txids[0]).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].rejection_reason, None);
+    let results =
+        StacksChainState::get_burnchain_op_results(chainstate.db(), &transfer_txids[0]).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].committed);
+
+    let records =
+        SortitionDB::get_stacks_op_records_by_txid(sort_db.conn(), &transfer_txids[1]).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].rejection_reason, None);
+    let results =
+        StacksChainState::get_burnchain_op_results(chainstate.db(), &transfer_txids[1]).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(!results[0].committed);
+    assert_eq!(results[0].result, None);
+
+    let records =
+        SortitionDB::get_stacks_op_records_by_txid(sort_db.conn(), &transfer_txids[2]).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(
+        records[0].rejection_reason,
+        Some("Transfer STX must not send to self".to_string())
+    );
+    let results =
+        StacksChainState::get_burnchain_op_results(chainstate.db(), &transfer_txids[2]).unwrap();
+    assert_eq!(results.len(), 0);
+
+    // the stacker's operations are listed newest first
+    let records =
+        SortitionDB::get_stacks_op_records_by_sender(sort_db.conn(), &stacker, 10).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].txid, transfer_txids[2]);
+    assert_eq!(records[1].txid, transfer_txids[0]);
+    match records[2].op {
+        Some(BlockstackOperationType::PreStx(ref op)) => assert_eq!(op.output, stacker),
+        _ => panic!("Expected the stacker's pre-stx op"),
+    }
+
+    // the accepted transfer is in both accounts' histories
+    let stacks_tip_id = StacksBlockId::new(&stacks_tip.0, &stacks_tip.1);
+    let history = chainstate
+        .get_account_history(&stacker.clone().into(), &stacks_tip_id, None, 10)
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].txid, transfer_txids[0]);
+    assert!(history[0].is_sender);
+    assert!(history[0].committed);
+    assert_eq!(history[0].events.len(), 1);
+    assert_eq!(history[0].events[0]["type"], "stx_transfer_event");
+
+    let history = chainstate
+        .get_account_history(&recipient.clone().into(), &stacks_tip_id, None, 10)
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].txid, transfer_txids[0]);
+    assert!(!history[0].is_sender);
+    assert_eq!(
+        history[0].events[0]["stx_transfer_event"]["recipient"],
+        recipient.to_string()
+    );
+
+    // paging past the transfer
+    let cursor = AccountHistoryCursor {
+        block_height: history[0].block_height,
+        txid: history[0].txid.clone(),
+    };
+    let history = chainstate
+        .get_account_history(&recipient.clone().into(), &stacks_tip_id, Some(&cursor), 10)
+        .unwrap();
+    assert!(history.is_empty());
+
+    // a cursor that is not in the principal's history
+    let bad_cursor = AccountHistoryCursor {
+        block_height: cursor.block_height,
+        txid: Txid([0x22; 32]),
+    };
+    match chainstate.get_account_history(
+        &recipient.clone().into(),
+        &stacks_tip_id,
+        Some(&bad_cursor),
+        10,
+    ) {
+        Err(crate::chainstate::stacks::Error::DBError(db_error::NotFoundError)) => {}
+        res => panic!("Expected NotFoundError for an unknown cursor: {:?}", res),
+    }
 }
 
 // This helper function retrieves the delegation info from the delegate address
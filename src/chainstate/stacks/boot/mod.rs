@@ -884,6 +884,7 @@ pub mod test {
     use std::collections::{HashMap, HashSet};
     use std::convert::From;
     use std::fs;
+    use std::sync::Arc;
 
     use crate::burnchains::Address;
     use crate::burnchains::PublicKey;
@@ -1275,7 +1276,10 @@ pub mod test {
         account
     }
 
-    fn get_contract(peer: &mut TestPeer, addr: &QualifiedContractIdentifier) -> Option<Contract> {
+    fn get_contract(
+        peer: &mut TestPeer,
+        addr: &QualifiedContractIdentifier,
+    ) -> Option<Arc<Contract>> {
         let contract_opt = with_sortdb(peer, |ref mut chainstate, ref mut sortdb| {
             let (consensus_hash, block_bhh) =
                 SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).unwrap();
@@ -6268,6 +6268,13 @@ impl StacksChainState {
             .as_ref()
             .map(|(_, _, _, info)| info.clone());
 
+        let burn_op_txids: Vec<_> = burn_stack_stx_ops
+            .iter()
+            .map(|op| op.txid)
+            .chain(burn_transfer_stx_ops.iter().map(|op| op.txid))
+            .chain(burn_delegate_stx_ops.iter().map(|op| op.txid))
+            .collect();
+
         let new_tip = StacksChainState::advance_tip(
             &mut chainstate_tx.tx,
             &parent_chain_tip.anchored_header,
@@ -6291,6 +6298,22 @@ impl StacksChainState {
         )
         .expect("FATAL: failed to advance chain tip");
 
+        StacksChainState::store_burnchain_op_results(
+            chainstate_tx.tx.deref(),
+            &new_tip.index_block_hash(),
+            &burn_op_txids,
+            &tx_receipts,
+        )?;
+
+        if chainstate_tx.index_account_history {
+            StacksChainState::store_account_history(
+                chainstate_tx.tx.deref(),
+                &new_tip.index_block_hash(),
+                new_tip.stacks_block_height,
+                &tx_receipts,
+            )?;
+        }
+
         chainstate_tx.log_transactions_processed(&new_tip.index_block_hash(), &tx_receipts);
 
         set_last_block_transaction_count(block.txs.len() as u64);
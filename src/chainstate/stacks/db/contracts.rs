@@ -25,6 +25,7 @@ use crate::chainstate::stacks::Error;
 use crate::chainstate::stacks::*;
 
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::util_lib::db::Error as db_error;
 use crate::util_lib::db::{query_count, query_rows, DBConn};
@@ -57,7 +58,7 @@ impl StacksChainState {
     pub fn get_contract<T: ClarityConnection>(
         clarity_tx: &mut T,
         contract_id: &QualifiedContractIdentifier,
-    ) -> Result<Option<Contract>, Error> {
+    ) -> Result<Option<Arc<Contract>>, Error> {
         clarity_tx
             .with_clarity_db_readonly(|ref mut db| match db.get_contract(contract_id) {
                 Ok(c) => Ok(Some(c)),
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{btree_map::Entry, BTreeMap, HashSet};
+use std::collections::{btree_map::Entry, BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::fs;
 use std::io;
@@ -36,7 +36,9 @@ use crate::burnchains::{Address, Burnchain, BurnchainParameters, PoxConstants};
 use crate::chainstate::burn::db::sortdb::BlockHeaderCache;
 use crate::chainstate::burn::db::sortdb::*;
 use crate::chainstate::burn::db::sortdb::{SortitionDB, SortitionDBConn};
-use crate::chainstate::burn::operations::{DelegateStxOp, StackStxOp, TransferStxOp};
+use crate::chainstate::burn::operations::{
+    BlockstackOperationType, DelegateStxOp, StackStxOp, TransferStxOp,
+};
 use crate::chainstate::burn::ConsensusHash;
 use crate::chainstate::stacks::boot::*;
 use crate::chainstate::stacks::db::accounts::*;
@@ -66,8 +68,8 @@ use crate::net::Error as net_error;
 use crate::net::MemPoolSyncData;
 use crate::util_lib::db::Error as db_error;
 use crate::util_lib::db::{
-    query_count, query_row, tx_begin_immediate, tx_busy_handler, DBConn, DBTx, FromColumn, FromRow,
-    IndexDBConn, IndexDBTx,
+    query_count, query_row, query_rows, tx_begin_immediate, tx_busy_handler, u64_to_sql, DBConn,
+    DBTx, FromColumn, FromRow, IndexDBConn, IndexDBTx,
 };
 use clarity::vm::analysis::analysis_db::AnalysisDatabase;
 use clarity::vm::analysis::run_analysis;
@@ -75,13 +77,15 @@ use clarity::vm::clarity::TransactionConnection;
 use clarity::vm::contexts::OwnedEnvironment;
 use clarity::vm::costs::{ExecutionCost, LimitedCostTracker};
 use clarity::vm::database::{
-    BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
+    BurnStateDB, ClarityDatabase, ClaritySerializable, HeadersDB, STXBalance, SqliteConnection,
+    NULL_BURN_STATE_DB,
 };
 
 use crate::clarity_vm::clarity::PreCommitClarityBlock;
 use clarity::vm::events::*;
 use clarity::vm::representations::ClarityName;
 use clarity::vm::representations::ContractName;
+use clarity::vm::types::StacksAddressExtensions as ClarityStacksAddressExtensions;
 use clarity::vm::types::TupleData;
 use stacks_common::util;
 use stacks_common::util::hash::to_hex;
@@ -133,6 +137,9 @@ pub struct StacksChainState {
     pub root_path: String,
     pub unconfirmed_state: Option<UnconfirmedState>,
     pub fault_injection: StacksChainStateFaults,
+    /// if true, then record the transactions and asset events that affect each principal as
+    /// blocks are appended, so they can be served as account history.
+    pub index_account_history: bool,
     marf_opts: Option<MARFOpenOpts>,
 }
 
@@ -271,6 +278,107 @@ impl StacksHeaderInfo {
     }
 }
 
+/// The outcome of applying a burnchain-hosted Stacks operation in a Stacks block
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurnchainOpResult {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub stacks_block_height: u64,
+    /// whether or not the operation's effects were committed
+    pub committed: bool,
+    /// the Clarity result of the operation, or None if it could not be evaluated at all
+    pub result: Option<Value>,
+}
+
+impl FromRow<BurnchainOpResult> for BurnchainOpResult {
+    fn from_row(row: &Row) -> Result<BurnchainOpResult, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let stacks_block_height = u64::from_column(row, "block_height")?;
+        let committed_i64: i64 = row.get_unwrap("committed");
+        let result_hex: Option<String> = row.get_unwrap("result");
+        let result = match result_hex {
+            Some(hex) => {
+                Some(Value::try_deserialize_hex_untyped(&hex).map_err(|_| db_error::ParseError)?)
+            }
+            None => None,
+        };
+
+        Ok(BurnchainOpResult {
+            txid,
+            index_block_hash,
+            stacks_block_height,
+            committed: committed_i64 != 0,
+            result,
+        })
+    }
+}
+
+/// Where a page of account history ends: the block height and txid of the last transaction
+/// in the page.  The next page starts with the transaction before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountHistoryCursor {
+    pub block_height: u64,
+    pub txid: Txid,
+}
+
+impl fmt::Display for AccountHistoryCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.block_height, &self.txid)
+    }
+}
+
+impl AccountHistoryCursor {
+    /// Parse a cursor in the `<block_height>:<txid>` form it is displayed in
+    pub fn parse(s: &str) -> Option<AccountHistoryCursor> {
+        let (height_str, txid_str) = s.split_once(':')?;
+        let block_height = height_str.parse::<u64>().ok()?;
+        let txid = Txid::from_hex(txid_str).ok()?;
+        Some(AccountHistoryCursor { block_height, txid })
+    }
+}
+
+/// A transaction that affected a principal, as recorded in the account history index
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountHistoryEntry {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    /// position of the transaction's receipt in the block's receipts.  Transactions from the
+    /// microblocks the block confirmed come first.
+    pub receipt_index: u32,
+    /// whether or not the principal sent or sponsored the transaction
+    pub is_sender: bool,
+    /// whether or not the transaction's effects were committed
+    pub committed: bool,
+    /// the transaction's STX, FT and NFT events that affect the principal, in the same JSON
+    /// encoding that is sent to event observers
+    pub events: Vec<serde_json::Value>,
+}
+
+impl FromRow<AccountHistoryEntry> for AccountHistoryEntry {
+    fn from_row(row: &Row) -> Result<AccountHistoryEntry, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let receipt_index: u32 = row.get_unwrap("receipt_index");
+        let is_sender_i64: i64 = row.get_unwrap("is_sender");
+        let committed_i64: i64 = row.get_unwrap("committed");
+        let events_json: String = row.get_unwrap("events");
+        let events = serde_json::from_str(&events_json).map_err(|_| db_error::ParseError)?;
+
+        Ok(AccountHistoryEntry {
+            txid,
+            index_block_hash,
+            block_height,
+            receipt_index,
+            is_sender: is_sender_i64 != 0,
+            committed: committed_i64 != 0,
+            events,
+        })
+    }
+}
+
 impl FromRow<DBConfig> for DBConfig {
     fn from_row<'a>(row: &'a Row) -> Result<DBConfig, db_error> {
         let version: String = row.get_unwrap("version");
@@ -452,6 +560,7 @@ pub struct ChainstateTx<'a> {
     pub blocks_path: String,
     pub tx: StacksDBTx<'a>,
     pub root_path: String,
+    pub index_account_history: bool,
 }
 
 impl<'a> ChainstateTx<'a> {
@@ -460,12 +569,14 @@ impl<'a> ChainstateTx<'a> {
         blocks_path: String,
         root_path: String,
         config: DBConfig,
+        index_account_history: bool,
     ) -> ChainstateTx<'a> {
         ChainstateTx {
             config,
             blocks_path,
             tx,
             root_path,
+            index_account_history,
         }
     }
 
@@ -607,7 +718,7 @@ pub struct TxStreamData {
     pub corked: bool,
 }
 
-pub const CHAINSTATE_VERSION: &'static str = "3";
+pub const CHAINSTATE_VERSION: &str = "5";
 
 const CHAINSTATE_INITIAL_SCHEMA: &'static [&'static str] = &[
     "PRAGMA foreign_keys = ON;",
@@ -812,6 +923,52 @@ const CHAINSTATE_SCHEMA_3: &'static [&'static str] = &[
     "#,
 ];
 
+const CHAINSTATE_SCHEMA_4: &[&str] = &[
+    // table of the outcome of each on-burnchain stacks operation that a block applied, so
+    // the sender can find out whether or not it took effect.
+    r#"
+    CREATE TABLE burnchain_op_results(
+        txid TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        -- 1 if the operation's effects were committed, 0 if not
+        committed INTEGER NOT NULL,
+        -- hex-encoded Clarity result, or NULL if the operation could not be evaluated
+        result TEXT,
+
+        PRIMARY KEY(txid,index_block_hash)
+    );"#,
+    r#"
+    UPDATE db_config SET version = "4";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_5: &[&str] = &[
+    // table of the transactions that affect each principal, for nodes that index account
+    // history.  A transaction affects a principal if the principal sent or sponsored it, or if
+    // one of its STX, FT or NFT events moved assets to or from the principal.
+    r#"
+    CREATE TABLE account_history(
+        principal TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        -- position of the transaction's receipt in the block's receipts
+        receipt_index INTEGER NOT NULL,
+        txid TEXT NOT NULL,
+        -- 1 if the principal sent or sponsored the transaction, 0 if not
+        is_sender INTEGER NOT NULL,
+        -- 1 if the transaction's effects were committed, 0 if not
+        committed INTEGER NOT NULL,
+        -- JSON array of the transaction's events that affect the principal
+        events TEXT NOT NULL,
+
+        PRIMARY KEY(principal,index_block_hash,receipt_index)
+    );"#,
+    "CREATE INDEX index_account_history_by_height ON account_history(principal,block_height,receipt_index);",
+    r#"
+    UPDATE db_config SET version = "5";
+    "#,
+];
+
 const CHAINSTATE_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS index_block_hash_to_primary_key ON block_headers(index_block_hash,consensus_hash,block_hash);",
     "CREATE INDEX IF NOT EXISTS block_headers_hash_index ON block_headers(block_hash,block_height);",
@@ -840,6 +997,9 @@ const CHAINSTATE_INDEXES: &'static [&'static str] = &[
 
 pub use stacks_common::consts::MINER_REWARD_MATURITY;
 
+/// How many account history rows to read at a time when looking for the ones on a given fork
+const ACCOUNT_HISTORY_SCAN_PAGE_SIZE: u64 = 256;
+
 // fraction (out of 100) of the coinbase a user will receive for reporting a microblock stream fork
 pub const POISON_MICROBLOCK_COMMISSION_FRACTION: u128 = 5;
 
@@ -1024,6 +1184,20 @@ impl StacksChainState {
                         }
                     }
                     "3" => {
+                        // migrate to 4
+                        info!("Migrating chainstate schema from version 3 to 4");
+                        for cmd in CHAINSTATE_SCHEMA_4.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "4" => {
+                        // migrate to 5
+                        info!("Migrating chainstate schema from version 4 to 5");
+                        for cmd in CHAINSTATE_SCHEMA_5.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "5" => {
                         // done
                         break;
                     }
@@ -1732,6 +1906,7 @@ impl StacksChainState {
             root_path: path_str.to_string(),
             unconfirmed_state: None,
             fault_injection: StacksChainStateFaults::new(),
+            index_account_history: false,
             marf_opts: marf_opts,
         };
 
@@ -1787,8 +1962,13 @@ impl StacksChainState {
         let clarity_instance = &mut self.clarity_state;
         let inner_tx = StacksDBTx::new(&mut self.state_index, ());
 
-        let chainstate_tx =
-            ChainstateTx::new(inner_tx, blocks_path, self.root_path.clone(), config);
+        let chainstate_tx = ChainstateTx::new(
+            inner_tx,
+            blocks_path,
+            self.root_path.clone(),
+            config,
+            self.index_account_history,
+        );
 
         Ok((chainstate_tx, clarity_instance))
     }
@@ -2367,6 +2547,251 @@ impl StacksChainState {
         Ok(())
     }
 
+    /// Store the outcome of each on-burnchain STX operation applied by the given block.  An
+    /// operation without a receipt could not be evaluated at all.
+    pub(crate) fn store_burnchain_op_results(
+        tx: &DBTx,
+        index_block_hash: &StacksBlockId,
+        burn_op_txids: &[Txid],
+        tx_receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        for txid in burn_op_txids.iter() {
+            let result = tx_receipts
+                .iter()
+                .find(|receipt| match receipt.transaction {
+                    TransactionOrigin::Burn(ref op) => op.txid_ref() == txid,
+                    TransactionOrigin::Stacks(_) => false,
+                })
+                .map(|receipt| &receipt.result);
+
+            let committed = match result {
+                Some(Value::Response(ref resp)) => resp.committed,
+                Some(_) => true,
+                None => false,
+            };
+
+            let sql = "INSERT OR REPLACE INTO burnchain_op_results (txid, index_block_hash, committed, result) VALUES (?1, ?2, ?3, ?4)";
+            let args: &[&dyn ToSql] = &[
+                txid,
+                index_block_hash,
+                &committed,
+                &result.map(|value| value.serialize()),
+            ];
+            tx.execute(sql, args)?;
+        }
+        Ok(())
+    }
+
+    /// Get the outcome of an on-burnchain STX operation in each Stacks block that applied it.
+    /// There can be more than one such block if the Stacks chain forked.
+    pub fn get_burnchain_op_results(
+        conn: &Connection,
+        txid: &Txid,
+    ) -> Result<Vec<BurnchainOpResult>, Error> {
+        let sql = "SELECT burnchain_op_results.*, block_headers.block_height FROM burnchain_op_results JOIN block_headers ON burnchain_op_results.index_block_hash = block_headers.index_block_hash WHERE burnchain_op_results.txid = ?1 ORDER BY block_headers.block_height";
+        let args: &[&dyn ToSql] = &[txid];
+        query_rows(conn, sql, args).map_err(Error::DBError)
+    }
+
+    /// Get the principals whose STX, fungible or non-fungible tokens an event moved.  Contract
+    /// events (i.e. `print`) do not affect any principal.
+    fn get_event_principals(event: &StacksTransactionEvent) -> Vec<&PrincipalData> {
+        match event {
+            StacksTransactionEvent::SmartContractEvent(_) => vec![],
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => {
+                vec![&data.sender, &data.recipient]
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(data)) => {
+                vec![&data.recipient]
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(data)) => {
+                vec![&data.sender]
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(data)) => {
+                vec![&data.locked_address]
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => {
+                vec![&data.sender, &data.recipient]
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(data)) => {
+                vec![&data.recipient]
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(data)) => {
+                vec![&data.sender]
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => {
+                vec![&data.sender, &data.recipient]
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(data)) => {
+                vec![&data.recipient]
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(data)) => {
+                vec![&data.sender]
+            }
+        }
+    }
+
+    /// Record, for each principal, the transactions in the given block's receipts that affect
+    /// it: the ones it sent or sponsored (including burnchain-hosted STX operations), and the
+    /// ones whose events moved its assets.  Events
+    /// are numbered across the whole block, as they are for event observers.
+    pub(crate) fn store_account_history(
+        tx: &DBTx,
+        index_block_hash: &StacksBlockId,
+        block_height: u64,
+        tx_receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        let mut event_index = 0;
+        for (receipt_index, receipt) in tx_receipts.iter().enumerate() {
+            let txid = receipt.transaction.txid();
+            let events_committed = !receipt.post_condition_aborted;
+            let committed = events_committed
+                && match receipt.result {
+                    Value::Response(ref resp) => resp.committed,
+                    _ => true,
+                };
+
+            // principal --> (is_sender, events)
+            let mut affected: BTreeMap<String, (bool, Vec<serde_json::Value>)> = BTreeMap::new();
+            let senders: Vec<StacksAddress> = match receipt.transaction {
+                TransactionOrigin::Stacks(ref stacks_tx) => Some(stacks_tx.origin_address())
+                    .into_iter()
+                    .chain(stacks_tx.sponsor_address())
+                    .collect(),
+                TransactionOrigin::Burn(BlockstackOperationType::StackStx(ref op)) => {
+                    vec![op.sender.clone()]
+                }
+                TransactionOrigin::Burn(BlockstackOperationType::TransferStx(ref op)) => {
+                    vec![op.sender.clone()]
+                }
+                TransactionOrigin::Burn(BlockstackOperationType::DelegateStx(ref op)) => {
+                    vec![op.sender.clone()]
+                }
+                TransactionOrigin::Burn(_) => vec![],
+            };
+            for sender in senders {
+                affected
+                    .entry(sender.to_account_principal().to_string())
+                    .or_insert_with(|| (true, vec![]));
+            }
+            for event in receipt.events.iter() {
+                let mut principals = StacksChainState::get_event_principals(event);
+                principals.dedup();
+                for principal in principals {
+                    affected
+                        .entry(principal.to_string())
+                        .or_insert_with(|| (false, vec![]))
+                        .1
+                        .push(event.json_serialize(event_index, &txid, events_committed));
+                }
+                event_index += 1;
+            }
+
+            for (principal, (is_sender, events)) in affected.into_iter() {
+                let sql = "INSERT OR REPLACE INTO account_history (principal, index_block_hash, block_height, receipt_index, txid, is_sender, committed, events) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+                let args: &[&dyn ToSql] = &[
+                    &principal,
+                    index_block_hash,
+                    &u64_to_sql(block_height)?,
+                    &(receipt_index as u32),
+                    &txid,
+                    &is_sender,
+                    &committed,
+                    &serde_json::Value::Array(events).to_string(),
+                ];
+                tx.execute(sql, args)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the transactions that affected a principal in the fork ending at `tip`, most recent
+    /// first.  If `cursor` is given, start with the transaction before it.  Returns at most
+    /// `limit` of them.  Only blocks appended while the account history index was enabled are
+    /// covered.  Returns NoSuchBlockError if `tip` is not a processed block, and
+    /// DBError(NotFoundError) if `cursor` is not a transaction in the principal's history on
+    /// this fork.
+    pub fn get_account_history(
+        &self,
+        principal: &PrincipalData,
+        tip: &StacksBlockId,
+        cursor: Option<&AccountHistoryCursor>,
+        limit: u64,
+    ) -> Result<Vec<AccountHistoryEntry>, Error> {
+        let tip_height = match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            self.db(),
+            tip,
+        )? {
+            Some(tip_header) => tip_header.stacks_block_height,
+            None => return Err(Error::NoSuchBlockError),
+        };
+
+        let index_conn = self.index_conn()?;
+        let principal_str = principal.to_string();
+
+        // rows are visited in (block_height, receipt_index, index_block_hash) order, descending.
+        // Resume after the cursor's row, or start at the tip.
+        let (mut before_height, mut before_receipt_index, mut before_block) = match cursor {
+            Some(cursor) => {
+                if cursor.block_height > tip_height {
+                    return Err(Error::DBError(db_error::NotFoundError));
+                }
+                let cursor_block = index_conn
+                    .get_ancestor_block_hash(cursor.block_height, tip)?
+                    .ok_or(Error::DBError(db_error::NotFoundError))?;
+                let sql = "SELECT receipt_index FROM account_history WHERE principal = ?1 AND index_block_hash = ?2 AND txid = ?3";
+                let args: &[&dyn ToSql] = &[&principal_str, &cursor_block, &cursor.txid];
+                let receipt_index: u64 = query_row(self.db(), sql, args)?
+                    .ok_or(Error::DBError(db_error::NotFoundError))?;
+                (cursor.block_height, receipt_index, "".to_string())
+            }
+            None => (tip_height + 1, 0, "".to_string()),
+        };
+
+        // block height --> the block at that height in the fork ending at `tip`
+        let mut ancestors: HashMap<u64, Option<StacksBlockId>> = HashMap::new();
+        let mut entries = vec![];
+
+        // entries from blocks off of this fork are skipped, so read the rows in pages until
+        // there are enough.
+        let sql = "SELECT * FROM account_history WHERE principal = ?1 AND (block_height, receipt_index, index_block_hash) < (?2, ?3, ?4) ORDER BY block_height DESC, receipt_index DESC, index_block_hash DESC LIMIT ?5";
+        loop {
+            let args: &[&dyn ToSql] = &[
+                &principal_str,
+                &u64_to_sql(before_height)?,
+                &u64_to_sql(before_receipt_index)?,
+                &before_block,
+                &u64_to_sql(ACCOUNT_HISTORY_SCAN_PAGE_SIZE)?,
+            ];
+            let rows: Vec<AccountHistoryEntry> = query_rows(self.db(), sql, args)?;
+            if rows.is_empty() {
+                return Ok(entries);
+            }
+
+            for row in rows.into_iter() {
+                before_height = row.block_height;
+                before_receipt_index = row.receipt_index.into();
+                before_block = row.index_block_hash.to_string();
+
+                let ancestor = match ancestors.get(&row.block_height) {
+                    Some(ancestor) => ancestor.clone(),
+                    None => {
+                        let ancestor = index_conn.get_ancestor_block_hash(row.block_height, tip)?;
+                        ancestors.insert(row.block_height, ancestor.clone());
+                        ancestor
+                    }
+                };
+                if ancestor.as_ref() != Some(&row.index_block_hash) {
+                    continue;
+                }
+                entries.push(row);
+                if entries.len() as u64 >= limit {
+                    return Ok(entries);
+                }
+            }
+        }
+    }
+
     /// Append a Stacks block to an existing Stacks block, and grant the miner the block reward.
     /// Return the new Stacks header info.
     pub fn advance_tip<'a>(
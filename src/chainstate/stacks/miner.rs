@@ -1139,6 +1139,9 @@ impl<'a> StacksMicroblockBuilder<'a> {
         let deadline = get_epoch_time_ms() + (self.settings.max_miner_time_ms as u128);
         let mut block_limit_hit = BlockLimitFunction::NO_LIMIT_HIT;
 
+        let selection_policy = &mempool_settings.selection_policy;
+        let mut contract_costs = HashMap::new(); // budget used by calls to each contract
+
         mem_pool.reset_nonce_cache()?;
         let stacks_epoch_id = clarity_tx.get_epoch();
         let block_limit = clarity_tx
@@ -1186,6 +1189,25 @@ impl<'a> StacksMicroblockBuilder<'a> {
                             considered.insert(mempool_tx.tx.txid());
                         }
 
+                        let estimated_cost = if selection_policy.uses_cost_estimate() {
+                            estimator
+                                .estimate_cost(&mempool_tx.tx.payload, &stacks_epoch_id)
+                                .ok()
+                        } else {
+                            None
+                        };
+                        if let Some(reason) = selection_policy.check_tx(
+                            &mempool_tx.tx,
+                            &clarity_tx.cost_so_far(),
+                            &block_limit,
+                            &contract_costs,
+                            estimated_cost.as_ref(),
+                        ) {
+                            return Ok(Some(
+                                TransactionResult::skipped(&mempool_tx.tx, reason).convert_to_event(),
+                            ));
+                        }
+
                         match StacksMicroblockBuilder::mine_next_transaction(
                             clarity_tx,
                             mempool_tx.tx.clone(),
@@ -1203,6 +1225,15 @@ impl<'a> StacksMicroblockBuilder<'a> {
                                     }) => {
                                         bytes_so_far += mempool_tx.metadata.len;
 
+                                        if let Some(contract_id) =
+                                            TxSelectionPolicy::billed_contract(&mempool_tx.tx)
+                                        {
+                                            let _ = contract_costs
+                                                .entry(contract_id)
+                                                .or_insert_with(ExecutionCost::zero)
+                                                .add(&receipt.execution_cost);
+                                        }
+
                                         if update_estimator {
                                             if let Err(e) = estimator.notify_event(
                                                 &mempool_tx.tx.payload,
@@ -2422,6 +2453,9 @@ impl StacksBlockBuilder {
         let mut num_txs = 0;
         let mut blocked = false;
 
+        let selection_policy = &mempool_settings.selection_policy;
+        let mut contract_costs = HashMap::new(); // block budget used by calls to each contract
+
         debug!(
             "Anchored block transaction selection begins (child of {})",
             &parent_stacks_header.anchored_header.block_hash()
@@ -2503,6 +2537,25 @@ impl StacksBlockBuilder {
                         considered.insert(txinfo.tx.txid());
                         num_considered += 1;
 
+                        let estimated_cost = if selection_policy.uses_cost_estimate() {
+                            estimator
+                                .estimate_cost(&txinfo.tx.payload, &stacks_epoch_id)
+                                .ok()
+                        } else {
+                            None
+                        };
+                        if let Some(reason) = selection_policy.check_tx(
+                            &txinfo.tx,
+                            &epoch_tx.cost_so_far(),
+                            &block_limit,
+                            &contract_costs,
+                            estimated_cost.as_ref(),
+                        ) {
+                            return Ok(Some(
+                                TransactionResult::skipped(&txinfo.tx, reason).convert_to_event(),
+                            ));
+                        }
+
                         let tx_result = builder.try_mine_tx_with_len(
                             epoch_tx,
                             &txinfo.tx,
@@ -2527,6 +2580,14 @@ impl StacksBlockBuilder {
                                               "error" => ?e);
                                     }
                                 }
+                                if let Some(contract_id) =
+                                    TxSelectionPolicy::billed_contract(&txinfo.tx)
+                                {
+                                    let _ = contract_costs
+                                        .entry(contract_id)
+                                        .or_insert_with(ExecutionCost::zero)
+                                        .add(&receipt.execution_cost);
+                                }
                                 mined_origin_nonces.insert(
                                     txinfo.tx.origin_address(),
                                     txinfo.tx.get_origin_nonce(),
@@ -4343,6 +4343,126 @@ fn test_fee_order_mismatch_nonce_order() {
     assert_eq!(stacks_block.txs.len(), 3);
 }
 
+#[test]
+fn test_build_anchored_blocks_tx_selection_policy() {
+    let privks: Vec<_> = [
+        "42faca653724860da7a41bfcef7e6ba78db55146f6900de8cb2a9f760ffac70c01",
+        "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+    ]
+    .iter()
+    .map(|hex| StacksPrivateKey::from_hex(hex).unwrap())
+    .collect();
+    let addrs: Vec<_> = privks
+        .iter()
+        .map(|privk| {
+            StacksAddress::from_public_keys(
+                C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                &AddressHashMode::SerializeP2PKH,
+                1,
+                &vec![StacksPublicKey::from_private(privk)],
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let mut peer_config = TestPeerConfig::new(function_name!(), 2026, 2027);
+    peer_config.initial_balances = addrs
+        .iter()
+        .map(|addr| (addr.to_account_principal(), 1000000000))
+        .collect();
+
+    let mut peer = TestPeer::new(peer_config);
+
+    let chainstate_path = peer.chainstate_path.clone();
+
+    let recipient_addr_str = "ST1RFD5Q2QPK3E0F08HG9XDX7SSC7CNRS0QR0SGEV";
+    let recipient = StacksAddress::from_string(recipient_addr_str).unwrap();
+
+    // send transactions to the mempool
+    let tip =
+        SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn()).unwrap();
+
+    let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+        |ref mut miner,
+         ref mut sortdb,
+         ref mut chainstate,
+         vrf_proof,
+         ref parent_opt,
+         ref parent_microblock_header_opt| {
+            let parent_tip = match parent_opt {
+                None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                Some(block) => {
+                    let ic = sortdb.index_conn();
+                    let snapshot = SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                        &ic,
+                        &tip.sortition_id,
+                        &block.block_hash(),
+                    )
+                    .unwrap()
+                    .unwrap(); // succeeds because we don't fork
+                    StacksChainState::get_anchored_block_header_info(
+                        chainstate.db(),
+                        &snapshot.consensus_hash,
+                        &snapshot.winning_stacks_block_hash,
+                    )
+                    .unwrap()
+                    .unwrap()
+                }
+            };
+
+            let parent_header_hash = parent_tip.anchored_header.block_hash();
+            let parent_consensus_hash = parent_tip.consensus_hash.clone();
+
+            let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+            let coinbase_tx = make_coinbase(miner, 0);
+
+            for privk in privks.iter() {
+                let stx_transfer =
+                    make_user_stacks_transfer(privk, 0, 200, &recipient.to_account_principal(), 1);
+                mempool
+                    .submit(
+                        chainstate,
+                        &parent_consensus_hash,
+                        &parent_header_hash,
+                        &stx_transfer,
+                        None,
+                        &ExecutionCost::max_value(),
+                        &StacksEpochId::Epoch20,
+                    )
+                    .unwrap();
+            }
+
+            // the miner won't select anything sent by the second account
+            let mut settings = BlockBuilderSettings::max_value();
+            settings.mempool_settings.selection_policy.deny_list =
+                vec![addrs[1].to_account_principal()].into_iter().collect();
+
+            let anchored_block = StacksBlockBuilder::build_anchored_block(
+                chainstate,
+                &sortdb.index_conn(),
+                &mut mempool,
+                &parent_tip,
+                tip.total_burn,
+                vrf_proof,
+                Hash160([0 as u8; 20]),
+                &coinbase_tx,
+                settings,
+                None,
+            )
+            .unwrap();
+            (anchored_block.0, vec![])
+        },
+    );
+
+    peer.next_burnchain_block(burn_ops.clone());
+    peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+
+    // Only the coinbase and the first account's transaction should have been mined.
+    assert_eq!(stacks_block.txs.len(), 2);
+    assert_eq!(stacks_block.txs[1].origin_address(), addrs[0]);
+}
+
 #[test]
 fn mempool_walk_test_users_1_rounds_10_cache_size_2_null_prob_0() {
     paramaterized_mempool_walk_test(1, 10, 2, 0, 30000)
@@ -26,7 +26,9 @@ use std::process;
 
 use clarity::util::get_epoch_time_ms;
 use clarity::vm::coverage::CoverageReporter;
-use rand::Rng;
+use clarity::vm::profiler::{CostDimension, CostProfiler};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rusqlite::types::ToSql;
 use rusqlite::Row;
 use rusqlite::Transaction;
@@ -36,14 +38,24 @@ use crate::chainstate::stacks::index::{storage::TrieFileStorage, MarfTrieId};
 use crate::util_lib::db::sqlite_open;
 use crate::util_lib::db::FromColumn;
 use stacks_common::address::c32::c32_address;
+use stacks_common::address::{
+    C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
 use stacks_common::util::hash::{bytes_to_hex, Hash160, Sha512Trunc256Sum};
 
 use crate::clarity::{
     vm::analysis,
-    vm::analysis::contract_interface_builder::build_contract_interface,
+    vm::analysis::call_graph::CallGraph,
+    vm::analysis::contract_interface_builder::{
+        build_contract_interface, ContractInterfaceAtomType, ContractInterfaceFunction,
+        ContractInterfaceFunctionAccess,
+    },
+    vm::analysis::cost_estimate::CostEstimate,
+    vm::analysis::lint::Linter,
     vm::analysis::{errors::CheckError, errors::CheckResult, AnalysisDatabase, ContractAnalysis},
     vm::ast,
     vm::ast::build_ast_with_rules,
+    vm::ast::format::format_source,
     vm::ast::ASTRules,
     vm::contexts::GlobalContext,
     vm::contexts::{AssetMap, OwnedEnvironment},
@@ -54,7 +66,11 @@ use crate::clarity::{
     },
     vm::errors::{Error, InterpreterResult, RuntimeErrorType},
     vm::eval_all,
-    vm::types::{OptionalData, PrincipalData, QualifiedContractIdentifier},
+    vm::functions::define::DefineFunctionsParsed,
+    vm::types::{
+        OptionalData, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, TupleData,
+    },
+    vm::ClarityName,
     vm::ClarityVersion,
     vm::ContractContext,
     vm::ContractName,
@@ -156,6 +172,11 @@ where command is one of:
   eval_raw           to typecheck and evaluate an expression without a contract or database context.
   repl               to typecheck and evaluate expressions in a stdin/stdout loop.
   execute            to execute a public function of a defined contract.
+  test               to run the `test-*` public functions of a set of `*-test` contracts.
+  fuzz               to check a contract's `invariant-*` functions against random calls.
+  deps               to list the calls, data and assets each function of a contract touches.
+  costs              to estimate the worst-case cost of each public and read-only function.
+  fmt                to rewrite contracts in the canonical Clarity style.
   generate_address   to generate a random Stacks public address for testing purposes.
 ",
         invoked_by
@@ -449,6 +470,7 @@ fn with_env_costs<F, R>(
     header_db: &CLIHeadersDB,
     marf: &mut WritableMarfStore,
     coverage: Option<&mut CoverageReporter>,
+    profiler: Option<&mut CostProfiler>,
     f: F,
 ) -> (R, ExecutionCost)
 where
@@ -477,6 +499,9 @@ where
     if let Some(coverage) = coverage {
         vm_env.add_eval_hook(coverage);
     }
+    if let Some(profiler) = profiler {
+        vm_env.add_eval_hook(profiler);
+    }
     let result = f(&mut vm_env);
     let cost = vm_env.get_cost_total();
     (result, cost)
@@ -670,6 +695,24 @@ impl CLIHeadersDB {
 
         (parent_block_hash, next_block_hash)
     }
+
+    /// Record a new block that is not a descendant of the CLI chain tip.  Used by
+    ///  `clarity-cli test` to build a fork of the chain for each test.
+    pub fn add_cli_block(&mut self) -> StacksBlockId {
+        let random_bytes = rand::thread_rng().gen::<[u8; 32]>();
+        let block_hash = friendly_expect_opt(
+            StacksBlockId::from_bytes(&random_bytes),
+            "Failed to generate random block header.",
+        );
+        friendly_expect(
+            self.conn.execute(
+                "INSERT INTO cli_chain_tips (block_hash) VALUES (?1)",
+                &[&block_hash],
+            ),
+            &format!("FATAL: failed to store block hash in '{}'", &self.db_path),
+        );
+        block_hash
+    }
 }
 
 impl HeadersDB for CLIHeadersDB {
@@ -852,6 +895,618 @@ fn consume_arg(
     }
 }
 
+/// A `test-*` public function discovered in a test contract (see `is_test_contract()`) passed to
+///  `clarity-cli test`, along with
+///  the settings read from the `;; @` annotations in the comment block directly above it:
+///
+///   ;; @sender 'ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5
+///   ;; @mine-blocks 10
+///   ;; @mint 'ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5 1000000
+///
+/// `@sender` sets `tx-sender` (by default, the test contract's issuer), `@mine-blocks` advances
+///  the chain by that many empty blocks before the test runs, and `@mint` (which may be repeated)
+///  credits a principal with uSTX in the test's block.
+struct UnitTest {
+    contract_identifier: QualifiedContractIdentifier,
+    name: String,
+    sender: PrincipalData,
+    mine_blocks: u32,
+    mints: Vec<(PrincipalData, u128)>,
+}
+
+fn parse_test_principal(arg: &str) -> Result<PrincipalData, String> {
+    let arg = arg.strip_prefix('\'').unwrap_or(arg);
+    PrincipalData::parse(arg).map_err(|e| format!("invalid principal '{}': {}", arg, e))
+}
+
+/// Read the `;; @` annotations in the comment lines directly above `line` (1-indexed) of `source`
+fn parse_test_annotations(source: &str, line: u32, unit_test: &mut UnitTest) -> Result<(), String> {
+    let lines: Vec<_> = source.lines().collect();
+    let mut comment_lines = vec![];
+    let mut idx = (line as usize).saturating_sub(1);
+    while idx > 0 {
+        idx -= 1;
+        let comment = match lines.get(idx).and_then(|l| l.trim().strip_prefix(";;")) {
+            Some(comment) => comment,
+            None => break,
+        };
+        comment_lines.push(comment.trim_start_matches(';').trim());
+    }
+
+    for comment in comment_lines.into_iter().rev() {
+        let annotation: Vec<_> = match comment.strip_prefix('@') {
+            Some(annotation) => annotation.split_whitespace().collect(),
+            None => continue,
+        };
+        match annotation.as_slice() {
+            ["sender", principal] => {
+                unit_test.sender = parse_test_principal(principal)?;
+            }
+            ["mine-blocks", count] => {
+                unit_test.mine_blocks = count
+                    .parse()
+                    .map_err(|_| format!("invalid block count '{}'", count))?;
+            }
+            ["mint", principal, amount] => {
+                let amount = amount
+                    .trim_start_matches('u')
+                    .parse()
+                    .map_err(|_| format!("invalid uSTX amount '{}'", amount))?;
+                unit_test
+                    .mints
+                    .push((parse_test_principal(principal)?, amount));
+            }
+            _ => {
+                return Err(format!(
+                    "unrecognized test annotation '@{}'",
+                    annotation.join(" ")
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Test functions are only looked for in test contracts, whose names end in `-test`, so that a
+///  contract under test may define `test-*` functions of its own.
+fn is_test_contract(contract_identifier: &QualifiedContractIdentifier) -> bool {
+    contract_identifier.name.ends_with("-test")
+}
+
+/// Find the `test-*` public functions in a contract, in the order they are defined
+fn find_unit_tests(
+    contract_identifier: &QualifiedContractIdentifier,
+    source: &str,
+    ast: &[SymbolicExpression],
+) -> Vec<(UnitTest, Result<(), String>)> {
+    let mut unit_tests = vec![];
+    for expr in ast.iter() {
+        let signature = match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(DefineFunctionsParsed::PublicFunction { signature, .. })) => signature,
+            _ => continue,
+        };
+        let name = match signature.first().and_then(|name| name.match_atom()) {
+            Some(name) if name.starts_with("test-") => name,
+            _ => continue,
+        };
+        let mut unit_test = UnitTest {
+            contract_identifier: contract_identifier.clone(),
+            name: name.to_string(),
+            sender: PrincipalData::Standard(contract_identifier.issuer.clone()),
+            mine_blocks: 0,
+            mints: vec![],
+        };
+        let annotations = parse_test_annotations(source, expr.span.start_line, &mut unit_test);
+        unit_tests.push((unit_test, annotations));
+    }
+    unit_tests
+}
+
+/// A contract to launch into a scratch VM state database
+struct ScratchContract {
+    contract_identifier: QualifiedContractIdentifier,
+    src_file: String,
+    content: String,
+    ast: Vec<SymbolicExpression>,
+}
+
+/// Read and parse the contracts named by `[contract-identifier] [contract-definition.clar] ...`
+///  command-line arguments.
+fn read_scratch_contracts(args: &[String]) -> Vec<ScratchContract> {
+    let mut contracts = vec![];
+    for pair in args.chunks(2) {
+        let contract_identifier = friendly_expect(
+            QualifiedContractIdentifier::parse(&pair[0]),
+            "Failed to parse contract identifier.",
+        );
+        let content: String = friendly_expect(
+            fs::read_to_string(&pair[1]),
+            &format!("Error reading file: {}", pair[1]),
+        );
+        let ast = friendly_expect(
+            parse(&contract_identifier, &content, ClarityVersion::Clarity2),
+            "Failed to parse program.",
+        );
+        contracts.push(ScratchContract {
+            contract_identifier,
+            src_file: pair[1].clone(),
+            content,
+            ast,
+        });
+    }
+    contracts
+}
+
+/// Open a scratch VM state database for `clarity-cli test` and `fuzz`, so that the one they are
+///  given is never modified.  If `vm_filename` is `:memory:`, the scratch database is a new one.
+///  Otherwise, it's a copy of `vm_filename`.  The caller removes the returned directory when
+///  done.
+fn open_scratch_vm_state(vm_filename: &str, mainnet: bool) -> (PathBuf, CLIHeadersDB, MarfedKV) {
+    let mut scratch_path = env::temp_dir();
+    scratch_path.push(format!(
+        "clarity-cli-scratch-{}",
+        bytes_to_hex(&rand::thread_rng().gen::<[u8; 16]>())
+    ));
+    let scratch_filename = scratch_path
+        .to_str()
+        .expect("FATAL: non-UTF-8 character in filename")
+        .to_string();
+
+    if vm_filename == ":memory:" {
+        let header_db = CLIHeadersDB::new(&scratch_filename, mainnet);
+        let marf_kv = friendly_expect(
+            MarfedKV::open(&scratch_filename, None, None),
+            "Failed to open VM database.",
+        );
+        let (header_db, marf_kv, _) = in_block(header_db, marf_kv, |header_db, mut marf| {
+            install_boot_code(&header_db, &mut marf);
+            (header_db, marf, ())
+        });
+        (scratch_path, header_db, marf_kv)
+    } else {
+        friendly_expect(
+            copy_vm_state(vm_filename, &scratch_path),
+            &format!("Failed to copy {} to {}", vm_filename, &scratch_filename),
+        );
+        let header_db = friendly_expect(
+            CLIHeadersDB::resume(&scratch_filename),
+            "Failed to open CLI DB",
+        );
+        let marf_kv = friendly_expect(
+            MarfedKV::open(&scratch_filename, None, None),
+            "Failed to open VM database.",
+        );
+        (scratch_path, header_db, marf_kv)
+    }
+}
+
+/// Launch contracts into a scratch VM state database, in a new block.  Returns the analysis of
+///  each contract, or the first contract that failed to launch and why.
+fn launch_scratch_contracts(
+    header_db: CLIHeadersDB,
+    marf_kv: MarfedKV,
+    contracts: &mut [ScratchContract],
+    coverage: Option<&mut CoverageReporter>,
+) -> (
+    CLIHeadersDB,
+    MarfedKV,
+    Result<Vec<ContractAnalysis>, (QualifiedContractIdentifier, String)>,
+) {
+    let mainnet = header_db.is_mainnet();
+    in_block(header_db, marf_kv, |header_db, mut marf| {
+        let mut coverage = coverage;
+        let mut analyses = vec![];
+        for contract in contracts.iter_mut() {
+            let analysis = match run_analysis(
+                &contract.contract_identifier,
+                &mut contract.ast,
+                &header_db,
+                &mut marf,
+                true,
+            ) {
+                Ok(analysis) => analysis,
+                Err((error, _)) => {
+                    let error = (contract.contract_identifier.clone(), error.to_string());
+                    return (header_db, marf, Err(error));
+                }
+            };
+            let (result, _) = with_env_costs(
+                mainnet,
+                &header_db,
+                &mut marf,
+                coverage.as_deref_mut(),
+                None,
+                |vm_env| {
+                    vm_env.initialize_versioned_contract(
+                        contract.contract_identifier.clone(),
+                        ClarityVersion::Clarity2,
+                        &contract.content,
+                        None,
+                        ASTRules::PrecheckSize,
+                    )
+                },
+            );
+            if let Err(error) = result {
+                let error = (contract.contract_identifier.clone(), error.to_string());
+                return (header_db, marf, Err(error));
+            }
+            analyses.push(analysis);
+        }
+        (header_db, marf, Ok(analyses))
+    })
+}
+
+/// Credit principals with newly-minted uSTX
+fn mint_stx(
+    marf: &mut WritableMarfStore,
+    header_db: &CLIHeadersDB,
+    mints: &[(PrincipalData, u128)],
+) {
+    let mut db = marf.as_clarity_db(header_db, &NULL_BURN_STATE_DB);
+    db.begin();
+    for (principal, amount) in mints.iter() {
+        let mut snapshot = db.get_stx_balance_snapshot(principal);
+        snapshot.credit(*amount);
+        snapshot.save();
+        friendly_expect(
+            db.increment_ustx_liquid_supply(*amount),
+            "Failed to mint STX",
+        );
+    }
+    db.commit();
+}
+
+/// Sequences, strings and buffers generated by `clarity-cli fuzz` are at most this long, unless
+///  their type's maximum length is small enough to try outright.
+const FUZZ_MAX_GENERATED_LENGTH: u32 = 32;
+const FUZZ_MAX_EXACT_LENGTH: u32 = 1024;
+/// Number of random senders (besides the fuzzed contract's issuer) and how much STX each gets
+const FUZZ_NUM_SENDERS: usize = 4;
+const FUZZ_SENDER_BALANCE: u128 = 1_000_000_000_000;
+
+/// A call to a public function, as made by `clarity-cli fuzz`
+#[derive(Clone)]
+struct FuzzCall {
+    sender: PrincipalData,
+    function: String,
+    args: Vec<Value>,
+}
+
+/// An invariant that didn't hold after the first `num_calls` calls in a sequence
+struct FuzzFailure {
+    num_calls: usize,
+    invariant: String,
+    result: String,
+}
+
+fn random_fuzz_length<R: Rng>(rng: &mut R, max: u32) -> u32 {
+    if max <= FUZZ_MAX_EXACT_LENGTH && rng.gen_range(0, 8) == 0 {
+        return max;
+    }
+    rng.gen_range(0, max.min(FUZZ_MAX_GENERATED_LENGTH) + 1)
+}
+
+fn random_fuzz_u128<R: Rng>(rng: &mut R) -> u128 {
+    match rng.gen_range(0, 6) {
+        0 => 0,
+        1 => 1,
+        2 => rng.gen_range(0, 1000),
+        3 => rng.gen::<u64>() as u128,
+        4 => rng.gen(),
+        _ => u128::MAX,
+    }
+}
+
+fn random_fuzz_i128<R: Rng>(rng: &mut R) -> i128 {
+    match rng.gen_range(0, 7) {
+        0 => 0,
+        1 => 1,
+        2 => -1,
+        3 => rng.gen_range(-1000, 1000),
+        4 => rng.gen(),
+        5 => i128::MIN,
+        _ => i128::MAX,
+    }
+}
+
+/// Generate a random value of a contract interface type, within that type's maximum lengths.
+///  Returns None if values of this type can't be generated (i.e. trait references).
+fn random_fuzz_value<R: Rng>(
+    rng: &mut R,
+    atom_type: &ContractInterfaceAtomType,
+    principals: &[PrincipalData],
+) -> Option<Value> {
+    let value = match atom_type {
+        ContractInterfaceAtomType::none => Value::none(),
+        ContractInterfaceAtomType::int128 => Value::Int(random_fuzz_i128(rng)),
+        ContractInterfaceAtomType::uint128 => Value::UInt(random_fuzz_u128(rng)),
+        ContractInterfaceAtomType::bool => Value::Bool(rng.gen()),
+        ContractInterfaceAtomType::principal => {
+            Value::Principal(principals[rng.gen_range(0, principals.len())].clone())
+        }
+        ContractInterfaceAtomType::buffer { length } => {
+            let bytes = (0..random_fuzz_length(rng, *length))
+                .map(|_| rng.gen())
+                .collect();
+            Value::buff_from(bytes).expect("BUG: generated an oversized buffer")
+        }
+        ContractInterfaceAtomType::string_ascii { length } => {
+            let bytes = (0..random_fuzz_length(rng, *length))
+                .map(|_| rng.gen_range(0x20, 0x7f))
+                .collect();
+            Value::string_ascii_from_bytes(bytes).expect("BUG: generated an invalid string")
+        }
+        ContractInterfaceAtomType::string_utf8 { length } => {
+            let chars = ['a', 'Z', '0', ' ', '"', '\\', 'é', 'Ж', '中', '🦊'];
+            let string: String = (0..random_fuzz_length(rng, *length))
+                .map(|_| chars[rng.gen_range(0, chars.len())])
+                .collect();
+            Value::string_utf8_from_bytes(string.into_bytes())
+                .expect("BUG: generated an invalid string")
+        }
+        ContractInterfaceAtomType::tuple(entries) => {
+            let mut data = vec![];
+            for entry in entries.iter() {
+                let name = ClarityName::try_from(entry.name.clone())
+                    .expect("BUG: invalid tuple entry name in contract interface");
+                data.push((name, random_fuzz_value(rng, &entry.type_f, principals)?));
+            }
+            Value::Tuple(TupleData::from_data(data).expect("BUG: generated an invalid tuple"))
+        }
+        ContractInterfaceAtomType::optional(inner) => {
+            if rng.gen_range(0, 4) == 0 {
+                Value::none()
+            } else {
+                Value::some(random_fuzz_value(rng, inner, principals)?)
+                    .expect("BUG: generated an oversized optional")
+            }
+        }
+        ContractInterfaceAtomType::response { ok, error } => if rng.gen() {
+            Value::okay(random_fuzz_value(rng, ok, principals)?)
+        } else {
+            Value::error(random_fuzz_value(rng, error, principals)?)
+        }
+        .expect("BUG: generated an oversized response"),
+        ContractInterfaceAtomType::list { type_f, length } => {
+            let mut items = vec![];
+            for _ in 0..random_fuzz_length(rng, *length) {
+                items.push(random_fuzz_value(rng, type_f, principals)?);
+            }
+            Value::list_from(items).expect("BUG: generated an invalid list")
+        }
+        ContractInterfaceAtomType::trait_reference => {
+            return None;
+        }
+    };
+    Some(value)
+}
+
+/// Generate a sequence of random calls to `functions`, from random `senders`
+fn random_fuzz_calls<R: Rng>(
+    rng: &mut R,
+    functions: &[&ContractInterfaceFunction],
+    senders: &[PrincipalData],
+    principals: &[PrincipalData],
+    num_calls: usize,
+) -> Vec<FuzzCall> {
+    let mut calls = vec![];
+    while calls.len() < num_calls {
+        let function = functions[rng.gen_range(0, functions.len())];
+        let args: Option<Vec<_>> = function
+            .args
+            .iter()
+            .map(|arg| random_fuzz_value(rng, &arg.type_f, principals))
+            .collect();
+        calls.push(FuzzCall {
+            sender: senders[rng.gen_range(0, senders.len())].clone(),
+            function: function.name.clone(),
+            args: args.expect("BUG: fuzzing a function with trait reference arguments"),
+        });
+    }
+    calls
+}
+
+/// Check each invariant of `contract_identifier`.  Returns the first one that doesn't hold (i.e.
+///  that doesn't return `true` or `(ok ...)`), and what it returned instead.
+fn check_fuzz_invariants(
+    mainnet: bool,
+    header_db: &CLIHeadersDB,
+    marf: &mut WritableMarfStore,
+    contract_identifier: &QualifiedContractIdentifier,
+    invariants: &[String],
+) -> Option<(String, String)> {
+    for invariant in invariants.iter() {
+        let (result, _) = with_env_costs(mainnet, header_db, marf, None, None, |vm_env| {
+            vm_env.eval_read_only_with_rules(
+                contract_identifier,
+                &format!("({})", invariant),
+                ASTRules::PrecheckSize,
+            )
+        });
+        let result = match result {
+            Ok((Value::Bool(true), ..)) => continue,
+            Ok((Value::Response(ref data), ..)) if data.committed => continue,
+            Ok((value, ..)) => format!("{}", value),
+            Err(error) => format!("{}", error),
+        };
+        return Some((invariant.clone(), result));
+    }
+    None
+}
+
+/// Run a sequence of calls in a new, rolled-back block on top of `base`, checking the invariants
+///  before the first call and after each one.  Stops at the first invariant that doesn't hold.
+///  Returns the result of each call that was made.
+fn run_fuzz_calls(
+    calls: &[FuzzCall],
+    contract_identifier: &QualifiedContractIdentifier,
+    invariants: &[String],
+    base: &StacksBlockId,
+    header_db: &mut CLIHeadersDB,
+    marf_kv: &mut MarfedKV,
+) -> (Vec<String>, Option<FuzzFailure>) {
+    let mainnet = header_db.is_mainnet();
+    let block = header_db.add_cli_block();
+    let mut marf = marf_kv.begin(base, &block);
+    let mut results = vec![];
+
+    let mut failure = check_fuzz_invariants(
+        mainnet,
+        header_db,
+        &mut marf,
+        contract_identifier,
+        invariants,
+    );
+    for call in calls.iter() {
+        if failure.is_some() {
+            break;
+        }
+        let args: Vec<_> = call
+            .args
+            .iter()
+            .map(|arg| SymbolicExpression::atom_value(arg.clone()))
+            .collect();
+        let (result, _) = with_env_costs(mainnet, header_db, &mut marf, None, None, |vm_env| {
+            vm_env.execute_transaction(
+                call.sender.clone(),
+                None,
+                contract_identifier.clone(),
+                &call.function,
+                &args,
+            )
+        });
+        results.push(match result {
+            Ok((value, ..)) => format!("{}", value),
+            Err(error) => format!("error: {}", error),
+        });
+        failure = check_fuzz_invariants(
+            mainnet,
+            header_db,
+            &mut marf,
+            contract_identifier,
+            invariants,
+        );
+    }
+    marf.rollback_block();
+
+    let failure = failure.map(|(invariant, result)| FuzzFailure {
+        num_calls: results.len(),
+        invariant,
+        result,
+    });
+    (results, failure)
+}
+
+/// Shrink a failing call sequence by dropping every call that the failure doesn't depend on.  A
+///  shorter sequence only replaces it if it still fails the same way: the same invariant, with
+///  the same result.
+fn minimize_fuzz_calls(
+    mut calls: Vec<FuzzCall>,
+    original: &FuzzFailure,
+    contract_identifier: &QualifiedContractIdentifier,
+    invariants: &[String],
+    base: &StacksBlockId,
+    header_db: &mut CLIHeadersDB,
+    marf_kv: &mut MarfedKV,
+) -> Vec<FuzzCall> {
+    let mut i = 0;
+    while i < calls.len() {
+        let mut candidate = calls.clone();
+        candidate.remove(i);
+        let (_, failure) = run_fuzz_calls(
+            &candidate,
+            contract_identifier,
+            invariants,
+            base,
+            header_db,
+            marf_kv,
+        );
+        match failure {
+            Some(failure)
+                if failure.invariant == original.invariant && failure.result == original.result =>
+            {
+                candidate.truncate(failure.num_calls);
+                calls = candidate;
+            }
+            _ => {
+                i += 1;
+            }
+        }
+    }
+    calls
+}
+
+/// Copy the files of a VM state database into `to`, so that `clarity-cli test` can run against a
+///  scratch copy of it.
+fn copy_vm_state(from: &str, to: &PathBuf) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a unit test in its own fork of the chain off of `base`.  The fork's last block, in which
+///  the test function itself is called, is rolled back.  Returns the JSON test report, and
+///  whether or not the test passed.
+fn run_unit_test(
+    unit_test: &UnitTest,
+    base: &StacksBlockId,
+    header_db: &mut CLIHeadersDB,
+    marf_kv: &mut MarfedKV,
+    coverage: Option<&mut CoverageReporter>,
+    costs: bool,
+) -> (serde_json::Value, bool) {
+    let mainnet = header_db.is_mainnet();
+    let mut parent = *base;
+    for _ in 0..unit_test.mine_blocks {
+        let next = header_db.add_cli_block();
+        marf_kv.begin(&parent, &next).commit_to(&next);
+        parent = next;
+    }
+
+    let block = header_db.add_cli_block();
+    let mut marf = marf_kv.begin(&parent, &block);
+    mint_stx(&mut marf, header_db, &unit_test.mints);
+
+    let (result, cost) = with_env_costs(mainnet, header_db, &mut marf, coverage, None, |vm_env| {
+        vm_env.execute_transaction(
+            unit_test.sender.clone(),
+            None,
+            unit_test.contract_identifier.clone(),
+            &unit_test.name,
+            &[],
+        )
+    });
+    marf.rollback_block();
+
+    let mut report = json!({
+        "contract": unit_test.contract_identifier.to_string(),
+        "test": unit_test.name,
+    });
+    let success = match result {
+        Ok((Value::Response(data), ..)) => {
+            report["output"] = serde_json::to_value(&data.data).unwrap();
+            data.committed
+        }
+        Ok((x, ..)) => {
+            report["error"] = json!("Expected a ResponseType result from test.");
+            report["output"] = serde_json::to_value(&x).unwrap();
+            false
+        }
+        Err(error) => {
+            report["error"] = json!(error.to_string());
+            false
+        }
+    };
+    report["success"] = json!(success);
+    add_costs(&mut report, costs, cost);
+    (report, success)
+}
+
 /// This function uses Clarity1 to parse the boot code.
 fn install_boot_code<C: ClarityStorage>(header_db: &CLIHeadersDB, marf: &mut C) {
     let mainnet = header_db.is_mainnet();
@@ -971,6 +1626,12 @@ pub fn add_assets(result: &mut serde_json::Value, assets: bool, asset_map: Asset
     }
 }
 
+pub fn add_profile(result: &mut serde_json::Value, profile: Option<serde_json::Value>) {
+    if let Some(profile) = profile {
+        result["profile"] = profile;
+    }
+}
+
 pub fn add_serialized_output(result: &mut serde_json::Value, value: Value) {
     let result_raw = {
         let bytes = (&value).serialize_to_vec();
@@ -1107,7 +1768,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
         "check" => {
             if args.len() < 2 {
                 eprintln!(
-                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--testnet] (vm-state.db)",
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--lint] [--testnet] (vm-state.db)",
                     invoked_by, args[0]
                 );
                 panic_test!();
@@ -1142,6 +1803,8 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 false
             };
 
+            let lint = matches!(consume_arg(&mut argv, &["--lint"], false), Ok(Some(_)));
+
             // NOTE: ignored if we're using a DB
             let mut testnet_given = false;
             let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
@@ -1231,6 +1894,10 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 contract_analysis.take_contract_cost_tracker().get_total(),
             );
 
+            if lint {
+                result["warnings"] = serde_json::to_value(Linter::run(&contract_analysis)).unwrap();
+            }
+
             if output_analysis {
                 result["analysis"] =
                     serde_json::to_value(&build_contract_interface(&contract_analysis)).unwrap();
@@ -1394,7 +2061,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
                 let result_and_cost =
-                    with_env_costs(mainnet, &header_db, &mut marf, None, |vm_env| {
+                    with_env_costs(mainnet, &header_db, &mut marf, None, None, |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
                             .eval_read_only_with_rules(
@@ -1471,6 +2138,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     &header_db,
                     &mut marf,
                     coverage.as_mut(),
+                    None,
                     |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
@@ -1558,7 +2226,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             );
             let result_and_cost = at_block(chain_tip, marf_kv, |mut marf| {
                 let result_and_cost =
-                    with_env_costs(mainnet, &header_db, &mut marf, None, |vm_env| {
+                    with_env_costs(mainnet, &header_db, &mut marf, None, None, |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
                             .eval_read_only_with_rules(
@@ -1689,6 +2357,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                                 &header_db,
                                 &mut marf,
                                 coverage.as_mut(),
+                                None,
                                 |vm_env| {
                                     vm_env.initialize_versioned_contract(
                                         contract_identifier,
@@ -1768,9 +2437,26 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 false
             };
+            let profile_file = if let Ok(profile_arg) = consume_arg(&mut argv, &["--profile"], true)
+            {
+                profile_arg
+            } else {
+                None
+            };
+            let profile_dimension = match consume_arg(&mut argv, &["--profile-dimension"], true) {
+                Ok(Some(dimension)) => friendly_expect(
+                    CostDimension::from_str(&dimension),
+                    "Failed to parse profile dimension.",
+                ),
+                _ => CostDimension::Runtime,
+            };
 
             if argv.len() < 5 {
-                eprintln!("Usage: {} {} [--costs] [--assets] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
+                eprintln!("Usage: {} {} [--costs] [--assets] [--profile folded-stacks-file] [--profile-dimension dimension] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
+                eprintln!("   --profile writes the cost of the call, broken down by call stack, to folded-stacks-file in");
+                eprintln!("   the format read by flamegraph.pl and inferno, and adds per-function and per-line costs to");
+                eprintln!("   the output.  The stacks are weighed by --profile-dimension, which is one of runtime (the");
+                eprintln!("   default), read_count, read_length, write_count or write_length.");
                 panic_test!();
             }
 
@@ -1820,12 +2506,21 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 None
             };
+            let mut profiler = if profile_file.is_some() {
+                Some(CostProfiler::new(&format!(
+                    "{}::{}",
+                    &contract_identifier, tx_name
+                )))
+            } else {
+                None
+            };
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
                 let result_and_cost = with_env_costs(
                     mainnet,
                     &header_db,
                     &mut marf,
                     coverage.as_mut(),
+                    profiler.as_mut(),
                     |vm_env| {
                         vm_env.execute_transaction(
                             sender,
@@ -1840,6 +2535,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 (header_db, marf, (result, cost))
             });
 
+            if let (Some(profile_file), Some(profiler)) = (&profile_file, &profiler) {
+                friendly_expect(
+                    fs::write(profile_file, profiler.folded_stacks(profile_dimension)),
+                    &format!("Failed to write profile to {}", profile_file),
+                );
+            }
+            let profile = profiler.map(|profiler| profiler.to_json());
+
             match result_and_cost {
                 (Ok((x, asset_map, events)), cost) => {
                     if let Value::Response(data) = x {
@@ -1854,6 +2557,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                             add_serialized_output(&mut result, *data.data);
                             add_costs(&mut result, costs, cost);
                             add_assets(&mut result, assets, asset_map);
+                            add_profile(&mut result, profile);
 
                             let events_json: Vec<_> = events
                                 .into_iter()
@@ -1872,6 +2576,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                             add_costs(&mut result, costs, cost);
                             add_serialized_output(&mut result, *data.data);
                             add_assets(&mut result, assets, asset_map);
+                            add_profile(&mut result, profile);
 
                             (0, Some(result))
                         }
@@ -1898,49 +2603,676 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 }
             }
         }
-        "make_lcov" => {
-            let mut register_files = vec![];
-            let mut coverage_files = vec![];
-            let coverage_folder = &args[1];
-            let lcov_output_file = &args[2];
-            for folder_entry in
-                fs::read_dir(coverage_folder).expect("Failed to read the coverage folder")
-            {
-                let folder_entry =
-                    folder_entry.expect("Failed to read entry in the coverage folder");
-                let entry_path = folder_entry.path();
-                if entry_path.is_file() {
-                    if entry_path.extension() == Some(OsStr::new("clarcovref")) {
-                        register_files.push(entry_path)
-                    } else if entry_path.extension() == Some(OsStr::new("clarcov")) {
-                        coverage_files.push(entry_path)
-                    }
-                }
+        "test" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let coverage_folder = if let Ok(covarg) = consume_arg(&mut argv, &["--c"], true) {
+                covarg
+            } else {
+                None
+            };
+            let costs = matches!(consume_arg(&mut argv, &["--costs"], false), Ok(Some(_)));
+            let mainnet = !matches!(consume_arg(&mut argv, &["--testnet"], false), Ok(Some(_)));
+
+            if argv.len() < 4 || argv.len() % 2 != 0 {
+                eprintln!(
+                    "Usage: {} {} [--costs] [--testnet] [--c coverage-folder] [vm-state.db] [contract-identifier] [contract-definition.clar] ...",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   Each contract is launched in order, and then every public function whose name");
+                eprintln!("   starts with `test-` in a test contract (one whose name ends in `-test`) is called");
+                eprintln!("   with no arguments in its own rolled-back block.");
+                eprintln!("   A test passes if it returns `(ok ...)`.  If vm-state.db is `:memory:`, the tests");
+                eprintln!("   run against a new database (mainnet, unless --testnet is given).  Otherwise they");
+                eprintln!("   run against a copy of the given database, which is left unchanged.");
+                eprintln!("   The comment block above a test may contain these annotations:");
+                eprintln!("     ;; @sender 'ST...           call the test with this tx-sender");
+                eprintln!("     ;; @mine-blocks 10          mine this many blocks before calling the test");
+                eprintln!("     ;; @mint 'ST... 1000000     credit this principal with uSTX first");
+                panic_test!();
             }
-            CoverageReporter::produce_lcov(lcov_output_file, &register_files, &coverage_files)
-                .expect("Failed to produce an lcov output");
-            (0, None)
-        }
-        _ => {
-            print_usage(invoked_by);
-            (1, None)
-        }
-    }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+            let vm_filename = &argv[1];
+            let mut contracts = read_scratch_contracts(&argv[2..]);
+            if let Some(ref coverage_folder) = coverage_folder {
+                for contract in contracts.iter() {
+                    let mut coverage_file = PathBuf::from(coverage_folder);
+                    coverage_file.push(&format!(
+                        "test_{}_{}",
+                        &contract.contract_identifier.name,
+                        get_epoch_time_ms()
+                    ));
+                    coverage_file.set_extension("clarcovref");
+                    CoverageReporter::register_src_file(
+                        &contract.contract_identifier,
+                        &contract.src_file,
+                        &contract.ast,
+                        &coverage_file,
+                    )
+                    .expect("Coverage reference file generation failure");
+                }
+            }
 
-    #[test]
-    fn test_initial_alloc() {
-        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
-        let json_name = format!("/tmp/test-alloc_{}.json", rand::thread_rng().gen::<i32>());
-        let clar_name = format!("/tmp/test-alloc_{}.clar", rand::thread_rng().gen::<i32>());
+            let mut coverage = if coverage_folder.is_some() {
+                Some(CoverageReporter::new())
+            } else {
+                None
+            };
 
-        fs::write(
-            &json_name,
-            r#"
+            let (scratch_path, header_db, marf_kv) = open_scratch_vm_state(vm_filename, mainnet);
+            let (mut header_db, mut marf_kv, launch_result) =
+                launch_scratch_contracts(header_db, marf_kv, &mut contracts, coverage.as_mut());
+            if let Err((contract_identifier, error)) = launch_result {
+                let _ = fs::remove_dir_all(&scratch_path);
+                return (
+                    1,
+                    Some(json!({
+                        "error": {
+                            "contract": contract_identifier.to_string(),
+                            "initialization": error,
+                        }
+                    })),
+                );
+            }
+
+            let mut unit_tests = vec![];
+            for contract in contracts
+                .iter()
+                .filter(|contract| is_test_contract(&contract.contract_identifier))
+            {
+                unit_tests.extend(find_unit_tests(
+                    &contract.contract_identifier,
+                    &contract.content,
+                    &contract.ast,
+                ));
+            }
+
+            let base = get_cli_chain_tip(header_db.conn());
+            let mut reports = vec![];
+            let mut passed = 0;
+            for (unit_test, annotations) in unit_tests.iter() {
+                if let Err(error) = annotations {
+                    reports.push(json!({
+                        "contract": unit_test.contract_identifier.to_string(),
+                        "test": unit_test.name,
+                        "success": false,
+                        "error": error,
+                    }));
+                    continue;
+                }
+                let (report, success) = run_unit_test(
+                    unit_test,
+                    &base,
+                    &mut header_db,
+                    &mut marf_kv,
+                    coverage.as_mut(),
+                    costs,
+                );
+                if success {
+                    passed += 1;
+                }
+                reports.push(report);
+            }
+
+            save_coverage(coverage_folder, coverage, "test");
+            drop(marf_kv);
+            drop(header_db);
+            let _ = fs::remove_dir_all(&scratch_path);
+
+            let failed = reports.len() - passed;
+            let result = json!({
+                "message": format!("{} passed, {} failed", passed, failed),
+                "passed": passed,
+                "failed": failed,
+                "tests": reports,
+            });
+            (if failed == 0 { 0 } else { 1 }, Some(result))
+        }
+        "fuzz" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let mainnet = !matches!(consume_arg(&mut argv, &["--testnet"], false), Ok(Some(_)));
+            let mut numeric_arg = |argnames: &[&str], default: u64| -> u64 {
+                match consume_arg(&mut argv, argnames, true) {
+                    Ok(Some(value)) => friendly_expect(
+                        value.parse::<u64>(),
+                        &format!("Failed to parse {} value", argnames[0]),
+                    ),
+                    _ => default,
+                }
+            };
+            let runs = numeric_arg(&["--runs"], 100);
+            let num_calls = numeric_arg(&["--calls"], 10) as usize;
+            let seed = numeric_arg(&["--seed"], rand::thread_rng().gen());
+
+            if argv.len() < 4 || argv.len() % 2 != 0 {
+                eprintln!(
+                    "Usage: {} {} [--runs N] [--calls N] [--seed N] [--testnet] [vm-state.db] [contract-identifier] [contract-definition.clar] ...",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   Each contract is launched in order, and then the last one is fuzzed: --runs (default");
+                eprintln!("   100) times, a sequence of --calls (default 10) calls to its public functions is made");
+                eprintln!("   with random arguments and senders, in its own rolled-back block.  Before the first");
+                eprintln!(
+                    "   call and after each one, every read-only function whose name starts with"
+                );
+                eprintln!("   `invariant-` and that takes no arguments is evaluated, and must return `true` or");
+                eprintln!("   `(ok ...)`.  The first sequence that violates an invariant is minimized and reported.");
+                eprintln!("   vm-state.db is handled as by the `test` command.");
+                panic_test!();
+            }
+
+            let vm_filename = &argv[1];
+            let mut contracts = read_scratch_contracts(&argv[2..]);
+            let (scratch_path, header_db, marf_kv) = open_scratch_vm_state(vm_filename, mainnet);
+            let (header_db, marf_kv, launch_result) =
+                launch_scratch_contracts(header_db, marf_kv, &mut contracts, None);
+            let analyses = match launch_result {
+                Ok(analyses) => analyses,
+                Err((contract_identifier, error)) => {
+                    let _ = fs::remove_dir_all(&scratch_path);
+                    return (
+                        1,
+                        Some(json!({
+                            "error": {
+                                "contract": contract_identifier.to_string(),
+                                "initialization": error,
+                            }
+                        })),
+                    );
+                }
+            };
+
+            let mainnet = header_db.is_mainnet();
+            let contract_identifier = contracts
+                .last()
+                .expect("BUG: no contracts")
+                .contract_identifier
+                .clone();
+            let interface = build_contract_interface(analyses.last().expect("BUG: no contracts"));
+            let functions: Vec<_> = interface
+                .functions
+                .iter()
+                .filter(|f| {
+                    f.access == ContractInterfaceFunctionAccess::public
+                        && f.args
+                            .iter()
+                            .all(|arg| arg.type_f != ContractInterfaceAtomType::trait_reference)
+                })
+                .collect();
+            let invariants: Vec<_> = interface
+                .functions
+                .iter()
+                .filter(|f| {
+                    f.access == ContractInterfaceFunctionAccess::read_only
+                        && f.name.starts_with("invariant-")
+                        && f.args.is_empty()
+                })
+                .map(|f| f.name.clone())
+                .collect();
+
+            // the fuzzed contract's issuer, plus some new, funded accounts
+            let mut rng = StdRng::seed_from_u64(seed);
+            let address_version = if mainnet {
+                C32_ADDRESS_VERSION_MAINNET_SINGLESIG
+            } else {
+                C32_ADDRESS_VERSION_TESTNET_SINGLESIG
+            };
+            let mut senders = vec![PrincipalData::Standard(contract_identifier.issuer.clone())];
+            for _ in 0..FUZZ_NUM_SENDERS {
+                senders.push(PrincipalData::Standard(StandardPrincipalData(
+                    address_version,
+                    rng.gen(),
+                )));
+            }
+            let mints: Vec<_> = senders
+                .iter()
+                .map(|sender| (sender.clone(), FUZZ_SENDER_BALANCE))
+                .collect();
+            let (mut header_db, mut marf_kv, _) =
+                in_block(header_db, marf_kv, |header_db, mut marf| {
+                    mint_stx(&mut marf, &header_db, &mints);
+                    (header_db, marf, ())
+                });
+            let base = get_cli_chain_tip(header_db.conn());
+
+            let mut principals = senders.clone();
+            principals.extend(
+                contracts
+                    .iter()
+                    .map(|contract| PrincipalData::Contract(contract.contract_identifier.clone())),
+            );
+
+            let mut failing_calls = None;
+            let mut runs_done = 0;
+            let mut calls_made = 0;
+            while runs_done < runs && failing_calls.is_none() {
+                let calls = if functions.is_empty() {
+                    vec![]
+                } else {
+                    random_fuzz_calls(&mut rng, &functions, &senders, &principals, num_calls)
+                };
+                let (results, failure) = run_fuzz_calls(
+                    &calls,
+                    &contract_identifier,
+                    &invariants,
+                    &base,
+                    &mut header_db,
+                    &mut marf_kv,
+                );
+                runs_done += 1;
+                calls_made += results.len();
+                if let Some(failure) = failure {
+                    failing_calls = Some((calls[..failure.num_calls].to_vec(), failure));
+                }
+            }
+
+            let mut result = json!({
+                "contract": contract_identifier.to_string(),
+                "seed": seed,
+                "runs": runs_done,
+                "calls": calls_made,
+                "functions": functions.iter().map(|f| f.name.clone()).collect::<Vec<_>>(),
+                "invariants": invariants,
+            });
+            let exit = if let Some((failing_calls, original_failure)) = failing_calls {
+                let calls = minimize_fuzz_calls(
+                    failing_calls,
+                    &original_failure,
+                    &contract_identifier,
+                    &invariants,
+                    &base,
+                    &mut header_db,
+                    &mut marf_kv,
+                );
+                // replay the minimized sequence to report what each call did
+                let (results, failure) = run_fuzz_calls(
+                    &calls,
+                    &contract_identifier,
+                    &invariants,
+                    &base,
+                    &mut header_db,
+                    &mut marf_kv,
+                );
+                let failure = failure.expect("BUG: minimized call sequence no longer fails");
+                result["message"] = json!(format!("Invariant {} violated", &failure.invariant));
+                result["failure"] = json!({
+                    "invariant": failure.invariant,
+                    "result": failure.result,
+                    "calls": calls
+                        .iter()
+                        .zip(results.iter())
+                        .map(|(call, call_result)| json!({
+                            "sender": call.sender.to_string(),
+                            "function": call.function,
+                            "args": call.args.iter().map(|arg| format!("{}", arg)).collect::<Vec<_>>(),
+                            "result": call_result,
+                        }))
+                        .collect::<Vec<_>>(),
+                });
+                1
+            } else {
+                result["message"] = json!("No invariant violations found.");
+                0
+            };
+
+            drop(marf_kv);
+            drop(header_db);
+            let _ = fs::remove_dir_all(&scratch_path);
+            (exit, Some(result))
+        }
+        "deps" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let contract_id = if let Ok(optarg) = consume_arg(&mut argv, &["--contract_id"], true) {
+                optarg
+                    .map(|optarg_str| {
+                        friendly_expect(
+                            QualifiedContractIdentifier::parse(&optarg_str),
+                            &format!("Error parsing contract identifier '{}", &optarg_str),
+                        )
+                    })
+                    .unwrap_or(QualifiedContractIdentifier::transient())
+            } else {
+                eprintln!("Expected argument for --contract-id");
+                panic_test!();
+            };
+            let dot = matches!(consume_arg(&mut argv, &["--dot"], false), Ok(Some(_)));
+            let mainnet = !matches!(consume_arg(&mut argv, &["--testnet"], false), Ok(Some(_)));
+
+            if argv.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--dot] [--testnet] (vm-state.db)",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   Prints each function's calls (within the contract, to named contracts, and through");
+                eprintln!("   traits), the data vars and maps it reads and writes, and its asset transfers, mints");
+                eprintln!("   and burns, as JSON, or as a Graphviz graph with --dot.  Calls to contracts are");
+                eprintln!("   resolved against vm-state.db if it is given, and against the boot contracts if not.");
+                panic_test!();
+            }
+
+            let content: String = if &argv[1] == "-" {
+                let mut buffer = String::new();
+                friendly_expect(
+                    io::stdin().read_to_string(&mut buffer),
+                    "Error reading from stdin.",
+                );
+                buffer
+            } else {
+                friendly_expect(
+                    fs::read_to_string(&argv[1]),
+                    &format!("Error reading file: {}", argv[1]),
+                )
+            };
+
+            let mut ast = friendly_expect(
+                parse(&contract_id, &content, ClarityVersion::Clarity2),
+                "Failed to parse program",
+            );
+
+            let call_graph_res = if argv.len() >= 3 {
+                let vm_filename = &argv[2];
+                let header_db =
+                    friendly_expect(CLIHeadersDB::resume(vm_filename), "Failed to open CLI DB");
+                let marf_kv = friendly_expect(
+                    MarfedKV::open(vm_filename, None, None),
+                    "Failed to open VM database.",
+                );
+                at_chaintip(vm_filename, marf_kv, |mut marf| {
+                    let result = run_analysis(&contract_id, &mut ast, &header_db, &mut marf, false)
+                        .map(|analysis| CallGraph::run(&analysis, &mut marf.get_analysis_db()));
+                    (marf, result)
+                })
+            } else {
+                let header_db = CLIHeadersDB::new_memory(mainnet);
+                let mut analysis_marf = MemoryBackingStore::new();
+                install_boot_code(&header_db, &mut analysis_marf);
+                run_analysis(
+                    &contract_id,
+                    &mut ast,
+                    &header_db,
+                    &mut analysis_marf,
+                    false,
+                )
+                .map(|analysis| CallGraph::run(&analysis, &mut analysis_marf.get_analysis_db()))
+            };
+
+            let call_graph = match call_graph_res {
+                Ok(call_graph) => call_graph,
+                Err((e, _)) => {
+                    return (
+                        1,
+                        Some(json!({
+                            "message": "Checks failed.",
+                            "error": {
+                                "analysis": serde_json::to_value(&e.diagnostic).unwrap(),
+                            }
+                        })),
+                    );
+                }
+            };
+
+            if dot {
+                print!("{}", call_graph.to_dot());
+                (0, None)
+            } else {
+                (0, Some(serde_json::to_value(&call_graph).unwrap()))
+            }
+        }
+        "costs" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let contract_id = if let Ok(optarg) = consume_arg(&mut argv, &["--contract_id"], true) {
+                optarg
+                    .map(|optarg_str| {
+                        friendly_expect(
+                            QualifiedContractIdentifier::parse(&optarg_str),
+                            &format!("Error parsing contract identifier '{}", &optarg_str),
+                        )
+                    })
+                    .unwrap_or(QualifiedContractIdentifier::transient())
+            } else {
+                eprintln!("Expected argument for --contract-id");
+                panic_test!();
+            };
+            let mainnet = !matches!(consume_arg(&mut argv, &["--testnet"], false), Ok(Some(_)));
+
+            if argv.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--testnet] (vm-state.db)",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   Prints an upper bound on the cost of calling each public and read-only function,");
+                eprintln!("   over every input its argument types admit, and whether it can exceed the block");
+                eprintln!("   limit.  The costs of functions in other contracts reached by contract-call? are");
+                eprintln!("   not included; they are listed as external_calls.  Exits with 1 if any function");
+                eprintln!("   can exceed the block limit.");
+                panic_test!();
+            }
+
+            let content: String = if &argv[1] == "-" {
+                let mut buffer = String::new();
+                friendly_expect(
+                    io::stdin().read_to_string(&mut buffer),
+                    "Error reading from stdin.",
+                );
+                buffer
+            } else {
+                friendly_expect(
+                    fs::read_to_string(&argv[1]),
+                    &format!("Error reading file: {}", argv[1]),
+                )
+            };
+
+            let mut ast = friendly_expect(
+                parse(&contract_id, &content, ClarityVersion::Clarity2),
+                "Failed to parse program",
+            );
+
+            let analysis_res = if argv.len() >= 3 {
+                let vm_filename = &argv[2];
+                let header_db =
+                    friendly_expect(CLIHeadersDB::resume(vm_filename), "Failed to open CLI DB");
+                let marf_kv = friendly_expect(
+                    MarfedKV::open(vm_filename, None, None),
+                    "Failed to open VM database.",
+                );
+                at_chaintip(vm_filename, marf_kv, |mut marf| {
+                    let result = run_analysis(&contract_id, &mut ast, &header_db, &mut marf, false);
+                    (marf, result)
+                })
+            } else {
+                let header_db = CLIHeadersDB::new_memory(mainnet);
+                let mut analysis_marf = MemoryBackingStore::new();
+                install_boot_code(&header_db, &mut analysis_marf);
+                run_analysis(
+                    &contract_id,
+                    &mut ast,
+                    &header_db,
+                    &mut analysis_marf,
+                    false,
+                )
+            };
+
+            let mut analysis = match analysis_res {
+                Ok(analysis) => analysis,
+                Err((e, _)) => {
+                    return (
+                        1,
+                        Some(json!({
+                            "message": "Checks failed.",
+                            "error": {
+                                "analysis": serde_json::to_value(e.diagnostic).unwrap(),
+                            }
+                        })),
+                    );
+                }
+            };
+
+            // the tracker the analysis ran with has the cost functions and block limit loaded
+            let mut cost_track = analysis.take_contract_cost_tracker();
+            let block_limit = cost_track.get_limit();
+            let estimate = match CostEstimate::run(
+                &analysis,
+                content.len() as u64,
+                &mut cost_track,
+                &block_limit,
+            ) {
+                Ok(estimate) => estimate,
+                Err(e) => {
+                    return (
+                        1,
+                        Some(json!({
+                            "message": "Cost estimation failed.",
+                            "error": {
+                                "costs": format!("{:?}", e),
+                            }
+                        })),
+                    );
+                }
+            };
+
+            let exceeding: Vec<_> = estimate
+                .functions
+                .iter()
+                .filter(|(_, function)| function.exceeds_block_limit)
+                .map(|(name, _)| name.clone())
+                .collect();
+            let mut result = serde_json::to_value(&estimate).unwrap();
+            if exceeding.is_empty() {
+                result["message"] = json!("No function can exceed the block limit.");
+                (0, Some(result))
+            } else {
+                result["message"] = json!(format!(
+                    "Can exceed the block limit: {}",
+                    exceeding.join(", ")
+                ));
+                (1, Some(result))
+            }
+        }
+        "fmt" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let check = matches!(consume_arg(&mut argv, &["--check"], false), Ok(Some(_)));
+
+            if argv.len() < 2 || (argv.len() > 2 && argv[1..].iter().any(|path| path == "-")) {
+                eprintln!(
+                    "Usage: {} {} [--check] [program-file.clar] ...",
+                    invoked_by, argv[0]
+                );
+                eprintln!(
+                    "   Rewrites each contract in the canonical Clarity style.  With --check, the"
+                );
+                eprintln!(
+                    "   files are left alone, and the command fails if any of them would change."
+                );
+                eprintln!(
+                    "   If the only file is `-`, the contract is read from stdin, and (without"
+                );
+                eprintln!("   --check) the formatted contract is written to stdout.");
+                panic_test!();
+            }
+
+            let mut changed = vec![];
+            for path in argv[1..].iter() {
+                let content = if path == "-" {
+                    let mut buffer = String::new();
+                    friendly_expect(
+                        io::stdin().read_to_string(&mut buffer),
+                        "Error reading from stdin.",
+                    );
+                    buffer
+                } else {
+                    friendly_expect(
+                        fs::read_to_string(path),
+                        &format!("Error reading file: {}", path),
+                    )
+                };
+
+                let formatted = match format_source(&content) {
+                    Ok(formatted) => formatted,
+                    Err(error) => {
+                        return (
+                            1,
+                            Some(json!({
+                                "error": {
+                                    "file": path,
+                                    "parse": error,
+                                }
+                            })),
+                        );
+                    }
+                };
+
+                if path == "-" && !check {
+                    print!("{}", formatted);
+                    return (0, None);
+                }
+                if formatted == content {
+                    continue;
+                }
+                if !check {
+                    friendly_expect(
+                        fs::write(path, &formatted),
+                        &format!("Error writing file: {}", path),
+                    );
+                }
+                changed.push(path.clone());
+            }
+
+            if check {
+                let result = json!({
+                    "message": format!("{} of {} files need formatting", changed.len(), argv.len() - 1),
+                    "unformatted": changed,
+                });
+                (if changed.is_empty() { 0 } else { 1 }, Some(result))
+            } else {
+                let result = json!({
+                    "message": format!("Formatted {} of {} files", changed.len(), argv.len() - 1),
+                    "formatted": changed,
+                });
+                (0, Some(result))
+            }
+        }
+        "make_lcov" => {
+            let mut register_files = vec![];
+            let mut coverage_files = vec![];
+            let coverage_folder = &args[1];
+            let lcov_output_file = &args[2];
+            for folder_entry in
+                fs::read_dir(coverage_folder).expect("Failed to read the coverage folder")
+            {
+                let folder_entry =
+                    folder_entry.expect("Failed to read entry in the coverage folder");
+                let entry_path = folder_entry.path();
+                if entry_path.is_file() {
+                    if entry_path.extension() == Some(OsStr::new("clarcovref")) {
+                        register_files.push(entry_path)
+                    } else if entry_path.extension() == Some(OsStr::new("clarcov")) {
+                        coverage_files.push(entry_path)
+                    }
+                }
+            }
+            CoverageReporter::produce_lcov(lcov_output_file, &register_files, &coverage_files)
+                .expect("Failed to produce an lcov output");
+            (0, None)
+        }
+        _ => {
+            print_usage(invoked_by);
+            (1, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_initial_alloc() {
+        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
+        let json_name = format!("/tmp/test-alloc_{}.json", rand::thread_rng().gen::<i32>());
+        let clar_name = format!("/tmp/test-alloc_{}.clar", rand::thread_rng().gen::<i32>());
+
+        fs::write(
+            &json_name,
+            r#"
 [ { "principal": "S1G2081040G2081040G2081040G208105NK8PE5",
     "amount": 1000 },
   { "principal": "S1G2081040G2081040G2081040G208105NK8PE5.names",
@@ -2037,6 +3369,28 @@ mod test {
 
         assert_eq!(exit, 0);
         assert!(result["message"].as_str().unwrap().len() > 0);
+        assert!(result["warnings"].is_null());
+
+        eprintln!("check tokens (lint)");
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "sample-contracts/tokens.clar".to_string(),
+                "--lint".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        // `mint!` binds `balance` but never reads it
+        let warnings = result["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0]["message"], "'balance' is never used");
+        assert_eq!(warnings[0]["level"], "Warning");
+        assert_eq!(warnings[0]["spans"][0]["start_line"], 24);
 
         eprintln!("check tokens (idempotency)");
         let invoked = invoke_command(
@@ -2389,4 +3743,526 @@ mod test {
                 })
         );
     }
+
+    #[test]
+    fn test_execute_profile() {
+        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
+        let profile_name = format!("/tmp/profile_{}.folded", rand::thread_rng().gen::<i32>());
+
+        invoke_command("test", &["initialize".to_string(), db_name.clone()]);
+        let invoked = invoke_command(
+            "test",
+            &[
+                "launch".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "sample-contracts/tokens.clar".to_string(),
+                db_name.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                "--profile".to_string(),
+                profile_name.clone(),
+                "--profile-dimension".to_string(),
+                "read_count".to_string(),
+                db_name,
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5".to_string(),
+                "u1000".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        eprintln!("{}", serde_json::to_string(&result).unwrap());
+        assert_eq!(exit, 0);
+
+        // `mint!` calls `get-balance` directly and through `token-credit!`
+        let functions = result["profile"]["functions"].as_array().unwrap();
+        let get_balance = functions
+            .iter()
+            .find(|f| {
+                f["function"] == "S1G2081040G2081040G2081040G208105NK8PE5.tokens::get-balance"
+            })
+            .unwrap();
+        assert_eq!(get_balance["calls"], 2);
+        assert!(get_balance["cost"]["runtime"].as_u64().unwrap() > 0);
+        assert!(!result["profile"]["lines"].as_array().unwrap().is_empty());
+
+        let folded = fs::read_to_string(&profile_name).unwrap();
+        let stacks: Vec<_> = folded
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().0)
+            .collect();
+        assert!(stacks.contains(
+            &"S1G2081040G2081040G2081040G208105NK8PE5.tokens::mint!;S1G2081040G2081040G2081040G208105NK8PE5.tokens::token-credit!;S1G2081040G2081040G2081040G208105NK8PE5.tokens::get-balance"
+        ));
+        // weighed by read count, so the root frame, which reads nothing itself, doesn't appear
+        assert!(!stacks.contains(&"S1G2081040G2081040G2081040G208105NK8PE5.tokens::mint!"));
+    }
+
+    #[test]
+    fn test_unit_tests() {
+        let contract_name = format!("/tmp/counter_{}.clar", rand::thread_rng().gen::<i32>());
+        let test_name = format!("/tmp/counter-test_{}.clar", rand::thread_rng().gen::<i32>());
+
+        fs::write(
+            &contract_name,
+            r#"
+(define-data-var count uint u0)
+(define-public (increment)
+  (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))
+(define-read-only (get-count) (var-get count))
+;; not a test contract, so this is not run
+(define-public (test-reset)
+  (begin (var-set count u0) (err u0)))
+"#,
+        )
+        .unwrap();
+
+        fs::write(
+            &test_name,
+            r#"
+(define-public (test-increment)
+  (begin
+    (unwrap-panic (contract-call? .counter increment))
+    (asserts! (is-eq (contract-call? .counter get-count) u1) (err u1))
+    (ok true)))
+
+;; each test gets its own fork of the chain
+;; @mine-blocks 5
+(define-public (test-isolated)
+  (begin
+    (asserts! (is-eq (contract-call? .counter get-count) u0) (err u2))
+    (ok block-height)))
+
+;; @sender 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR
+;; @mint 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 1000
+(define-public (test-mint)
+  (ok (stx-get-balance tx-sender)))
+
+(define-public (test-fails)
+  (if true (err u42) (ok true)))
+
+;; @mine-blocks lots
+(define-public (test-bad-annotation)
+  (ok true))
+
+(define-public (not-a-test)
+  (if true (err u43) (ok true)))
+"#,
+        )
+        .unwrap();
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "test".to_string(),
+                "--testnet".to_string(),
+                ":memory:".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.counter".to_string(),
+                contract_name,
+                "S1G2081040G2081040G2081040G208105NK8PE5.counter-test".to_string(),
+                test_name,
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        eprintln!("{}", serde_json::to_string(&result).unwrap());
+
+        assert_eq!(exit, 1);
+        assert_eq!(result["passed"], 3);
+        assert_eq!(result["failed"], 2);
+
+        let tests = result["tests"].as_array().unwrap();
+        let names: Vec<_> = tests.iter().map(|t| t["test"].as_str().unwrap()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "test-increment",
+                "test-isolated",
+                "test-mint",
+                "test-fails",
+                "test-bad-annotation"
+            ]
+        );
+
+        // the boot code and contracts are at heights 0 and 1, and 5 blocks are mined after them
+        assert_eq!(tests[1]["output"], json!({ "UInt": 7 }));
+        assert_eq!(tests[2]["output"], json!({ "UInt": 1000 }));
+        assert_eq!(tests[3]["success"], false);
+        assert_eq!(tests[3]["output"], json!({ "UInt": 42 }));
+        assert_eq!(tests[4]["success"], false);
+        assert_eq!(tests[4]["error"], "invalid block count 'lots'");
+    }
+
+    #[test]
+    fn test_fuzz() {
+        let contract_name = format!("/tmp/fuzzed_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_name,
+            r#"
+(define-data-var count uint u0)
+(define-data-var armed bool false)
+
+(define-public (arm)
+  (begin (var-set armed true) (ok true)))
+
+(define-public (bump)
+  (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))
+
+(define-public (noise (memo (buff 34)) (tags (list 5 (string-ascii 10))) (who (optional principal)))
+  (ok true))
+
+(define-read-only (invariant-count-disarmed)
+  (not (and (var-get armed) (>= (var-get count) u3))))
+
+(define-read-only (invariant-ok)
+  (ok true))
+"#,
+        )
+        .unwrap();
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "fuzz".to_string(),
+                "--calls".to_string(),
+                "30".to_string(),
+                "--seed".to_string(),
+                "42".to_string(),
+                ":memory:".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.fuzzed".to_string(),
+                contract_name.clone(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        eprintln!("{}", serde_json::to_string(&result).unwrap());
+
+        assert_eq!(exit, 1);
+        assert_eq!(result["seed"], 42);
+        assert_eq!(
+            result["invariants"],
+            json!(["invariant-count-disarmed", "invariant-ok"])
+        );
+        assert_eq!(result["failure"]["invariant"], "invariant-count-disarmed");
+        assert_eq!(result["failure"]["result"], "false");
+
+        // only the calls that matter are left
+        let mut functions: Vec<_> = result["failure"]["calls"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|call| call["function"].as_str().unwrap())
+            .collect();
+        functions.sort();
+        assert_eq!(functions, vec!["arm", "bump", "bump", "bump"]);
+
+        // without the bug, no violations are found
+        let contract = fs::read_to_string(&contract_name).unwrap();
+        fs::write(
+            &contract_name,
+            contract.replace("(define-public (arm)", "(define-private (arm)"),
+        )
+        .unwrap();
+        let invoked = invoke_command(
+            "test",
+            &[
+                "fuzz".to_string(),
+                "--runs".to_string(),
+                "5".to_string(),
+                ":memory:".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.fuzzed".to_string(),
+                contract_name,
+            ],
+        );
+        let result = invoked.1.unwrap();
+        assert_eq!(invoked.0, 0);
+        assert_eq!(result["runs"], 5);
+        assert_eq!(result["calls"], 50);
+        assert_eq!(result["functions"], json!(["bump", "noise"]));
+        assert!(result["failure"].is_null());
+    }
+
+    #[test]
+    fn test_fuzz_minimize_same_failure() {
+        let contract_name = format!("/tmp/minimized_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_name,
+            r#"
+(define-data-var count uint u0)
+(define-data-var armed bool false)
+(define-data-var was-armed bool false)
+
+(define-public (arm)
+  (begin (var-set armed true) (var-set was-armed true) (ok true)))
+
+(define-public (disarm)
+  (begin (var-set armed false) (ok true)))
+
+(define-public (bump)
+  (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))
+
+(define-read-only (invariant-armed)
+  (not (and (var-get armed) (>= (var-get count) u1))))
+
+(define-read-only (invariant-was-armed)
+  (not (and (var-get was-armed) (>= (var-get count) u2))))
+"#,
+        )
+        .unwrap();
+
+        let contract_identifier =
+            QualifiedContractIdentifier::parse("S1G2081040G2081040G2081040G208105NK8PE5.minimized")
+                .unwrap();
+        let mut contracts =
+            read_scratch_contracts(&[contract_identifier.to_string(), contract_name]);
+        let (scratch_path, header_db, marf_kv) = open_scratch_vm_state(":memory:", false);
+        let (mut header_db, mut marf_kv, launch_result) =
+            launch_scratch_contracts(header_db, marf_kv, &mut contracts, None);
+        assert!(launch_result.is_ok());
+        let base = get_cli_chain_tip(header_db.conn());
+
+        let invariants = vec![
+            "invariant-armed".to_string(),
+            "invariant-was-armed".to_string(),
+        ];
+        let sender = PrincipalData::Standard(contract_identifier.issuer.clone());
+        let calls: Vec<_> = ["arm", "disarm", "bump", "bump"]
+            .iter()
+            .map(|function| FuzzCall {
+                sender: sender.clone(),
+                function: function.to_string(),
+                args: vec![],
+            })
+            .collect();
+
+        let (_, failure) = run_fuzz_calls(
+            &calls,
+            &contract_identifier,
+            &invariants,
+            &base,
+            &mut header_db,
+            &mut marf_kv,
+        );
+        let failure = failure.unwrap();
+        assert_eq!(failure.invariant, "invariant-was-armed");
+        assert_eq!(failure.num_calls, 4);
+
+        // dropping `disarm` fails sooner, but on `invariant-armed`, so it is kept
+        let minimized = minimize_fuzz_calls(
+            calls,
+            &failure,
+            &contract_identifier,
+            &invariants,
+            &base,
+            &mut header_db,
+            &mut marf_kv,
+        );
+        let functions: Vec<_> = minimized
+            .iter()
+            .map(|call| call.function.as_str())
+            .collect();
+        assert_eq!(functions, vec!["arm", "disarm", "bump", "bump"]);
+
+        drop(marf_kv);
+        drop(header_db);
+        let _ = fs::remove_dir_all(&scratch_path);
+    }
+
+    #[test]
+    fn test_fmt() {
+        let contract_name = format!("/tmp/fmt_{}.clar", rand::thread_rng().gen::<i32>());
+        let unformatted = "(define-data-var   count uint u0)\n(define-public (bump) (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))\n";
+        let formatted = "(define-data-var count uint u0)\n(define-public (bump)\n  (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))\n";
+        fs::write(&contract_name, unformatted).unwrap();
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "fmt".to_string(),
+                "--check".to_string(),
+                contract_name.clone(),
+            ],
+        );
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        assert_eq!(exit, 1);
+        assert_eq!(result["unformatted"], json!([contract_name]));
+        assert_eq!(fs::read_to_string(&contract_name).unwrap(), unformatted);
+
+        let invoked = invoke_command("test", &["fmt".to_string(), contract_name.clone()]);
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        assert_eq!(exit, 0);
+        assert_eq!(result["formatted"], json!([contract_name]));
+        assert_eq!(fs::read_to_string(&contract_name).unwrap(), formatted);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "fmt".to_string(),
+                "--check".to_string(),
+                contract_name.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+
+        fs::write(&contract_name, "(define-data-var count uint").unwrap();
+        let invoked = invoke_command("test", &["fmt".to_string(), contract_name.clone()]);
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        assert_eq!(exit, 1);
+        assert!(result["error"]["parse"].as_str().is_some());
+        assert_eq!(
+            fs::read_to_string(&contract_name).unwrap(),
+            "(define-data-var count uint"
+        );
+
+        fs::remove_file(&contract_name).unwrap();
+    }
+
+    #[test]
+    fn test_deps() {
+        let contract_name = format!("/tmp/deps_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_name,
+            r#"
+(define-data-var calls uint u0)
+(define-private (bump) (var-set calls (+ u1 (var-get calls))))
+(define-public (info)
+  (begin
+    (bump)
+    (ok (contract-call? 'SP000000000000000000002Q6VF78.pox-2 get-stacker-info tx-sender))))
+"#,
+        )
+        .unwrap();
+
+        let invoked = invoke_command("test", &["deps".to_string(), contract_name.clone()]);
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        assert_eq!(exit, 0);
+        assert_eq!(result["functions"]["info"]["calls"], json!(["bump"]));
+        assert_eq!(
+            result["functions"]["info"]["contract_calls"],
+            json!([{
+                "contract": "SP000000000000000000002Q6VF78.pox-2",
+                "function": "get-stacker-info",
+                "function_type": "read-only",
+                "as_contract": false,
+            }])
+        );
+        assert_eq!(result["functions"]["bump"]["var_writes"], json!(["calls"]));
+
+        // --dot prints the graph instead
+        let invoked = invoke_command(
+            "test",
+            &[
+                "deps".to_string(),
+                "--dot".to_string(),
+                contract_name.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+        assert!(invoked.1.is_none());
+
+        fs::remove_file(&contract_name).unwrap();
+    }
+
+    #[test]
+    fn test_costs() {
+        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
+        invoke_command("test", &["initialize".to_string(), db_name.clone()]);
+        invoke_command(
+            "test",
+            &[
+                "launch".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "sample-contracts/tokens.clar".to_string(),
+                db_name.clone(),
+            ],
+        );
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                "--costs".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5".to_string(),
+                "u1000".to_string(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+        let actual = invoked.1.unwrap()["costs"].clone();
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "costs".to_string(),
+                "--contract_id".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "sample-contracts/tokens.clar".to_string(),
+                db_name,
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+        let result = invoked.1.unwrap();
+        let mint = &result["functions"]["mint!"];
+        assert_eq!(mint["visibility"], json!("public"));
+        assert_eq!(mint["exceeds_block_limit"], json!(false));
+        for dimension in [
+            "runtime",
+            "read_count",
+            "read_length",
+            "write_count",
+            "write_length",
+        ] {
+            assert!(
+                mint["cost"][dimension].as_u64().unwrap() >= actual[dimension].as_u64().unwrap(),
+                "{} estimate is below an actual execution",
+                dimension
+            );
+        }
+
+        // one map read per item is more than a block can hold
+        let contract_name = format!("/tmp/costs_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_name,
+            r#"
+(define-map balances uint uint)
+(define-private (balance-of (id uint)) (default-to u0 (map-get? balances id)))
+(define-read-only (balances-of (ids (list 20000 uint))) (map balance-of ids))
+(define-read-only (balance (id uint)) (balance-of id))
+"#,
+        )
+        .unwrap();
+        let invoked = invoke_command("test", &["costs".to_string(), contract_name.clone()]);
+        assert_eq!(invoked.0, 1);
+        let result = invoked.1.unwrap();
+        assert_eq!(
+            result["functions"]["balances-of"]["exceeds_block_limit"],
+            json!(true)
+        );
+        assert_eq!(
+            result["functions"]["balance"]["exceeds_block_limit"],
+            json!(false)
+        );
+        assert_eq!(
+            result["message"],
+            json!("Can exceed the block limit: balances-of")
+        );
+
+        fs::remove_file(&contract_name).unwrap();
+    }
 }
@@ -66,8 +66,8 @@ use clarity::vm::ast::{errors::ParseError, errors::ParseErrors, ASTRules, Contra
 use clarity::vm::contexts::{AssetMap, Environment, OwnedEnvironment};
 use clarity::vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
 use clarity::vm::database::{
-    BurnStateDB, ClarityDatabase, HeadersDB, RollbackWrapper, RollbackWrapperPersistedLog,
-    STXBalance, SqliteConnection, NULL_BURN_STATE_DB, NULL_HEADER_DB,
+    BurnStateDB, ClarityDatabase, ContractCacheStats, HeadersDB, RollbackWrapper,
+    RollbackWrapperPersistedLog, STXBalance, SqliteConnection, NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
 use clarity::vm::errors::Error as InterpreterError;
 use clarity::vm::representations::SymbolicExpression;
@@ -255,6 +255,10 @@ impl ClarityInstance {
         }
     }
 
+    pub fn get_contract_cache_stats(&self) -> Option<ContractCacheStats> {
+        self.datastore.get_contract_cache_stats()
+    }
+
     pub fn with_marf<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut MARF<StacksBlockId>) -> R,
@@ -1708,6 +1712,120 @@ mod tests {
         assert!(conn.get_contract_hash(&contract_identifier).is_ok());
     }
 
+    #[test]
+    pub fn test_contract_cache() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+
+        let deploy = |conn: &mut ClarityBlockConnection, contract: &str| {
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+        };
+        let call = |conn: &mut ClarityBlockConnection| {
+            conn.as_transaction(|tx| {
+                tx.run_contract_call(
+                    &StandardPrincipalData::transient().into(),
+                    None,
+                    &contract_identifier,
+                    "foo",
+                    &[Value::Int(3)],
+                    |_, _| false,
+                )
+            })
+            .unwrap()
+            .0
+        };
+
+        // two forks off of the genesis block deploy different contracts with the same name
+        for (fork, contract) in [
+            (1u8, "(define-public (foo (x int)) (ok (+ x x)))"),
+            (2u8, "(define-public (foo (x int)) (ok (* x x)))"),
+        ] {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0; 32]),
+                &StacksBlockId([fork; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            deploy(&mut conn, contract);
+            // not cached while the deploying block is still open
+            call(&mut conn);
+            conn.commit_block();
+        }
+        // (the boot cost contract is cached as soon as it is first used)
+        let before = clarity_instance.get_contract_cache_stats().unwrap();
+        assert_eq!(before.entries, 1);
+
+        for (fork, expected) in [(1u8, 6), (2u8, 9)] {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([fork; 32]),
+                &StacksBlockId([fork + 10; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            assert_eq!(call(&mut conn), Value::okay(Value::Int(expected)).unwrap());
+            assert_eq!(call(&mut conn), Value::okay(Value::Int(expected)).unwrap());
+            conn.commit_block();
+        }
+
+        let after = clarity_instance.get_contract_cache_stats().unwrap();
+        assert_eq!(after.misses - before.misses, 2);
+        assert_eq!(after.entries, 3);
+
+        // read-only connections share the cache, and only treat confirmed blocks as committed
+        let mut store = clarity_instance
+            .datastore
+            .begin_read_only(Some(&StacksBlockId([11; 32])));
+        assert!(store.is_committed_block(&StacksBlockId([1; 32])));
+        assert!(!store.is_committed_block(&StacksBlockId([99; 32])));
+        assert_eq!(
+            store
+                .get_committed_contract_hash(&contract_identifier)
+                .map(|(deployed_at, _)| deployed_at),
+            Some(StacksBlockId([1; 32]))
+        );
+        let mut db = store.as_clarity_db(&TEST_HEADER_DB, &TEST_BURN_STATE_DB);
+        db.begin();
+        let contract = db.get_contract(&contract_identifier).unwrap();
+        db.roll_back();
+        assert_eq!(
+            contract.contract_context.contract_identifier,
+            contract_identifier
+        );
+        let read_only = clarity_instance.get_contract_cache_stats().unwrap();
+        assert_eq!(read_only.misses, after.misses);
+        assert_eq!(read_only.hits, after.hits + 1);
+    }
+
     #[test]
     pub fn test_block_roll_back() {
         let marf = MarfedKV::temporary();
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use rusqlite::Connection;
@@ -16,9 +17,12 @@ use clarity::vm::errors::{
 use clarity::vm::types::QualifiedContractIdentifier;
 
 use crate::chainstate::stacks::index::{ClarityMarfTrieId, MARFValue, TrieMerkleProof};
-use clarity::vm::database::SpecialCaseHandler;
+use clarity::vm::database::{
+    ContractCache, ContractCacheStats, SpecialCaseHandler, DEFAULT_CONTRACT_CACHE_SIZE,
+};
 use stacks_common::types::chainstate::BlockHeaderHash;
 use stacks_common::types::chainstate::{StacksBlockId, TrieHash};
+use stacks_common::util::hash::Sha512Trunc256Sum;
 
 use crate::clarity_vm::special::handle_contract_call_special_cases;
 use crate::codec::StacksMessageCodec;
@@ -34,6 +38,9 @@ use crate::util_lib::db::Error as DatabaseError;
 pub struct MarfedKV {
     chain_tip: StacksBlockId,
     marf: MARF<StacksBlockId>,
+    /// parsed contracts, shared by every store opened on this MARF.  Unconfirmed MARFs don't
+    ///   get one, since their chain tip is rebuilt in place.
+    contract_cache: Option<ContractCache>,
 }
 
 impl MarfedKV {
@@ -91,7 +98,11 @@ impl MarfedKV {
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            contract_cache: Some(ContractCache::new(DEFAULT_CONTRACT_CACHE_SIZE)),
+        })
     }
 
     pub fn open_unconfirmed(
@@ -105,7 +116,11 @@ impl MarfedKV {
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            contract_cache: None,
+        })
     }
 
     // used by benchmarks
@@ -135,7 +150,11 @@ impl MarfedKV {
 
         let chain_tip = StacksBlockId::sentinel();
 
-        MarfedKV { marf, chain_tip }
+        MarfedKV {
+            marf,
+            chain_tip,
+            contract_cache: Some(ContractCache::new(DEFAULT_CONTRACT_CACHE_SIZE)),
+        }
     }
 
     pub fn begin_read_only<'a>(
@@ -157,6 +176,8 @@ impl MarfedKV {
         ReadOnlyMarfStore {
             chain_tip,
             marf: &mut self.marf,
+            contract_cache: self.contract_cache.as_ref(),
+            committed_contract_hashes: HashMap::new(),
         }
     }
 
@@ -179,6 +200,8 @@ impl MarfedKV {
         Ok(ReadOnlyMarfStore {
             chain_tip,
             marf: &mut self.marf,
+            contract_cache: self.contract_cache.as_ref(),
+            committed_contract_hashes: HashMap::new(),
         })
     }
 
@@ -213,6 +236,8 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            contract_cache: self.contract_cache.as_ref(),
+            committed_contract_hashes: HashMap::new(),
         }
     }
 
@@ -234,6 +259,8 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            contract_cache: self.contract_cache.as_ref(),
+            committed_contract_hashes: HashMap::new(),
         }
     }
 
@@ -241,6 +268,10 @@ impl MarfedKV {
         &self.chain_tip
     }
 
+    pub fn get_contract_cache_stats(&self) -> Option<ContractCacheStats> {
+        self.contract_cache.as_ref().map(|cache| cache.stats())
+    }
+
     pub fn get_marf(&mut self) -> &mut MARF<StacksBlockId> {
         &mut self.marf
     }
@@ -258,14 +289,26 @@ impl MarfedKV {
     }
 }
 
+/// Memoized `get_committed_contract_hash()` results, keyed by the chain tip they were looked up
+///   at.  The answer for a given tip can't change while the store is open: contracts can't be
+///   redeployed, and one deployed in the open chain tip isn't committed.
+type CommittedContractHashes = HashMap<
+    (StacksBlockId, QualifiedContractIdentifier),
+    Option<(StacksBlockId, Sha512Trunc256Sum)>,
+>;
+
 pub struct WritableMarfStore<'a> {
     chain_tip: StacksBlockId,
     marf: MarfTransaction<'a, StacksBlockId>,
+    contract_cache: Option<&'a ContractCache>,
+    committed_contract_hashes: CommittedContractHashes,
 }
 
 pub struct ReadOnlyMarfStore<'a> {
     chain_tip: StacksBlockId,
     marf: &'a mut MARF<StacksBlockId>,
+    contract_cache: Option<&'a ContractCache>,
+    committed_contract_hashes: CommittedContractHashes,
 }
 
 impl<'a> ReadOnlyMarfStore<'a> {
@@ -298,6 +341,38 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
         Some(&handle_contract_call_special_cases)
     }
 
+    fn get_contract_cache(&self) -> Option<&ContractCache> {
+        self.contract_cache
+    }
+
+    /// Only confirmed blocks are committed: not the unconfirmed tip, nor a block still open for
+    ///   writing in the MARF.
+    fn is_committed_block(&mut self, bhh: &StacksBlockId) -> bool {
+        if self.marf.get_open_chain_tip() == Some(bhh) {
+            return false;
+        }
+        self.marf
+            .with_conn(|conn| conn.has_confirmed_block(bhh))
+            .unwrap_or(false)
+    }
+
+    fn get_committed_contract_hash(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> Option<(StacksBlockId, Sha512Trunc256Sum)> {
+        let memo_key = (self.chain_tip.clone(), contract.clone());
+        if let Some(result) = self.committed_contract_hashes.get(&memo_key) {
+            return result.clone();
+        }
+        let result = self
+            .get_contract_hash(contract)
+            .ok()
+            .filter(|(bhh, _)| self.is_committed_block(bhh));
+        self.committed_contract_hashes
+            .insert(memo_key, result.clone());
+        result
+    }
+
     /// Sets the chain tip at which queries will happen.  Used for `(at-block ..)`
     fn set_block_hash(&mut self, bhh: StacksBlockId) -> InterpreterResult<StacksBlockId> {
         self.marf
@@ -544,6 +619,31 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
         Some(&handle_contract_call_special_cases)
     }
 
+    fn get_contract_cache(&self) -> Option<&ContractCache> {
+        self.contract_cache
+    }
+
+    fn is_committed_block(&mut self, bhh: &StacksBlockId) -> bool {
+        *bhh != self.get_open_chain_tip()
+    }
+
+    fn get_committed_contract_hash(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> Option<(StacksBlockId, Sha512Trunc256Sum)> {
+        let memo_key = (self.chain_tip.clone(), contract.clone());
+        if let Some(result) = self.committed_contract_hashes.get(&memo_key) {
+            return result.clone();
+        }
+        let result = self
+            .get_contract_hash(contract)
+            .ok()
+            .filter(|(bhh, _)| self.is_committed_block(bhh));
+        self.committed_contract_hashes
+            .insert(memo_key, result.clone());
+        result
+    }
+
     fn get(&mut self, key: &str) -> Option<String> {
         trace!("MarfedKV get: {:?} tip={}", key, &self.chain_tip);
         self.marf
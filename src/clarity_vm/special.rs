@@ -614,7 +614,7 @@ fn synthesize_pox_2_event_info(
             .special_cc_handler_execute_read_only(
                 sender.clone(),
                 None,
-                pox_2_contract.contract_context,
+                &pox_2_contract.contract_context,
                 |env| {
                     let base_event_info = env
                         .eval_read_only_with_rules(
@@ -60,7 +60,7 @@ use crate::util_lib::db::Error as db_error;
 use crate::util_lib::db::FromColumn;
 use crate::util_lib::db::{query_row, Error};
 use crate::util_lib::db::{sql_pragma, DBConn, DBTx, FromRow};
-use clarity::vm::types::PrincipalData;
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
 use stacks_common::util::get_epoch_time_ms;
 use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::to_hex;
@@ -300,6 +300,8 @@ pub struct MemPoolWalkSettings {
     /// Size of the candidate cache. These are the candidates that will be retried after each
     /// transaction is mined.
     pub candidate_retry_cache_size: u64,
+    /// Miner-specific rules for which transactions may be selected
+    pub selection_policy: TxSelectionPolicy,
 }
 
 impl MemPoolWalkSettings {
@@ -310,6 +312,7 @@ impl MemPoolWalkSettings {
             consider_no_estimate_tx_prob: 5,
             nonce_cache_size: 1024 * 1024,
             candidate_retry_cache_size: 64 * 1024,
+            selection_policy: TxSelectionPolicy::default(),
         }
     }
     pub fn zero() -> MemPoolWalkSettings {
@@ -319,10 +322,161 @@ impl MemPoolWalkSettings {
             consider_no_estimate_tx_prob: 5,
             nonce_cache_size: 1024 * 1024,
             candidate_retry_cache_size: 64 * 1024,
+            selection_policy: TxSelectionPolicy::default(),
         }
     }
 }
 
+/// Rules a miner applies on top of fee-rate ordering when selecting mempool transactions for a
+/// block.  The default policy selects everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TxSelectionPolicy {
+    /// If non-empty, only transactions involving at least one of these principals are selected
+    pub include_list: HashSet<PrincipalData>,
+    /// Transactions involving any of these principals are never selected
+    pub deny_list: HashSet<PrincipalData>,
+    /// Transactions involving any of these principals are priority transactions
+    pub priority_list: HashSet<PrincipalData>,
+    /// Percentage of the block budget that only priority transactions may use
+    pub reserved_priority_pct: u8,
+    /// Once calls to a contract have used this percentage of the block budget, no further calls
+    /// to it are selected
+    pub max_contract_cost_pct: Option<u8>,
+}
+
+impl TxSelectionPolicy {
+    /// The principals a transaction involves: its origin and sponsor, plus the recipient of its
+    /// STX transfer, the contract it calls, or the contract it deploys.
+    pub fn involved_principals(tx: &StacksTransaction) -> Vec<PrincipalData> {
+        let mut principals = vec![PrincipalData::from(tx.origin_address())];
+        if let Some(sponsor) = tx.sponsor_address() {
+            principals.push(PrincipalData::from(sponsor));
+        }
+        match &tx.payload {
+            TransactionPayload::TokenTransfer(recipient, ..) => {
+                principals.push(recipient.clone());
+            }
+            TransactionPayload::ContractCall(cc) => {
+                principals.push(PrincipalData::Contract(cc.to_clarity_contract_id()));
+            }
+            TransactionPayload::SmartContract(sc, _) => {
+                principals.push(PrincipalData::Contract(QualifiedContractIdentifier::new(
+                    tx.origin_address().into(),
+                    sc.name.clone(),
+                )));
+            }
+            _ => {}
+        }
+        principals
+    }
+
+    /// The contract whose share of the block budget this transaction counts against, if any
+    pub fn billed_contract(tx: &StacksTransaction) -> Option<QualifiedContractIdentifier> {
+        match &tx.payload {
+            TransactionPayload::ContractCall(cc) => Some(cc.to_clarity_contract_id()),
+            _ => None,
+        }
+    }
+
+    /// Is this a priority transaction?
+    pub fn is_priority(&self, tx: &StacksTransaction) -> bool {
+        !self.priority_list.is_empty()
+            && Self::involved_principals(tx)
+                .iter()
+                .any(|p| self.priority_list.contains(p))
+    }
+
+    /// Is this a poison-microblock transaction?  These report microblock forks, so they are
+    /// exempt from the include list and the priority reservation.
+    fn is_poison_microblock(tx: &StacksTransaction) -> bool {
+        match tx.payload {
+            TransactionPayload::PoisonMicroblock(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Does `check_tx` use an estimate of the transaction's cost?
+    pub fn uses_cost_estimate(&self) -> bool {
+        self.reserved_priority_pct > 0 || self.max_contract_cost_pct.is_some()
+    }
+
+    /// Decide whether or not `tx` may be selected, given the block budget used so far
+    /// (`block_cost`) and how much of it calls to each contract have used.  If the cost of `tx`
+    /// can be estimated (`estimated_cost`), it is reserved against the unreserved part of the
+    /// block and against its contract's share up front, so that selecting it cannot eat into
+    /// the priority reservation or take the contract over its limit.
+    ///
+    /// Poison-microblock transactions from any sender are exempt from the include list and the
+    /// priority reservation, so they can be mined even if no principal they involve is
+    /// include-listed.  Only the deny list applies to them.
+    ///
+    /// Returns the reason to skip the transaction, if it must be skipped.
+    pub fn check_tx(
+        &self,
+        tx: &StacksTransaction,
+        block_cost: &ExecutionCost,
+        block_limit: &ExecutionCost,
+        contract_costs: &HashMap<QualifiedContractIdentifier, ExecutionCost>,
+        estimated_cost: Option<&ExecutionCost>,
+    ) -> Option<String> {
+        let principals = Self::involved_principals(tx);
+        if let Some(denied) = principals.iter().find(|p| self.deny_list.contains(p)) {
+            return Some(format!("Principal {} is deny-listed.", denied));
+        }
+        if Self::is_poison_microblock(tx) {
+            return None;
+        }
+        if !self.include_list.is_empty()
+            && !principals.iter().any(|p| self.include_list.contains(p))
+        {
+            return Some("No principal is include-listed.".to_string());
+        }
+
+        if self.reserved_priority_pct > 0 && !self.is_priority(tx) {
+            let unreserved_pct = 100 - self.reserved_priority_pct as u64;
+            let over_limit = match estimated_cost {
+                Some(estimated_cost) => {
+                    // on overflow, the transaction certainly doesn't fit
+                    let mut total_cost = block_cost.clone();
+                    total_cost.add(estimated_cost).is_err()
+                        || block_limit.proportion_largest_dimension(&total_cost) > unreserved_pct
+                }
+                None => block_limit.proportion_largest_dimension(block_cost) >= unreserved_pct,
+            };
+            if over_limit {
+                return Some(format!(
+                    "Remaining {}% of the block budget is reserved for priority transactions.",
+                    self.reserved_priority_pct
+                ));
+            }
+        }
+
+        if let (Some(max_pct), Some(contract_id)) =
+            (self.max_contract_cost_pct, Self::billed_contract(tx))
+        {
+            let mut contract_cost = contract_costs
+                .get(&contract_id)
+                .cloned()
+                .unwrap_or_else(ExecutionCost::zero);
+            let over_limit = match estimated_cost {
+                Some(estimated_cost) => {
+                    // on overflow, the call certainly doesn't fit
+                    contract_cost.add(estimated_cost).is_err()
+                        || block_limit.proportion_largest_dimension(&contract_cost) > max_pct as u64
+                }
+                None => block_limit.proportion_largest_dimension(&contract_cost) >= max_pct as u64,
+            };
+            if over_limit {
+                return Some(format!(
+                    "Calls to contract {} would use more than {}% of the block budget.",
+                    contract_id, max_pct
+                ));
+            }
+        }
+        None
+    }
+}
+
 impl FromRow<Txid> for Txid {
     fn from_row<'a>(row: &'a Row) -> Result<Txid, db_error> {
         row.get(0).map_err(db_error::SqliteError)
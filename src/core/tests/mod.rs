@@ -42,6 +42,7 @@ use crate::chainstate::stacks::{
 };
 use crate::core::mempool::db_get_all_nonces;
 use crate::core::mempool::MemPoolWalkSettings;
+use crate::core::mempool::TxSelectionPolicy;
 use crate::core::mempool::TxTag;
 use crate::core::mempool::{BLOOM_COUNTER_DEPTH, BLOOM_COUNTER_ERROR_RATE, MAX_BLOOM_COUNTER_TXS};
 use crate::core::FIRST_BURNCHAIN_CONSENSUS_HASH;
@@ -3013,3 +3014,244 @@ fn test_drop_and_blacklist_txs_by_size() {
 
     assert_eq!(num_blacklisted, 5);
 }
+
+#[test]
+fn test_tx_selection_policy() {
+    let privk = StacksPrivateKey::from_hex(
+        "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+    )
+    .unwrap();
+    let auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+    let origin = PrincipalData::from(auth.origin().address_testnet());
+    let recipient = PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+    let contract_id =
+        QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello")
+            .unwrap();
+
+    let transfer_tx = StacksTransaction::new(
+        TransactionVersion::Testnet,
+        auth.clone(),
+        TransactionPayload::TokenTransfer(recipient.clone(), 123, TokenTransferMemo([0u8; 34])),
+    );
+    let call_tx = StacksTransaction::new(
+        TransactionVersion::Testnet,
+        auth.clone(),
+        TransactionPayload::ContractCall(TransactionContractCall {
+            address: contract_id.issuer.clone().into(),
+            contract_name: contract_id.name.clone(),
+            function_name: "hello".into(),
+            function_args: vec![],
+        }),
+    );
+
+    assert_eq!(
+        TxSelectionPolicy::involved_principals(&transfer_tx),
+        vec![origin.clone(), recipient.clone()]
+    );
+    assert_eq!(
+        TxSelectionPolicy::involved_principals(&call_tx),
+        vec![origin.clone(), PrincipalData::Contract(contract_id.clone())]
+    );
+    assert_eq!(TxSelectionPolicy::billed_contract(&transfer_tx), None);
+    assert_eq!(
+        TxSelectionPolicy::billed_contract(&call_tx),
+        Some(contract_id.clone())
+    );
+
+    let block_limit = ExecutionCost {
+        write_length: 100,
+        write_count: 100,
+        read_length: 100,
+        read_count: 100,
+        runtime: 100,
+    };
+    let no_costs = HashMap::new();
+    let half_full = ExecutionCost {
+        write_length: 50,
+        write_count: 0,
+        read_length: 0,
+        read_count: 0,
+        runtime: 0,
+    };
+
+    // default policy selects everything
+    let policy = TxSelectionPolicy::default();
+    assert!(policy
+        .check_tx(&call_tx, &half_full, &block_limit, &no_costs, None)
+        .is_none());
+
+    // deny-listing the contract only stops the contract-call
+    let policy = TxSelectionPolicy {
+        deny_list: vec![PrincipalData::Contract(contract_id.clone())]
+            .into_iter()
+            .collect(),
+        ..TxSelectionPolicy::default()
+    };
+    assert!(policy
+        .check_tx(
+            &call_tx,
+            &ExecutionCost::zero(),
+            &block_limit,
+            &no_costs,
+            None
+        )
+        .is_some());
+    assert!(policy
+        .check_tx(
+            &transfer_tx,
+            &ExecutionCost::zero(),
+            &block_limit,
+            &no_costs,
+            None
+        )
+        .is_none());
+
+    // include-listing the recipient only admits the transfer
+    let policy = TxSelectionPolicy {
+        include_list: vec![recipient.clone()].into_iter().collect(),
+        ..TxSelectionPolicy::default()
+    };
+    assert!(policy
+        .check_tx(
+            &call_tx,
+            &ExecutionCost::zero(),
+            &block_limit,
+            &no_costs,
+            None
+        )
+        .is_some());
+    assert!(policy
+        .check_tx(
+            &transfer_tx,
+            &ExecutionCost::zero(),
+            &block_limit,
+            &no_costs,
+            None
+        )
+        .is_none());
+
+    // with half the block reserved, only priority transactions get the second half
+    let policy = TxSelectionPolicy {
+        priority_list: vec![PrincipalData::Contract(contract_id.clone())]
+            .into_iter()
+            .collect(),
+        reserved_priority_pct: 50,
+        ..TxSelectionPolicy::default()
+    };
+    assert!(policy.is_priority(&call_tx));
+    assert!(!policy.is_priority(&transfer_tx));
+    assert!(policy
+        .check_tx(
+            &transfer_tx,
+            &ExecutionCost::zero(),
+            &block_limit,
+            &no_costs,
+            None
+        )
+        .is_none());
+    assert!(policy
+        .check_tx(&transfer_tx, &half_full, &block_limit, &no_costs, None)
+        .is_some());
+    assert!(policy
+        .check_tx(&call_tx, &half_full, &block_limit, &no_costs, None)
+        .is_none());
+
+    // an estimated cost is reserved against the unreserved part of the block, so a non-priority
+    // transaction that would cross into the reserved half is skipped
+    let block_cost = ExecutionCost::runtime(40);
+    assert!(policy
+        .check_tx(
+            &transfer_tx,
+            &block_cost,
+            &block_limit,
+            &no_costs,
+            Some(&ExecutionCost::runtime(10))
+        )
+        .is_none());
+    assert!(policy
+        .check_tx(
+            &transfer_tx,
+            &block_cost,
+            &block_limit,
+            &no_costs,
+            Some(&ExecutionCost::runtime(11))
+        )
+        .is_some());
+    assert!(policy
+        .check_tx(
+            &call_tx,
+            &block_cost,
+            &block_limit,
+            &no_costs,
+            Some(&ExecutionCost::runtime(11))
+        )
+        .is_none());
+
+    // calls to a contract stop once it has used its share of the block
+    let policy = TxSelectionPolicy {
+        max_contract_cost_pct: Some(50),
+        ..TxSelectionPolicy::default()
+    };
+    let mut contract_costs = HashMap::new();
+    contract_costs.insert(contract_id.clone(), ExecutionCost::runtime(49));
+    assert!(policy
+        .check_tx(&call_tx, &half_full, &block_limit, &contract_costs, None)
+        .is_none());
+    contract_costs.insert(contract_id.clone(), ExecutionCost::runtime(50));
+    assert!(policy
+        .check_tx(&call_tx, &half_full, &block_limit, &contract_costs, None)
+        .is_some());
+    assert!(policy
+        .check_tx(
+            &transfer_tx,
+            &half_full,
+            &block_limit,
+            &contract_costs,
+            None
+        )
+        .is_none());
+
+    // an estimated cost is reserved against the contract's share before the call is selected
+    contract_costs.insert(contract_id, ExecutionCost::runtime(40));
+    assert!(policy
+        .check_tx(
+            &call_tx,
+            &half_full,
+            &block_limit,
+            &contract_costs,
+            Some(&ExecutionCost::runtime(10))
+        )
+        .is_none());
+    assert!(policy
+        .check_tx(
+            &call_tx,
+            &half_full,
+            &block_limit,
+            &contract_costs,
+            Some(&ExecutionCost::runtime(11))
+        )
+        .is_some());
+
+    // the miner's poison-microblock transactions are exempt from the include list
+    let poison_tx = StacksTransaction::new(
+        TransactionVersion::Testnet,
+        auth.clone(),
+        TransactionPayload::PoisonMicroblock(
+            StacksMicroblockHeader::first_empty_unsigned(&BlockHeaderHash([1u8; 32])),
+            StacksMicroblockHeader::first_empty_unsigned(&BlockHeaderHash([2u8; 32])),
+        ),
+    );
+    let policy = TxSelectionPolicy {
+        include_list: vec![recipient.clone()].into_iter().collect(),
+        ..TxSelectionPolicy::default()
+    };
+    assert!(policy
+        .check_tx(
+            &poison_tx,
+            &ExecutionCost::zero(),
+            &block_limit,
+            &no_costs,
+            None
+        )
+        .is_none());
+}
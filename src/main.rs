@@ -45,6 +45,7 @@ use rusqlite::types::ToSql;
 use rusqlite::Connection;
 use rusqlite::OpenFlags;
 
+use blockstack_lib::burnchains::affirmation::SortitionForkHeaderReader;
 use blockstack_lib::burnchains::bitcoin::indexer::BitcoinIndexer;
 use blockstack_lib::burnchains::bitcoin::indexer::{BitcoinIndexerConfig, BitcoinIndexerRuntime};
 use blockstack_lib::burnchains::bitcoin::spv;
@@ -54,6 +55,10 @@ use blockstack_lib::burnchains::Address;
 use blockstack_lib::burnchains::Burnchain;
 use blockstack_lib::burnchains::Txid;
 use blockstack_lib::chainstate::burn::ConsensusHash;
+use blockstack_lib::chainstate::coordinator::{
+    static_get_canonical_affirmation_map, static_get_heaviest_affirmation_map,
+    static_get_stacks_tip_affirmation_map,
+};
 use blockstack_lib::chainstate::stacks::db::blocks::DummyEventDispatcher;
 use blockstack_lib::chainstate::stacks::db::blocks::StagingBlock;
 use blockstack_lib::chainstate::stacks::db::ChainStateBootData;
@@ -65,6 +70,7 @@ use blockstack_lib::chainstate::stacks::miner::*;
 use blockstack_lib::chainstate::stacks::StacksBlockHeader;
 use blockstack_lib::chainstate::stacks::*;
 use blockstack_lib::clarity::vm::costs::ExecutionCost;
+use blockstack_lib::clarity::vm::database::SqliteConnection;
 use blockstack_lib::clarity::vm::types::StacksAddressExtensions;
 use blockstack_lib::clarity::vm::ClarityVersion;
 use blockstack_lib::clarity_cli::vm_execute;
@@ -73,6 +79,7 @@ use blockstack_lib::core::*;
 use blockstack_lib::cost_estimates::metrics::UnitMetric;
 use blockstack_lib::net::relay::Relayer;
 use blockstack_lib::net::{db::LocalPeer, p2p::PeerNetwork, PeerAddress};
+use blockstack_lib::net::{RPCAffirmationMaps, RPCAffirmationMapsInfo};
 use blockstack_lib::types::chainstate::StacksAddress;
 use blockstack_lib::types::chainstate::{
     BlockHeaderHash, BurnchainHeaderHash, PoxId, StacksBlockId,
@@ -700,6 +707,107 @@ check if the associated microblocks can be downloaded
         process::exit(0);
     }
 
+    if argv[1] == "get-affirmation-maps" || argv[1] == "get-anchor-block-candidates" {
+        let usage = if argv[1] == "get-affirmation-maps" {
+            "<working-dir> [reward-cycle]
+
+Given a <working-dir>, print the heaviest, canonical, stacks-tip and sortition-tip affirmation maps,
+where each pair of maps diverges, and a per-reward-cycle timeline of what each map affirms.  If
+[reward-cycle] is given, only that reward cycle is shown in the timeline.
+"
+        } else {
+            "<working-dir> <reward-cycle>
+
+Given a <working-dir>, list every block-commit confirmed by <reward-cycle>'s prepare phase that
+could have been its anchor block, with its confirmations, burn, and whether it was selected.
+"
+        };
+        if argv.len() < 3 || (argv[1] == "get-anchor-block-candidates" && argv.len() < 4) {
+            eprintln!("Usage: {} {} {}", argv[0], &argv[1], usage);
+            process::exit(1);
+        }
+
+        let burnchain_path = format!("{}/mainnet/burnchain", &argv[2]);
+        let sort_db_path = format!("{}/mainnet/burnchain/sortition", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+        let reward_cycle_opt: Option<u64> = argv
+            .get(3)
+            .map(|x| x.parse().expect("Failed to parse <reward-cycle> argument"));
+
+        let burnchain = Burnchain::new(&burnchain_path, "bitcoin", "mainnet")
+            .expect("Failed to instantiate burnchain");
+        let burnchain_db = burnchain
+            .open_burnchain_db(false)
+            .expect("Failed to open burnchain DB");
+        let sort_db = SortitionDB::open(&sort_db_path, false, burnchain.pox_constants.clone())
+            .unwrap_or_else(|_| panic!("Failed to open {}", &sort_db_path));
+        let (chain_state, _) =
+            StacksChainState::open(true, CHAIN_ID_MAINNET, &chain_state_path, None)
+                .expect("Failed to open stacks chain state");
+
+        let sort_tip = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn())
+            .expect("Failed to get sortition chain tip");
+        let (canonical_ch, canonical_bhh) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sort_db.conn())
+                .expect("Failed to get canonical Stacks chain tip");
+        let tip_reward_cycle = burnchain
+            .block_height_to_reward_cycle(sort_tip.block_height)
+            .unwrap_or(0);
+        let indexer = SortitionForkHeaderReader::new(&burnchain_db, &sort_db, sort_tip.clone());
+
+        let affirmation_maps = RPCAffirmationMaps {
+            heaviest: static_get_heaviest_affirmation_map(
+                &burnchain,
+                &indexer,
+                &burnchain_db,
+                &sort_db,
+                &sort_tip.sortition_id,
+            )
+            .expect("Failed to compute heaviest affirmation map"),
+            canonical: static_get_canonical_affirmation_map(
+                &burnchain,
+                &indexer,
+                &burnchain_db,
+                &sort_db,
+                &chain_state,
+                &sort_tip.sortition_id,
+            )
+            .expect("Failed to compute canonical affirmation map"),
+            stacks_tip: static_get_stacks_tip_affirmation_map(
+                &burnchain_db,
+                &sort_db,
+                &sort_tip.sortition_id,
+                &canonical_ch,
+                &canonical_bhh,
+            )
+            .expect("Failed to compute stacks tip affirmation map"),
+            sortition_tip: sort_db
+                .find_sortition_tip_affirmation_map(&sort_tip.sortition_id)
+                .expect("Failed to load sortition tip affirmation map"),
+        };
+
+        let (first_reward_cycle, last_reward_cycle) = match reward_cycle_opt {
+            Some(reward_cycle) => (reward_cycle, reward_cycle),
+            None => (0, tip_reward_cycle),
+        };
+        let info = RPCAffirmationMapsInfo::from_db(
+            &sort_db,
+            &burnchain_db,
+            &burnchain,
+            affirmation_maps,
+            first_reward_cycle,
+            last_reward_cycle,
+        )
+        .expect("Failed to load affirmation maps");
+
+        if argv[1] == "get-affirmation-maps" {
+            print_affirmation_maps(&info);
+        } else {
+            print_anchor_block_candidates(&info);
+        }
+        process::exit(0);
+    }
+
     if argv[1] == "try-mine" {
         if argv.len() < 3 {
             eprintln!(
@@ -954,6 +1062,41 @@ simulating a miner.
         return;
     }
 
+    if argv[1] == "migrate-clarity-metadata" {
+        if argv.len() < 3 {
+            eprintln!(
+                "Usage: {} migrate-clarity-metadata CHAINSTATE_DIR",
+                &argv[0]
+            );
+            eprintln!();
+            eprintln!("Rewrites the Clarity contract metadata in CHAINSTATE_DIR that is still stored as JSON");
+            eprintln!("in the compact binary encoding. The node must not be running.");
+            process::exit(1);
+        }
+        let side_store_path =
+            StacksChainState::vm_state_index_marf_path(std::path::PathBuf::from(&argv[2]));
+        let side_store_path = side_store_path
+            .to_str()
+            .expect("FATAL: non-UTF-8 chainstate path");
+        let mut conn = SqliteConnection::open(side_store_path).unwrap_or_else(|e| {
+            eprintln!("Failed to open {}: {:?}", side_store_path, &e);
+            process::exit(1);
+        });
+        let migrated =
+            SqliteConnection::migrate_metadata_to_binary(&mut conn).unwrap_or_else(|e| {
+                eprintln!(
+                    "Failed to migrate metadata in {}: {:?}",
+                    side_store_path, &e
+                );
+                process::exit(1);
+            });
+        println!(
+            "Rewrote {} metadata entries in {}. Run `VACUUM` on it to reclaim the freed space.",
+            migrated, side_store_path
+        );
+        return;
+    }
+
     if argv[1] == "get-ancestors" {
         let path = &argv[2];
         let tip = BlockHeaderHash::from_hex(&argv[3]).unwrap();
@@ -1324,6 +1467,91 @@ simulating a miner.
     }
 }
 
+/// Print a node's affirmation maps, where they diverge, and a per-reward-cycle timeline
+fn print_affirmation_maps(info: &RPCAffirmationMapsInfo) {
+    println!("Sortition tip is in reward cycle {}", info.reward_cycle);
+    println!("heaviest:      {}", &info.affirmation_maps.heaviest);
+    println!("canonical:     {}", &info.affirmation_maps.canonical);
+    println!("stacks_tip:    {}", &info.affirmation_maps.stacks_tip);
+    println!("sortition_tip: {}", &info.affirmation_maps.sortition_tip);
+    println!();
+
+    if info.divergences.is_empty() {
+        println!("All affirmation maps agree");
+    }
+    for divergence in info.divergences.iter() {
+        println!(
+            "{} and {} diverge at reward cycle {}",
+            &divergence.left, &divergence.right, divergence.reward_cycle
+        );
+    }
+    println!();
+
+    println!("Reward cycle, heaviest, canonical, stacks_tip, sortition_tip, candidates, selected anchor block, notes");
+    for reward_cycle in info.reward_cycles.iter() {
+        let selected = reward_cycle
+            .anchor_block_candidates
+            .iter()
+            .find(|candidate| candidate.selected)
+            .map(|candidate| {
+                format!(
+                    "{},{} ({} confs)",
+                    candidate.burn_block_height, candidate.vtxindex, candidate.confirmations
+                )
+            })
+            .unwrap_or("-".to_string());
+        let notes: Vec<String> = info
+            .divergences
+            .iter()
+            .filter(|divergence| divergence.reward_cycle == reward_cycle.reward_cycle)
+            .map(|divergence| format!("{}/{} diverge", &divergence.left, &divergence.right))
+            .collect();
+        let entry = |entry_opt: &Option<String>| entry_opt.clone().unwrap_or("-".to_string());
+        println!(
+            "{}, {}, {}, {}, {}, {}, {}, {}",
+            reward_cycle.reward_cycle,
+            entry(&reward_cycle.heaviest),
+            entry(&reward_cycle.canonical),
+            entry(&reward_cycle.stacks_tip),
+            entry(&reward_cycle.sortition_tip),
+            reward_cycle.anchor_block_candidates.len(),
+            selected,
+            notes.join("; ")
+        );
+    }
+}
+
+/// Print the anchor block candidates for each reward cycle's prepare phase
+fn print_anchor_block_candidates(info: &RPCAffirmationMapsInfo) {
+    println!(
+        "Anchor threshold is {} confirmations",
+        info.anchor_threshold
+    );
+    println!("Reward cycle, burn height, vtxindex, txid, block hash, confirmations, burnt, meets threshold, selected");
+    for reward_cycle in info.reward_cycles.iter() {
+        for candidate in reward_cycle.anchor_block_candidates.iter() {
+            println!(
+                "{}, {}, {}, {}, {}, {}, {}, {}, {}",
+                reward_cycle.reward_cycle,
+                candidate.burn_block_height,
+                candidate.vtxindex,
+                candidate
+                    .txid
+                    .map(|txid| txid.to_string())
+                    .unwrap_or("-".to_string()),
+                candidate
+                    .block_header_hash
+                    .map(|bhh| bhh.to_string())
+                    .unwrap_or("-".to_string()),
+                candidate.confirmations,
+                candidate.burnt,
+                candidate.confirmations >= u64::from(info.anchor_threshold),
+                candidate.selected
+            );
+        }
+    }
+}
+
 fn tip_mine() {
     let argv: Vec<String> = env::args().collect();
     if argv.len() < 6 {
@@ -29,6 +29,7 @@ use crate::{
     util_lib::db::{tx_busy_handler, DBConn},
 };
 use clarity::vm::costs::ExecutionCost;
+use clarity::vm::database::ContractCacheStats;
 use stacks_common::util::uint::{Uint256, Uint512};
 use std::convert::TryInto;
 use std::error::Error;
@@ -125,6 +126,20 @@ pub fn set_last_execution_cost_observed(
     }
 }
 
+/// Log the state of the chainstate's parsed contract cache.
+#[allow(unused_variables)]
+pub fn update_contract_cache_stats(stats: &ContractCacheStats) {
+    #[cfg(feature = "monitoring_prom")]
+    {
+        let to_gauge = |value: u64| i64::try_from(value).unwrap_or(i64::MAX);
+        prometheus::CONTRACT_CACHE_HITS.set(to_gauge(stats.hits));
+        prometheus::CONTRACT_CACHE_MISSES.set(to_gauge(stats.misses));
+        prometheus::CONTRACT_CACHE_EVICTIONS.set(to_gauge(stats.evictions));
+        prometheus::CONTRACT_CACHE_ENTRIES.set(to_gauge(stats.entries));
+        prometheus::CONTRACT_CACHE_SIZE.set(to_gauge(stats.size));
+    }
+}
+
 /// Log the number of transactions in the latest block.
 #[allow(unused_variables)]
 pub fn set_last_block_transaction_count(transactions_in_block: u64) {
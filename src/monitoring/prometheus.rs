@@ -220,6 +220,31 @@ lazy_static! {
         "stacks_node_miner_current_median_commitment_low",
         "Low 64 bits of a miner's median commitment over the mining commitment window."
     )).unwrap();
+
+    pub static ref CONTRACT_CACHE_HITS: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_contract_cache_hits",
+        "Number of contract loads served from the parsed contract cache"
+    )).unwrap();
+
+    pub static ref CONTRACT_CACHE_MISSES: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_contract_cache_misses",
+        "Number of contract loads that had to deserialize the contract's metadata"
+    )).unwrap();
+
+    pub static ref CONTRACT_CACHE_EVICTIONS: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_contract_cache_evictions",
+        "Number of contracts evicted from the parsed contract cache"
+    )).unwrap();
+
+    pub static ref CONTRACT_CACHE_ENTRIES: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_contract_cache_entries",
+        "Number of contracts in the parsed contract cache"
+    )).unwrap();
+
+    pub static ref CONTRACT_CACHE_SIZE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_contract_cache_size",
+        "Total serialized size (bytes) of the contracts in the parsed contract cache"
+    )).unwrap();
 }
 
 pub fn new_rpc_call_timer(path: &str) -> HistogramTimer {
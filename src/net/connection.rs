@@ -380,6 +380,18 @@ pub struct ConnectionOptions {
     pub mempool_max_tx_query: u64,
     /// how long a mempool sync is allowed to take, in total, before timing out
     pub mempool_sync_timeout: u64,
+    /// how many requests per minute a client IP address may make to the RPC server's query,
+    /// stream, read-only call, and submission endpoints (0 means unlimited)
+    pub rpc_query_rate_limit: u64,
+    pub rpc_stream_rate_limit: u64,
+    pub rpc_read_only_call_rate_limit: u64,
+    pub rpc_submit_rate_limit: u64,
+    /// how much execution cost a client IP address may spend on read-only calls per
+    /// `read_only_call_budget_interval`
+    pub read_only_call_budget: ExecutionCost,
+    /// how long a read-only call budget lasts before it is replenished, in seconds (0 means
+    /// there is no budget)
+    pub read_only_call_budget_interval: u64,
 
     // fault injection
     pub disable_neighbor_walk: bool,
@@ -466,6 +478,18 @@ impl std::default::Default for ConnectionOptions {
             mempool_sync_interval: 30, // number of seconds in-between mempool sync
             mempool_max_tx_query: 128, // maximum number of transactions to visit per mempool query
             mempool_sync_timeout: 180, // how long a mempool sync can go for (3 minutes)
+            rpc_query_rate_limit: 0,   // no RPC rate limits by default
+            rpc_stream_rate_limit: 0,
+            rpc_read_only_call_rate_limit: 0,
+            rpc_submit_rate_limit: 0,
+            read_only_call_budget: ExecutionCost {
+                write_length: 0,
+                write_count: 0,
+                read_length: 10_000_000,
+                read_count: 3_000,
+                runtime: 100_000_000_000,
+            },
+            read_only_call_budget_interval: 0, // no read-only call budget by default
 
             // no faults on by default
             disable_neighbor_walk: false,
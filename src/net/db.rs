@@ -956,6 +956,31 @@ impl PeerDB {
         Ok(())
     }
 
+    /// Add an initial peer at runtime, inserting it if it is not present.  Like the initial
+    /// peers given when the DB is opened, it stays an initial peer until the DB is next opened.
+    pub fn add_initial_peer(tx: &mut Transaction<'_>, neighbor: &Neighbor) -> Result<(), db_error> {
+        if !PeerDB::try_insert_peer(tx, neighbor)? {
+            let mut slots = PeerDB::peer_slots(
+                tx,
+                neighbor.addr.network_id,
+                &neighbor.addr.addrbytes,
+                neighbor.addr.port,
+            )?;
+            let slot = slots.pop().expect("BUG: no slots");
+            warn!(
+                "Forcing replacement of peer at slot {} for initial peer {:?}",
+                slot, &neighbor.addr
+            );
+            PeerDB::insert_or_replace_peer(tx, neighbor, slot)?;
+        }
+        PeerDB::set_initial_peer(
+            tx,
+            neighbor.addr.network_id,
+            &neighbor.addr.addrbytes,
+            neighbor.addr.port,
+        )
+    }
+
     /// clear all initial peers
     fn clear_initial_peers<'a>(tx: &mut Transaction<'a>) -> Result<(), db_error> {
         tx.execute("UPDATE frontier SET initial = 0", NO_PARAMS)
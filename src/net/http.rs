@@ -39,11 +39,15 @@ use url::{form_urlencoded, Url};
 
 use crate::burnchains::{Address, Txid};
 use crate::chainstate::burn::ConsensusHash;
+use crate::chainstate::stacks::db::AccountHistoryCursor;
 use crate::chainstate::stacks::{
     StacksBlock, StacksMicroblock, StacksPublicKey, StacksTransaction,
 };
 use crate::deps::httparse;
 use crate::net::atlas::Attachment;
+use crate::net::rpc::{ACCOUNT_HISTORY_DEFAULT_LIMIT, ACCOUNT_HISTORY_MAX_LIMIT};
+use crate::net::v3::{V3Endpoint, V3Operation};
+use crate::net::BurnOpsQuery;
 use crate::net::ClientError;
 use crate::net::Error as net_error;
 use crate::net::Error::ClarityError;
@@ -62,17 +66,24 @@ use crate::net::NeighborAddress;
 use crate::net::PeerAddress;
 use crate::net::PeerHost;
 use crate::net::ProtocolFamily;
+use crate::net::SortitionQuery;
 use crate::net::StacksHttpMessage;
 use crate::net::StacksHttpPreamble;
 use crate::net::UnconfirmedTransactionResponse;
 use crate::net::UnconfirmedTransactionStatus;
+use crate::net::DENY_BAN_DURATION;
 use crate::net::HTTP_PREAMBLE_MAX_ENCODED_SIZE;
 use crate::net::HTTP_PREAMBLE_MAX_NUM_HEADERS;
 use crate::net::HTTP_REQUEST_ID_RESERVED;
 use crate::net::MAX_HEADERS;
 use crate::net::MAX_MICROBLOCKS_UNCONFIRMED;
+use crate::net::{
+    AdminBanPeerRequestBody, AdminBootstrapPeerRequestBody, AdminDropMempoolTxsRequestBody,
+    AdminRequest, AdminSetMiningRequestBody, AdminToken,
+};
 use crate::net::{CallReadOnlyRequestBody, TipRequest};
 use crate::net::{GetAttachmentResponse, GetAttachmentsInvResponse, PostTransactionRequestBody};
+use crate::net::{RPCErrorBody, RPCErrorCode, V3Request, V3Response};
 use clarity::vm::ast::parser::v1::CLARITY_NAME_REGEX;
 use clarity::vm::types::{StandardPrincipalData, TraitIdentifier};
 use clarity::vm::{
@@ -95,7 +106,9 @@ use crate::codec::{
     read_next, write_next, Error as codec_error, StacksMessageCodec, MAX_MESSAGE_LEN,
     MAX_PAYLOAD_LEN,
 };
-use crate::types::chainstate::{BlockHeaderHash, StacksAddress, StacksBlockId};
+use crate::types::chainstate::{
+    BlockHeaderHash, BurnchainHeaderHash, StacksAddress, StacksBlockId,
+};
 
 use super::FeeRateEstimateRequestBody;
 
@@ -104,6 +117,8 @@ lazy_static! {
     static ref PATH_GETPOXINFO: Regex = Regex::new(r#"^/v2/pox$"#).unwrap();
     static ref PATH_GETNEIGHBORS: Regex = Regex::new(r#"^/v2/neighbors$"#).unwrap();
     static ref PATH_GETHEADERS: Regex = Regex::new(r#"^/v2/headers/([0-9]+)$"#).unwrap();
+    static ref PATH_GET_LIGHT_HEADERS: Regex =
+        Regex::new(r#"^/v2/headers/light/([0-9]+)$"#).unwrap();
     static ref PATH_GETBLOCK: Regex = Regex::new(r#"^/v2/blocks/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_GETMICROBLOCKS_INDEXED: Regex =
         Regex::new(r#"^/v2/microblocks/([0-9a-f]{64})$"#).unwrap();
@@ -122,6 +137,11 @@ lazy_static! {
         *PRINCIPAL_DATA_REGEX_STRING
     ))
     .unwrap();
+    static ref PATH_GET_ACCOUNT_HISTORY: Regex = Regex::new(&format!(
+        "^/v2/accounts/(?P<principal>{})/history$",
+        *PRINCIPAL_DATA_REGEX_STRING
+    ))
+    .unwrap();
     static ref PATH_GET_DATA_VAR: Regex = Regex::new(&format!(
         "^/v2/data_var/(?P<address>{})/(?P<contract>{})/(?P<varname>{})$",
         *STANDARD_PRINCIPAL_REGEX_STRING, *CONTRACT_NAME_REGEX_STRING, *CLARITY_NAME_REGEX
@@ -158,7 +178,26 @@ lazy_static! {
         Regex::new(r#"^/v2/attachments/([0-9a-f]{40})$"#).unwrap();
     static ref PATH_POST_MEMPOOL_QUERY: Regex =
         Regex::new(r#"^/v2/mempool/query$"#).unwrap();
-    static ref PATH_OPTIONS_WILDCARD: Regex = Regex::new("^/v2/.{0,4096}$").unwrap();
+    static ref PATH_GET_REWARD_CYCLE: Regex =
+        Regex::new(r#"^/v2/pox/reward_cycles/([0-9]{1,20})$"#).unwrap();
+    static ref PATH_GET_SORTITION: Regex = Regex::new(
+        r#"^/v2/sortitions(/(?P<key>burn_height|consensus|burn)/(?P<value>[0-9a-f]{1,64}))?$"#
+    )
+    .unwrap();
+    static ref PATH_GET_BURN_OPS: Regex = Regex::new(
+        r#"^/v2/burn_ops/(?P<key>txid|sender)/(?P<value>[0-9a-zA-Z]{1,64})$"#
+    )
+    .unwrap();
+    static ref PATH_GET_AFFIRMATION_MAPS: Regex =
+        Regex::new(r#"^/v2/pox/affirmations(/(?P<cycle>[0-9]{1,20}))?$"#).unwrap();
+    static ref PATH_OPTIONS_WILDCARD: Regex = Regex::new("^/v[23]/.{0,4096}$").unwrap();
+    static ref PATH_ADMIN_BAN_PEER: Regex = Regex::new("^/admin/peers/ban$").unwrap();
+    static ref PATH_ADMIN_BOOTSTRAP_PEER: Regex =
+        Regex::new("^/admin/peers/bootstrap$").unwrap();
+    static ref PATH_ADMIN_DROP_MEMPOOL_TXS: Regex =
+        Regex::new("^/admin/mempool/drop$").unwrap();
+    static ref PATH_ADMIN_MINING: Regex = Regex::new("^/admin/mining$").unwrap();
+    static ref PATH_ADMIN_RELOAD_CONFIG: Regex = Regex::new("^/admin/config/reload$").unwrap();
 }
 
 /// HTTP headers that we really care about
@@ -1505,6 +1544,11 @@ impl HttpRequestType {
                 &HttpRequestType::parse_getneighbors,
             ),
             ("GET", &PATH_GETHEADERS, &HttpRequestType::parse_getheaders),
+            (
+                "GET",
+                &PATH_GET_LIGHT_HEADERS,
+                &HttpRequestType::parse_get_light_headers,
+            ),
             ("GET", &PATH_GETBLOCK, &HttpRequestType::parse_getblock),
             (
                 "GET",
@@ -1602,6 +1646,61 @@ impl HttpRequestType {
                 &PATH_POST_MEMPOOL_QUERY,
                 &HttpRequestType::parse_post_mempool_query,
             ),
+            (
+                "GET",
+                &PATH_GET_SORTITION,
+                &HttpRequestType::parse_get_sortition,
+            ),
+            (
+                "GET",
+                &PATH_GET_REWARD_CYCLE,
+                &HttpRequestType::parse_get_reward_cycle,
+            ),
+            (
+                "GET",
+                &PATH_GET_BURN_OPS,
+                &HttpRequestType::parse_get_burn_ops,
+            ),
+            (
+                "GET",
+                &PATH_GET_AFFIRMATION_MAPS,
+                &HttpRequestType::parse_get_affirmation_maps,
+            ),
+            (
+                "GET",
+                &PATH_GET_ACCOUNT_HISTORY,
+                &HttpRequestType::parse_get_account_history,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_BAN_PEER,
+                &HttpRequestType::parse_admin_ban_peer,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_BOOTSTRAP_PEER,
+                &HttpRequestType::parse_admin_bootstrap_peer,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_DROP_MEMPOOL_TXS,
+                &HttpRequestType::parse_admin_drop_mempool_txs,
+            ),
+            (
+                "GET",
+                &PATH_ADMIN_MINING,
+                &HttpRequestType::parse_admin_get_mining,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_MINING,
+                &HttpRequestType::parse_admin_set_mining,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_RELOAD_CONFIG,
+                &HttpRequestType::parse_admin_reload_config,
+            ),
         ];
 
         // use url::Url to parse path and query string
@@ -1617,6 +1716,12 @@ impl HttpRequestType {
             )
         })?;
 
+        // /v3 paths are matched against the /v3 route table instead
+        if decoded_path.starts_with("/v3/") && preamble.verb != "OPTIONS" {
+            info!("Handle HTTPRequest"; "verb" => %preamble.verb, "peer_addr" => %protocol.peer_addr, "path" => %decoded_path, "query" => %url.query().map(|q| format!("?{}", q)).unwrap_or_default());
+            return HttpRequestType::parse_v3(preamble, &decoded_path, url.query(), fd);
+        }
+
         for (verb, regex, parser) in REQUEST_METHODS.iter() {
             match HttpRequestType::try_parse(
                 protocol,
@@ -2088,6 +2193,31 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_get_light_headers<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetLightHeaders".to_string(),
+            ));
+        }
+
+        let quantity: u64 = captures[1].parse().map_err(|_| {
+            net_error::ClientError(ClientError::Message("Failed to parse quantity".to_string()))
+        })?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetLightHeaders(
+            HttpRequestMetadata::from_preamble(preamble),
+            quantity,
+            tip,
+        ))
+    }
+
     fn parse_getblock<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2664,128 +2794,624 @@ impl HttpRequestType {
         ))
     }
 
-    fn parse_options_preflight<R: Read>(
+    fn parse_get_sortition<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
-        _regex: &Captures,
+        captures: &Captures,
         _query: Option<&str>,
         _fd: &mut R,
     ) -> Result<HttpRequestType, net_error> {
-        Ok(HttpRequestType::OptionsPreflight(
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let (key, value) = match (captures.name("key"), captures.name("value")) {
+            (Some(key), Some(value)) => (key.as_str(), value.as_str()),
+            _ => {
+                return Ok(HttpRequestType::GetSortition(
+                    HttpRequestMetadata::from_preamble(preamble),
+                    SortitionQuery::LatestSortition,
+                ));
+            }
+        };
+
+        let sortition_query = match key {
+            "burn_height" => {
+                let height = value.parse::<u64>().map_err(|_| {
+                    net_error::ClientError(ClientError::Message(
+                        "Failed to parse burn block height".to_string(),
+                    ))
+                })?;
+                SortitionQuery::BurnHeight(height)
+            }
+            "consensus" => {
+                let consensus_hash = ConsensusHash::from_hex(value).map_err(|_| {
+                    net_error::ClientError(ClientError::Message(
+                        "Failed to parse consensus hash".to_string(),
+                    ))
+                })?;
+                SortitionQuery::ConsensusHash(consensus_hash)
+            }
+            "burn" => {
+                let burn_header_hash = BurnchainHeaderHash::from_hex(value).map_err(|_| {
+                    net_error::ClientError(ClientError::Message(
+                        "Failed to parse burn header hash".to_string(),
+                    ))
+                })?;
+                SortitionQuery::BurnHeaderHash(burn_header_hash)
+            }
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Unknown sortition lookup key".to_string(),
+                ));
+            }
+        };
+
+        Ok(HttpRequestType::GetSortition(
             HttpRequestMetadata::from_preamble(preamble),
-            preamble.path.to_string(),
+            sortition_query,
         ))
     }
 
-    pub fn metadata(&self) -> &HttpRequestMetadata {
-        match *self {
-            HttpRequestType::GetInfo(ref md) => md,
-            HttpRequestType::GetPoxInfo(ref md, ..) => md,
-            HttpRequestType::GetNeighbors(ref md) => md,
-            HttpRequestType::GetHeaders(ref md, ..) => md,
-            HttpRequestType::GetBlock(ref md, _) => md,
-            HttpRequestType::GetMicroblocksIndexed(ref md, _) => md,
-            HttpRequestType::GetMicroblocksConfirmed(ref md, _) => md,
-            HttpRequestType::GetMicroblocksUnconfirmed(ref md, _, _) => md,
-            HttpRequestType::GetTransactionUnconfirmed(ref md, _) => md,
-            HttpRequestType::PostTransaction(ref md, _, _) => md,
-            HttpRequestType::PostBlock(ref md, ..) => md,
-            HttpRequestType::PostMicroblock(ref md, ..) => md,
-            HttpRequestType::GetAccount(ref md, ..) => md,
-            HttpRequestType::GetDataVar(ref md, ..) => md,
-            HttpRequestType::GetMapEntry(ref md, ..) => md,
-            HttpRequestType::GetTransferCost(ref md) => md,
-            HttpRequestType::GetContractABI(ref md, ..) => md,
-            HttpRequestType::GetContractSrc(ref md, ..) => md,
-            HttpRequestType::GetIsTraitImplemented(ref md, ..) => md,
-            HttpRequestType::CallReadOnlyFunction(ref md, ..) => md,
-            HttpRequestType::OptionsPreflight(ref md, ..) => md,
-            HttpRequestType::GetAttachmentsInv(ref md, ..) => md,
-            HttpRequestType::GetAttachment(ref md, ..) => md,
-            HttpRequestType::MemPoolQuery(ref md, ..) => md,
-            HttpRequestType::FeeRateEstimate(ref md, _, _) => md,
-            HttpRequestType::ClientError(ref md, ..) => md,
+    fn parse_get_reward_cycle<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetRewardCycle".to_string(),
+            ));
         }
+
+        let reward_cycle = captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to reward cycle group".to_string(),
+            ))?
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_| {
+                net_error::ClientError(ClientError::Message(
+                    "Failed to parse reward cycle".to_string(),
+                ))
+            })?;
+
+        Ok(HttpRequestType::GetRewardCycle(
+            HttpRequestMetadata::from_preamble(preamble),
+            reward_cycle,
+        ))
     }
 
-    pub fn metadata_mut(&mut self) -> &mut HttpRequestMetadata {
-        match *self {
-            HttpRequestType::GetInfo(ref mut md) => md,
-            HttpRequestType::GetPoxInfo(ref mut md, ..) => md,
-            HttpRequestType::GetNeighbors(ref mut md) => md,
-            HttpRequestType::GetHeaders(ref mut md, ..) => md,
-            HttpRequestType::GetBlock(ref mut md, _) => md,
-            HttpRequestType::GetMicroblocksIndexed(ref mut md, _) => md,
-            HttpRequestType::GetMicroblocksConfirmed(ref mut md, _) => md,
-            HttpRequestType::GetMicroblocksUnconfirmed(ref mut md, _, _) => md,
-            HttpRequestType::GetTransactionUnconfirmed(ref mut md, _) => md,
-            HttpRequestType::PostTransaction(ref mut md, _, _) => md,
-            HttpRequestType::PostBlock(ref mut md, ..) => md,
-            HttpRequestType::PostMicroblock(ref mut md, ..) => md,
-            HttpRequestType::GetAccount(ref mut md, ..) => md,
-            HttpRequestType::GetDataVar(ref mut md, ..) => md,
-            HttpRequestType::GetMapEntry(ref mut md, ..) => md,
-            HttpRequestType::GetTransferCost(ref mut md) => md,
-            HttpRequestType::GetContractABI(ref mut md, ..) => md,
-            HttpRequestType::GetContractSrc(ref mut md, ..) => md,
-            HttpRequestType::GetIsTraitImplemented(ref mut md, ..) => md,
-            HttpRequestType::CallReadOnlyFunction(ref mut md, ..) => md,
-            HttpRequestType::OptionsPreflight(ref mut md, ..) => md,
-            HttpRequestType::GetAttachmentsInv(ref mut md, ..) => md,
-            HttpRequestType::GetAttachment(ref mut md, ..) => md,
-            HttpRequestType::MemPoolQuery(ref mut md, ..) => md,
-            HttpRequestType::FeeRateEstimate(ref mut md, _, _) => md,
-            HttpRequestType::ClientError(ref mut md, ..) => md,
+    fn parse_get_affirmation_maps<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetAffirmationMaps".to_string(),
+            ));
         }
+
+        let reward_cycle = match captures.name("cycle") {
+            Some(cycle) => Some(cycle.as_str().parse::<u64>().map_err(|_| {
+                net_error::ClientError(ClientError::Message(
+                    "Failed to parse reward cycle".to_string(),
+                ))
+            })?),
+            None => None,
+        };
+
+        Ok(HttpRequestType::GetAffirmationMaps(
+            HttpRequestMetadata::from_preamble(preamble),
+            reward_cycle,
+        ))
     }
 
-    fn make_tip_query_string(tip_req: &TipRequest, with_proof: bool) -> String {
-        match tip_req {
-            TipRequest::UseLatestUnconfirmedTip => {
-                format!("?tip=latest{}", if with_proof { "" } else { "&proof=0" })
-            }
-            TipRequest::SpecificTip(tip) => {
-                format!("?tip={}{}", tip, if with_proof { "" } else { "&proof=0" })
-            }
-            TipRequest::UseLatestAnchoredTip => {
-                if !with_proof {
-                    format!("?proof=0")
-                } else {
-                    "".to_string()
+    fn parse_get_account_history<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetAccountHistory".to_string(),
+            ));
+        }
+
+        let principal = PrincipalData::parse(&captures["principal"]).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse account principal".into())
+        })?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        let mut cursor = None;
+        let mut limit = ACCOUNT_HISTORY_DEFAULT_LIMIT;
+        if let Some(query_string) = query {
+            for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+                match &*key {
+                    "cursor" => {
+                        cursor = Some(AccountHistoryCursor::parse(&value).ok_or_else(|| {
+                            net_error::ClientError(ClientError::Message(
+                                "Failed to parse cursor".to_string(),
+                            ))
+                        })?);
+                    }
+                    "limit" => {
+                        limit = value.parse::<u64>().map_err(|_| {
+                            net_error::ClientError(ClientError::Message(
+                                "Failed to parse limit".to_string(),
+                            ))
+                        })?;
+                    }
+                    _ => {}
                 }
             }
         }
+        if limit == 0 || limit > ACCOUNT_HISTORY_MAX_LIMIT {
+            return Err(net_error::ClientError(ClientError::Message(format!(
+                "limit must be between 1 and {}",
+                ACCOUNT_HISTORY_MAX_LIMIT
+            ))));
+        }
+
+        Ok(HttpRequestType::GetAccountHistory(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+            tip,
+            cursor,
+            limit,
+        ))
     }
 
-    pub fn request_path(&self) -> String {
-        match self {
-            HttpRequestType::GetInfo(_md) => "/v2/info".to_string(),
-            HttpRequestType::GetPoxInfo(_md, tip_req) => format!(
-                "/v2/pox{}",
-                HttpRequestType::make_tip_query_string(tip_req, true)
-            ),
-            HttpRequestType::GetNeighbors(_md) => "/v2/neighbors".to_string(),
-            HttpRequestType::GetHeaders(_md, quantity, tip_req) => format!(
-                "/v2/headers/{}{}",
-                quantity,
-                HttpRequestType::make_tip_query_string(tip_req, true)
-            ),
-            HttpRequestType::GetBlock(_md, block_hash) => {
-                format!("/v2/blocks/{}", block_hash.to_hex())
-            }
-            HttpRequestType::GetMicroblocksIndexed(_md, block_hash) => {
-                format!("/v2/microblocks/{}", block_hash.to_hex())
-            }
-            HttpRequestType::GetMicroblocksConfirmed(_md, block_hash) => {
-                format!("/v2/microblocks/confirmed/{}", block_hash.to_hex())
-            }
-            HttpRequestType::GetMicroblocksUnconfirmed(_md, block_hash, min_seq) => format!(
-                "/v2/microblocks/unconfirmed/{}/{}",
-                block_hash.to_hex(),
-                min_seq
-            ),
-            HttpRequestType::GetTransactionUnconfirmed(_md, txid) => {
-                format!("/v2/transactions/unconfirmed/{}", txid)
+    /// Get the bearer token in an admin request's Authorization header, if there is one
+    fn parse_admin_token(preamble: &HttpRequestPreamble) -> Option<AdminToken> {
+        let value = preamble.headers.get("authorization")?;
+        let mut parts = value.splitn(2, ' ');
+        match (parts.next(), parts.next()) {
+            (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => {
+                Some(AdminToken::new(token.trim()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse the JSON body of an admin request
+    fn parse_admin_json_body<R: Read, T: serde::de::DeserializeOwned>(
+        preamble: &HttpRequestPreamble,
+        fd: &mut R,
+    ) -> Result<T, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::ClientError(ClientError::Message(format!(
+                "Invalid body length for admin request ({})",
+                content_len
+            ))));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::ClientError(ClientError::Message(
+                "Invalid content-type: expected application/json".to_string(),
+            )));
+        }
+
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        serde_json::from_reader(bound_fd).map_err(|e| {
+            net_error::ClientError(ClientError::Message(format!(
+                "Failed to parse JSON body: {}",
+                e
+            )))
+        })
+    }
+
+    fn parse_admin_peer_addr(peer: &str) -> Result<SocketAddr, net_error> {
+        peer.parse::<SocketAddr>().map_err(|_| {
+            net_error::ClientError(ClientError::Message(format!(
+                "Failed to parse peer address '{}': expected IP:PORT",
+                peer
+            )))
+        })
+    }
+
+    fn parse_admin_ban_peer<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let body: AdminBanPeerRequestBody = HttpRequestType::parse_admin_json_body(preamble, fd)?;
+        let addr = HttpRequestType::parse_admin_peer_addr(&body.peer)?;
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::parse_admin_token(preamble),
+            AdminRequest::BanPeer(addr, body.duration.unwrap_or(DENY_BAN_DURATION)),
+        ))
+    }
+
+    fn parse_admin_bootstrap_peer<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let body: AdminBootstrapPeerRequestBody =
+            HttpRequestType::parse_admin_json_body(preamble, fd)?;
+        let (pubkey_hex, peer) = body.peer.split_once('@').ok_or_else(|| {
+            net_error::ClientError(ClientError::Message(format!(
+                "Invalid bootstrap peer '{}': expected PUBKEY@IP:PORT",
+                &body.peer
+            )))
+        })?;
+        let public_key = StacksPublicKey::from_hex(pubkey_hex).map_err(|_| {
+            net_error::ClientError(ClientError::Message(format!(
+                "Invalid public key '{}'",
+                pubkey_hex
+            )))
+        })?;
+        let addr = HttpRequestType::parse_admin_peer_addr(peer)?;
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::parse_admin_token(preamble),
+            AdminRequest::AddBootstrapPeer(public_key, addr),
+        ))
+    }
+
+    fn parse_admin_drop_mempool_txs<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let body: AdminDropMempoolTxsRequestBody =
+            HttpRequestType::parse_admin_json_body(preamble, fd)?;
+        let txids = body
+            .txids
+            .iter()
+            .map(|txid| {
+                let txid_hex = txid.strip_prefix("0x").unwrap_or(txid);
+                Txid::from_hex(txid_hex).map_err(|_| {
+                    net_error::ClientError(ClientError::Message(format!(
+                        "Failed to parse txid '{}'",
+                        txid
+                    )))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::parse_admin_token(preamble),
+            AdminRequest::DropMempoolTxs(txids),
+        ))
+    }
+
+    fn parse_admin_get_mining<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::parse_admin_token(preamble),
+            AdminRequest::GetMining,
+        ))
+    }
+
+    fn parse_admin_set_mining<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let body: AdminSetMiningRequestBody = HttpRequestType::parse_admin_json_body(preamble, fd)?;
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::parse_admin_token(preamble),
+            AdminRequest::SetMining(body.enabled),
+        ))
+    }
+
+    fn parse_admin_reload_config<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        Ok(HttpRequestType::Admin(
+            HttpRequestMetadata::from_preamble(preamble),
+            HttpRequestType::parse_admin_token(preamble),
+            AdminRequest::ReloadConfig,
+        ))
+    }
+
+    fn parse_get_burn_ops<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let (key, value) = match (captures.name("key"), captures.name("value")) {
+            (Some(key), Some(value)) => (key.as_str(), value.as_str()),
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Failed to match path to burn ops lookup".to_string(),
+                ));
+            }
+        };
+
+        let burn_ops_query = match key {
+            "txid" => {
+                let txid = Txid::from_hex(value).map_err(|_| {
+                    net_error::ClientError(ClientError::Message("Failed to parse txid".to_string()))
+                })?;
+                BurnOpsQuery::Txid(txid)
+            }
+            "sender" => {
+                let sender = StacksAddress::from_string(value).ok_or_else(|| {
+                    net_error::ClientError(ClientError::Message(
+                        "Failed to parse sender address".to_string(),
+                    ))
+                })?;
+                BurnOpsQuery::Sender(sender)
+            }
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Unknown burn ops lookup key".to_string(),
+                ));
+            }
+        };
+
+        Ok(HttpRequestType::GetBurnOps(
+            HttpRequestMetadata::from_preamble(preamble),
+            burn_ops_query,
+        ))
+    }
+
+    /// Parse a /v3 request.  Every failure is reported as a `ClientError::V3`, so that the
+    /// client always gets an `RPCErrorBody` back.
+    fn parse_v3<R: Read>(
+        preamble: &HttpRequestPreamble,
+        path: &str,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        match HttpRequestType::parse_v3_request(preamble, path, query, fd) {
+            Ok(v3_req) => Ok(HttpRequestType::V3(
+                HttpRequestMetadata::from_preamble(preamble),
+                v3_req,
+            )),
+            Err(body) => Err(net_error::ClientError(ClientError::V3(body))),
+        }
+    }
+
+    fn parse_v3_request<R: Read>(
+        preamble: &HttpRequestPreamble,
+        path: &str,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<V3Request, RPCErrorBody> {
+        let (endpoint, captures) = V3Endpoint::find(&preamble.verb, path)?;
+        let params = endpoint.parse_params(captures, query)?;
+
+        let content_len = preamble.get_content_length();
+        if endpoint.request_body.is_none() && content_len != 0 {
+            return Err(RPCErrorBody::new(
+                RPCErrorCode::BadRequest,
+                "Expected an empty request body",
+            ));
+        }
+
+        match endpoint.operation {
+            V3Operation::GetOpenAPI => Ok(V3Request::GetOpenAPI),
+            V3Operation::GetInfo => Ok(V3Request::GetInfo),
+            V3Operation::GetAccount => {
+                let principal = params.principal("principal").cloned().ok_or_else(|| {
+                    RPCErrorBody::server_error("Principal parameter was not validated")
+                })?;
+                Ok(V3Request::GetAccount(
+                    principal,
+                    params.tip("tip"),
+                    params.bool("proof", true),
+                ))
+            }
+            V3Operation::PostTransaction => {
+                if content_len == 0 || content_len > MAX_PAYLOAD_LEN {
+                    return Err(RPCErrorBody::new(
+                        RPCErrorCode::BadRequest,
+                        &format!(
+                            "Expected a transaction body of 1 to {} bytes",
+                            MAX_PAYLOAD_LEN
+                        ),
+                    ));
+                }
+                if preamble.content_type != Some(HttpContentType::Bytes) {
+                    return Err(RPCErrorBody::new(
+                        RPCErrorCode::BadRequest,
+                        "Invalid content-type: expected application/octet-stream",
+                    ));
+                }
+                let mut bound_fd = BoundReader::from_reader(fd, content_len as u64);
+                let tx = StacksTransaction::consensus_deserialize(&mut bound_fd).map_err(|e| {
+                    RPCErrorBody::new(
+                        RPCErrorCode::BadRequest,
+                        "Failed to deserialize posted transaction",
+                    )
+                    .with_details(json!({ "message": e.to_string() }))
+                })?;
+                Ok(V3Request::PostTransaction(tx))
+            }
+        }
+    }
+
+    fn parse_options_preflight<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        Ok(HttpRequestType::OptionsPreflight(
+            HttpRequestMetadata::from_preamble(preamble),
+            preamble.path.to_string(),
+        ))
+    }
+
+    pub fn metadata(&self) -> &HttpRequestMetadata {
+        match *self {
+            HttpRequestType::GetInfo(ref md) => md,
+            HttpRequestType::GetPoxInfo(ref md, ..) => md,
+            HttpRequestType::GetNeighbors(ref md) => md,
+            HttpRequestType::GetHeaders(ref md, ..) => md,
+            HttpRequestType::GetBlock(ref md, _) => md,
+            HttpRequestType::GetMicroblocksIndexed(ref md, _) => md,
+            HttpRequestType::GetMicroblocksConfirmed(ref md, _) => md,
+            HttpRequestType::GetMicroblocksUnconfirmed(ref md, _, _) => md,
+            HttpRequestType::GetTransactionUnconfirmed(ref md, _) => md,
+            HttpRequestType::PostTransaction(ref md, _, _) => md,
+            HttpRequestType::PostBlock(ref md, ..) => md,
+            HttpRequestType::PostMicroblock(ref md, ..) => md,
+            HttpRequestType::GetAccount(ref md, ..) => md,
+            HttpRequestType::GetDataVar(ref md, ..) => md,
+            HttpRequestType::GetMapEntry(ref md, ..) => md,
+            HttpRequestType::GetTransferCost(ref md) => md,
+            HttpRequestType::GetContractABI(ref md, ..) => md,
+            HttpRequestType::GetContractSrc(ref md, ..) => md,
+            HttpRequestType::GetIsTraitImplemented(ref md, ..) => md,
+            HttpRequestType::CallReadOnlyFunction(ref md, ..) => md,
+            HttpRequestType::OptionsPreflight(ref md, ..) => md,
+            HttpRequestType::GetAttachmentsInv(ref md, ..) => md,
+            HttpRequestType::GetAttachment(ref md, ..) => md,
+            HttpRequestType::MemPoolQuery(ref md, ..) => md,
+            HttpRequestType::GetSortition(ref md, ..) => md,
+            HttpRequestType::GetRewardCycle(ref md, ..) => md,
+            HttpRequestType::GetBurnOps(ref md, ..) => md,
+            HttpRequestType::GetAffirmationMaps(ref md, ..) => md,
+            HttpRequestType::GetAccountHistory(ref md, ..) => md,
+            HttpRequestType::GetLightHeaders(ref md, ..) => md,
+            HttpRequestType::Admin(ref md, ..) => md,
+            HttpRequestType::V3(ref md, ..) => md,
+            HttpRequestType::FeeRateEstimate(ref md, _, _) => md,
+            HttpRequestType::ClientError(ref md, ..) => md,
+        }
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut HttpRequestMetadata {
+        match *self {
+            HttpRequestType::GetInfo(ref mut md) => md,
+            HttpRequestType::GetPoxInfo(ref mut md, ..) => md,
+            HttpRequestType::GetNeighbors(ref mut md) => md,
+            HttpRequestType::GetHeaders(ref mut md, ..) => md,
+            HttpRequestType::GetBlock(ref mut md, _) => md,
+            HttpRequestType::GetMicroblocksIndexed(ref mut md, _) => md,
+            HttpRequestType::GetMicroblocksConfirmed(ref mut md, _) => md,
+            HttpRequestType::GetMicroblocksUnconfirmed(ref mut md, _, _) => md,
+            HttpRequestType::GetTransactionUnconfirmed(ref mut md, _) => md,
+            HttpRequestType::PostTransaction(ref mut md, _, _) => md,
+            HttpRequestType::PostBlock(ref mut md, ..) => md,
+            HttpRequestType::PostMicroblock(ref mut md, ..) => md,
+            HttpRequestType::GetAccount(ref mut md, ..) => md,
+            HttpRequestType::GetDataVar(ref mut md, ..) => md,
+            HttpRequestType::GetMapEntry(ref mut md, ..) => md,
+            HttpRequestType::GetTransferCost(ref mut md) => md,
+            HttpRequestType::GetContractABI(ref mut md, ..) => md,
+            HttpRequestType::GetContractSrc(ref mut md, ..) => md,
+            HttpRequestType::GetIsTraitImplemented(ref mut md, ..) => md,
+            HttpRequestType::CallReadOnlyFunction(ref mut md, ..) => md,
+            HttpRequestType::OptionsPreflight(ref mut md, ..) => md,
+            HttpRequestType::GetAttachmentsInv(ref mut md, ..) => md,
+            HttpRequestType::GetAttachment(ref mut md, ..) => md,
+            HttpRequestType::MemPoolQuery(ref mut md, ..) => md,
+            HttpRequestType::GetSortition(ref mut md, ..) => md,
+            HttpRequestType::GetRewardCycle(ref mut md, ..) => md,
+            HttpRequestType::GetBurnOps(ref mut md, ..) => md,
+            HttpRequestType::GetAffirmationMaps(ref mut md, ..) => md,
+            HttpRequestType::GetAccountHistory(ref mut md, ..) => md,
+            HttpRequestType::GetLightHeaders(ref mut md, ..) => md,
+            HttpRequestType::Admin(ref mut md, ..) => md,
+            HttpRequestType::V3(ref mut md, ..) => md,
+            HttpRequestType::FeeRateEstimate(ref mut md, _, _) => md,
+            HttpRequestType::ClientError(ref mut md, ..) => md,
+        }
+    }
+
+    fn make_tip_query_string(tip_req: &TipRequest, with_proof: bool) -> String {
+        match tip_req {
+            TipRequest::UseLatestUnconfirmedTip => {
+                format!("?tip=latest{}", if with_proof { "" } else { "&proof=0" })
+            }
+            TipRequest::SpecificTip(tip) => {
+                format!("?tip={}{}", tip, if with_proof { "" } else { "&proof=0" })
+            }
+            TipRequest::UseLatestAnchoredTip => {
+                if !with_proof {
+                    format!("?proof=0")
+                } else {
+                    "".to_string()
+                }
+            }
+        }
+    }
+
+    pub fn request_path(&self) -> String {
+        match self {
+            HttpRequestType::GetInfo(_md) => "/v2/info".to_string(),
+            HttpRequestType::GetPoxInfo(_md, tip_req) => format!(
+                "/v2/pox{}",
+                HttpRequestType::make_tip_query_string(tip_req, true)
+            ),
+            HttpRequestType::GetNeighbors(_md) => "/v2/neighbors".to_string(),
+            HttpRequestType::GetHeaders(_md, quantity, tip_req) => format!(
+                "/v2/headers/{}{}",
+                quantity,
+                HttpRequestType::make_tip_query_string(tip_req, true)
+            ),
+            HttpRequestType::GetBlock(_md, block_hash) => {
+                format!("/v2/blocks/{}", block_hash.to_hex())
+            }
+            HttpRequestType::GetMicroblocksIndexed(_md, block_hash) => {
+                format!("/v2/microblocks/{}", block_hash.to_hex())
+            }
+            HttpRequestType::GetMicroblocksConfirmed(_md, block_hash) => {
+                format!("/v2/microblocks/confirmed/{}", block_hash.to_hex())
+            }
+            HttpRequestType::GetMicroblocksUnconfirmed(_md, block_hash, min_seq) => format!(
+                "/v2/microblocks/unconfirmed/{}/{}",
+                block_hash.to_hex(),
+                min_seq
+            ),
+            HttpRequestType::GetTransactionUnconfirmed(_md, txid) => {
+                format!("/v2/transactions/unconfirmed/{}", txid)
             }
             HttpRequestType::PostTransaction(_md, ..) => "/v2/transactions".to_string(),
             HttpRequestType::PostBlock(_md, ch, ..) => format!("/v2/blocks/upload/{}", &ch),
@@ -2904,6 +3530,70 @@ impl HttpRequestType {
                 }
                 None => "/v2/mempool/query".to_string(),
             },
+            HttpRequestType::GetSortition(_, query) => match query {
+                SortitionQuery::LatestSortition => "/v2/sortitions".to_string(),
+                SortitionQuery::BurnHeight(height) => {
+                    format!("/v2/sortitions/burn_height/{}", height)
+                }
+                SortitionQuery::ConsensusHash(consensus_hash) => {
+                    format!("/v2/sortitions/consensus/{}", consensus_hash)
+                }
+                SortitionQuery::BurnHeaderHash(burn_header_hash) => {
+                    format!("/v2/sortitions/burn/{}", burn_header_hash)
+                }
+            },
+            HttpRequestType::GetRewardCycle(_, reward_cycle) => {
+                format!("/v2/pox/reward_cycles/{}", reward_cycle)
+            }
+            HttpRequestType::GetBurnOps(_, query) => match query {
+                BurnOpsQuery::Txid(txid) => format!("/v2/burn_ops/txid/{}", txid),
+                BurnOpsQuery::Sender(sender) => format!("/v2/burn_ops/sender/{}", sender),
+            },
+            HttpRequestType::GetAffirmationMaps(_, reward_cycle_opt) => match reward_cycle_opt {
+                Some(reward_cycle) => format!("/v2/pox/affirmations/{}", reward_cycle),
+                None => "/v2/pox/affirmations".to_string(),
+            },
+            HttpRequestType::GetAccountHistory(_, principal, tip_req, cursor, limit) => {
+                let tip_query = match tip_req {
+                    TipRequest::UseLatestUnconfirmedTip => "tip=latest&".to_string(),
+                    TipRequest::SpecificTip(tip) => format!("tip={}&", tip),
+                    TipRequest::UseLatestAnchoredTip => "".to_string(),
+                };
+                let cursor_query = match cursor {
+                    Some(cursor) => format!("cursor={}&", cursor),
+                    None => "".to_string(),
+                };
+                format!(
+                    "/v2/accounts/{}/history?{}{}limit={}",
+                    principal, tip_query, cursor_query, limit
+                )
+            }
+            HttpRequestType::GetLightHeaders(_md, quantity, tip_req) => format!(
+                "/v2/headers/light/{}{}",
+                quantity,
+                HttpRequestType::make_tip_query_string(tip_req, true)
+            ),
+            HttpRequestType::Admin(..) => self.get_path().to_string(),
+            HttpRequestType::V3(_, v3_req) => match v3_req {
+                V3Request::GetAccount(principal, tip_req, with_proof) => {
+                    let mut query = vec![];
+                    match tip_req {
+                        TipRequest::UseLatestUnconfirmedTip => query.push("tip=latest".to_string()),
+                        TipRequest::SpecificTip(tip) => query.push(format!("tip={}", tip)),
+                        TipRequest::UseLatestAnchoredTip => {}
+                    }
+                    if !with_proof {
+                        query.push("proof=0".to_string());
+                    }
+                    format!(
+                        "/v3/accounts/{}{}{}",
+                        principal,
+                        if query.is_empty() { "" } else { "?" },
+                        query.join("&")
+                    )
+                }
+                _ => self.get_path().to_string(),
+            },
             HttpRequestType::FeeRateEstimate(_, _, _) => self.get_path().to_string(),
             HttpRequestType::ClientError(_md, e) => match e {
                 ClientError::NotFound(path) => path.to_string(),
@@ -2943,6 +3633,25 @@ impl HttpRequestType {
             HttpRequestType::GetAttachment(..) => "/v2/attachments/:hash",
             HttpRequestType::GetIsTraitImplemented(..) => "/v2/traits/:principal/:contract_name",
             HttpRequestType::MemPoolQuery(..) => "/v2/mempool/query",
+            HttpRequestType::GetSortition(..) => "/v2/sortitions/:key/:value",
+            HttpRequestType::GetRewardCycle(..) => "/v2/pox/reward_cycles/:cycle",
+            HttpRequestType::GetBurnOps(..) => "/v2/burn_ops/:key/:value",
+            HttpRequestType::GetAffirmationMaps(..) => "/v2/pox/affirmations/:cycle",
+            HttpRequestType::GetAccountHistory(..) => "/v2/accounts/:principal/history",
+            HttpRequestType::GetLightHeaders(..) => "/v2/headers/light/:count",
+            HttpRequestType::Admin(_, _, admin_req) => match admin_req {
+                AdminRequest::BanPeer(..) => "/admin/peers/ban",
+                AdminRequest::AddBootstrapPeer(..) => "/admin/peers/bootstrap",
+                AdminRequest::DropMempoolTxs(..) => "/admin/mempool/drop",
+                AdminRequest::GetMining | AdminRequest::SetMining(..) => "/admin/mining",
+                AdminRequest::ReloadConfig => "/admin/config/reload",
+            },
+            HttpRequestType::V3(_, v3_req) => match v3_req {
+                V3Request::GetOpenAPI => "/v3/openapi.json",
+                V3Request::GetInfo => "/v3/info",
+                V3Request::GetAccount(..) => "/v3/accounts/:principal",
+                V3Request::PostTransaction(..) => "/v3/transactions",
+            },
             HttpRequestType::FeeRateEstimate(_, _, _) => "/v2/fees/transaction",
             HttpRequestType::OptionsPreflight(..) | HttpRequestType::ClientError(..) => "/",
         }
@@ -3117,6 +3826,89 @@ impl HttpRequestType {
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
             }
+            HttpRequestType::Admin(md, token_opt, admin_req) => {
+                let request_body = match admin_req {
+                    AdminRequest::BanPeer(addr, duration) => {
+                        Some(serde_json::to_vec(&AdminBanPeerRequestBody {
+                            peer: addr.to_string(),
+                            duration: Some(*duration),
+                        }))
+                    }
+                    AdminRequest::AddBootstrapPeer(public_key, addr) => {
+                        Some(serde_json::to_vec(&AdminBootstrapPeerRequestBody {
+                            peer: format!("{}@{}", public_key.to_hex(), addr),
+                        }))
+                    }
+                    AdminRequest::DropMempoolTxs(txids) => {
+                        Some(serde_json::to_vec(&AdminDropMempoolTxsRequestBody {
+                            txids: txids.iter().map(|txid| txid.to_hex()).collect(),
+                        }))
+                    }
+                    AdminRequest::SetMining(enabled) => {
+                        Some(serde_json::to_vec(&AdminSetMiningRequestBody {
+                            enabled: *enabled,
+                        }))
+                    }
+                    AdminRequest::GetMining | AdminRequest::ReloadConfig => None,
+                };
+                let request_body_bytes = match request_body {
+                    Some(body_res) => body_res.map_err(|e| {
+                        net_error::SerializeError(format!(
+                            "Failed to serialize admin request to JSON: {:?}",
+                            &e
+                        ))
+                    })?,
+                    None => vec![],
+                };
+                let verb = match admin_req {
+                    AdminRequest::GetMining => "GET",
+                    _ => "POST",
+                };
+                let content_type = if request_body_bytes.is_empty() {
+                    None
+                } else {
+                    Some(&HttpContentType::JSON)
+                };
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    verb,
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    content_type,
+                    |fd| {
+                        if let Some(token) = token_opt {
+                            fd.write_all(
+                                format!("Authorization: Bearer {}\r\n", token.as_str()).as_bytes(),
+                            )
+                            .map_err(codec_error::WriteError)?;
+                        }
+                        Ok(())
+                    },
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::V3(md, V3Request::PostTransaction(tx)) => {
+                let mut tx_bytes = vec![];
+                write_next(&mut tx_bytes, tx)?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(tx_bytes.len() as u32),
+                    Some(&HttpContentType::Bytes),
+                    |fd| stacks_height_headers(fd, md),
+                )?;
+                fd.write_all(&tx_bytes).map_err(net_error::WriteError)?;
+            }
             other_type => {
                 let md = other_type.metadata();
                 let request_path = other_type.request_path();
@@ -3197,6 +3989,14 @@ impl HttpResponseType {
             402 => HttpResponseType::PaymentRequired(md, error_text),
             403 => HttpResponseType::Forbidden(md, error_text),
             404 => HttpResponseType::NotFound(md, error_text),
+            429 => {
+                let retry_after = preamble
+                    .headers
+                    .get("retry-after")
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or(0);
+                HttpResponseType::TooManyRequests(md, retry_after, error_text)
+            }
             500 => HttpResponseType::ServerError(md, error_text),
             503 => HttpResponseType::ServiceUnavailable(md, error_text),
             _ => HttpResponseType::Error(md, preamble.status_code, error_text),
@@ -3347,6 +4147,16 @@ impl HttpResponseType {
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
+        if request_path.starts_with("/v3/") {
+            return HttpResponseType::parse_v3(
+                request_version,
+                preamble,
+                &request_path,
+                fd,
+                len_hint,
+            );
+        }
+
         if preamble.status_code >= 400 {
             return HttpResponseType::parse_error(protocol, request_version, preamble, fd);
         }
@@ -3366,6 +4176,10 @@ impl HttpResponseType {
             (&PATH_GETPOXINFO, &HttpResponseType::parse_poxinfo),
             (&PATH_GETNEIGHBORS, &HttpResponseType::parse_neighbors),
             (&PATH_GETHEADERS, &HttpResponseType::parse_headers),
+            (
+                &PATH_GET_LIGHT_HEADERS,
+                &HttpResponseType::parse_get_light_headers,
+            ),
             (&PATH_GETBLOCK, &HttpResponseType::parse_block),
             (&PATH_GET_DATA_VAR, &HttpResponseType::parse_get_data_var),
             (&PATH_GET_MAP_ENTRY, &HttpResponseType::parse_get_map_entry),
@@ -3423,6 +4237,34 @@ impl HttpResponseType {
                 &PATH_POST_MEMPOOL_QUERY,
                 &HttpResponseType::parse_post_mempool_query,
             ),
+            (&PATH_GET_SORTITION, &HttpResponseType::parse_get_sortition),
+            (
+                &PATH_GET_REWARD_CYCLE,
+                &HttpResponseType::parse_get_reward_cycle,
+            ),
+            (&PATH_GET_BURN_OPS, &HttpResponseType::parse_get_burn_ops),
+            (
+                &PATH_GET_AFFIRMATION_MAPS,
+                &HttpResponseType::parse_get_affirmation_maps,
+            ),
+            (
+                &PATH_GET_ACCOUNT_HISTORY,
+                &HttpResponseType::parse_get_account_history,
+            ),
+            (&PATH_ADMIN_BAN_PEER, &HttpResponseType::parse_admin_result),
+            (
+                &PATH_ADMIN_BOOTSTRAP_PEER,
+                &HttpResponseType::parse_admin_result,
+            ),
+            (
+                &PATH_ADMIN_DROP_MEMPOOL_TXS,
+                &HttpResponseType::parse_admin_result,
+            ),
+            (&PATH_ADMIN_MINING, &HttpResponseType::parse_admin_result),
+            (
+                &PATH_ADMIN_RELOAD_CONFIG,
+                &HttpResponseType::parse_admin_result,
+            ),
         ];
 
         // use url::Url to parse path and query string
@@ -3958,6 +4800,156 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_get_sortition<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let sortition_info =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetSortition(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            sortition_info,
+        ))
+    }
+
+    fn parse_get_reward_cycle<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let reward_cycle_info =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetRewardCycle(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            reward_cycle_info,
+        ))
+    }
+
+    fn parse_get_burn_ops<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let burn_ops =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetBurnOps(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            burn_ops,
+        ))
+    }
+
+    fn parse_get_affirmation_maps<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let affirmation_maps_info =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetAffirmationMaps(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            affirmation_maps_info,
+        ))
+    }
+
+    fn parse_get_account_history<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let account_history =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetAccountHistory(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            account_history,
+        ))
+    }
+
+    fn parse_get_light_headers<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let light_headers =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetLightHeaders(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            light_headers,
+        ))
+    }
+
+    fn parse_admin_result<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let admin_result =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminResult(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            admin_result,
+        ))
+    }
+
+    /// Parse a response to a /v3 request.  Errors are always `RPCErrorBody`s.
+    fn parse_v3<R: Read>(
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        request_path: &str,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let md = HttpResponseMetadata::from_preamble(request_version, preamble);
+        if preamble.status_code >= 400 {
+            let body =
+                HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+            return Ok(HttpResponseType::V3Error(md, body));
+        }
+
+        let path = request_path.split('?').next().unwrap_or("");
+        let endpoint = V3Endpoint::find_by_path(path).ok_or_else(|| {
+            net_error::DeserializeError(format!("No /v3 endpoint for path '{}'", path))
+        })?;
+        let response = match endpoint.operation {
+            V3Operation::GetOpenAPI => V3Response::OpenAPI(HttpResponseType::parse_json(
+                preamble,
+                fd,
+                len_hint,
+                MAX_MESSAGE_LEN as u64,
+            )?),
+            V3Operation::GetInfo => V3Response::PeerInfo(HttpResponseType::parse_json(
+                preamble,
+                fd,
+                len_hint,
+                MAX_MESSAGE_LEN as u64,
+            )?),
+            V3Operation::GetAccount => V3Response::Account(HttpResponseType::parse_json(
+                preamble,
+                fd,
+                len_hint,
+                MAX_MESSAGE_LEN as u64,
+            )?),
+            V3Operation::PostTransaction => V3Response::TransactionAccepted(
+                HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?,
+            ),
+        };
+        Ok(HttpResponseType::V3(md, response))
+    }
+
     fn error_reason(code: u16) -> &'static str {
         match code {
             400 => "Bad Request",
@@ -3965,6 +4957,8 @@ impl HttpResponseType {
             402 => "Payment Required",
             403 => "Forbidden",
             404 => "Not Found",
+            405 => "Method Not Allowed",
+            429 => "Too Many Requests",
             500 => "Internal Server Error",
             503 => "Service Temporarily Unavailable",
             _ => "Error",
@@ -4021,14 +5015,24 @@ impl HttpResponseType {
             HttpResponseType::MemPoolTxs(ref md, ..) => md,
             HttpResponseType::OptionsPreflight(ref md) => md,
             HttpResponseType::TransactionFeeEstimation(ref md, _) => md,
+            HttpResponseType::GetSortition(ref md, _) => md,
+            HttpResponseType::GetRewardCycle(ref md, _) => md,
+            HttpResponseType::GetBurnOps(ref md, _) => md,
+            HttpResponseType::GetAffirmationMaps(ref md, _) => md,
+            HttpResponseType::GetAccountHistory(ref md, _) => md,
+            HttpResponseType::GetLightHeaders(ref md, _) => md,
+            HttpResponseType::AdminResult(ref md, _) => md,
+            HttpResponseType::V3(ref md, _) => md,
             // errors
             HttpResponseType::BadRequestJSON(ref md, _) => md,
+            HttpResponseType::V3Error(ref md, _) => md,
             HttpResponseType::BadRequest(ref md, _) => md,
             HttpResponseType::Unauthorized(ref md, _) => md,
             HttpResponseType::PaymentRequired(ref md, _) => md,
             HttpResponseType::Forbidden(ref md, _) => md,
             HttpResponseType::NotFound(ref md, _) => md,
             HttpResponseType::ServerError(ref md, _) => md,
+            HttpResponseType::TooManyRequests(ref md, ..) => md,
             HttpResponseType::ServiceUnavailable(ref md, _) => md,
             HttpResponseType::Error(ref md, _, _) => md,
         }
@@ -4152,6 +5156,51 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, zonefile_data)?;
             }
+            HttpResponseType::GetSortition(ref md, ref sortition_info) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, sortition_info)?;
+            }
+            HttpResponseType::GetRewardCycle(ref md, ref reward_cycle_info) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, reward_cycle_info)?;
+            }
+            HttpResponseType::GetBurnOps(ref md, ref burn_ops) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, burn_ops)?;
+            }
+            HttpResponseType::GetAffirmationMaps(ref md, ref affirmation_maps_info) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, affirmation_maps_info)?;
+            }
+            HttpResponseType::GetAccountHistory(ref md, ref account_history) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, account_history)?;
+            }
+            HttpResponseType::GetLightHeaders(ref md, ref light_headers) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, light_headers)?;
+            }
+            HttpResponseType::AdminResult(ref md, ref admin_result) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, admin_result)?;
+            }
+            HttpResponseType::V3(ref md, ref v3_response) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                match v3_response {
+                    V3Response::OpenAPI(ref document) => {
+                        HttpResponseType::send_json(protocol, md, fd, document)?
+                    }
+                    V3Response::PeerInfo(ref peer_info) => {
+                        HttpResponseType::send_json(protocol, md, fd, peer_info)?
+                    }
+                    V3Response::Account(ref account) => {
+                        HttpResponseType::send_json(protocol, md, fd, account)?
+                    }
+                    V3Response::TransactionAccepted(ref accepted) => {
+                        HttpResponseType::send_json(protocol, md, fd, accepted)?
+                    }
+                }
+            }
             HttpResponseType::Headers(ref md, ref headers) => {
                 HttpResponsePreamble::new_serialized(
                     fd,
@@ -4342,11 +5391,41 @@ impl HttpResponseType {
                 )?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
             }
+            HttpResponseType::V3Error(ref md, ref body) => {
+                let code = body.code.status_code();
+                HttpResponsePreamble::new_serialized(
+                    fd,
+                    code,
+                    HttpResponseType::error_reason(code),
+                    md.content_length.clone(),
+                    &HttpContentType::JSON,
+                    md.request_id,
+                    |ref mut fd| keep_alive_headers(fd, md),
+                )?;
+                HttpResponseType::send_json(protocol, md, fd, body)?;
+            }
             HttpResponseType::BadRequest(_, ref msg) => self.error_response(fd, 400, msg)?,
             HttpResponseType::Unauthorized(_, ref msg) => self.error_response(fd, 401, msg)?,
             HttpResponseType::PaymentRequired(_, ref msg) => self.error_response(fd, 402, msg)?,
             HttpResponseType::Forbidden(_, ref msg) => self.error_response(fd, 403, msg)?,
             HttpResponseType::NotFound(_, ref msg) => self.error_response(fd, 404, msg)?,
+            HttpResponseType::TooManyRequests(ref md, retry_after, ref msg) => {
+                HttpResponsePreamble::new_serialized(
+                    fd,
+                    429,
+                    HttpResponseType::error_reason(429),
+                    Some(msg.len() as u32),
+                    &HttpContentType::Text,
+                    md.request_id,
+                    |ref mut fd| {
+                        keep_alive_headers(fd, md)?;
+                        fd.write_all(format!("Retry-After: {}\r\n", retry_after).as_bytes())
+                            .map_err(codec_error::WriteError)
+                    },
+                )?;
+                fd.write_all(msg.as_bytes())
+                    .map_err(net_error::WriteError)?;
+            }
             HttpResponseType::ServerError(_, ref msg) => self.error_response(fd, 500, msg)?,
             HttpResponseType::ServiceUnavailable(_, ref msg) => {
                 self.error_response(fd, 503, msg)?
@@ -4435,6 +5514,14 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::GetAttachment(..) => "HTTP(GetAttachment)",
                 HttpRequestType::GetAttachmentsInv(..) => "HTTP(GetAttachmentsInv)",
                 HttpRequestType::MemPoolQuery(..) => "HTTP(MemPoolQuery)",
+                HttpRequestType::GetSortition(..) => "HTTP(GetSortition)",
+                HttpRequestType::GetRewardCycle(..) => "HTTP(GetRewardCycle)",
+                HttpRequestType::GetBurnOps(..) => "HTTP(GetBurnOps)",
+                HttpRequestType::GetAffirmationMaps(..) => "HTTP(GetAffirmationMaps)",
+                HttpRequestType::GetAccountHistory(..) => "HTTP(GetAccountHistory)",
+                HttpRequestType::GetLightHeaders(..) => "HTTP(GetLightHeaders)",
+                HttpRequestType::Admin(..) => "HTTP(Admin)",
+                HttpRequestType::V3(..) => "HTTP(V3)",
                 HttpRequestType::OptionsPreflight(..) => "HTTP(OptionsPreflight)",
                 HttpRequestType::ClientError(..) => "HTTP(ClientError)",
                 HttpRequestType::FeeRateEstimate(_, _, _) => "HTTP(FeeRateEstimate)",
@@ -4465,6 +5552,15 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::UnconfirmedTransaction(_, _) => "HTTP(UnconfirmedTransaction)",
                 HttpResponseType::MemPoolTxStream(..) => "HTTP(MemPoolTxStream)",
                 HttpResponseType::MemPoolTxs(..) => "HTTP(MemPoolTxs)",
+                HttpResponseType::GetSortition(..) => "HTTP(GetSortition)",
+                HttpResponseType::GetRewardCycle(..) => "HTTP(GetRewardCycle)",
+                HttpResponseType::GetBurnOps(..) => "HTTP(GetBurnOps)",
+                HttpResponseType::GetAffirmationMaps(..) => "HTTP(GetAffirmationMaps)",
+                HttpResponseType::GetAccountHistory(..) => "HTTP(GetAccountHistory)",
+                HttpResponseType::GetLightHeaders(..) => "HTTP(GetLightHeaders)",
+                HttpResponseType::AdminResult(..) => "HTTP(AdminResult)",
+                HttpResponseType::V3(..) => "HTTP(V3)",
+                HttpResponseType::V3Error(..) => "HTTP(V3Error)",
                 HttpResponseType::OptionsPreflight(_) => "HTTP(OptionsPreflight)",
                 HttpResponseType::BadRequestJSON(..) | HttpResponseType::BadRequest(..) => {
                     "HTTP(400)"
@@ -4474,6 +5570,7 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::Forbidden(_, _) => "HTTP(403)",
                 HttpResponseType::NotFound(_, _) => "HTTP(404)",
                 HttpResponseType::ServerError(_, _) => "HTTP(500)",
+                HttpResponseType::TooManyRequests(..) => "HTTP(429)",
                 HttpResponseType::ServiceUnavailable(_, _) => "HTTP(503)",
                 HttpResponseType::Error(_, _, _) => "HTTP(other)",
                 HttpResponseType::TransactionFeeEstimation(_, _) => {
@@ -5008,10 +6105,12 @@ mod test {
     use crate::chainstate::stacks::TransactionPayload;
     use crate::chainstate::stacks::TransactionPostConditionMode;
     use crate::chainstate::stacks::TransactionVersion;
+    use crate::chainstate::stacks::C32_ADDRESS_VERSION_TESTNET_SINGLESIG;
     use crate::net::codec::test::check_codec_and_corruption;
     use crate::net::test::*;
     use crate::net::RPCNeighbor;
     use crate::net::RPCNeighborsInfo;
+    use crate::net::{AccountEntryResponse, RPCTransactionAccepted};
     use stacks_common::util::hash::to_hex;
     use stacks_common::util::hash::Hash160;
     use stacks_common::util::hash::MerkleTree;
@@ -6552,13 +7651,43 @@ mod test {
                 }
             }
 
-            assert_eq!(
-                preamble,
-                StacksHttpPreamble::Response((*expected_http_preamble).clone())
-            );
-            assert_eq!(message, StacksHttpMessage::Response((*test).clone()));
-            assert_eq!(http.num_pending(), 0);
+            assert_eq!(
+                preamble,
+                StacksHttpPreamble::Response((*expected_http_preamble).clone())
+            );
+            assert_eq!(message, StacksHttpMessage::Response((*test).clone()));
+            assert_eq!(http.num_pending(), 0);
+        }
+    }
+
+    #[test]
+    fn test_http_response_too_many_requests() {
+        let response = HttpResponseType::TooManyRequests(
+            HttpResponseMetadata::new(HttpVersion::Http11, 123, Some(17), true, None),
+            30,
+            "Too many requests".to_string(),
+        );
+
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let mut bytes = vec![];
+        http.begin_request(HttpVersion::Http11, "/v2/info".to_string());
+        http.write_message(&mut bytes, &StacksHttpMessage::Response(response.clone()))
+            .unwrap();
+
+        let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+        match preamble {
+            StacksHttpPreamble::Response(ref resp) => {
+                assert_eq!(resp.status_code, 429);
+                assert_eq!(resp.reason, "Too Many Requests");
+                assert_eq!(resp.headers.get("retry-after"), Some(&"30".to_string()));
+            }
+            StacksHttpPreamble::Request(_) => {
+                panic!("parsed a request");
+            }
         }
+
+        let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+        assert_eq!(message, StacksHttpMessage::Response(response));
     }
 
     #[test]
@@ -7005,5 +8134,627 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_http_parse_get_sortition() {
+        let tests = vec![
+            ("/v2/sortitions", SortitionQuery::LatestSortition),
+            ("/v2/sortitions/burn_height/123", SortitionQuery::BurnHeight(123)),
+            (
+                "/v2/sortitions/consensus/1111111111111111111111111111111111111111",
+                SortitionQuery::ConsensusHash(ConsensusHash([0x11; 20])),
+            ),
+            (
+                "/v2/sortitions/burn/2222222222222222222222222222222222222222222222222222222222222222",
+                SortitionQuery::BurnHeaderHash(BurnchainHeaderHash([0x22; 32])),
+            ),
+        ];
+        for (path, expected_query) in tests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => {
+                    assert_eq!(req.request_path(), path);
+                    match req {
+                        HttpRequestType::GetSortition(_, query) => {
+                            assert_eq!(query, expected_query)
+                        }
+                        _ => panic!("Expected GetSortition for {}: {:?}", path, &req),
+                    }
+                }
+                _ => panic!("Expected a request for {}", path),
+            }
+        }
+
+        let bad_paths = vec![
+            "/v2/sortitions/burn_height/12a",
+            "/v2/sortitions/burn_height/99999999999999999999999",
+            "/v2/sortitions/consensus/11",
+            "/v2/sortitions/burn/22",
+        ];
+        for path in bad_paths {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(HttpRequestType::ClientError(..)) => {}
+                _ => panic!("Expected a client error for {}: {:?}", path, &message),
+            }
+        }
+    }
+
+    #[test]
+    fn test_http_parse_get_reward_cycle() {
+        let request = "GET /v2/pox/reward_cycles/42 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n";
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(req) => {
+                assert_eq!(req.request_path(), "/v2/pox/reward_cycles/42");
+                match req {
+                    HttpRequestType::GetRewardCycle(_, reward_cycle) => {
+                        assert_eq!(reward_cycle, 42)
+                    }
+                    _ => panic!("Expected GetRewardCycle: {:?}", &req),
+                }
+            }
+            _ => panic!("Expected a request"),
+        }
+
+        // reward cycle does not fit in a u64
+        let request = "GET /v2/pox/reward_cycles/99999999999999999999 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n";
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::ClientError(..)) => {}
+            _ => panic!("Expected a client error: {:?}", &message),
+        }
+    }
+
+    #[test]
+    fn test_http_parse_get_burn_ops() {
+        let sender = StacksAddress::new(C32_ADDRESS_VERSION_TESTNET_SINGLESIG, Hash160([0x11; 20]));
+        let tests = vec![
+            (
+                "/v2/burn_ops/txid/2222222222222222222222222222222222222222222222222222222222222222"
+                    .to_string(),
+                BurnOpsQuery::Txid(Txid([0x22; 32])),
+            ),
+            (
+                format!("/v2/burn_ops/sender/{}", &sender),
+                BurnOpsQuery::Sender(sender),
+            ),
+        ];
+        for (path, expected_query) in tests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => {
+                    assert_eq!(req.request_path(), path);
+                    match req {
+                        HttpRequestType::GetBurnOps(_, query) => assert_eq!(query, expected_query),
+                        _ => panic!("Expected GetBurnOps for {}: {:?}", path, &req),
+                    }
+                }
+                _ => panic!("Expected a request for {}", path),
+            }
+        }
+
+        let bad_paths = vec![
+            "/v2/burn_ops/txid/22",
+            "/v2/burn_ops/txid/zz22222222222222222222222222222222222222222222222222222222222222",
+            "/v2/burn_ops/sender/not-an-address",
+            "/v2/burn_ops/sender/ST000000000000000000000000000000000000000",
+        ];
+        for path in bad_paths {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            match http.read_payload(&preamble, &request.as_bytes()[offset..]) {
+                Ok((StacksHttpMessage::Request(HttpRequestType::ClientError(..)), _)) => {}
+                Err(_) => {}
+                Ok((message, _)) => panic!("Expected a client error for {}: {:?}", path, &message),
+            }
+        }
+    }
+
+    #[test]
+    fn test_http_parse_get_affirmation_maps() {
+        let tests = vec![
+            ("/v2/pox/affirmations", None),
+            ("/v2/pox/affirmations/42", Some(42)),
+        ];
+        for (path, expected_reward_cycle) in tests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => {
+                    assert_eq!(req.request_path(), path);
+                    match req {
+                        HttpRequestType::GetAffirmationMaps(_, reward_cycle) => {
+                            assert_eq!(reward_cycle, expected_reward_cycle)
+                        }
+                        _ => panic!("Expected GetAffirmationMaps for {}: {:?}", path, &req),
+                    }
+                }
+                _ => panic!("Expected a request for {}", path),
+            }
+        }
+
+        // reward cycle does not fit in a u64
+        let request = "GET /v2/pox/affirmations/99999999999999999999 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n";
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::ClientError(..)) => {}
+            _ => panic!("Expected a client error: {:?}", &message),
+        }
+    }
+
+    #[test]
+    fn test_http_parse_get_account_history() {
+        let principal = "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R";
+        let tip = StacksBlockId([0x11; 32]);
+        let cursor = AccountHistoryCursor {
+            block_height: 123,
+            txid: Txid([0x22; 32]),
+        };
+        let tests = vec![
+            (
+                format!("/v2/accounts/{}/history", principal),
+                format!("/v2/accounts/{}/history?limit=20", principal),
+                TipRequest::UseLatestAnchoredTip,
+                None,
+                ACCOUNT_HISTORY_DEFAULT_LIMIT,
+            ),
+            (
+                format!("/v2/accounts/{}.hello-world/history?limit=5", principal),
+                format!("/v2/accounts/{}.hello-world/history?limit=5", principal),
+                TipRequest::UseLatestAnchoredTip,
+                None,
+                5,
+            ),
+            (
+                format!(
+                    "/v2/accounts/{}/history?tip={}&cursor={}&limit=50",
+                    principal, &tip, &cursor
+                ),
+                format!(
+                    "/v2/accounts/{}/history?tip={}&cursor={}&limit=50",
+                    principal, &tip, &cursor
+                ),
+                TipRequest::SpecificTip(tip.clone()),
+                Some(cursor.clone()),
+                50,
+            ),
+        ];
+        for (path, expected_path, expected_tip, expected_cursor, expected_limit) in tests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => {
+                    assert_eq!(req.request_path(), expected_path);
+                    match req {
+                        HttpRequestType::GetAccountHistory(_, _, tip, cursor, limit) => {
+                            assert_eq!(tip, expected_tip);
+                            assert_eq!(cursor, expected_cursor);
+                            assert_eq!(limit, expected_limit);
+                        }
+                        _ => panic!("Expected GetAccountHistory for {}: {:?}", path, &req),
+                    }
+                }
+                _ => panic!("Expected a request for {}", path),
+            }
+        }
+
+        let bad_queries = vec![
+            "limit=0",
+            "limit=51",
+            "limit=ten",
+            "cursor=123",
+            "cursor=-1:2222222222222222222222222222222222222222222222222222222222222222",
+            "cursor=123:not-a-txid",
+        ];
+        for query in bad_queries {
+            let request = format!(
+                "GET /v2/accounts/{}/history?{} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n",
+                principal, query
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(HttpRequestType::ClientError(..)) => {}
+                _ => panic!("Expected a client error for {}: {:?}", query, &message),
+            }
+        }
+    }
+
+    #[test]
+    fn test_http_parse_get_light_headers() {
+        let tip = StacksBlockId([0x11; 32]);
+        let tests = vec![
+            (
+                "/v2/headers/light/10".to_string(),
+                10,
+                TipRequest::UseLatestAnchoredTip,
+            ),
+            (
+                format!("/v2/headers/light/100?tip={}", &tip),
+                100,
+                TipRequest::SpecificTip(tip.clone()),
+            ),
+        ];
+        for (path, expected_quantity, expected_tip) in tests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(req) => {
+                    assert_eq!(req.request_path(), path);
+                    match req {
+                        HttpRequestType::GetLightHeaders(_, quantity, tip) => {
+                            assert_eq!(quantity, expected_quantity);
+                            assert_eq!(tip, expected_tip);
+                        }
+                        _ => panic!("Expected GetLightHeaders for {}: {:?}", path, &req),
+                    }
+                }
+                _ => panic!("Expected a request for {}", path),
+            }
+        }
+
+        // quantity does not fit in a u64
+        let request = "GET /v2/headers/light/99999999999999999999 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:6270\r\n\r\n";
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::ClientError(..)) => {}
+            _ => panic!("Expected a client error: {:?}", &message),
+        }
+    }
+
+    #[test]
+    fn test_http_parse_admin_requests() {
+        let md = HttpRequestMetadata {
+            version: HttpVersion::Http11,
+            peer: PeerHost::DNS("localhost".to_string(), 20445),
+            keep_alive: true,
+            canonical_stacks_tip_height: None,
+        };
+        let pubkey = StacksPublicKey::from_hex(
+            "02fa66b66f8971a8cd4d20ffded09674e030f0f33883f337f34b95ad4935bac0e3",
+        )
+        .unwrap();
+        let tests = vec![
+            AdminRequest::BanPeer("1.2.3.4:20444".parse().unwrap(), 3600),
+            AdminRequest::AddBootstrapPeer(pubkey, "5.6.7.8:20444".parse().unwrap()),
+            AdminRequest::DropMempoolTxs(vec![Txid([0x11; 32]), Txid([0x22; 32])]),
+            AdminRequest::GetMining,
+            AdminRequest::SetMining(false),
+            AdminRequest::ReloadConfig,
+        ];
+
+        // all of these should survive a round-trip, along with their bearer token
+        for admin_req in tests {
+            for token in [None, Some(AdminToken::new("hunter2"))] {
+                let req = HttpRequestType::Admin(md.clone(), token, admin_req.clone());
+                let mut bytes = vec![];
+                let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+                http.write_message(&mut bytes, &StacksHttpMessage::Request(req.clone()))
+                    .unwrap();
+
+                let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+                let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+                match message {
+                    StacksHttpMessage::Request(parsed) => assert_eq!(parsed, req),
+                    _ => panic!("Expected a request: {:?}", &message),
+                }
+            }
+        }
+
+        // a ban's duration is optional
+        let body = "{\"peer\":\"1.2.3.4:20444\"}";
+        let request = format!("POST /admin/peers/ban HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20445\r\nAuthorization: Bearer hunter2\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::Admin(
+                _,
+                Some(token),
+                AdminRequest::BanPeer(addr, duration),
+            )) => {
+                assert!(token.matches(&AdminToken::new("hunter2")));
+                assert_eq!(addr, "1.2.3.4:20444".parse().unwrap());
+                assert_eq!(duration, DENY_BAN_DURATION);
+            }
+            _ => panic!("Expected a ban request: {:?}", &message),
+        }
+
+        // malformed bodies are client errors
+        let bad_bodies = vec![
+            ("/admin/peers/ban", "{\"peer\":\"not-an-address\"}"),
+            ("/admin/peers/bootstrap", "{\"peer\":\"1.2.3.4:20444\"}"),
+            ("/admin/peers/bootstrap", "{\"peer\":\"00@1.2.3.4:20444\"}"),
+            ("/admin/mempool/drop", "{\"txids\":[\"0x1234\"]}"),
+            ("/admin/mining", "{\"enabled\":\"yes\"}"),
+            ("/admin/mining", "{}"),
+        ];
+        for (path, body) in bad_bodies {
+            let request = format!("POST {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20445\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body);
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(HttpRequestType::ClientError(..)) => {}
+                _ => panic!("Expected a client error for {}: {:?}", body, &message),
+            }
+        }
+    }
+
+    #[test]
+    fn test_http_parse_v3_requests() {
+        let md = HttpRequestMetadata {
+            version: HttpVersion::Http11,
+            peer: PeerHost::DNS("localhost".to_string(), 20443),
+            keep_alive: true,
+            canonical_stacks_tip_height: None,
+        };
+        let principal =
+            PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world").unwrap();
+        let tests = vec![
+            V3Request::GetOpenAPI,
+            V3Request::GetInfo,
+            V3Request::GetAccount(principal.clone(), TipRequest::UseLatestAnchoredTip, true),
+            V3Request::GetAccount(
+                principal.clone(),
+                TipRequest::UseLatestUnconfirmedTip,
+                false,
+            ),
+            V3Request::GetAccount(
+                principal.clone(),
+                TipRequest::SpecificTip(StacksBlockId([0x11; 32])),
+                true,
+            ),
+            V3Request::PostTransaction(make_test_transaction()),
+        ];
+
+        // all of these should survive a round-trip
+        for v3_req in tests {
+            let req = HttpRequestType::V3(md.clone(), v3_req);
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(req.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match message {
+                StacksHttpMessage::Request(parsed) => assert_eq!(parsed, req),
+                _ => panic!("Expected a request: {:?}", &message),
+            }
+        }
+
+        // every malformed request is a client error carrying an error body
+        let bad_requests = vec![
+            ("GET", "/v3/nope", "", RPCErrorCode::NotFound),
+            (
+                "GET",
+                "/v3/transactions",
+                "",
+                RPCErrorCode::MethodNotAllowed,
+            ),
+            (
+                "GET",
+                "/v3/accounts/not-a-principal",
+                "",
+                RPCErrorCode::InvalidParameter,
+            ),
+            (
+                "GET",
+                "/v3/accounts/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R?tip=nope",
+                "",
+                RPCErrorCode::InvalidParameter,
+            ),
+            (
+                "GET",
+                "/v3/accounts/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R?proof=0&proof=1",
+                "",
+                RPCErrorCode::InvalidParameter,
+            ),
+            (
+                "GET",
+                "/v3/info?verbose=1",
+                "",
+                RPCErrorCode::InvalidParameter,
+            ),
+            ("GET", "/v3/info", "body", RPCErrorCode::BadRequest),
+            ("POST", "/v3/transactions", "", RPCErrorCode::BadRequest),
+            ("POST", "/v3/transactions", "0001", RPCErrorCode::BadRequest),
+        ];
+        for (verb, path, body, expected_code) in bad_requests {
+            let request = format!("{} {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\n\r\n{}", verb, path, body.len(), body);
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(HttpRequestType::ClientError(
+                    _,
+                    ClientError::V3(error_body),
+                )) => {
+                    assert_eq!(error_body.code, expected_code, "{} {}", verb, path);
+                }
+                _ => panic!(
+                    "Expected a client error for {} {}: {:?}",
+                    verb, path, &message
+                ),
+            }
+        }
+
+        // CORS preflights still work
+        let request =
+            "OPTIONS /v3/info HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n";
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::OptionsPreflight(..)) => {}
+            _ => panic!("Expected a preflight request: {:?}", &message),
+        }
+    }
+
+    #[test]
+    fn test_http_v3_response_codec() {
+        let md = HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true, None);
+        let tests = vec![
+            (
+                "/v3/transactions",
+                HttpResponseType::V3(
+                    md.clone(),
+                    V3Response::TransactionAccepted(RPCTransactionAccepted {
+                        txid: Txid([0x22; 32]),
+                    }),
+                ),
+                200,
+            ),
+            (
+                "/v3/accounts/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R?proof=0",
+                HttpResponseType::V3(
+                    md.clone(),
+                    V3Response::Account(AccountEntryResponse {
+                        balance: "0x00000000000000000000000000000064".to_string(),
+                        locked: "0x00000000000000000000000000000000".to_string(),
+                        unlock_height: 0,
+                        nonce: 1,
+                        balance_proof: None,
+                        nonce_proof: None,
+                    }),
+                ),
+                200,
+            ),
+            (
+                "/v3/accounts/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R?tip=nope",
+                HttpResponseType::V3Error(
+                    md.clone(),
+                    RPCErrorBody::new(RPCErrorCode::InvalidParameter, "Invalid value")
+                        .with_details(json!({ "parameter": "tip" })),
+                ),
+                400,
+            ),
+            (
+                "/v3/transactions",
+                HttpResponseType::V3Error(
+                    md.clone(),
+                    RPCErrorBody::new(RPCErrorCode::MethodNotAllowed, "Not allowed"),
+                ),
+                405,
+            ),
+            (
+                "/v3/nope",
+                HttpResponseType::V3Error(md.clone(), RPCErrorBody::not_found("No such endpoint")),
+                404,
+            ),
+        ];
+
+        for (request_path, response, status_code) in tests {
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let mut bytes = vec![];
+            http.begin_request(HttpVersion::Http11, request_path.to_string());
+            http.write_message(&mut bytes, &StacksHttpMessage::Response(response.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            match preamble {
+                StacksHttpPreamble::Response(ref resp) => {
+                    assert_eq!(resp.status_code, status_code);
+                    assert_eq!(resp.content_type, HttpContentType::JSON);
+                }
+                StacksHttpPreamble::Request(_) => {
+                    panic!("parsed a request");
+                }
+            }
+
+            let (message_opt, _) = http
+                .stream_payload(&preamble, &mut &bytes[offset..])
+                .unwrap();
+            match (message_opt.unwrap().0, response) {
+                (
+                    StacksHttpMessage::Response(HttpResponseType::V3(_, parsed)),
+                    HttpResponseType::V3(_, expected),
+                ) => assert_eq!(parsed, expected),
+                (
+                    StacksHttpMessage::Response(HttpResponseType::V3Error(_, parsed)),
+                    HttpResponseType::V3Error(_, expected),
+                ) => assert_eq!(parsed, expected),
+                (message, _) => panic!("Unexpected response for {}: {:?}", request_path, &message),
+            }
+        }
+    }
+
     // TODO: test mismatch between request path and reply
 }
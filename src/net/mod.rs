@@ -46,9 +46,11 @@ use url;
 use crate::burnchains::affirmation::AffirmationMap;
 use crate::burnchains::Error as burnchain_error;
 use crate::burnchains::Txid;
+use crate::chainstate::burn::operations::BlockstackOperationType;
 use crate::chainstate::burn::ConsensusHash;
 use crate::chainstate::coordinator::Error as coordinator_error;
 use crate::chainstate::stacks::db::blocks::MemPoolRejection;
+use crate::chainstate::stacks::db::AccountHistoryCursor;
 use crate::chainstate::stacks::index::Error as marf_error;
 use crate::chainstate::stacks::Error as chainstate_error;
 use crate::chainstate::stacks::{
@@ -90,6 +92,7 @@ use crate::codec::BURNCHAIN_HEADER_HASH_ENCODED_SIZE;
 use crate::cost_estimates::FeeRateEstimate;
 use crate::types::chainstate::BlockHeaderHash;
 use crate::types::chainstate::PoxId;
+use crate::types::chainstate::VRFSeed;
 use crate::types::chainstate::{BurnchainHeaderHash, StacksAddress, StacksBlockId};
 use crate::types::StacksPublicKeyBuffer;
 use crate::util::hash::Sha256Sum;
@@ -132,9 +135,18 @@ pub mod p2p;
 /// p2p server and the http server.
 pub mod poll;
 pub mod prune;
+/// Implements `RPCRateLimiter`, which enforces per-client request rate limits and read-only call
+/// cost budgets on the RPC server.
+pub mod ratelimit;
 pub mod relay;
 pub mod rpc;
 pub mod server;
+/// Implements TLS for the RPC server: certificate loading and reloading, and a non-blocking
+/// TLS stream over a `mio` socket.
+pub mod tls;
+/// Implements the /v3 RPC interface's route table, uniform path and query parameter validation,
+/// error bodies, and the OpenAPI document that describes it.
+pub mod v3;
 
 #[derive(Debug)]
 pub enum Error {
@@ -246,6 +258,8 @@ pub enum Error {
     ExpectedEndOfStream,
     /// burnchain error
     BurnchainError(burnchain_error),
+    /// Failed to load or use a TLS certificate
+    TlsError(String),
 }
 
 impl From<codec_error> for Error {
@@ -271,6 +285,8 @@ pub enum ClientError {
     Message(String),
     /// 404
     NotFound(String),
+    /// Any error on the /v3 interface, which is reported with its own error body
+    V3(RPCErrorBody),
 }
 
 impl error::Error for ClientError {
@@ -284,6 +300,7 @@ impl fmt::Display for ClientError {
         match self {
             ClientError::Message(s) => write!(f, "{}", s),
             ClientError::NotFound(s) => write!(f, "HTTP path not matched: {}", s),
+            ClientError::V3(body) => write!(f, "{}: {}", body.code.as_str(), &body.reason),
         }
     }
 }
@@ -347,6 +364,7 @@ impl fmt::Display for Error {
             Error::Transient(ref s) => write!(f, "Transient network error: {}", s),
             Error::ExpectedEndOfStream => write!(f, "Expected end-of-stream"),
             Error::BurnchainError(ref e) => fmt::Display::fmt(e, f),
+            Error::TlsError(ref s) => write!(f, "TLS error: {}", s),
         }
     }
 }
@@ -408,6 +426,7 @@ impl error::Error for Error {
             Error::Transient(ref _s) => None,
             Error::ExpectedEndOfStream => None,
             Error::BurnchainError(ref e) => Some(e),
+            Error::TlsError(ref _s) => None,
         }
     }
 }
@@ -1301,6 +1320,77 @@ pub struct AccountEntryResponse {
     pub nonce_proof: Option<String>,
 }
 
+/// A transaction that affected a principal, as reported by GET /v2/accounts/:principal/history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAccountHistoryEntry {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    /// whether or not the principal sent or sponsored the transaction
+    pub is_sender: bool,
+    /// whether or not the transaction's effects were committed
+    pub committed: bool,
+    /// the transaction's STX, FT and NFT events that affect the principal
+    pub events: Vec<serde_json::Value>,
+}
+
+/// The data we return on GET /v2/accounts/:principal/history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAccountHistory {
+    pub principal: String,
+    /// the chain tip whose fork the history was read from
+    pub tip: StacksBlockId,
+    pub limit: u64,
+    /// most recent first
+    pub results: Vec<RPCAccountHistoryEntry>,
+    /// pass this as `?cursor=` to get the next page.  None if there are no more transactions.
+    pub next_cursor: Option<String>,
+}
+
+/// The sortition that selected a Stacks block, as reported by GET /v2/headers/light/:count
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCLightSortition {
+    pub burn_block_height: u64,
+    pub burn_header_hash: String,
+    pub parent_burn_header_hash: String,
+    pub consensus_hash: ConsensusHash,
+    pub sortition_id: String,
+    pub ops_hash: String,
+    pub sortition_hash: String,
+    /// total burn tokens destroyed since genesis, as of this burn block
+    pub total_burn: u64,
+    pub winning_block_txid: Txid,
+    pub winning_stacks_block_hash: BlockHeaderHash,
+}
+
+/// Proof that a sortition's winning block-commit was mined in its Bitcoin block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBlockCommitProof {
+    pub txid: Txid,
+    pub vtxindex: u32,
+    /// the transaction without its witness data, so it hashes to its txid
+    pub raw_tx: String,
+    /// sibling hashes from the transaction up to the block header's merkle root, in internal
+    /// byte order
+    pub merkle_branch: Vec<String>,
+}
+
+/// A Stacks block header together with what a light client needs to check it against the
+/// Bitcoin headers it has, as reported by GET /v2/headers/light/:count
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCLightHeader {
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub header: ExtendedStacksHeader,
+    pub sortition: RPCLightSortition,
+    /// the 80-byte header of the Bitcoin block the sortition happened in.  None if the node
+    /// processed that block before it started recording Bitcoin headers.
+    pub burn_block_header: Option<String>,
+    /// None if the node processed the sortition's Bitcoin block before it started recording
+    /// block-commit proofs.
+    pub block_commit_proof: Option<RPCBlockCommitProof>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UnconfirmedTransactionStatus {
     Microblock {
@@ -1456,6 +1546,400 @@ pub struct RPCNeighborsInfo {
     pub outbound: Vec<RPCNeighbor>,
 }
 
+/// A block-commit that competed in a sortition, as reported by GET /v2/sortitions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCSortitionBlockCommit {
+    pub txid: Txid,
+    pub vtxindex: u32,
+    pub block_header_hash: BlockHeaderHash,
+    pub burn_fee: u64,
+    pub sunset_burn: u64,
+    pub vrf_seed: VRFSeed,
+    /// VRF public key of the leader key this commit references, if it can be found
+    pub vrf_public_key: Option<String>,
+    pub parent_block_ptr: u32,
+    pub parent_vtxindex: u16,
+    pub key_block_ptr: u32,
+    pub key_vtxindex: u16,
+    pub apparent_sender: String,
+    /// PoX reward (or burn) addresses paid by this commit
+    pub commit_outs: Vec<String>,
+    pub won: bool,
+}
+
+/// A leader key registered in a sortition's burn block, as reported by GET /v2/sortitions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCSortitionLeaderKey {
+    pub txid: Txid,
+    pub vtxindex: u32,
+    pub consensus_hash: ConsensusHash,
+    pub vrf_public_key: String,
+}
+
+/// The data we return on GET /v2/sortitions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCSortitionInfo {
+    pub burn_block_height: u64,
+    pub burn_header_hash: String,
+    pub parent_burn_header_hash: String,
+    pub burn_header_timestamp: u64,
+    pub consensus_hash: ConsensusHash,
+    pub sortition_id: String,
+    pub parent_sortition_id: String,
+    pub pox_valid: bool,
+    /// whether or not this snapshot is on the canonical burnchain fork
+    pub canonical: bool,
+    /// whether or not a block-commit won in this burn block
+    pub sortition: bool,
+    /// total burn tokens destroyed since genesis, as of this burn block
+    pub total_burn: u64,
+    /// burn tokens committed by all the block-commits in this burn block
+    pub block_commits_burn: u64,
+    pub sortition_hash: String,
+    pub winning_block_txid: Option<Txid>,
+    pub winning_stacks_block_hash: Option<BlockHeaderHash>,
+    /// the VRF seed committed to by the winning block-commit
+    pub vrf_seed: Option<VRFSeed>,
+    pub block_commits: Vec<RPCSortitionBlockCommit>,
+    pub leader_keys: Vec<RPCSortitionLeaderKey>,
+}
+
+/// A reward cycle's PoX anchor block, as reported by GET /v2/pox/reward_cycles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardCycleAnchorBlock {
+    pub anchor_block_hash: BlockHeaderHash,
+    pub anchor_block_txid: Txid,
+    /// consensus hash of the sortition that chose the anchor block, if it is known
+    pub consensus_hash: Option<ConsensusHash>,
+    /// whether or not this node has processed the anchor block
+    pub processed: bool,
+}
+
+/// One PoX address's entry in a reward set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSetEntry {
+    pub pox_address: String,
+    pub slots: u64,
+    pub stacked_ustx: u64,
+}
+
+/// A reward cycle's reward set, as computed from its anchor block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSet {
+    pub threshold_ustx: u64,
+    pub total_stacked_ustx: u64,
+    pub total_slots: u64,
+    pub entries: Vec<RPCRewardSetEntry>,
+}
+
+/// An amount of burnchain tokens sent to a PoX address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPoxPayout {
+    pub pox_address: String,
+    pub amount: u64,
+}
+
+/// The payouts made by one block-commit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBlockCommitPayouts {
+    pub txid: Txid,
+    pub apparent_sender: String,
+    pub won: bool,
+    pub burnt: u64,
+    pub payouts: Vec<RPCPoxPayout>,
+}
+
+/// The payouts observed in one burnchain block of a reward cycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBurnBlockPayouts {
+    pub burn_block_height: u64,
+    pub burn_header_hash: String,
+    /// the reward set addresses block-commits in this block were required to pay
+    pub expected_recipients: Vec<String>,
+    pub block_commits: Vec<RPCBlockCommitPayouts>,
+}
+
+/// The data we return on GET /v2/pox/reward_cycles/:cycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardCycleInfo {
+    pub reward_cycle: u64,
+    pub first_burn_block_height: u64,
+    pub last_burn_block_height: u64,
+    pub prepare_phase_start_burn_block_height: u64,
+    pub anchor_block: Option<RPCRewardCycleAnchorBlock>,
+    /// the reward set, if an anchor block was chosen and this node has processed it
+    pub reward_set: Option<RPCRewardSet>,
+    /// payouts in each of the cycle's burnchain blocks this node has processed, on the
+    /// canonical burnchain fork
+    pub payouts: Vec<RPCBurnBlockPayouts>,
+    /// total payouts to each PoX address over those blocks
+    pub total_payouts: Vec<RPCPoxPayout>,
+    pub total_burnt: u64,
+}
+
+/// The outcome of a burnchain-hosted Stacks operation in one Stacks block that applied it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBurnOpStacksResult {
+    pub index_block_hash: StacksBlockId,
+    pub stacks_block_height: u64,
+    /// whether or not the block is on the canonical Stacks fork
+    pub canonical: bool,
+    /// whether or not the operation's effects were committed
+    pub committed: bool,
+    /// hex-encoded Clarity result, if the operation could be evaluated at all
+    pub result: Option<String>,
+}
+
+/// The data we return for each operation on GET /v2/burn_ops/txid/:txid and
+/// GET /v2/burn_ops/sender/:address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBurnOpInfo {
+    pub txid: Txid,
+    pub vtxindex: u32,
+    pub burn_block_height: u64,
+    pub burn_header_hash: BurnchainHeaderHash,
+    pub op_type: String,
+    /// None if the operation's pre-stx input could not be found
+    pub sender: Option<String>,
+    /// whether or not the burnchain block is on the canonical burnchain fork
+    pub canonical: bool,
+    /// whether or not the operation parsed and passed its burnchain checks
+    pub accepted: bool,
+    pub rejection_reason: Option<String>,
+    /// None if the transaction could not be parsed
+    pub op: Option<BlockstackOperationType>,
+    /// the outcome in each Stacks block that applied the operation
+    pub stacks_results: Vec<RPCBurnOpStacksResult>,
+}
+
+/// A block-commit that prepare-phase block-commits confirmed, and so could have been chosen as a
+/// reward cycle's anchor block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAnchorBlockCandidate {
+    pub burn_block_height: u64,
+    pub vtxindex: u32,
+    /// the block-commit's txid and Stacks block, if the confirmed block-commit exists at all
+    pub txid: Option<Txid>,
+    pub block_header_hash: Option<BlockHeaderHash>,
+    /// number of prepare-phase blocks with a block-commit that descends from this one
+    pub confirmations: u64,
+    /// total BTC burnt by the prepare-phase block-commits that descend from this one
+    pub burnt: u64,
+    /// whether or not this is the anchor block this node selected
+    pub selected: bool,
+}
+
+/// What each affirmation map says about the anchor block chosen in one reward cycle's prepare
+/// phase (i.e. the anchor block for the next reward cycle)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAffirmationRewardCycle {
+    pub reward_cycle: u64,
+    /// "p", "a" or "n", or null if the map does not cover this reward cycle
+    pub heaviest: Option<String>,
+    pub canonical: Option<String>,
+    pub stacks_tip: Option<String>,
+    pub sortition_tip: Option<String>,
+    /// candidates confirmed in this reward cycle's prepare phase, highest first
+    pub anchor_block_candidates: Vec<RPCAnchorBlockCandidate>,
+}
+
+/// A node's affirmation maps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAffirmationMaps {
+    pub heaviest: AffirmationMap,
+    pub canonical: AffirmationMap,
+    pub stacks_tip: AffirmationMap,
+    pub sortition_tip: AffirmationMap,
+}
+
+/// The first reward cycle in which two affirmation maps affirm different anchor block histories
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAffirmationMapDivergence {
+    pub left: String,
+    pub right: String,
+    pub reward_cycle: u64,
+}
+
+/// The data we return on GET /v2/pox/affirmations and GET /v2/pox/affirmations/:cycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAffirmationMapsInfo {
+    /// the reward cycle of the sortition tip
+    pub reward_cycle: u64,
+    pub anchor_threshold: u32,
+    pub affirmation_maps: RPCAffirmationMaps,
+    pub divergences: Vec<RPCAffirmationMapDivergence>,
+    pub reward_cycles: Vec<RPCAffirmationRewardCycle>,
+}
+
+/// How burnchain-hosted Stacks operations are looked up in GET /v2/burn_ops
+#[derive(Debug, Clone, PartialEq)]
+pub enum BurnOpsQuery {
+    Txid(Txid),
+    Sender(StacksAddress),
+}
+
+/// How a sortition is identified in GET /v2/sortitions
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortitionQuery {
+    LatestSortition,
+    BurnHeight(u64),
+    ConsensusHash(ConsensusHash),
+    BurnHeaderHash(BurnchainHeaderHash),
+}
+
+/// A bearer token for the admin RPC interface.  It is never printed, so it can't end up in the
+/// logs.
+#[derive(Clone, PartialEq)]
+pub struct AdminToken(String);
+
+impl AdminToken {
+    pub fn new(token: &str) -> AdminToken {
+        AdminToken(token.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Does a presented token match this one?  Compares in constant time.
+    pub fn matches(&self, presented: &AdminToken) -> bool {
+        let (expected, presented) = (self.0.as_bytes(), presented.0.as_bytes());
+        if expected.len() != presented.len() {
+            return false;
+        }
+        expected
+            .iter()
+            .zip(presented.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+    }
+}
+
+impl fmt::Debug for AdminToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AdminToken(<redacted>)")
+    }
+}
+
+/// Operations on the admin RPC interface
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminRequest {
+    /// Deny a peer for the given number of seconds, and disconnect from it
+    BanPeer(SocketAddr, u64),
+    /// Add a bootstrap peer, given its public key and address
+    AddBootstrapPeer(StacksPublicKey, SocketAddr),
+    /// Remove transactions from the mempool, and refuse to accept them again
+    DropMempoolTxs(Vec<Txid>),
+    /// Report whether or not the node is mining
+    GetMining,
+    /// Start or stop mining
+    SetMining(bool),
+    /// Re-read the node's config file
+    ReloadConfig,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminBanPeerRequestBody {
+    /// IP:PORT
+    pub peer: String,
+    /// seconds; defaults to DENY_BAN_DURATION
+    #[serde(default)]
+    pub duration: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminBootstrapPeerRequestBody {
+    /// PUBKEY@IP:PORT, as in the node config file
+    pub peer: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminDropMempoolTxsRequestBody {
+    pub txids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminSetMiningRequestBody {
+    pub enabled: bool,
+}
+
+/// The data we return from the admin RPC interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminResult {
+    pub message: String,
+    /// whether or not the node is mining, for mining requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mining: Option<bool>,
+    /// when a peer ban expires, for ban requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub banned_until: Option<u64>,
+}
+
+impl RPCAdminResult {
+    pub fn new(message: String) -> RPCAdminResult {
+        RPCAdminResult {
+            message,
+            mining: None,
+            banned_until: None,
+        }
+    }
+}
+
+/// Machine-readable error codes in /v3 error bodies.  Each maps to one HTTP status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RPCErrorCode {
+    /// The request was malformed, e.g. it had an unparseable body (400)
+    BadRequest,
+    /// A path or query parameter was unknown, missing, repeated or invalid (400)
+    InvalidParameter,
+    /// The mempool refused a POSTed transaction (400)
+    TransactionRejected,
+    /// No such endpoint, or the requested data does not exist (404)
+    NotFound,
+    /// The endpoint exists, but not for this HTTP method (405)
+    MethodNotAllowed,
+    /// The node failed to process a valid request (500)
+    ServerError,
+}
+
+/// The body of every /v3 error response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCErrorBody {
+    pub code: RPCErrorCode,
+    /// human-readable description of what went wrong
+    pub reason: String,
+    /// code-specific structured data, such as the offending parameter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+/// Operations on the /v3 RPC interface
+#[derive(Debug, Clone, PartialEq)]
+pub enum V3Request {
+    /// Get the OpenAPI document describing the /v3 interface
+    GetOpenAPI,
+    GetInfo,
+    /// principal, chain tip, and whether or not to include MARF proofs
+    GetAccount(PrincipalData, TipRequest, bool),
+    PostTransaction(StacksTransaction),
+}
+
+/// Successful responses from the /v3 RPC interface
+#[derive(Debug, Clone, PartialEq)]
+pub enum V3Response {
+    OpenAPI(serde_json::Value),
+    PeerInfo(RPCPeerInfoData),
+    Account(AccountEntryResponse),
+    TransactionAccepted(RPCTransactionAccepted),
+}
+
+/// The data we return on POST /v3/transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCTransactionAccepted {
+    pub txid: Txid,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TipRequest {
     UseLatestAnchoredTip,
@@ -1527,6 +2011,22 @@ pub enum HttpRequestType {
         TipRequest,
     ),
     MemPoolQuery(HttpRequestMetadata, MemPoolSyncData, Option<Txid>),
+    GetSortition(HttpRequestMetadata, SortitionQuery),
+    GetRewardCycle(HttpRequestMetadata, u64),
+    GetBurnOps(HttpRequestMetadata, BurnOpsQuery),
+    GetAffirmationMaps(HttpRequestMetadata, Option<u64>),
+    /// principal, chain tip, cursor and limit
+    GetAccountHistory(
+        HttpRequestMetadata,
+        PrincipalData,
+        TipRequest,
+        Option<AccountHistoryCursor>,
+        u64,
+    ),
+    GetLightHeaders(HttpRequestMetadata, u64, TipRequest),
+    /// admin interface requests carry the bearer token they presented, if any
+    Admin(HttpRequestMetadata, Option<AdminToken>, AdminRequest),
+    V3(HttpRequestMetadata, V3Request),
     /// catch-all for any errors we should surface from parsing
     ClientError(HttpRequestMetadata, ClientError),
 }
@@ -1644,13 +2144,24 @@ pub enum HttpResponseType {
     MemPoolTxs(HttpResponseMetadata, Option<Txid>, Vec<StacksTransaction>),
     OptionsPreflight(HttpResponseMetadata),
     TransactionFeeEstimation(HttpResponseMetadata, RPCFeeEstimateResponse),
+    GetSortition(HttpResponseMetadata, RPCSortitionInfo),
+    GetRewardCycle(HttpResponseMetadata, RPCRewardCycleInfo),
+    GetBurnOps(HttpResponseMetadata, Vec<RPCBurnOpInfo>),
+    GetAffirmationMaps(HttpResponseMetadata, RPCAffirmationMapsInfo),
+    GetAccountHistory(HttpResponseMetadata, RPCAccountHistory),
+    GetLightHeaders(HttpResponseMetadata, Vec<RPCLightHeader>),
+    AdminResult(HttpResponseMetadata, RPCAdminResult),
+    V3(HttpResponseMetadata, V3Response),
     // peer-given error responses
     BadRequest(HttpResponseMetadata, String),
     BadRequestJSON(HttpResponseMetadata, serde_json::Value),
+    /// any /v3 error; the HTTP status code is determined by the error code
+    V3Error(HttpResponseMetadata, RPCErrorBody),
     Unauthorized(HttpResponseMetadata, String),
     PaymentRequired(HttpResponseMetadata, String),
     Forbidden(HttpResponseMetadata, String),
     NotFound(HttpResponseMetadata, String),
+    TooManyRequests(HttpResponseMetadata, u64, String),
     ServerError(HttpResponseMetadata, String),
     ServiceUnavailable(HttpResponseMetadata, String),
     Error(HttpResponseMetadata, u16, String),
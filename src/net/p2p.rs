@@ -70,11 +70,13 @@ use crate::net::neighbors::*;
 use crate::net::poll::NetworkPollState;
 use crate::net::poll::NetworkState;
 use crate::net::prune::*;
+use crate::net::ratelimit::RPCRateLimiter;
 use crate::net::relay::RelayerStats;
 use crate::net::relay::*;
 use crate::net::relay::*;
 use crate::net::rpc::RPCHandlerArgs;
 use crate::net::server::*;
+use crate::net::tls::TlsServerConfig;
 use crate::net::Error as net_error;
 use crate::net::Neighbor;
 use crate::net::NeighborKey;
@@ -264,6 +266,8 @@ pub struct PeerNetwork {
     pub events: HashMap<NeighborKey, usize>,
     pub connecting: HashMap<usize, (mio_net::TcpStream, bool, u64)>, // (socket, outbound?, connection sent timestamp)
     pub bans: HashSet<usize>,
+    // connections to peers banned via the admin interface, to close on the next pass
+    pub admin_bans: HashSet<usize>,
 
     // ongoing messages the network is sending via the p2p interface (not bound to a specific
     // conversation).
@@ -277,6 +281,8 @@ pub struct PeerNetwork {
     pub network: Option<NetworkState>,
     p2p_network_handle: usize,
     http_network_handle: usize,
+    admin_network_handle: Option<usize>,
+    tls_network_handle: Option<usize>,
 
     // info on the burn chain we're tracking
     pub burnchain: Burnchain,
@@ -333,6 +339,9 @@ pub struct PeerNetwork {
     // http endpoint, used for driving HTTP conversations (some of which we initiate)
     pub http: Option<HttpPeer>,
 
+    // per-client rate limits and read-only call budgets for the http endpoint
+    pub rpc_rate_limiter: RPCRateLimiter,
+
     // our own neighbor address that we bind on
     bind_nk: NeighborKey,
 
@@ -425,6 +434,7 @@ impl PeerNetwork {
             events: HashMap::new(),
             connecting: HashMap::new(),
             bans: HashSet::new(),
+            admin_bans: HashSet::new(),
 
             relay_handles: HashMap::new(),
             relayer_stats: RelayerStats::new(),
@@ -433,6 +443,8 @@ impl PeerNetwork {
             network: None,
             p2p_network_handle: 0,
             http_network_handle: 0,
+            admin_network_handle: None,
+            tls_network_handle: None,
 
             burnchain: burnchain,
             connection_opts: connection_opts,
@@ -468,6 +480,7 @@ impl PeerNetwork {
             prune_inbound_counts: HashMap::new(),
 
             http: Some(http),
+            rpc_rate_limiter: RPCRateLimiter::default(),
             bind_nk: NeighborKey {
                 network_id: 0,
                 peer_version: 0,
@@ -561,6 +574,126 @@ impl PeerNetwork {
         Ok(())
     }
 
+    /// Start serving the admin interface, whose clients must present the given bearer token.
+    /// Call after bind().
+    pub fn bind_admin(
+        &mut self,
+        admin_addr: &SocketAddr,
+        admin_token: AdminToken,
+    ) -> Result<(), net_error> {
+        let admin_handle = match self.network {
+            Some(ref mut net) => net.bind(admin_addr)?,
+            None => {
+                return Err(net_error::NotConnected);
+            }
+        };
+
+        test_debug!("{:?}: bound admin on {:?}", &self.local_peer, admin_addr);
+
+        self.admin_network_handle = Some(admin_handle);
+        PeerNetwork::with_http(self, |_, ref mut http| {
+            http.set_admin_server_handle(admin_handle, admin_token);
+        });
+        Ok(())
+    }
+
+    /// Also serve the RPC interface over TLS on the given address, with the certificate chain
+    /// and private key in the given PEM files.  The files are re-read when they change.  The
+    /// plaintext RPC server is unaffected, since other peers reach our data_url through it.
+    /// Call after bind().
+    pub fn bind_rpc_tls(
+        &mut self,
+        tls_addr: &SocketAddr,
+        cert_path: &str,
+        key_path: &str,
+    ) -> Result<(), net_error> {
+        let tls_config = TlsServerConfig::from_files(cert_path, key_path)?;
+        self.bind_rpc_tls_with_config(tls_addr, tls_config)
+    }
+
+    /// Serve the RPC interface over TLS on the given address, with the given certificate.
+    /// Call after bind().
+    pub fn bind_rpc_tls_with_config(
+        &mut self,
+        tls_addr: &SocketAddr,
+        tls_config: TlsServerConfig,
+    ) -> Result<(), net_error> {
+        let tls_handle = match self.network {
+            Some(ref mut net) => net.bind(tls_addr)?,
+            None => {
+                return Err(net_error::NotConnected);
+            }
+        };
+
+        test_debug!("{:?}: bound TLS on {:?}", &self.local_peer, tls_addr);
+
+        self.tls_network_handle = Some(tls_handle);
+        PeerNetwork::with_http(self, |_, ref mut http| {
+            http.set_tls_server_handle(tls_handle, tls_config);
+        });
+        Ok(())
+    }
+
+    /// Deny a peer until the given time, and disconnect from it on the next pass.
+    /// Returns the number of connections to it that will be closed.
+    pub fn ban_peer_until(&mut self, addr: &SocketAddr, deadline: u64) -> Result<usize, net_error> {
+        let addrbytes = PeerAddress::from_socketaddr(addr);
+        let mut tx = self.peerdb.tx_begin()?;
+        PeerDB::set_deny_peer(
+            &mut tx,
+            self.local_peer.network_id,
+            &addrbytes,
+            addr.port(),
+            deadline,
+        )?;
+        tx.commit()?;
+
+        let mut num_banned = 0;
+        for (event_id, convo) in self.peers.iter() {
+            let neighbor_key = convo.to_neighbor_key();
+            if neighbor_key.addrbytes == addrbytes && neighbor_key.port == addr.port() {
+                debug!(
+                    "{:?}: Will disconnect from banned peer {:?} on event {}",
+                    &self.local_peer, &neighbor_key, event_id
+                );
+                self.admin_bans.insert(*event_id);
+                num_banned += 1;
+            }
+        }
+        Ok(num_banned)
+    }
+
+    /// Add a bootstrap peer.  It will be used like the node's configured bootstrap peers until
+    /// the peer DB is next opened.
+    pub fn add_bootstrap_peer(
+        &mut self,
+        public_key: &StacksPublicKey,
+        addr: &SocketAddr,
+    ) -> Result<(), net_error> {
+        let neighbor = Neighbor {
+            addr: NeighborKey {
+                peer_version: self.peer_version,
+                network_id: self.local_peer.network_id,
+                addrbytes: PeerAddress::from_socketaddr(addr),
+                port: addr.port(),
+            },
+            public_key: *public_key,
+            // same as the node's configured bootstrap peers
+            expire_block: 9999999,
+            last_contact_time: 0,
+            allowed: 0,
+            denied: 0,
+            asn: 0,
+            org: 0,
+            in_degree: 0,
+            out_degree: 0,
+        };
+        let mut tx = self.peerdb.tx_begin()?;
+        PeerDB::add_initial_peer(&mut tx, &neighbor)?;
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Run a closure with the network state
     pub fn with_network_state<F, R>(
         peer_network: &mut PeerNetwork,
@@ -5357,9 +5490,21 @@ impl PeerNetwork {
         let p2p_poll_state = poll_states
             .remove(&self.p2p_network_handle)
             .expect("BUG: no poll state for p2p network handle");
-        let http_poll_state = poll_states
+        let mut http_poll_state = poll_states
             .remove(&self.http_network_handle)
             .expect("BUG: no poll state for http network handle");
+        let admin_poll_state_opt = self.admin_network_handle.map(|admin_handle| {
+            let admin_poll_state = poll_states
+                .remove(&admin_handle)
+                .expect("BUG: no poll state for admin network handle");
+            (admin_handle, admin_poll_state)
+        });
+        let tls_poll_state_opt = self.tls_network_handle.map(|tls_handle| {
+            let tls_poll_state = poll_states
+                .remove(&tls_handle)
+                .expect("BUG: no poll state for TLS network handle");
+            (tls_handle, tls_poll_state)
+        });
 
         // update local-peer state
         self.refresh_local_peer()
@@ -5402,6 +5547,19 @@ impl PeerNetwork {
 
         PeerNetwork::with_network_state(self, |ref mut network, ref mut network_state| {
             let http_stacks_msgs = PeerNetwork::with_http(network, |ref mut net, ref mut http| {
+                for (listener_handle, listener_poll_state) in admin_poll_state_opt
+                    .into_iter()
+                    .chain(tls_poll_state_opt.into_iter())
+                {
+                    http.merge_listener_poll_state(
+                        network_state,
+                        mempool,
+                        chainstate,
+                        listener_handle,
+                        listener_poll_state,
+                        &mut http_poll_state,
+                    );
+                }
                 http.run(
                     network_state,
                     net,
@@ -5417,6 +5575,16 @@ impl PeerNetwork {
         })
         .expect("FATAL: with_network_state should be infallable (not connected)");
 
+        // disconnect from peers that were banned via the admin interface
+        let admin_bans: Vec<usize> = self.admin_bans.drain().collect();
+        for event_id in admin_bans {
+            debug!(
+                "{:?}: Banned connection on event {}",
+                &self.local_peer, event_id
+            );
+            self.deregister_peer(event_id);
+        }
+
         self.dispatch_network(
             &mut network_result,
             sortdb,
@@ -0,0 +1,343 @@
+/*
+ copyright: (c) 2013-2022 by Blockstack PBC, a public benefit corporation.
+
+ This file is part of Blockstack.
+
+ Blockstack is free software. You may redistribute or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License or
+ (at your option) any later version.
+
+ Blockstack is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY, including without the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use crate::net::connection::ConnectionOptions;
+use crate::net::HttpRequestType;
+use crate::net::V3Request;
+
+use clarity::vm::costs::ExecutionCost;
+
+/// How often to forget about idle clients, in milliseconds
+const RATE_LIMITER_PRUNE_INTERVAL_MS: u128 = 60_000;
+
+/// Classes of RPC endpoints that are rate-limited separately, so that a client hammering one
+/// kind of endpoint does not lock itself out of the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RPCEndpointClass {
+    /// Cheap queries about the chain state
+    Query,
+    /// Block, microblock, header, attachment and mempool streams
+    Stream,
+    /// Read-only Clarity function calls
+    ReadOnlyCall,
+    /// Transaction, block and microblock submission
+    Submit,
+}
+
+impl RPCEndpointClass {
+    /// Classify a request.  Returns None for requests that are never rate-limited.
+    pub fn from_request(req: &HttpRequestType) -> Option<RPCEndpointClass> {
+        match req {
+            HttpRequestType::ClientError(..) | HttpRequestType::Admin(..) => None,
+            HttpRequestType::GetHeaders(..)
+            | HttpRequestType::GetLightHeaders(..)
+            | HttpRequestType::GetBlock(..)
+            | HttpRequestType::GetMicroblocksIndexed(..)
+            | HttpRequestType::GetMicroblocksConfirmed(..)
+            | HttpRequestType::GetMicroblocksUnconfirmed(..)
+            | HttpRequestType::GetAttachment(..)
+            | HttpRequestType::GetAttachmentsInv(..)
+            | HttpRequestType::MemPoolQuery(..) => Some(RPCEndpointClass::Stream),
+            HttpRequestType::CallReadOnlyFunction(..) => Some(RPCEndpointClass::ReadOnlyCall),
+            HttpRequestType::V3(_, V3Request::PostTransaction(..))
+            | HttpRequestType::PostTransaction(..)
+            | HttpRequestType::PostBlock(..)
+            | HttpRequestType::PostMicroblock(..) => Some(RPCEndpointClass::Submit),
+            _ => Some(RPCEndpointClass::Query),
+        }
+    }
+
+    /// Maximum number of requests per minute a client may make to this class of endpoints.
+    /// 0 means unlimited.
+    fn requests_per_minute(&self, opts: &ConnectionOptions) -> u64 {
+        match self {
+            RPCEndpointClass::Query => opts.rpc_query_rate_limit,
+            RPCEndpointClass::Stream => opts.rpc_stream_rate_limit,
+            RPCEndpointClass::ReadOnlyCall => opts.rpc_read_only_call_rate_limit,
+            RPCEndpointClass::Submit => opts.rpc_submit_rate_limit,
+        }
+    }
+}
+
+/// A token bucket that holds up to a minute's worth of requests, and refills continuously.
+#[derive(Debug, Clone, PartialEq)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill_ms: u128,
+}
+
+impl TokenBucket {
+    fn new(requests_per_minute: u64, now_ms: u128) -> TokenBucket {
+        TokenBucket {
+            tokens: requests_per_minute as f64,
+            last_refill_ms: now_ms,
+        }
+    }
+
+    /// Take a token out of the bucket.  Returns None if there was one, or the number of seconds
+    /// until there will be one.
+    fn take(&mut self, requests_per_minute: u64, now_ms: u128) -> Option<u64> {
+        let capacity = requests_per_minute as f64;
+        let elapsed_ms = now_ms.saturating_sub(self.last_refill_ms) as f64;
+        self.tokens = (self.tokens + elapsed_ms * capacity / 60_000.0).min(capacity);
+        self.last_refill_ms = now_ms;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            let retry_after = ((1.0 - self.tokens) * 60.0 / capacity).ceil() as u64;
+            Some(retry_after.max(1))
+        }
+    }
+
+    /// Would this bucket be full by now?
+    fn is_full(&self, now_ms: u128) -> bool {
+        now_ms.saturating_sub(self.last_refill_ms) >= 60_000
+    }
+}
+
+/// The execution cost of read-only calls a client has incurred in the current budget interval
+#[derive(Debug, Clone, PartialEq)]
+struct CostBudget {
+    spent: ExecutionCost,
+    interval_start_ms: u128,
+}
+
+/// Per-client rate limits and read-only call cost budgets for the RPC server.  Clients are
+/// identified by IP address.  Each client gets a token bucket per class of endpoint, which holds
+/// up to a minute's worth of requests.  In addition, each client may only spend so much
+/// execution cost on read-only calls per budget interval.
+#[derive(Debug, Default)]
+pub struct RPCRateLimiter {
+    buckets: HashMap<(IpAddr, RPCEndpointClass), TokenBucket>,
+    cost_budgets: HashMap<IpAddr, CostBudget>,
+    last_prune_ms: u128,
+}
+
+impl RPCRateLimiter {
+    /// When the client's current read-only call budget interval ends
+    fn budget_interval_end_ms(opts: &ConnectionOptions, budget: &CostBudget) -> u128 {
+        budget.interval_start_ms + (opts.read_only_call_budget_interval as u128) * 1000
+    }
+
+    /// Account for a request from the given client.  Returns None if the client may proceed, or
+    /// the number of seconds it must wait before retrying if it is over its rate limit or (for
+    /// read-only calls) its cost budget.
+    pub fn check_request(
+        &mut self,
+        opts: &ConnectionOptions,
+        addr: &IpAddr,
+        class: RPCEndpointClass,
+        now_ms: u128,
+    ) -> Option<u64> {
+        self.prune(opts, now_ms);
+
+        if class == RPCEndpointClass::ReadOnlyCall && opts.read_only_call_budget_interval > 0 {
+            if let Some(budget) = self.cost_budgets.get(addr) {
+                let interval_end_ms = RPCRateLimiter::budget_interval_end_ms(opts, budget);
+                if now_ms < interval_end_ms && budget.spent.exceeds(&opts.read_only_call_budget) {
+                    let retry_after = (interval_end_ms - now_ms + 999) / 1000;
+                    return Some(retry_after as u64);
+                }
+            }
+        }
+
+        let requests_per_minute = class.requests_per_minute(opts);
+        if requests_per_minute == 0 {
+            return None;
+        }
+        self.buckets
+            .entry((*addr, class))
+            .or_insert_with(|| TokenBucket::new(requests_per_minute, now_ms))
+            .take(requests_per_minute, now_ms)
+    }
+
+    /// Charge the execution cost of a read-only call against the client's budget
+    pub fn charge_read_only_call(
+        &mut self,
+        opts: &ConnectionOptions,
+        addr: &IpAddr,
+        cost: &ExecutionCost,
+        now_ms: u128,
+    ) {
+        if opts.read_only_call_budget_interval == 0 {
+            return;
+        }
+        let budget = self
+            .cost_budgets
+            .entry(*addr)
+            .or_insert_with(|| CostBudget {
+                spent: ExecutionCost::zero(),
+                interval_start_ms: now_ms,
+            });
+        if now_ms >= RPCRateLimiter::budget_interval_end_ms(opts, budget) {
+            budget.spent = ExecutionCost::zero();
+            budget.interval_start_ms = now_ms;
+        }
+        if budget.spent.add(cost).is_err() {
+            budget.spent = ExecutionCost::max_value();
+        }
+    }
+
+    /// Forget clients whose buckets have refilled and whose budget intervals have passed, so
+    /// the limiter's memory use is bounded by the number of recently-active clients.
+    fn prune(&mut self, opts: &ConnectionOptions, now_ms: u128) {
+        if now_ms < self.last_prune_ms + RATE_LIMITER_PRUNE_INTERVAL_MS {
+            return;
+        }
+        self.last_prune_ms = now_ms;
+        self.buckets.retain(|_, bucket| !bucket.is_full(now_ms));
+        self.cost_budgets
+            .retain(|_, budget| now_ms < RPCRateLimiter::budget_interval_end_ms(opts, budget));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn rate_limited_opts() -> ConnectionOptions {
+        ConnectionOptions {
+            rpc_query_rate_limit: 60,
+            rpc_read_only_call_rate_limit: 6,
+            read_only_call_budget: ExecutionCost {
+                write_length: 0,
+                write_count: 0,
+                read_length: 1000,
+                read_count: 10,
+                runtime: 1000,
+            },
+            read_only_call_budget_interval: 60,
+            ..ConnectionOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_token_bucket() {
+        let opts = rate_limited_opts();
+        let mut limiter = RPCRateLimiter::default();
+        let client = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let other_client = IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8));
+        let now = 1_000_000;
+
+        // a client gets a minute's worth of requests up front
+        for _ in 0..6 {
+            assert_eq!(
+                limiter.check_request(&opts, &client, RPCEndpointClass::ReadOnlyCall, now),
+                None
+            );
+        }
+        // one token refills every 10 seconds
+        assert_eq!(
+            limiter.check_request(&opts, &client, RPCEndpointClass::ReadOnlyCall, now),
+            Some(10)
+        );
+        assert_eq!(
+            limiter.check_request(&opts, &client, RPCEndpointClass::ReadOnlyCall, now + 5_000),
+            Some(5)
+        );
+        assert_eq!(
+            limiter.check_request(&opts, &client, RPCEndpointClass::ReadOnlyCall, now + 10_000),
+            None
+        );
+
+        // other endpoint classes and other clients are unaffected
+        assert_eq!(
+            limiter.check_request(&opts, &client, RPCEndpointClass::Query, now + 10_000),
+            None
+        );
+        assert_eq!(
+            limiter.check_request(
+                &opts,
+                &other_client,
+                RPCEndpointClass::ReadOnlyCall,
+                now + 10_000
+            ),
+            None
+        );
+
+        // unlimited endpoint classes are never limited
+        for _ in 0..1000 {
+            assert_eq!(
+                limiter.check_request(&opts, &client, RPCEndpointClass::Submit, now),
+                None
+            );
+        }
+        assert!(!limiter
+            .buckets
+            .contains_key(&(client, RPCEndpointClass::Submit)));
+
+        // idle clients are forgotten once their buckets refill
+        limiter.check_request(&opts, &client, RPCEndpointClass::Query, now + 120_000);
+        assert!(!limiter
+            .buckets
+            .contains_key(&(other_client, RPCEndpointClass::ReadOnlyCall)));
+    }
+
+    #[test]
+    fn test_rate_limit_read_only_call_budget() {
+        let mut opts = rate_limited_opts();
+        opts.rpc_read_only_call_rate_limit = 0;
+        let mut limiter = RPCRateLimiter::default();
+        let client = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let now = 1_000_000;
+        let call_cost = ExecutionCost {
+            write_length: 0,
+            write_count: 0,
+            read_length: 100,
+            read_count: 4,
+            runtime: 100,
+        };
+
+        // the client can keep calling until it exceeds its budget in any dimension
+        for i in 0..3 {
+            assert_eq!(
+                limiter.check_request(&opts, &client, RPCEndpointClass::ReadOnlyCall, now + i),
+                None
+            );
+            limiter.charge_read_only_call(&opts, &client, &call_cost, now + i);
+        }
+        assert_eq!(
+            limiter.check_request(&opts, &client, RPCEndpointClass::ReadOnlyCall, now + 30_000),
+            Some(30)
+        );
+
+        // the budget doesn't apply to other endpoint classes
+        assert_eq!(
+            limiter.check_request(&opts, &client, RPCEndpointClass::Query, now + 30_000),
+            None
+        );
+
+        // the budget is replenished at the end of the interval
+        assert_eq!(
+            limiter.check_request(&opts, &client, RPCEndpointClass::ReadOnlyCall, now + 60_000),
+            None
+        );
+        limiter.charge_read_only_call(&opts, &client, &call_cost, now + 60_000);
+        assert_eq!(
+            limiter.check_request(&opts, &client, RPCEndpointClass::ReadOnlyCall, now + 60_001),
+            None
+        );
+    }
+}
@@ -17,6 +17,9 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -31,15 +34,20 @@ use rand::prelude::*;
 use rand::thread_rng;
 use rusqlite::{DatabaseName, NO_PARAMS};
 
-use crate::burnchains::affirmation::AffirmationMap;
+use crate::burnchains::affirmation::{
+    find_pox_anchor_block_candidates, AffirmationMap, SortitionForkHeaderReader,
+};
+use crate::burnchains::db::BurnchainDB;
 use crate::burnchains::Burnchain;
 use crate::burnchains::BurnchainView;
 use crate::burnchains::*;
 use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::burn::operations::BlockstackOperationType;
 use crate::chainstate::burn::ConsensusHash;
 use crate::chainstate::stacks::db::blocks::CheckError;
+use crate::chainstate::stacks::db::blocks::MemPoolRejection;
 use crate::chainstate::stacks::db::{
-    blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, StacksChainState, StreamCursor,
+    blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, AccountHistoryCursor, StacksChainState, StreamCursor,
 };
 use crate::chainstate::stacks::Error as chain_error;
 use crate::chainstate::stacks::*;
@@ -58,7 +66,9 @@ use crate::net::db::PeerDB;
 use crate::net::http::*;
 use crate::net::p2p::PeerMap;
 use crate::net::p2p::PeerNetwork;
+use crate::net::ratelimit::RPCEndpointClass;
 use crate::net::relay::Relayer;
+use crate::net::v3;
 use crate::net::BlocksDatum;
 use crate::net::Error as net_error;
 use crate::net::HttpRequestMetadata;
@@ -87,13 +97,29 @@ use crate::net::{
     AccountEntryResponse, AttachmentPage, CallReadOnlyResponse, ContractSrcResponse,
     DataVarResponse, GetAttachmentResponse, GetAttachmentsInvResponse, MapEntryResponse,
 };
+use crate::net::{AdminRequest, AdminToken, RPCAdminResult};
 use crate::net::{BlocksData, GetIsTraitImplementedResponse};
+use crate::net::{BurnOpsQuery, RPCBurnOpInfo, RPCBurnOpStacksResult};
 use crate::net::{ClientError, TipRequest};
+use crate::net::{ExtendedStacksHeader, RPCBlockCommitProof, RPCLightHeader, RPCLightSortition};
+use crate::net::{RPCAccountHistory, RPCAccountHistoryEntry};
 use crate::net::{
     RPCAffirmationData, RPCLastPoxAnchorData, RPCPeerInfoData, RPCPoxContractVersion,
     RPCPoxInfoData,
 };
+use crate::net::{
+    RPCAffirmationMapDivergence, RPCAffirmationMaps, RPCAffirmationMapsInfo,
+    RPCAffirmationRewardCycle, RPCAnchorBlockCandidate,
+};
+use crate::net::{
+    RPCBlockCommitPayouts, RPCBurnBlockPayouts, RPCPoxPayout, RPCRewardCycleAnchorBlock,
+    RPCRewardCycleInfo, RPCRewardSet, RPCRewardSetEntry,
+};
+use crate::net::{RPCErrorBody, RPCTransactionAccepted, V3Request, V3Response};
 use crate::net::{RPCNeighbor, RPCNeighborsInfo};
+use crate::net::{
+    RPCSortitionBlockCommit, RPCSortitionInfo, RPCSortitionLeaderKey, SortitionQuery,
+};
 use crate::util_lib::db::DBConn;
 use crate::util_lib::db::Error as db_error;
 use clarity::vm::database::clarity_store::make_contract_hash_key;
@@ -113,10 +139,12 @@ use clarity::vm::{
     types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
     ClarityName, ContractName, SymbolicExpression, Value,
 };
+use stacks_common::util::get_epoch_time_ms;
 use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::Hash160;
 use stacks_common::util::hash::{hex_bytes, to_hex};
 
+use crate::chainstate::stacks::address::PoxAddress;
 use crate::chainstate::stacks::boot::{POX_1_NAME, POX_2_NAME};
 use crate::chainstate::stacks::StacksBlockHeader;
 use crate::clarity_vm::database::marf::MarfedKV;
@@ -137,6 +165,33 @@ use super::{RPCPoxCurrentCycleInfo, RPCPoxNextCycleInfo};
 
 pub const STREAM_CHUNK_SIZE: u64 = 4096;
 
+/// Maximum number of operations returned by GET /v2/burn_ops/sender/:address
+pub const MAX_BURN_OPS_PER_SENDER: u64 = 100;
+
+/// Number of reward cycles, up to the current one, described by GET /v2/pox/affirmations
+pub const AFFIRMATION_MAPS_REWARD_CYCLES: u64 = 10;
+
+/// Number of transactions returned by GET /v2/accounts/:principal/history if no `limit` is given
+pub const ACCOUNT_HISTORY_DEFAULT_LIMIT: u64 = 20;
+
+/// Maximum `limit` for GET /v2/accounts/:principal/history
+pub const ACCOUNT_HISTORY_MAX_LIMIT: u64 = 50;
+
+/// Maximum number of headers returned by GET /v2/headers/light/:count
+pub const MAX_LIGHT_HEADERS: u64 = 100;
+
+/// Node-level operations exposed by the /admin RPC namespace which the networking
+/// stack cannot carry out by itself (they live in the node's run loop).
+pub trait AdminHandler {
+    /// Is the miner currently allowed to mine?  Returns None if this node is not a miner.
+    fn is_mining_enabled(&self) -> Option<bool>;
+    /// Pause or resume mining.  Fails if this node is not a miner.
+    fn set_mining_enabled(&self, enabled: bool) -> Result<(), String>;
+    /// Re-read the node's config file and apply the settings that can change at runtime.
+    /// Returns a human-readable description of what was applied.
+    fn reload_config(&self) -> Result<String, String>;
+}
+
 #[derive(Default)]
 pub struct RPCHandlerArgs<'a> {
     pub exit_at_block_height: Option<u64>,
@@ -145,6 +200,7 @@ pub struct RPCHandlerArgs<'a> {
     pub cost_estimator: Option<&'a dyn CostEstimator>,
     pub fee_estimator: Option<&'a dyn FeeEstimator>,
     pub cost_metric: Option<&'a dyn CostMetric>,
+    pub admin_handler: Option<&'a dyn AdminHandler>,
 }
 
 pub struct ConversationHttp {
@@ -174,6 +230,10 @@ pub struct ConversationHttp {
     pending_request: Option<ReplyHandleHttp>,
     pending_response: Option<HttpResponseType>,
     pending_error_response: Option<HttpResponseType>,
+
+    // if set, this conversation was accepted on the admin listener, and requests must present
+    // this bearer token
+    admin_token: Option<AdminToken>,
 }
 
 impl fmt::Display for ConversationHttp {
@@ -525,6 +585,613 @@ impl RPCPoxInfoData {
     }
 }
 
+impl RPCSortitionInfo {
+    /// Load a sortition's snapshot, block-commits and leader keys from the sortition DB.
+    /// Burn heights and burn header hashes are resolved against the canonical burnchain fork;
+    /// consensus hashes identify a snapshot on any fork.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        query: &SortitionQuery,
+    ) -> Result<RPCSortitionInfo, net_error> {
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let snapshot = match query {
+            SortitionQuery::LatestSortition => Some(tip.clone()),
+            SortitionQuery::BurnHeight(height) => match height.cmp(&tip.block_height) {
+                Ordering::Equal => Some(tip.clone()),
+                Ordering::Greater => None,
+                Ordering::Less => sortdb
+                    .index_handle(&tip.sortition_id)
+                    .get_block_snapshot_by_height(*height)?,
+            },
+            SortitionQuery::ConsensusHash(consensus_hash) => {
+                SortitionDB::get_block_snapshot_consensus(sortdb.conn(), consensus_hash)?
+            }
+            SortitionQuery::BurnHeaderHash(burn_header_hash) => sortdb
+                .index_handle(&tip.sortition_id)
+                .get_block_snapshot(burn_header_hash)?,
+        }
+        .ok_or(net_error::NotFoundError)?;
+
+        let canonical = if snapshot.block_height == tip.block_height {
+            snapshot.sortition_id == tip.sortition_id
+        } else {
+            sortdb
+                .index_handle(&tip.sortition_id)
+                .get_block_snapshot_by_height(snapshot.block_height)?
+                .map(|sn| sn.sortition_id == snapshot.sortition_id)
+                .unwrap_or(false)
+        };
+
+        let handle = sortdb.index_handle(&snapshot.sortition_id);
+        let mut block_commits = vec![];
+        let mut block_commits_burn = 0u64;
+        let mut vrf_seed = None;
+        for commit in
+            SortitionDB::get_block_commits_by_block(sortdb.conn(), &snapshot.sortition_id)?
+        {
+            let won = snapshot.sortition && commit.txid == snapshot.winning_block_txid;
+            if won {
+                vrf_seed = Some(commit.new_seed);
+            }
+            block_commits_burn = block_commits_burn.saturating_add(commit.burn_fee);
+
+            let vrf_public_key = handle
+                .get_leader_key_at(commit.key_block_ptr as u64, commit.key_vtxindex as u32)?
+                .map(|key| key.public_key.to_hex());
+
+            block_commits.push(RPCSortitionBlockCommit {
+                txid: commit.txid,
+                vtxindex: commit.vtxindex,
+                block_header_hash: commit.block_header_hash,
+                burn_fee: commit.burn_fee,
+                sunset_burn: commit.sunset_burn,
+                vrf_seed: commit.new_seed,
+                vrf_public_key,
+                parent_block_ptr: commit.parent_block_ptr,
+                parent_vtxindex: commit.parent_vtxindex,
+                key_block_ptr: commit.key_block_ptr,
+                key_vtxindex: commit.key_vtxindex,
+                apparent_sender: commit.apparent_sender.to_string(),
+                commit_outs: commit
+                    .commit_outs
+                    .into_iter()
+                    .map(|addr| addr.to_b58())
+                    .collect(),
+                won,
+            });
+        }
+
+        let leader_keys =
+            SortitionDB::get_leader_keys_by_block(sortdb.conn(), &snapshot.sortition_id)?
+                .into_iter()
+                .map(|key| RPCSortitionLeaderKey {
+                    txid: key.txid,
+                    vtxindex: key.vtxindex,
+                    consensus_hash: key.consensus_hash,
+                    vrf_public_key: key.public_key.to_hex(),
+                })
+                .collect();
+
+        let (winning_block_txid, winning_stacks_block_hash) = if snapshot.sortition {
+            (
+                Some(snapshot.winning_block_txid),
+                Some(snapshot.winning_stacks_block_hash),
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(RPCSortitionInfo {
+            burn_block_height: snapshot.block_height,
+            burn_header_hash: snapshot.burn_header_hash.to_hex(),
+            parent_burn_header_hash: snapshot.parent_burn_header_hash.to_hex(),
+            burn_header_timestamp: snapshot.burn_header_timestamp,
+            consensus_hash: snapshot.consensus_hash,
+            sortition_id: snapshot.sortition_id.to_hex(),
+            parent_sortition_id: snapshot.parent_sortition_id.to_hex(),
+            pox_valid: snapshot.pox_valid,
+            canonical,
+            sortition: snapshot.sortition,
+            total_burn: snapshot.total_burn,
+            block_commits_burn,
+            sortition_hash: snapshot.sortition_hash.to_hex(),
+            winning_block_txid,
+            winning_stacks_block_hash,
+            vrf_seed,
+            block_commits,
+            leader_keys,
+        })
+    }
+}
+
+impl RPCRewardCycleInfo {
+    /// Load a reward cycle's anchor block and reward set, and the PoX payouts made by the
+    /// block-commits in each of its burnchain blocks on the canonical burnchain fork.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        burnchain: &Burnchain,
+        reward_cycle: u64,
+    ) -> Result<RPCRewardCycleInfo, net_error> {
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let reward_cycle_length = burnchain.pox_constants.reward_cycle_length as u64;
+        let prepare_length = burnchain.pox_constants.prepare_length as u64;
+        let first_burn_block_height = burnchain.reward_cycle_to_block_height(reward_cycle);
+        let last_burn_block_height = first_burn_block_height + reward_cycle_length - 1;
+        let prepare_phase_start_burn_block_height =
+            first_burn_block_height + reward_cycle_length - prepare_length;
+
+        if first_burn_block_height > tip.block_height {
+            return Err(net_error::NotFoundError);
+        }
+
+        let ic = sortdb.index_handle(&tip.sortition_id);
+        let mut snapshots = vec![];
+        for height in first_burn_block_height..=last_burn_block_height.min(tip.block_height) {
+            let snapshot = if height == tip.block_height {
+                tip.clone()
+            } else {
+                ic.get_block_snapshot_by_height(height)?
+                    .ok_or(net_error::NotFoundError)?
+            };
+            snapshots.push(snapshot);
+        }
+
+        // the anchor block is recorded in the sortition that starts the reward cycle
+        let start_handle = sortdb.index_handle(&snapshots[0].sortition_id);
+        let anchor_block = match (
+            start_handle.get_last_anchor_block_hash()?,
+            start_handle.get_last_anchor_block_txid()?,
+        ) {
+            (Some(anchor_block_hash), Some(anchor_block_txid)) => {
+                let consensus_hash = SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                    &sortdb.index_conn(),
+                    &snapshots[0].sortition_id,
+                    &anchor_block_hash,
+                )?
+                .map(|sn| sn.consensus_hash);
+                let processed = match consensus_hash {
+                    Some(ref consensus_hash) => StacksChainState::is_stacks_block_processed(
+                        chainstate.db(),
+                        consensus_hash,
+                        &anchor_block_hash,
+                    )?,
+                    None => false,
+                };
+                Some(RPCRewardCycleAnchorBlock {
+                    anchor_block_hash,
+                    anchor_block_txid,
+                    consensus_hash,
+                    processed,
+                })
+            }
+            _ => None,
+        };
+
+        let reward_set = match anchor_block {
+            Some(RPCRewardCycleAnchorBlock {
+                ref anchor_block_hash,
+                consensus_hash: Some(ref consensus_hash),
+                processed: true,
+                ..
+            }) => {
+                let block_id = StacksBlockId::new(consensus_hash, anchor_block_hash);
+                Some(RPCRewardCycleInfo::load_reward_set(
+                    sortdb,
+                    chainstate,
+                    burnchain,
+                    first_burn_block_height,
+                    &block_id,
+                )?)
+            }
+            _ => None,
+        };
+
+        let sortdb_conn = sortdb.index_conn();
+        let mut payouts = vec![];
+        let mut total_payouts: BTreeMap<String, u64> = BTreeMap::new();
+        let mut total_burnt = 0u64;
+        for snapshot in snapshots.iter() {
+            let (expected_recipients, _) =
+                sortdb_conn.get_reward_set_payouts_at(&snapshot.sortition_id)?;
+            let mut block_commits = vec![];
+            for commit in
+                SortitionDB::get_block_commits_by_block(sortdb.conn(), &snapshot.sortition_id)?
+            {
+                let mut burnt = 0u64;
+                let mut commit_payouts = vec![];
+                if commit.commit_outs.is_empty() {
+                    burnt = commit.burn_fee;
+                } else {
+                    let amount = commit.burn_fee / (commit.commit_outs.len() as u64);
+                    for pox_addr in commit.commit_outs.into_iter() {
+                        if pox_addr.is_burn() {
+                            burnt += amount;
+                        } else {
+                            let pox_address = pox_addr.to_b58();
+                            *total_payouts.entry(pox_address.clone()).or_insert(0) += amount;
+                            commit_payouts.push(RPCPoxPayout {
+                                pox_address,
+                                amount,
+                            });
+                        }
+                    }
+                }
+                total_burnt += burnt;
+                block_commits.push(RPCBlockCommitPayouts {
+                    txid: commit.txid,
+                    apparent_sender: commit.apparent_sender.to_string(),
+                    won: snapshot.sortition && commit.txid == snapshot.winning_block_txid,
+                    burnt,
+                    payouts: commit_payouts,
+                });
+            }
+            payouts.push(RPCBurnBlockPayouts {
+                burn_block_height: snapshot.block_height,
+                burn_header_hash: snapshot.burn_header_hash.to_hex(),
+                expected_recipients: expected_recipients
+                    .into_iter()
+                    .map(|addr| addr.to_b58())
+                    .collect(),
+                block_commits,
+            });
+        }
+
+        Ok(RPCRewardCycleInfo {
+            reward_cycle,
+            first_burn_block_height,
+            last_burn_block_height,
+            prepare_phase_start_burn_block_height,
+            anchor_block,
+            reward_set,
+            payouts,
+            total_payouts: total_payouts
+                .into_iter()
+                .map(|(pox_address, amount)| RPCPoxPayout {
+                    pox_address,
+                    amount,
+                })
+                .collect(),
+            total_burnt,
+        })
+    }
+
+    /// Compute the reward set chosen by the given anchor block, along with how much each
+    /// address stacked.  This follows `OnChainRewardSetProvider::get_reward_set()`.
+    fn load_reward_set(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        burnchain: &Burnchain,
+        first_burn_block_height: u64,
+        block_id: &StacksBlockId,
+    ) -> Result<RPCRewardSet, net_error> {
+        let registered_addrs = chainstate.get_reward_addresses(
+            burnchain,
+            sortdb,
+            first_burn_block_height,
+            block_id,
+        )?;
+        let liquid_ustx = chainstate.get_liquid_ustx(block_id);
+        let (threshold, participation) = StacksChainState::get_reward_threshold_and_participation(
+            &burnchain.pox_constants,
+            &registered_addrs[..],
+            liquid_ustx,
+        );
+
+        let mut stacked: HashMap<PoxAddress, u128> = HashMap::new();
+        for entry in registered_addrs.iter() {
+            *stacked.entry(entry.reward_address.clone()).or_insert(0) += entry.amount_stacked;
+        }
+
+        let rewarded_addresses = if burnchain
+            .pox_constants
+            .enough_participation(participation, liquid_ustx)
+        {
+            let epoch = SortitionDB::get_stacks_epoch(sortdb.conn(), first_burn_block_height)?
+                .ok_or(net_error::ChainstateError(format!(
+                    "No epoch defined at burn height {}",
+                    first_burn_block_height
+                )))?;
+            StacksChainState::make_reward_set(threshold, registered_addrs, epoch.epoch_id)
+                .rewarded_addresses
+        } else {
+            vec![]
+        };
+
+        // each of an address's slots is a separate entry in the reward set
+        let mut entries: Vec<RPCRewardSetEntry> = vec![];
+        let mut entry_indexes: HashMap<PoxAddress, usize> = HashMap::new();
+        for pox_addr in rewarded_addresses.iter() {
+            if let Some(ix) = entry_indexes.get(pox_addr) {
+                entries[*ix].slots += 1;
+            } else {
+                entry_indexes.insert(pox_addr.clone(), entries.len());
+                entries.push(RPCRewardSetEntry {
+                    pox_address: pox_addr.clone().to_b58(),
+                    slots: 1,
+                    stacked_ustx: stacked.get(pox_addr).cloned().unwrap_or(0) as u64,
+                });
+            }
+        }
+
+        Ok(RPCRewardSet {
+            threshold_ustx: threshold as u64,
+            total_stacked_ustx: participation as u64,
+            total_slots: rewarded_addresses.len() as u64,
+            entries,
+        })
+    }
+}
+
+impl RPCBurnOpInfo {
+    /// Load the recorded burnchain-hosted Stacks operations that match a query, along with
+    /// their outcome in each Stacks block that applied them.  Looking up an unknown txid is an
+    /// error; a sender with no operations simply has none.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        query: &BurnOpsQuery,
+    ) -> Result<Vec<RPCBurnOpInfo>, net_error> {
+        let records = match query {
+            BurnOpsQuery::Txid(txid) => {
+                let records = SortitionDB::get_stacks_op_records_by_txid(sortdb.conn(), txid)?;
+                if records.is_empty() {
+                    return Err(net_error::NotFoundError);
+                }
+                records
+            }
+            BurnOpsQuery::Sender(sender) => SortitionDB::get_stacks_op_records_by_sender(
+                sortdb.conn(),
+                sender,
+                MAX_BURN_OPS_PER_SENDER,
+            )?,
+        };
+
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let ic = sortdb.index_handle(&tip.sortition_id);
+        let stacks_tip = StacksBlockHeader::make_index_block_hash(
+            &tip.canonical_stacks_tip_consensus_hash,
+            &tip.canonical_stacks_tip_hash,
+        );
+        let index_conn = chainstate.index_conn()?;
+
+        let mut burn_ops = vec![];
+        for record in records.into_iter() {
+            let txid = record.txid;
+            let burn_header_hash = record.burn_header_hash;
+            let canonical = ic.get_block_snapshot(&burn_header_hash)?.is_some();
+
+            let mut stacks_results = vec![];
+            for result in StacksChainState::get_burnchain_op_results(chainstate.db(), &txid)? {
+                let canonical = index_conn
+                    .get_ancestor_block_height(&result.index_block_hash, &stacks_tip)?
+                    .is_some();
+                stacks_results.push(RPCBurnOpStacksResult {
+                    index_block_hash: result.index_block_hash,
+                    stacks_block_height: result.stacks_block_height,
+                    canonical,
+                    committed: result.committed,
+                    result: result
+                        .result
+                        .map(|value| format!("0x{}", value.serialize())),
+                });
+            }
+
+            burn_ops.push(RPCBurnOpInfo {
+                txid,
+                vtxindex: record.vtxindex,
+                burn_block_height: record.block_height,
+                burn_header_hash,
+                op_type: record.op_type,
+                sender: record.sender.map(|sender| sender.to_string()),
+                canonical,
+                accepted: record.rejection_reason.is_none(),
+                rejection_reason: record.rejection_reason,
+                op: record.op,
+                stacks_results,
+            });
+        }
+        Ok(burn_ops)
+    }
+}
+
+impl RPCAffirmationMaps {
+    fn named(&self) -> [(&'static str, &AffirmationMap); 4] {
+        [
+            ("heaviest", &self.heaviest),
+            ("canonical", &self.canonical),
+            ("stacks_tip", &self.stacks_tip),
+            ("sortition_tip", &self.sortition_tip),
+        ]
+    }
+
+    /// Find the first reward cycle in which each pair of affirmation maps disagree.  Like the
+    /// chains coordinator, this only considers the reward cycles that both maps cover.
+    pub fn find_divergences(&self) -> Vec<RPCAffirmationMapDivergence> {
+        let named = self.named();
+        let mut divergences = vec![];
+        for (i, (left, left_am)) in named.iter().enumerate() {
+            for (right, right_am) in named.iter().skip(i + 1) {
+                let divergence_opt = if left_am.len() <= right_am.len() {
+                    right_am.find_divergence(left_am)
+                } else {
+                    left_am.find_divergence(right_am)
+                };
+                if let Some(reward_cycle) = divergence_opt {
+                    divergences.push(RPCAffirmationMapDivergence {
+                        left: left.to_string(),
+                        right: right.to_string(),
+                        reward_cycle,
+                    });
+                }
+            }
+        }
+        divergences
+    }
+}
+
+impl RPCAffirmationMapsInfo {
+    /// Describe a node's affirmation maps over the given range of reward cycles.  For each
+    /// reward cycle, this reports what each map affirms about the anchor block chosen in its
+    /// prepare phase, and replays anchor block selection over the canonical burnchain fork to
+    /// list every block-commit the prepare phase confirmed.
+    /// Returns NotFoundError if the first reward cycle has not started.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        burnchain_db: &BurnchainDB,
+        burnchain: &Burnchain,
+        affirmation_maps: RPCAffirmationMaps,
+        first_reward_cycle: u64,
+        last_reward_cycle: u64,
+    ) -> Result<RPCAffirmationMapsInfo, net_error> {
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let tip_reward_cycle = burnchain
+            .block_height_to_reward_cycle(tip.block_height)
+            .unwrap_or(0);
+        if first_reward_cycle > tip_reward_cycle {
+            return Err(net_error::NotFoundError);
+        }
+
+        let indexer = SortitionForkHeaderReader::new(burnchain_db, sortdb, tip);
+        let mut reward_cycles = vec![];
+        for reward_cycle in first_reward_cycle..=cmp::min(last_reward_cycle, tip_reward_cycle) {
+            let selected = BurnchainDB::get_canonical_anchor_block_commit_metadata(
+                burnchain_db.conn(),
+                &indexer,
+                reward_cycle + 1,
+            )?
+            .map(|metadata| (metadata.block_height, metadata.vtxindex));
+
+            let mut anchor_block_candidates = vec![];
+            for candidate in find_pox_anchor_block_candidates(
+                burnchain_db.conn(),
+                reward_cycle,
+                &indexer,
+                burnchain,
+            )? {
+                let commit_opt = BurnchainDB::get_commit_at(
+                    burnchain_db.conn(),
+                    &indexer,
+                    candidate.block_height as u32,
+                    candidate.vtxindex as u16,
+                )?;
+                anchor_block_candidates.push(RPCAnchorBlockCandidate {
+                    burn_block_height: candidate.block_height,
+                    vtxindex: candidate.vtxindex,
+                    txid: commit_opt.as_ref().map(|commit| commit.txid),
+                    block_header_hash: commit_opt.map(|commit| commit.block_header_hash),
+                    confirmations: candidate.confs,
+                    burnt: candidate.burnt,
+                    selected: selected == Some((candidate.block_height, candidate.vtxindex)),
+                });
+            }
+
+            let entry_at = |am: &AffirmationMap| am.at(reward_cycle).map(|entry| entry.to_string());
+            reward_cycles.push(RPCAffirmationRewardCycle {
+                reward_cycle,
+                heaviest: entry_at(&affirmation_maps.heaviest),
+                canonical: entry_at(&affirmation_maps.canonical),
+                stacks_tip: entry_at(&affirmation_maps.stacks_tip),
+                sortition_tip: entry_at(&affirmation_maps.sortition_tip),
+                anchor_block_candidates,
+            });
+        }
+
+        Ok(RPCAffirmationMapsInfo {
+            reward_cycle: tip_reward_cycle,
+            anchor_threshold: burnchain.pox_constants.anchor_threshold,
+            divergences: affirmation_maps.find_divergences(),
+            affirmation_maps,
+            reward_cycles,
+        })
+    }
+}
+
+impl RPCLightHeader {
+    /// Load up to `quantity` Stacks block headers, starting at `tip` and walking back towards
+    /// genesis, along with the sortition that selected each block, the header of the Bitcoin
+    /// block that sortition happened in, and the proof that its winning block-commit was mined.
+    /// Returns NotFoundError if the tip is not known.
+    pub fn from_db(
+        chainstate: &StacksChainState,
+        sortdb: &SortitionDB,
+        burnchain_db: &BurnchainDB,
+        tip: &StacksBlockId,
+        quantity: u64,
+    ) -> Result<Vec<RPCLightHeader>, net_error> {
+        let mut headers = vec![];
+        let mut index_block_hash = tip.clone();
+        while (headers.len() as u64) < quantity {
+            let header_info = StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                chainstate.db(),
+                &index_block_hash,
+            )?
+            .ok_or(net_error::NotFoundError)?;
+
+            // the genesis block was not chosen by a sortition
+            if header_info.stacks_block_height == 0 {
+                break;
+            }
+
+            let parent_block_id =
+                StacksChainState::get_parent_block_id(chainstate.db(), &index_block_hash)?
+                    .ok_or(net_error::NotFoundError)?;
+            let snapshot = SortitionDB::get_block_snapshot_consensus(
+                sortdb.conn(),
+                &header_info.consensus_hash,
+            )?
+            .ok_or(net_error::NotFoundError)?;
+
+            let burn_block_header = BurnchainDB::get_bitcoin_raw_header(
+                burnchain_db.conn(),
+                &snapshot.burn_header_hash,
+            )?
+            .map(|raw_header| to_hex(&raw_header));
+            let block_commit_proof = BurnchainDB::get_bitcoin_tx_merkle_proof(
+                burnchain_db.conn(),
+                &snapshot.burn_header_hash,
+                &snapshot.winning_block_txid,
+            )?
+            .map(|proof| RPCBlockCommitProof {
+                txid: proof.txid,
+                vtxindex: proof.vtxindex,
+                raw_tx: to_hex(&proof.raw_tx),
+                merkle_branch: proof
+                    .merkle_branch
+                    .iter()
+                    .map(|hash| to_hex(hash))
+                    .collect(),
+            });
+
+            headers.push(RPCLightHeader {
+                index_block_hash: index_block_hash,
+                block_height: header_info.stacks_block_height,
+                header: ExtendedStacksHeader {
+                    consensus_hash: header_info.consensus_hash,
+                    header: header_info.anchored_header,
+                    parent_block_id: parent_block_id.clone(),
+                },
+                sortition: RPCLightSortition {
+                    burn_block_height: snapshot.block_height,
+                    burn_header_hash: snapshot.burn_header_hash.to_hex(),
+                    parent_burn_header_hash: snapshot.parent_burn_header_hash.to_hex(),
+                    consensus_hash: snapshot.consensus_hash,
+                    sortition_id: snapshot.sortition_id.to_hex(),
+                    ops_hash: snapshot.ops_hash.to_hex(),
+                    sortition_hash: snapshot.sortition_hash.to_hex(),
+                    total_burn: snapshot.total_burn,
+                    winning_block_txid: snapshot.winning_block_txid,
+                    winning_stacks_block_hash: snapshot.winning_stacks_block_hash,
+                },
+                burn_block_header,
+                block_commit_proof,
+            });
+            index_block_hash = parent_block_id;
+        }
+        Ok(headers)
+    }
+}
+
 impl RPCNeighborsInfo {
     /// Load neighbor address information from the peer network
     pub fn from_p2p(
@@ -625,9 +1292,21 @@ impl ConversationHttp {
             last_request_timestamp: 0,
             last_response_timestamp: 0,
             connection_time: get_epoch_time_secs(),
+            admin_token: None,
         }
     }
 
+    /// Mark this conversation as having been accepted on the admin listener.  Only /admin
+    /// requests bearing the given token will be served on it.
+    pub fn set_admin_token(&mut self, token: AdminToken) {
+        self.admin_token = Some(token);
+    }
+
+    /// Is this a conversation on the admin listener?
+    pub fn is_admin(&self) -> bool {
+        self.admin_token.is_some()
+    }
+
     /// How many ongoing requests do we have on this conversation?
     pub fn num_pending_outbound(&self) -> usize {
         self.reply_streams.len()
@@ -880,76 +1559,548 @@ impl ConversationHttp {
         }
     }
 
-    /// Handle a GET neighbors
+    /// Handle a GET sortition.
     /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
-    fn handle_getneighbors<W: Write>(
+    fn handle_getsortition<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
-        network: &PeerNetwork,
+        sortdb: &SortitionDB,
+        query: &SortitionQuery,
         canonical_stacks_tip_height: u64,
     ) -> Result<(), net_error> {
-        let epoch = network.get_current_epoch();
-
         let response_metadata =
             HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
-        let neighbor_data = RPCNeighborsInfo::from_p2p(
-            network.local_peer.network_id,
-            epoch.network_epoch,
-            &network.peers,
-            &network.chain_view,
-            &network.peerdb,
-        )?;
-        let response = HttpResponseType::Neighbors(response_metadata, neighbor_data);
-        response.send(http, fd)
-    }
 
-    /// Handle a not-found
-    fn handle_notfound<W: Write>(
-        http: &mut StacksHttp,
-        fd: &mut W,
-        response_metadata: HttpResponseMetadata,
-        msg: String,
-    ) -> Result<Option<StreamCursor>, net_error> {
-        let response = HttpResponseType::NotFound(response_metadata, msg);
-        return response.send(http, fd).and_then(|_| Ok(None));
+        match RPCSortitionInfo::from_db(sortdb, query) {
+            Ok(sortition_info) => {
+                let response = HttpResponseType::GetSortition(response_metadata, sortition_info);
+                response.send(http, fd)
+            }
+            Err(net_error::NotFoundError) => {
+                debug!("Sortition not found: {:?}", query);
+                let response =
+                    HttpResponseType::NotFound(response_metadata, "No such sortition".to_string());
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to load sortition {:?}: {:?}", query, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query sortition".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
     }
 
-    /// Handle a server error
-    fn handle_server_error<W: Write>(
+    /// Handle a GET reward cycle.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getrewardcycle<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
-        response_metadata: HttpResponseMetadata,
-        msg: String,
-    ) -> Result<Option<StreamCursor>, net_error> {
-        // oops
-        warn!("{}", &msg);
-        let response = HttpResponseType::ServerError(response_metadata, msg);
-        return response.send(http, fd).and_then(|_| Ok(None));
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        network: &PeerNetwork,
+        reward_cycle: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from_http_request_type(
+            req,
+            Some(network.burnchain_tip.canonical_stacks_tip_height),
+        );
+
+        match RPCRewardCycleInfo::from_db(sortdb, chainstate, &network.burnchain, reward_cycle) {
+            Ok(reward_cycle_info) => {
+                let response =
+                    HttpResponseType::GetRewardCycle(response_metadata, reward_cycle_info);
+                response.send(http, fd)
+            }
+            Err(net_error::NotFoundError) => {
+                debug!("Reward cycle {} has not started", reward_cycle);
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "Reward cycle has not started".to_string(),
+                );
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to load reward cycle {}: {:?}", reward_cycle, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query reward cycle".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
     }
 
-    /// Handle a GET headers. Start streaming the reply.
-    /// The response's preamble (but not the headers list) will be synchronously written to the fd
-    /// (so use a fd that can buffer!)
-    /// Return a StreamCursor struct for the reward cycle we're sending, so we can continue to
-    /// make progress sending it
-    fn handle_getheaders<W: Write>(
+    /// Handle a GET affirmation maps.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getaffirmationmaps<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
-        tip: &StacksBlockId,
-        quantity: u64,
-        chainstate: &StacksChainState,
-        canonical_stacks_tip_height: u64,
-    ) -> Result<Option<StreamCursor>, net_error> {
-        let response_metadata =
-            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
-        if quantity > (MAX_HEADERS as u64) {
-            // bad request
-            let response = HttpResponseType::BadRequestJSON(
-                response_metadata,
-                serde_json::Value::String(format!(
-                    "Invalid request: requested more than {} headers",
+        sortdb: &SortitionDB,
+        network: &PeerNetwork,
+        reward_cycle_opt: Option<u64>,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from_http_request_type(
+            req,
+            Some(network.burnchain_tip.canonical_stacks_tip_height),
+        );
+
+        let (first_reward_cycle, last_reward_cycle) = match reward_cycle_opt {
+            Some(reward_cycle) => (reward_cycle, reward_cycle),
+            None => {
+                let tip_reward_cycle = network
+                    .burnchain
+                    .block_height_to_reward_cycle(network.burnchain_tip.block_height)
+                    .unwrap_or(0);
+                (
+                    (tip_reward_cycle + 1).saturating_sub(AFFIRMATION_MAPS_REWARD_CYCLES),
+                    tip_reward_cycle,
+                )
+            }
+        };
+        let affirmation_maps = RPCAffirmationMaps {
+            heaviest: network.heaviest_affirmation_map.clone(),
+            canonical: network.tentative_best_affirmation_map.clone(),
+            stacks_tip: network.stacks_tip_affirmation_map.clone(),
+            sortition_tip: network.sortition_tip_affirmation_map.clone(),
+        };
+
+        let info_res = network
+            .burnchain
+            .open_burnchain_db(false)
+            .map_err(net_error::from)
+            .and_then(|burnchain_db| {
+                RPCAffirmationMapsInfo::from_db(
+                    sortdb,
+                    &burnchain_db,
+                    &network.burnchain,
+                    affirmation_maps,
+                    first_reward_cycle,
+                    last_reward_cycle,
+                )
+            });
+
+        match info_res {
+            Ok(affirmation_maps_info) => {
+                let response =
+                    HttpResponseType::GetAffirmationMaps(response_metadata, affirmation_maps_info);
+                response.send(http, fd)
+            }
+            Err(net_error::NotFoundError) => {
+                debug!("Reward cycle {} has not started", first_reward_cycle);
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "Reward cycle has not started".to_string(),
+                );
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to load affirmation maps for reward cycles {}-{}: {:?}",
+                    first_reward_cycle, last_reward_cycle, &e
+                );
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query affirmation maps".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    /// Decide whether or not a request may be served on this conversation.  /admin requests are
+    /// only served on the admin listener, and only if they present its bearer token; nothing
+    /// else is served on the admin listener.  Returns the error response to send if the request
+    /// is refused.
+    fn check_admin_access(
+        &self,
+        req: &HttpRequestType,
+        canonical_stacks_tip_height: u64,
+    ) -> Option<HttpResponseType> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        match (req, self.admin_token.as_ref()) {
+            (HttpRequestType::ClientError(..), _) => None,
+            (HttpRequestType::Admin(_, ref presented, _), Some(token)) => {
+                match presented.as_ref() {
+                    Some(presented) if token.matches(presented) => None,
+                    _ => Some(HttpResponseType::Unauthorized(
+                        response_metadata,
+                        "Missing or invalid admin token".to_string(),
+                    )),
+                }
+            }
+            (HttpRequestType::Admin(..), None) | (_, Some(_)) => Some(HttpResponseType::NotFound(
+                response_metadata,
+                format!("No such endpoint: {}", req.request_path()),
+            )),
+            (_, None) => None,
+        }
+    }
+
+    /// Handle an /admin request.  The caller must have already checked its bearer token.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_admin<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        admin_req: &AdminRequest,
+        network: &mut PeerNetwork,
+        mempool: &mut MemPoolDB,
+        handler_args: &RPCHandlerArgs,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from_http_request_type(
+            req,
+            Some(network.burnchain_tip.canonical_stacks_tip_height),
+        );
+        let result = match admin_req {
+            AdminRequest::BanPeer(addr, duration) => {
+                // deadlines are stored as signed 64-bit integers
+                let banned_until = cmp::min(
+                    get_epoch_time_secs().saturating_add(*duration),
+                    i64::MAX as u64,
+                );
+                match network.ban_peer_until(addr, banned_until) {
+                    Ok(num_closed) => Ok(RPCAdminResult {
+                        banned_until: Some(banned_until),
+                        ..RPCAdminResult::new(format!(
+                            "Banned {} until {}; closing {} connection(s)",
+                            addr, banned_until, num_closed
+                        ))
+                    }),
+                    Err(e) => {
+                        warn!("Failed to ban {}: {:?}", addr, &e);
+                        Err(HttpResponseType::ServerError(
+                            response_metadata.clone(),
+                            "Failed to ban peer".to_string(),
+                        ))
+                    }
+                }
+            }
+            AdminRequest::AddBootstrapPeer(public_key, addr) => {
+                match network.add_bootstrap_peer(public_key, addr) {
+                    Ok(()) => Ok(RPCAdminResult::new(format!(
+                        "Added bootstrap peer {}@{}",
+                        public_key.to_hex(),
+                        addr
+                    ))),
+                    Err(e) => {
+                        warn!("Failed to add bootstrap peer {}: {:?}", addr, &e);
+                        Err(HttpResponseType::ServerError(
+                            response_metadata.clone(),
+                            "Failed to add bootstrap peer".to_string(),
+                        ))
+                    }
+                }
+            }
+            AdminRequest::DropMempoolTxs(txids) => match mempool.drop_and_blacklist_txs(txids) {
+                Ok(()) => Ok(RPCAdminResult::new(format!(
+                    "Dropped and blacklisted {} transaction(s)",
+                    txids.len()
+                ))),
+                Err(e) => {
+                    warn!("Failed to drop mempool transactions: {:?}", &e);
+                    Err(HttpResponseType::ServerError(
+                        response_metadata.clone(),
+                        "Failed to drop mempool transactions".to_string(),
+                    ))
+                }
+            },
+            AdminRequest::GetMining => match handler_args.admin_handler {
+                Some(handler) => {
+                    let mining = handler.is_mining_enabled();
+                    let message = match mining {
+                        Some(true) => "Mining is enabled",
+                        Some(false) => "Mining is paused",
+                        None => "This node is not a miner",
+                    };
+                    Ok(RPCAdminResult {
+                        mining: Some(mining.unwrap_or(false)),
+                        ..RPCAdminResult::new(message.to_string())
+                    })
+                }
+                None => Err(HttpResponseType::ServiceUnavailable(
+                    response_metadata.clone(),
+                    "Mining control is not available on this node".to_string(),
+                )),
+            },
+            AdminRequest::SetMining(enabled) => match handler_args.admin_handler {
+                Some(handler) => match handler.set_mining_enabled(*enabled) {
+                    Ok(()) => Ok(RPCAdminResult {
+                        mining: Some(*enabled),
+                        ..RPCAdminResult::new(
+                            if *enabled {
+                                "Mining resumed"
+                            } else {
+                                "Mining paused"
+                            }
+                            .to_string(),
+                        )
+                    }),
+                    Err(msg) => Err(HttpResponseType::BadRequest(response_metadata.clone(), msg)),
+                },
+                None => Err(HttpResponseType::ServiceUnavailable(
+                    response_metadata.clone(),
+                    "Mining control is not available on this node".to_string(),
+                )),
+            },
+            AdminRequest::ReloadConfig => match handler_args.admin_handler {
+                Some(handler) => match handler.reload_config() {
+                    Ok(msg) => Ok(RPCAdminResult::new(msg)),
+                    Err(msg) => Err(HttpResponseType::ServerError(
+                        response_metadata.clone(),
+                        msg,
+                    )),
+                },
+                None => Err(HttpResponseType::ServiceUnavailable(
+                    response_metadata.clone(),
+                    "Config reload is not available on this node".to_string(),
+                )),
+            },
+        };
+        let response = match result {
+            Ok(result) => {
+                info!("Admin request {}: {}", req.request_path(), &result.message);
+                HttpResponseType::AdminResult(response_metadata, result)
+            }
+            Err(response) => response,
+        };
+        response.send(http, fd)
+    }
+
+    /// Resolve a /v3 request's chain tip, as `handle_load_stacks_chain_tip()` does.
+    fn load_v3_chain_tip(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip_req: &TipRequest,
+    ) -> Result<StacksBlockId, RPCErrorBody> {
+        if let TipRequest::SpecificTip(tip) = tip_req {
+            return Ok(*tip);
+        }
+        if let TipRequest::UseLatestUnconfirmedTip = tip_req {
+            if let Some(unconfirmed_state) = chainstate.unconfirmed_state.as_mut() {
+                match unconfirmed_state.get_unconfirmed_state_if_exists() {
+                    Ok(Some(unconfirmed_tip)) => return Ok(unconfirmed_tip),
+                    Ok(None) => {}
+                    Err(msg) => return Err(RPCErrorBody::not_found(&msg)),
+                }
+            }
+        }
+        match chainstate.get_stacks_chain_tip(sortdb) {
+            Ok(Some(tip)) => Ok(StacksBlockHeader::make_index_block_hash(
+                &tip.consensus_hash,
+                &tip.anchored_block_hash,
+            )),
+            Ok(None) => Err(RPCErrorBody::not_found("No Stacks chain tip yet")),
+            Err(e) => {
+                warn!("Failed to load Stacks chain tip: {:?}", &e);
+                Err(RPCErrorBody::server_error(
+                    "Failed to load Stacks chain tip",
+                ))
+            }
+        }
+    }
+
+    /// Handle a /v3 request.  Every failure is reported with an `RPCErrorBody`.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    /// Returns the transaction to forward to the peer network, if a POSTed transaction was
+    /// accepted.
+    fn handle_v3<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        v3_req: &V3Request,
+        network: &mut PeerNetwork,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        mempool: &mut MemPoolDB,
+        handler_args: &RPCHandlerArgs,
+    ) -> Result<Option<StacksMessageType>, net_error> {
+        let response_metadata = HttpResponseMetadata::from_http_request_type(
+            req,
+            Some(network.burnchain_tip.canonical_stacks_tip_height),
+        );
+        let mut relay = None;
+        let result = match v3_req {
+            V3Request::GetOpenAPI => Ok(V3Response::OpenAPI(v3::openapi_document())),
+            V3Request::GetInfo => Ok(V3Response::PeerInfo(RPCPeerInfoData::from_network(
+                network,
+                chainstate,
+                handler_args.exit_at_block_height.clone(),
+                &handler_args.genesis_chainstate_hash,
+            ))),
+            V3Request::GetAccount(principal, tip_req, with_proof) => {
+                ConversationHttp::load_v3_chain_tip(sortdb, chainstate, tip_req).and_then(|tip| {
+                    ConversationHttp::load_account_entry(
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        principal,
+                        *with_proof,
+                    )
+                    .map(V3Response::Account)
+                    .ok_or_else(|| RPCErrorBody::not_found("Chain tip not found"))
+                })
+            }
+            V3Request::PostTransaction(tx) => match chainstate.get_stacks_chain_tip(sortdb)? {
+                Some(tip) => {
+                    let txid = tx.txid();
+                    match ConversationHttp::submit_posted_transaction(
+                        chainstate,
+                        sortdb,
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                        mempool,
+                        tx,
+                        handler_args.event_observer.as_deref(),
+                        network.ast_rules,
+                    )? {
+                        Ok(accepted) => {
+                            if accepted {
+                                relay = Some(StacksMessageType::Transaction(tx.clone()));
+                            }
+                            Ok(V3Response::TransactionAccepted(RPCTransactionAccepted {
+                                txid,
+                            }))
+                        }
+                        Err(rejection) => Err(RPCErrorBody::transaction_rejected(&txid, rejection)),
+                    }
+                }
+                None => {
+                    warn!("Failed to load Stacks chain tip");
+                    Err(RPCErrorBody::server_error(
+                        "Failed to load Stacks chain tip",
+                    ))
+                }
+            },
+        };
+        let response = match result {
+            Ok(v3_response) => HttpResponseType::V3(response_metadata, v3_response),
+            Err(body) => {
+                debug!("/v3 request {} failed: {:?}", req.request_path(), &body);
+                HttpResponseType::V3Error(response_metadata, body)
+            }
+        };
+        response.send(http, fd)?;
+        Ok(relay)
+    }
+
+    /// Handle a GET burn ops.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getburnops<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        query: &BurnOpsQuery,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        match RPCBurnOpInfo::from_db(sortdb, chainstate, query) {
+            Ok(burn_ops) => {
+                let response = HttpResponseType::GetBurnOps(response_metadata, burn_ops);
+                response.send(http, fd)
+            }
+            Err(net_error::NotFoundError) => {
+                debug!("No burnchain operation found for {:?}", query);
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "No such burnchain operation".to_string(),
+                );
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to load burnchain operations for {:?}: {:?}",
+                    query, &e
+                );
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query burnchain operations".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    /// Handle a GET neighbors
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getneighbors<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        network: &PeerNetwork,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let epoch = network.get_current_epoch();
+
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let neighbor_data = RPCNeighborsInfo::from_p2p(
+            network.local_peer.network_id,
+            epoch.network_epoch,
+            &network.peers,
+            &network.chain_view,
+            &network.peerdb,
+        )?;
+        let response = HttpResponseType::Neighbors(response_metadata, neighbor_data);
+        response.send(http, fd)
+    }
+
+    /// Handle a not-found
+    fn handle_notfound<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        response_metadata: HttpResponseMetadata,
+        msg: String,
+    ) -> Result<Option<StreamCursor>, net_error> {
+        let response = HttpResponseType::NotFound(response_metadata, msg);
+        return response.send(http, fd).and_then(|_| Ok(None));
+    }
+
+    /// Handle a server error
+    fn handle_server_error<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        response_metadata: HttpResponseMetadata,
+        msg: String,
+    ) -> Result<Option<StreamCursor>, net_error> {
+        // oops
+        warn!("{}", &msg);
+        let response = HttpResponseType::ServerError(response_metadata, msg);
+        return response.send(http, fd).and_then(|_| Ok(None));
+    }
+
+    /// Handle a GET headers. Start streaming the reply.
+    /// The response's preamble (but not the headers list) will be synchronously written to the fd
+    /// (so use a fd that can buffer!)
+    /// Return a StreamCursor struct for the reward cycle we're sending, so we can continue to
+    /// make progress sending it
+    fn handle_getheaders<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        tip: &StacksBlockId,
+        quantity: u64,
+        chainstate: &StacksChainState,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<Option<StreamCursor>, net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        if quantity > (MAX_HEADERS as u64) {
+            // bad request
+            let response = HttpResponseType::BadRequestJSON(
+                response_metadata,
+                serde_json::Value::String(format!(
+                    "Invalid request: requested more than {} headers",
                     MAX_HEADERS
                 )),
             );
@@ -980,25 +2131,76 @@ impl ConversationHttp {
         }
     }
 
-    /// Handle a GET block.  Start streaming the reply.
-    /// The response's preamble (but not the block data) will be synchronously written to the fd
-    /// (so use a fd that can buffer!)
-    /// Return a StreamCursor struct for the block that we're sending, so we can continue to
-    /// make progress sending it.
-    fn handle_getblock<W: Write>(
+    /// Handle a GET for Stacks block headers with the sortition and Bitcoin evidence a light
+    /// client needs to verify them.  Unlike GET /v2/headers, the reply is not streamed, so
+    /// fewer headers may be requested at once.
+    fn handle_get_light_headers<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
-        index_block_hash: &StacksBlockId,
+        tip: &StacksBlockId,
+        quantity: u64,
+        network: &PeerNetwork,
+        sortdb: &SortitionDB,
         chainstate: &StacksChainState,
         canonical_stacks_tip_height: u64,
-    ) -> Result<Option<StreamCursor>, net_error> {
-        monitoring::increment_stx_blocks_served_counter();
+    ) -> Result<(), net_error> {
         let response_metadata =
             HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
-
-        // do we have this block?
-        match StacksChainState::has_block_indexed(&chainstate.blocks_path, index_block_hash) {
+        if quantity > MAX_LIGHT_HEADERS {
+            let response = HttpResponseType::BadRequestJSON(
+                response_metadata,
+                serde_json::Value::String(format!(
+                    "Invalid request: requested more than {} headers",
+                    MAX_LIGHT_HEADERS
+                )),
+            );
+            return response.send(http, fd);
+        }
+
+        let headers_res = network
+            .burnchain
+            .open_burnchain_db(false)
+            .map_err(net_error::from)
+            .and_then(|burnchain_db| {
+                RPCLightHeader::from_db(chainstate, sortdb, &burnchain_db, tip, quantity)
+            });
+
+        let response = match headers_res {
+            Ok(headers) => HttpResponseType::GetLightHeaders(response_metadata, headers),
+            Err(net_error::NotFoundError) => {
+                HttpResponseType::NotFound(response_metadata, format!("No such block {:?}", &tip))
+            }
+            Err(e) => {
+                warn!("Failed to load light client headers {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    format!("Failed to query block header {}", tip.to_hex()),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET block.  Start streaming the reply.
+    /// The response's preamble (but not the block data) will be synchronously written to the fd
+    /// (so use a fd that can buffer!)
+    /// Return a StreamCursor struct for the block that we're sending, so we can continue to
+    /// make progress sending it.
+    fn handle_getblock<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        index_block_hash: &StacksBlockId,
+        chainstate: &StacksChainState,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<Option<StreamCursor>, net_error> {
+        monitoring::increment_stx_blocks_served_counter();
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        // do we have this block?
+        match StacksChainState::has_block_indexed(&chainstate.blocks_path, index_block_hash) {
             Ok(false) => {
                 return ConversationHttp::handle_notfound(
                     http,
@@ -1239,23 +2441,17 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
-    /// Handle a GET on an existing account, given the current chain tip.  Optionally supplies a
-    /// MARF proof for each account detail loaded from the chain tip.
-    fn handle_get_account_entry<W: Write>(
-        http: &mut StacksHttp,
-        fd: &mut W,
-        req: &HttpRequestType,
+    /// Load an account's balance, locked balance and nonce at the given chain tip, optionally
+    /// with MARF proofs.  Returns None if the chain tip does not exist.
+    fn load_account_entry(
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
         tip: &StacksBlockId,
         account: &PrincipalData,
         with_proof: bool,
-        canonical_stacks_tip_height: u64,
-    ) -> Result<(), net_error> {
-        let response_metadata =
-            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
-        let response =
-            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+    ) -> Option<AccountEntryResponse> {
+        chainstate
+            .maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
                 clarity_tx.with_clarity_db_readonly(|clarity_db| {
                     let key = ClarityDatabase::make_key_for_account_balance(&account);
                     let burn_block_height = clarity_db.get_current_burnchain_block_height() as u64;
@@ -1302,16 +2498,114 @@ impl ConversationHttp {
                         nonce_proof,
                     }
                 })
-            }) {
-                Ok(Some(data)) => HttpResponseType::GetAccount(response_metadata, data),
-                Ok(None) | Err(_) => {
-                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
-                }
-            };
+            })
+            .ok()
+            .flatten()
+    }
+
+    /// Handle a GET on an existing account, given the current chain tip.  Optionally supplies a
+    /// MARF proof for each account detail loaded from the chain tip.
+    fn handle_get_account_entry<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        account: &PrincipalData,
+        with_proof: bool,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response = match ConversationHttp::load_account_entry(
+            sortdb, chainstate, tip, account, with_proof,
+        ) {
+            Some(data) => HttpResponseType::GetAccount(response_metadata, data),
+            None => HttpResponseType::NotFound(response_metadata, "Chain tip not found".into()),
+        };
 
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a GET on the transactions that affected a principal in the fork ending at the
+    /// given chain tip.  Only available if this node indexes account history.
+    fn handle_get_account_history<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+        cursor: Option<&AccountHistoryCursor>,
+        limit: u64,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        if !chainstate.index_account_history {
+            let response = HttpResponseType::ServiceUnavailable(
+                response_metadata,
+                "Account history is not indexed by this node".to_string(),
+            );
+            return response.send(http, fd);
+        }
+
+        let response = match chainstate.get_account_history(principal, tip, cursor, limit) {
+            Ok(entries) => {
+                let next_cursor = match entries.last() {
+                    Some(last) if entries.len() as u64 == limit => Some(
+                        AccountHistoryCursor {
+                            block_height: last.block_height,
+                            txid: last.txid.clone(),
+                        }
+                        .to_string(),
+                    ),
+                    _ => None,
+                };
+                let results = entries
+                    .into_iter()
+                    .map(|entry| RPCAccountHistoryEntry {
+                        txid: entry.txid,
+                        index_block_hash: entry.index_block_hash,
+                        block_height: entry.block_height,
+                        is_sender: entry.is_sender,
+                        committed: entry.committed,
+                        events: entry.events,
+                    })
+                    .collect();
+                HttpResponseType::GetAccountHistory(
+                    response_metadata,
+                    RPCAccountHistory {
+                        principal: principal.to_string(),
+                        tip: *tip,
+                        limit,
+                        results,
+                        next_cursor,
+                    },
+                )
+            }
+            Err(chain_error::NoSuchBlockError) => {
+                HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+            }
+            Err(chain_error::DBError(db_error::NotFoundError)) => HttpResponseType::NotFound(
+                response_metadata,
+                "Cursor not found in this fork's history".into(),
+            ),
+            Err(e) => {
+                warn!(
+                    "Failed to load account history for {} at {}: {:?}",
+                    principal, tip, &e
+                );
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query account history".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
     /// Handle a GET on a smart contract's data var, given the current chain tip.  Optionally
     /// supplies a MARF proof for the value.
     fn handle_get_data_var<W: Write>(
@@ -1427,6 +2721,7 @@ impl ConversationHttp {
 
     /// Handle a POST to run a read-only function call with the given parameters on the given chain
     /// tip.  Returns the result of the function call.  Returns a CallReadOnlyResponse on success.
+    /// Returns the execution cost the call incurred.
     fn handle_readonly_function_call<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
@@ -1442,11 +2737,12 @@ impl ConversationHttp {
         args: &[Value],
         options: &ConnectionOptions,
         canonical_stacks_tip_height: u64,
-    ) -> Result<(), net_error> {
+    ) -> Result<ExecutionCost, net_error> {
         let response_metadata =
             HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
         let contract_identifier =
             QualifiedContractIdentifier::new(contract_addr.clone().into(), contract_name.clone());
+        let mut cost_used = ExecutionCost::zero();
 
         let args: Vec<_> = args
             .iter()
@@ -1496,7 +2792,14 @@ impl ConversationHttp {
                         // can be called, and also circumvents limitations on `define-read-only`
                         // functions that can not use `contrac-call?`, even when calling other
                         // read-only functions
-                        env.execute_contract(&contract_identifier, function.as_str(), &args, false)
+                        let result = env.execute_contract(
+                            &contract_identifier,
+                            function.as_str(),
+                            &args,
+                            false,
+                        );
+                        cost_used = env.global_context.cost_track.get_total();
+                        result
                     },
                 )
             });
@@ -1536,7 +2839,7 @@ impl ConversationHttp {
                 HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
             }
         };
-        response.send(http, fd).map(|_| ())
+        response.send(http, fd).map(|_| cost_used)
     }
 
     /// Handle a GET to fetch a contract's source code, given the chain tip.  Optionally returns a
@@ -2030,6 +3333,74 @@ impl ConversationHttp {
         }
     }
 
+    /// Submit a POSTed transaction directly to the mempool.  Returns whether or not it was newly
+    /// accepted (and thus needs to be forwarded), or why the mempool rejected it.  Transactions
+    /// the mempool already has, and problematic transactions, are not rejected, but are not
+    /// accepted either.
+    fn submit_posted_transaction(
+        chainstate: &mut StacksChainState,
+        sortdb: &SortitionDB,
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+        mempool: &mut MemPoolDB,
+        tx: &StacksTransaction,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+        ast_rules: ASTRules,
+    ) -> Result<Result<bool, MemPoolRejection>, net_error> {
+        let txid = tx.txid();
+        if mempool.has_tx(&txid) {
+            debug!("Mempool already has POSTed transaction {}", &txid);
+            return Ok(Ok(false));
+        }
+
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let stacks_epoch = sortdb
+            .index_conn()
+            .get_stacks_epoch(tip.block_height as u32)
+            .ok_or_else(|| {
+                warn!(
+                    "Failed to store transaction because could not load Stacks epoch for canonical burn height = {}",
+                    tip.block_height
+                );
+                net_error::ChainstateError("Could not load Stacks epoch for canonical burn height".into())
+            })?;
+
+        if Relayer::do_static_problematic_checks()
+            && !Relayer::static_check_problematic_relayed_tx(
+                chainstate.mainnet,
+                stacks_epoch.epoch_id,
+                tx,
+                ast_rules,
+            )
+            .is_ok()
+        {
+            debug!(
+                "Transaction {} is problematic in rules {:?}; will not store or relay",
+                &txid, ast_rules
+            );
+            return Ok(Ok(false));
+        }
+
+        match mempool.submit(
+            chainstate,
+            consensus_hash,
+            block_hash,
+            tx,
+            event_observer,
+            &stacks_epoch.block_limit,
+            &stacks_epoch.epoch_id,
+        ) {
+            Ok(_) => {
+                debug!("Mempool accepted POSTed transaction {}", &txid);
+                Ok(Ok(true))
+            }
+            Err(e) => {
+                debug!("Mempool rejected POSTed transaction {}: {:?}", &txid, &e);
+                Ok(Err(e))
+            }
+        }
+    }
+
     /// Handle a transaction.  Directly submit it to the mempool so the client can see any
     /// rejection reasons up-front (different from how the peer network handles it).  Indicate
     /// whether or not the transaction was accepted (and thus needs to be forwarded) in the return
@@ -2053,69 +3424,24 @@ impl ConversationHttp {
         let txid = tx.txid();
         let response_metadata =
             HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
-        let (response, accepted) = if mempool.has_tx(&txid) {
-            debug!("Mempool already has POSTed transaction {}", &txid);
-            (
+        let (response, accepted) = match ConversationHttp::submit_posted_transaction(
+            chainstate,
+            sortdb,
+            &consensus_hash,
+            &block_hash,
+            mempool,
+            &tx,
+            event_observer,
+            ast_rules,
+        )? {
+            Ok(accepted) => (
                 HttpResponseType::TransactionID(response_metadata, txid),
+                accepted,
+            ),
+            Err(e) => (
+                HttpResponseType::BadRequestJSON(response_metadata, e.into_json(&txid)),
                 false,
-            )
-        } else {
-            let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
-            let stacks_epoch = sortdb
-                .index_conn()
-                .get_stacks_epoch(tip.block_height as u32)
-                .ok_or_else(|| {
-                    warn!(
-                        "Failed to store transaction because could not load Stacks epoch for canonical burn height = {}",
-                        tip.block_height
-                    );
-                    net_error::ChainstateError("Could not load Stacks epoch for canonical burn height".into())
-                })?;
-
-            if Relayer::do_static_problematic_checks()
-                && !Relayer::static_check_problematic_relayed_tx(
-                    chainstate.mainnet,
-                    stacks_epoch.epoch_id,
-                    &tx,
-                    ast_rules,
-                )
-                .is_ok()
-            {
-                debug!(
-                    "Transaction {} is problematic in rules {:?}; will not store or relay",
-                    &tx.txid(),
-                    ast_rules
-                );
-                (
-                    HttpResponseType::TransactionID(response_metadata, txid),
-                    false,
-                )
-            } else {
-                match mempool.submit(
-                    chainstate,
-                    &consensus_hash,
-                    &block_hash,
-                    &tx,
-                    event_observer,
-                    &stacks_epoch.block_limit,
-                    &stacks_epoch.epoch_id,
-                ) {
-                    Ok(_) => {
-                        debug!("Mempool accepted POSTed transaction {}", &txid);
-                        (
-                            HttpResponseType::TransactionID(response_metadata, txid),
-                            true,
-                        )
-                    }
-                    Err(e) => {
-                        debug!("Mempool rejected POSTed transaction {}: {:?}", &txid, &e);
-                        (
-                            HttpResponseType::BadRequestJSON(response_metadata, e.into_json(&txid)),
-                            false,
-                        )
-                    }
-                }
-            }
+            ),
         };
 
         if let Some(ref attachment) = attachment {
@@ -2401,6 +3727,45 @@ impl ConversationHttp {
         let keep_alive = req.metadata().keep_alive;
         let mut ret = None;
 
+        if let Some(response) =
+            self.check_admin_access(&req, network.burnchain_tip.canonical_stacks_tip_height)
+        {
+            debug!(
+                "{:?}: refused {} request on this listener",
+                &self,
+                req.request_path()
+            );
+            response.send(&mut self.connection.protocol, &mut reply)?;
+            self.reply_streams.push_back((reply, None, keep_alive));
+            return Ok(None);
+        }
+
+        if let Some(class) = RPCEndpointClass::from_request(&req) {
+            if let Some(retry_after) = network.rpc_rate_limiter.check_request(
+                &self.connection.options,
+                &self.peer_addr.ip(),
+                class,
+                get_epoch_time_ms(),
+            ) {
+                debug!(
+                    "{:?}: rate-limited {:?} request; retry after {}s",
+                    &self, &class, retry_after
+                );
+                let response_metadata = HttpResponseMetadata::from_http_request_type(
+                    &req,
+                    Some(network.burnchain_tip.canonical_stacks_tip_height),
+                );
+                let response = HttpResponseType::TooManyRequests(
+                    response_metadata,
+                    retry_after,
+                    "Too many requests".to_string(),
+                );
+                response.send(&mut self.connection.protocol, &mut reply)?;
+                self.reply_streams.push_back((reply, None, keep_alive));
+                return Ok(None);
+            }
+        }
+
         let stream_opt = match req {
             HttpRequestType::GetInfo(ref _md) => {
                 ConversationHttp::handle_getinfo(
@@ -2470,6 +3835,35 @@ impl ConversationHttp {
                     None
                 }
             }
+            HttpRequestType::GetLightHeaders(ref _md, ref quantity, ref tip_req) => {
+                // only anchored blocks are chosen by sortitions
+                let tip_req = match tip_req {
+                    TipRequest::UseLatestUnconfirmedTip => &TipRequest::UseLatestAnchoredTip,
+                    _ => tip_req,
+                };
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_light_headers(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        &tip,
+                        *quantity,
+                        network,
+                        sortdb,
+                        chainstate,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetBlock(ref _md, ref index_block_hash) => {
                 ConversationHttp::handle_getblock(
                     &mut self.connection.protocol,
@@ -2549,6 +3943,42 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetAccountHistory(
+                ref _md,
+                ref principal,
+                ref tip_req,
+                ref cursor,
+                ref limit,
+            ) => {
+                // the history only covers processed blocks, so read it from the latest
+                // anchored block instead of the unconfirmed state
+                let tip_req = match tip_req {
+                    TipRequest::UseLatestUnconfirmedTip => &TipRequest::UseLatestAnchoredTip,
+                    _ => tip_req,
+                };
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_account_history(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        principal,
+                        cursor.as_ref(),
+                        *limit,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetDataVar(
                 ref _md,
                 ref contract_addr,
@@ -2687,7 +4117,7 @@ impl ConversationHttp {
                     chainstate,
                     network.burnchain_tip.canonical_stacks_tip_height,
                 )? {
-                    ConversationHttp::handle_readonly_function_call(
+                    let cost = ConversationHttp::handle_readonly_function_call(
                         &mut self.connection.protocol,
                         &mut reply,
                         &req,
@@ -2703,6 +4133,12 @@ impl ConversationHttp {
                         &self.connection.options,
                         network.burnchain_tip.canonical_stacks_tip_height,
                     )?;
+                    network.rpc_rate_limiter.charge_read_only_call(
+                        &self.connection.options,
+                        &self.peer_addr.ip(),
+                        &cost,
+                        get_epoch_time_ms(),
+                    );
                 }
                 None
             }
@@ -2776,22 +4212,94 @@ impl ConversationHttp {
                 }
                 None
             }
-            HttpRequestType::GetAttachment(ref _md, ref content_hash) => {
-                ConversationHttp::handle_getattachment(
+            HttpRequestType::GetSortition(ref _md, ref query) => {
+                ConversationHttp::handle_getsortition(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
-                    &mut network.atlasdb,
-                    content_hash.clone(),
+                    sortdb,
+                    query,
                     network.burnchain_tip.canonical_stacks_tip_height,
                 )?;
                 None
             }
-            HttpRequestType::GetAttachmentsInv(
-                ref _md,
-                ref index_block_hash,
-                ref pages_indexes,
-            ) => {
+            HttpRequestType::GetRewardCycle(ref _md, ref reward_cycle) => {
+                ConversationHttp::handle_getrewardcycle(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    network,
+                    *reward_cycle,
+                )?;
+                None
+            }
+            HttpRequestType::GetBurnOps(ref _md, ref query) => {
+                ConversationHttp::handle_getburnops(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    query,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )?;
+                None
+            }
+            HttpRequestType::GetAffirmationMaps(ref _md, ref reward_cycle_opt) => {
+                ConversationHttp::handle_getaffirmationmaps(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    network,
+                    *reward_cycle_opt,
+                )?;
+                None
+            }
+            HttpRequestType::Admin(ref _md, _, ref admin_req) => {
+                ConversationHttp::handle_admin(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    admin_req,
+                    network,
+                    mempool,
+                    handler_opts,
+                )?;
+                None
+            }
+            HttpRequestType::V3(ref _md, ref v3_req) => {
+                ret = ConversationHttp::handle_v3(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    v3_req,
+                    network,
+                    sortdb,
+                    chainstate,
+                    mempool,
+                    handler_opts,
+                )?;
+                None
+            }
+            HttpRequestType::GetAttachment(ref _md, ref content_hash) => {
+                ConversationHttp::handle_getattachment(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    &mut network.atlasdb,
+                    content_hash.clone(),
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )?;
+                None
+            }
+            HttpRequestType::GetAttachmentsInv(
+                ref _md,
+                ref index_block_hash,
+                ref pages_indexes,
+            ) => {
                 ConversationHttp::handle_getattachmentsinv(
                     &mut self.connection.protocol,
                     &mut reply,
@@ -2937,6 +4445,9 @@ impl ConversationHttp {
                     ClientError::NotFound(path) => {
                         HttpResponseType::NotFound(response_metadata, path.clone())
                     }
+                    ClientError::V3(body) => {
+                        HttpResponseType::V3Error(response_metadata, body.clone())
+                    }
                 };
 
                 response
@@ -3351,6 +4862,72 @@ impl ConversationHttp {
         ))
     }
 
+    /// Make a new getsortition request to this endpoint
+    pub fn new_getsortition(&self, query: SortitionQuery) -> HttpRequestType {
+        HttpRequestType::GetSortition(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            query,
+        )
+    }
+
+    /// Make a new getrewardcycle request to this endpoint
+    pub fn new_getrewardcycle(&self, reward_cycle: u64) -> HttpRequestType {
+        HttpRequestType::GetRewardCycle(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            reward_cycle,
+        )
+    }
+
+    /// Make a new getburnops request to this endpoint
+    pub fn new_getburnops(&self, query: BurnOpsQuery) -> HttpRequestType {
+        HttpRequestType::GetBurnOps(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            query,
+        )
+    }
+
+    /// Make a new getaccounthistory request to this endpoint
+    pub fn new_getaccounthistory(
+        &self,
+        principal: PrincipalData,
+        tip_req: TipRequest,
+        cursor: Option<AccountHistoryCursor>,
+        limit: u64,
+    ) -> HttpRequestType {
+        HttpRequestType::GetAccountHistory(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            principal,
+            tip_req,
+            cursor,
+            limit,
+        )
+    }
+
+    /// Make a new getaffirmationmaps request to this endpoint
+    pub fn new_getaffirmationmaps(&self, reward_cycle_opt: Option<u64>) -> HttpRequestType {
+        HttpRequestType::GetAffirmationMaps(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            reward_cycle_opt,
+        )
+    }
+
+    /// Make a new admin request to this endpoint, presenting the given bearer token
+    pub fn new_admin(&self, token: Option<AdminToken>, admin_req: AdminRequest) -> HttpRequestType {
+        HttpRequestType::Admin(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            token,
+            admin_req,
+        )
+    }
+
+    /// Make a new /v3 request to this endpoint
+    pub fn new_v3(&self, v3_req: V3Request) -> HttpRequestType {
+        HttpRequestType::V3(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            v3_req,
+        )
+    }
+
     /// Make a new getinfo request to this endpoint
     pub fn new_getpoxinfo(&self, tip_req: TipRequest) -> HttpRequestType {
         HttpRequestType::GetPoxInfo(
@@ -3373,6 +4950,15 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for light client headers to this endpoint
+    pub fn new_get_light_headers(&self, quantity: u64, tip_req: TipRequest) -> HttpRequestType {
+        HttpRequestType::GetLightHeaders(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            quantity,
+            tip_req,
+        )
+    }
+
     /// Make a new getblock request to this endpoint
     pub fn new_getblock(&self, index_block_hash: StacksBlockId) -> HttpRequestType {
         HttpRequestType::GetBlock(
@@ -3602,6 +5188,7 @@ mod test {
     use crate::burnchains::Burnchain;
     use crate::burnchains::BurnchainView;
     use crate::burnchains::*;
+    use crate::chainstate::burn::operations::TransferStxOp;
     use crate::chainstate::burn::ConsensusHash;
     use crate::chainstate::stacks::db::blocks::test::*;
     use crate::chainstate::stacks::db::StacksChainState;
@@ -4253,25 +5840,1142 @@ mod test {
         let client_stacks_height = 17;
         test_rpc(
             function_name!(),
-            40000,
-            40001,
-            50000,
-            50001,
+            40000,
+            40001,
+            50000,
+            50001,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_info = RPCPeerInfoData::from_network(
+                    &peer_server.network,
+                    &peer_server.stacks_node.as_ref().unwrap().chainstate,
+                    None,
+                    &Sha256Sum::zero(),
+                );
+
+                *peer_server_info.borrow_mut() = Some(peer_info);
+
+                convo_client.new_getinfo(Some(client_stacks_height))
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                assert_eq!(convo_client.canonical_stacks_tip_height, Some(1));
+                assert_eq!(
+                    convo_server.canonical_stacks_tip_height,
+                    Some(client_stacks_height)
+                );
+                match http_response {
+                    HttpResponseType::PeerInfo(response_md, peer_data) => {
+                        assert_eq!(Some((*peer_data).clone()), *peer_server_info.borrow());
+                        assert!(peer_data.node_public_key.is_some());
+                        assert!(peer_data.node_public_key_hash.is_some());
+                        assert_eq!(
+                            peer_data.node_public_key_hash,
+                            Some(Hash160::from_node_public_key(
+                                &peer_data
+                                    .node_public_key
+                                    .clone()
+                                    .unwrap()
+                                    .to_public_key()
+                                    .unwrap()
+                            ))
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getpoxinfo() {
+        // Test v2/pox (aka GetPoxInfo) endpoint.
+        // In this test, `tip_req` is set to UseLatestAnchoredTip.
+        // Thus, the query for pox info will be against the canonical Stacks tip, which we expect to succeed.
+        let pox_server_info = RefCell::new(None);
+        test_rpc(
+            function_name!(),
+            40002,
+            40003,
+            50002,
+            50003,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                let pox_info = RPCPoxInfoData::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                )
+                .unwrap();
+                *pox_server_info.borrow_mut() = Some(pox_info);
+                convo_client.new_getpoxinfo(TipRequest::UseLatestAnchoredTip)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             convo_client,
+             convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::PoxInfo(response_md, pox_data) => {
+                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getpoxinfo_use_latest_tip() {
+        // Test v2/pox (aka GetPoxInfo) endpoint.
+        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying for pox
+        // info against the unconfirmed state will succeed.
+        let pox_server_info = RefCell::new(None);
+        test_rpc(
+            function_name!(),
+            40004,
+            40005,
+            50004,
+            50005,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = chainstate
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                let pox_info = RPCPoxInfoData::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                )
+                .unwrap();
+                *pox_server_info.borrow_mut() = Some(pox_info);
+                convo_client.new_getpoxinfo(TipRequest::UseLatestUnconfirmedTip)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::PoxInfo(response_md, pox_data) => {
+                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getneighbors() {
+        test_rpc(
+            function_name!(),
+            40010,
+            40011,
+            50010,
+            50011,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| { convo_client.new_getneighbors() },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Neighbors(response_md, neighbor_info) => {
+                        assert_eq!(neighbor_info.sample.len(), 1);
+                        assert_eq!(neighbor_info.sample[0].port, peer_client.config.server_port); // we see ourselves as the neighbor
+                        assert_eq!(neighbor_info.bootstrap.len(), 1);
+                        assert_eq!(
+                            neighbor_info.bootstrap[0].port,
+                            peer_client.config.server_port
+                        ); // we see ourselves as the bootstrap
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getheaders() {
+        let server_blocks_cell = RefCell::new(None);
+
+        test_rpc(
+            function_name!(),
+            40012,
+            40013,
+            50012,
+            50013,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // have "server" peer store a few continuous block to staging
+                let mut blocks: Vec<StacksBlock> = vec![];
+                let mut index_block_hashes = vec![];
+                for i in 0..25 {
+                    let mut peer_server_block = make_codec_test_block(25);
+
+                    peer_server_block.header.total_work.work = (i + 1) as u64;
+                    peer_server_block.header.total_work.burn = (i + 1) as u64;
+                    peer_server_block.header.parent_block = blocks
+                        .last()
+                        .map(|blk| blk.block_hash())
+                        .unwrap_or(BlockHeaderHash([0u8; 32]));
+
+                    let peer_server_consensus_hash = ConsensusHash([(i + 1) as u8; 20]);
+                    let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                        &peer_server_consensus_hash,
+                        &peer_server_block.block_hash(),
+                    );
+
+                    test_debug!("Store peer server index block {:?}", &index_block_hash);
+                    store_staging_block(
+                        peer_server.chainstate(),
+                        &peer_server_consensus_hash,
+                        &peer_server_block,
+                        &ConsensusHash([i as u8; 20]),
+                        456,
+                        123,
+                    );
+                    set_block_processed(
+                        peer_server.chainstate(),
+                        &peer_server_consensus_hash,
+                        &peer_server_block.block_hash(),
+                        true,
+                    );
+
+                    index_block_hashes.push(index_block_hash);
+                    blocks.push(peer_server_block);
+                }
+
+                let rev_blocks: Vec<_> = blocks.into_iter().rev().collect();
+                let rev_ibhs: Vec<_> = index_block_hashes.into_iter().rev().collect();
+
+                let tip = rev_ibhs[0].clone();
+                *server_blocks_cell.borrow_mut() = Some((rev_blocks, rev_ibhs));
+
+                // now ask for it
+                convo_client.new_getheaders(25, TipRequest::SpecificTip(tip))
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Headers(response_md, headers) => {
+                        assert_eq!(headers.len(), 25);
+                        let expected = server_blocks_cell.borrow().clone().unwrap();
+                        for (i, h) in headers.iter().enumerate() {
+                            assert_eq!(h.header, expected.0[i].header);
+                            assert_eq!(h.consensus_hash, ConsensusHash([(25 - i) as u8; 20]));
+                            if i + 1 < headers.len() {
+                                assert_eq!(h.parent_block_id, expected.1[i + 1]);
+                            }
+                        }
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_unconfirmed_getblock() {
+        let server_block_cell = RefCell::new(None);
+
+        test_rpc(
+            function_name!(),
+            40020,
+            40021,
+            50020,
+            50021,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // have "server" peer store a block to staging
+                let peer_server_block = make_codec_test_block(25);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block.block_hash(),
+                );
+
+                test_debug!("Store peer server index block {:?}", &index_block_hash);
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &peer_server_consensus_hash,
+                    &peer_server_block,
+                    &ConsensusHash([0x03; 20]),
+                    456,
+                    123,
+                );
+
+                *server_block_cell.borrow_mut() = Some(peer_server_block);
+
+                // now ask for it
+                convo_client.new_getblock(index_block_hash)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Block(response_md, block_info) => {
+                        assert_eq!(
+                            block_info.block_hash(),
+                            (*server_block_cell.borrow()).as_ref().unwrap().block_hash()
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_confirmed_getblock() {
+        let server_block_cell = RefCell::new(None);
+
+        test_rpc(
+            function_name!(),
+            40030,
+            40031,
+            50030,
+            50031,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // have "server" peer store a block to staging
+                let peer_server_block = make_codec_test_block(25);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block.block_hash(),
+                );
+
+                test_debug!("Store peer server index block {:?}", &index_block_hash);
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &peer_server_consensus_hash,
+                    &peer_server_block,
+                    &ConsensusHash([0x03; 20]),
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &peer_server_consensus_hash,
+                    &peer_server_block.block_hash(),
+                    true,
+                );
+
+                *server_block_cell.borrow_mut() = Some(peer_server_block);
+
+                // now ask for it
+                convo_client.new_getblock(index_block_hash)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Block(response_md, block_info) => {
+                        assert_eq!(
+                            block_info.block_hash(),
+                            (*server_block_cell.borrow()).as_ref().unwrap().block_hash()
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_indexed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            function_name!(),
+            40040,
+            40041,
+            50040,
+            50041,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let parent_block = make_codec_test_block(25);
+                let parent_consensus_hash = ConsensusHash([0x02; 20]);
+                let parent_index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &parent_consensus_hash,
+                    &parent_block.block_hash(),
+                );
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
+                mblocks.truncate(15);
+
+                let mut child_block = make_codec_test_block(25);
+                let child_consensus_hash = ConsensusHash([0x03; 20]);
+
+                child_block.header.parent_block = parent_block.block_hash();
+                child_block.header.parent_microblock =
+                    mblocks.last().as_ref().unwrap().block_hash();
+                child_block.header.parent_microblock_sequence =
+                    mblocks.last().as_ref().unwrap().header.sequence;
+
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &parent_consensus_hash,
+                    &parent_block,
+                    &ConsensusHash([0x01; 20]),
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &parent_consensus_hash,
+                    &parent_block.block_hash(),
+                    true,
+                );
+
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block,
+                    &parent_consensus_hash,
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                    true,
+                );
+
+                let index_microblock_hash = StacksBlockHeader::make_index_block_hash(
+                    &parent_consensus_hash,
+                    &mblocks.last().as_ref().unwrap().block_hash(),
+                );
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &parent_consensus_hash,
+                        &parent_block.block_hash(),
+                        &mblock,
+                    );
+                }
+
+                set_microblocks_processed(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                    &mblocks.last().as_ref().unwrap().block_hash(),
+                );
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                convo_client.new_getmicroblocks_indexed(index_microblock_hash)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match (*http_response).clone() {
+                    HttpResponseType::Microblocks(_, mut microblocks) => {
+                        microblocks.reverse();
+                        assert_eq!(microblocks.len(), (*server_microblocks_cell.borrow()).len());
+                        assert_eq!(microblocks, *server_microblocks_cell.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_confirmed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            function_name!(),
+            40042,
+            40043,
+            50042,
+            50043,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let parent_block = make_codec_test_block(25);
+                let parent_consensus_hash = ConsensusHash([0x02; 20]);
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
+                mblocks.truncate(15);
+
+                let mut child_block = make_codec_test_block(25);
+                let child_consensus_hash = ConsensusHash([0x03; 20]);
+
+                child_block.header.parent_block = parent_block.block_hash();
+                child_block.header.parent_microblock =
+                    mblocks.last().as_ref().unwrap().block_hash();
+                child_block.header.parent_microblock_sequence =
+                    mblocks.last().as_ref().unwrap().header.sequence;
+
+                let child_index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                );
+
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &parent_consensus_hash,
+                    &parent_block,
+                    &ConsensusHash([0x01; 20]),
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &parent_consensus_hash,
+                    &parent_block.block_hash(),
+                    true,
+                );
+
+                store_staging_block(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block,
+                    &parent_consensus_hash,
+                    456,
+                    123,
+                );
+                set_block_processed(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                    true,
+                );
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &parent_consensus_hash,
+                        &parent_block.block_hash(),
+                        &mblock,
+                    );
+                }
+
+                set_microblocks_processed(
+                    peer_server.chainstate(),
+                    &child_consensus_hash,
+                    &child_block.block_hash(),
+                    &mblocks.last().as_ref().unwrap().block_hash(),
+                );
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                convo_client.new_getmicroblocks_confirmed(child_index_block_hash)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match (*http_response).clone() {
+                    HttpResponseType::Microblocks(_, mut microblocks) => {
+                        microblocks.reverse();
+                        assert_eq!(microblocks.len(), (*server_microblocks_cell.borrow()).len());
+                        assert_eq!(microblocks, *server_microblocks_cell.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_unconfirmed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            function_name!(),
+            40050,
+            40051,
+            50050,
+            50051,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let consensus_hash = ConsensusHash([0x02; 20]);
+                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
+                let index_block_hash =
+                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
+                mblocks.truncate(15);
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &consensus_hash,
+                        &anchored_block_hash,
+                        &mblock,
+                    );
+                }
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                // start at seq 5
+                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 5)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Microblocks(response_md, microblocks) => {
+                        assert_eq!(microblocks.len(), 10);
+                        assert_eq!(
+                            *microblocks,
+                            (*server_microblocks_cell.borrow())[5..].to_vec()
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_unconfirmed_transaction() {
+        let last_txid = RefCell::new(Txid([0u8; 32]));
+        let last_mblock = RefCell::new(BlockHeaderHash([0u8; 32]));
+
+        test_rpc(
+            function_name!(),
+            40052,
+            40053,
+            50052,
+            50053,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let sortdb = peer_server.sortdb.take().unwrap();
+                Relayer::setup_unconfirmed_state(peer_server.chainstate(), &sortdb).unwrap();
+                peer_server.sortdb = Some(sortdb);
+
+                assert!(peer_server.chainstate().unconfirmed_state.is_some());
+                let (txid, mblock_hash) = match peer_server.chainstate().unconfirmed_state {
+                    Some(ref unconfirmed) => {
+                        assert!(unconfirmed.mined_txs.len() > 0);
+                        let mut txid = Txid([0u8; 32]);
+                        let mut mblock_hash = BlockHeaderHash([0u8; 32]);
+                        for (next_txid, (_, mbh, ..)) in unconfirmed.mined_txs.iter() {
+                            txid = next_txid.clone();
+                            mblock_hash = mbh.clone();
+                            break;
+                        }
+                        (txid, mblock_hash)
+                    }
+                    None => {
+                        panic!("No unconfirmed state");
+                    }
+                };
+
+                *last_txid.borrow_mut() = txid.clone();
+                *last_mblock.borrow_mut() = mblock_hash.clone();
+
+                convo_client.new_gettransaction_unconfirmed(txid)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::UnconfirmedTransaction(response_md, unconfirmed_resp) => {
+                        assert_eq!(
+                            unconfirmed_resp.status,
+                            UnconfirmedTransactionStatus::Microblock {
+                                block_hash: (*last_mblock.borrow()).clone(),
+                                seq: 0
+                            }
+                        );
+                        let tx = StacksTransaction::consensus_deserialize(
+                            &mut &hex_bytes(&unconfirmed_resp.tx).unwrap()[..],
+                        )
+                        .unwrap();
+                        assert_eq!(tx.txid(), *last_txid.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_getblock() {
+        test_rpc(
+            function_name!(),
+            40060,
+            40061,
+            50060,
+            50061,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getblock(index_block_hash)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_index_getmicroblocks() {
+        test_rpc(
+            function_name!(),
+            40070,
+            40071,
+            50070,
+            50071,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getmicroblocks_indexed(index_block_hash)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_confirmed_getmicroblocks() {
+        test_rpc(
+            function_name!(),
+            40072,
+            40073,
+            50072,
+            50073,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getmicroblocks_confirmed(index_block_hash)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_unconfirmed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            function_name!(),
+            40080,
+            40081,
+            50080,
+            50081,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let consensus_hash = ConsensusHash([0x02; 20]);
+                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
+                let index_block_hash =
+                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
+                mblocks.truncate(15);
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &consensus_hash,
+                        &anchored_block_hash,
+                        &mblock,
+                    );
+                }
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                // start at seq 16 (which doesn't exist)
+                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 16)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_src() {
+        // Test v2/contracts/source (aka GetContractSrc) endpoint.
+        // In this test, we don't set any tip parameters, and allow the endpoint to execute against
+        // the canonical Stacks tip.
+        // The contract source we are querying for exists in the anchored state, so we expect the
+        // query to succeed.
+        test_rpc(
+            function_name!(),
+            40090,
+            40091,
+            50090,
+            50091,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getcontractsrc(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetContractSrc(response_md, data) => {
+                        assert_eq!(data.source, TEST_CONTRACT);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_src_unconfirmed_with_canonical_tip() {
+        // Test v2/contracts/source (aka GetContractSrc) endpoint.
+        // In this test, we don't set any tip parameters, and allow the endpoint to execute against
+        // the canonical Stacks tip.
+        // The contract source we are querying for only exists in the unconfirmed state, so we
+        // expect the query to fail.
+        test_rpc(
+            function_name!(),
+            40100,
+            40101,
+            50100,
+            50101,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getcontractsrc(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(_, error_str) => {
+                        assert_eq!(error_str, "No contract source data found");
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_src_with_unconfirmed_tip() {
+        // Test v2/contracts/source (aka GetContractSrc) endpoint.
+        // In this test, we set `tip_req` to be the unconfirmed chain tip.
+        // The contract source we are querying for exists in the unconfirmed state, so we expect
+        // the query to succeed.
+        test_rpc(
+            function_name!(),
+            40102,
+            40103,
+            50102,
+            50103,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_info = RPCPeerInfoData::from_network(
-                    &peer_server.network,
-                    &peer_server.stacks_node.as_ref().unwrap().chainstate,
-                    None,
-                    &Sha256Sum::zero(),
-                );
-
-                *peer_server_info.borrow_mut() = Some(peer_info);
-
-                convo_client.new_getinfo(Some(client_stacks_height))
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                convo_client.new_getcontractsrc(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    TipRequest::SpecificTip(unconfirmed_tip),
+                    false,
+                )
             },
             |ref http_request,
              ref http_response,
@@ -4280,31 +6984,13 @@ mod test {
              ref convo_client,
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
-                assert_eq!(convo_client.canonical_stacks_tip_height, Some(1));
-                assert_eq!(
-                    convo_server.canonical_stacks_tip_height,
-                    Some(client_stacks_height)
-                );
                 match http_response {
-                    HttpResponseType::PeerInfo(response_md, peer_data) => {
-                        assert_eq!(Some((*peer_data).clone()), *peer_server_info.borrow());
-                        assert!(peer_data.node_public_key.is_some());
-                        assert!(peer_data.node_public_key_hash.is_some());
-                        assert_eq!(
-                            peer_data.node_public_key_hash,
-                            Some(Hash160::from_node_public_key(
-                                &peer_data
-                                    .node_public_key
-                                    .clone()
-                                    .unwrap()
-                                    .to_public_key()
-                                    .unwrap()
-                            ))
-                        );
+                    HttpResponseType::GetContractSrc(response_md, data) => {
+                        assert_eq!(data.source, TEST_CONTRACT_UNCONFIRMED);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4314,55 +7000,44 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_getpoxinfo() {
-        // Test v2/pox (aka GetPoxInfo) endpoint.
-        // In this test, `tip_req` is set to UseLatestAnchoredTip.
-        // Thus, the query for pox info will be against the canonical Stacks tip, which we expect to succeed.
-        let pox_server_info = RefCell::new(None);
+    fn test_rpc_get_contract_src_use_latest_tip() {
+        // Test v2/contracts/source (aka GetContractSrc) endpoint.
+        // In this test, we set `tip_req` to UseLatestUnconfirmedTip.
+        // The contract source we are querying for exists in the unconfirmed state, so we expect
+        // the query to succeed.
         test_rpc(
             function_name!(),
-            40002,
-            40003,
-            50002,
-            50003,
+            40104,
+            40105,
+            50104,
+            50105,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
-                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
-                let stacks_block_id = {
-                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
-                    StacksBlockHeader::make_index_block_hash(
-                        &tip.consensus_hash,
-                        &tip.anchored_block_hash,
-                    )
-                };
-                let pox_info = RPCPoxInfoData::from_db(
-                    &mut sortdb,
-                    chainstate,
-                    &stacks_block_id,
-                    &peer_client.config.burnchain,
+                convo_client.new_getcontractsrc(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
                 )
-                .unwrap();
-                *pox_server_info.borrow_mut() = Some(pox_info);
-                convo_client.new_getpoxinfo(TipRequest::UseLatestAnchoredTip)
             },
             |ref http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             convo_client,
-             convo_server| {
+             ref convo_client,
+             ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::PoxInfo(response_md, pox_data) => {
-                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                    HttpResponseType::GetContractSrc(response_md, data) => {
+                        assert_eq!(data.source, TEST_CONTRACT_UNCONFIRMED);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4372,39 +7047,25 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_getpoxinfo_use_latest_tip() {
-        // Test v2/pox (aka GetPoxInfo) endpoint.
-        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying for pox
-        // info against the unconfirmed state will succeed.
-        let pox_server_info = RefCell::new(None);
+    fn test_rpc_get_account() {
         test_rpc(
             function_name!(),
-            40004,
-            40005,
-            50004,
-            50005,
+            40110,
+            40111,
+            50110,
+            50111,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
-                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
-                let stacks_block_id = chainstate
-                    .unconfirmed_state
-                    .as_ref()
-                    .unwrap()
-                    .unconfirmed_chain_tip
-                    .clone();
-                let pox_info = RPCPoxInfoData::from_db(
-                    &mut sortdb,
-                    chainstate,
-                    &stacks_block_id,
-                    &peer_client.config.burnchain,
+                convo_client.new_getaccount(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
                 )
-                .unwrap();
-                *pox_server_info.borrow_mut() = Some(pox_info);
-                convo_client.new_getpoxinfo(TipRequest::UseLatestUnconfirmedTip)
             },
             |ref http_request,
              ref http_response,
@@ -4414,12 +7075,14 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::PoxInfo(response_md, pox_data) => {
-                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                    HttpResponseType::GetAccount(response_md, data) => {
+                        assert_eq!(data.nonce, 2);
+                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
+                        assert_eq!(balance, 1000000000);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4427,20 +7090,31 @@ mod test {
         );
     }
 
+    /// In this test, the query parameter `tip_req` is set to UseLatestUnconfirmedTip, and so we expect the
+    /// tip used for the query to be the latest microblock.
+    /// We check that the account state matches the state in the most recent microblock.
     #[test]
     #[ignore]
-    fn test_rpc_getneighbors() {
+    fn test_rpc_get_account_use_latest_tip() {
         test_rpc(
             function_name!(),
-            40010,
-            40011,
-            50010,
-            50011,
+            40112,
+            40113,
+            50112,
+            50113,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
-             ref mut convo_server| { convo_client.new_getneighbors() },
+             ref mut convo_server| {
+                convo_client.new_getaccount(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
+                )
+            },
             |ref http_request,
              ref http_response,
              ref mut peer_client,
@@ -4449,18 +7123,14 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Neighbors(response_md, neighbor_info) => {
-                        assert_eq!(neighbor_info.sample.len(), 1);
-                        assert_eq!(neighbor_info.sample[0].port, peer_client.config.server_port); // we see ourselves as the neighbor
-                        assert_eq!(neighbor_info.bootstrap.len(), 1);
-                        assert_eq!(
-                            neighbor_info.bootstrap[0].port,
-                            peer_client.config.server_port
-                        ); // we see ourselves as the bootstrap
+                    HttpResponseType::GetAccount(response_md, data) => {
+                        assert_eq!(data.nonce, 4);
+                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
+                        assert_eq!(balance, 999999877);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4468,69 +7138,31 @@ mod test {
         );
     }
 
+    /// In this test, the query parameter `tip_req` is set to UseLatestUnconfirmedTip, but we did not generate
+    /// microblocks in the rpc test. Thus, we expect the tip used for the query to be the previous
+    /// anchor block (which is the latest tip).
+    /// We check that the account state matches the state in the previous anchor block.
     #[test]
     #[ignore]
-    fn test_rpc_getheaders() {
-        let server_blocks_cell = RefCell::new(None);
-
+    fn test_rpc_get_account_use_latest_tip_no_microblocks() {
         test_rpc(
             function_name!(),
-            40012,
-            40013,
-            50012,
-            50013,
-            true,
-            |ref mut peer_client,
-             ref mut convo_client,
-             ref mut peer_server,
-             ref mut convo_server| {
-                // have "server" peer store a few continuous block to staging
-                let mut blocks: Vec<StacksBlock> = vec![];
-                let mut index_block_hashes = vec![];
-                for i in 0..25 {
-                    let mut peer_server_block = make_codec_test_block(25);
-
-                    peer_server_block.header.total_work.work = (i + 1) as u64;
-                    peer_server_block.header.total_work.burn = (i + 1) as u64;
-                    peer_server_block.header.parent_block = blocks
-                        .last()
-                        .map(|blk| blk.block_hash())
-                        .unwrap_or(BlockHeaderHash([0u8; 32]));
-
-                    let peer_server_consensus_hash = ConsensusHash([(i + 1) as u8; 20]);
-                    let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                        &peer_server_consensus_hash,
-                        &peer_server_block.block_hash(),
-                    );
-
-                    test_debug!("Store peer server index block {:?}", &index_block_hash);
-                    store_staging_block(
-                        peer_server.chainstate(),
-                        &peer_server_consensus_hash,
-                        &peer_server_block,
-                        &ConsensusHash([i as u8; 20]),
-                        456,
-                        123,
-                    );
-                    set_block_processed(
-                        peer_server.chainstate(),
-                        &peer_server_consensus_hash,
-                        &peer_server_block.block_hash(),
-                        true,
-                    );
-
-                    index_block_hashes.push(index_block_hash);
-                    blocks.push(peer_server_block);
-                }
-
-                let rev_blocks: Vec<_> = blocks.into_iter().rev().collect();
-                let rev_ibhs: Vec<_> = index_block_hashes.into_iter().rev().collect();
-
-                let tip = rev_ibhs[0].clone();
-                *server_blocks_cell.borrow_mut() = Some((rev_blocks, rev_ibhs));
-
-                // now ask for it
-                convo_client.new_getheaders(25, TipRequest::SpecificTip(tip))
+            40114,
+            40115,
+            50114,
+            50115,
+            false,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getaccount(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
+                )
             },
             |ref http_request,
              ref http_response,
@@ -4540,20 +7172,14 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Headers(response_md, headers) => {
-                        assert_eq!(headers.len(), 25);
-                        let expected = server_blocks_cell.borrow().clone().unwrap();
-                        for (i, h) in headers.iter().enumerate() {
-                            assert_eq!(h.header, expected.0[i].header);
-                            assert_eq!(h.consensus_hash, ConsensusHash([(25 - i) as u8; 20]));
-                            if i + 1 < headers.len() {
-                                assert_eq!(h.parent_block_id, expected.1[i + 1]);
-                            }
-                        }
+                    HttpResponseType::GetAccount(response_md, data) => {
+                        assert_eq!(data.nonce, 2);
+                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
+                        assert_eq!(balance, 1000000000);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4563,42 +7189,32 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_unconfirmed_getblock() {
-        let server_block_cell = RefCell::new(None);
-
+    fn test_rpc_get_account_unconfirmed() {
         test_rpc(
             function_name!(),
-            40020,
-            40021,
-            50020,
-            50021,
+            40120,
+            40121,
+            50120,
+            50121,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                // have "server" peer store a block to staging
-                let peer_server_block = make_codec_test_block(25);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block.block_hash(),
-                );
-
-                test_debug!("Store peer server index block {:?}", &index_block_hash);
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &peer_server_consensus_hash,
-                    &peer_server_block,
-                    &ConsensusHash([0x03; 20]),
-                    456,
-                    123,
-                );
-
-                *server_block_cell.borrow_mut() = Some(peer_server_block);
-
-                // now ask for it
-                convo_client.new_getblock(index_block_hash)
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                convo_client.new_getaccount(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    TipRequest::SpecificTip(unconfirmed_tip),
+                    false,
+                )
             },
             |ref http_request,
              ref http_response,
@@ -4608,15 +7224,14 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Block(response_md, block_info) => {
-                        assert_eq!(
-                            block_info.block_hash(),
-                            (*server_block_cell.borrow()).as_ref().unwrap().block_hash()
-                        );
+                    HttpResponseType::GetAccount(response_md, data) => {
+                        assert_eq!(data.nonce, 4);
+                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
+                        assert_eq!(balance, 1000000000 - 123);
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4626,48 +7241,26 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_confirmed_getblock() {
-        let server_block_cell = RefCell::new(None);
-
+    fn test_rpc_get_data_var() {
         test_rpc(
             function_name!(),
-            40030,
-            40031,
-            50030,
-            50031,
+            40122,
+            40123,
+            50122,
+            50123,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                // have "server" peer store a block to staging
-                let peer_server_block = make_codec_test_block(25);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block.block_hash(),
-                );
-
-                test_debug!("Store peer server index block {:?}", &index_block_hash);
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &peer_server_consensus_hash,
-                    &peer_server_block,
-                    &ConsensusHash([0x03; 20]),
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &peer_server_consensus_hash,
-                    &peer_server_block.block_hash(),
-                    true,
-                );
-
-                *server_block_cell.borrow_mut() = Some(peer_server_block);
-
-                // now ask for it
-                convo_client.new_getblock(index_block_hash)
+                convo_client.new_getdatavar(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "bar".try_into().unwrap(),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
+                )
             },
             |ref http_request,
              ref http_response,
@@ -4677,15 +7270,15 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Block(response_md, block_info) => {
+                    HttpResponseType::GetDataVar(response_md, data) => {
                         assert_eq!(
-                            block_info.block_hash(),
-                            (*server_block_cell.borrow()).as_ref().unwrap().block_hash()
+                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
+                            Value::Int(0)
                         );
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4695,115 +7288,51 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_indexed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_get_data_var_unconfirmed() {
         test_rpc(
             function_name!(),
-            40040,
-            40041,
-            50040,
-            50041,
+            40124,
+            40125,
+            50124,
+            50125,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                convo_client.new_getdatavar(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "bar".try_into().unwrap(),
+                    TipRequest::SpecificTip(unconfirmed_tip),
+                    false,
                 )
-                .unwrap();
-
-                let parent_block = make_codec_test_block(25);
-                let parent_consensus_hash = ConsensusHash([0x02; 20]);
-                let parent_index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &parent_consensus_hash,
-                    &parent_block.block_hash(),
-                );
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
-                mblocks.truncate(15);
-
-                let mut child_block = make_codec_test_block(25);
-                let child_consensus_hash = ConsensusHash([0x03; 20]);
-
-                child_block.header.parent_block = parent_block.block_hash();
-                child_block.header.parent_microblock =
-                    mblocks.last().as_ref().unwrap().block_hash();
-                child_block.header.parent_microblock_sequence =
-                    mblocks.last().as_ref().unwrap().header.sequence;
-
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &parent_consensus_hash,
-                    &parent_block,
-                    &ConsensusHash([0x01; 20]),
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &parent_consensus_hash,
-                    &parent_block.block_hash(),
-                    true,
-                );
-
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block,
-                    &parent_consensus_hash,
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                    true,
-                );
-
-                let index_microblock_hash = StacksBlockHeader::make_index_block_hash(
-                    &parent_consensus_hash,
-                    &mblocks.last().as_ref().unwrap().block_hash(),
-                );
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &parent_consensus_hash,
-                        &parent_block.block_hash(),
-                        &mblock,
-                    );
-                }
-
-                set_microblocks_processed(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                    &mblocks.last().as_ref().unwrap().block_hash(),
-                );
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                convo_client.new_getmicroblocks_indexed(index_microblock_hash)
             },
             |ref http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
-                match (*http_response).clone() {
-                    HttpResponseType::Microblocks(_, mut microblocks) => {
-                        microblocks.reverse();
-                        assert_eq!(microblocks.len(), (*server_microblocks_cell.borrow()).len());
-                        assert_eq!(microblocks, *server_microblocks_cell.borrow());
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetDataVar(response_md, data) => {
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
+                            Value::Int(1)
+                        );
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4813,94 +7342,26 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_confirmed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_get_data_var_nonexistant() {
         test_rpc(
             function_name!(),
-            40042,
-            40043,
-            50042,
-            50043,
+            40125,
+            40126,
+            50125,
+            50126,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                convo_client.new_getdatavar(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "bar-nonexistant".try_into().unwrap(),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
                 )
-                .unwrap();
-
-                let parent_block = make_codec_test_block(25);
-                let parent_consensus_hash = ConsensusHash([0x02; 20]);
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
-                mblocks.truncate(15);
-
-                let mut child_block = make_codec_test_block(25);
-                let child_consensus_hash = ConsensusHash([0x03; 20]);
-
-                child_block.header.parent_block = parent_block.block_hash();
-                child_block.header.parent_microblock =
-                    mblocks.last().as_ref().unwrap().block_hash();
-                child_block.header.parent_microblock_sequence =
-                    mblocks.last().as_ref().unwrap().header.sequence;
-
-                let child_index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                );
-
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &parent_consensus_hash,
-                    &parent_block,
-                    &ConsensusHash([0x01; 20]),
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &parent_consensus_hash,
-                    &parent_block.block_hash(),
-                    true,
-                );
-
-                store_staging_block(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block,
-                    &parent_consensus_hash,
-                    456,
-                    123,
-                );
-                set_block_processed(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                    true,
-                );
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &parent_consensus_hash,
-                        &parent_block.block_hash(),
-                        &mblock,
-                    );
-                }
-
-                set_microblocks_processed(
-                    peer_server.chainstate(),
-                    &child_consensus_hash,
-                    &child_block.block_hash(),
-                    &mblocks.last().as_ref().unwrap().block_hash(),
-                );
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                convo_client.new_getmicroblocks_confirmed(child_index_block_hash)
             },
             |ref http_request,
              ref http_response,
@@ -4909,15 +7370,13 @@ mod test {
              ref convo_client,
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
-                match (*http_response).clone() {
-                    HttpResponseType::Microblocks(_, mut microblocks) => {
-                        microblocks.reverse();
-                        assert_eq!(microblocks.len(), (*server_microblocks_cell.borrow()).len());
-                        assert_eq!(microblocks, *server_microblocks_cell.borrow());
+                match http_response {
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert_eq!(msg, "Data var not found");
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4927,46 +7386,37 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_unconfirmed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_get_map_entry() {
+        // Test v2/map_entry (aka GetMapEntry) endpoint.
+        // In this test, we don't set any tip parameters, and we expect that querying for map data
+        // against the canonical Stacks tip will succeed.
         test_rpc(
             function_name!(),
-            40050,
-            40051,
-            50050,
-            50051,
+            40130,
+            40131,
+            50130,
+            50131,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                let principal =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                convo_client.new_getmapentry(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "unit-map".try_into().unwrap(),
+                    Value::Tuple(
+                        TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
+                            .unwrap(),
+                    ),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
                 )
-                .unwrap();
-
-                let consensus_hash = ConsensusHash([0x02; 20]);
-                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
-                let index_block_hash =
-                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
-                mblocks.truncate(15);
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &consensus_hash,
-                        &anchored_block_hash,
-                        &mblock,
-                    );
-                }
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                // start at seq 5
-                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 5)
             },
             |ref http_request,
              ref http_response,
@@ -4976,16 +7426,19 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::Microblocks(response_md, microblocks) => {
-                        assert_eq!(microblocks.len(), 10);
+                    HttpResponseType::GetMapEntry(response_md, data) => {
                         assert_eq!(
-                            *microblocks,
-                            (*server_microblocks_cell.borrow())[5..].to_vec()
+                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
+                            Value::some(Value::Tuple(
+                                TupleData::from_data(vec![("units".into(), Value::Int(123))])
+                                    .unwrap()
+                            ))
+                            .unwrap()
                         );
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -4995,52 +7448,44 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_unconfirmed_transaction() {
-        let last_txid = RefCell::new(Txid([0u8; 32]));
-        let last_mblock = RefCell::new(BlockHeaderHash([0u8; 32]));
-
+    fn test_rpc_get_map_entry_unconfirmed() {
+        // Test v2/map_entry (aka GetMapEntry) endpoint.
+        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying for map data
+        // against the unconfirmed state will succeed.
         test_rpc(
             function_name!(),
-            40052,
-            40053,
-            50052,
-            50053,
+            40140,
+            40141,
+            50140,
+            50141,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                let principal =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                convo_client.new_getmapentry(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "unit-map".try_into().unwrap(),
+                    Value::Tuple(
+                        TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
+                            .unwrap(),
+                    ),
+                    TipRequest::SpecificTip(unconfirmed_tip),
+                    false,
                 )
-                .unwrap();
-
-                let sortdb = peer_server.sortdb.take().unwrap();
-                Relayer::setup_unconfirmed_state(peer_server.chainstate(), &sortdb).unwrap();
-                peer_server.sortdb = Some(sortdb);
-
-                assert!(peer_server.chainstate().unconfirmed_state.is_some());
-                let (txid, mblock_hash) = match peer_server.chainstate().unconfirmed_state {
-                    Some(ref unconfirmed) => {
-                        assert!(unconfirmed.mined_txs.len() > 0);
-                        let mut txid = Txid([0u8; 32]);
-                        let mut mblock_hash = BlockHeaderHash([0u8; 32]);
-                        for (next_txid, (_, mbh, ..)) in unconfirmed.mined_txs.iter() {
-                            txid = next_txid.clone();
-                            mblock_hash = mbh.clone();
-                            break;
-                        }
-                        (txid, mblock_hash)
-                    }
-                    None => {
-                        panic!("No unconfirmed state");
-                    }
-                };
-
-                *last_txid.borrow_mut() = txid.clone();
-                *last_mblock.borrow_mut() = mblock_hash.clone();
-
-                convo_client.new_gettransaction_unconfirmed(txid)
             },
             |ref http_request,
              ref http_response,
@@ -5050,23 +7495,19 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::UnconfirmedTransaction(response_md, unconfirmed_resp) => {
-                        assert_eq!(
-                            unconfirmed_resp.status,
-                            UnconfirmedTransactionStatus::Microblock {
-                                block_hash: (*last_mblock.borrow()).clone(),
-                                seq: 0
-                            }
+                    HttpResponseType::GetMapEntry(response_md, data) => {
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
+                            Value::some(Value::Tuple(
+                                TupleData::from_data(vec![("units".into(), Value::Int(1))])
+                                    .unwrap()
+                            ))
+                            .unwrap()
                         );
-                        let tx = StacksTransaction::consensus_deserialize(
-                            &mut &hex_bytes(&unconfirmed_resp.tx).unwrap()[..],
-                        )
-                        .unwrap();
-                        assert_eq!(tx.txid(), *last_txid.borrow());
                         true
                     }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -5076,27 +7517,34 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_getblock() {
+    fn test_rpc_get_map_entry_use_latest_tip() {
         test_rpc(
             function_name!(),
-            40060,
-            40061,
-            50060,
-            50061,
+            40142,
+            40143,
+            50142,
+            50143,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
-                );
-
-                // now ask for it
-                convo_client.new_getblock(index_block_hash)
+                let principal =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                convo_client.new_getmapentry(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    "unit-map".try_into().unwrap(),
+                    Value::Tuple(
+                        TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
+                            .unwrap(),
+                    ),
+                    TipRequest::UseLatestAnchoredTip,
+                    false,
+                )
             },
             |ref http_request,
              ref http_response,
@@ -5106,9 +7554,19 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::GetMapEntry(response_md, data) => {
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
+                            Value::some(Value::Tuple(
+                                TupleData::from_data(vec![("units".into(), Value::Int(1))])
+                                    .unwrap()
+                            ))
+                            .unwrap()
+                        );
+                        true
+                    }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -5118,27 +7576,27 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_index_getmicroblocks() {
+    fn test_rpc_get_contract_abi() {
+        // Test /v2/contracts/interface (aka GetContractABI) endpoint.
+        // In this test, we don't set any tip parameters, and we expect that querying
+        // against the canonical Stacks tip will succeed.
         test_rpc(
             function_name!(),
-            40070,
-            40071,
-            50070,
-            50071,
+            40150,
+            40151,
+            50150,
+            50151,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
-                );
-
-                // now ask for it
-                convo_client.new_getmicroblocks_indexed(index_block_hash)
+                convo_client.new_getcontractabi(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    TipRequest::UseLatestAnchoredTip,
+                )
             },
             |ref http_request,
              ref http_response,
@@ -5148,9 +7606,12 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::NotFound(..) => {
+                        // not confirmed yet
+                        true
+                    }
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -5160,27 +7621,34 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_confirmed_getmicroblocks() {
+    fn test_rpc_get_contract_abi_unconfirmed() {
+        // Test /v2/contracts/interface (aka GetContractABI) endpoint.
+        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying
+        // against the unconfirmed state will succeed.
         test_rpc(
             function_name!(),
-            40072,
-            40073,
-            50072,
-            50073,
+            40152,
+            40153,
+            50152,
+            50153,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
-                );
-
-                // now ask for it
-                convo_client.new_getmicroblocks_confirmed(index_block_hash)
+                let unconfirmed_tip = peer_client
+                    .chainstate()
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                convo_client.new_getcontractabi(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    TipRequest::SpecificTip(unconfirmed_tip),
+                )
             },
             |ref http_request,
              ref http_response,
@@ -5190,9 +7658,9 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::GetContractABI(response_md, data) => true,
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -5202,46 +7670,24 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_unconfirmed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_get_contract_abi_use_latest_tip() {
         test_rpc(
             function_name!(),
-            40080,
-            40081,
-            50080,
-            50081,
+            40154,
+            40155,
+            50154,
+            50155,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                convo_client.new_getcontractabi(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    TipRequest::UseLatestAnchoredTip,
                 )
-                .unwrap();
-
-                let consensus_hash = ConsensusHash([0x02; 20]);
-                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
-                let index_block_hash =
-                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
-                mblocks.truncate(15);
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &consensus_hash,
-                        &anchored_block_hash,
-                        &mblock,
-                    );
-                }
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                // start at seq 16 (which doesn't exist)
-                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 16)
             },
             |ref http_request,
              ref http_response,
@@ -5251,9 +7697,9 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::GetContractABI(response_md, data) => true,
                     _ => {
-                        error!("Invalid response: {:?}", &http_response);
+                        error!("Invalid response; {:?}", &http_response);
                         false
                     }
                 }
@@ -5263,29 +7709,32 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_contract_src() {
-        // Test v2/contracts/source (aka GetContractSrc) endpoint.
-        // In this test, we don't set any tip parameters, and allow the endpoint to execute against
-        // the canonical Stacks tip.
-        // The contract source we are querying for exists in the anchored state, so we expect the
-        // query to succeed.
+    fn test_rpc_call_read_only() {
+        // Test /v2/contracts/call-read (aka CallReadOnlyFunction) endpoint.
+        // In this test, we don't set any tip parameters, and we expect that querying
+        // against the canonical Stacks tip will succeed.
         test_rpc(
             function_name!(),
-            40090,
-            40091,
-            50090,
-            50091,
+            40170,
+            40171,
+            50170,
+            50171,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getcontractsrc(
+                convo_client.new_callreadonlyfunction(
                     StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
                         .unwrap(),
-                    "hello-world".try_into().unwrap(),
+                    "hello-world-unconfirmed".try_into().unwrap(),
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
+                    "ro-test".try_into().unwrap(),
+                    vec![],
                     TipRequest::UseLatestAnchoredTip,
-                    false,
                 )
             },
             |ref http_request,
@@ -5296,8 +7745,11 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::GetContractSrc(response_md, data) => {
-                        assert_eq!(data.source, TEST_CONTRACT);
+                    HttpResponseType::CallReadOnlyFunction(response_md, data) => {
+                        assert!(data.cause.is_some());
+                        assert!(data.cause.clone().unwrap().find("NoSuchContract").is_some());
+                        assert!(!data.okay);
+                        assert!(data.result.is_none());
                         true
                     }
                     _ => {
@@ -5311,29 +7763,32 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_contract_src_unconfirmed_with_canonical_tip() {
-        // Test v2/contracts/source (aka GetContractSrc) endpoint.
-        // In this test, we don't set any tip parameters, and allow the endpoint to execute against
-        // the canonical Stacks tip.
-        // The contract source we are querying for only exists in the unconfirmed state, so we
-        // expect the query to fail.
+    fn test_rpc_call_read_only_use_latest_tip() {
+        // Test /v2/contracts/call-read (aka CallReadOnlyFunction) endpoint.
+        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying
+        // against the unconfirmed state will succeed.
         test_rpc(
             function_name!(),
-            40100,
-            40101,
-            50100,
-            50101,
+            40172,
+            40173,
+            50172,
+            50173,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getcontractsrc(
+                convo_client.new_callreadonlyfunction(
                     StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
                         .unwrap(),
                     "hello-world-unconfirmed".try_into().unwrap(),
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
+                    "ro-test".try_into().unwrap(),
+                    vec![],
                     TipRequest::UseLatestAnchoredTip,
-                    false,
                 )
             },
             |ref http_request,
@@ -5344,8 +7799,14 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(_, error_str) => {
-                        assert_eq!(error_str, "No contract source data found");
+                    HttpResponseType::CallReadOnlyFunction(response_md, data) => {
+                        assert!(data.okay);
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.result.clone().unwrap())
+                                .unwrap(),
+                            Value::okay(Value::Int(1)).unwrap()
+                        );
+                        assert!(data.cause.is_none());
                         true
                     }
                     _ => {
@@ -5359,17 +7820,13 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_contract_src_with_unconfirmed_tip() {
-        // Test v2/contracts/source (aka GetContractSrc) endpoint.
-        // In this test, we set `tip_req` to be the unconfirmed chain tip.
-        // The contract source we are querying for exists in the unconfirmed state, so we expect
-        // the query to succeed.
+    fn test_rpc_call_read_only_unconfirmed() {
         test_rpc(
             function_name!(),
-            40102,
-            40103,
-            50102,
-            50103,
+            40180,
+            40181,
+            50180,
+            50181,
             true,
             |ref mut peer_client,
              ref mut convo_client,
@@ -5382,12 +7839,17 @@ mod test {
                     .unwrap()
                     .unconfirmed_chain_tip
                     .clone();
-                convo_client.new_getcontractsrc(
+                convo_client.new_callreadonlyfunction(
                     StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
                         .unwrap(),
                     "hello-world-unconfirmed".try_into().unwrap(),
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
+                    "ro-test".try_into().unwrap(),
+                    vec![],
                     TipRequest::SpecificTip(unconfirmed_tip),
-                    false,
                 )
             },
             |ref http_request,
@@ -5398,8 +7860,14 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::GetContractSrc(response_md, data) => {
-                        assert_eq!(data.source, TEST_CONTRACT_UNCONFIRMED);
+                    HttpResponseType::CallReadOnlyFunction(response_md, data) => {
+                        assert!(data.okay);
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.result.clone().unwrap())
+                                .unwrap(),
+                            Value::okay(Value::Int(1)).unwrap()
+                        );
+                        assert!(data.cause.is_none());
                         true
                     }
                     _ => {
@@ -5413,29 +7881,20 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_contract_src_use_latest_tip() {
-        // Test v2/contracts/source (aka GetContractSrc) endpoint.
-        // In this test, we set `tip_req` to UseLatestUnconfirmedTip.
-        // The contract source we are querying for exists in the unconfirmed state, so we expect
-        // the query to succeed.
+    fn test_rpc_getattachmentsinv_limit_reached() {
         test_rpc(
             function_name!(),
-            40104,
-            40105,
-            50104,
-            50105,
+            40190,
+            40191,
+            50190,
+            50191,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getcontractsrc(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world-unconfirmed".try_into().unwrap(),
-                    TipRequest::UseLatestAnchoredTip,
-                    false,
-                )
+                let pages_indexes = HashSet::from_iter(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+                convo_client.new_getattachmentsinv(StacksBlockId([0x00; 32]), pages_indexes)
             },
             |ref http_request,
              ref http_response,
@@ -5444,15 +7903,16 @@ mod test {
              ref convo_client,
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
+                println!("{:?}", http_response);
                 match http_response {
-                    HttpResponseType::GetContractSrc(response_md, data) => {
-                        assert_eq!(data.source, TEST_CONTRACT_UNCONFIRMED);
+                    HttpResponseType::BadRequest(_, msg) => {
+                        assert_eq!(
+                            msg,
+                            "Number of attachment inv pages is limited by 8 per request"
+                        );
                         true
                     }
-                    _ => {
-                        error!("Invalid response; {:?}", &http_response);
-                        false
-                    }
+                    _ => false,
                 }
             },
         );
@@ -5460,24 +7920,21 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_account() {
+    fn test_rpc_mempool_query_txtags() {
         test_rpc(
             function_name!(),
-            40110,
-            40111,
-            50110,
-            50111,
-            true,
+            40813,
+            40814,
+            50813,
+            50814,
+            false,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getaccount(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal(),
-                    TipRequest::UseLatestAnchoredTip,
-                    false,
+                convo_client.new_mempool_query(
+                    MemPoolSyncData::TxTags([0u8; 32], vec![]),
+                    Some(Txid([0u8; 32])),
                 )
             },
             |ref http_request,
@@ -5487,45 +7944,41 @@ mod test {
              ref convo_client,
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
+                println!("{:?}", http_response);
                 match http_response {
-                    HttpResponseType::GetAccount(response_md, data) => {
-                        assert_eq!(data.nonce, 2);
-                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
-                        assert_eq!(balance, 1000000000);
+                    HttpResponseType::MemPoolTxs(_, _, txs) => {
+                        // got everything
+                        assert_eq!(txs.len(), 10);
                         true
                     }
-                    _ => {
-                        error!("Invalid response; {:?}", &http_response);
-                        false
-                    }
+                    _ => false,
                 }
             },
         );
     }
 
-    /// In this test, the query parameter `tip_req` is set to UseLatestUnconfirmedTip, and so we expect the
-    /// tip used for the query to be the latest microblock.
-    /// We check that the account state matches the state in the most recent microblock.
     #[test]
     #[ignore]
-    fn test_rpc_get_account_use_latest_tip() {
+    fn test_rpc_mempool_query_bloom() {
         test_rpc(
             function_name!(),
-            40112,
-            40113,
-            50112,
-            50113,
-            true,
+            40815,
+            40816,
+            50815,
+            50816,
+            false,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getaccount(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal(),
-                    TipRequest::UseLatestAnchoredTip,
-                    false,
+                // empty bloom filter
+                convo_client.new_mempool_query(
+                    MemPoolSyncData::BloomFilter(BloomFilter::new(
+                        BLOOM_COUNTER_ERROR_RATE,
+                        MAX_BLOOM_COUNTER_TXS,
+                        BloomNodeHasher::new(&[0u8; 32]),
+                    )),
+                    Some(Txid([0u8; 32])),
                 )
             },
             |ref http_request,
@@ -5535,64 +7988,80 @@ mod test {
              ref convo_client,
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
+                println!("{:?}", http_response);
                 match http_response {
-                    HttpResponseType::GetAccount(response_md, data) => {
-                        assert_eq!(data.nonce, 4);
-                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
-                        assert_eq!(balance, 999999877);
+                    HttpResponseType::MemPoolTxs(_, _, txs) => {
+                        // got everything
+                        assert_eq!(txs.len(), 10);
                         true
                     }
-                    _ => {
-                        error!("Invalid response; {:?}", &http_response);
-                        false
-                    }
+                    _ => false,
                 }
             },
         );
     }
 
-    /// In this test, the query parameter `tip_req` is set to UseLatestUnconfirmedTip, but we did not generate
-    /// microblocks in the rpc test. Thus, we expect the tip used for the query to be the previous
-    /// anchor block (which is the latest tip).
-    /// We check that the account state matches the state in the previous anchor block.
     #[test]
     #[ignore]
-    fn test_rpc_get_account_use_latest_tip_no_microblocks() {
+    fn test_rpc_getsortition() {
+        let sortition_server_info = RefCell::new(None);
         test_rpc(
             function_name!(),
-            40114,
-            40115,
-            50114,
-            50115,
-            false,
+            40817,
+            40818,
+            50817,
+            50818,
+            true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getaccount(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal(),
-                    TipRequest::UseLatestAnchoredTip,
-                    false,
+                let sortdb = peer_server.sortdb.as_ref().unwrap();
+                let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+                let sortition_info = RPCSortitionInfo::from_db(
+                    sortdb,
+                    &SortitionQuery::BurnHeight(tip.block_height),
                 )
+                .unwrap();
+                *sortition_server_info.borrow_mut() = Some(sortition_info);
+                convo_client.new_getsortition(SortitionQuery::ConsensusHash(tip.consensus_hash))
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
+             convo_client,
+             convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::GetAccount(response_md, data) => {
-                        assert_eq!(data.nonce, 2);
-                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
-                        assert_eq!(balance, 1000000000);
+                    HttpResponseType::GetSortition(response_md, sortition_info) => {
+                        assert_eq!(
+                            Some((*sortition_info).clone()),
+                            *sortition_server_info.borrow()
+                        );
+                        assert!(sortition_info.canonical);
+                        assert!(sortition_info.sortition);
+                        let winners: Vec<_> = sortition_info
+                            .block_commits
+                            .iter()
+                            .filter(|commit| commit.won)
+                            .collect();
+                        assert_eq!(winners.len(), 1);
+                        assert_eq!(Some(winners[0].txid), sortition_info.winning_block_txid);
+                        assert_eq!(Some(winners[0].vrf_seed), sortition_info.vrf_seed);
+                        assert!(winners[0].vrf_public_key.is_some());
+                        assert_eq!(
+                            sortition_info.block_commits_burn,
+                            sortition_info
+                                .block_commits
+                                .iter()
+                                .map(|commit| commit.burn_fee)
+                                .sum::<u64>()
+                        );
                         true
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -5602,49 +8071,34 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_account_unconfirmed() {
+    fn test_rpc_getsortition_notfound() {
         test_rpc(
             function_name!(),
-            40120,
-            40121,
-            50120,
-            50121,
+            40819,
+            40820,
+            50819,
+            50820,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let unconfirmed_tip = peer_client
-                    .chainstate()
-                    .unconfirmed_state
-                    .as_ref()
-                    .unwrap()
-                    .unconfirmed_chain_tip
-                    .clone();
-                convo_client.new_getaccount(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal(),
-                    TipRequest::SpecificTip(unconfirmed_tip),
-                    false,
-                )
+                convo_client.new_getsortition(SortitionQuery::BurnHeight(u32::MAX as u64))
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
+             convo_client,
+             convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::GetAccount(response_md, data) => {
-                        assert_eq!(data.nonce, 4);
-                        let balance = u128::from_str_radix(&data.balance[2..], 16).unwrap();
-                        assert_eq!(balance, 1000000000 - 123);
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert_eq!(msg, "No such sortition");
                         true
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -5654,44 +8108,110 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_data_var() {
+    fn test_rpc_getrewardcycle() {
+        let reward_cycle_server_info = RefCell::new(None);
         test_rpc(
             function_name!(),
-            40122,
-            40123,
-            50122,
-            50123,
+            40821,
+            40822,
+            50821,
+            50822,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getdatavar(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    "bar".try_into().unwrap(),
-                    TipRequest::UseLatestAnchoredTip,
-                    false,
-                )
+                let burnchain = peer_server.config.burnchain.clone();
+                let sortdb = peer_server.sortdb.as_ref().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+                let reward_cycle = burnchain
+                    .block_height_to_reward_cycle(tip.block_height)
+                    .unwrap();
+                let reward_cycle_info =
+                    RPCRewardCycleInfo::from_db(sortdb, chainstate, &burnchain, reward_cycle)
+                        .unwrap();
+                *reward_cycle_server_info.borrow_mut() = Some(reward_cycle_info);
+                convo_client.new_getrewardcycle(reward_cycle)
             },
-            |ref http_request,
+            |http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             convo_client,
+             convo_server| {
+                match http_response {
+                    HttpResponseType::GetRewardCycle(_, reward_cycle_info) => {
+                        assert_eq!(
+                            Some((*reward_cycle_info).clone()),
+                            *reward_cycle_server_info.borrow()
+                        );
+                        assert!(!reward_cycle_info.payouts.is_empty());
+                        assert_eq!(
+                            reward_cycle_info.payouts[0].burn_block_height,
+                            reward_cycle_info.first_burn_block_height
+                        );
+
+                        // every payout is accounted for in the totals
+                        let mut paid = 0;
+                        let mut burnt = 0;
+                        for block_payouts in reward_cycle_info.payouts.iter() {
+                            for commit in block_payouts.block_commits.iter() {
+                                paid += commit
+                                    .payouts
+                                    .iter()
+                                    .map(|payout| payout.amount)
+                                    .sum::<u64>();
+                                burnt += commit.burnt;
+                            }
+                        }
+                        assert_eq!(
+                            paid,
+                            reward_cycle_info
+                                .total_payouts
+                                .iter()
+                                .map(|payout| payout.amount)
+                                .sum::<u64>()
+                        );
+                        assert_eq!(burnt, reward_cycle_info.total_burnt);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getrewardcycle_notfound() {
+        test_rpc(
+            function_name!(),
+            40823,
+            40824,
+            50823,
+            50824,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| { convo_client.new_getrewardcycle(1_000_000) },
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::GetDataVar(response_md, data) => {
-                        assert_eq!(
-                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
-                            Value::Int(0)
-                        );
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert_eq!(msg, "Reward cycle has not started");
                         true
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -5701,51 +8221,72 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_data_var_unconfirmed() {
+    fn test_rpc_getburnops() {
+        let burn_ops_server_info = RefCell::new(None);
         test_rpc(
             function_name!(),
-            40124,
-            40125,
-            50124,
-            50125,
+            40825,
+            40826,
+            50825,
+            50826,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let unconfirmed_tip = peer_client
-                    .chainstate()
-                    .unconfirmed_state
-                    .as_ref()
-                    .unwrap()
-                    .unconfirmed_chain_tip
-                    .clone();
-                convo_client.new_getdatavar(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    "bar".try_into().unwrap(),
-                    TipRequest::SpecificTip(unconfirmed_tip),
-                    false,
+                let sender =
+                    StacksAddress::new(C32_ADDRESS_VERSION_TESTNET_SINGLESIG, Hash160([0x11; 20]));
+                let sortdb = peer_server.sortdb.as_mut().unwrap();
+                let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+                let transfer_op = BlockstackOperationType::TransferStx(TransferStxOp {
+                    sender,
+                    recipient: sender,
+                    transfered_ustx: 123,
+                    memo: vec![],
+
+                    txid: Txid([0x22; 32]),
+                    vtxindex: 1,
+                    block_height: tip.block_height,
+                    burn_header_hash: tip.burn_header_hash,
+                });
+                let mut tx = sortdb.tx_begin_at_tip();
+                tx.insert_stacks_op_record(
+                    &transfer_op,
+                    Some("Transfer STX must not send to self"),
                 )
+                .unwrap();
+                tx.commit().unwrap();
+
+                let sortdb = peer_server.sortdb.as_ref().unwrap();
+                let chainstate = &peer_server.stacks_node.as_ref().unwrap().chainstate;
+                let query = BurnOpsQuery::Sender(sender);
+                let burn_ops = RPCBurnOpInfo::from_db(sortdb, chainstate, &query).unwrap();
+                *burn_ops_server_info.borrow_mut() = Some(burn_ops);
+                convo_client.new_getburnops(query)
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::GetDataVar(response_md, data) => {
+                    HttpResponseType::GetBurnOps(_, burn_ops) => {
+                        assert_eq!(Some((*burn_ops).clone()), *burn_ops_server_info.borrow());
+                        assert_eq!(burn_ops.len(), 1);
+                        assert_eq!(burn_ops[0].txid, Txid([0x22; 32]));
+                        assert_eq!(burn_ops[0].op_type, "transfer-stx");
+                        assert!(burn_ops[0].canonical);
+                        assert!(!burn_ops[0].accepted);
                         assert_eq!(
-                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
-                            Value::Int(1)
+                            burn_ops[0].rejection_reason,
+                            Some("Transfer STX must not send to self".to_string())
                         );
+                        assert!(burn_ops[0].stacks_results.is_empty());
                         true
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -5755,41 +8296,33 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_data_var_nonexistant() {
+    fn test_rpc_getburnops_notfound() {
         test_rpc(
             function_name!(),
-            40125,
-            40126,
-            50125,
-            50126,
+            40827,
+            40828,
+            50827,
+            50828,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getdatavar(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    "bar-nonexistant".try_into().unwrap(),
-                    TipRequest::UseLatestAnchoredTip,
-                    false,
-                )
+                convo_client.new_getburnops(BurnOpsQuery::Txid(Txid([0x33; 32])))
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
                     HttpResponseType::NotFound(_, msg) => {
-                        assert_eq!(msg, "Data var not found");
+                        assert_eq!(msg, "No such burnchain operation");
                         true
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -5799,59 +8332,68 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_map_entry() {
-        // Test v2/map_entry (aka GetMapEntry) endpoint.
-        // In this test, we don't set any tip parameters, and we expect that querying for map data
-        // against the canonical Stacks tip will succeed.
+    fn test_rpc_getaffirmationmaps() {
+        let affirmation_maps_server_info = RefCell::new(None);
         test_rpc(
             function_name!(),
-            40130,
-            40131,
-            50130,
-            50131,
+            40829,
+            40830,
+            50829,
+            50830,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let principal =
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal();
-                convo_client.new_getmapentry(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    "unit-map".try_into().unwrap(),
-                    Value::Tuple(
-                        TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
-                            .unwrap(),
-                    ),
-                    TipRequest::UseLatestAnchoredTip,
-                    false,
+                let sortdb = peer_server.sortdb.as_ref().unwrap();
+                let network = &peer_server.network;
+                let burnchain_db = network.burnchain.open_burnchain_db(false).unwrap();
+                let tip_reward_cycle = network
+                    .burnchain
+                    .block_height_to_reward_cycle(network.burnchain_tip.block_height)
+                    .unwrap();
+                let affirmation_maps = RPCAffirmationMaps {
+                    heaviest: network.heaviest_affirmation_map.clone(),
+                    canonical: network.tentative_best_affirmation_map.clone(),
+                    stacks_tip: network.stacks_tip_affirmation_map.clone(),
+                    sortition_tip: network.sortition_tip_affirmation_map.clone(),
+                };
+                let affirmation_maps_info = RPCAffirmationMapsInfo::from_db(
+                    sortdb,
+                    &burnchain_db,
+                    &network.burnchain,
+                    affirmation_maps,
+                    (tip_reward_cycle + 1).saturating_sub(AFFIRMATION_MAPS_REWARD_CYCLES),
+                    tip_reward_cycle,
                 )
+                .unwrap();
+                *affirmation_maps_server_info.borrow_mut() = Some(affirmation_maps_info);
+                convo_client.new_getaffirmationmaps(None)
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::GetMapEntry(response_md, data) => {
+                    HttpResponseType::GetAffirmationMaps(_, affirmation_maps_info) => {
                         assert_eq!(
-                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
-                            Value::some(Value::Tuple(
-                                TupleData::from_data(vec![("units".into(), Value::Int(123))])
-                                    .unwrap()
-                            ))
-                            .unwrap()
+                            Some((*affirmation_maps_info).clone()),
+                            *affirmation_maps_server_info.borrow()
+                        );
+                        assert_eq!(
+                            affirmation_maps_info
+                                .reward_cycles
+                                .last()
+                                .unwrap()
+                                .reward_cycle,
+                            affirmation_maps_info.reward_cycle
                         );
                         true
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -5861,66 +8403,33 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_map_entry_unconfirmed() {
-        // Test v2/map_entry (aka GetMapEntry) endpoint.
-        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying for map data
-        // against the unconfirmed state will succeed.
+    fn test_rpc_getaffirmationmaps_notfound() {
         test_rpc(
             function_name!(),
-            40140,
-            40141,
-            50140,
-            50141,
+            40831,
+            40832,
+            50831,
+            50832,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let unconfirmed_tip = peer_client
-                    .chainstate()
-                    .unconfirmed_state
-                    .as_ref()
-                    .unwrap()
-                    .unconfirmed_chain_tip
-                    .clone();
-                let principal =
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal();
-                convo_client.new_getmapentry(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    "unit-map".try_into().unwrap(),
-                    Value::Tuple(
-                        TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
-                            .unwrap(),
-                    ),
-                    TipRequest::SpecificTip(unconfirmed_tip),
-                    false,
-                )
+                convo_client.new_getaffirmationmaps(Some(1_000_000))
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::GetMapEntry(response_md, data) => {
-                        assert_eq!(
-                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
-                            Value::some(Value::Tuple(
-                                TupleData::from_data(vec![("units".into(), Value::Int(1))])
-                                    .unwrap()
-                            ))
-                            .unwrap()
-                        );
+                    HttpResponseType::NotFound(_, msg) => {
+                        assert_eq!(msg, "Reward cycle has not started");
                         true
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -5929,57 +8438,33 @@ mod test {
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_get_map_entry_use_latest_tip() {
+    fn test_rpc_admin_not_on_public_port() {
         test_rpc(
             function_name!(),
-            40142,
-            40143,
-            50142,
-            50143,
+            40833,
+            40834,
+            50833,
+            50834,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let principal =
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal();
-                convo_client.new_getmapentry(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    "unit-map".try_into().unwrap(),
-                    Value::Tuple(
-                        TupleData::from_data(vec![("account".into(), Value::Principal(principal))])
-                            .unwrap(),
-                    ),
-                    TipRequest::UseLatestAnchoredTip,
-                    false,
+                convo_client.new_admin(
+                    Some(AdminToken::new("hunter2")),
+                    AdminRequest::DropMempoolTxs(vec![Txid([0x11; 32])]),
                 )
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::GetMapEntry(response_md, data) => {
-                        assert_eq!(
-                            Value::try_deserialize_hex_untyped(&data.data).unwrap(),
-                            Value::some(Value::Tuple(
-                                TupleData::from_data(vec![("units".into(), Value::Int(1))])
-                                    .unwrap()
-                            ))
-                            .unwrap()
-                        );
-                        true
-                    }
+                    HttpResponseType::NotFound(..) => true,
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -5988,43 +8473,42 @@ mod test {
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_get_contract_abi() {
-        // Test /v2/contracts/interface (aka GetContractABI) endpoint.
-        // In this test, we don't set any tip parameters, and we expect that querying
-        // against the canonical Stacks tip will succeed.
+    fn test_rpc_admin_bad_token() {
         test_rpc(
             function_name!(),
-            40150,
-            40151,
-            50150,
-            50151,
+            40835,
+            40836,
+            50835,
+            50836,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getcontractabi(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world-unconfirmed".try_into().unwrap(),
-                    TipRequest::UseLatestAnchoredTip,
+                convo_server.set_admin_token(AdminToken::new("hunter2"));
+                convo_client.new_admin(
+                    Some(AdminToken::new("hunter3")),
+                    AdminRequest::DropMempoolTxs(vec![Txid([0x11; 32])]),
                 )
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::NotFound(..) => {
-                        // not confirmed yet
-                        true
+                    HttpResponseType::Unauthorized(..) => {
+                        // nothing was dropped
+                        !peer_server
+                            .mempool
+                            .as_ref()
+                            .unwrap()
+                            .is_tx_blacklisted(&Txid([0x11; 32]))
+                            .unwrap()
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -6033,47 +8517,31 @@ mod test {
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_get_contract_abi_unconfirmed() {
-        // Test /v2/contracts/interface (aka GetContractABI) endpoint.
-        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying
-        // against the unconfirmed state will succeed.
+    fn test_rpc_admin_port_refuses_public_endpoints() {
         test_rpc(
             function_name!(),
-            40152,
-            40153,
-            50152,
-            50153,
+            40837,
+            40838,
+            50837,
+            50838,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let unconfirmed_tip = peer_client
-                    .chainstate()
-                    .unconfirmed_state
-                    .as_ref()
-                    .unwrap()
-                    .unconfirmed_chain_tip
-                    .clone();
-                convo_client.new_getcontractabi(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world-unconfirmed".try_into().unwrap(),
-                    TipRequest::SpecificTip(unconfirmed_tip),
-                )
+                convo_server.set_admin_token(AdminToken::new("hunter2"));
+                convo_client.new_getinfo(None)
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::GetContractABI(response_md, data) => true,
+                    HttpResponseType::NotFound(..) => true,
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -6082,37 +8550,40 @@ mod test {
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_get_contract_abi_use_latest_tip() {
+    fn test_rpc_admin_drop_mempool_txs() {
         test_rpc(
             function_name!(),
-            40154,
-            40155,
-            50154,
-            50155,
+            40839,
+            40840,
+            50839,
+            50840,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getcontractabi(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world-unconfirmed".try_into().unwrap(),
-                    TipRequest::UseLatestAnchoredTip,
+                convo_server.set_admin_token(AdminToken::new("hunter2"));
+                convo_client.new_admin(
+                    Some(AdminToken::new("hunter2")),
+                    AdminRequest::DropMempoolTxs(vec![Txid([0x11; 32]), Txid([0x22; 32])]),
                 )
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::GetContractABI(response_md, data) => true,
+                    HttpResponseType::AdminResult(_, result) => {
+                        assert_eq!(result.message, "Dropped and blacklisted 2 transaction(s)");
+                        let mempool = peer_server.mempool.as_ref().unwrap();
+                        assert!(mempool.is_tx_blacklisted(&Txid([0x11; 32])).unwrap());
+                        assert!(mempool.is_tx_blacklisted(&Txid([0x22; 32])).unwrap());
+                        true
+                    }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -6121,52 +8592,48 @@ mod test {
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_call_read_only() {
-        // Test /v2/contracts/call-read (aka CallReadOnlyFunction) endpoint.
-        // In this test, we don't set any tip parameters, and we expect that querying
-        // against the canonical Stacks tip will succeed.
+    fn test_rpc_admin_ban_peer() {
         test_rpc(
             function_name!(),
-            40170,
-            40171,
-            50170,
-            50171,
+            40841,
+            40842,
+            50841,
+            50842,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_callreadonlyfunction(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world-unconfirmed".try_into().unwrap(),
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal(),
-                    None,
-                    "ro-test".try_into().unwrap(),
-                    vec![],
-                    TipRequest::UseLatestAnchoredTip,
+                convo_server.set_admin_token(AdminToken::new("hunter2"));
+                convo_client.new_admin(
+                    Some(AdminToken::new("hunter2")),
+                    AdminRequest::BanPeer("1.2.3.4:20444".parse().unwrap(), 3600),
                 )
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::CallReadOnlyFunction(response_md, data) => {
-                        assert!(data.cause.is_some());
-                        assert!(data.cause.clone().unwrap().find("NoSuchContract").is_some());
-                        assert!(!data.okay);
-                        assert!(data.result.is_none());
+                    HttpResponseType::AdminResult(_, result) => {
+                        let banned_until = result.banned_until.unwrap();
+                        assert!(banned_until >= get_epoch_time_secs() + 3500);
+                        let addr: SocketAddr = "1.2.3.4:20444".parse().unwrap();
+                        let neighbor = PeerDB::get_peer(
+                            peer_server.network.peerdb.conn(),
+                            peer_server.network.local_peer.network_id,
+                            &PeerAddress::from_socketaddr(&addr),
+                            addr.port(),
+                        )
+                        .unwrap()
+                        .unwrap();
+                        assert_eq!(neighbor.denied, banned_until as i64);
                         true
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -6175,55 +8642,35 @@ mod test {
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_call_read_only_use_latest_tip() {
-        // Test /v2/contracts/call-read (aka CallReadOnlyFunction) endpoint.
-        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying
-        // against the unconfirmed state will succeed.
+    fn test_rpc_admin_mining_unavailable() {
         test_rpc(
             function_name!(),
-            40172,
-            40173,
-            50172,
-            50173,
+            40843,
+            40844,
+            50843,
+            50844,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_callreadonlyfunction(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world-unconfirmed".try_into().unwrap(),
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal(),
-                    None,
-                    "ro-test".try_into().unwrap(),
-                    vec![],
-                    TipRequest::UseLatestAnchoredTip,
+                convo_server.set_admin_token(AdminToken::new("hunter2"));
+                convo_client.new_admin(
+                    Some(AdminToken::new("hunter2")),
+                    AdminRequest::SetMining(false),
                 )
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
+                // no admin handler is installed in the test RPC harness
                 match http_response {
-                    HttpResponseType::CallReadOnlyFunction(response_md, data) => {
-                        assert!(data.okay);
-                        assert_eq!(
-                            Value::try_deserialize_hex_untyped(&data.result.clone().unwrap())
-                                .unwrap(),
-                            Value::okay(Value::Int(1)).unwrap()
-                        );
-                        assert!(data.cause.is_none());
-                        true
-                    }
+                    HttpResponseType::ServiceUnavailable(..) => true,
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -6232,59 +8679,30 @@ mod test {
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_call_read_only_unconfirmed() {
+    fn test_rpc_v3_getinfo() {
         test_rpc(
             function_name!(),
-            40180,
-            40181,
-            50180,
-            50181,
+            40845,
+            40846,
+            50845,
+            50846,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
-             ref mut convo_server| {
-                let unconfirmed_tip = peer_client
-                    .chainstate()
-                    .unconfirmed_state
-                    .as_ref()
-                    .unwrap()
-                    .unconfirmed_chain_tip
-                    .clone();
-                convo_client.new_callreadonlyfunction(
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world-unconfirmed".try_into().unwrap(),
-                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap()
-                        .to_account_principal(),
-                    None,
-                    "ro-test".try_into().unwrap(),
-                    vec![],
-                    TipRequest::SpecificTip(unconfirmed_tip),
-                )
-            },
-            |ref http_request,
+             ref mut convo_server| { convo_client.new_v3(V3Request::GetInfo) },
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::CallReadOnlyFunction(response_md, data) => {
-                        assert!(data.okay);
-                        assert_eq!(
-                            Value::try_deserialize_hex_untyped(&data.result.clone().unwrap())
-                                .unwrap(),
-                            Value::okay(Value::Int(1)).unwrap()
-                        );
-                        assert!(data.cause.is_none());
-                        true
+                    HttpResponseType::V3(_, V3Response::PeerInfo(peer_data)) => {
+                        peer_data.stacks_tip_height > 0
                     }
                     _ => {
-                        error!("Invalid response; {:?}", &http_response);
+                        error!("Invalid response: {:?}", &http_response);
                         false
                     }
                 }
@@ -6293,122 +8711,171 @@ mod test {
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_getattachmentsinv_limit_reached() {
+    fn test_rpc_v3_get_account_unknown_tip() {
         test_rpc(
             function_name!(),
-            40190,
-            40191,
-            50190,
-            50191,
+            40847,
+            40848,
+            50847,
+            50848,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let pages_indexes = HashSet::from_iter(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
-                convo_client.new_getattachmentsinv(StacksBlockId([0x00; 32]), pages_indexes)
+                let principal =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                convo_client.new_v3(V3Request::GetAccount(
+                    principal,
+                    TipRequest::SpecificTip(StacksBlockId([0x11; 32])),
+                    false,
+                ))
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
-                println!("{:?}", http_response);
+             convo_client,
+             convo_server| {
                 match http_response {
-                    HttpResponseType::BadRequest(_, msg) => {
-                        assert_eq!(
-                            msg,
-                            "Number of attachment inv pages is limited by 8 per request"
-                        );
-                        true
+                    HttpResponseType::V3Error(_, body) => body.code == RPCErrorCode::NotFound,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
                     }
-                    _ => false,
                 }
             },
         );
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_mempool_query_txtags() {
+    fn test_rpc_get_account_history_not_indexed() {
         test_rpc(
             function_name!(),
-            40813,
-            40814,
-            50813,
-            50814,
-            false,
+            40849,
+            40850,
+            50849,
+            50850,
+            true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_mempool_query(
-                    MemPoolSyncData::TxTags([0u8; 32], vec![]),
-                    Some(Txid([0u8; 32])),
+                let principal =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                convo_client.new_getaccounthistory(
+                    principal,
+                    TipRequest::UseLatestAnchoredTip,
+                    None,
+                    ACCOUNT_HISTORY_DEFAULT_LIMIT,
                 )
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
-                println!("{:?}", http_response);
+             convo_client,
+             convo_server| {
+                // the test peers do not index account history
                 match http_response {
-                    HttpResponseType::MemPoolTxs(_, _, txs) => {
-                        // got everything
-                        assert_eq!(txs.len(), 10);
-                        true
+                    HttpResponseType::ServiceUnavailable(..) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
                     }
-                    _ => false,
                 }
             },
         );
     }
 
     #[test]
-    #[ignore]
-    fn test_rpc_mempool_query_bloom() {
+    fn test_rpc_get_light_headers() {
+        let server_tip_cell = RefCell::new(None);
         test_rpc(
             function_name!(),
-            40815,
-            40816,
-            50815,
-            50816,
-            false,
+            40851,
+            40852,
+            50851,
+            50852,
+            true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                // empty bloom filter
-                convo_client.new_mempool_query(
-                    MemPoolSyncData::BloomFilter(BloomFilter::new(
-                        BLOOM_COUNTER_ERROR_RATE,
-                        MAX_BLOOM_COUNTER_TXS,
-                        BloomNodeHasher::new(&[0u8; 32]),
-                    )),
-                    Some(Txid([0u8; 32])),
-                )
+                let sortdb = peer_server.sortdb.as_ref().unwrap();
+                let chainstate = &peer_server.stacks_node.as_ref().unwrap().chainstate;
+                let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                let stacks_block_id = StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                );
+                *server_tip_cell.borrow_mut() = Some((stacks_block_id, tip.height));
+                convo_client
+                    .new_get_light_headers(MAX_LIGHT_HEADERS, TipRequest::UseLatestUnconfirmedTip)
             },
-            |ref http_request,
+            |http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             ref convo_client,
-             ref convo_server| {
-                let req_md = http_request.metadata().clone();
-                println!("{:?}", http_response);
+             convo_client,
+             convo_server| {
+                let (tip, tip_height) = server_tip_cell.borrow().clone().unwrap();
                 match http_response {
-                    HttpResponseType::MemPoolTxs(_, _, txs) => {
-                        // got everything
-                        assert_eq!(txs.len(), 10);
+                    HttpResponseType::GetLightHeaders(_, headers) => {
+                        // one header per block, back to (but not including) genesis
+                        assert_eq!(headers.len() as u64, tip_height);
+                        assert_eq!(headers[0].index_block_hash, tip);
+
+                        let sortdb = peer_server.sortdb.as_ref().unwrap();
+                        for (i, light_header) in headers.iter().enumerate() {
+                            assert_eq!(light_header.block_height, tip_height - (i as u64));
+                            assert_eq!(
+                                light_header.index_block_hash,
+                                StacksBlockHeader::make_index_block_hash(
+                                    &light_header.header.consensus_hash,
+                                    &light_header.header.header.block_hash(),
+                                )
+                            );
+                            if i + 1 < headers.len() {
+                                assert_eq!(
+                                    light_header.header.parent_block_id,
+                                    headers[i + 1].index_block_hash
+                                );
+                            }
+
+                            // each header was chosen by the sortition it reports
+                            let sortition = &light_header.sortition;
+                            let snapshot = SortitionDB::get_block_snapshot_consensus(
+                                sortdb.conn(),
+                                &light_header.header.consensus_hash,
+                            )
+                            .unwrap()
+                            .unwrap();
+                            assert_eq!(sortition.consensus_hash, snapshot.consensus_hash);
+                            assert_eq!(
+                                sortition.burn_header_hash,
+                                snapshot.burn_header_hash.to_hex()
+                            );
+                            assert_eq!(sortition.winning_block_txid, snapshot.winning_block_txid);
+                            assert_eq!(
+                                sortition.winning_stacks_block_hash,
+                                light_header.header.header.block_hash()
+                            );
+
+                            // the test burnchain does not produce real Bitcoin blocks
+                            assert!(light_header.burn_block_header.is_none());
+                            assert!(light_header.block_commit_proof.is_none());
+                        }
                         true
                     }
-                    _ => false,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
                 }
             },
         );
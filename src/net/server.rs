@@ -17,6 +17,7 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::fmt;
 use std::io::Error as io_error;
 use std::io::ErrorKind;
 use std::io::{Read, Write};
@@ -38,6 +39,7 @@ use crate::net::http::*;
 use crate::net::p2p::{PeerMap, PeerNetwork};
 use crate::net::poll::*;
 use crate::net::rpc::*;
+use crate::net::tls::{TlsServerConfig, TlsStream};
 use crate::net::Error as net_error;
 use crate::net::*;
 
@@ -49,6 +51,8 @@ use crate::burnchains::BurnchainView;
 
 use mio::net as mio_net;
 
+use rustls::ServerConnection;
+
 use stacks_common::util::get_epoch_time_secs;
 
 use crate::core::mempool::*;
@@ -73,6 +77,16 @@ pub struct HttpPeer {
     // server network handle
     pub http_server_handle: usize,
 
+    // admin server network handle, and the bearer token its clients must present
+    pub admin_server_handle: Option<usize>,
+    admin_token: Option<AdminToken>,
+
+    // TLS server network handle, the certificate it serves, and the TLS sessions of the
+    // connections it accepted
+    pub tls_server_handle: Option<usize>,
+    tls_config: Option<TlsServerConfig>,
+    tls_sessions: HashMap<usize, ServerConnection>,
+
     // connection options
     pub connection_opts: ConnectionOptions,
 }
@@ -86,6 +100,13 @@ impl HttpPeer {
             connecting: HashMap::new(),
             http_server_handle: server_handle,
 
+            admin_server_handle: None,
+            admin_token: None,
+
+            tls_server_handle: None,
+            tls_config: None,
+            tls_sessions: HashMap::new(),
+
             connection_opts: conn_opts,
         }
     }
@@ -94,6 +115,19 @@ impl HttpPeer {
         self.http_server_handle = h;
     }
 
+    /// Serve the admin interface on the given server handle.  Its clients must present the given
+    /// bearer token.
+    pub fn set_admin_server_handle(&mut self, h: usize, token: AdminToken) {
+        self.admin_server_handle = Some(h);
+        self.admin_token = Some(token);
+    }
+
+    /// Serve the RPC interface over TLS on the given server handle, using the given certificate.
+    pub fn set_tls_server_handle(&mut self, h: usize, tls_config: TlsServerConfig) {
+        self.tls_server_handle = Some(h);
+        self.tls_config = Some(tls_config);
+    }
+
     /// Is there a HTTP conversation open to this data_url that is not in progress?
     pub fn find_free_conversation(&self, data_url: &UrlString) -> Option<usize> {
         for (event_id, convo) in self.peers.iter() {
@@ -288,6 +322,14 @@ impl HttpPeer {
     pub fn deregister_http(&mut self, network_state: &mut NetworkState, event_id: usize) -> () {
         self.peers.remove(&event_id);
 
+        if let Some(mut session) = self.tls_sessions.remove(&event_id) {
+            // best-effort
+            session.send_close_notify();
+            if let Some(sock) = self.sockets.get_mut(&event_id) {
+                let _ = session.write_tls(sock);
+            }
+        }
+
         match self.sockets.remove(&event_id) {
             None => {}
             Some(sock) => {
@@ -342,8 +384,8 @@ impl HttpPeer {
 
     /// Saturate a conversation's socket -- either sends the whole request, or fills the socket
     /// buffer.
-    pub fn saturate_http_socket(
-        client_sock: &mut mio::net::TcpStream,
+    pub fn saturate_http_socket<W: Write + fmt::Debug>(
+        client_sock: &mut W,
         convo: &mut ConversationHttp,
         mempool: &MemPoolDB,
         chainstate: &mut StacksChainState,
@@ -364,10 +406,13 @@ impl HttpPeer {
             }
         }
 
+        // a TLS stream may have data left over from an earlier write
+        client_sock.flush().map_err(net_error::WriteError)?;
         Ok(())
     }
 
-    /// Process new inbound HTTP connections we just accepted.
+    /// Process new inbound HTTP connections we just accepted, on the RPC server, the admin
+    /// server, or the TLS server (identified by `server_handle`).
     /// Returns the event IDs of sockets we need to register
     fn process_new_sockets(
         &mut self,
@@ -375,15 +420,15 @@ impl HttpPeer {
         mempool: &MemPoolDB,
         chainstate: &mut StacksChainState,
         poll_state: &mut NetworkPollState,
+        server_handle: usize,
     ) -> Vec<usize> {
         let mut registered = vec![];
+        let admin = self.admin_server_handle == Some(server_handle);
+        let tls = self.tls_server_handle == Some(server_handle);
 
         for (hint_event_id, client_sock) in poll_state.new.drain() {
-            let event_id = match network_state.register(
-                self.http_server_handle,
-                hint_event_id,
-                &client_sock,
-            ) {
+            let event_id = match network_state.register(server_handle, hint_event_id, &client_sock)
+            {
                 Ok(event_id) => event_id,
                 Err(e) => {
                     warn!(
@@ -417,22 +462,68 @@ impl HttpPeer {
                 // NOTE: register_http will deregister the socket for us
                 continue;
             }
+            if admin {
+                if let (Some(convo), Some(token)) =
+                    (self.peers.get_mut(&event_id), self.admin_token.as_ref())
+                {
+                    convo.set_admin_token(token.clone());
+                }
+            } else if let (true, Some(tls_config)) = (tls, self.tls_config.as_ref()) {
+                match tls_config.new_session() {
+                    Ok(session) => {
+                        self.tls_sessions.insert(event_id, session);
+                    }
+                    Err(e) => {
+                        warn!("Failed to start TLS on event {}: {:?}", event_id, &e);
+                        self.deregister_http(network_state, event_id);
+                        continue;
+                    }
+                }
+            }
             registered.push(event_id);
         }
 
         registered
     }
 
+    /// Accept new connections to the admin server or the TLS server (identified by
+    /// `server_handle`), and fold its poll state into the RPC server's, so that its
+    /// conversations are carried out along with all the others in run().
+    pub fn merge_listener_poll_state(
+        &mut self,
+        network_state: &mut NetworkState,
+        mempool: &MemPoolDB,
+        chainstate: &mut StacksChainState,
+        server_handle: usize,
+        mut listener_poll_state: NetworkPollState,
+        poll_state: &mut NetworkPollState,
+    ) {
+        if self.tls_server_handle == Some(server_handle) {
+            // pick up a renewed TLS certificate
+            if let Some(tls_config) = self.tls_config.as_mut() {
+                tls_config.reload_if_changed(get_epoch_time_secs());
+            }
+        }
+        self.process_new_sockets(
+            network_state,
+            mempool,
+            chainstate,
+            &mut listener_poll_state,
+            server_handle,
+        );
+        poll_state.ready.append(&mut listener_poll_state.ready);
+    }
+
     /// Process network traffic on a HTTP conversation.
     /// Returns whether or not the convo is still alive, as well as any message(s) that need to be
     /// forwarded to the peer network.
-    fn process_http_conversation(
+    fn process_http_conversation<S: Read + Write + fmt::Debug>(
         network: &mut PeerNetwork,
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
         event_id: usize,
-        client_sock: &mut mio_net::TcpStream,
+        client_sock: &mut S,
         convo: &mut ConversationHttp,
         handler_args: &RPCHandlerArgs,
     ) -> Result<(bool, Vec<StacksMessageType>), net_error> {
@@ -604,16 +695,29 @@ impl HttpPeer {
                 Some(ref mut convo) => {
                     // activity on a http socket
                     test_debug!("Process HTTP data from {:?}", convo);
-                    match HttpPeer::process_http_conversation(
-                        network,
-                        sortdb,
-                        chainstate,
-                        mempool,
-                        *event_id,
-                        client_sock,
-                        convo,
-                        handler_args,
-                    ) {
+                    let res = match self.tls_sessions.get_mut(event_id) {
+                        Some(session) => HttpPeer::process_http_conversation(
+                            network,
+                            sortdb,
+                            chainstate,
+                            mempool,
+                            *event_id,
+                            &mut TlsStream::new(client_sock, session),
+                            convo,
+                            handler_args,
+                        ),
+                        None => HttpPeer::process_http_conversation(
+                            network,
+                            sortdb,
+                            chainstate,
+                            mempool,
+                            *event_id,
+                            client_sock,
+                            convo,
+                            handler_args,
+                        ),
+                    };
+                    match res {
                         Ok((alive, mut new_msgs)) => {
                             if !alive {
                                 to_remove.push(*event_id);
@@ -656,6 +760,12 @@ impl HttpPeer {
                 }
             }
             if convo.is_drained() && !convo.is_keep_alive() {
+                if let Some(session) = self.tls_sessions.get(event_id) {
+                    if session.wants_write() {
+                        // still have encrypted data to send
+                        continue;
+                    }
+                }
                 // did some work, but nothing more to do and we're not keep-alive
                 debug!("Close drained HTTP connection {:?}", convo);
                 close.push(*event_id);
@@ -682,7 +792,14 @@ impl HttpPeer {
         handler_args: &RPCHandlerArgs,
     ) -> Vec<StacksMessageType> {
         // set up new inbound conversations
-        self.process_new_sockets(network_state, mempool, chainstate, &mut poll_state);
+        let server_handle = self.http_server_handle;
+        self.process_new_sockets(
+            network_state,
+            mempool,
+            chainstate,
+            &mut poll_state,
+            server_handle,
+        );
 
         // set up connected sockets
         self.process_connecting_sockets(network_state, mempool, chainstate, &mut poll_state);
@@ -767,6 +884,10 @@ mod test {
     use clarity::vm::types::*;
     use stacks_common::codec::MAX_MESSAGE_LEN;
 
+    use crate::net::tls::test::{TEST_CERT_1, TEST_CERT_2, TEST_KEY_1, TEST_KEY_2};
+    use std::fs;
+    use std::io;
+
     fn test_http_server<F, C>(
         test_name: &str,
         peer_p2p: u16,
@@ -1287,12 +1408,182 @@ mod test {
                 let http_response_bytes = http_response_bytes_res.unwrap();
                 let http_response_str = String::from_utf8(http_response_bytes).unwrap();
                 eprintln!("HTTP response\n{}", http_response_str);
-                assert!(http_response_str.find("404 Not Found").is_some());
+                assert!(http_response_str.contains("404 Not Found"));
                 true
             },
         );
     }
 
+    #[test]
+    fn test_http_admin_listener() {
+        let mut peer_config = TestPeerConfig::new(function_name!(), 51074, 51075);
+        peer_config.connection_opts = ConnectionOptions::default();
+
+        let mut peer = TestPeer::new(peer_config);
+        peer.network
+            .bind_admin(
+                &"127.0.0.1:51076".parse().unwrap(),
+                AdminToken::new("hunter2"),
+            )
+            .unwrap();
+
+        let (http_sx, http_rx) = sync_channel(1);
+        let (mempool_sx, mempool_rx) = sync_channel(1);
+        let http_thread = thread::spawn(move || {
+            loop {
+                peer.step().unwrap();
+                if http_rx.try_recv().is_ok() {
+                    break;
+                }
+            }
+            let blacklisted = peer
+                .mempool
+                .as_ref()
+                .unwrap()
+                .is_tx_blacklisted(&Txid([0x11; 32]))
+                .unwrap();
+            let _ = mempool_sx.send(blacklisted);
+        });
+
+        let send_request = |port: u16, token: Option<&str>| {
+            let mut request = HttpRequestType::Admin(
+                HttpRequestMetadata::from_host(
+                    PeerHost::from_host_port("127.0.0.1".to_string(), port),
+                    None,
+                ),
+                token.map(AdminToken::new),
+                AdminRequest::DropMempoolTxs(vec![Txid([0x11; 32])]),
+            );
+            request.metadata_mut().keep_alive = false;
+
+            let mut sock =
+                TcpStream::connect(format!("127.0.0.1:{}", port).parse::<SocketAddr>().unwrap())
+                    .unwrap();
+            sock.write_all(&StacksHttp::serialize_request(&request).unwrap())
+                .unwrap();
+            let mut resp = vec![];
+            sock.read_to_end(&mut resp).unwrap();
+            String::from_utf8(resp).unwrap()
+        };
+
+        // not served on the public RPC port, even with the right token
+        let response = send_request(51075, Some("hunter2"));
+        assert!(response.contains("404 Not Found"), "{}", &response);
+
+        // not served on the admin port without the right token
+        let response = send_request(51076, None);
+        assert!(response.contains("401 Unauthorized"), "{}", &response);
+        let response = send_request(51076, Some("hunter3"));
+        assert!(response.contains("401 Unauthorized"), "{}", &response);
+
+        // served on the admin port with the right token
+        let response = send_request(51076, Some("hunter2"));
+        assert!(response.contains("200 OK"), "{}", &response);
+
+        http_sx.send(true).unwrap();
+        assert!(mempool_rx.recv().unwrap());
+        http_thread.join().unwrap();
+    }
+
+    /// Send a request over TLS to 127.0.0.1:port, trusting only the given certificate for
+    /// "localhost".  Returns the response, or the I/O error if the handshake failed.
+    fn send_tls_request(port: u16, trusted_cert_pem: &str, request: &[u8]) -> io::Result<String> {
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut trusted_cert_pem.as_bytes()).unwrap() {
+            roots.add(&rustls::Certificate(cert)).unwrap();
+        }
+        let client_config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let session = rustls::ClientConnection::new(
+            std::sync::Arc::new(client_config),
+            "localhost".try_into().unwrap(),
+        )
+        .unwrap();
+
+        let sock = TcpStream::connect(format!("127.0.0.1:{}", port)).unwrap();
+        let mut stream = rustls::StreamOwned::new(session, sock);
+        stream.write_all(request)?;
+
+        let mut resp = vec![];
+        match stream.read_to_end(&mut resp) {
+            Ok(_) => {}
+            // the server may hang up without a close_notify
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && !resp.is_empty() => {}
+            Err(e) => {
+                return Err(e);
+            }
+        }
+        Ok(String::from_utf8(resp).unwrap())
+    }
+
+    #[test]
+    fn test_http_tls() {
+        let tls_dir = format!("/tmp/stacks-node-tests/tls/{}", function_name!());
+        let _ = fs::remove_dir_all(&tls_dir);
+        fs::create_dir_all(&tls_dir).unwrap();
+        let cert_path = format!("{}/cert.pem", &tls_dir);
+        let key_path = format!("{}/key.pem", &tls_dir);
+        fs::write(&cert_path, TEST_CERT_1).unwrap();
+        fs::write(&key_path, TEST_KEY_1).unwrap();
+
+        let mut peer_config = TestPeerConfig::new(function_name!(), 51077, 51078);
+        peer_config.connection_opts = ConnectionOptions::default();
+
+        let mut peer = TestPeer::new(peer_config);
+        let mut tls_config = TlsServerConfig::from_files(&cert_path, &key_path).unwrap();
+        tls_config.set_check_interval(0);
+        peer.network
+            .bind_rpc_tls_with_config(&"127.0.0.1:51079".parse().unwrap(), tls_config)
+            .unwrap();
+
+        let (http_sx, http_rx) = sync_channel(1);
+        let http_thread = thread::spawn(move || loop {
+            peer.step().unwrap();
+            if http_rx.try_recv().is_ok() {
+                break;
+            }
+        });
+
+        let mut request = HttpRequestType::GetInfo(HttpRequestMetadata::from_host(
+            PeerHost::from_host_port("localhost".to_string(), 51079),
+            None,
+        ));
+        request.metadata_mut().keep_alive = false;
+        let request_bytes = StacksHttp::serialize_request(&request).unwrap();
+
+        let response = send_tls_request(51079, TEST_CERT_1, &request_bytes).unwrap();
+        assert!(response.contains("200 OK"), "{}", &response);
+        assert!(response.contains("\"peer_version\""), "{}", &response);
+
+        // plaintext HTTP gets no response on the TLS port...
+        let mut sock = TcpStream::connect("127.0.0.1:51079").unwrap();
+        sock.write_all(&request_bytes).unwrap();
+        let mut resp = vec![];
+        let _ = sock.read_to_end(&mut resp);
+        assert!(!String::from_utf8_lossy(&resp).contains("HTTP/1.1"));
+
+        // ...but the plaintext RPC port (and thus the data_url) still serves it
+        let mut sock = TcpStream::connect("127.0.0.1:51078").unwrap();
+        sock.write_all(&request_bytes).unwrap();
+        let mut resp = vec![];
+        sock.read_to_end(&mut resp).unwrap();
+        let response = String::from_utf8(resp).unwrap();
+        assert!(response.contains("200 OK"), "{}", &response);
+
+        // renew the certificate.  New connections get the new one.
+        fs::write(&cert_path, TEST_CERT_2).unwrap();
+        fs::write(&key_path, TEST_KEY_2).unwrap();
+
+        let response = send_tls_request(51079, TEST_CERT_2, &request_bytes).unwrap();
+        assert!(response.contains("200 OK"), "{}", &response);
+        assert!(send_tls_request(51079, TEST_CERT_1, &request_bytes).is_err());
+
+        http_sx.send(true).unwrap();
+        http_thread.join().unwrap();
+    }
+
     #[test]
     fn test_http_no_connecting_event_id_leak() {
         use std::net::TcpListener;
@@ -0,0 +1,351 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use mio::net as mio_net;
+
+use rustls::{Certificate, PrivateKey, ServerConfig, ServerConnection};
+
+use crate::net::Error as net_error;
+
+use stacks_common::util::get_epoch_time_secs;
+
+/// How often, in seconds, to check the certificate and key files for changes
+pub const TLS_RELOAD_CHECK_INTERVAL: u64 = 10;
+
+/// TLS settings for the RPC server.  The certificate chain and private key are re-read from
+/// their files periodically, so a renewed certificate is picked up without a restart.  New
+/// connections use the newest certificate; established ones keep the one they started with.
+pub struct TlsServerConfig {
+    cert_path: String,
+    key_path: String,
+    /// contents of the certificate and key files the current config was built from
+    cert_pem: Vec<u8>,
+    key_pem: Vec<u8>,
+    config: Arc<ServerConfig>,
+    /// when we last looked at the files, and how often to look
+    last_check: u64,
+    check_interval: u64,
+}
+
+impl fmt::Debug for TlsServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TlsServerConfig(cert={},key={})",
+            &self.cert_path, &self.key_path
+        )
+    }
+}
+
+impl TlsServerConfig {
+    /// Load a PEM-encoded certificate chain and private key (PKCS#8, PKCS#1 or SEC1).
+    pub fn from_files(cert_path: &str, key_path: &str) -> Result<TlsServerConfig, net_error> {
+        let cert_pem = TlsServerConfig::read_file(cert_path)?;
+        let key_pem = TlsServerConfig::read_file(key_path)?;
+        let config = TlsServerConfig::make_config(&cert_pem, &key_pem)?;
+        Ok(TlsServerConfig {
+            cert_path: cert_path.to_string(),
+            key_path: key_path.to_string(),
+            cert_pem,
+            key_pem,
+            config: Arc::new(config),
+            last_check: get_epoch_time_secs(),
+            check_interval: TLS_RELOAD_CHECK_INTERVAL,
+        })
+    }
+
+    /// Set how often, in seconds, to check the files for changes
+    pub fn set_check_interval(&mut self, check_interval: u64) {
+        self.check_interval = check_interval;
+    }
+
+    fn read_file(path: &str) -> Result<Vec<u8>, net_error> {
+        fs::read(path)
+            .map_err(|e| net_error::TlsError(format!("Failed to read {}: {:?}", path, &e)))
+    }
+
+    fn make_config(cert_pem: &[u8], key_pem: &[u8]) -> Result<ServerConfig, net_error> {
+        let certs: Vec<Certificate> = rustls_pemfile::certs(&mut io::Cursor::new(cert_pem))
+            .map_err(|e| net_error::TlsError(format!("Invalid certificate file: {:?}", &e)))?
+            .into_iter()
+            .map(Certificate)
+            .collect();
+        if certs.is_empty() {
+            return Err(net_error::TlsError(
+                "No certificates in certificate file".to_string(),
+            ));
+        }
+
+        let mut key_reader = io::Cursor::new(key_pem);
+        let key = loop {
+            match rustls_pemfile::read_one(&mut key_reader)
+                .map_err(|e| net_error::TlsError(format!("Invalid key file: {:?}", &e)))?
+            {
+                Some(rustls_pemfile::Item::PKCS8Key(key))
+                | Some(rustls_pemfile::Item::RSAKey(key))
+                | Some(rustls_pemfile::Item::ECKey(key)) => break PrivateKey(key),
+                Some(_) => continue,
+                None => {
+                    return Err(net_error::TlsError(
+                        "No private key in key file".to_string(),
+                    ));
+                }
+            }
+        };
+
+        ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| net_error::TlsError(format!("Invalid certificate or key: {:?}", &e)))
+    }
+
+    /// Re-read the certificate and key files if it's time to, and switch to them if they changed.
+    /// If they can't be loaded, keep using the current ones.
+    /// Returns true if a new certificate was loaded.
+    pub fn reload_if_changed(&mut self, now: u64) -> bool {
+        if self.last_check + self.check_interval > now {
+            return false;
+        }
+        self.last_check = now;
+
+        let (cert_pem, key_pem) = match (
+            TlsServerConfig::read_file(&self.cert_path),
+            TlsServerConfig::read_file(&self.key_path),
+        ) {
+            (Ok(cert_pem), Ok(key_pem)) => (cert_pem, key_pem),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to reload RPC TLS certificate: {:?}", &e);
+                return false;
+            }
+        };
+        if cert_pem == self.cert_pem && key_pem == self.key_pem {
+            return false;
+        }
+
+        // NOTE: the two files may be replaced one after the other.  If we load them in between,
+        // the key won't match the certificate and handshakes will fail until the next check.
+        match TlsServerConfig::make_config(&cert_pem, &key_pem) {
+            Ok(config) => {
+                info!(
+                    "Reloaded RPC TLS certificate from {} and {}",
+                    &self.cert_path, &self.key_path
+                );
+                self.cert_pem = cert_pem;
+                self.key_pem = key_pem;
+                self.config = Arc::new(config);
+                true
+            }
+            Err(e) => {
+                warn!("Failed to reload RPC TLS certificate: {:?}", &e);
+                false
+            }
+        }
+    }
+
+    /// Start a TLS session for a newly-accepted connection
+    pub fn new_session(&self) -> Result<ServerConnection, net_error> {
+        ServerConnection::new(self.config.clone())
+            .map_err(|e| net_error::TlsError(format!("Failed to start TLS session: {:?}", &e)))
+    }
+}
+
+/// A non-blocking TLS stream over a mio socket.  Reads yield plaintext from the client, and
+/// writes are encrypted and sent to it.  Like the socket, it returns `WouldBlock` when it can't
+/// make progress, so a conversation can use it in place of the socket.
+pub struct TlsStream<'a> {
+    sock: &'a mut mio_net::TcpStream,
+    session: &'a mut ServerConnection,
+}
+
+impl<'a> fmt::Debug for TlsStream<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TlsStream({:?})", &self.sock)
+    }
+}
+
+impl<'a> TlsStream<'a> {
+    pub fn new(
+        sock: &'a mut mio_net::TcpStream,
+        session: &'a mut ServerConnection,
+    ) -> TlsStream<'a> {
+        TlsStream { sock, session }
+    }
+
+    /// Send as much buffered TLS data to the socket as it will take
+    fn write_tls(&mut self) -> io::Result<()> {
+        while self.session.wants_write() {
+            match self.session.write_tls(self.sock) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    break;
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Read for TlsStream<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.session.reader().read(buf) {
+                Ok(nr) => {
+                    return Ok(nr);
+                }
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    // client closed the socket without a close_notify, which HTTP tolerates
+                    return Ok(0);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => {
+                    return Err(e);
+                }
+            }
+
+            // no plaintext buffered, so get more from the socket
+            self.session.read_tls(self.sock)?;
+            if let Err(e) = self.session.process_new_packets() {
+                // try to tell the client why
+                let _ = self.write_tls();
+                return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+            }
+
+            // the handshake may need to reply
+            self.write_tls()?;
+        }
+    }
+}
+
+impl<'a> Write for TlsStream<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // don't let unsent data pile up in the session
+        self.write_tls()?;
+        if self.session.wants_write() {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        let nw = self.session.writer().write(buf)?;
+        self.write_tls()?;
+        if nw == 0 && !buf.is_empty() {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        Ok(nw)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_tls()
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Self-signed certificates for "localhost", and their keys
+    pub const TEST_CERT_1: &str = include_str!("./tls/test-cert-1.pem");
+    pub const TEST_KEY_1: &str = include_str!("./tls/test-key-1.pem");
+    pub const TEST_CERT_2: &str = include_str!("./tls/test-cert-2.pem");
+    pub const TEST_KEY_2: &str = include_str!("./tls/test-key-2.pem");
+
+    fn test_dir(name: &str) -> PathBuf {
+        let path = PathBuf::from(format!("/tmp/stacks-node-tests/tls/{}", name));
+        if path.exists() {
+            fs::remove_dir_all(&path).unwrap();
+        }
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_tls_server_config_load() {
+        let dir = test_dir(function_name!());
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+
+        fs::write(&cert_path, TEST_CERT_1).unwrap();
+        fs::write(&key_path, TEST_KEY_1).unwrap();
+        let config =
+            TlsServerConfig::from_files(cert_path.to_str().unwrap(), key_path.to_str().unwrap())
+                .unwrap();
+        assert!(config.new_session().is_ok());
+
+        // no key in the key file
+        fs::write(&key_path, TEST_CERT_2).unwrap();
+        assert!(TlsServerConfig::from_files(
+            cert_path.to_str().unwrap(),
+            key_path.to_str().unwrap()
+        )
+        .is_err());
+
+        // not PEM
+        fs::write(&cert_path, "hello world").unwrap();
+        assert!(TlsServerConfig::from_files(
+            cert_path.to_str().unwrap(),
+            key_path.to_str().unwrap()
+        )
+        .is_err());
+
+        // no such file
+        assert!(TlsServerConfig::from_files(
+            dir.join("nope.pem").to_str().unwrap(),
+            key_path.to_str().unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_tls_server_config_reload() {
+        let dir = test_dir(function_name!());
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+
+        fs::write(&cert_path, TEST_CERT_1).unwrap();
+        fs::write(&key_path, TEST_KEY_1).unwrap();
+        let mut config =
+            TlsServerConfig::from_files(cert_path.to_str().unwrap(), key_path.to_str().unwrap())
+                .unwrap();
+        let now = get_epoch_time_secs();
+
+        // nothing changed
+        assert!(!config.reload_if_changed(now + TLS_RELOAD_CHECK_INTERVAL));
+
+        // both have been replaced, but it's not time to check yet
+        fs::write(&cert_path, TEST_CERT_2).unwrap();
+        fs::write(&key_path, TEST_KEY_2).unwrap();
+        assert!(!config.reload_if_changed(now + TLS_RELOAD_CHECK_INTERVAL + 1));
+
+        assert!(config.reload_if_changed(now + 3 * TLS_RELOAD_CHECK_INTERVAL));
+        assert_eq!(config.cert_pem, TEST_CERT_2.as_bytes());
+        assert_eq!(config.key_pem, TEST_KEY_2.as_bytes());
+        assert!(config.new_session().is_ok());
+
+        // files went missing, so keep the current pair
+        fs::remove_file(&cert_path).unwrap();
+        assert!(!config.reload_if_changed(now + 4 * TLS_RELOAD_CHECK_INTERVAL));
+        assert_eq!(config.cert_pem, TEST_CERT_2.as_bytes());
+    }
+}
@@ -0,0 +1,782 @@
+/*
+ copyright: (c) 2013-2022 by Blockstack PBC, a public benefit corporation.
+
+ This file is part of Blockstack.
+
+ Blockstack is free software. You may redistribute or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License or
+ (at your option) any later version.
+
+ Blockstack is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY, including without the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! The /v3 RPC interface.  Unlike /v2, whose endpoints are each matched by their own regex and
+//! parse their own query strings, every /v3 endpoint is declared once in `V3_ENDPOINTS`.  That
+//! table drives path matching, the validation of path and query parameters, and the OpenAPI
+//! document served at /v3/openapi.json, so the three cannot drift apart.  Every /v3 error is
+//! reported with an `RPCErrorBody`.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+use url::form_urlencoded;
+
+use crate::burnchains::Txid;
+use crate::chainstate::stacks::db::blocks::MemPoolRejection;
+use crate::net::{RPCErrorBody, RPCErrorCode, TipRequest};
+use crate::types::chainstate::StacksBlockId;
+use crate::version_string;
+use clarity::vm::types::PrincipalData;
+
+/// Version of the /v3 interface reported in its OpenAPI document
+pub const V3_API_VERSION: &str = "3.0.0";
+
+impl RPCErrorCode {
+    /// The HTTP status code of a response with this error code
+    pub fn status_code(&self) -> u16 {
+        match self {
+            RPCErrorCode::BadRequest
+            | RPCErrorCode::InvalidParameter
+            | RPCErrorCode::TransactionRejected => 400,
+            RPCErrorCode::NotFound => 404,
+            RPCErrorCode::MethodNotAllowed => 405,
+            RPCErrorCode::ServerError => 500,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RPCErrorCode::BadRequest => "bad_request",
+            RPCErrorCode::InvalidParameter => "invalid_parameter",
+            RPCErrorCode::TransactionRejected => "transaction_rejected",
+            RPCErrorCode::NotFound => "not_found",
+            RPCErrorCode::MethodNotAllowed => "method_not_allowed",
+            RPCErrorCode::ServerError => "server_error",
+        }
+    }
+
+    pub const ALL: &'static [RPCErrorCode] = &[
+        RPCErrorCode::BadRequest,
+        RPCErrorCode::InvalidParameter,
+        RPCErrorCode::TransactionRejected,
+        RPCErrorCode::NotFound,
+        RPCErrorCode::MethodNotAllowed,
+        RPCErrorCode::ServerError,
+    ];
+}
+
+impl RPCErrorBody {
+    pub fn new(code: RPCErrorCode, reason: &str) -> RPCErrorBody {
+        RPCErrorBody {
+            code,
+            reason: reason.to_string(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> RPCErrorBody {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn not_found(reason: &str) -> RPCErrorBody {
+        RPCErrorBody::new(RPCErrorCode::NotFound, reason)
+    }
+
+    pub fn server_error(reason: &str) -> RPCErrorBody {
+        RPCErrorBody::new(RPCErrorCode::ServerError, reason)
+    }
+
+    /// Report why the mempool rejected a POSTed transaction.  The details are the same
+    /// `reason` and `reason_data` that /v2/transactions reports.
+    pub fn transaction_rejected(txid: &Txid, rejection: MemPoolRejection) -> RPCErrorBody {
+        let mut details = rejection.into_json(txid);
+        if let Some(obj) = details.as_object_mut() {
+            obj.remove("error");
+        }
+        RPCErrorBody::new(
+            RPCErrorCode::TransactionRejected,
+            "Transaction rejected by the mempool",
+        )
+        .with_details(details)
+    }
+
+    fn invalid_parameter(name: &str, reason: String) -> RPCErrorBody {
+        RPCErrorBody::new(RPCErrorCode::InvalidParameter, &reason)
+            .with_details(json!({ "parameter": name }))
+    }
+}
+
+/// The operations on the /v3 interface
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum V3Operation {
+    GetOpenAPI,
+    GetInfo,
+    GetAccount,
+    PostTransaction,
+}
+
+/// Where a /v3 parameter is given
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum V3ParamLocation {
+    Path,
+    Query,
+}
+
+/// The kinds of values a /v3 parameter can take
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum V3ParamKind {
+    /// `latest`, or a hex-encoded index block hash
+    Tip,
+    /// `0`, `1`, `false` or `true`
+    Bool,
+    /// A standard or contract principal
+    Principal,
+}
+
+/// A validated /v3 parameter value
+#[derive(Debug, Clone, PartialEq)]
+pub enum V3ParamValue {
+    Tip(TipRequest),
+    Bool(bool),
+    Principal(PrincipalData),
+}
+
+impl V3ParamKind {
+    /// What a value of this kind looks like, for error messages and the OpenAPI document
+    pub fn expected(&self) -> &'static str {
+        match self {
+            V3ParamKind::Tip => "'latest' or a 64-character hex index block hash",
+            V3ParamKind::Bool => "one of 0, 1, false or true",
+            V3ParamKind::Principal => "a standard or contract principal",
+        }
+    }
+
+    pub fn parse(&self, value: &str) -> Option<V3ParamValue> {
+        match self {
+            V3ParamKind::Tip => {
+                if value == "latest" {
+                    Some(V3ParamValue::Tip(TipRequest::UseLatestUnconfirmedTip))
+                } else if value.len() == 64 {
+                    StacksBlockId::from_hex(value)
+                        .ok()
+                        .map(|tip| V3ParamValue::Tip(TipRequest::SpecificTip(tip)))
+                } else {
+                    None
+                }
+            }
+            V3ParamKind::Bool => match value {
+                "1" | "true" => Some(V3ParamValue::Bool(true)),
+                "0" | "false" => Some(V3ParamValue::Bool(false)),
+                _ => None,
+            },
+            V3ParamKind::Principal => PrincipalData::parse(value)
+                .ok()
+                .map(V3ParamValue::Principal),
+        }
+    }
+
+    fn schema(&self) -> Value {
+        match self {
+            V3ParamKind::Tip => json!({
+                "type": "string",
+                "pattern": "^(latest|[0-9a-f]{64})$",
+            }),
+            V3ParamKind::Bool => json!({
+                "type": "string",
+                "enum": ["0", "1", "false", "true"],
+            }),
+            V3ParamKind::Principal => json!({ "type": "string" }),
+        }
+    }
+}
+
+/// A path or query parameter of a /v3 endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct V3Param {
+    pub name: &'static str,
+    pub location: V3ParamLocation,
+    pub kind: V3ParamKind,
+    /// path parameters are always required
+    pub required: bool,
+    pub description: &'static str,
+}
+
+/// The validated path and query parameters of a /v3 request
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct V3Params(HashMap<&'static str, V3ParamValue>);
+
+impl V3Params {
+    pub fn get(&self, name: &str) -> Option<&V3ParamValue> {
+        self.0.get(name)
+    }
+
+    /// Get a chain tip parameter, which defaults to the latest anchored tip
+    pub fn tip(&self, name: &str) -> TipRequest {
+        match self.0.get(name) {
+            Some(V3ParamValue::Tip(tip_req)) => tip_req.clone(),
+            _ => TipRequest::UseLatestAnchoredTip,
+        }
+    }
+
+    pub fn bool(&self, name: &str, default: bool) -> bool {
+        match self.0.get(name) {
+            Some(V3ParamValue::Bool(value)) => *value,
+            _ => default,
+        }
+    }
+
+    pub fn principal(&self, name: &str) -> Option<&PrincipalData> {
+        match self.0.get(name) {
+            Some(V3ParamValue::Principal(principal)) => Some(principal),
+            _ => None,
+        }
+    }
+}
+
+/// A /v3 endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct V3Endpoint {
+    pub operation: V3Operation,
+    pub method: &'static str,
+    /// path template; `{name}` segments are path parameters
+    pub path: &'static str,
+    pub summary: &'static str,
+    pub params: &'static [V3Param],
+    /// content type of the request body, if the endpoint takes one
+    pub request_body: Option<&'static str>,
+    /// name of the response schema in the OpenAPI document's components
+    pub response_schema: &'static str,
+    /// errors this endpoint can return, in addition to the ones every endpoint can return
+    pub errors: &'static [RPCErrorCode],
+}
+
+const TIP_PARAM: V3Param = V3Param {
+    name: "tip",
+    location: V3ParamLocation::Query,
+    kind: V3ParamKind::Tip,
+    required: false,
+    description: "The Stacks chain tip to query from: `latest` for the latest unconfirmed \
+                  state, or an index block hash.  Defaults to the latest anchored tip.",
+};
+
+const PROOF_PARAM: V3Param = V3Param {
+    name: "proof",
+    location: V3ParamLocation::Query,
+    kind: V3ParamKind::Bool,
+    required: false,
+    description: "Whether or not to include MARF proofs.  Defaults to true.",
+};
+
+/// Every endpoint on the /v3 interface
+pub const V3_ENDPOINTS: &[V3Endpoint] = &[
+    V3Endpoint {
+        operation: V3Operation::GetOpenAPI,
+        method: "GET",
+        path: "/v3/openapi.json",
+        summary: "Get the OpenAPI document describing the /v3 interface",
+        params: &[],
+        request_body: None,
+        response_schema: "OpenAPIDocument",
+        errors: &[],
+    },
+    V3Endpoint {
+        operation: V3Operation::GetInfo,
+        method: "GET",
+        path: "/v3/info",
+        summary: "Get core node information",
+        params: &[],
+        request_body: None,
+        response_schema: "CoreNodeInfo",
+        errors: &[],
+    },
+    V3Endpoint {
+        operation: V3Operation::GetAccount,
+        method: "GET",
+        path: "/v3/accounts/{principal}",
+        summary: "Get an account's balance, locked balance and nonce",
+        params: &[
+            V3Param {
+                name: "principal",
+                location: V3ParamLocation::Path,
+                kind: V3ParamKind::Principal,
+                required: true,
+                description: "The account's principal",
+            },
+            TIP_PARAM,
+            PROOF_PARAM,
+        ],
+        request_body: None,
+        response_schema: "AccountData",
+        errors: &[RPCErrorCode::NotFound],
+    },
+    V3Endpoint {
+        operation: V3Operation::PostTransaction,
+        method: "POST",
+        path: "/v3/transactions",
+        summary: "Broadcast a raw transaction",
+        params: &[],
+        request_body: Some("application/octet-stream"),
+        response_schema: "TransactionAccepted",
+        errors: &[RPCErrorCode::BadRequest, RPCErrorCode::TransactionRejected],
+    },
+];
+
+impl V3Endpoint {
+    /// Match a request path against this endpoint's path template.  Returns the path
+    /// parameters' raw values on success.
+    pub fn match_path(&self, path: &str) -> Option<Vec<(&'static str, String)>> {
+        let template: Vec<&'static str> = self.path.split('/').collect();
+        let segments: Vec<&str> = path.split('/').collect();
+        if template.len() != segments.len() {
+            return None;
+        }
+        let mut captures = vec![];
+        for (expected, segment) in template.into_iter().zip(segments.into_iter()) {
+            match expected
+                .strip_prefix('{')
+                .and_then(|name| name.strip_suffix('}'))
+            {
+                Some(name) => {
+                    if segment.is_empty() {
+                        return None;
+                    }
+                    captures.push((name, segment.to_string()));
+                }
+                None => {
+                    if expected != segment {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some(captures)
+    }
+
+    /// Find the endpoint for a request, along with its path parameters' raw values.  A path
+    /// that matches an endpoint with a different method is a `MethodNotAllowed` error.
+    pub fn find(
+        method: &str,
+        path: &str,
+    ) -> Result<(&'static V3Endpoint, Vec<(&'static str, String)>), RPCErrorBody> {
+        let mut allowed = vec![];
+        for endpoint in V3_ENDPOINTS.iter() {
+            if let Some(captures) = endpoint.match_path(path) {
+                if endpoint.method == method {
+                    return Ok((endpoint, captures));
+                }
+                allowed.push(endpoint.method);
+            }
+        }
+        if allowed.is_empty() {
+            Err(RPCErrorBody::not_found(&format!(
+                "No such endpoint: {}",
+                path
+            )))
+        } else {
+            Err(RPCErrorBody::new(
+                RPCErrorCode::MethodNotAllowed,
+                &format!("{} is not supported on {}", method, path),
+            )
+            .with_details(json!({ "allowed": allowed })))
+        }
+    }
+
+    /// Find the endpoint whose response answers a request for this path
+    pub fn find_by_path(path: &str) -> Option<&'static V3Endpoint> {
+        V3_ENDPOINTS
+            .iter()
+            .find(|endpoint| endpoint.match_path(path).is_some())
+    }
+
+    /// Validate a request's path parameters and query string against this endpoint's
+    /// parameters.  Unknown, repeated, missing and malformed parameters are all errors.
+    pub fn parse_params(
+        &self,
+        path_captures: Vec<(&'static str, String)>,
+        query: Option<&str>,
+    ) -> Result<V3Params, RPCErrorBody> {
+        let mut params = V3Params::default();
+        let query_pairs: Vec<(String, String)> = query
+            .map(|query_string| {
+                form_urlencoded::parse(query_string.as_bytes())
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let path_values = path_captures
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value, V3ParamLocation::Path));
+        let query_values = query_pairs
+            .into_iter()
+            .map(|(name, value)| (name, value, V3ParamLocation::Query));
+
+        for (name, value, location) in path_values.chain(query_values) {
+            let param = self
+                .params
+                .iter()
+                .find(|param| param.name == name && param.location == location)
+                .ok_or_else(|| {
+                    RPCErrorBody::invalid_parameter(
+                        &name,
+                        format!("Unknown query parameter '{}'", &name),
+                    )
+                })?;
+            if params.0.contains_key(param.name) {
+                return Err(RPCErrorBody::invalid_parameter(
+                    &name,
+                    format!("Parameter '{}' was given more than once", &name),
+                ));
+            }
+            let parsed = param.kind.parse(&value).ok_or_else(|| {
+                RPCErrorBody::new(
+                    RPCErrorCode::InvalidParameter,
+                    &format!("Invalid value for parameter '{}'", &name),
+                )
+                .with_details(json!({
+                    "parameter": &name,
+                    "value": &value,
+                    "expected": param.kind.expected(),
+                }))
+            })?;
+            params.0.insert(param.name, parsed);
+        }
+
+        for param in self.params.iter() {
+            if param.required && !params.0.contains_key(param.name) {
+                return Err(RPCErrorBody::invalid_parameter(
+                    param.name,
+                    format!("Missing required parameter '{}'", param.name),
+                ));
+            }
+        }
+        Ok(params)
+    }
+
+    fn openapi_operation(&self) -> Value {
+        let parameters: Vec<Value> = self
+            .params
+            .iter()
+            .map(|param| {
+                json!({
+                    "name": param.name,
+                    "in": match param.location {
+                        V3ParamLocation::Path => "path",
+                        V3ParamLocation::Query => "query",
+                    },
+                    "required": param.required,
+                    "description": format!("{} Expected {}.", param.description, param.kind.expected()),
+                    "schema": param.kind.schema(),
+                })
+            })
+            .collect();
+
+        // every endpoint can fail to validate its parameters, or fail on the server
+        let mut codes: Vec<RPCErrorCode> = vec![RPCErrorCode::ServerError];
+        if !self.params.is_empty() {
+            codes.push(RPCErrorCode::InvalidParameter);
+        }
+        codes.extend(self.errors.iter());
+
+        let mut responses = Map::new();
+        responses.insert(
+            "200".to_string(),
+            json!({
+                "description": "Success",
+                "content": {
+                    "application/json": {
+                        "schema": { "$ref": format!("#/components/schemas/{}", self.response_schema) }
+                    }
+                }
+            }),
+        );
+        for code in RPCErrorCode::ALL
+            .iter()
+            .filter(|code| codes.contains(*code))
+        {
+            let status = code.status_code().to_string();
+            if responses.contains_key(&status) {
+                continue;
+            }
+            let names: Vec<&str> = RPCErrorCode::ALL
+                .iter()
+                .filter(|other| codes.contains(*other) && other.status_code() == code.status_code())
+                .map(|other| other.as_str())
+                .collect();
+            responses.insert(
+                status,
+                json!({
+                    "description": format!("Error: {}", names.join(", ")),
+                    "content": {
+                        "application/json": {
+                            "schema": { "$ref": "#/components/schemas/RPCError" }
+                        }
+                    }
+                }),
+            );
+        }
+
+        let mut operation = json!({
+            "summary": self.summary,
+            "operationId": format!("{:?}", &self.operation),
+            "tags": ["v3"],
+            "parameters": parameters,
+            "responses": responses,
+        });
+        if let Some(content_type) = self.request_body {
+            operation.as_object_mut().unwrap().insert(
+                "requestBody".to_string(),
+                json!({
+                    "required": true,
+                    "content": { content_type: { "schema": { "type": "string", "format": "binary" } } }
+                }),
+            );
+        }
+        operation
+    }
+}
+
+/// Load one of the JSON schemas in docs/rpc as an OpenAPI component schema
+fn component_schema(schema_json: &str) -> Value {
+    let mut schema: Value =
+        serde_json::from_str(schema_json).expect("FATAL: invalid built-in JSON schema");
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("$schema");
+    }
+    schema
+}
+
+/// Generate the OpenAPI document that describes the /v3 interface
+pub fn openapi_document() -> Value {
+    let mut paths = Map::new();
+    for endpoint in V3_ENDPOINTS.iter() {
+        let path_item = paths
+            .entry(endpoint.path.to_string())
+            .or_insert_with(|| json!({}));
+        path_item
+            .as_object_mut()
+            .unwrap()
+            .insert(endpoint.method.to_lowercase(), endpoint.openapi_operation());
+    }
+
+    let error_codes: Vec<&str> = RPCErrorCode::ALL.iter().map(|code| code.as_str()).collect();
+    json!({
+        "openapi": "3.0.2",
+        "info": {
+            "title": "Stacks node RPC API",
+            "description": "The /v3 interface of the Stacks node RPC API.  It only covers part of the \
+                            /v2 interface so far: node info, account lookups and transaction \
+                            submission.  All other endpoints are only served under /v2.  Every \
+                            error response has an `RPCError` body.",
+            "version": V3_API_VERSION,
+            "x-server-version": version_string(
+                "stacks-node",
+                option_env!("STACKS_NODE_VERSION")
+                    .or(option_env!("CARGO_PKG_VERSION"))
+                    .unwrap_or("0.0.0.0"),
+            ),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": {
+                "RPCError": {
+                    "type": "object",
+                    "required": ["code", "reason"],
+                    "properties": {
+                        "code": { "type": "string", "enum": error_codes },
+                        "reason": { "type": "string" },
+                        "details": { "type": "object" },
+                    },
+                },
+                "OpenAPIDocument": { "type": "object" },
+                "CoreNodeInfo": component_schema(include_str!(
+                    "../../docs/rpc/api/core-node/get-info.schema.json"
+                )),
+                "AccountData": component_schema(include_str!(
+                    "../../docs/rpc/api/core-node/get-account-data.schema.json"
+                )),
+                "TransactionAccepted": {
+                    "type": "object",
+                    "required": ["txid"],
+                    "properties": {
+                        "txid": { "type": "string", "pattern": "^[0-9a-f]{64}$" },
+                    },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_v3_find_endpoint() {
+        let (endpoint, captures) =
+            V3Endpoint::find("GET", "/v3/accounts/SP000000000000000000002Q6VF78").unwrap();
+        assert_eq!(endpoint.operation, V3Operation::GetAccount);
+        assert_eq!(
+            captures,
+            vec![("principal", "SP000000000000000000002Q6VF78".to_string())]
+        );
+
+        let (endpoint, captures) = V3Endpoint::find("POST", "/v3/transactions").unwrap();
+        assert_eq!(endpoint.operation, V3Operation::PostTransaction);
+        assert!(captures.is_empty());
+
+        let err = V3Endpoint::find("GET", "/v3/transactions").unwrap_err();
+        assert_eq!(err.code, RPCErrorCode::MethodNotAllowed);
+        assert_eq!(err.details, Some(json!({ "allowed": ["POST"] })));
+
+        for path in [
+            "/v3/nope",
+            "/v3/accounts",
+            "/v3/accounts/",
+            "/v3/info/extra",
+        ] {
+            let err = V3Endpoint::find("GET", path).unwrap_err();
+            assert_eq!(err.code, RPCErrorCode::NotFound, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_v3_parse_params() {
+        let (endpoint, captures) =
+            V3Endpoint::find("GET", "/v3/accounts/SP000000000000000000002Q6VF78").unwrap();
+
+        let params = endpoint.parse_params(captures.clone(), None).unwrap();
+        assert_eq!(
+            params.principal("principal").unwrap().to_string(),
+            "SP000000000000000000002Q6VF78"
+        );
+        assert_eq!(params.tip("tip"), TipRequest::UseLatestAnchoredTip);
+        assert!(params.bool("proof", true));
+
+        let tip_hex = "7e4d0fe6bbbe9c5fe3a2c9a6b3e5d5c4b2f1a0e9d8c7b6a5f4e3d2c1b0a9f8e7";
+        let params = endpoint
+            .parse_params(captures.clone(), Some(&format!("tip={}&proof=0", tip_hex)))
+            .unwrap();
+        assert_eq!(
+            params.tip("tip"),
+            TipRequest::SpecificTip(StacksBlockId::from_hex(tip_hex).unwrap())
+        );
+        assert!(!params.bool("proof", true));
+
+        let params = endpoint
+            .parse_params(captures.clone(), Some("tip=latest&proof=true"))
+            .unwrap();
+        assert_eq!(params.tip("tip"), TipRequest::UseLatestUnconfirmedTip);
+        assert!(params.bool("proof", false));
+
+        // every malformed query is rejected the same way, naming the offending parameter
+        let bad_queries = [
+            ("tip=nope", "tip"),
+            ("tip=00", "tip"),
+            ("proof=yes", "proof"),
+            ("proof=0&proof=1", "proof"),
+            ("page=1", "page"),
+            ("principal=SP000000000000000000002Q6VF78", "principal"),
+        ];
+        for (query, name) in bad_queries.iter() {
+            let err = endpoint
+                .parse_params(captures.clone(), Some(query))
+                .unwrap_err();
+            assert_eq!(err.code, RPCErrorCode::InvalidParameter, "{}", query);
+            assert_eq!(
+                err.details.as_ref().unwrap()["parameter"],
+                json!(name),
+                "{}",
+                query
+            );
+        }
+
+        let (endpoint, captures) = V3Endpoint::find("GET", "/v3/accounts/not-a-principal").unwrap();
+        let err = endpoint.parse_params(captures, None).unwrap_err();
+        assert_eq!(err.code, RPCErrorCode::InvalidParameter);
+        assert_eq!(err.details.unwrap()["value"], json!("not-a-principal"));
+    }
+
+    #[test]
+    fn test_v3_openapi_document() {
+        let doc = openapi_document();
+        assert_eq!(doc["openapi"], json!("3.0.2"));
+
+        for endpoint in V3_ENDPOINTS.iter() {
+            let operation = &doc["paths"][endpoint.path][endpoint.method.to_lowercase()];
+            assert_eq!(
+                operation["operationId"],
+                json!(format!("{:?}", &endpoint.operation))
+            );
+            assert_eq!(
+                operation["parameters"].as_array().unwrap().len(),
+                endpoint.params.len()
+            );
+
+            // every response schema is defined
+            let schema_ref = operation["responses"]["200"]["content"]["application/json"]["schema"]
+                ["$ref"]
+                .as_str()
+                .unwrap();
+            let schema_name = schema_ref.strip_prefix("#/components/schemas/").unwrap();
+            assert!(doc["components"]["schemas"][schema_name].is_object());
+
+            // every error an endpoint can return is documented
+            for code in endpoint.errors.iter() {
+                let status = code.status_code().to_string();
+                assert!(operation["responses"][&status]["description"]
+                    .as_str()
+                    .unwrap()
+                    .contains(code.as_str()));
+            }
+            assert!(operation["responses"]["500"].is_object());
+        }
+
+        let account_op = &doc["paths"]["/v3/accounts/{principal}"]["get"];
+        assert!(account_op["responses"]["400"]["description"]
+            .as_str()
+            .unwrap()
+            .contains("invalid_parameter"));
+        assert_eq!(account_op["parameters"][0]["in"], json!("path"));
+        assert!(doc["paths"]["/v3/transactions"]["post"]["requestBody"].is_object());
+    }
+
+    #[test]
+    fn test_v3_error_body_json() {
+        let body = RPCErrorBody::new(RPCErrorCode::InvalidParameter, "Invalid value")
+            .with_details(json!({ "parameter": "tip" }));
+        let body_json = serde_json::to_value(&body).unwrap();
+        assert_eq!(
+            body_json,
+            json!({
+                "code": "invalid_parameter",
+                "reason": "Invalid value",
+                "details": { "parameter": "tip" },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<RPCErrorBody>(body_json).unwrap(),
+            body
+        );
+
+        // details are omitted if there are none
+        let body = RPCErrorBody::not_found("No such endpoint: /v3/nope");
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "code": "not_found", "reason": "No such endpoint: /v3/nope" })
+        );
+
+        // error codes serialize to the same strings they are documented as
+        for code in RPCErrorCode::ALL.iter() {
+            assert_eq!(serde_json::to_value(code).unwrap(), json!(code.as_str()));
+        }
+    }
+}
@@ -18,13 +18,15 @@ use std::cmp;
 use super::super::operations::BurnchainOpSigner;
 use super::super::Config;
 use super::{BurnchainController, BurnchainTip, Error as BurnchainControllerError};
+use crate::commit_strategy::{CommitStrategy, RbfContext};
+use crate::signer::Error as SignerError;
 
 use stacks::burnchains::bitcoin::indexer::{
     BitcoinIndexer, BitcoinIndexerConfig, BitcoinIndexerRuntime,
 };
 use stacks::burnchains::bitcoin::spv::SpvClient;
-use stacks::burnchains::bitcoin::BitcoinNetworkType;
-use stacks::burnchains::db::BurnchainDB;
+use stacks::burnchains::bitcoin::{BitcoinNetworkType, BitcoinPendingTx, BitcoinWatchedOutput};
+use stacks::burnchains::db::{BurnchainDB, BurnchainHeaderReader, PENDING_TX_LIFETIME};
 use stacks::burnchains::indexer::BurnchainIndexer;
 use stacks::burnchains::BurnchainStateTransitionOps;
 use stacks::burnchains::Error as burnchain_error;
@@ -57,7 +59,6 @@ use stacks_common::deps_common::bitcoin::blockdata::transaction::{
 };
 use stacks_common::deps_common::bitcoin::network::encodable::ConsensusEncodable;
 
-#[cfg(test)]
 use stacks_common::deps_common::bitcoin::network::serialize::deserialize as btc_deserialize;
 
 use stacks_common::deps_common::bitcoin::network::serialize::RawEncoder;
@@ -86,6 +87,8 @@ pub struct BitcoinRegtestController {
     ongoing_block_commit: Option<OngoingBlockCommit>,
     should_keep_running: Option<Arc<AtomicBool>>,
     allow_rbf: bool,
+    /// decides the amount of, and when to replace, our block-commits
+    commit_strategy: Box<dyn CommitStrategy>,
 }
 
 #[derive(Clone)]
@@ -131,6 +134,39 @@ pub fn addr2str(btc_addr: &BitcoinAddress) -> String {
     format!("{}", &btc_addr)
 }
 
+/// Get the scriptPubKey of the miner's address in the given epoch
+fn get_miner_script_pubkey(
+    config: &Config,
+    epoch_id: StacksEpochId,
+    public_key: &Secp256k1PublicKey,
+) -> Script {
+    if config.miner.segwit && epoch_id >= StacksEpochId::Epoch21 {
+        let hash160 = Hash160::from_data(&public_key.to_bytes_compressed());
+        SegwitBitcoinAddress::to_p2wpkh_tx_out(&hash160.0, 0).script_pubkey
+    } else {
+        let hash160 = Hash160::from_data(&public_key.to_bytes());
+        LegacyBitcoinAddress::to_p2pkh_tx_out(&hash160, 0).script_pubkey
+    }
+}
+
+/// Get the scriptPubKeys whose UTXOs the indexer should track for the miner.
+/// This is empty unless `miner.internal_wallet` is set.  Fails if the signer cannot be asked
+/// for the miner's public key.
+pub fn make_miner_watched_scripts(config: &Config) -> Result<Vec<Vec<u8>>, SignerError> {
+    if !config.node.miner || !config.miner.internal_wallet {
+        return Ok(vec![]);
+    }
+    let public_key = config.make_signer().public_key()?;
+
+    let mut scripts =
+        vec![get_miner_script_pubkey(config, StacksEpochId::Epoch2_05, &public_key).to_bytes()];
+    if config.miner.segwit {
+        scripts
+            .push(get_miner_script_pubkey(config, StacksEpochId::Epoch21, &public_key).to_bytes());
+    }
+    Ok(scripts)
+}
+
 /// Helper method to create a BitcoinIndexer
 pub fn make_bitcoin_indexer(config: &Config) -> BitcoinIndexer {
     let (network, _) = config.burnchain.get_bitcoin_network();
@@ -150,6 +186,8 @@ pub fn make_bitcoin_indexer(config: &Config) -> BitcoinIndexer {
             first_block: burnchain_params.first_block_height,
             magic_bytes: burnchain_config.magic_bytes,
             epochs: burnchain_config.epochs,
+            // only the burnchain controller's indexer parses blocks
+            watched_scripts: vec![],
         }
     };
 
@@ -299,6 +337,8 @@ impl BitcoinRegtestController {
                 first_block: burnchain_params.first_block_height,
                 magic_bytes: burnchain_config.magic_bytes,
                 epochs: burnchain_config.epochs,
+                // loaded from the signer before the first sync
+                watched_scripts: vec![],
             }
         };
 
@@ -309,6 +349,7 @@ impl BitcoinRegtestController {
             runtime: indexer_runtime,
         };
 
+        let commit_strategy = config.make_commit_strategy();
         Self {
             use_coordinator: coordinator_channel,
             config,
@@ -320,6 +361,7 @@ impl BitcoinRegtestController {
             ongoing_block_commit: None,
             should_keep_running,
             allow_rbf: true,
+            commit_strategy,
         }
     }
 
@@ -344,6 +386,8 @@ impl BitcoinRegtestController {
                 first_block: burnchain_params.first_block_height,
                 magic_bytes: burnchain_config.magic_bytes,
                 epochs: burnchain_config.epochs,
+                // the dummy controller never downloads blocks
+                watched_scripts: vec![],
             }
         };
 
@@ -354,6 +398,7 @@ impl BitcoinRegtestController {
             runtime: indexer_runtime,
         };
 
+        let commit_strategy = config.make_commit_strategy();
         Self {
             use_coordinator: None,
             config,
@@ -365,6 +410,7 @@ impl BitcoinRegtestController {
             ongoing_block_commit: None,
             should_keep_running: None,
             allow_rbf: true,
+            commit_strategy,
         }
     }
 
@@ -385,6 +431,11 @@ impl BitcoinRegtestController {
         self.ongoing_block_commit = ongoing;
     }
 
+    /// Get the strategy that decides how much to spend on each block-commit
+    pub fn get_commit_strategy(&self) -> &dyn CommitStrategy {
+        self.commit_strategy.as_ref()
+    }
+
     /// Get the default Burnchain instance from our config
     fn default_burnchain(&self) -> Burnchain {
         let burnchain = match &self.burnchain_config {
@@ -408,10 +459,28 @@ impl BitcoinRegtestController {
         }
     }
 
+    /// Have the indexer track the miner's UTXOs, if it should and does not do so yet.  The
+    /// scriptPubKeys to watch come from the signer, which may be unreachable; in that case,
+    /// blocks must not be downloaded until it is back, or the outputs they create are missed.
+    fn load_miner_watched_scripts(&mut self) -> Result<(), SignerError> {
+        if self.indexer.config.watched_scripts.is_empty() {
+            self.indexer.config.watched_scripts = make_miner_watched_scripts(&self.config)?;
+        }
+        Ok(())
+    }
+
     /// Helium (devnet) blocks receiver.  Returns the new burnchain tip.
     fn receive_blocks_helium(&mut self) -> BurnchainTip {
         let mut burnchain = self.get_burnchain();
         let (block_snapshot, state_transition) = loop {
+            if let Err(e) = self.load_miner_watched_scripts() {
+                error!(
+                    "Unable to sync with burnchain: failed to load miner UTXO scripts: {}",
+                    e
+                );
+                sleep_ms(5000);
+                continue;
+            }
             match burnchain.sync_with_indexer_deprecated(&mut self.indexer) {
                 Ok(x) => {
                     break x;
@@ -488,6 +557,15 @@ impl BitcoinRegtestController {
                 return Err(BurnchainControllerError::CoordinatorClosed);
             }
 
+            if let Err(e) = self.load_miner_watched_scripts() {
+                error!(
+                    "Unable to sync with burnchain: failed to load miner UTXO scripts: {}",
+                    e
+                );
+                sleep_ms(5000);
+                continue;
+            }
+
             match burnchain.sync_with_indexer(
                 &mut self.indexer,
                 coordinator_comms.clone(),
@@ -709,6 +787,16 @@ impl BitcoinRegtestController {
         );
         let filter_addresses = vec![addr2str(&address)];
 
+        if self.config.miner.internal_wallet {
+            return self.get_internal_utxos(
+                epoch_id,
+                &pubk,
+                total_required,
+                utxos_to_exclude,
+                block_height,
+            );
+        }
+
         let mut utxos = loop {
             let result = BitcoinRPCRequest::list_unspent(
                 &self.config,
@@ -793,6 +881,173 @@ impl BitcoinRegtestController {
         Some(utxos)
     }
 
+    /// Get the miner's UTXOs from the outputs the indexer tracked in the burnchain DB, instead
+    /// of from bitcoind's wallet.  These are the unspent outputs on the canonical burnchain
+    /// fork, adjusted for the transactions this node broadcast that have not been mined yet.
+    fn get_internal_utxos(
+        &self,
+        epoch_id: StacksEpochId,
+        public_key: &Secp256k1PublicKey,
+        total_required: u64,
+        utxos_to_exclude: Option<UTXOSet>,
+        block_height: u64,
+    ) -> Option<UTXOSet> {
+        let opened_burnchain_db;
+        let burnchain_db = match self.burnchain_db.as_ref() {
+            Some(burnchain_db) => burnchain_db,
+            None => {
+                let burnchaindb_path = self.get_burnchain().get_burnchaindb_path();
+                opened_burnchain_db = match BurnchainDB::open(&burnchaindb_path, false) {
+                    Ok(burnchain_db) => burnchain_db,
+                    Err(e) => {
+                        warn!("Failed to open burnchain DB to look up UTXOs: {:?}", &e);
+                        return None;
+                    }
+                };
+                &opened_burnchain_db
+            }
+        };
+
+        let script_pubkey = get_miner_script_pubkey(&self.config, epoch_id, public_key);
+        let tip = burnchain_db.get_canonical_chain_tip().ok()?;
+        let outputs = match burnchain_db.get_spendable_watched_outputs(
+            &self.indexer,
+            &[script_pubkey.to_bytes()],
+            1,
+            tip.block_height.saturating_sub(PENDING_TX_LIFETIME),
+        ) {
+            Ok(outputs) => outputs,
+            Err(e) => {
+                warn!("Failed to query tracked UTXOs: {:?}", &e);
+                return None;
+            }
+        };
+
+        let txids_to_filter = match utxos_to_exclude {
+            Some(utxos_to_exclude) => utxos_to_exclude
+                .utxos
+                .iter()
+                .map(|utxo| utxo.txid)
+                .collect::<Vec<_>>(),
+            None => vec![],
+        };
+
+        let mut utxos = vec![];
+        for (output, output_height) in outputs.into_iter() {
+            // the indexer stores txids in the opposite byte order from rust-bitcoin
+            let mut txid_bytes = output.txid.0;
+            txid_bytes.reverse();
+            let txid = Sha256dHash::from(&txid_bytes[..]);
+            if txids_to_filter.contains(&txid) {
+                continue;
+            }
+            utxos.push(UTXO {
+                txid,
+                vout: output.vout,
+                script_pub_key: Script::from(output.script_pubkey),
+                amount: output.amount,
+                confirmations: match output_height {
+                    Some(output_height) => {
+                        (tip.block_height.saturating_sub(output_height) + 1) as u32
+                    }
+                    None => 0,
+                },
+            });
+        }
+
+        // spend the largest outputs first, so block-commits need as few inputs as possible
+        utxos.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+        let bhh = match self.indexer.read_burnchain_header(block_height) {
+            Ok(Some(header)) => header.block_hash,
+            _ => tip.block_hash,
+        };
+        let utxos = UTXOSet { bhh, utxos };
+
+        let total_unspent = utxos.total_available();
+        if total_unspent < total_required {
+            warn!(
+                "Total tracked unspent {} < {} for {:?}",
+                total_unspent,
+                total_required,
+                &public_key.to_hex()
+            );
+            return None;
+        }
+
+        debug!(
+            "Got {} tracked UTXOs for {}",
+            utxos.utxos.len(),
+            &public_key.to_hex()
+        );
+        Some(utxos)
+    }
+
+    /// Remember a transaction that was just broadcast, so that until it is mined, the UTXOs
+    /// it spends are not offered again and its change can be spent by the next transaction.
+    fn store_pending_tx(
+        &self,
+        epoch_id: StacksEpochId,
+        public_key: &Secp256k1PublicKey,
+        transaction: &SerializedTx,
+    ) {
+        let tx: Transaction = match btc_deserialize(&transaction.bytes) {
+            Ok(tx) => tx,
+            Err(e) => {
+                warn!("Failed to decode broadcast transaction: {:?}", &e);
+                return;
+            }
+        };
+
+        // the indexer stores txids in the opposite byte order from rust-bitcoin
+        let to_txid = |hash: &Sha256dHash| {
+            let mut txid_bytes = hash.0;
+            txid_bytes.reverse();
+            Txid(txid_bytes)
+        };
+
+        let script_pubkey = get_miner_script_pubkey(&self.config, epoch_id, public_key);
+        let outputs = tx
+            .output
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.script_pubkey == script_pubkey)
+            .map(|(vout, output)| BitcoinWatchedOutput {
+                txid: transaction.txid(),
+                vout: vout as u32,
+                script_pubkey: output.script_pubkey.to_bytes(),
+                amount: output.value,
+            })
+            .collect();
+
+        let burnchaindb_path = self.get_burnchain().get_burnchaindb_path();
+        let result = BurnchainDB::open(&burnchaindb_path, true).and_then(|mut burnchain_db| {
+            let tip = burnchain_db.get_canonical_chain_tip()?;
+            burnchain_db.store_pending_tx(&BitcoinPendingTx {
+                txid: transaction.txid(),
+                broadcast_height: tip.block_height,
+                spent_outpoints: tx
+                    .input
+                    .iter()
+                    .map(|input| {
+                        (
+                            to_txid(&input.previous_output.txid),
+                            input.previous_output.vout,
+                        )
+                    })
+                    .collect(),
+                outputs,
+            })
+        });
+        if let Err(e) = result {
+            warn!(
+                "Failed to record pending transaction {}: {:?}",
+                &transaction.txid(),
+                &e
+            );
+        }
+    }
+
     fn build_leader_key_register_tx(
         &mut self,
         epoch_id: StacksEpochId,
@@ -1320,29 +1575,25 @@ impl BitcoinRegtestController {
             return res;
         }
 
-        // Stop as soon as the fee_rate is ${self.config.burnchain.max_rbf} percent higher, stop RBF
-        if ongoing_op.fees.fee_rate
-            > (self.config.burnchain.satoshis_per_byte * self.config.burnchain.max_rbf / 100)
-        {
-            warn!(
-                "RBF'd block commits reached {}% satoshi per byte fee rate, not resubmitting",
-                self.config.burnchain.max_rbf
-            );
-            self.ongoing_block_commit = Some(ongoing_op);
-            return None;
-        }
-
         // An ongoing operation is in the mempool and we received a new block. The desired behaviour is the following:
-        // 1) If the ongoing and the incoming operation are **strictly** identical, we will be idempotent and discard the incoming.
-        // 2) If the 2 operations are different, we will try to avoid wasting UTXOs, and attempt to RBF the outgoing transaction:
+        // 1) If the commit strategy says not to replace the ongoing operation (e.g. because it is
+        //    **strictly** identical to the incoming one, or because its fee rate is already
+        //    ${self.config.burnchain.max_rbf} percent higher), we will be idempotent and discard the incoming.
+        // 2) Otherwise, we will try to avoid wasting UTXOs, and attempt to RBF the outgoing transaction:
         //  i) If UTXOs are insufficient,
         //    a) If no other UTXOs, we'll have to wait on the ongoing operation to be mined before resuming operation.
         //    b) If we have some other UTXOs, drop the ongoing operation, and track the new one.
         //  ii) If UTXOs initially used are sufficient for paying for a fee bump, then RBF
 
         // Let's start by early returning 1)
-        if payload == ongoing_op.payload {
-            info!("Abort attempt to re-submit identical LeaderBlockCommit");
+        let should_rbf = self.commit_strategy.should_rbf(&RbfContext {
+            ongoing: &ongoing_op.payload,
+            replacement: &payload,
+            ongoing_fee_rate: ongoing_op.fees.fee_rate,
+            max_fee_rate: self.config.burnchain.satoshis_per_byte * self.config.burnchain.max_rbf
+                / 100,
+        });
+        if !should_rbf {
             self.ongoing_block_commit = Some(ongoing_op);
             return None;
         }
@@ -1936,7 +2187,11 @@ impl BurnchainController for BitcoinRegtestController {
         attempt: u64,
     ) -> Option<Txid> {
         let transaction = self.make_operation_tx(epoch_id, operation, op_signer, attempt)?;
-        self.send_transaction(transaction)
+        let txid = self.send_transaction(transaction.clone())?;
+        if self.config.miner.internal_wallet {
+            self.store_pending_tx(epoch_id, &op_signer.get_public_key(), &transaction);
+        }
+        Some(txid)
     }
 
     #[cfg(test)]
@@ -1,5 +1,6 @@
 pub mod bitcoin_regtest_controller;
 pub mod mocknet_controller;
+pub mod zmq;
 
 pub use self::bitcoin_regtest_controller::BitcoinRegtestController;
 pub use self::mocknet_controller::MocknetController;
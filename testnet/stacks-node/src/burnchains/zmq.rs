@@ -0,0 +1,572 @@
+//! Bitcoin ZMQ block notifications.
+//!
+//! bitcoind can publish a message on a ZMQ PUB socket whenever it connects a new block
+//! (`-zmqpubhashblock`).  Subscribing to it lets the run loop sync the burnchain as soon as a
+//! block arrives, instead of waiting out `burnchain.poll_time_secs`.  The notification only
+//! wakes up the sync; the block itself is still downloaded by the indexer, so the much larger
+//! `rawblock` topic is not subscribed to.
+//!
+//! Only the subset of ZMTP 3.0 that a SUB socket needs to talk to bitcoind is implemented here:
+//! the NULL security mechanism, subscriptions, and (possibly multi-part) messages.  The `zmq`
+//! crate would instead link the node against libzmq, a C++ library that every build and
+//! release target would then need, for what is a single read-only connection to a trusted,
+//! local publisher.  The wire format is pinned by `test_libzmq_publisher_interop` (a byte-exact
+//! libzmq 4.3 PUB session) and by `bitcoind_zmq_integration_test` (a real bitcoind).
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use stacks::util::hash::to_hex;
+use stacks::util::sleep_ms;
+
+/// Topic on which bitcoind publishes the hash of each new block
+pub const ZMQ_TOPIC_HASHBLOCK: &str = "hashblock";
+
+/// How long to wait before reconnecting to a ZMQ publisher that went away
+const ZMQ_RECONNECT_MS: u64 = 5_000;
+/// How long a read may block before the listener re-checks whether it should keep running
+const ZMQ_POLL_TIMEOUT_MS: u64 = 1_000;
+/// How long the publisher may take to send the remainder of a frame
+const ZMQ_FRAME_TIMEOUT_MS: u64 = 30_000;
+/// Largest frame we will accept (comfortably bigger than any Bitcoin block)
+const ZMQ_MAX_FRAME_LEN: u64 = 64 * 1024 * 1024;
+
+const ZMTP_FLAG_MORE: u8 = 0x01;
+const ZMTP_FLAG_LONG: u8 = 0x02;
+const ZMTP_FLAG_COMMAND: u8 = 0x04;
+const ZMTP_GREETING_LEN: usize = 64;
+
+/// Build the 64-byte ZMTP 3.0 greeting for the NULL mechanism
+fn zmtp_greeting() -> [u8; ZMTP_GREETING_LEN] {
+    let mut greeting = [0u8; ZMTP_GREETING_LEN];
+    greeting[0] = 0xff;
+    greeting[9] = 0x7f;
+    // version 3.0, so that subscriptions are sent as messages
+    greeting[10] = 3;
+    greeting[11] = 0;
+    greeting[12..16].copy_from_slice(b"NULL");
+    greeting
+}
+
+/// Encode a single ZMTP frame
+fn zmtp_frame(flags: u8, body: &[u8]) -> Vec<u8> {
+    let mut frame = vec![];
+    if body.len() > 255 {
+        frame.push(flags | ZMTP_FLAG_LONG);
+        frame.extend_from_slice(&(body.len() as u64).to_be_bytes());
+    } else {
+        frame.push(flags);
+        frame.push(body.len() as u8);
+    }
+    frame.extend_from_slice(body);
+    frame
+}
+
+/// Encode a ZMTP READY command announcing the given socket type
+fn zmtp_ready(socket_type: &str) -> Vec<u8> {
+    let mut body = vec![];
+    body.push(5u8);
+    body.extend_from_slice(b"READY");
+    body.push(11u8);
+    body.extend_from_slice(b"Socket-Type");
+    body.extend_from_slice(&(socket_type.len() as u32).to_be_bytes());
+    body.extend_from_slice(socket_type.as_bytes());
+    zmtp_frame(ZMTP_FLAG_COMMAND, &body)
+}
+
+/// Parse a `tcp://host:port` ZMQ endpoint into a socket address string
+fn parse_endpoint(endpoint: &str) -> io::Result<String> {
+    match endpoint.strip_prefix("tcp://") {
+        Some(addr) if addr.len() > 0 => Ok(addr.to_string()),
+        _ => Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Unsupported ZMQ endpoint '{}': expected tcp://host:port",
+                endpoint
+            ),
+        )),
+    }
+}
+
+/// A ZMQ SUB socket connected to a single publisher
+pub struct ZmqSubscriber {
+    stream: TcpStream,
+}
+
+impl ZmqSubscriber {
+    /// Connect to the publisher at `endpoint` (e.g. `tcp://127.0.0.1:28332`), complete the ZMTP
+    /// handshake, and subscribe to each of `topics`.
+    pub fn connect(endpoint: &str, topics: &[&str]) -> io::Result<ZmqSubscriber> {
+        let addr = parse_endpoint(endpoint)?
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::NotFound,
+                    format!("Could not resolve ZMQ endpoint '{}'", endpoint),
+                )
+            })?;
+        let stream = TcpStream::connect_timeout(&addr, Duration::from_millis(ZMQ_RECONNECT_MS))?;
+        stream.set_read_timeout(Some(Duration::from_millis(ZMQ_FRAME_TIMEOUT_MS)))?;
+        stream.set_nodelay(true)?;
+
+        let mut subscriber = ZmqSubscriber { stream };
+        subscriber.handshake()?;
+        for topic in topics.iter() {
+            // in ZMTP 3.0, a subscription is a message starting with 0x01
+            let mut body = vec![0x01];
+            body.extend_from_slice(topic.as_bytes());
+            subscriber.stream.write_all(&zmtp_frame(0, &body))?;
+        }
+        subscriber.stream.flush()?;
+        Ok(subscriber)
+    }
+
+    fn handshake(&mut self) -> io::Result<()> {
+        self.stream.write_all(&zmtp_greeting())?;
+        self.stream.flush()?;
+
+        let mut greeting = [0u8; ZMTP_GREETING_LEN];
+        self.stream.read_exact(&mut greeting)?;
+        if greeting[0] != 0xff || greeting[9] != 0x7f || greeting[10] < 3 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "ZMQ publisher does not speak ZMTP 3",
+            ));
+        }
+        if &greeting[12..16] != b"NULL" {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "ZMQ publisher requires an unsupported security mechanism",
+            ));
+        }
+
+        self.stream.write_all(&zmtp_ready("SUB"))?;
+        self.stream.flush()?;
+
+        let (flags, body) = self.read_frame()?;
+        if flags & ZMTP_FLAG_COMMAND == 0 || !body.starts_with(b"\x05READY") {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "ZMQ publisher did not send READY",
+            ));
+        }
+        Ok(())
+    }
+
+    fn read_frame(&mut self) -> io::Result<(u8, Vec<u8>)> {
+        let mut flags = [0u8; 1];
+        self.stream.read_exact(&mut flags)?;
+        self.read_frame_after_flags(flags[0])
+    }
+
+    fn read_frame_after_flags(&mut self, flags: u8) -> io::Result<(u8, Vec<u8>)> {
+        let len = if flags & ZMTP_FLAG_LONG != 0 {
+            let mut len_bytes = [0u8; 8];
+            self.stream.read_exact(&mut len_bytes)?;
+            u64::from_be_bytes(len_bytes)
+        } else {
+            let mut len_bytes = [0u8; 1];
+            self.stream.read_exact(&mut len_bytes)?;
+            len_bytes[0] as u64
+        };
+        if len > ZMQ_MAX_FRAME_LEN {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("ZMQ frame of {} bytes is too big", len),
+            ));
+        }
+        let mut body = vec![];
+        (&mut self.stream).take(len).read_to_end(&mut body)?;
+        if body.len() as u64 != len {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "ZMQ publisher closed the connection mid-frame",
+            ));
+        }
+        Ok((flags, body))
+    }
+
+    /// Wait up to `timeout` for the next message, and return its parts.
+    /// Returns Ok(None) if no message began arriving in that time.
+    pub fn recv_message(&mut self, timeout: Duration) -> io::Result<Option<Vec<Vec<u8>>>> {
+        loop {
+            // only the first byte of a message is read with the short timeout, so a timeout
+            // never leaves us in the middle of a frame
+            self.stream.set_read_timeout(Some(timeout))?;
+            let mut flags = [0u8; 1];
+            let res = self.stream.read(&mut flags);
+            self.stream
+                .set_read_timeout(Some(Duration::from_millis(ZMQ_FRAME_TIMEOUT_MS)))?;
+            match res {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "ZMQ publisher closed the connection",
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                    return Ok(None);
+                }
+                Err(e) => return Err(e),
+            }
+
+            let (mut flags, body) = self.read_frame_after_flags(flags[0])?;
+            if flags & ZMTP_FLAG_COMMAND != 0 {
+                // e.g. a heartbeat; nothing to do
+                continue;
+            }
+            let mut parts = vec![body];
+            while flags & ZMTP_FLAG_MORE != 0 {
+                let (next_flags, body) = self.read_frame()?;
+                flags = next_flags;
+                parts.push(body);
+            }
+            return Ok(Some(parts));
+        }
+    }
+}
+
+impl Drop for ZmqSubscriber {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// Counts new-burn-block announcements, and lets a thread wait for the next one.
+#[derive(Clone)]
+pub struct BurnBlockNotifier {
+    inner: Arc<(Mutex<u64>, Condvar)>,
+}
+
+impl BurnBlockNotifier {
+    pub fn new() -> BurnBlockNotifier {
+        BurnBlockNotifier {
+            inner: Arc::new((Mutex::new(0), Condvar::new())),
+        }
+    }
+
+    /// Record that a new burn block has arrived, and wake up any waiters
+    pub fn notify(&self) {
+        let (count, cvar) = &*self.inner;
+        let mut count = count.lock().expect("FATAL: burn block notifier poisoned");
+        *count += 1;
+        cvar.notify_all();
+    }
+
+    /// How many new burn blocks have been announced so far?
+    pub fn get_count(&self) -> u64 {
+        let (count, _) = &*self.inner;
+        *count.lock().expect("FATAL: burn block notifier poisoned")
+    }
+
+    /// Wait up to `timeout` for the count to exceed `last_count`.
+    /// Returns the count at the time this returns.
+    pub fn wait_for_new_block(&self, last_count: u64, timeout: Duration) -> u64 {
+        let (count, cvar) = &*self.inner;
+        let count = count.lock().expect("FATAL: burn block notifier poisoned");
+        let (count, _) = cvar
+            .wait_timeout_while(count, timeout, |count| *count <= last_count)
+            .expect("FATAL: burn block notifier poisoned");
+        *count
+    }
+}
+
+/// Subscribe to `endpoint` and notify `notifier` whenever a new block is published, until
+/// `should_keep_running` is cleared.  Reconnects if the publisher goes away.
+fn run_zmq_listener(
+    endpoint: String,
+    notifier: BurnBlockNotifier,
+    should_keep_running: Arc<AtomicBool>,
+) {
+    let topics = [ZMQ_TOPIC_HASHBLOCK];
+    while should_keep_running.load(Ordering::SeqCst) {
+        let mut subscriber = match ZmqSubscriber::connect(&endpoint, &topics) {
+            Ok(subscriber) => subscriber,
+            Err(e) => {
+                warn!(
+                    "Failed to subscribe to ZMQ endpoint {}: {:?}",
+                    &endpoint, &e
+                );
+                sleep_ms(ZMQ_RECONNECT_MS);
+                continue;
+            }
+        };
+        info!("Subscribed to Bitcoin block notifications at {}", &endpoint);
+
+        while should_keep_running.load(Ordering::SeqCst) {
+            let parts = match subscriber.recv_message(Duration::from_millis(ZMQ_POLL_TIMEOUT_MS)) {
+                Ok(Some(parts)) => parts,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Lost ZMQ connection to {}: {:?}", &endpoint, &e);
+                    sleep_ms(ZMQ_RECONNECT_MS);
+                    break;
+                }
+            };
+
+            let topic = parts.get(0).map(|t| t.as_slice()).unwrap_or(&[]);
+            if topic != ZMQ_TOPIC_HASHBLOCK.as_bytes() {
+                continue;
+            }
+            debug!(
+                "ZMQ: new Bitcoin block {}",
+                &to_hex(parts.get(1).map(|b| b.as_slice()).unwrap_or(&[]))
+            );
+            notifier.notify();
+        }
+    }
+    debug!("ZMQ listener for {} exit", &endpoint);
+}
+
+/// Spawn a thread that subscribes to bitcoind's ZMQ block notifications at `endpoint`.
+pub fn spawn_zmq_listener(
+    endpoint: String,
+    notifier: BurnBlockNotifier,
+    should_keep_running: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    thread::Builder::new()
+        .name(format!("zmq-{}", &endpoint))
+        .spawn(move || run_zmq_listener(endpoint, notifier, should_keep_running))
+        .expect("FATAL: failed to spawn ZMQ listener thread")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc::{channel, Receiver};
+
+    /// A stand-in for bitcoind's ZMQ PUB socket.  It accepts one subscriber, reports the
+    /// `num_topics` topics it subscribes to, and then publishes whatever multi-part messages it is
+    /// given.
+    struct StubPublisher {
+        endpoint: String,
+        subscriptions: Receiver<Vec<u8>>,
+        handle: JoinHandle<()>,
+    }
+
+    impl StubPublisher {
+        fn spawn(num_topics: usize, messages: Vec<Vec<Vec<u8>>>) -> StubPublisher {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+            let (sub_tx, sub_rx) = channel();
+            let handle = thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut greeting = [0u8; ZMTP_GREETING_LEN];
+                stream.read_exact(&mut greeting).unwrap();
+                assert_eq!(&greeting[12..16], b"NULL");
+                stream.write_all(&zmtp_greeting()).unwrap();
+
+                let mut subscriber = ZmqSubscriber { stream };
+                let (flags, body) = subscriber.read_frame().unwrap();
+                assert!(flags & ZMTP_FLAG_COMMAND != 0);
+                assert!(body.ends_with(b"Socket-Type\x00\x00\x00\x03SUB"));
+                subscriber.stream.write_all(&zmtp_ready("PUB")).unwrap();
+
+                for _ in 0..num_topics {
+                    let (_, body) = subscriber.read_frame().unwrap();
+                    assert_eq!(body[0], 0x01);
+                    sub_tx.send(body[1..].to_vec()).unwrap();
+                }
+
+                for message in messages.iter() {
+                    for (i, part) in message.iter().enumerate() {
+                        let flags = if i + 1 < message.len() {
+                            ZMTP_FLAG_MORE
+                        } else {
+                            0
+                        };
+                        subscriber
+                            .stream
+                            .write_all(&zmtp_frame(flags, part))
+                            .unwrap();
+                    }
+                }
+                subscriber.stream.flush().unwrap();
+                // hold the connection open until the subscriber hangs up
+                let mut buf = [0u8; 1];
+                let _ = subscriber.stream.read(&mut buf);
+            });
+            StubPublisher {
+                endpoint,
+                subscriptions: sub_rx,
+                handle,
+            }
+        }
+    }
+
+    fn hashblock_message(hash_byte: u8, seq: u32) -> Vec<Vec<u8>> {
+        vec![
+            ZMQ_TOPIC_HASHBLOCK.as_bytes().to_vec(),
+            vec![hash_byte; 32],
+            seq.to_le_bytes().to_vec(),
+        ]
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(
+            parse_endpoint("tcp://127.0.0.1:28332").unwrap(),
+            "127.0.0.1:28332"
+        );
+        assert!(parse_endpoint("ipc:///tmp/bitcoind.sock").is_err());
+        assert!(parse_endpoint("tcp://").is_err());
+    }
+
+    #[test]
+    fn test_subscriber_receives_messages() {
+        // one long frame, to exercise 8-byte frame lengths
+        let rawblock = vec![
+            b"rawblock".to_vec(),
+            vec![0xab; 1000],
+            1u32.to_le_bytes().to_vec(),
+        ];
+        let publisher = StubPublisher::spawn(2, vec![hashblock_message(0x01, 0), rawblock.clone()]);
+
+        let mut subscriber =
+            ZmqSubscriber::connect(&publisher.endpoint, &[ZMQ_TOPIC_HASHBLOCK, "rawblock"])
+                .unwrap();
+        assert_eq!(publisher.subscriptions.recv().unwrap(), b"hashblock");
+        assert_eq!(publisher.subscriptions.recv().unwrap(), b"rawblock");
+
+        let timeout = Duration::from_secs(10);
+        assert_eq!(
+            subscriber.recv_message(timeout).unwrap().unwrap(),
+            hashblock_message(0x01, 0)
+        );
+        assert_eq!(subscriber.recv_message(timeout).unwrap().unwrap(), rawblock);
+
+        // nothing else is coming
+        assert!(subscriber
+            .recv_message(Duration::from_millis(100))
+            .unwrap()
+            .is_none());
+
+        drop(subscriber);
+        publisher.handle.join().unwrap();
+    }
+
+    /// Replays what a libzmq 4.3 PUB socket (as used by bitcoind) puts on the wire, and checks
+    /// that we send it exactly what a libzmq SUB socket would.
+    #[test]
+    fn test_libzmq_publisher_interop() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            // libzmq sends its greeting in two parts: the signature, and then the rest once it
+            // has seen the peer's signature.
+            stream
+                .write_all(&[0xff, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x7f])
+                .unwrap();
+            let mut greeting = [0u8; ZMTP_GREETING_LEN];
+            stream.read_exact(&mut greeting).unwrap();
+            assert_eq!(&greeting[..], &zmtp_greeting()[..]);
+
+            // ZMTP 3.1, NULL mechanism, not as-server
+            let mut rest = vec![0x03, 0x01];
+            rest.extend_from_slice(b"NULL");
+            rest.extend_from_slice(&[0u8; 16]);
+            rest.push(0x00);
+            rest.extend_from_slice(&[0u8; 31]);
+            assert_eq!(rest.len(), ZMTP_GREETING_LEN - 10);
+            stream.write_all(&rest).unwrap();
+
+            let mut ready = [0u8; 27];
+            stream.read_exact(&mut ready).unwrap();
+            assert_eq!(
+                &ready[..],
+                &b"\x04\x19\x05READY\x0bSocket-Type\x00\x00\x00\x03SUB"[..]
+            );
+            stream
+                .write_all(b"\x04\x19\x05READY\x0bSocket-Type\x00\x00\x00\x03PUB")
+                .unwrap();
+
+            let mut subscribe = [0u8; 12];
+            stream.read_exact(&mut subscribe).unwrap();
+            assert_eq!(&subscribe[..], &b"\x00\x0a\x01hashblock"[..]);
+
+            // a ZMTP 3.1 heartbeat, then bitcoind's three-part hashblock message
+            stream.write_all(b"\x04\x07\x04PING\x00\x00").unwrap();
+            let mut message = b"\x01\x09hashblock\x01\x20".to_vec();
+            message.extend_from_slice(&[0x11; 32]);
+            message.extend_from_slice(b"\x00\x04\x07\x00\x00\x00");
+            stream.write_all(&message).unwrap();
+            stream.flush().unwrap();
+
+            let mut buf = [0u8; 1];
+            let _ = stream.read(&mut buf);
+        });
+
+        let mut subscriber = ZmqSubscriber::connect(&endpoint, &[ZMQ_TOPIC_HASHBLOCK]).unwrap();
+        assert_eq!(
+            subscriber
+                .recv_message(Duration::from_secs(10))
+                .unwrap()
+                .unwrap(),
+            vec![
+                b"hashblock".to_vec(),
+                vec![0x11; 32],
+                7u32.to_le_bytes().to_vec()
+            ]
+        );
+
+        drop(subscriber);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_notifier_wait() {
+        let notifier = BurnBlockNotifier::new();
+        assert_eq!(notifier.wait_for_new_block(0, Duration::from_millis(10)), 0);
+
+        let waiter = notifier.clone();
+        let handle = thread::spawn(move || waiter.wait_for_new_block(0, Duration::from_secs(60)));
+        notifier.notify();
+        assert_eq!(handle.join().unwrap(), 1);
+        assert_eq!(notifier.get_count(), 1);
+    }
+
+    #[test]
+    fn test_zmq_listener_notifies() {
+        let publisher = StubPublisher::spawn(
+            1,
+            vec![
+                hashblock_message(0x01, 0),
+                vec![b"sequence".to_vec(), vec![0x02; 33]],
+                hashblock_message(0x03, 1),
+            ],
+        );
+        let notifier = BurnBlockNotifier::new();
+        let should_keep_running = Arc::new(AtomicBool::new(true));
+        let listener = spawn_zmq_listener(
+            publisher.endpoint.clone(),
+            notifier.clone(),
+            should_keep_running.clone(),
+        );
+        // only `hashblock` is subscribed to
+        assert_eq!(publisher.subscriptions.recv().unwrap(), b"hashblock");
+
+        // only the two block announcements count
+        let mut count = 0;
+        for _ in 0..60 {
+            if count >= 2 {
+                break;
+            }
+            count = notifier.wait_for_new_block(count, Duration::from_secs(1));
+        }
+        assert_eq!(count, 2);
+
+        should_keep_running.store(false, Ordering::SeqCst);
+        listener.join().unwrap();
+        publisher.handle.join().unwrap();
+        assert_eq!(notifier.get_count(), 2);
+    }
+}
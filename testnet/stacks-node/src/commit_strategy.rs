@@ -0,0 +1,207 @@
+//! Strategies for deciding how much BTC a miner commits in each block-commit.
+//!
+//! The miner asks the configured `CommitStrategy` for the commit amount every time it builds a
+//! block-commit.  The strategy gets to look at recent sortition history, and is bounded by the
+//! miner's spend amount (`burn_fee_cap`, or whatever it was set to at runtime).  The burnchain
+//! controller also asks it whether an in-flight block-commit should be replaced-by-fee.
+
+use stacks::burnchains::Burnchain;
+use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::burn::distribution::BurnSamplePoint;
+use stacks::chainstate::burn::operations::LeaderBlockCommitOp;
+use stacks::chainstate::burn::BlockSnapshot;
+use stacks::util_lib::db::Error as db_error;
+
+use crate::run_loop::RegisteredKey;
+
+/// Number of recent sortitions the adaptive strategy looks at
+pub const ADAPTIVE_COMMIT_LOOKBACK: u64 = 6;
+
+/// Smallest amount the adaptive strategy will commit -- enough for both PoX outputs to be above
+/// the dust limit.
+pub const MIN_ADAPTIVE_COMMIT: u64 = 11_000;
+
+/// Everything a strategy gets to see when deciding on a commit amount
+pub struct CommitContext<'a> {
+    pub sortdb: &'a SortitionDB,
+    pub burnchain: &'a Burnchain,
+    /// the sortition the block-commit will be mined after
+    pub burn_tip: &'a BlockSnapshot,
+    /// the VRF key the block-commit will use; identifies this miner's past commits
+    pub registered_key: &'a RegisteredKey,
+    /// the most the miner is willing to commit
+    pub spend_cap: u64,
+}
+
+impl<'a> CommitContext<'a> {
+    /// Is this burn sample point one of our own block-commits?
+    fn is_ours(&self, point: &BurnSamplePoint) -> bool {
+        point.candidate.key_block_ptr as u64 == self.registered_key.block_height
+            && point.candidate.key_vtxindex as u32 == self.registered_key.op_vtxindex
+    }
+
+    /// Get the total effective burn of the other miners in each of the last `lookback`
+    /// sortitions (up to and including `burn_tip`), most recent first.
+    pub fn get_competing_burns(&self, lookback: u64) -> Result<Vec<u64>, db_error> {
+        let ic = self.sortdb.index_conn();
+        let mut competing_burns = vec![];
+        for blocks_back in 0..lookback {
+            if self.burn_tip.block_height < self.burnchain.first_block_height + blocks_back {
+                break;
+            }
+            let snapshot = match SortitionDB::get_ancestor_snapshot(
+                &ic,
+                self.burn_tip.block_height - blocks_back,
+                &self.burn_tip.sortition_id,
+            )? {
+                Some(sn) => sn,
+                None => break,
+            };
+            let distribution =
+                SortitionDB::get_block_burn_distribution(&ic, self.burnchain, &snapshot)?;
+            let competing_burn: u128 = distribution
+                .iter()
+                .filter(|point| !self.is_ours(point))
+                .map(|point| point.burns)
+                .sum();
+            competing_burns.push(u64::try_from(competing_burn).unwrap_or(u64::MAX));
+        }
+        Ok(competing_burns)
+    }
+}
+
+/// Everything a strategy gets to see when deciding whether to replace-by-fee an in-flight
+/// block-commit
+pub struct RbfContext<'a> {
+    /// the block-commit that is still in the mempool
+    pub ongoing: &'a LeaderBlockCommitOp,
+    /// the block-commit that would replace it
+    pub replacement: &'a LeaderBlockCommitOp,
+    /// the fee rate (sats/vbyte) the in-flight block-commit was last sent with
+    pub ongoing_fee_rate: u64,
+    /// the highest fee rate the miner will bump to (`satoshis_per_byte * max_rbf / 100`)
+    pub max_fee_rate: u64,
+}
+
+/// Decides how many sats to commit in a block-commit, and when to replace one that has not been
+/// mined yet
+pub trait CommitStrategy: Send {
+    /// How many sats to commit (including any sunset burn).  Must not exceed `ctx.spend_cap`.
+    fn commit_amount(&self, ctx: &CommitContext) -> u64;
+
+    /// Should the in-flight block-commit be replaced-by-fee with `ctx.replacement`?
+    /// By default, replace it unless the fee rate is already at its limit, or the replacement is
+    /// identical.
+    fn should_rbf(&self, ctx: &RbfContext) -> bool {
+        if ctx.ongoing_fee_rate > ctx.max_fee_rate {
+            warn!(
+                "RBF'd block commits reached the maximum fee rate of {} sats/vbyte, not resubmitting",
+                ctx.max_fee_rate
+            );
+            return false;
+        }
+        if ctx.ongoing == ctx.replacement {
+            info!("Abort attempt to re-submit identical LeaderBlockCommit");
+            return false;
+        }
+        true
+    }
+}
+
+/// Always commit the full spend amount.  This is the default.
+pub struct FixedCommitStrategy;
+
+impl CommitStrategy for FixedCommitStrategy {
+    fn commit_amount(&self, ctx: &CommitContext) -> u64 {
+        ctx.spend_cap
+    }
+}
+
+/// Commit just enough to win a sortition with the target probability, given what the other
+/// miners have been committing recently.
+pub struct AdaptiveCommitStrategy {
+    /// desired chance of winning each sortition, in (0, 1)
+    pub target_win_probability: f64,
+    /// number of recent sortitions to consider
+    pub lookback: u64,
+}
+
+impl AdaptiveCommitStrategy {
+    pub fn new(target_win_probability: f64) -> AdaptiveCommitStrategy {
+        AdaptiveCommitStrategy {
+            target_win_probability,
+            lookback: ADAPTIVE_COMMIT_LOOKBACK,
+        }
+    }
+
+    /// Given the other miners' total burns in recent sortitions, how much would we need to commit
+    /// to win with probability `target_win_probability`?  Our chance of winning is roughly
+    /// `ours / (ours + theirs)`, so we need `theirs * p / (1 - p)`.  The median of the recent
+    /// competing burns is used so a single outlier sortition doesn't swing the amount.
+    /// Returns None if there is no history to go on.
+    pub fn required_commit(&self, competing_burns: &[u64]) -> Option<u64> {
+        if competing_burns.is_empty() {
+            return None;
+        }
+        let mut sorted = competing_burns.to_vec();
+        sorted.sort();
+        let median = sorted[sorted.len() / 2];
+
+        let p = self.target_win_probability;
+        let required = ((median as f64) * p / (1.0 - p)).ceil();
+        if required >= u64::MAX as f64 {
+            Some(u64::MAX)
+        } else {
+            Some(required as u64)
+        }
+    }
+}
+
+impl CommitStrategy for AdaptiveCommitStrategy {
+    fn commit_amount(&self, ctx: &CommitContext) -> u64 {
+        let competing_burns = match ctx.get_competing_burns(self.lookback) {
+            Ok(burns) => burns,
+            Err(e) => {
+                warn!(
+                    "Failed to load recent burn distributions; committing the full spend amount: {:?}",
+                    &e
+                );
+                return ctx.spend_cap;
+            }
+        };
+        let amount = match self.required_commit(&competing_burns) {
+            Some(required) => required.max(MIN_ADAPTIVE_COMMIT).min(ctx.spend_cap),
+            None => ctx.spend_cap,
+        };
+        debug!(
+            "Adaptive commit strategy: commit {} of {} (target win probability {}, competing burns {:?})",
+            amount, ctx.spend_cap, self.target_win_probability, &competing_burns
+        );
+        amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_commit() {
+        let strategy = AdaptiveCommitStrategy::new(0.5);
+        assert_eq!(strategy.required_commit(&[]), None);
+        assert_eq!(strategy.required_commit(&[1000]), Some(1000));
+
+        // median of the competition, not the mean
+        assert_eq!(
+            strategy.required_commit(&[1000, 1_000_000, 2000]),
+            Some(2000)
+        );
+
+        let strategy = AdaptiveCommitStrategy::new(0.25);
+        assert_eq!(strategy.required_commit(&[3000, 3000]), Some(1000));
+
+        let strategy = AdaptiveCommitStrategy::new(0.75);
+        assert_eq!(strategy.required_commit(&[1000, 1000, 1000]), Some(3000));
+        assert_eq!(strategy.required_commit(&[u64::MAX]), Some(u64::MAX));
+    }
+}
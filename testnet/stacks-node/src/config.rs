@@ -1,10 +1,13 @@
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::fs;
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
+use http_types::Url;
 use rand::RngCore;
 
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
@@ -15,7 +18,7 @@ use stacks::chainstate::stacks::index::storage::TrieHashCalculationMode;
 use stacks::chainstate::stacks::miner::BlockBuilderSettings;
 use stacks::chainstate::stacks::miner::MinerStatus;
 use stacks::chainstate::stacks::MAX_BLOCK_LEN;
-use stacks::core::mempool::MemPoolWalkSettings;
+use stacks::core::mempool::{MemPoolWalkSettings, TxSelectionPolicy};
 use stacks::core::StacksEpoch;
 use stacks::core::StacksEpochExtension;
 use stacks::core::StacksEpochId;
@@ -31,7 +34,7 @@ use stacks::cost_estimates::CostEstimator;
 use stacks::cost_estimates::FeeEstimator;
 use stacks::cost_estimates::PessimisticEstimator;
 use stacks::net::connection::ConnectionOptions;
-use stacks::net::{Neighbor, NeighborKey, PeerAddress};
+use stacks::net::{AdminToken, Neighbor, NeighborKey, PeerAddress};
 use stacks::util::get_epoch_time_ms;
 use stacks::util::hash::hex_bytes;
 use stacks::util::secp256k1::Secp256k1PrivateKey;
@@ -39,6 +42,10 @@ use stacks::util::secp256k1::Secp256k1PublicKey;
 use stacks::vm::costs::ExecutionCost;
 use stacks::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier};
 
+use crate::commit_strategy::{AdaptiveCommitStrategy, CommitStrategy, FixedCommitStrategy};
+use crate::keychain::Keychain;
+use crate::signer::{RemoteSigner, SignerRef};
+
 const DEFAULT_SATS_PER_VB: u64 = 50;
 const DEFAULT_MAX_RBF_RATE: u64 = 150; // 1.5x
 const DEFAULT_RBF_FEE_RATE_INCREMENT: u64 = 5;
@@ -129,6 +136,216 @@ mod tests {
         assert!(Config::from_config_file(ConfigFile::from_str("").unwrap()).is_ok());
     }
 
+    #[test]
+    fn should_load_rpc_rate_limits_toml() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                rpc_query_rate_limit = 600
+                rpc_read_only_call_rate_limit = 60
+                read_only_call_budget_runtime = 5000000000
+                read_only_call_budget_interval = 300
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let opts = &config.connection_options;
+        assert_eq!(opts.rpc_query_rate_limit, 600);
+        assert_eq!(opts.rpc_read_only_call_rate_limit, 60);
+        assert_eq!(opts.rpc_stream_rate_limit, 0);
+        assert_eq!(opts.rpc_submit_rate_limit, 0);
+        assert_eq!(opts.read_only_call_budget.runtime, 5_000_000_000);
+        assert_eq!(
+            opts.read_only_call_budget.read_count,
+            HELIUM_DEFAULT_CONNECTION_OPTIONS
+                .read_only_call_budget
+                .read_count
+        );
+        assert_eq!(opts.read_only_call_budget_interval, 300);
+    }
+
+    #[test]
+    fn should_load_rpc_tls_toml() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                rpc_tls_bind = "0.0.0.0:20445"
+                rpc_tls_cert_path = "/etc/stacks/rpc-cert.pem"
+                rpc_tls_key_path = "/etc/stacks/rpc-key.pem"
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.node.rpc_tls_bind, Some("0.0.0.0:20445".to_string()));
+        assert_eq!(
+            config.node.rpc_tls_cert_path,
+            Some("/etc/stacks/rpc-cert.pem".to_string())
+        );
+        assert_eq!(
+            config.node.rpc_tls_key_path,
+            Some("/etc/stacks/rpc-key.pem".to_string())
+        );
+
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert_eq!(config.node.rpc_tls_bind, None);
+        assert_eq!(config.node.rpc_tls_cert_path, None);
+        assert_eq!(config.node.rpc_tls_key_path, None);
+
+        assert_eq!(
+            "node.rpc_tls_bind, node.rpc_tls_cert_path and node.rpc_tls_key_path must be set together".to_string(),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [node]
+                    rpc_tls_bind = "0.0.0.0:20445"
+                    rpc_tls_cert_path = "/etc/stacks/rpc-cert.pem"
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+
+        // other nodes reach the data_url over plain HTTP
+        assert_eq!(
+            "node.data_url must point at the plaintext RPC server, not at node.rpc_tls_bind"
+                .to_string(),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [node]
+                    data_url = "https://stacks.example.com:20445"
+                    rpc_tls_bind = "0.0.0.0:20445"
+                    rpc_tls_cert_path = "/etc/stacks/rpc-cert.pem"
+                    rpc_tls_key_path = "/etc/stacks/rpc-key.pem"
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_load_commit_strategy_toml() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [miner]
+                commit_strategy = "Adaptive"
+                target_win_probability = 0.25
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            config.miner.commit_strategy,
+            CommitStrategyName::Adaptive
+        ));
+        assert_eq!(config.miner.target_win_probability, 0.25);
+
+        assert_eq!(
+            "Bad commit strategy name supplied in configuration file: greedy".to_string(),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [miner]
+                    commit_strategy = "greedy"
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+
+        assert_eq!(
+            "miner.target_win_probability must be strictly between 0 and 1, got 1".to_string(),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [miner]
+                    target_win_probability = 1.0
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_load_admin_rpc_toml() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                admin_rpc_bind = "127.0.0.1:20445"
+                admin_rpc_token = "hunter2"
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.node.admin_rpc_bind,
+            Some("127.0.0.1:20445".to_string())
+        );
+        assert_eq!(
+            config.node.admin_rpc_token,
+            Some(AdminToken::new("hunter2"))
+        );
+        assert_eq!(config.node.config_path, None);
+
+        // the token must never show up in logged configs
+        assert!(!format!("{:?}", &config.node).contains("hunter2"));
+
+        assert_eq!(
+            format!("node.admin_rpc_bind requires a non-empty node.admin_rpc_token"),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [node]
+                    admin_rpc_bind = "127.0.0.1:20445"
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+        assert_eq!(
+            format!("node.admin_rpc_bind should be an IP:PORT socket address"),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [node]
+                    admin_rpc_bind = "localhost"
+                    admin_rpc_token = "hunter2"
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+        assert_eq!(
+            format!("miner.block_reward_recipient is not a valid principal identifier: nope"),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [miner]
+                    block_reward_recipient = "nope"
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+    }
+
     #[test]
     fn should_load_legacy_mstx_balances_toml() {
         let config = ConfigFile::from_str(
@@ -668,7 +885,55 @@ impl Config {
                     chain_liveness_poll_time_secs: node
                         .chain_liveness_poll_time_secs
                         .unwrap_or(default_node_config.chain_liveness_poll_time_secs),
+                    admin_rpc_bind: node.admin_rpc_bind,
+                    admin_rpc_token: node.admin_rpc_token.as_deref().map(AdminToken::new),
+                    rpc_tls_bind: node.rpc_tls_bind,
+                    rpc_tls_cert_path: node.rpc_tls_cert_path,
+                    rpc_tls_key_path: node.rpc_tls_key_path,
+                    account_history_index: node
+                        .account_history_index
+                        .unwrap_or(default_node_config.account_history_index),
+                    // set by the caller, once it knows where the config file came from.
+                    // you can't set this in the config file.
+                    config_path: None,
                 };
+                if let Some(ref admin_rpc_bind) = node_config.admin_rpc_bind {
+                    admin_rpc_bind.parse::<SocketAddr>().map_err(|_e| {
+                        "node.admin_rpc_bind should be an IP:PORT socket address".to_string()
+                    })?;
+                    match node_config.admin_rpc_token {
+                        Some(ref token) if !token.as_str().is_empty() => {}
+                        _ => {
+                            return Err(
+                                "node.admin_rpc_bind requires a non-empty node.admin_rpc_token"
+                                    .to_string(),
+                            );
+                        }
+                    }
+                }
+                match (
+                    node_config.rpc_tls_bind.as_ref(),
+                    node_config.rpc_tls_cert_path.is_some(),
+                    node_config.rpc_tls_key_path.is_some(),
+                ) {
+                    (None, false, false) => {}
+                    (Some(rpc_tls_bind), true, true) => {
+                        let rpc_tls_sock = rpc_tls_bind.parse::<SocketAddr>().map_err(|_e| {
+                            "node.rpc_tls_bind should be an IP:PORT socket address".to_string()
+                        })?;
+                        // other nodes fetch blocks, microblocks and mempool transactions from
+                        // the data_url over plain HTTP
+                        let data_url_port = Url::parse(&node_config.data_url)
+                            .map_err(|_e| "node.data_url should be a URL".to_string())?
+                            .port_or_known_default();
+                        if data_url_port == Some(rpc_tls_sock.port()) {
+                            return Err("node.data_url must point at the plaintext RPC server, not at node.rpc_tls_bind".to_string());
+                        }
+                    }
+                    _ => {
+                        return Err("node.rpc_tls_bind, node.rpc_tls_cert_path and node.rpc_tls_key_path must be set together".to_string());
+                    }
+                }
                 (node_config, node.bootstrap_node, node.deny_nodes)
             }
             None => (default_node_config, None, None),
@@ -787,6 +1052,7 @@ impl Config {
                     poll_time_secs: burnchain
                         .poll_time_secs
                         .unwrap_or(default_burnchain_config.poll_time_secs),
+                    zmq_endpoint: burnchain.zmq_endpoint,
                     satoshis_per_byte: burnchain
                         .satoshis_per_byte
                         .unwrap_or(default_burnchain_config.satoshis_per_byte),
@@ -857,10 +1123,15 @@ impl Config {
                 probability_pick_no_estimate_tx: miner
                     .probability_pick_no_estimate_tx
                     .unwrap_or(miner_default_config.probability_pick_no_estimate_tx),
-                block_reward_recipient: miner.block_reward_recipient.as_ref().map(|c| {
-                    PrincipalData::parse(&c)
-                        .expect(&format!("FATAL: not a valid principal identifier: {}", c))
-                }),
+                block_reward_recipient: match miner.block_reward_recipient.as_ref() {
+                    Some(c) => Some(PrincipalData::parse(c).map_err(|_e| {
+                        format!(
+                            "miner.block_reward_recipient is not a valid principal identifier: {}",
+                            c
+                        )
+                    })?),
+                    None => None,
+                },
                 segwit: miner.segwit.unwrap_or(miner_default_config.segwit),
                 wait_for_block_download: miner_default_config.wait_for_block_download,
                 nonce_cache_size: miner
@@ -869,6 +1140,41 @@ impl Config {
                 candidate_retry_cache_size: miner
                     .candidate_retry_cache_size
                     .unwrap_or(miner_default_config.candidate_retry_cache_size),
+                signer_endpoint: miner.signer_endpoint.clone(),
+                signer_auth_token: miner.signer_auth_token.clone(),
+                signer_timeout_ms: miner
+                    .signer_timeout_ms
+                    .unwrap_or(miner_default_config.signer_timeout_ms),
+                internal_wallet: miner
+                    .internal_wallet
+                    .unwrap_or(miner_default_config.internal_wallet),
+                commit_strategy: match miner.commit_strategy.as_ref() {
+                    Some(name) => CommitStrategyName::parse(name)?,
+                    None => miner_default_config.commit_strategy,
+                },
+                target_win_probability: match miner.target_win_probability {
+                    Some(p) if p > 0.0 && p < 1.0 => p,
+                    Some(p) => {
+                        return Err(format!(
+                            "miner.target_win_probability must be strictly between 0 and 1, got {}",
+                            p
+                        ));
+                    }
+                    None => miner_default_config.target_win_probability,
+                },
+                tx_selection_policy: TxSelectionPolicy {
+                    include_list: parse_principal_list(&miner.include_principals),
+                    deny_list: parse_principal_list(&miner.deny_principals),
+                    priority_list: parse_principal_list(&miner.priority_principals),
+                    reserved_priority_pct: match miner.priority_reserved_pct {
+                        Some(pct) => {
+                            assert!(pct < 100, "priority_reserved_pct must be less than 100");
+                            pct
+                        }
+                        None => 0,
+                    },
+                    max_contract_cost_pct: miner.max_contract_cost_pct,
+                },
             },
             None => miner_default_config,
         };
@@ -980,8 +1286,46 @@ impl Config {
                 opts.read_only_call_limit_runtime.map(|x| {
                     read_only_call_limit.runtime = x;
                 });
+                let mut read_only_call_budget = HELIUM_DEFAULT_CONNECTION_OPTIONS
+                    .read_only_call_budget
+                    .clone();
+                opts.read_only_call_budget_write_length.map(|x| {
+                    read_only_call_budget.write_length = x;
+                });
+                opts.read_only_call_budget_write_count.map(|x| {
+                    read_only_call_budget.write_count = x;
+                });
+                opts.read_only_call_budget_read_length.map(|x| {
+                    read_only_call_budget.read_length = x;
+                });
+                opts.read_only_call_budget_read_count.map(|x| {
+                    read_only_call_budget.read_count = x;
+                });
+                opts.read_only_call_budget_runtime.map(|x| {
+                    read_only_call_budget.runtime = x;
+                });
                 ConnectionOptions {
                     read_only_call_limit,
+                    read_only_call_budget,
+                    read_only_call_budget_interval: opts
+                        .read_only_call_budget_interval
+                        .unwrap_or_else(|| {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS.read_only_call_budget_interval
+                        }),
+                    rpc_query_rate_limit: opts
+                        .rpc_query_rate_limit
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.rpc_query_rate_limit),
+                    rpc_stream_rate_limit: opts
+                        .rpc_stream_rate_limit
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.rpc_stream_rate_limit),
+                    rpc_read_only_call_rate_limit: opts
+                        .rpc_read_only_call_rate_limit
+                        .unwrap_or_else(|| {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS.rpc_read_only_call_rate_limit
+                        }),
+                    rpc_submit_rate_limit: opts
+                        .rpc_submit_rate_limit
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.rpc_submit_rate_limit),
                     inbox_maxlen: opts
                         .inbox_maxlen
                         .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.inbox_maxlen.clone()),
@@ -1247,6 +1591,7 @@ impl Config {
                 consider_no_estimate_tx_prob: self.miner.probability_pick_no_estimate_tx,
                 nonce_cache_size: self.miner.nonce_cache_size,
                 candidate_retry_cache_size: self.miner.candidate_retry_cache_size,
+                selection_policy: self.miner.tx_selection_policy.clone(),
             },
             miner_status,
         }
@@ -1298,6 +1643,9 @@ pub struct BurnchainConfig {
     pub local_mining_public_key: Option<String>,
     pub process_exit_at_block_height: Option<u64>,
     pub poll_time_secs: u64,
+    /// bitcoind's ZMQ `hashblock` publisher (e.g. `tcp://127.0.0.1:28332`).
+    /// If set, the burnchain is synced as soon as a new block is announced.
+    pub zmq_endpoint: Option<String>,
     pub satoshis_per_byte: u64,
     pub max_rbf: u64,
     pub leader_key_tx_estimated_size: u64,
@@ -1333,6 +1681,7 @@ impl BurnchainConfig {
             local_mining_public_key: None,
             process_exit_at_block_height: None,
             poll_time_secs: 10, // TODO: this is a testnet specific value.
+            zmq_endpoint: None,
             satoshis_per_byte: DEFAULT_SATS_PER_VB,
             max_rbf: DEFAULT_MAX_RBF_RATE,
             leader_key_tx_estimated_size: LEADER_KEY_TX_ESTIM_SIZE,
@@ -1411,6 +1760,7 @@ pub struct BurnchainConfigFile {
     pub local_mining_public_key: Option<String>,
     pub process_exit_at_block_height: Option<u64>,
     pub poll_time_secs: Option<u64>,
+    pub zmq_endpoint: Option<String>,
     pub satoshis_per_byte: Option<u64>,
     pub leader_key_tx_estimated_size: Option<u64>,
     pub block_commit_tx_estimated_size: Option<u64>,
@@ -1456,6 +1806,22 @@ pub struct NodeConfig {
     /// At most, how often should the chain-liveness thread
     ///  wake up the chains-coordinator. Defaults to 300s (5 min).
     pub chain_liveness_poll_time_secs: u64,
+    /// IP:PORT on which to serve the /admin RPC interface, if at all
+    pub admin_rpc_bind: Option<String>,
+    /// bearer token that clients of the /admin RPC interface must present
+    pub admin_rpc_token: Option<AdminToken>,
+    /// IP:PORT on which to also serve the RPC interface over TLS, if at all
+    pub rpc_tls_bind: Option<String>,
+    /// PEM certificate chain for `rpc_tls_bind`.  Re-read when it changes on disk.
+    pub rpc_tls_cert_path: Option<String>,
+    /// PEM private key for `rpc_tls_cert_path`
+    pub rpc_tls_key_path: Option<String>,
+    /// Index the transactions and asset events that affect each principal as blocks are
+    /// processed, and serve them on /v2/accounts/:principal/history
+    pub account_history_index: bool,
+    // path to the config file this config was loaded from, if any.
+    // not part of the config file.
+    pub config_path: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -1530,6 +1896,35 @@ impl CostMetricName {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum CommitStrategyName {
+    /// Always commit the full spend amount
+    Fixed,
+    /// Commit enough to hit `target_win_probability`, up to the spend amount
+    Adaptive,
+}
+
+impl Default for CommitStrategyName {
+    fn default() -> Self {
+        CommitStrategyName::Fixed
+    }
+}
+
+impl CommitStrategyName {
+    fn parse(s: &str) -> Result<CommitStrategyName, String> {
+        if &s.to_lowercase() == "fixed" {
+            Ok(CommitStrategyName::Fixed)
+        } else if &s.to_lowercase() == "adaptive" {
+            Ok(CommitStrategyName::Adaptive)
+        } else {
+            Err(format!(
+                "Bad commit strategy name supplied in configuration file: {}",
+                s
+            ))
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FeeEstimationConfig {
     pub cost_estimator: Option<CostEstimatorName>,
@@ -1629,6 +2024,29 @@ impl Config {
 
         Some(fee_estimator)
     }
+
+    /// Instantiate the signer that holds the miner's keys: a `RemoteSigner` if
+    /// `miner.signer_endpoint` is set, or the seed-derived `Keychain` otherwise.
+    pub fn make_signer(&self) -> SignerRef {
+        match self.miner.signer_endpoint.as_ref() {
+            Some(endpoint) => Arc::new(RemoteSigner::new(
+                endpoint,
+                self.miner.signer_auth_token.clone(),
+                Duration::from_millis(self.miner.signer_timeout_ms),
+            )),
+            None => Arc::new(Keychain::default(self.node.seed.clone())),
+        }
+    }
+
+    /// Instantiate the strategy that decides how much to spend on each block-commit
+    pub fn make_commit_strategy(&self) -> Box<dyn CommitStrategy> {
+        match self.miner.commit_strategy {
+            CommitStrategyName::Fixed => Box::new(FixedCommitStrategy),
+            CommitStrategyName::Adaptive => Box::new(AdaptiveCommitStrategy::new(
+                self.miner.target_win_probability,
+            )),
+        }
+    }
 }
 
 impl FeeEstimationConfig {
@@ -1734,6 +2152,13 @@ impl NodeConfig {
             require_affirmed_anchor_blocks: true,
             fault_injection_hide_blocks: false,
             chain_liveness_poll_time_secs: 300,
+            admin_rpc_bind: None,
+            admin_rpc_token: None,
+            rpc_tls_bind: None,
+            rpc_tls_cert_path: None,
+            rpc_tls_key_path: None,
+            account_history_index: false,
+            config_path: None,
         }
     }
 
@@ -1846,6 +2271,22 @@ pub struct MinerConfig {
     pub wait_for_block_download: bool,
     pub nonce_cache_size: u64,
     pub candidate_retry_cache_size: u64,
+    /// host:port of an external signer that holds the miner's keys.
+    /// If unset, keys are derived in-process from `node.seed`.
+    pub signer_endpoint: Option<String>,
+    /// bearer token to present to the external signer, if it requires one
+    pub signer_auth_token: Option<String>,
+    /// how long to wait on the external signer to connect, or to send or receive a request
+    pub signer_timeout_ms: u64,
+    /// Track the miner's UTXOs from downloaded burnchain blocks, instead of asking
+    /// bitcoind's wallet for them.
+    pub internal_wallet: bool,
+    /// How to decide the amount of each block-commit
+    pub commit_strategy: CommitStrategyName,
+    /// If using the adaptive commit strategy, the chance of winning each sortition to aim for
+    pub target_win_probability: f64,
+    /// Which mempool transactions may be selected, beyond ordering by fee rate
+    pub tx_selection_policy: TxSelectionPolicy,
 }
 
 impl MinerConfig {
@@ -1861,10 +2302,29 @@ impl MinerConfig {
             wait_for_block_download: true,
             nonce_cache_size: 10_000,
             candidate_retry_cache_size: 10_000,
+            signer_endpoint: None,
+            signer_auth_token: None,
+            signer_timeout_ms: 10_000,
+            internal_wallet: false,
+            commit_strategy: CommitStrategyName::default(),
+            target_win_probability: 0.5,
+            tx_selection_policy: TxSelectionPolicy::default(),
         }
     }
 }
 
+/// Parse a config file list of principals, panicking on any bad entry
+fn parse_principal_list(principals: &Option<Vec<String>>) -> HashSet<PrincipalData> {
+    principals
+        .iter()
+        .flatten()
+        .map(|p| {
+            PrincipalData::parse(p)
+                .unwrap_or_else(|_| panic!("FATAL: not a valid principal identifier: {}", p))
+        })
+        .collect()
+}
+
 #[derive(Clone, Default, Deserialize, Debug)]
 pub struct ConnectionOptionsFile {
     pub inbox_maxlen: Option<usize>,
@@ -1896,6 +2356,16 @@ pub struct ConnectionOptionsFile {
     pub read_only_call_limit_write_count: Option<u64>,
     pub read_only_call_limit_read_count: Option<u64>,
     pub read_only_call_limit_runtime: Option<u64>,
+    pub read_only_call_budget_write_length: Option<u64>,
+    pub read_only_call_budget_read_length: Option<u64>,
+    pub read_only_call_budget_write_count: Option<u64>,
+    pub read_only_call_budget_read_count: Option<u64>,
+    pub read_only_call_budget_runtime: Option<u64>,
+    pub read_only_call_budget_interval: Option<u64>,
+    pub rpc_query_rate_limit: Option<u64>,
+    pub rpc_stream_rate_limit: Option<u64>,
+    pub rpc_read_only_call_rate_limit: Option<u64>,
+    pub rpc_submit_rate_limit: Option<u64>,
     pub maximum_call_argument_size: Option<u32>,
     pub download_interval: Option<u64>,
     pub inv_sync_interval: Option<u64>,
@@ -1938,6 +2408,12 @@ pub struct NodeConfigFile {
     /// At most, how often should the chain-liveness thread
     ///  wake up the chains-coordinator. Defaults to 300s (5 min).
     pub chain_liveness_poll_time_secs: Option<u64>,
+    pub admin_rpc_bind: Option<String>,
+    pub admin_rpc_token: Option<String>,
+    pub rpc_tls_bind: Option<String>,
+    pub rpc_tls_cert_path: Option<String>,
+    pub rpc_tls_key_path: Option<String>,
+    pub account_history_index: Option<bool>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -1976,6 +2452,17 @@ pub struct MinerConfigFile {
     pub segwit: Option<bool>,
     pub nonce_cache_size: Option<u64>,
     pub candidate_retry_cache_size: Option<u64>,
+    pub signer_endpoint: Option<String>,
+    pub signer_auth_token: Option<String>,
+    pub signer_timeout_ms: Option<u64>,
+    pub internal_wallet: Option<bool>,
+    pub commit_strategy: Option<String>,
+    pub target_win_probability: Option<f64>,
+    pub include_principals: Option<Vec<String>>,
+    pub deny_principals: Option<Vec<String>>,
+    pub priority_principals: Option<Vec<String>>,
+    pub priority_reserved_pct: Option<u8>,
+    pub max_contract_cost_pct: Option<u8>,
 }
 
 #[derive(Clone, Deserialize, Default, Debug)]
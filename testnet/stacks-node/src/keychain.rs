@@ -1,13 +1,15 @@
 use stacks::address::AddressHashMode;
-use stacks::burnchains::BurnchainSigner;
+use stacks::burnchains::{BurnchainSigner, PrivateKey};
 use stacks::chainstate::stacks::{
     StacksPrivateKey, StacksPublicKey, StacksTransactionSigner, TransactionAuth,
 };
 use stacks::types::chainstate::StacksAddress;
 use stacks::util::hash::{Hash160, Sha256Sum};
+use stacks::util::secp256k1::{MessageSignature, Secp256k1PublicKey};
 use stacks::util::vrf::{VRFPrivateKey, VRFProof, VRFPublicKey, VRF};
 
 use super::operations::BurnchainOpSigner;
+use super::signer::{Error as SignerError, Signer};
 
 use stacks_common::address::{
     C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
@@ -112,7 +114,7 @@ impl Keychain {
     /// Generate a microblock signing key for this burnchain block height.
     /// `salt` can be any byte string; in practice, it's the parent Stacks block's block ID hash.
     pub fn make_microblock_secret_key(
-        &mut self,
+        &self,
         burn_block_height: u64,
         salt: &[u8],
     ) -> StacksPrivateKey {
@@ -185,6 +187,35 @@ impl Keychain {
     }
 }
 
+/// The in-process signer: all keys are derived from the seed held in this keychain
+impl Signer for Keychain {
+    fn public_key(&self) -> Result<Secp256k1PublicKey, SignerError> {
+        Ok(StacksPublicKey::from_private(&self.get_secret_key()))
+    }
+
+    fn sign_digest(&self, digest: &[u8]) -> Result<MessageSignature, SignerError> {
+        self.get_secret_key()
+            .sign(digest)
+            .map_err(|e| SignerError::Rejected(e.to_string()))
+    }
+
+    fn vrf_public_key(&self, block_height: u64) -> Result<VRFPublicKey, SignerError> {
+        Ok(self.make_vrf_keypair(block_height).0)
+    }
+
+    fn vrf_prove(&self, block_height: u64, message: &[u8; 32]) -> Result<VRFProof, SignerError> {
+        Ok(self.generate_proof(block_height, message))
+    }
+
+    fn microblock_secret_key(
+        &self,
+        burn_block_height: u64,
+        salt: &[u8],
+    ) -> Result<StacksPrivateKey, SignerError> {
+        Ok(self.make_microblock_secret_key(burn_block_height, salt))
+    }
+}
+
 #[cfg(test)]
 #[allow(dead_code)]
 mod tests {
@@ -23,6 +23,7 @@ use stacks::util::hash::hex_bytes;
 pub mod monitoring;
 
 pub mod burnchains;
+pub mod commit_strategy;
 pub mod config;
 pub mod event_dispatcher;
 pub mod genesis_data;
@@ -31,6 +32,7 @@ pub mod neon_node;
 pub mod node;
 pub mod operations;
 pub mod run_loop;
+pub mod signer;
 pub mod syncctl;
 pub mod tenure;
 
@@ -92,6 +94,9 @@ fn main() {
         );
     }
 
+    // the config file to re-read when asked to reload the config at runtime
+    let mut reload_config_path = None;
+
     let config_file = match subcommand.as_str() {
         "mocknet" => {
             args.finish().unwrap();
@@ -139,7 +144,10 @@ fn main() {
             args.finish().unwrap();
             info!("Loading config at path {}", config_path);
             match ConfigFile::from_path(&config_path) {
-                Ok(config_file) => config_file,
+                Ok(config_file) => {
+                    reload_config_path = Some(config_path);
+                    config_file
+                }
                 Err(e) => {
                     warn!("Invalid config file: {}", e);
                     process::exit(1);
@@ -170,11 +178,11 @@ fn main() {
             let keychain = Keychain::default(seed);
             println!(
                 "Hex formatted secret key: {}",
-                keychain.generate_op_signer().get_sk_as_hex()
+                keychain.generate_op_signer().get_sk_as_hex().unwrap()
             );
             println!(
                 "WIF formatted secret key: {}",
-                keychain.generate_op_signer().get_sk_as_wif()
+                keychain.generate_op_signer().get_sk_as_wif().unwrap()
             );
             return;
         }
@@ -184,13 +192,14 @@ fn main() {
         }
     };
 
-    let conf = match Config::from_config_file(config_file) {
+    let mut conf = match Config::from_config_file(config_file) {
         Ok(conf) => conf,
         Err(e) => {
             warn!("Invalid config: {}", e);
             process::exit(1);
         }
     };
+    conf.node.config_path = reload_config_path;
     debug!("node configuration {:?}", &conf.node);
     debug!("burnchain configuration {:?}", &conf.burnchain);
     debug!("connection configuration {:?}", &conf.connection_options);
@@ -190,7 +190,7 @@ use stacks::net::{
     dns::DNSResolver,
     p2p::PeerNetwork,
     relay::Relayer,
-    rpc::RPCHandlerArgs,
+    rpc::{AdminHandler, RPCHandlerArgs},
     Error as NetError, NetworkResult, PeerAddress, ServiceFlags,
 };
 use stacks::types::chainstate::{
@@ -208,12 +208,16 @@ use stacks::vm::costs::ExecutionCost;
 use crate::burnchains::bitcoin_regtest_controller::BitcoinRegtestController;
 use crate::burnchains::bitcoin_regtest_controller::OngoingBlockCommit;
 use crate::burnchains::make_bitcoin_indexer;
+use crate::commit_strategy::{CommitContext, CommitStrategy};
+use crate::config::MinerConfig;
 use crate::run_loop::neon::Counters;
 use crate::run_loop::neon::RunLoop;
 use crate::run_loop::RegisteredKey;
 use crate::ChainTip;
 
-use super::{BurnchainController, Config, EventDispatcher, Keychain};
+use super::{BurnchainController, Config, ConfigFile, EventDispatcher};
+use crate::operations::BurnchainOpSigner;
+use crate::signer::{self, SignerRef};
 use crate::syncctl::PoxSyncWatchdogComms;
 use stacks::monitoring;
 
@@ -227,6 +231,16 @@ use clarity::vm::types::PrincipalData;
 pub const RELAYER_MAX_BUFFER: usize = 100;
 const VRF_MOCK_MINER_KEY: u64 = 1;
 
+/// The key a mock miner pretends to have registered
+fn mock_registered_key(vrf_public_key: VRFPublicKey) -> RegisteredKey {
+    RegisteredKey {
+        target_block_height: VRF_MOCK_MINER_KEY,
+        block_height: 1,
+        op_vtxindex: 1,
+        vrf_public_key,
+    }
+}
+
 pub const BLOCK_PROCESSOR_STACK_SIZE: usize = 32 * 1024 * 1024; // 32 MB
 
 type MinedBlocks = HashMap<BlockHeaderHash, (AssembledAnchorBlock, Secp256k1PrivateKey)>;
@@ -276,6 +290,8 @@ pub enum RelayerDirective {
     RunTenure(RegisteredKey, BlockSnapshot, u128), // (vrf key, chain tip, time of issuance in ms)
     /// Try to register a VRF public key
     RegisterKey(BlockSnapshot),
+    /// Use new miner settings, re-read from the config file
+    ReloadMinerConfig(MinerConfig),
     /// Stop the relayer thread
     Exit,
 }
@@ -301,6 +317,9 @@ pub struct Globals {
     pub should_keep_running: Arc<AtomicBool>,
     /// Status of our VRF key registration state (shared between the main thread and the relayer)
     leader_key_registration_state: Arc<Mutex<LeaderKeyRegistrationState>>,
+    /// Whether or not the operator allows the miner to start new tenures (set via the admin RPC
+    /// interface)
+    mining_enabled: Arc<AtomicBool>,
 }
 
 /// Miner chain tip, on top of which to build microblocks
@@ -357,6 +376,7 @@ impl Globals {
             leader_key_registration_state: Arc::new(Mutex::new(
                 LeaderKeyRegistrationState::Inactive,
             )),
+            mining_enabled: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -441,6 +461,16 @@ impl Globals {
         self.should_keep_running.load(Ordering::SeqCst)
     }
 
+    /// Is the miner allowed to start new tenures?
+    pub fn is_mining_enabled(&self) -> bool {
+        self.mining_enabled.load(Ordering::SeqCst)
+    }
+
+    /// Allow or forbid the miner to start new tenures.  Tenures already running are unaffected.
+    pub fn set_mining_enabled(&self, enabled: bool) {
+        self.mining_enabled.store(enabled, Ordering::SeqCst);
+    }
+
     /// Get the handle to the coordinator
     pub fn coord(&self) -> &CoordinatorChannels {
         &self.coord_comms
@@ -628,6 +658,7 @@ fn open_chainstate_with_faults(config: &Config) -> Result<StacksChainState, Chai
     )?;
 
     chainstate.fault_injection.hide_blocks = config.node.fault_injection_hide_blocks;
+    chainstate.index_account_history = config.node.account_history_index;
     Ok(chainstate)
 }
 
@@ -689,8 +720,8 @@ pub struct RelayerThread {
     mempool: Option<MemPoolDB>,
     /// Handle to global state and inter-thread communication channels
     globals: Globals,
-    /// Authoritative copy of the keychain state
-    keychain: Keychain,
+    /// Handle to the signer that holds the miner's keys
+    signer: SignerRef,
     /// Burnchian configuration
     burnchain: Burnchain,
     /// height of last VRF key registration request
@@ -750,8 +781,8 @@ struct BlockMinerThread {
     config: Config,
     /// handle to global state
     globals: Globals,
-    /// copy of the node's keychain
-    keychain: Keychain,
+    /// handle to the signer that holds the miner's keys
+    signer: SignerRef,
     /// burnchain configuration
     burnchain: Burnchain,
     /// Set of blocks that we have mined, but are still potentially-broadcastable
@@ -1226,7 +1257,7 @@ impl BlockMinerThread {
         BlockMinerThread {
             config: rt.config.clone(),
             globals: rt.globals.clone(),
-            keychain: rt.keychain.clone(),
+            signer: rt.signer.clone(),
             burnchain: rt.burnchain.clone(),
             last_mined_blocks: rt.last_mined_blocks.clone(),
             ongoing_commit: rt.bitcoin_controller.get_ongoing_commit(),
@@ -1251,10 +1282,19 @@ impl BlockMinerThread {
         &mut self,
         nonce: u64,
         epoch_id: StacksEpochId,
-    ) -> StacksTransaction {
+    ) -> Option<StacksTransaction> {
         let is_mainnet = self.config.is_mainnet();
         let chain_id = self.config.burnchain.chain_id;
-        let mut tx_auth = self.keychain.get_transaction_auth().unwrap();
+        let mut tx_auth = match signer::get_transaction_auth(self.signer.as_ref()) {
+            Ok(auth) => auth,
+            Err(e) => {
+                warn!(
+                    "Failed to get coinbase transaction auth from signer: {}",
+                    &e
+                );
+                return None;
+            }
+        };
         tx_auth.set_origin_nonce(nonce);
 
         let version = if is_mainnet {
@@ -1272,9 +1312,12 @@ impl BlockMinerThread {
         tx.chain_id = chain_id;
         tx.anchor_mode = TransactionAnchorMode::OnChainOnly;
         let mut tx_signer = StacksTransactionSigner::new(&tx);
-        self.keychain.sign_as_origin(&mut tx_signer);
+        if let Err(e) = signer::sign_as_origin(self.signer.as_ref(), &mut tx_signer) {
+            warn!("Failed to sign coinbase transaction: {}", &e);
+            return None;
+        }
 
-        tx_signer.get_tx().unwrap()
+        tx_signer.get_tx()
     }
 
     /// Create a poison microblock transaction.
@@ -1282,10 +1325,19 @@ impl BlockMinerThread {
         &mut self,
         nonce: u64,
         poison_payload: TransactionPayload,
-    ) -> StacksTransaction {
+    ) -> Option<StacksTransaction> {
         let is_mainnet = self.config.is_mainnet();
         let chain_id = self.config.burnchain.chain_id;
-        let mut tx_auth = self.keychain.get_transaction_auth().unwrap();
+        let mut tx_auth = match signer::get_transaction_auth(self.signer.as_ref()) {
+            Ok(auth) => auth,
+            Err(e) => {
+                warn!(
+                    "Failed to get poison-microblock transaction auth from signer: {}",
+                    &e
+                );
+                return None;
+            }
+        };
         tx_auth.set_origin_nonce(nonce);
 
         let version = if is_mainnet {
@@ -1297,12 +1349,16 @@ impl BlockMinerThread {
         tx.chain_id = chain_id;
         tx.anchor_mode = TransactionAnchorMode::OnChainOnly;
         let mut tx_signer = StacksTransactionSigner::new(&tx);
-        self.keychain.sign_as_origin(&mut tx_signer);
+        if let Err(e) = signer::sign_as_origin(self.signer.as_ref(), &mut tx_signer) {
+            warn!("Failed to sign poison-microblock transaction: {}", &e);
+            return None;
+        }
 
-        tx_signer.get_tx().unwrap()
+        tx_signer.get_tx()
     }
 
     /// Constructs and returns a LeaderBlockCommitOp out of the provided params.
+    /// Returns None if the signer could not report the miner's address.
     fn inner_generate_block_commit_op(
         &self,
         block_header_hash: BlockHeaderHash,
@@ -1314,29 +1370,37 @@ impl BlockMinerThread {
         commit_outs: Vec<PoxAddress>,
         sunset_burn: u64,
         current_burn_height: u64,
-    ) -> BlockstackOperationType {
+    ) -> Option<BlockstackOperationType> {
         let (parent_block_ptr, parent_vtxindex) = (parent_burnchain_height, parent_winning_vtx);
         let burn_parent_modulus = (current_burn_height % BURN_BLOCK_MINED_AT_MODULUS) as u8;
-        let sender = self.keychain.get_burnchain_signer();
-        BlockstackOperationType::LeaderBlockCommit(LeaderBlockCommitOp {
-            sunset_burn,
-            block_header_hash,
-            burn_fee,
-            input: (Txid([0; 32]), 0),
-            apparent_sender: sender,
-            key_block_ptr: key.block_height as u32,
-            key_vtxindex: key.op_vtxindex as u16,
-            memo: vec![STACKS_EPOCH_2_1_MARKER],
-            new_seed: vrf_seed,
-            parent_block_ptr,
-            parent_vtxindex,
-            vtxindex: 0,
-            txid: Txid([0u8; 32]),
-            block_height: 0,
-            burn_header_hash: BurnchainHeaderHash::zero(),
-            burn_parent_modulus,
-            commit_outs,
-        })
+        let sender = match signer::get_burnchain_signer(self.signer.as_ref()) {
+            Ok(sender) => sender,
+            Err(e) => {
+                warn!("Failed to get burnchain signer: {}", &e);
+                return None;
+            }
+        };
+        Some(BlockstackOperationType::LeaderBlockCommit(
+            LeaderBlockCommitOp {
+                sunset_burn,
+                block_header_hash,
+                burn_fee,
+                input: (Txid([0; 32]), 0),
+                apparent_sender: sender,
+                key_block_ptr: key.block_height as u32,
+                key_vtxindex: key.op_vtxindex as u16,
+                memo: vec![STACKS_EPOCH_2_1_MARKER],
+                new_seed: vrf_seed,
+                parent_block_ptr,
+                parent_vtxindex,
+                vtxindex: 0,
+                txid: Txid([0u8; 32]),
+                block_height: 0,
+                burn_header_hash: BurnchainHeaderHash::zero(),
+                burn_parent_modulus,
+                commit_outs,
+            },
+        ))
     }
 
     /// Get references to the inner assembled anchor block data we've produced for a given burnchain block height
@@ -1365,10 +1429,14 @@ impl BlockMinerThread {
             .get_stacks_chain_tip(burn_db)
             .expect("FATAL: could not query chain tip")
         {
-            let miner_address = self
-                .keychain
-                .origin_address(self.config.is_mainnet())
-                .unwrap();
+            let miner_address =
+                match signer::get_address(self.signer.as_ref(), self.config.is_mainnet()) {
+                    Ok(addr) => addr,
+                    Err(e) => {
+                        warn!("Failed to get miner address from signer: {}", &e);
+                        return None;
+                    }
+                };
             match ParentStacksBlockInfo::lookup(
                 chain_state,
                 burn_db,
@@ -1530,18 +1598,22 @@ impl BlockMinerThread {
     /// Returns Some(proof) if we could make the proof
     /// Return None if we could not make the proof
     fn make_vrf_proof(&mut self) -> Option<VRFProof> {
-        // if we're a mock miner, then make sure that the keychain has a keypair for the mocked VRF
+        // if we're a mock miner, then make sure that the signer has a keypair for the mocked VRF
         // key
-        let vrf_proof = if self.config.node.mock_mining {
-            self.keychain.generate_proof(
-                VRF_MOCK_MINER_KEY,
-                self.burn_block.sortition_hash.as_bytes(),
-            )
+        let vrf_key_height = if self.config.node.mock_mining {
+            VRF_MOCK_MINER_KEY
         } else {
-            self.keychain.generate_proof(
-                self.registered_key.target_block_height,
-                self.burn_block.sortition_hash.as_bytes(),
-            )
+            self.registered_key.target_block_height
+        };
+        let vrf_proof = match self
+            .signer
+            .vrf_prove(vrf_key_height, self.burn_block.sortition_hash.as_bytes())
+        {
+            Ok(proof) => proof,
+            Err(e) => {
+                warn!("Failed to generate VRF proof: {}", &e);
+                return None;
+            }
         };
 
         debug!(
@@ -1564,11 +1636,13 @@ impl BlockMinerThread {
     fn make_microblock_private_key(
         &mut self,
         parent_stacks_hash: &StacksBlockId,
-    ) -> Secp256k1PrivateKey {
+    ) -> Option<Secp256k1PrivateKey> {
         // Generates a new secret key for signing the trail of microblocks
         // of the upcoming tenure.
-        self.keychain
-            .make_microblock_secret_key(self.burn_block.block_height, &parent_stacks_hash.0)
+        self.signer
+            .microblock_secret_key(self.burn_block.block_height, &parent_stacks_hash.0)
+            .map_err(|e| warn!("Failed to get microblock secret key: {}", &e))
+            .ok()
     }
 
     /// Get the microblock private key we'll be using for this tenure, should we win.
@@ -1577,14 +1651,17 @@ impl BlockMinerThread {
     fn make_microblock_private_key(
         &mut self,
         _parent_stacks_hash: &StacksBlockId,
-    ) -> Secp256k1PrivateKey {
+    ) -> Option<Secp256k1PrivateKey> {
         // Generates a new secret key for signing the trail of microblocks
         // of the upcoming tenure.
         warn!("test version of make_microblock_secret_key");
-        self.keychain.make_microblock_secret_key(
-            self.burn_block.block_height,
-            &self.burn_block.block_height.to_be_bytes(),
-        )
+        self.signer
+            .microblock_secret_key(
+                self.burn_block.block_height,
+                &self.burn_block.block_height.to_be_bytes(),
+            )
+            .map_err(|e| warn!("Failed to get microblock secret key: {}", &e))
+            .ok()
     }
 
     /// Load the parent microblock stream and vet it for the absence of forks.
@@ -1653,10 +1730,13 @@ impl BlockMinerThread {
                 // eventually getting picked up (even if the miner sends other transactions from
                 // the same address)
                 for i in 0..10 {
-                    let poison_microblock_tx = self.inner_generate_poison_microblock_tx(
+                    let poison_microblock_tx = match self.inner_generate_poison_microblock_tx(
                         parent_block_info.coinbase_nonce + 1 + i,
                         poison_payload.clone(),
-                    );
+                    ) {
+                        Some(tx) => tx,
+                        None => break,
+                    };
 
                     // submit the poison payload, privately, so we'll mine it when building the
                     // anchored block.
@@ -1697,6 +1777,7 @@ impl BlockMinerThread {
         parent_winning_vtxindex: u16,
         vrf_proof: &VRFProof,
         target_epoch_id: StacksEpochId,
+        commit_strategy: &dyn CommitStrategy,
     ) -> Option<BlockstackOperationType> {
         // let's figure out the recipient set!
         let recipients = match get_next_recipients(
@@ -1714,8 +1795,14 @@ impl BlockMinerThread {
             }
         };
 
-        // let burn_fee_cap = self.config.burnchain.burn_fee_cap;
-        let burn_fee_cap = get_mining_spend_amount(self.globals.get_miner_status());
+        let spend_cap = get_mining_spend_amount(self.globals.get_miner_status());
+        let burn_fee_cap = commit_strategy.commit_amount(&CommitContext {
+            sortdb: burn_db,
+            burnchain: &self.burnchain,
+            burn_tip: &self.burn_block,
+            registered_key: &self.registered_key,
+            spend_cap,
+        });
         let sunset_burn = self.burnchain.expected_sunset_burn(
             self.burn_block.block_height + 1,
             burn_fee_cap,
@@ -1737,7 +1824,7 @@ impl BlockMinerThread {
         };
 
         // let's commit, but target the current burnchain tip with our modulus
-        let op = self.inner_generate_block_commit_op(
+        self.inner_generate_block_commit_op(
             block_hash,
             rest_commit,
             &self.registered_key,
@@ -1749,8 +1836,7 @@ impl BlockMinerThread {
             commit_outs,
             sunset_burn,
             self.burn_block.block_height,
-        );
-        Some(op)
+        )
     }
 
     /// Are there enough unprocessed blocks that we shouldn't mine?
@@ -1862,7 +1948,7 @@ impl BlockMinerThread {
         // of the upcoming tenure.
         let microblock_private_key = self.make_microblock_private_key(
             &parent_block_info.stacks_parent_header.index_block_hash(),
-        );
+        )?;
         let mblock_pubkey_hash = {
             let mut pubkh = Hash160::from_node_public_key(&StacksPublicKey::from_private(
                 &microblock_private_key,
@@ -1883,7 +1969,7 @@ impl BlockMinerThread {
 
         // create our coinbase
         let coinbase_tx =
-            self.inner_generate_coinbase_tx(parent_block_info.coinbase_nonce, target_epoch_id);
+            self.inner_generate_coinbase_tx(parent_block_info.coinbase_nonce, target_epoch_id)?;
 
         // find the longest microblock tail we can build off of.
         // target it to the microblock tail in parent_block_info
@@ -1989,6 +2075,7 @@ impl BlockMinerThread {
             parent_block_info.parent_winning_vtxindex,
             &vrf_proof,
             target_epoch_id,
+            bitcoin_controller.get_commit_strategy(),
         )?;
 
         // last chance -- confirm that the stacks tip is unchanged (since it could have taken long
@@ -2042,7 +2129,13 @@ impl BlockMinerThread {
             }
         }
 
-        let mut op_signer = self.keychain.generate_op_signer();
+        let mut op_signer = match BurnchainOpSigner::from_signer(self.signer.clone(), false) {
+            Ok(op_signer) => op_signer,
+            Err(e) => {
+                warn!("Relayer: Failed to set up block-commit signer: {}", &e);
+                return None;
+            }
+        };
         debug!(
             "Relayer: Submit block-commit";
             "block_hash" => %anchored_block.block_hash(),
@@ -2119,7 +2212,7 @@ impl RelayerThread {
         )
         .expect("Database failure opening mempool");
 
-        let keychain = Keychain::default(config.node.seed.clone());
+        let signer = config.make_signer();
         let bitcoin_controller = BitcoinRegtestController::new_dummy(config.clone());
 
         RelayerThread {
@@ -2128,7 +2221,7 @@ impl RelayerThread {
             chainstate: Some(chainstate),
             mempool: Some(mempool),
             globals,
-            keychain,
+            signer,
             burnchain: runloop.get_burnchain(),
             last_vrf_key_burn_height: 0,
             last_mined_blocks: MinedBlocks::new(),
@@ -2856,12 +2949,38 @@ impl RelayerThread {
             // already in-flight
             return;
         }
+        if self.config.node.mock_mining {
+            // mock miners don't register keys; just retry getting the mocked one
+            match self.signer.vrf_public_key(VRF_MOCK_MINER_KEY) {
+                Ok(vrf_public_key) => {
+                    info!(
+                        "Obtained mock-mining VRF public key from signer; mock mining is enabled"
+                    );
+                    self.globals.set_initial_leader_key_registration_state(
+                        LeaderKeyRegistrationState::Active(mock_registered_key(vrf_public_key)),
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to get mock-mining VRF public key from signer: {}",
+                        &e
+                    );
+                }
+            }
+            return;
+        }
         let cur_epoch =
             SortitionDB::get_stacks_epoch(self.sortdb_ref().conn(), burn_block.block_height)
                 .expect("FATAL: failed to query sortition DB")
                 .expect("FATAL: no epoch defined")
                 .epoch_id;
-        let (vrf_pk, _) = self.keychain.make_vrf_keypair(burn_block.block_height);
+        let vrf_pk = match self.signer.vrf_public_key(burn_block.block_height) {
+            Ok(vrf_pk) => vrf_pk,
+            Err(e) => {
+                warn!("Failed to get VRF public key from signer: {}", &e);
+                return;
+            }
+        };
 
         debug!(
             "Submit leader-key-register for {} {}",
@@ -2872,7 +2991,13 @@ impl RelayerThread {
         let burnchain_tip_consensus_hash = &burn_block.consensus_hash;
         let op = Self::inner_generate_leader_key_register_op(vrf_pk, burnchain_tip_consensus_hash);
 
-        let mut one_off_signer = self.keychain.generate_op_signer();
+        let mut one_off_signer = match BurnchainOpSigner::from_signer(self.signer.clone(), false) {
+            Ok(op_signer) => op_signer,
+            Err(e) => {
+                warn!("Failed to set up leader-key-register signer: {}", &e);
+                return;
+            }
+        };
         if let Some(txid) =
             self.bitcoin_controller
                 .submit_operation(cur_epoch, op, &mut one_off_signer, 1)
@@ -2935,6 +3060,14 @@ impl RelayerThread {
             return None;
         }
 
+        if !self.globals.is_mining_enabled() {
+            debug!(
+                "Relayer: mining is paused by the operator; cannot mine Stacks block at {}",
+                &last_burn_block.burn_header_hash
+            );
+            return None;
+        }
+
         if fault_injection_skip_mining(&self.config.node.rpc_bind, last_burn_block.block_height) {
             debug!(
                 "Relayer: fault injection skip mining at block height {}",
@@ -3153,6 +3286,10 @@ impl RelayerThread {
             self.globals.counters.set_microblocks_processed(0);
             return false;
         }
+        if !self.globals.is_mining_enabled() {
+            debug!("Relayer: mining is paused by the operator; cannot mine microblock");
+            return false;
+        }
 
         let parent_consensus_hash = &miner_tip.consensus_hash;
         let parent_block_hash = &miner_tip.block_hash;
@@ -3397,6 +3534,14 @@ impl RelayerThread {
                 debug!("Relayer: directive Ran tenure");
                 true
             }
+            RelayerDirective::ReloadMinerConfig(mut miner_config) => {
+                debug!("Relayer: directive Reload miner config");
+                // not settable in the config file
+                miner_config.wait_for_block_download = self.config.miner.wait_for_block_download;
+                self.config.miner = miner_config;
+                info!("Relayer: reloaded miner config");
+                true
+            }
             RelayerDirective::Exit => false,
         };
         if !continue_running {
@@ -3514,6 +3659,55 @@ impl ParentStacksBlockInfo {
     }
 }
 
+/// Node operations carried out on behalf of the /admin RPC interface.
+pub struct AdminHooks {
+    /// handle to global inter-thread comms
+    globals: Globals,
+    /// whether or not this node is a miner
+    is_miner: bool,
+    /// config file to re-read on a config reload, if the node was started from one
+    config_path: Option<String>,
+}
+
+impl AdminHandler for AdminHooks {
+    fn is_mining_enabled(&self) -> Option<bool> {
+        if self.is_miner {
+            Some(self.globals.is_mining_enabled())
+        } else {
+            None
+        }
+    }
+
+    fn set_mining_enabled(&self, enabled: bool) -> Result<(), String> {
+        if !self.is_miner {
+            return Err("This node is not a miner".to_string());
+        }
+        info!(
+            "Mining {} by the operator",
+            if enabled { "resumed" } else { "paused" }
+        );
+        self.globals.set_mining_enabled(enabled);
+        Ok(())
+    }
+
+    fn reload_config(&self) -> Result<String, String> {
+        let config_path = self
+            .config_path
+            .as_ref()
+            .ok_or_else(|| "This node was not started from a config file".to_string())?;
+        let config = Config::from_config_file(ConfigFile::from_path(config_path)?)?;
+        match self
+            .globals
+            .relay_send
+            .try_send(RelayerDirective::ReloadMinerConfig(config.miner))
+        {
+            Ok(()) => Ok(format!("Reloaded miner settings from {}", config_path)),
+            Err(TrySendError::Full(_)) => Err("Relayer is busy; try again later".to_string()),
+            Err(TrySendError::Disconnected(_)) => Err("Relayer has stopped".to_string()),
+        }
+    }
+}
+
 /// Thread that runs the network state machine, handling both p2p and http requests.
 pub struct PeerThread {
     /// Node config
@@ -3548,6 +3742,8 @@ pub struct PeerThread {
     num_download_passes: u64,
     /// last burnchain block seen in the PeerNetwork's chain view since the last run
     last_burn_block_height: u64,
+    /// operations available to the /admin RPC interface
+    admin_hooks: Arc<AdminHooks>,
 }
 
 impl PeerThread {
@@ -3604,8 +3800,41 @@ impl PeerThread {
         net.bind(&p2p_sock, &rpc_sock)
             .expect("BUG: PeerNetwork could not bind or is already bound");
 
+        if let (Some(admin_rpc_bind), Some(admin_rpc_token)) = (
+            config.node.admin_rpc_bind.as_ref(),
+            config.node.admin_rpc_token.as_ref(),
+        ) {
+            let admin_sock: SocketAddr = admin_rpc_bind
+                .parse()
+                .unwrap_or_else(|_| panic!("Failed to parse socket: {}", admin_rpc_bind));
+            net.bind_admin(&admin_sock, admin_rpc_token.clone())
+                .expect("BUG: PeerNetwork could not bind the admin interface");
+            info!("Start admin RPC server on {}", &admin_sock);
+        }
+        if let (Some(rpc_tls_bind), Some(cert_path), Some(key_path)) = (
+            config.node.rpc_tls_bind.as_ref(),
+            config.node.rpc_tls_cert_path.as_ref(),
+            config.node.rpc_tls_key_path.as_ref(),
+        ) {
+            let rpc_tls_sock: SocketAddr = rpc_tls_bind
+                .parse()
+                .unwrap_or_else(|_| panic!("Failed to parse socket: {}", rpc_tls_bind));
+            net.bind_rpc_tls(&rpc_tls_sock, cert_path, key_path)
+                .unwrap_or_else(|e| panic!("Failed to start the TLS RPC server: {:?}", &e));
+            info!(
+                "Start TLS RPC server on {} with certificate {} and key {}",
+                &rpc_tls_sock, cert_path, key_path
+            );
+        }
+
         let poll_timeout = cmp::min(5000, config.miner.first_attempt_time_ms / 2);
 
+        let admin_hooks = Arc::new(AdminHooks {
+            globals: runloop.get_globals(),
+            is_miner: runloop.is_miner(),
+            config_path: config.node.config_path.clone(),
+        });
+
         PeerThread {
             config,
             net: Some(net),
@@ -3620,6 +3849,7 @@ impl PeerThread {
             num_inv_sync_passes: 0,
             num_download_passes: 0,
             last_burn_block_height: 0,
+            admin_hooks,
         }
     }
 
@@ -3711,6 +3941,7 @@ impl PeerThread {
         });
 
         // do one pass
+        let admin_hooks = self.admin_hooks.clone();
         let p2p_res = self.with_chainstate(|p2p_thread, sortdb, chainstate, mempool| {
             // NOTE: handler_args must be created such that it outlives the inner net.run() call and
             // doesn't ref anything within p2p_thread.
@@ -3726,6 +3957,7 @@ impl PeerThread {
                 cost_estimator: Some(cost_estimator.as_ref()),
                 cost_metric: Some(cost_metric.as_ref()),
                 fee_estimator: fee_estimator.map(|boxed_estimator| boxed_estimator.as_ref()),
+                admin_handler: Some(admin_hooks.as_ref()),
                 ..RPCHandlerArgs::default()
             };
             p2p_thread.with_network(|_, net| {
@@ -4141,7 +4373,7 @@ impl StacksNode {
         let is_miner = runloop.is_miner();
         let burnchain = runloop.get_burnchain();
         let atlas_config = AtlasConfig::default(config.is_mainnet());
-        let keychain = Keychain::default(config.node.seed.clone());
+        let signer = config.make_signer();
 
         // we can call _open_ here rather than _connect_, since connect is first called in
         //   make_genesis_block
@@ -4161,24 +4393,33 @@ impl StacksNode {
 
         let local_peer = p2p_net.local_peer.clone();
 
-        let burnchain_signer = keychain.get_burnchain_signer();
-        match monitoring::set_burnchain_signer(burnchain_signer.clone()) {
+        match signer::get_burnchain_signer(signer.as_ref()) {
+            Ok(burnchain_signer) => {
+                if let Err(e) = monitoring::set_burnchain_signer(burnchain_signer) {
+                    warn!("Failed to set global burnchain signer: {:?}", &e);
+                }
+            }
             Err(e) => {
-                warn!("Failed to set global burnchain signer: {:?}", &e);
+                warn!("Failed to get burnchain signer: {}", &e);
             }
-            _ => {}
         }
 
         // setup initial key registration
         let leader_key_registration_state = if config.node.mock_mining {
             // mock mining, pretend to have a registered key
-            let (vrf_public_key, _) = keychain.make_vrf_keypair(VRF_MOCK_MINER_KEY);
-            LeaderKeyRegistrationState::Active(RegisteredKey {
-                target_block_height: VRF_MOCK_MINER_KEY,
-                block_height: 1,
-                op_vtxindex: 1,
-                vrf_public_key,
-            })
+            match signer.vrf_public_key(VRF_MOCK_MINER_KEY) {
+                Ok(vrf_public_key) => {
+                    LeaderKeyRegistrationState::Active(mock_registered_key(vrf_public_key))
+                }
+                Err(e) => {
+                    // without the key, there's nothing to mock-mine with
+                    error!(
+                        "Could not get mock-mining VRF public key from signer; mock mining is disabled until it can be obtained: {}",
+                        &e
+                    );
+                    LeaderKeyRegistrationState::Inactive
+                }
+            }
         } else {
             LeaderKeyRegistrationState::Inactive
         };
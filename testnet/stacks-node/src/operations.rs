@@ -1,8 +1,18 @@
 use stacks::util::secp256k1::{MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey};
 use stacks::{burnchains::PrivateKey, util::hash::hex_bytes};
 
+use crate::signer::{Error as SignerError, SignerRef};
+
+/// Where a BurnchainOpSigner's signatures come from
+enum OpSigningKey {
+    /// The secret key is held in-process
+    Local(Secp256k1PrivateKey),
+    /// Signing is delegated to a (possibly remote) signer, whose public key we cache
+    Delegated(SignerRef, Secp256k1PublicKey),
+}
+
 pub struct BurnchainOpSigner {
-    secret_key: Secp256k1PrivateKey,
+    key: OpSigningKey,
     is_one_off: bool,
     is_disposed: bool,
     usages: u8,
@@ -11,27 +21,49 @@ pub struct BurnchainOpSigner {
 impl BurnchainOpSigner {
     pub fn new(secret_key: Secp256k1PrivateKey, is_one_off: bool) -> BurnchainOpSigner {
         BurnchainOpSigner {
-            secret_key,
+            key: OpSigningKey::Local(secret_key),
             usages: 0,
             is_one_off,
             is_disposed: false,
         }
     }
 
-    pub fn get_sk_as_wif(&self) -> String {
-        let hex_encoded = self.secret_key.to_hex();
+    /// Create an op signer that asks `signer` to sign each Bitcoin input.
+    /// Fails if the signer cannot report its public key.
+    pub fn from_signer(
+        signer: SignerRef,
+        is_one_off: bool,
+    ) -> Result<BurnchainOpSigner, SignerError> {
+        let public_key = signer.public_key()?;
+        Ok(BurnchainOpSigner {
+            key: OpSigningKey::Delegated(signer, public_key),
+            usages: 0,
+            is_one_off,
+            is_disposed: false,
+        })
+    }
+
+    /// Get the secret key as WIF, if it is held in-process
+    pub fn get_sk_as_wif(&self) -> Option<String> {
+        let hex_encoded = self.get_sk_as_hex()?;
         let mut as_bytes = hex_bytes(&hex_encoded).unwrap();
         as_bytes.insert(0, 0x80);
-        stacks::address::b58::check_encode_slice(&as_bytes)
+        Some(stacks::address::b58::check_encode_slice(&as_bytes))
     }
 
-    pub fn get_sk_as_hex(&self) -> String {
-        self.secret_key.to_hex()
+    /// Get the secret key as hex, if it is held in-process
+    pub fn get_sk_as_hex(&self) -> Option<String> {
+        match self.key {
+            OpSigningKey::Local(ref secret_key) => Some(secret_key.to_hex()),
+            OpSigningKey::Delegated(..) => None,
+        }
     }
 
     pub fn get_public_key(&mut self) -> Secp256k1PublicKey {
-        let public_key = Secp256k1PublicKey::from_private(&self.secret_key);
-        public_key
+        match self.key {
+            OpSigningKey::Local(ref secret_key) => Secp256k1PublicKey::from_private(secret_key),
+            OpSigningKey::Delegated(_, ref public_key) => *public_key,
+        }
     }
 
     pub fn sign_message(&mut self, hash: &[u8]) -> Option<MessageSignature> {
@@ -40,12 +72,21 @@ impl BurnchainOpSigner {
             return None;
         }
 
-        let signature = match self.secret_key.sign(hash) {
-            Ok(r) => r,
-            Err(e) => {
-                debug!("Secret key error: {:?}", &e);
-                return None;
-            }
+        let signature = match self.key {
+            OpSigningKey::Local(ref secret_key) => match secret_key.sign(hash) {
+                Ok(r) => r,
+                Err(e) => {
+                    debug!("Secret key error: {:?}", &e);
+                    return None;
+                }
+            },
+            OpSigningKey::Delegated(ref signer, _) => match signer.sign_digest(hash) {
+                Ok(r) => r,
+                Err(e) => {
+                    warn!("Failed to sign burnchain operation: {}", &e);
+                    return None;
+                }
+            },
         };
         self.usages += 1;
 
@@ -76,7 +117,7 @@ mod test {
         for (secret_key, expected_wif) in examples.iter() {
             let secp_k = Secp256k1PrivateKey::from_hex(secret_key).unwrap();
             let op_signer = BurnchainOpSigner::new(secp_k, false);
-            assert_eq!(expected_wif, &op_signer.get_sk_as_wif());
+            assert_eq!(expected_wif, &op_signer.get_sk_as_wif().unwrap());
         }
     }
 }
@@ -34,16 +34,18 @@ use stx_genesis::GenesisData;
 
 use super::RunLoopCallbacks;
 use crate::burnchains::make_bitcoin_indexer;
+use crate::burnchains::zmq::{spawn_zmq_listener, BurnBlockNotifier};
 use crate::monitoring::start_serving_monitoring_metrics;
 use crate::neon_node::Globals;
 use crate::neon_node::StacksNode;
 use crate::neon_node::BLOCK_PROCESSOR_STACK_SIZE;
 use crate::neon_node::RELAYER_MAX_BUFFER;
 use crate::node::use_test_genesis_chainstate;
+use crate::operations::BurnchainOpSigner;
 use crate::syncctl::{PoxSyncWatchdog, PoxSyncWatchdogComms};
 use crate::{
     node::{get_account_balances, get_account_lockups, get_names, get_namespaces},
-    run_loop, BitcoinRegtestController, BurnchainController, Config, EventDispatcher, Keychain,
+    run_loop, BitcoinRegtestController, BurnchainController, Config, EventDispatcher,
 };
 use stacks::chainstate::stacks::miner::{signal_mining_blocked, signal_mining_ready, MinerStatus};
 use stacks_common::util::get_epoch_time_secs;
@@ -310,55 +312,75 @@ impl RunLoop {
 
     /// Determine if we're the miner.
     /// If there's a network error, then assume that we're not a miner.
+    /// With `miner.internal_wallet`, the UTXO check is deferred until the burnchain is synced
+    /// (see `check_miner_utxos()`), since the node's own indexer has not seen them yet.
     fn check_is_miner(&mut self, burnchain: &mut BitcoinRegtestController) -> bool {
         if self.config.node.miner {
-            let keychain = Keychain::default(self.config.node.seed.clone());
-            let mut op_signer = keychain.generate_op_signer();
+            if let Err(e) = BurnchainOpSigner::from_signer(self.config.make_signer(), false) {
+                warn!("Failed to reach the miner's signer: {}", &e);
+                return false;
+            }
+            if self.config.miner.internal_wallet {
+                info!("Miner node: will check UTXOs in the internal wallet once the burnchain is synced");
+                return true;
+            }
             match burnchain.create_wallet_if_dne() {
                 Err(e) => warn!("Error when creating wallet: {:?}", e),
                 _ => {}
             }
-            let mut btc_addrs = vec![(
-                StacksEpochId::Epoch2_05,
-                // legacy
-                BitcoinAddress::from_bytes_legacy(
+            self.check_miner_utxos(burnchain)
+        } else {
+            info!("Will run as a Follower node");
+            false
+        }
+    }
+
+    /// Determine if the miner has UTXOs to spend (or is configured to mock-mine anyway).
+    fn check_miner_utxos(&self, burnchain: &mut BitcoinRegtestController) -> bool {
+        let mut op_signer = match BurnchainOpSigner::from_signer(self.config.make_signer(), false) {
+            Ok(op_signer) => op_signer,
+            Err(e) => {
+                warn!("Failed to reach the miner's signer: {}", &e);
+                return false;
+            }
+        };
+        let mut btc_addrs = vec![(
+            StacksEpochId::Epoch2_05,
+            // legacy
+            BitcoinAddress::from_bytes_legacy(
+                self.config.burnchain.get_bitcoin_network().1,
+                LegacyBitcoinAddressType::PublicKeyHash,
+                &Hash160::from_data(&op_signer.get_public_key().to_bytes()).0,
+            )
+            .expect("FATAL: failed to construct legacy bitcoin address"),
+        )];
+        if self.config.miner.segwit {
+            btc_addrs.push((
+                StacksEpochId::Epoch21,
+                // segwit p2wpkh
+                BitcoinAddress::from_bytes_segwit_p2wpkh(
                     self.config.burnchain.get_bitcoin_network().1,
-                    LegacyBitcoinAddressType::PublicKeyHash,
-                    &Hash160::from_data(&op_signer.get_public_key().to_bytes()).0,
+                    &Hash160::from_data(&op_signer.get_public_key().to_bytes_compressed()).0,
                 )
-                .expect("FATAL: failed to construct legacy bitcoin address"),
-            )];
-            if self.config.miner.segwit {
-                btc_addrs.push((
-                    StacksEpochId::Epoch21,
-                    // segwit p2wpkh
-                    BitcoinAddress::from_bytes_segwit_p2wpkh(
-                        self.config.burnchain.get_bitcoin_network().1,
-                        &Hash160::from_data(&op_signer.get_public_key().to_bytes_compressed()).0,
-                    )
-                    .expect("FATAL: failed to construct segwit p2wpkh address"),
-                ));
-            }
+                .expect("FATAL: failed to construct segwit p2wpkh address"),
+            ));
+        }
 
-            for (epoch_id, btc_addr) in btc_addrs.into_iter() {
-                info!("Miner node: checking UTXOs at address: {}", &btc_addr);
-                let utxos = burnchain.get_utxos(epoch_id, &op_signer.get_public_key(), 1, None, 0);
-                if utxos.is_none() {
-                    warn!("UTXOs not found for {}. If this is unexpected, please ensure that your bitcoind instance is indexing transactions for the address {} (importaddress)", btc_addr, btc_addr);
-                } else {
-                    info!("UTXOs found - will run as a Miner node");
-                    return true;
-                }
-            }
-            if self.config.node.mock_mining {
-                info!("No UTXOs found, but configured to mock mine");
-                return true;
+        for (epoch_id, btc_addr) in btc_addrs.into_iter() {
+            info!("Miner node: checking UTXOs at address: {}", &btc_addr);
+            let utxos = burnchain.get_utxos(epoch_id, &op_signer.get_public_key(), 1, None, 0);
+            if utxos.is_none() {
+                warn!("UTXOs not found for {}. If this is unexpected, please ensure that your bitcoind instance is indexing transactions for the address {} (importaddress)", btc_addr, btc_addr);
             } else {
-                return false;
+                info!("UTXOs found - will run as a Miner node");
+                return true;
             }
+        }
+        if self.config.node.mock_mining {
+            info!("No UTXOs found, but configured to mock mine");
+            return true;
         } else {
-            info!("Will run as a Follower node");
-            false
+            return false;
         }
     }
 
@@ -477,7 +499,7 @@ impl RunLoop {
             get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
         };
 
-        let (chain_state_db, receipts) = StacksChainState::open_and_exec(
+        let (mut chain_state_db, receipts) = StacksChainState::open_and_exec(
             self.config.is_mainnet(),
             self.config.burnchain.chain_id,
             &self.config.get_chainstate_path_str(),
@@ -491,6 +513,7 @@ impl RunLoop {
             &burnchain_config.pox_constants,
             &receipts,
         );
+        chain_state_db.index_account_history = self.config.node.account_history_index;
         chain_state_db
     }
 
@@ -566,8 +589,18 @@ impl RunLoop {
 
     /// Instantiate the PoX watchdog
     fn instantiate_pox_watchdog(&mut self) {
-        let pox_watchdog = PoxSyncWatchdog::new(&self.config, self.pox_watchdog_comms.clone())
+        let mut pox_watchdog = PoxSyncWatchdog::new(&self.config, self.pox_watchdog_comms.clone())
             .expect("FATAL: failed to instantiate PoX sync watchdog");
+        if let Some(zmq_endpoint) = self.config.burnchain.zmq_endpoint.clone() {
+            info!("Listening for new burnchain blocks on {}", &zmq_endpoint);
+            let notifier = BurnBlockNotifier::new();
+            spawn_zmq_listener(
+                zmq_endpoint,
+                notifier.clone(),
+                self.should_keep_running.clone(),
+            );
+            pox_watchdog.set_burn_block_notifier(notifier);
+        }
         self.pox_watchdog = Some(pox_watchdog);
     }
 
@@ -966,6 +999,9 @@ impl RunLoop {
         let is_miner = self.check_is_miner(&mut burnchain);
         self.is_miner = Some(is_miner);
 
+        // an internal-wallet miner can only find its UTXOs once its indexer has caught up
+        let mut miner_utxos_ready = !is_miner || !self.config.miner.internal_wallet;
+
         // relayer linkup
         let (relay_send, relay_recv) = sync_channel(RELAYER_MAX_BUFFER);
 
@@ -1245,6 +1281,14 @@ impl RunLoop {
                         last_tenure_sortition_height = sortition_db_height;
                     }
 
+                    if !miner_utxos_ready {
+                        miner_utxos_ready = self.check_miner_utxos(&mut burnchain);
+                        if !miner_utxos_ready {
+                            warn!("Runloop: miner has no spendable UTXOs yet, not mining");
+                            continue;
+                        }
+                    }
+
                     if !node.relayer_issue_tenure(ibd) {
                         // relayer hung up, exit.
                         error!("Runloop: Block relayer and miner hung up, exiting.");
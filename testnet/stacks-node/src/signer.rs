@@ -0,0 +1,656 @@
+//! Miner key management.
+//!
+//! A `Signer` holds (or has access to) the miner's long-lived secret state, and produces the
+//! VRF proofs, transaction signatures, microblock keys, and Bitcoin signatures that the miner
+//! needs.  The node's `Keychain` is the in-process implementation; `RemoteSigner` forwards every
+//! request to an external signing process over HTTP, so the seed never needs to be present in
+//! the node's config file.
+
+use std::fmt;
+use std::net::{TcpStream as StdTcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_h1::client;
+use async_std::io::ReadExt;
+use async_std::net::TcpStream;
+use http_types::{Method, Request, Url};
+
+use stacks::address::AddressHashMode;
+use stacks::burnchains::BurnchainSigner;
+use stacks::chainstate::stacks::{
+    StacksPrivateKey, StacksTransactionSigner, TransactionAuth, TransactionAuthFlags,
+    TransactionSpendingCondition,
+};
+use stacks::types::chainstate::StacksAddress;
+use stacks::util::hash::{hex_bytes, to_hex};
+use stacks::util::secp256k1::{MessageSignature, Secp256k1PublicKey};
+use stacks::util::vrf::{VRFProof, VRFPublicKey};
+
+use stacks_common::address::{
+    C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
+
+/// Path on the remote signer to which all requests are POSTed
+pub const REMOTE_SIGNER_PATH: &str = "/v1/signer";
+
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to reach the signer
+    Network(String),
+    /// The signer sent back something we could not decode
+    Parsing(String),
+    /// The signer refused or failed to carry out the request
+    Rejected(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Network(ref s) => write!(f, "Signer network error: {}", s),
+            Error::Parsing(ref s) => write!(f, "Signer parsing error: {}", s),
+            Error::Rejected(ref s) => write!(f, "Signer rejected request: {}", s),
+        }
+    }
+}
+
+/// Operations the miner needs from whatever holds its secret keys.
+pub trait Signer: Send + Sync {
+    /// The secp256k1 public key whose private key signs the miner's Stacks transactions and
+    /// Bitcoin operations.
+    fn public_key(&self) -> Result<Secp256k1PublicKey, Error>;
+
+    /// Sign a 32-byte digest with the private key for `public_key()`.
+    fn sign_digest(&self, digest: &[u8]) -> Result<MessageSignature, Error>;
+
+    /// The VRF public key to register for the given burn block height.
+    fn vrf_public_key(&self, block_height: u64) -> Result<VRFPublicKey, Error>;
+
+    /// Generate a VRF proof over `message` with the VRF key registered at `block_height`.
+    fn vrf_prove(&self, block_height: u64, message: &[u8; 32]) -> Result<VRFProof, Error>;
+
+    /// Produce the ephemeral microblock signing key for the tenure started at
+    /// `burn_block_height`.  `salt` is typically the parent Stacks block's index block hash.
+    /// The key only lives for a single tenure, so it is handed to the miner rather than having
+    /// every microblock round-trip through the signer.
+    fn microblock_secret_key(
+        &self,
+        burn_block_height: u64,
+        salt: &[u8],
+    ) -> Result<StacksPrivateKey, Error>;
+}
+
+/// Get the single-sig p2pkh transaction authorization for this signer's key
+pub fn get_transaction_auth(signer: &dyn Signer) -> Result<TransactionAuth, Error> {
+    let pubkey = signer.public_key()?;
+    TransactionSpendingCondition::new_singlesig_p2pkh(pubkey)
+        .map(TransactionAuth::Standard)
+        .ok_or_else(|| Error::Rejected("could not make p2pkh spending condition".to_string()))
+}
+
+/// Get the Stacks address of this signer's key
+pub fn get_address(signer: &dyn Signer, is_mainnet: bool) -> Result<StacksAddress, Error> {
+    let pubkey = signer.public_key()?;
+    let version = if is_mainnet {
+        C32_ADDRESS_VERSION_MAINNET_SINGLESIG
+    } else {
+        C32_ADDRESS_VERSION_TESTNET_SINGLESIG
+    };
+    StacksAddress::from_public_keys(version, &AddressHashMode::SerializeP2PKH, 1, &vec![pubkey])
+        .ok_or_else(|| Error::Rejected("could not produce address from public key".to_string()))
+}
+
+/// Get a BurnchainSigner representation of this signer
+pub fn get_burnchain_signer(signer: &dyn Signer) -> Result<BurnchainSigner, Error> {
+    Ok(BurnchainSigner(format!("{}", &get_address(signer, true)?)))
+}
+
+/// Sign a transaction's single-sig origin spending condition.
+/// Only the sighash leaves the node; the private key stays with the signer.
+pub fn sign_as_origin(
+    signer: &dyn Signer,
+    tx_signer: &mut StacksTransactionSigner,
+) -> Result<(), Error> {
+    let pubkey = signer.public_key()?;
+    let (tx_fee, nonce) = {
+        let origin = tx_signer.tx.auth.origin();
+        (origin.tx_fee(), origin.nonce())
+    };
+    let presign_sighash = TransactionSpendingCondition::make_sighash_presign(
+        &tx_signer.sighash,
+        &TransactionAuthFlags::AuthStandard,
+        tx_fee,
+        nonce,
+    );
+    let sig = signer.sign_digest(presign_sighash.as_bytes())?;
+
+    match tx_signer.tx.auth {
+        TransactionAuth::Standard(TransactionSpendingCondition::Singlesig(ref mut cond))
+        | TransactionAuth::Sponsored(TransactionSpendingCondition::Singlesig(ref mut cond), _) => {
+            cond.set_signature(sig);
+        }
+        _ => {
+            return Err(Error::Rejected(
+                "only single-sig origins can be signed".to_string(),
+            ));
+        }
+    }
+
+    tx_signer.sighash =
+        TransactionSpendingCondition::make_sighash_postsign(&presign_sighash, &pubkey, &sig);
+    Ok(())
+}
+
+/// A request sent to a remote signer.
+/// All byte strings are hex-encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum SignerRequest {
+    PublicKey,
+    SignDigest {
+        digest: String,
+    },
+    VrfPublicKey {
+        block_height: u64,
+    },
+    VrfProve {
+        block_height: u64,
+        message: String,
+    },
+    MicroblockSecretKey {
+        burn_block_height: u64,
+        salt: String,
+    },
+}
+
+/// A remote signer's reply to a `SignerRequest`.
+/// All byte strings are hex-encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerResponse {
+    PublicKey(String),
+    Signature(String),
+    VrfPublicKey(String),
+    VrfProof(String),
+    MicroblockSecretKey(String),
+    Error(String),
+}
+
+impl SignerRequest {
+    /// Carry out this request with the given signer.
+    /// This is what a signing process runs when it receives a request from a node.
+    pub fn handle(&self, signer: &dyn Signer) -> SignerResponse {
+        match self.try_handle(signer) {
+            Ok(resp) => resp,
+            Err(e) => SignerResponse::Error(format!("{}", &e)),
+        }
+    }
+
+    fn try_handle(&self, signer: &dyn Signer) -> Result<SignerResponse, Error> {
+        let resp = match self {
+            SignerRequest::PublicKey => SignerResponse::PublicKey(signer.public_key()?.to_hex()),
+            SignerRequest::SignDigest { digest } => {
+                let digest = decode_hex(digest)?;
+                SignerResponse::Signature(signer.sign_digest(&digest)?.to_hex())
+            }
+            SignerRequest::VrfPublicKey { block_height } => {
+                SignerResponse::VrfPublicKey(signer.vrf_public_key(*block_height)?.to_hex())
+            }
+            SignerRequest::VrfProve {
+                block_height,
+                message,
+            } => {
+                let message_bytes = decode_hex(message)?;
+                if message_bytes.len() != 32 {
+                    return Err(Error::Parsing("VRF message must be 32 bytes".to_string()));
+                }
+                let mut message = [0u8; 32];
+                message.copy_from_slice(&message_bytes);
+                SignerResponse::VrfProof(signer.vrf_prove(*block_height, &message)?.to_hex())
+            }
+            SignerRequest::MicroblockSecretKey {
+                burn_block_height,
+                salt,
+            } => {
+                let salt = decode_hex(salt)?;
+                SignerResponse::MicroblockSecretKey(
+                    signer
+                        .microblock_secret_key(*burn_block_height, &salt)?
+                        .to_hex(),
+                )
+            }
+        };
+        Ok(resp)
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, Error> {
+    hex_bytes(s).map_err(|e| Error::Parsing(format!("invalid hex string: {:?}", &e)))
+}
+
+/// A signer that lives in a separate process, reached over HTTP.
+/// Each call POSTs a JSON-encoded `SignerRequest` to `REMOTE_SIGNER_PATH` and expects a
+/// JSON-encoded `SignerResponse` in reply.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    /// host:port of the signer
+    endpoint: String,
+    /// if set, sent as a bearer token in the `Authorization` header
+    auth_token: Option<String>,
+    /// how long to wait to connect, and for each of sending the request and reading the reply
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    pub fn new(endpoint: &str, auth_token: Option<String>, timeout: Duration) -> RemoteSigner {
+        RemoteSigner {
+            endpoint: endpoint.to_string(),
+            auth_token,
+            timeout,
+        }
+    }
+
+    /// Connect to the signer, giving up after `self.timeout`.
+    /// The socket's read and write timeouts are set to `self.timeout` as well.
+    fn connect(&self) -> Result<TcpStream, Error> {
+        let addrs = self
+            .endpoint
+            .to_socket_addrs()
+            .map_err(|e| Error::Network(format!("bad endpoint {}: {:?}", &self.endpoint, &e)))?;
+        let mut last_err = None;
+        for addr in addrs {
+            match StdTcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => {
+                    stream
+                        .set_read_timeout(Some(self.timeout))
+                        .and_then(|_| stream.set_write_timeout(Some(self.timeout)))
+                        .map_err(|e| {
+                            Error::Network(format!("failed to set timeouts - {:?}", &e))
+                        })?;
+                    return Ok(TcpStream::from(stream));
+                }
+                Err(e) => {
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(Error::Network(format!(
+            "connection to {} failed - {:?}",
+            &self.endpoint, &last_err
+        )))
+    }
+
+    fn send(&self, payload: &SignerRequest) -> Result<SignerResponse, Error> {
+        let url = {
+            let url = format!("http://{}{}", &self.endpoint, REMOTE_SIGNER_PATH);
+            Url::parse(&url).map_err(|e| Error::Network(format!("bad URL {}: {:?}", &url, &e)))?
+        };
+        let body = serde_json::to_vec(payload)
+            .map_err(|e| Error::Parsing(format!("failed to serialize request: {:?}", &e)))?;
+
+        let mut request = Request::new(Method::Post, url);
+        request.append_header("Content-Type", "application/json");
+        if let Some(token) = self.auth_token.as_ref() {
+            request.append_header("Authorization", format!("Bearer {}", token));
+        }
+        request.set_body(body);
+
+        // the async stream is non-blocking, so the socket timeouts alone won't bound each await
+        let stream = self.connect()?;
+        let timeout = self.timeout;
+        let mut response = async_std::task::block_on(async move {
+            async_std::future::timeout(timeout, client::connect(stream, request)).await
+        })
+        .map_err(|_| Error::Network("request timed out".to_string()))?
+        .map_err(|e| Error::Network(format!("request failed - {:?}", &e)))?;
+
+        let status = response.status();
+        let buffer = async_std::task::block_on(async move {
+            let mut buffer = Vec::new();
+            let mut body = response.take_body();
+            async_std::future::timeout(timeout, body.read_to_end(&mut buffer))
+                .await
+                .map(|res| res.map(|_| buffer))
+        })
+        .map_err(|_| Error::Network("timed out reading body".to_string()))?
+        .map_err(|e| Error::Network(format!("unable to read body - {:?}", &e)))?;
+
+        if !status.is_success() {
+            return Err(Error::Network(format!(
+                "status({}) != success, body is '{}'",
+                status,
+                String::from_utf8_lossy(&buffer)
+            )));
+        }
+
+        let resp: SignerResponse = serde_json::from_slice(&buffer)
+            .map_err(|e| Error::Parsing(format!("failed to decode response: {:?}", &e)))?;
+        match resp {
+            SignerResponse::Error(msg) => Err(Error::Rejected(msg)),
+            resp => Ok(resp),
+        }
+    }
+}
+
+fn unexpected_response(resp: SignerResponse) -> Error {
+    Error::Parsing(format!("unexpected response: {:?}", &resp))
+}
+
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> Result<Secp256k1PublicKey, Error> {
+        match self.send(&SignerRequest::PublicKey)? {
+            SignerResponse::PublicKey(hex) => {
+                Secp256k1PublicKey::from_hex(&hex).map_err(|e| Error::Parsing(e.to_string()))
+            }
+            resp => Err(unexpected_response(resp)),
+        }
+    }
+
+    fn sign_digest(&self, digest: &[u8]) -> Result<MessageSignature, Error> {
+        let req = SignerRequest::SignDigest {
+            digest: to_hex(digest),
+        };
+        match self.send(&req)? {
+            SignerResponse::Signature(hex) => MessageSignature::from_hex(&hex)
+                .map_err(|e| Error::Parsing(format!("bad signature: {:?}", &e))),
+            resp => Err(unexpected_response(resp)),
+        }
+    }
+
+    fn vrf_public_key(&self, block_height: u64) -> Result<VRFPublicKey, Error> {
+        match self.send(&SignerRequest::VrfPublicKey { block_height })? {
+            SignerResponse::VrfPublicKey(hex) => VRFPublicKey::from_hex(&hex)
+                .ok_or_else(|| Error::Parsing("bad VRF public key".to_string())),
+            resp => Err(unexpected_response(resp)),
+        }
+    }
+
+    fn vrf_prove(&self, block_height: u64, message: &[u8; 32]) -> Result<VRFProof, Error> {
+        let req = SignerRequest::VrfProve {
+            block_height,
+            message: to_hex(message),
+        };
+        match self.send(&req)? {
+            SignerResponse::VrfProof(hex) => {
+                VRFProof::from_hex(&hex).ok_or_else(|| Error::Parsing("bad VRF proof".to_string()))
+            }
+            resp => Err(unexpected_response(resp)),
+        }
+    }
+
+    fn microblock_secret_key(
+        &self,
+        burn_block_height: u64,
+        salt: &[u8],
+    ) -> Result<StacksPrivateKey, Error> {
+        let req = SignerRequest::MicroblockSecretKey {
+            burn_block_height,
+            salt: to_hex(salt),
+        };
+        match self.send(&req)? {
+            SignerResponse::MicroblockSecretKey(hex) => {
+                StacksPrivateKey::from_hex(&hex).map_err(|e| Error::Parsing(e.to_string()))
+            }
+            resp => Err(unexpected_response(resp)),
+        }
+    }
+}
+
+/// Convenience alias for the shared signer handle passed between the relayer and miner threads
+pub type SignerRef = Arc<dyn Signer>;
+
+#[cfg(test)]
+pub mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Instant;
+
+    use stacks::chainstate::stacks::{
+        StacksPublicKey, StacksTransaction, StacksTransactionSigner, TokenTransferMemo,
+        TransactionPayload, TransactionPostConditionMode, TransactionVersion,
+    };
+    use stacks::types::chainstate::StacksAddress;
+    use stacks::util::vrf::VRF;
+
+    use crate::operations::BurnchainOpSigner;
+    use crate::stacks_common::types::Address;
+    use crate::Keychain;
+
+    use super::*;
+
+    /// A stand-in for an external signing process.  It serves `SignerRequest`s out of an
+    /// in-process `Keychain`, one request per connection, until the test process exits.
+    pub struct StubSigner {
+        pub addr: SocketAddr,
+    }
+
+    impl StubSigner {
+        pub fn spawn(keychain: Keychain, auth_token: Option<String>) -> StubSigner {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = match stream {
+                        Ok(s) => s,
+                        Err(_) => continue,
+                    };
+                    let (status, body) = match Self::read_request(&mut stream) {
+                        Ok((headers, body)) => {
+                            let authorized = match auth_token.as_ref() {
+                                Some(token) => headers
+                                    .iter()
+                                    .any(|h| h == &format!("authorization: bearer {}", token)),
+                                None => true,
+                            };
+                            if !authorized {
+                                ("401 Unauthorized", vec![])
+                            } else {
+                                match serde_json::from_slice::<SignerRequest>(&body) {
+                                    Ok(req) => (
+                                        "200 OK",
+                                        serde_json::to_vec(&req.handle(&keychain)).unwrap(),
+                                    ),
+                                    Err(_) => ("400 Bad Request", vec![]),
+                                }
+                            }
+                        }
+                        Err(_) => ("400 Bad Request", vec![]),
+                    };
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        status,
+                        body.len()
+                    );
+                    let _ = stream.write_all(&body);
+                    let _ = stream.flush();
+                }
+            });
+            StubSigner { addr }
+        }
+
+        /// Read an HTTP request, returning its lowercased header lines and its body
+        fn read_request<R: Read>(stream: &mut R) -> std::io::Result<(Vec<String>, Vec<u8>)> {
+            let mut reader = BufReader::new(stream);
+            let mut headers = vec![];
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line)?;
+                let line = line.trim_end().to_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(len) = line.strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap_or(0);
+                }
+                headers.push(line);
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body)?;
+            Ok((headers, body))
+        }
+
+        pub fn endpoint(&self) -> String {
+            format!("{}", &self.addr)
+        }
+    }
+
+    const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn test_keychain() -> Keychain {
+        Keychain::default(vec![0x01, 0x02, 0x03, 0x04])
+    }
+
+    #[test]
+    fn test_request_codec() {
+        let req = SignerRequest::VrfProve {
+            block_height: 123,
+            message: "00".repeat(32),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                "{{\"method\":\"vrf_prove\",\"params\":{{\"block_height\":123,\"message\":\"{}\"}}}}",
+                "00".repeat(32)
+            )
+        );
+        assert_eq!(serde_json::from_str::<SignerRequest>(&json).unwrap(), req);
+
+        let json = serde_json::to_string(&SignerRequest::PublicKey).unwrap();
+        assert_eq!(json, "{\"method\":\"public_key\"}");
+    }
+
+    #[test]
+    fn test_remote_signer_matches_keychain() {
+        let keychain = test_keychain();
+        let stub = StubSigner::spawn(keychain.clone(), None);
+        let remote = RemoteSigner::new(&stub.endpoint(), None, TEST_TIMEOUT);
+
+        assert_eq!(
+            remote.public_key().unwrap(),
+            Signer::public_key(&keychain).unwrap()
+        );
+
+        let (vrf_pk, _) = keychain.make_vrf_keypair(100);
+        assert_eq!(remote.vrf_public_key(100).unwrap(), vrf_pk);
+
+        let message = [0x11u8; 32];
+        let proof = remote.vrf_prove(100, &message).unwrap();
+        assert!(VRF::verify(&vrf_pk, &proof, message.as_ref()).unwrap());
+        assert_eq!(proof, keychain.generate_proof(100, &message));
+
+        assert_eq!(
+            remote.microblock_secret_key(100, &[0x22; 32]).unwrap(),
+            keychain.make_microblock_secret_key(100, &[0x22; 32])
+        );
+
+        let digest = [0x33u8; 32];
+        let sig = remote.sign_digest(&digest).unwrap();
+        let pubk = StacksPublicKey::recover_to_pubkey(&digest, &sig).unwrap();
+        assert_eq!(
+            pubk.to_bytes_compressed(),
+            remote.public_key().unwrap().to_bytes_compressed()
+        );
+    }
+
+    #[test]
+    fn test_remote_signer_auth_token() {
+        let stub = StubSigner::spawn(test_keychain(), Some("hello".to_string()));
+
+        let authorized =
+            RemoteSigner::new(&stub.endpoint(), Some("hello".to_string()), TEST_TIMEOUT);
+        assert!(authorized.public_key().is_ok());
+
+        let unauthorized =
+            RemoteSigner::new(&stub.endpoint(), Some("world".to_string()), TEST_TIMEOUT);
+        match unauthorized.public_key() {
+            Err(Error::Network(_)) => {}
+            x => panic!("expected network error, got {:?}", &x),
+        }
+
+        let anonymous = RemoteSigner::new(&stub.endpoint(), None, TEST_TIMEOUT);
+        assert!(anonymous.public_key().is_err());
+    }
+
+    #[test]
+    fn test_remote_signer_timeout() {
+        // accepts connections, but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut streams = vec![];
+            for stream in listener.incoming() {
+                streams.push(stream);
+            }
+        });
+
+        let remote = RemoteSigner::new(&format!("{}", &addr), None, Duration::from_millis(500));
+        let start = Instant::now();
+        match remote.public_key() {
+            Err(Error::Network(_)) => {}
+            x => panic!("expected network error, got {:?}", &x),
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_remote_sign_as_origin() {
+        let keychain = test_keychain();
+        let stub = StubSigner::spawn(keychain.clone(), None);
+        let remote: SignerRef = Arc::new(RemoteSigner::new(&stub.endpoint(), None, TEST_TIMEOUT));
+
+        let recv_addr =
+            StacksAddress::from_string("SP1Z4P459B2M5XC2PMM2CSCNZ6824DN5GZG2XYWFH").unwrap();
+
+        let mut tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            get_transaction_auth(remote.as_ref()).unwrap(),
+            TransactionPayload::TokenTransfer(recv_addr.into(), 123, TokenTransferMemo([0u8; 34])),
+        );
+        tx.chain_id = 0x80000000;
+        tx.post_condition_mode = TransactionPostConditionMode::Allow;
+        tx.set_tx_fee(0);
+
+        let mut remote_signer = StacksTransactionSigner::new(&tx);
+        sign_as_origin(remote.as_ref(), &mut remote_signer).unwrap();
+        let remote_tx = remote_signer.get_tx().unwrap();
+        remote_tx.verify().unwrap();
+
+        let mut local_signer = StacksTransactionSigner::new(&tx);
+        keychain.sign_as_origin(&mut local_signer);
+        assert_eq!(local_signer.get_tx().unwrap(), remote_tx);
+
+        assert_eq!(
+            get_burnchain_signer(remote.as_ref()).unwrap(),
+            keychain.get_burnchain_signer()
+        );
+    }
+
+    #[test]
+    fn test_remote_op_signer() {
+        let keychain = test_keychain();
+        let stub = StubSigner::spawn(keychain.clone(), None);
+        let remote: SignerRef = Arc::new(RemoteSigner::new(&stub.endpoint(), None, TEST_TIMEOUT));
+
+        let mut local_op_signer = keychain.generate_op_signer();
+        let mut remote_op_signer = BurnchainOpSigner::from_signer(remote, true).unwrap();
+        assert_eq!(
+            local_op_signer.get_public_key(),
+            remote_op_signer.get_public_key()
+        );
+
+        let digest = [0x44u8; 32];
+        assert_eq!(
+            local_op_signer.sign_message(&digest),
+            remote_op_signer.sign_message(&digest)
+        );
+
+        // one-off signers are disposed after a single use
+        assert!(remote_op_signer.sign_message(&digest).is_none());
+        assert!(remote_op_signer.get_sk_as_hex().is_none());
+    }
+}
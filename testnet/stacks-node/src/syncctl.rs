@@ -1,10 +1,12 @@
 use std::collections::VecDeque;
+use std::time::Duration;
 
 use stacks::burnchains::{Burnchain, Error as burnchain_error};
 use stacks::chainstate::stacks::db::StacksChainState;
 use stacks::util::get_epoch_time_secs;
 use stacks::util::sleep_ms;
 
+use crate::burnchains::zmq::BurnBlockNotifier;
 use crate::burnchains::BurnchainTip;
 use crate::Config;
 
@@ -161,6 +163,10 @@ pub struct PoxSyncWatchdog {
     relayer_comms: PoxSyncWatchdogComms,
     /// should this sync watchdog always download? used in integration tests.
     unconditionally_download: bool,
+    /// if set, wakes up the watchdog as soon as a new burnchain block is announced over ZMQ
+    burn_block_notifier: Option<BurnBlockNotifier>,
+    /// last burnchain block notification count we acted upon
+    last_burn_block_count: u64,
 }
 
 const PER_SAMPLE_WAIT_MS: u64 = 1000;
@@ -206,6 +212,8 @@ impl PoxSyncWatchdog {
             steady_state_resync_ts: 0,
             chainstate: chainstate,
             relayer_comms: watchdog_comms,
+            burn_block_notifier: None,
+            last_burn_block_count: 0,
         })
     }
 
@@ -213,6 +221,57 @@ impl PoxSyncWatchdog {
         self.relayer_comms.clone()
     }
 
+    /// Have the watchdog cut its burnchain poll interval short whenever `notifier` fires.
+    pub fn set_burn_block_notifier(&mut self, notifier: BurnBlockNotifier) {
+        self.last_burn_block_count = notifier.get_count();
+        self.burn_block_notifier = Some(notifier);
+    }
+
+    /// Has a new burnchain block been announced since we last acted on one?
+    /// Consumes the notification.
+    fn take_burn_block_notification(&mut self) -> bool {
+        let notifier = match self.burn_block_notifier.as_ref() {
+            Some(n) => n,
+            None => {
+                return false;
+            }
+        };
+        let count = notifier.get_count();
+        if count != self.last_burn_block_count {
+            self.last_burn_block_count = count;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Wait out the burnchain poll interval, but return early if a new burnchain block is
+    /// announced in the meantime.
+    fn wait_for_burnchain_poll(&mut self) -> Result<(), burnchain_error> {
+        let notifier = match self.burn_block_notifier.as_ref() {
+            Some(n) => n.clone(),
+            None => {
+                return self
+                    .relayer_comms
+                    .interruptable_sleep(self.steady_state_burnchain_sync_interval);
+            }
+        };
+        let deadline = get_epoch_time_secs() + self.steady_state_burnchain_sync_interval;
+        while get_epoch_time_secs() < deadline {
+            let count =
+                notifier.wait_for_new_block(self.last_burn_block_count, Duration::from_secs(1));
+            if count != self.last_burn_block_count {
+                debug!("PoX watchdog: new burnchain block announced -- sync immediately");
+                self.last_burn_block_count = count;
+                return Ok(());
+            }
+            if !self.relayer_comms.should_keep_running() {
+                return Err(burnchain_error::CoordinatorClosed);
+            }
+        }
+        Ok(())
+    }
+
     /// How many recently-added Stacks blocks are in an attachable state, up to $max_staging?
     fn count_attachable_stacks_blocks(&mut self) -> Result<u64, String> {
         // number of staging blocks that have arrived since the last sortition
@@ -468,8 +527,7 @@ impl PoxSyncWatchdog {
             debug!("PoX watchdog in first reward cycle -- sync immediately");
             self.relayer_comms.set_ibd(ibbd);
 
-            self.relayer_comms
-                .interruptable_sleep(self.steady_state_burnchain_sync_interval)?;
+            self.wait_for_burnchain_poll()?;
 
             return Ok(ibbd);
         }
@@ -513,8 +571,7 @@ impl PoxSyncWatchdog {
                 );
                 self.relayer_comms.set_ibd(ibbd);
 
-                self.relayer_comms
-                    .interruptable_sleep(self.steady_state_burnchain_sync_interval)?;
+                self.wait_for_burnchain_poll()?;
             } else {
                 debug!("PoX watchdog in last reward cycle -- sync immediately");
                 self.relayer_comms.set_ibd(ibbd);
@@ -642,6 +699,10 @@ impl PoxSyncWatchdog {
                         }
                     } else {
                         let now = get_epoch_time_secs();
+                        if self.take_burn_block_notification() {
+                            debug!("PoX watchdog: new burnchain block announced -- resync now");
+                            self.steady_state_resync_ts = now;
+                        }
                         if now < self.steady_state_resync_ts {
                             // steady state
                             if !steady_state {
@@ -69,6 +69,10 @@ impl BitcoinCoreController {
             _ => {}
         }
 
+        if let Some(zmq_endpoint) = self.config.burnchain.zmq_endpoint.as_ref() {
+            command.arg(&format!("-zmqpubhashblock={}", zmq_endpoint));
+        }
+
         eprintln!("bitcoind spawn: {:?}", command);
 
         let mut process = match command.spawn() {
@@ -66,6 +66,7 @@ use stacks::{
     util_lib::db::u64_to_sql,
 };
 
+use crate::burnchains::zmq::{ZmqSubscriber, ZMQ_TOPIC_HASHBLOCK};
 use crate::{
     burnchains::bitcoin_regtest_controller::UTXO, config::EventKeyType,
     config::EventObserverConfig, config::InitialBalance, neon, operations::BurnchainOpSigner,
@@ -835,6 +836,106 @@ fn bitcoind_integration_test() {
     channel.stop_chains_coordinator();
 }
 
+#[test]
+#[ignore]
+fn bitcoind_internal_wallet_integration_test() {
+    if env::var("BITCOIND_TEST") != Ok("1".into()) {
+        return;
+    }
+
+    let (mut conf, miner_account) = neon_integration_test_conf();
+    conf.miner.internal_wallet = true;
+    conf.burnchain.max_rbf = 1000000;
+
+    let mut btcd_controller = BitcoinCoreController::new(conf.clone());
+    btcd_controller
+        .start_bitcoind()
+        .map_err(|_e| ())
+        .expect("Failed starting bitcoind");
+
+    let mut btc_regtest_controller = BitcoinRegtestController::new(conf.clone(), None);
+    let http_origin = format!("http://{}", &conf.node.rpc_bind);
+
+    btc_regtest_controller.bootstrap_chain(201);
+
+    eprintln!("Chain bootstrapped...");
+
+    // the node starts with an empty burnchain DB, so it only finds the miner's UTXOs once its
+    // own indexer has caught up with bitcoind.
+    let mut run_loop = neon::RunLoop::new(conf);
+    let blocks_processed = run_loop.get_blocks_processed_arc();
+
+    let channel = run_loop.get_coordinator_channel().unwrap();
+
+    thread::spawn(move || run_loop.start(None, 0));
+
+    // give the run loop some time to start up!
+    wait_for_runloop(&blocks_processed);
+
+    // first block wakes up the run loop
+    next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
+
+    // first block will hold our VRF registration
+    next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
+
+    // second block will be the first mined Stacks block.  Its block-commit spends the change
+    // of the VRF registration.
+    next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
+
+    // third block's commit spends the second's change
+    next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
+
+    eprintln!("Miner account: {}", miner_account);
+
+    let account = get_account(&http_origin, &miner_account);
+    assert_eq!(account.nonce, 2);
+
+    channel.stop_chains_coordinator();
+}
+
+#[test]
+#[ignore]
+fn bitcoind_zmq_integration_test() {
+    if env::var("BITCOIND_TEST") != Ok("1".into()) {
+        return;
+    }
+
+    let (mut conf, _) = neon_integration_test_conf();
+    conf.burnchain.zmq_endpoint = Some("tcp://127.0.0.1:28332".to_string());
+
+    let mut btcd_controller = BitcoinCoreController::new(conf.clone());
+    btcd_controller
+        .start_bitcoind()
+        .map_err(|_e| ())
+        .expect("Failed starting bitcoind");
+
+    let btc_regtest_controller = BitcoinRegtestController::new(conf.clone(), None);
+    let mut subscriber = ZmqSubscriber::connect(
+        conf.burnchain.zmq_endpoint.as_ref().unwrap(),
+        &[ZMQ_TOPIC_HASHBLOCK],
+    )
+    .unwrap();
+
+    // the subscription may take a moment to reach bitcoind, so keep mining until a block is
+    // announced
+    let mut block_hashes = vec![];
+    let mut announced = None;
+    for height in 1..=10 {
+        btc_regtest_controller.build_next_block(1);
+        block_hashes.push(btc_regtest_controller.get_block_hash(height));
+        if let Some(parts) = subscriber.recv_message(Duration::from_secs(5)).unwrap() {
+            announced = Some(parts);
+            break;
+        }
+    }
+
+    let parts = announced.expect("bitcoind did not announce any block over ZMQ");
+    assert_eq!(parts[0], ZMQ_TOPIC_HASHBLOCK.as_bytes());
+    // bitcoind sends the hash in the same byte order as its RPC interface
+    let hash = BurnchainHeaderHash::from_bytes(&parts[1]).unwrap();
+    assert!(block_hashes.contains(&hash));
+}
+
 #[test]
 #[ignore]
 fn most_recent_utxo_integration_test() {